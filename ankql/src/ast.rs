@@ -1,7 +1,7 @@
 use crate::error::ParseError;
 use crate::selection::sql::generate_selection_sql;
-use ankurah_core_types::EntityId;
 pub use ankurah_core_types::Value;
+use ankurah_core_types::{Date, Decimal, EntityId, Timestamp, Uuid};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -228,6 +228,8 @@ impl Predicate {
                         ComparisonOperator::LessThanOrEqual => Predicate::False,
                         // NULL IN (...) is false
                         ComparisonOperator::In => Predicate::False,
+                        // A NULL collection contains nothing
+                        ComparisonOperator::Contains => Predicate::False,
                         // NULL BETWEEN ... is false
                         ComparisonOperator::Between => Predicate::False,
                     }
@@ -383,6 +385,7 @@ pub enum ComparisonOperator {
     LessThan,           // <
     LessThanOrEqual,    // <=
     In,                 // IN
+    Contains,           // CONTAINS (the left operand is a collection with the right operand as a member)
     Between,            // BETWEEN
 }
 
@@ -565,6 +568,22 @@ impl From<&EntityId> for Expr {
     fn from(id: &EntityId) -> Expr { Expr::Literal(Value::EntityId(*id)) }
 }
 
+impl From<Timestamp> for Expr {
+    fn from(t: Timestamp) -> Expr { Expr::Literal(Value::Timestamp(t)) }
+}
+
+impl From<Date> for Expr {
+    fn from(d: Date) -> Expr { Expr::Literal(Value::Date(d)) }
+}
+
+impl From<Decimal> for Expr {
+    fn from(d: Decimal) -> Expr { Expr::Literal(Value::Decimal(d)) }
+}
+
+impl From<Uuid> for Expr {
+    fn from(u: Uuid) -> Expr { Expr::Literal(Value::Uuid(u)) }
+}
+
 // These create Expr::ExprList for use in IN clauses
 impl<T> From<Vec<T>> for Expr
 where T: Into<Expr>
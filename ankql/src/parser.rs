@@ -112,7 +112,8 @@ fn parse_expr(pair: Pair<grammar::Rule>) -> Result<ast::Predicate, ParseError> {
                     | grammar::Rule::LtEq
                     | grammar::Rule::Lt
                     | grammar::Rule::NotEq
-                    | grammar::Rule::In => create_comparison(result, op.as_rule(), right)?,
+                    | grammar::Rule::In
+                    | grammar::Rule::Contains => create_comparison(result, op.as_rule(), right)?,
                     grammar::Rule::And | grammar::Rule::Or => create_logical_op(op.as_rule(), result, right, &mut pairs)?,
                     _ => {
                         return Err(ParseError::UnexpectedRule { expected: "comparison operator, And, or Or", got: op.as_rule() });
@@ -136,11 +137,23 @@ fn create_comparison(left: ast::Expr, op: grammar::Rule, right: Pair<grammar::Ru
         grammar::Rule::Lt => ast::ComparisonOperator::LessThan,
         grammar::Rule::NotEq => ast::ComparisonOperator::NotEqual,
         grammar::Rule::In => ast::ComparisonOperator::In,
+        grammar::Rule::Contains => ast::ComparisonOperator::Contains,
         _ => {
             return Err(ParseError::UnexpectedRule { expected: "comparison operator", got: op });
         }
     };
-    Ok(ast::Expr::Predicate(ast::Predicate::Comparison { left: Box::new(left), operator, right: Box::new(right_expr) }))
+    Ok(ast::Expr::Predicate(comparison(left, operator, right_expr)))
+}
+
+/// Build a comparison, turning `value IN collection` into `collection CONTAINS value` so that
+/// membership has one form whichever way round it is written
+fn comparison(left: ast::Expr, operator: ast::ComparisonOperator, right: ast::Expr) -> ast::Predicate {
+    match (operator, right) {
+        (ast::ComparisonOperator::In, right @ ast::Expr::Path(_)) => {
+            ast::Predicate::Comparison { left: Box::new(right), operator: ast::ComparisonOperator::Contains, right: Box::new(left) }
+        }
+        (operator, right) => ast::Predicate::Comparison { left: Box::new(left), operator, right: Box::new(right) },
+    }
 }
 
 /// Create a logical operation (AND/OR) from a left expression and a right pair
@@ -162,12 +175,13 @@ fn create_logical_op(
             | grammar::Rule::LtEq
             | grammar::Rule::Lt
             | grammar::Rule::NotEq
-            | grammar::Rule::In => {
+            | grammar::Rule::In
+            | grammar::Rule::Contains => {
                 let next_right = rest.next().ok_or(ParseError::MissingOperand("comparison right"))?;
                 let next_right_expr = parse_atomic_expr(next_right)?;
-                ast::Predicate::Comparison {
-                    left: Box::new(right_expr),
-                    operator: match next_op.as_rule() {
+                comparison(
+                    right_expr,
+                    match next_op.as_rule() {
                         grammar::Rule::Eq => ast::ComparisonOperator::Equal,
                         grammar::Rule::GtEq => ast::ComparisonOperator::GreaterThanOrEqual,
                         grammar::Rule::Gt => ast::ComparisonOperator::GreaterThan,
@@ -175,10 +189,11 @@ fn create_logical_op(
                         grammar::Rule::Lt => ast::ComparisonOperator::LessThan,
                         grammar::Rule::NotEq => ast::ComparisonOperator::NotEqual,
                         grammar::Rule::In => ast::ComparisonOperator::In,
+                        grammar::Rule::Contains => ast::ComparisonOperator::Contains,
                         _ => unimplemented!("rule not implemented: {:?}", next_op.as_rule()),
                     },
-                    right: Box::new(next_right_expr),
-                }
+                    next_right_expr,
+                )
             }
             _ => {
                 return Err(ParseError::UnexpectedRule { expected: "comparison operator", got: next_op.as_rule() });
@@ -262,7 +277,15 @@ fn parse_number(pair: Pair<grammar::Rule>) -> Result<ast::Expr, ParseError> {
         return Err(ParseError::UnexpectedRule { expected: "Unsigned", got: pair.as_rule() });
     }
 
-    let num = pair.as_str().trim().parse::<i64>().map_err(|e| ParseError::InvalidPredicate(format!("Failed to parse number: {}", e)))?;
+    let text = pair.as_str().trim();
+    let Ok(num) = text.parse::<i64>() else {
+        // Past the i64 range: unsigned 64-bit ids and counters, then 128-bit integers
+        if let Ok(num) = text.parse::<u64>() {
+            return Ok(ast::Expr::Literal(Value::U64(num)));
+        }
+        let num = text.parse::<i128>().map_err(|e| ParseError::InvalidPredicate(format!("Failed to parse number: {}", e)))?;
+        return Ok(ast::Expr::Literal(Value::I128(num)));
+    };
 
     if num < i32::MAX as i64 && num > i32::MIN as i64 {
         return Ok(ast::Expr::Literal(Value::I32(num as i32)));
@@ -368,6 +391,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_integers_past_i64() {
+        let literal = |input: &str| match parse_selection(input).unwrap().predicate {
+            ast::Predicate::Comparison { right, .. } => *right,
+            other => panic!("unexpected predicate {other:?}"),
+        };
+        assert_eq!(literal("n = 9223372036854775807"), ast::Expr::Literal(Value::I64(i64::MAX)));
+        assert_eq!(literal("n = 18446744073709551615"), ast::Expr::Literal(Value::U64(u64::MAX)));
+        assert_eq!(literal("n = 18446744073709551616"), ast::Expr::Literal(Value::I128(1 << 64)));
+    }
+
     #[test]
     fn test_parse_selection_user_and_status() {
         let input = r#"user = 123 AND status = 'active'"#;
@@ -550,6 +584,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_contains_and_reversed_in() {
+        let contains = ast::Predicate::Comparison {
+            left: Box::new(ast::Expr::Path(ast::PathExpr::simple("tags".to_string()))),
+            operator: ast::ComparisonOperator::Contains,
+            right: Box::new(ast::Expr::Literal(Value::String("urgent".to_string()))),
+        };
+        assert_eq!(parse_selection("tags CONTAINS 'urgent'").unwrap().predicate, contains);
+        // `value IN collection` is the same membership test written the other way round
+        assert_eq!(parse_selection("'urgent' in tags").unwrap().predicate, contains);
+        assert_eq!(
+            parse_selection("name = 'x' AND 'urgent' IN tags").unwrap().predicate,
+            ast::Predicate::And(
+                Box::new(ast::Predicate::Comparison {
+                    left: Box::new(ast::Expr::Path(ast::PathExpr::simple("name".to_string()))),
+                    operator: ast::ComparisonOperator::Equal,
+                    right: Box::new(ast::Expr::Literal(Value::String("x".to_string()))),
+                }),
+                Box::new(contains)
+            )
+        );
+        // Identifiers that start with the keyword are still identifiers
+        assert!(parse_selection("containers = 1").is_ok());
+    }
+
     #[test]
     fn test_comparison_to_true() {
         let input = r#"bool_field = true"#;
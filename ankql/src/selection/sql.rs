@@ -31,6 +31,15 @@ fn generate_expr_sql(
             Value::I64(i) => {
                 buffer.push_str(&i.to_string());
             }
+            Value::U32(i) => {
+                buffer.push_str(&i.to_string());
+            }
+            Value::U64(i) => {
+                buffer.push_str(&i.to_string());
+            }
+            Value::I128(i) => {
+                buffer.push_str(&i.to_string());
+            }
             Value::F64(f) => {
                 buffer.push_str(&f.to_string());
             }
@@ -69,6 +78,18 @@ fn generate_expr_sql(
                 buffer.push_str(&value.to_string());
                 buffer.push('\'');
             }
+            Value::Decimal(d) => {
+                buffer.push_str(&d.to_string());
+            }
+            Value::Timestamp(t) => {
+                buffer.push_str(&format!("'{t}'"));
+            }
+            Value::Date(d) => {
+                buffer.push_str(&format!("'{d}'"));
+            }
+            Value::Uuid(u) => {
+                buffer.push_str(&format!("'{u}'"));
+            }
         },
         Expr::Path(path) => {
             // Output each step quoted and dot-separated: "a"."b"."c"
@@ -113,6 +134,15 @@ fn generate_expr_sql(
                         Value::I64(i) => {
                             buffer.push_str(&i.to_string());
                         }
+                        Value::U32(i) => {
+                            buffer.push_str(&i.to_string());
+                        }
+                        Value::U64(i) => {
+                            buffer.push_str(&i.to_string());
+                        }
+                        Value::I128(i) => {
+                            buffer.push_str(&i.to_string());
+                        }
                         Value::F64(f) => {
                             buffer.push_str(&f.to_string());
                         }
@@ -151,6 +181,18 @@ fn generate_expr_sql(
                             buffer.push_str(&value.to_string());
                             buffer.push('\'');
                         }
+                        Value::Decimal(d) => {
+                            buffer.push_str(&d.to_string());
+                        }
+                        Value::Timestamp(t) => {
+                            buffer.push_str(&format!("'{t}'"));
+                        }
+                        Value::Date(d) => {
+                            buffer.push_str(&format!("'{d}'"));
+                        }
+                        Value::Uuid(u) => {
+                            buffer.push_str(&format!("'{u}'"));
+                        }
                     },
                     _ => {
                         return Err(SqlGenerationError::InvalidExpression(
@@ -175,6 +217,7 @@ fn comparison_op_to_sql(op: &ComparisonOperator) -> Result<&'static str, SqlGene
         ComparisonOperator::LessThan => "<",
         ComparisonOperator::LessThanOrEqual => "<=",
         ComparisonOperator::In => "IN",
+        ComparisonOperator::Contains => "CONTAINS",
         ComparisonOperator::Between => return Err(SqlGenerationError::UnsupportedOperator("BETWEEN operator is not yet supported")),
     })
 }
@@ -157,7 +157,7 @@ pub use ankurah_core::{
     resultset::ResultSet,
     session::{Session, SessionSet},
     storage, transaction, value,
-    value::{Value, ValueType},
+    value::{Date, Decimal, Timestamp, Uuid, Value, ValueType},
 };
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -0,0 +1,94 @@
+//! The on-disk dump format: a magic header followed by length-prefixed
+//! bincode [`StorageDumpItem`] records, in the order the engine emitted them.
+
+use ankurah_core::{error::RetrievalError, storage::StorageDumpItem};
+use anyhow::{anyhow, Result};
+use futures::{stream, StreamExt};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::store::DumpStream;
+
+const MAGIC: &[u8; 8] = b"ANKDUMP1";
+
+/// Write every item of `items` to `out`, returning the number written.
+pub async fn write<W: AsyncWrite + Unpin>(out: &mut W, mut items: DumpStream) -> Result<usize> {
+    out.write_all(MAGIC).await?;
+    let mut count = 0;
+    while let Some(item) = items.next().await {
+        let bytes = bincode::serialize(&item?)?;
+        out.write_all(&(bytes.len() as u32).to_le_bytes()).await?;
+        out.write_all(&bytes).await?;
+        count += 1;
+    }
+    out.flush().await?;
+    Ok(count)
+}
+
+/// Stream the records of a dump written by [`write`].
+pub async fn read<R: AsyncRead + Unpin + Send + 'static>(mut input: R) -> Result<DumpStream> {
+    let mut magic = [0u8; 8];
+    input.read_exact(&mut magic).await?;
+    if &magic != MAGIC {
+        return Err(anyhow!("not an Ankurah dump file"));
+    }
+    Ok(stream::try_unfold(input, |mut input| async move {
+        let mut len = [0u8; 4];
+        match input.read_exact(&mut len).await {
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(RetrievalError::Other(format!("failed to read dump record: {e}"))),
+        }
+        let mut bytes = vec![0u8; u32::from_le_bytes(len) as usize];
+        input.read_exact(&mut bytes).await.map_err(|e| RetrievalError::Other(format!("truncated dump record: {e}")))?;
+        let item: StorageDumpItem = bincode::deserialize(&bytes)?;
+        Ok(Some((item, input)))
+    })
+    .boxed())
+}
+
+#[cfg(test)]
+mod tests {
+    use ankurah_core::storage::{StorageDump, StorageEngine};
+    use ankurah_proto::{AttestationSet, Attested, AuthorId, CollectionId, EntityState, Event, OperationSet, State};
+    use ankurah_storage_sled::SledStorageEngine;
+
+    use super::*;
+    use crate::store::Store;
+
+    #[tokio::test]
+    async fn dump_file_round_trips_through_restore() -> Result<()> {
+        let source = SledStorageEngine::new_test()?;
+        let collection_id = CollectionId::from("round_trip");
+        let collection = source.collection(&collection_id).await?;
+        let event = Attested {
+            payload: Event::genesis(collection_id.clone(), None, AuthorId::Unknown, OperationSet::default()),
+            attestations: AttestationSet::default(),
+        };
+        let entity_id = event.payload.entity_id;
+        collection.add_event(&event).await?;
+        let mut state = State::default();
+        state.head = event.payload.id().into();
+        collection
+            .set_state(Attested {
+                payload: EntityState { entity_id, collection: collection_id.clone(), state },
+                attestations: AttestationSet::default(),
+            })
+            .await?;
+
+        let mut bytes = Vec::new();
+        assert_eq!(write(&mut bytes, source.dump().await?.boxed()).await?, 2);
+
+        let target = Store::Sled(SledStorageEngine::new_test()?);
+        let summary = target.restore(read(std::io::Cursor::new(bytes)).await?).await?;
+        assert_eq!((summary.events, summary.states), (1, 1));
+        let restored = target.collection(&collection_id).await?;
+        assert_eq!(restored.get_state(entity_id).await?.payload.state.head, event.payload.id().into());
+        assert_eq!(restored.get_events(vec![event.payload.id()]).await?.len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn rejects_a_file_without_the_header() {
+        assert!(read(std::io::Cursor::new(b"not a dump".to_vec())).await.is_err());
+    }
+}
@@ -0,0 +1,146 @@
+//! `ankurah`: inspect and maintain an Ankurah store outside the application.
+
+mod dumpfile;
+mod render;
+mod store;
+
+use std::path::PathBuf;
+
+use ankurah_core::{storage::VerifyOptions, TypeResolver};
+use ankurah_proto::{CollectionId, EntityId};
+use anyhow::{anyhow, Result};
+use clap::{Parser, Subcommand};
+
+use crate::store::Store;
+
+#[derive(Parser)]
+#[command(name = "ankurah", version, about = "Inspect and maintain an Ankurah store")]
+struct Cli {
+    /// Store location: a Sled directory, a SQLite file (`.db`, `.sqlite`,
+    /// `sqlite://path`), or a `postgres://` connection URI
+    #[arg(short, long, global = true)]
+    store: Option<String>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List the collections in the store
+    Collections,
+    /// Run an AnkQL selection against a collection
+    Query { collection: String, selection: String },
+    /// Print an entity's stored state
+    Entity { collection: String, id: String },
+    /// Print an entity's event DAG, parents first
+    Events { collection: String, id: String },
+    /// Export every event and state to a dump file
+    Dump { file: PathBuf },
+    /// Load a dump file into the store, creating it if needed
+    Restore { file: PathBuf },
+    /// Discard and rebuild secondary indexes
+    Reindex {
+        /// Only rebuild this collection's indexes
+        #[arg(long)]
+        collection: Option<String>,
+    },
+    /// List secondary indexes with their status, size and build progress
+    Indexes {
+        /// Only list this collection's indexes
+        #[arg(long)]
+        collection: Option<String>,
+    },
+    /// Drop a secondary index by id; queries recreate it when needed
+    DropIndex { id: u32 },
+    /// Check stored events, states and indexes for consistency
+    Verify {
+        /// Rewrite states, materializations and indexes that can be
+        /// derived again from the event log
+        #[arg(long)]
+        repair: bool,
+    },
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt().with_writer(std::io::stderr).with_max_level(tracing_subscriber::filter::LevelFilter::WARN).init();
+    let cli = Cli::parse();
+    let spec = cli.store.ok_or_else(|| anyhow!("--store is required"))?;
+
+    match cli.command {
+        Command::Collections => {
+            for collection in Store::open(&spec).await?.list_collections().await? {
+                println!("{collection}");
+            }
+        }
+        Command::Query { collection, selection } => {
+            let store = Store::open(&spec).await?;
+            let selection = TypeResolver::new().resolve_selection_types(ankql::parser::parse_selection(&selection)?);
+            let states = store.collection(&CollectionId::from(collection)).await?.fetch_states(&selection).await?;
+            for state in &states {
+                print!("{}", render::entity_state(state)?);
+            }
+            eprintln!("{} entities", states.len());
+        }
+        Command::Entity { collection, id } => {
+            let store = Store::open(&spec).await?;
+            let state = store.collection(&CollectionId::from(collection)).await?.get_state(parse_id(&id)?).await?;
+            print!("{}", render::entity_state(&state)?);
+        }
+        Command::Events { collection, id } => {
+            let store = Store::open(&spec).await?;
+            let events = store.collection(&CollectionId::from(collection)).await?.dump_entity_events(parse_id(&id)?).await?;
+            print!("{}", render::event_dag(events));
+        }
+        Command::Dump { file } => {
+            let store = Store::open(&spec).await?;
+            let mut out = tokio::io::BufWriter::new(tokio::fs::File::create(&file).await?);
+            let count = dumpfile::write(&mut out, store.dump().await?).await?;
+            eprintln!("wrote {count} records to {}", file.display());
+        }
+        Command::Restore { file } => {
+            let store = Store::create(&spec).await?;
+            let items = dumpfile::read(tokio::io::BufReader::new(tokio::fs::File::open(&file).await?)).await?;
+            let summary = store.restore(items).await?;
+            eprintln!("restored {} events and {} states", summary.events, summary.states);
+        }
+        Command::Reindex { collection } => {
+            let store = Store::open(&spec).await?;
+            let rebuilt = store.rebuild_indexes(collection.map(CollectionId::from).as_ref()).await?;
+            eprintln!("rebuilt {rebuilt} indexes");
+        }
+        Command::Indexes { collection } => {
+            let store = Store::open(&spec).await?;
+            for index in store.list_indexes(collection.map(CollectionId::from).as_ref()).await? {
+                print!("{}", render::index(&index));
+            }
+        }
+        Command::DropIndex { id } => {
+            if !Store::open(&spec).await?.drop_index(id).await? {
+                return Err(anyhow!("no index with id {id}"));
+            }
+            eprintln!("dropped index {id}");
+        }
+        Command::Verify { repair } => {
+            let store = Store::open(&spec).await?;
+            let (mut events, mut states, mut problems, mut repaired) = (0, 0, 0, 0);
+            for (collection, report) in store.verify(VerifyOptions { repair }).await? {
+                for problem in &report.problems {
+                    println!("{collection}/{problem}");
+                }
+                events += report.events;
+                states += report.states;
+                problems += report.problems.len();
+                repaired += report.repaired;
+            }
+            eprintln!("checked {events} events and {states} states: {problems} problems, {repaired} repaired");
+            if problems > repaired {
+                std::process::exit(1);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn parse_id(id: &str) -> Result<EntityId> { EntityId::from_base64(id).map_err(|e| anyhow!("invalid entity id {id}: {e}")) }
@@ -0,0 +1,78 @@
+//! Plain-text rendering of entity states and event DAGs.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+};
+
+use ankurah_core::{entity::TemporaryEntity, error::RetrievalError};
+use ankurah_proto::{Attested, EntityState, Event, EventId};
+use ankurah_storage_sled::index::{BuildStatus, IndexInfo};
+
+/// An entity's head followed by one `name = value` line per property.
+pub fn entity_state(state: &Attested<EntityState>) -> Result<String, RetrievalError> {
+    let EntityState { entity_id, collection, state } = &state.payload;
+    let entity = TemporaryEntity::new(*entity_id, collection.clone(), state)?;
+    let mut out = String::new();
+    writeln!(out, "{collection}/{entity_id}").unwrap();
+    writeln!(out, "  head: {}", state.head).unwrap();
+    let mut values = entity.values();
+    values.sort_by(|(a, _), (b, _)| a.cmp(b));
+    for (name, value) in values {
+        match value {
+            Some(value) => writeln!(out, "  {name} = {value}").unwrap(),
+            None => writeln!(out, "  {name} = null").unwrap(),
+        }
+    }
+    Ok(out)
+}
+
+/// Events parents-first, one per line, each naming the parents it extends.
+pub fn event_dag(events: Vec<Attested<Event>>) -> String {
+    let mut out = String::new();
+    for event in parents_first(events) {
+        let parents = if event.is_entity_create() { "genesis".to_owned() } else { format!("<- {}", event.parent) };
+        writeln!(out, "{} {} t={} {}", event.id(), parents, event.timestamp(), event.operations()).unwrap();
+    }
+    out
+}
+
+/// One line per index: id, collection and name, status, entries and size.
+pub fn index(index: &IndexInfo) -> String {
+    let status = match (index.status, index.progress) {
+        (BuildStatus::Building, Some(p)) => format!("building {}/{}", p.scanned, p.total),
+        (BuildStatus::Building, None) => "building".to_owned(),
+        (BuildStatus::NotBuilt, _) => "not built".to_owned(),
+        (BuildStatus::Ready, _) => "ready".to_owned(),
+    };
+    let created = chrono::DateTime::from_timestamp_millis(index.created_at_unix_ms).map(|t| t.to_rfc3339()).unwrap_or_default();
+    format!(
+        "{} {}.{} {} entries={} bytes={} created={}\n",
+        index.id, index.collection, index.name, status, index.entries, index.bytes, created
+    )
+}
+
+/// Order events so each follows every parent present in the set. Parents
+/// outside the set do not hold an event back; events caught in a parent
+/// cycle are appended last so a damaged DAG still prints in full.
+fn parents_first(events: Vec<Attested<Event>>) -> Vec<Event> {
+    let mut pending: BTreeMap<EventId, Event> = events.into_iter().map(|e| (e.payload.id(), e.payload)).collect();
+    let mut emitted = BTreeSet::new();
+    let mut ordered = Vec::with_capacity(pending.len());
+    loop {
+        let ready: Vec<EventId> = pending
+            .iter()
+            .filter(|(_, event)| event.parent.iter().all(|p| emitted.contains(p) || !pending.contains_key(p)))
+            .map(|(id, _)| id.clone())
+            .collect();
+        if ready.is_empty() {
+            break;
+        }
+        for id in ready {
+            ordered.push(pending.remove(&id).expect("ready id is pending"));
+            emitted.insert(id);
+        }
+    }
+    ordered.extend(pending.into_values());
+    ordered
+}
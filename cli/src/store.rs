@@ -0,0 +1,165 @@
+//! Opening a store by path or connection URI, and the per-engine operations
+//! the commands need that are not part of [`StorageEngine`].
+
+use std::{path::PathBuf, pin::Pin, sync::Arc};
+
+use ankurah_core::{
+    error::RetrievalError,
+    storage::{
+        local::is_node_local, restore_dump, RestoreSummary, StorageCollection, StorageDump, StorageDumpItem, StorageEngine, VerifyOptions,
+        VerifyReport,
+    },
+};
+use ankurah_proto::CollectionId;
+use ankurah_storage_postgres::Postgres;
+use ankurah_storage_sled::{index::IndexInfo, SledStorageEngine};
+use ankurah_storage_sqlite::SqliteStorageEngine;
+use anyhow::{anyhow, Result};
+use futures::{Stream, StreamExt};
+
+pub type DumpStream = Pin<Box<dyn Stream<Item = Result<StorageDumpItem, RetrievalError>> + Send>>;
+
+pub enum Store {
+    Sled(SledStorageEngine),
+    Sqlite(SqliteStorageEngine),
+    Postgres(Postgres),
+}
+
+/// Where a store lives, parsed from the `--store` argument.
+#[derive(Debug, PartialEq)]
+pub enum Location {
+    Sled(PathBuf),
+    Sqlite(PathBuf),
+    Postgres(String),
+}
+
+impl Location {
+    /// `postgres://` and `postgresql://` URIs open Postgres; `sqlite://path`
+    /// or a path ending in `.db`, `.sqlite` or `.sqlite3` opens SQLite;
+    /// `sled://path` or any other path opens a Sled directory (the same
+    /// directory passed to `SledStorageEngine::with_path`).
+    pub fn parse(spec: &str) -> Self {
+        if spec.starts_with("postgres://") || spec.starts_with("postgresql://") {
+            return Location::Postgres(spec.to_owned());
+        }
+        if let Some(path) = spec.strip_prefix("sqlite://") {
+            return Location::Sqlite(path.into());
+        }
+        if let Some(path) = spec.strip_prefix("sled://") {
+            return Location::Sled(path.into());
+        }
+        let path = PathBuf::from(spec);
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("db" | "sqlite" | "sqlite3") => Location::Sqlite(path),
+            _ => Location::Sled(path),
+        }
+    }
+}
+
+impl Store {
+    pub async fn open(spec: &str) -> Result<Self> {
+        Ok(match Location::parse(spec) {
+            Location::Sled(path) => {
+                if !path.join("sled").exists() {
+                    return Err(anyhow!("no sled store at {}", path.display()));
+                }
+                Store::Sled(SledStorageEngine::with_path(path)?)
+            }
+            Location::Sqlite(path) => Store::Sqlite(SqliteStorageEngine::open(path).await?),
+            Location::Postgres(uri) => Store::Postgres(Postgres::open(&uri).await?),
+        })
+    }
+
+    /// Open a store as a restore target, creating it if it does not exist.
+    pub async fn create(spec: &str) -> Result<Self> {
+        Ok(match Location::parse(spec) {
+            Location::Sled(path) => Store::Sled(SledStorageEngine::with_path(path)?),
+            _ => Self::open(spec).await?,
+        })
+    }
+
+    pub async fn list_collections(&self) -> Result<Vec<CollectionId>, RetrievalError> {
+        match self {
+            Store::Sled(engine) => engine.list_collections(),
+            Store::Sqlite(engine) => engine.list_collections().await,
+            Store::Postgres(engine) => engine.list_collections().await,
+        }
+    }
+
+    pub async fn collection(&self, id: &CollectionId) -> Result<Arc<dyn StorageCollection>, RetrievalError> {
+        match self {
+            Store::Sled(engine) => engine.collection(id).await,
+            Store::Sqlite(engine) => engine.collection(id).await,
+            Store::Postgres(engine) => engine.collection(id).await,
+        }
+    }
+
+    pub async fn dump(&self) -> Result<DumpStream, RetrievalError> {
+        Ok(match self {
+            Store::Sled(engine) => engine.dump().await?.boxed(),
+            Store::Sqlite(engine) => engine.dump().await?.boxed(),
+            Store::Postgres(engine) => engine.dump().await?.boxed(),
+        })
+    }
+
+    pub async fn restore(&self, items: DumpStream) -> Result<RestoreSummary> {
+        Ok(match self {
+            Store::Sled(engine) => restore_dump(engine, items).await?,
+            Store::Sqlite(engine) => restore_dump(engine, items).await?,
+            Store::Postgres(engine) => restore_dump(engine, items).await?,
+        })
+    }
+
+    /// Verify every replicated collection. Sled reads its global event log
+    /// once for all of them; the SQL engines keep events per collection.
+    /// Node-local collections have no event log to check against.
+    pub async fn verify(&self, options: VerifyOptions) -> Result<Vec<(CollectionId, VerifyReport)>, RetrievalError> {
+        if let Store::Sled(engine) = self {
+            return engine.verify(options).await;
+        }
+        let mut reports = Vec::new();
+        for collection in self.list_collections().await?.into_iter().filter(|c| !is_node_local(c)) {
+            let report = self.collection(&collection).await?.verify(options).await?;
+            reports.push((collection, report));
+        }
+        Ok(reports)
+    }
+
+    /// Rebuild secondary indexes. Only Sled maintains its own; the SQL
+    /// engines leave index maintenance to the database.
+    pub async fn rebuild_indexes(&self, collection: Option<&CollectionId>) -> Result<usize> {
+        match self {
+            Store::Sled(engine) => Ok(engine.rebuild_indexes(collection).await?),
+            Store::Sqlite(_) | Store::Postgres(_) => Err(anyhow!("this engine does not maintain Ankurah-managed indexes")),
+        }
+    }
+
+    pub async fn list_indexes(&self, collection: Option<&CollectionId>) -> Result<Vec<IndexInfo>> {
+        match self {
+            Store::Sled(engine) => Ok(engine.list_indexes(collection).await?),
+            Store::Sqlite(_) | Store::Postgres(_) => Err(anyhow!("this engine does not maintain Ankurah-managed indexes")),
+        }
+    }
+
+    pub async fn drop_index(&self, id: u32) -> Result<bool> {
+        match self {
+            Store::Sled(engine) => Ok(engine.drop_index(id).await?),
+            Store::Sqlite(_) | Store::Postgres(_) => Err(anyhow!("this engine does not maintain Ankurah-managed indexes")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_locations() {
+        assert_eq!(Location::parse("postgres://u@h/db"), Location::Postgres("postgres://u@h/db".into()));
+        assert_eq!(Location::parse("postgresql://h/db"), Location::Postgres("postgresql://h/db".into()));
+        assert_eq!(Location::parse("sqlite://data/app"), Location::Sqlite("data/app".into()));
+        assert_eq!(Location::parse("app.sqlite3"), Location::Sqlite("app.sqlite3".into()));
+        assert_eq!(Location::parse("sled://x.db"), Location::Sled("x.db".into()));
+        assert_eq!(Location::parse("/home/me/.ankurah"), Location::Sled("/home/me/.ankurah".into()));
+    }
+}
@@ -60,6 +60,9 @@ where
                 node_id: node2.id,
                 durable: node2.durable,
                 system_root: node2.system.root(),
+                wire: proto::WireSupport::default(),
+                features: proto::protocol_features(),
+                min_protocol_version: proto::MIN_PROTOCOL_VERSION,
                 protocol_version: proto::PROTOCOL_VERSION,
             },
             Box::new(LocalProcessSender { sender: node2_tx, node_id: node2.id }),
@@ -69,6 +72,9 @@ where
                 node_id: node1.id,
                 durable: node1.durable,
                 system_root: node1.system.root(),
+                wire: proto::WireSupport::default(),
+                features: proto::protocol_features(),
+                min_protocol_version: proto::MIN_PROTOCOL_VERSION,
                 protocol_version: proto::PROTOCOL_VERSION,
             },
             Box::new(LocalProcessSender { sender: node1_tx, node_id: node1.id }),
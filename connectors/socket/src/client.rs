@@ -0,0 +1,347 @@
+use ankurah_core::connector::NodeComms;
+use ankurah_proto as proto;
+use ankurah_signals::{Mut, Read, Wait};
+use anyhow::{anyhow, Result};
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use strum::Display;
+use thiserror::Error;
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::TcpStream,
+    select,
+    sync::Notify,
+    task::JoinHandle,
+    time::sleep,
+};
+use tokio_rustls::{
+    rustls::{self, pki_types::ServerName},
+    TlsConnector,
+};
+use tracing::{error, info, warn};
+
+use crate::connection;
+
+/// The address a [`SocketClient`] connects to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Endpoint {
+    /// A TCP `host:port`
+    Tcp(String),
+    /// The path of a Unix domain socket
+    #[cfg(unix)]
+    Unix(std::path::PathBuf),
+}
+
+impl Endpoint {
+    pub fn tcp(addr: impl Into<String>) -> Self { Endpoint::Tcp(addr.into()) }
+
+    #[cfg(unix)]
+    pub fn unix(path: impl Into<std::path::PathBuf>) -> Self { Endpoint::Unix(path.into()) }
+}
+
+impl std::str::FromStr for Endpoint {
+    type Err = anyhow::Error;
+
+    /// Parses the `tcp://` and `unix://` forms [`Endpoint`] displays as; a bare address is TCP
+    fn from_str(s: &str) -> Result<Self> {
+        if let Some(path) = s.strip_prefix("unix://") {
+            #[cfg(unix)]
+            return Ok(Endpoint::unix(path));
+            #[cfg(not(unix))]
+            return Err(anyhow!("Unix domain sockets are not supported here: {}", path));
+        }
+        let addr = s.strip_prefix("tcp://").unwrap_or(s);
+        if addr.is_empty() || addr.contains("://") {
+            return Err(anyhow!("not a socket endpoint: {}", s));
+        }
+        Ok(Endpoint::tcp(addr))
+    }
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Endpoint::Tcp(addr) => write!(f, "tcp://{}", addr),
+            #[cfg(unix)]
+            Endpoint::Unix(path) => write!(f, "unix://{}", path.display()),
+        }
+    }
+}
+
+/// Builder for configuring and creating a socket client
+pub struct SocketClientBuilder {
+    node: Box<dyn NodeComms>,
+    endpoint: Endpoint,
+    tls: Option<(Arc<rustls::ClientConfig>, String)>,
+}
+
+impl SocketClientBuilder {
+    /// Connect over TLS, verifying the server's certificate against `server_name`
+    pub fn tls(mut self, config: Arc<rustls::ClientConfig>, server_name: impl Into<String>) -> Self {
+        self.tls = Some((config, server_name.into()));
+        self
+    }
+
+    /// Build and start the socket client
+    pub async fn build(self) -> anyhow::Result<SocketClient> {
+        let tls = match self.tls {
+            Some((config, server_name)) => {
+                let server_name = ServerName::try_from(server_name).map_err(|e| anyhow!("invalid TLS server name: {}", e))?;
+                Some((TlsConnector::from(config), server_name))
+            }
+            None => None,
+        };
+        SocketClient::create(self.node, self.endpoint, tls).await
+    }
+}
+
+/// Connection state for the socket client
+#[derive(Debug, Clone, PartialEq, Display)]
+pub enum ConnectionState {
+    Disconnected,
+    #[strum(serialize = "Connecting")]
+    Connecting {
+        endpoint: Endpoint,
+    },
+    #[strum(serialize = "Connected")]
+    Connected {
+        endpoint: Endpoint,
+        server_presence: Box<proto::Presence>,
+    },
+    #[strum(serialize = "Error")]
+    Error(ConnectionError),
+}
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ConnectionError {
+    #[error("General connection error: {0}")]
+    General(String),
+}
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+struct Inner {
+    node: Box<dyn NodeComms>,
+    /// Where to connect; replaced when a server redirects us elsewhere
+    endpoint: std::sync::RwLock<Endpoint>,
+    tls: Option<(TlsConnector, ServerName<'static>)>,
+    connection_state: Mut<ConnectionState>,
+    connected: AtomicBool,
+    shutdown: Notify,
+    shutdown_requested: AtomicBool,
+}
+
+/// A TCP or Unix domain socket client for connecting Ankurah nodes
+///
+/// Reconnects with exponential backoff whenever the connection is lost.
+pub struct SocketClient {
+    inner: Arc<Inner>,
+    task: std::sync::Mutex<Option<JoinHandle<()>>>,
+}
+
+impl SocketClient {
+    /// Create a new socket client and start connecting to the server
+    pub async fn new(node: impl NodeComms + 'static, endpoint: Endpoint) -> anyhow::Result<Self> {
+        Self::create(Box::new(node), endpoint, None).await
+    }
+
+    /// Create a builder for configuring the socket client
+    pub fn builder(node: impl NodeComms + 'static, endpoint: Endpoint) -> SocketClientBuilder {
+        SocketClientBuilder { node: Box::new(node), endpoint, tls: None }
+    }
+
+    async fn create(
+        node: Box<dyn NodeComms>,
+        endpoint: Endpoint,
+        tls: Option<(TlsConnector, ServerName<'static>)>,
+    ) -> anyhow::Result<Self> {
+        info!("Creating socket client for {}", endpoint);
+
+        let inner = Arc::new(Inner {
+            node,
+            endpoint: std::sync::RwLock::new(endpoint),
+            tls,
+            connection_state: Mut::new(ConnectionState::Disconnected),
+            connected: AtomicBool::new(false),
+            shutdown: Notify::new(),
+            shutdown_requested: AtomicBool::new(false),
+        });
+
+        let task = tokio::spawn(Self::run_connection_loop(inner.clone()));
+        Ok(Self { inner, task: std::sync::Mutex::new(Some(task)) })
+    }
+
+    /// Get the connection state as a reactive signal
+    pub fn state(&self) -> Read<ConnectionState> { self.inner.connection_state.read() }
+
+    /// Check if currently connected to the server
+    pub fn is_connected(&self) -> bool { self.inner.connected.load(Ordering::Acquire) }
+
+    /// Gracefully shutdown the socket connection
+    pub async fn shutdown(self) -> anyhow::Result<()> {
+        info!("Shutting down socket client");
+
+        let task = self.task.lock().unwrap().take();
+        if let Some(task) = task {
+            self.inner.shutdown_requested.store(true, Ordering::Release);
+            self.inner.shutdown.notify_waiters();
+
+            match task.await {
+                Ok(()) => info!("Socket client shutdown completed"),
+                Err(e) => warn!("Connection task join error during shutdown: {}", e),
+            }
+        } else {
+            info!("Socket client already shut down");
+        }
+        Ok(())
+    }
+
+    /// Wait for the client to establish a connection to the server (signal-based)
+    pub async fn wait_connected(&self) -> Result<(), ConnectionError> {
+        self.state()
+            .wait_for(|state| match state {
+                ConnectionState::Connected { .. } => Some(Ok(())),
+                ConnectionState::Error(e) => Some(Err(e.clone())),
+                _ => None,
+            })
+            .await
+    }
+
+    /// Get the node ID of the connected server (if connected)
+    pub fn server_node_id(&self) -> Option<proto::EntityId> {
+        use ankurah_signals::Get;
+        match self.state().get() {
+            ConnectionState::Connected { server_presence, .. } => Some(server_presence.node_id),
+            _ => None,
+        }
+    }
+
+    /// Main connection loop with automatic reconnection
+    async fn run_connection_loop(inner: Arc<Inner>) {
+        let mut backoff = INITIAL_BACKOFF;
+        info!("Starting socket connection loop to {}", inner.endpoint());
+
+        loop {
+            select! {
+                _ = inner.shutdown.notified() => {
+                    info!("Socket connection shutting down");
+                    break;
+                }
+                result = Self::connect_once(&inner) => {
+                    match result {
+                        Ok(Some(goodbye)) => {
+                            // A planned disconnect: come back as soon as the server asks
+                            // rather than backing off
+                            info!("Server {} said goodbye: {}", inner.endpoint(), goodbye);
+                            backoff = INITIAL_BACKOFF;
+                            if let Some(redirect) = &goodbye.redirect {
+                                match redirect.parse() {
+                                    Ok(endpoint) => *inner.endpoint.write().unwrap_or_else(|e| e.into_inner()) = endpoint,
+                                    Err(e) => warn!("Ignoring redirect from {}: {}", inner.endpoint(), e),
+                                }
+                            }
+                            if inner.shutdown_requested.load(Ordering::Acquire) {
+                                break;
+                            }
+                            inner.connection_state.set(ConnectionState::Disconnected);
+                            select! {
+                                _ = inner.shutdown.notified() => break,
+                                _ = sleep(Duration::from_millis(goodbye.reconnect_after_ms)) => {}
+                            }
+                        }
+                        Ok(None) => {
+                            info!("Connection to {} completed normally", inner.endpoint());
+                            backoff = INITIAL_BACKOFF;
+                            if inner.shutdown_requested.load(Ordering::Acquire) {
+                                info!("Shutdown requested, stopping reconnection attempts");
+                                break;
+                            }
+                        }
+                        Err(e) => {
+                            error!("Connection to {} failed: {}", inner.endpoint(), e);
+                            inner.connection_state.set(ConnectionState::Error(ConnectionError::General(e.to_string())));
+                            inner.connected.store(false, Ordering::Release);
+
+                            info!("Retrying connection in {:?}", backoff);
+                            select! {
+                                _ = inner.shutdown.notified() => break,
+                                _ = sleep(backoff) => {}
+                            }
+                            backoff = (backoff * 2).min(MAX_BACKOFF);
+                        }
+                    }
+                }
+            }
+        }
+
+        inner.connection_state.set(ConnectionState::Disconnected);
+        inner.connected.store(false, Ordering::Release);
+    }
+
+    /// Attempt a single connection, returning the server's goodbye if it ended the connection with one
+    async fn connect_once(inner: &Arc<Inner>) -> Result<Option<proto::Goodbye>> {
+        let endpoint = inner.endpoint();
+        info!("Attempting to connect to {}", endpoint);
+        inner.connection_state.set(ConnectionState::Connecting { endpoint: endpoint.clone() });
+
+        match &endpoint {
+            Endpoint::Tcp(addr) => {
+                let stream = TcpStream::connect(addr).await?;
+                stream.set_nodelay(true)?;
+                Self::establish(inner, &endpoint, stream).await
+            }
+            #[cfg(unix)]
+            Endpoint::Unix(path) => {
+                let stream = tokio::net::UnixStream::connect(path).await?;
+                Self::establish(inner, &endpoint, stream).await
+            }
+        }
+    }
+
+    async fn establish<S>(inner: &Arc<Inner>, endpoint: &Endpoint, stream: S) -> Result<Option<proto::Goodbye>>
+    where S: AsyncRead + AsyncWrite + Unpin {
+        match &inner.tls {
+            Some((connector, server_name)) => {
+                let stream = connector.connect(server_name.clone(), stream).await?;
+                Self::run_connection(inner, endpoint, stream).await
+            }
+            None => Self::run_connection(inner, endpoint, stream).await,
+        }
+    }
+
+    async fn run_connection<S>(inner: &Arc<Inner>, endpoint: &Endpoint, stream: S) -> Result<Option<proto::Goodbye>>
+    where S: AsyncRead + AsyncWrite + Unpin {
+        let peer = endpoint.to_string();
+        let result = connection::run(inner.node.as_ref(), stream, &peer, inner.shutdown.notified(), |server_presence| {
+            info!("Successfully connected to server {}", endpoint);
+            inner
+                .connection_state
+                .set(ConnectionState::Connected { endpoint: endpoint.clone(), server_presence: Box::new(server_presence) });
+            inner.connected.store(true, Ordering::Release);
+        })
+        .await;
+        inner.connected.store(false, Ordering::Release);
+        result
+    }
+}
+
+impl Inner {
+    fn endpoint(&self) -> Endpoint { self.endpoint.read().unwrap_or_else(|e| e.into_inner()).clone() }
+}
+
+impl Drop for SocketClient {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.lock().unwrap().take() {
+            self.inner.shutdown_requested.store(true, Ordering::Release);
+            self.inner.shutdown.notify_waiters();
+            task.abort();
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use ankurah_core::connector::{NodeComms, PeerSender};
+use ankurah_proto as proto;
+use anyhow::{anyhow, Result};
+use bytes::Bytes;
+use futures_util::{SinkExt, StreamExt};
+use std::{future::Future, time::Duration};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    select,
+    sync::mpsc,
+};
+use tokio_util::codec::{Framed, LengthDelimitedCodec};
+use tracing::{debug, info, warn};
+
+use crate::sender::SocketPeerSender;
+
+/// Largest frame either end accepts; a longer length prefix closes the connection
+pub const MAX_FRAME_LENGTH: usize = 64 * 1024 * 1024;
+
+// A peer that cannot decode our Presence will never send its own, so the
+// pre-establishment state must not be allowed to live for the socket lifetime.
+pub(crate) const INITIAL_PRESENCE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Frame a stream as four byte big-endian length prefixed messages
+pub(crate) fn framed<S: AsyncRead + AsyncWrite>(stream: S) -> Framed<S, LengthDelimitedCodec> {
+    LengthDelimitedCodec::builder().max_frame_length(MAX_FRAME_LENGTH).new_framed(stream)
+}
+
+async fn send<S: AsyncRead + AsyncWrite + Unpin>(framed: &mut Framed<S, LengthDelimitedCodec>, message: &proto::Message) -> Result<()> {
+    let data = bincode::serialize(message)?;
+    framed.send(Bytes::from(data)).await?;
+    Ok(())
+}
+
+/// Send a queued message along with whatever else is already queued behind it
+async fn send_outgoing<S: AsyncRead + AsyncWrite + Unpin>(
+    framed: &mut Framed<S, LengthDelimitedCodec>,
+    wire: &proto::Wire,
+    recipient: proto::EntityId,
+    rx: &mut mpsc::UnboundedReceiver<proto::NodeMessage>,
+    first: proto::NodeMessage,
+) -> Result<()> {
+    let mut batch = vec![first];
+    while batch.len() < proto::wire::MAX_BATCH_LEN {
+        match rx.try_recv() {
+            Ok(message) => batch.push(message),
+            Err(_) => break,
+        }
+    }
+    for frame in wire.encode_all(batch)? {
+        ankurah_core::metrics::bytes_sent(recipient, frame.len());
+        framed.feed(Bytes::from(frame)).await?;
+    }
+    SinkExt::<Bytes>::flush(framed).await?;
+    Ok(())
+}
+
+fn handle_peer_message(node: &dyn NodeComms, peer: &str, message: proto::NodeMessage) {
+    let node = node.cloned();
+    let peer = peer.to_string();
+    tokio::spawn(async move {
+        if let Err(e) = node.handle_message(message).await {
+            warn!("Error handling message from {}: {}", peer, e);
+        }
+    });
+}
+
+async fn next_outgoing(outgoing: &mut Option<mpsc::UnboundedReceiver<proto::NodeMessage>>) -> Option<proto::NodeMessage> {
+    match outgoing {
+        Some(rx) => rx.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Drive one socket connection from the Presence exchange until either end closes it
+///
+/// Both ends run this same exchange: send our Presence, wait for the peer's, and register the
+/// peer with the node once its protocol version is accepted. `on_established` is called with the
+/// peer's Presence once it is registered. Returns `Ok` when the stream or `shutdown` ends the
+/// connection, with the peer's [`proto::Goodbye`] if it closed on purpose, and an error when the
+/// peer is refused, refuses us, or breaks the protocol.
+pub(crate) async fn run<S>(
+    node: &dyn NodeComms,
+    stream: S,
+    peer: &str,
+    shutdown: impl Future<Output = ()>,
+    on_established: impl FnOnce(proto::Presence),
+) -> Result<Option<proto::Goodbye>>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut framed = framed(stream);
+    let presence = proto::Presence {
+        node_id: node.id(),
+        durable: node.durable(),
+        system_root: node.system_root(),
+        wire: proto::WireSupport::all(),
+        features: proto::protocol_features(),
+        min_protocol_version: proto::MIN_PROTOCOL_VERSION,
+        protocol_version: proto::PROTOCOL_VERSION,
+    };
+    send(&mut framed, &proto::Message::Presence(presence)).await?;
+    debug!("Sent presence to {}", peer);
+
+    let initial_presence_deadline = tokio::time::Instant::now() + INITIAL_PRESENCE_TIMEOUT;
+    let mut on_established = Some(on_established);
+    let mut peer_sender: Option<SocketPeerSender> = None;
+    let mut outgoing: Option<mpsc::UnboundedReceiver<proto::NodeMessage>> = None;
+    let mut wire = proto::Wire::default();
+    tokio::pin!(shutdown);
+
+    let result = loop {
+        select! {
+            _ = &mut shutdown => {
+                debug!("Connection to {} received shutdown signal", peer);
+                break Ok(None);
+            }
+            _ = tokio::time::sleep_until(initial_presence_deadline), if peer_sender.is_none() => {
+                break Err(anyhow!("{} did not send presence within {:?}", peer, INITIAL_PRESENCE_TIMEOUT));
+            }
+            Some(message) = next_outgoing(&mut outgoing) => {
+                let rx = outgoing.as_mut().expect("outgoing messages come from the receiver");
+                let recipient = peer_sender.as_ref().expect("outgoing messages follow registration").recipient_node_id();
+                if let Err(e) = send_outgoing(&mut framed, &wire, recipient, rx, message).await {
+                    break Err(e);
+                }
+            }
+            frame = framed.next() => {
+                let frame = match frame {
+                    Some(Ok(frame)) => frame,
+                    Some(Err(e)) => break Err(e.into()),
+                    None => {
+                        debug!("Connection to {} closed", peer);
+                        break Ok(None);
+                    }
+                };
+                debug!(">>> {} sent {} bytes", peer, frame.len());
+                if let Some(sender) = &peer_sender {
+                    ankurah_core::metrics::bytes_received(sender.recipient_node_id(), frame.len());
+                }
+
+                match wire.decode(&frame) {
+                    Ok(proto::Decoded::Presence(presence)) => {
+                        if peer_sender.is_some() {
+                            warn!("Received presence from {} but already have a peer sender - ignoring", peer);
+                            continue;
+                        }
+                        // Pre-check the version while we can still reply through the
+                        // connection and await the flush; register_peer re-enforces
+                        // this for every transport.
+                        let registered = proto::Wire::negotiate(&presence).and_then(|negotiated| {
+                            let (sender, rx) = SocketPeerSender::new(presence.node_id);
+                            node.register_peer(presence.clone(), Box::new(sender.clone())).map(|_| (negotiated, sender, rx))
+                        });
+                        match registered {
+                            Ok((negotiated, sender, rx)) => {
+                                debug!("Registered peer {} at {} speaking protocol v{}", presence.node_id, peer, negotiated.version);
+                                peer_sender = Some(sender);
+                                outgoing = Some(rx);
+                                wire = negotiated;
+                                if let Some(on_established) = on_established.take() {
+                                    on_established(presence);
+                                }
+                            }
+                            Err(rejection) => {
+                                let reason = rejection.to_string();
+                                let _ = send(&mut framed, &proto::Message::PresenceRejected(rejection)).await;
+                                break Err(anyhow!("refused {}: {}", peer, reason));
+                            }
+                        }
+                    }
+                    Ok(proto::Decoded::PresenceRejected(rejection)) => {
+                        break Err(anyhow!("{} refused our presence: {}", peer, rejection));
+                    }
+                    Ok(proto::Decoded::PeerMessage(_) | proto::Decoded::Batch(_)) if peer_sender.is_none() => {
+                        // Negotiation has not admitted this peer, so nothing it says may reach the node
+                        break Err(anyhow!("{} sent application traffic before presence negotiation completed", peer));
+                    }
+                    Ok(proto::Decoded::PeerMessage(message)) => handle_peer_message(node, peer, message),
+                    Ok(proto::Decoded::Batch(messages)) => {
+                        for message in messages {
+                            handle_peer_message(node, peer, message);
+                        }
+                    }
+                    Ok(proto::Decoded::Goodbye(goodbye)) => {
+                        // Ending normally reconnects straight away rather than backing off
+                        info!("{} is closing the connection: {}", peer, goodbye);
+                        break Ok(Some(goodbye));
+                    }
+                    Err(e) => {
+                        if peer_sender.is_none() {
+                            // A handshake we cannot read will never establish; close
+                            // instead of idling on a dead connection.
+                            if proto::is_version0_presence(&frame) {
+                                break Err(anyhow!("{} speaks a pre-versioning (0.9.x or older) protocol", peer));
+                            }
+                            break Err(anyhow!("failed to deserialize handshake message from {}: {}", peer, e));
+                        }
+                        warn!("Failed to deserialize message from {}: {}", peer, e);
+                    }
+                }
+            }
+        }
+    };
+
+    if let Some(sender) = peer_sender {
+        node.deregister_peer(sender.recipient_node_id());
+        debug!("Deregistered peer {}", sender.recipient_node_id());
+    }
+    result
+}
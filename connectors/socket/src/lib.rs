@@ -0,0 +1,46 @@
+//! # Ankurah Socket Connector
+//!
+//! Connects Ankurah nodes over plain TCP or Unix domain sockets, optionally with TLS, for
+//! backend services and sidecars that have no use for a websocket stack.
+//!
+//! Each [`proto::Message`](ankurah_proto::Message) is sent as a single bincode frame behind a
+//! four byte big-endian length prefix. Both ends send their Presence as soon as the stream is
+//! open, and a peer speaking an incompatible protocol version is refused with a
+//! `PresenceRejected` message exactly as the websocket server does. After the handshake, frames
+//! are batched and compressed as the peer's Presence allows (see [`ankurah_proto::wire`]).
+//!
+//! ## Basic Usage
+//!
+//! ```rust,no_run
+//! # use ankurah_core::{Node, policy::PermissiveAgent, storage::StorageEngine};
+//! # use ankurah_connector_socket::{Endpoint, SocketClient, SocketServer};
+//! # use std::sync::Arc;
+//! # async fn example<SE: StorageEngine + Send + Sync + 'static>(server_storage: Arc<SE>, client_storage: Arc<SE>) -> anyhow::Result<()> {
+//! let server_node = Node::new_durable(server_storage, PermissiveAgent::new());
+//! let server = SocketServer::bind_tcp(server_node, "127.0.0.1:9797").await?;
+//! tokio::spawn(server.run());
+//!
+//! let client_node = Node::new(client_storage, PermissiveAgent::new());
+//! let client = SocketClient::new(client_node, Endpoint::tcp("127.0.0.1:9797")).await?;
+//! client.wait_connected().await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! ## TLS
+//!
+//! TCP connections can be wrapped in TLS with [`SocketServer::tls`] and
+//! [`SocketClientBuilder::tls`]; the [`tls`] module builds the rustls configurations from PEM.
+
+pub mod client;
+mod connection;
+pub mod sender;
+pub mod server;
+pub mod tls;
+
+pub use client::{ConnectionError, ConnectionState, Endpoint, SocketClient, SocketClientBuilder};
+pub use connection::MAX_FRAME_LENGTH;
+pub use sender::SocketPeerSender;
+pub use server::SocketServer;
+
+pub use tokio_rustls::rustls;
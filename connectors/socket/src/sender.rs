@@ -0,0 +1,37 @@
+use ankurah_core::connector::{PeerSender, SendError};
+use ankurah_proto as proto;
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+/// PeerSender implementation for socket connections
+///
+/// Messages are queued to the connection task, which owns the write half of the socket.
+#[derive(Clone)]
+pub struct SocketPeerSender {
+    tx: mpsc::UnboundedSender<proto::NodeMessage>,
+    recipient_node_id: proto::EntityId,
+}
+
+impl SocketPeerSender {
+    pub fn new(recipient_node_id: proto::EntityId) -> (Self, mpsc::UnboundedReceiver<proto::NodeMessage>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        (Self { tx, recipient_node_id }, rx)
+    }
+}
+
+#[async_trait]
+impl PeerSender for SocketPeerSender {
+    fn send_message(&self, message: proto::NodeMessage) -> Result<(), SendError> {
+        debug!("Queuing message for peer {}", self.recipient_node_id);
+
+        self.tx.send(message).map_err(|_| {
+            warn!("Failed to send message to peer {} - connection closed", self.recipient_node_id);
+            SendError::ConnectionClosed
+        })
+    }
+
+    fn recipient_node_id(&self) -> proto::EntityId { self.recipient_node_id }
+
+    fn cloned(&self) -> Box<dyn PeerSender> { Box::new(self.clone()) }
+}
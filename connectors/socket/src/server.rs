@@ -0,0 +1,122 @@
+use ankurah_core::connector::NodeComms;
+use anyhow::Result;
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::{TcpListener, ToSocketAddrs},
+};
+use tokio_rustls::{rustls, TlsAcceptor};
+use tracing::{debug, error, info, warn};
+
+use crate::connection::{self, INITIAL_PRESENCE_TIMEOUT};
+
+// Failed accepts are usually resource exhaustion (EMFILE), which retrying at once only repeats
+const ACCEPT_ERROR_DELAY: Duration = Duration::from_millis(100);
+
+enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(tokio::net::UnixListener, std::path::PathBuf),
+}
+
+/// Accepts peer connections on a TCP or Unix domain socket
+pub struct SocketServer {
+    node: Box<dyn NodeComms>,
+    listener: Listener,
+    tls: Option<TlsAcceptor>,
+}
+
+impl SocketServer {
+    /// Listen for TCP connections on `addr`
+    pub async fn bind_tcp(node: impl NodeComms + 'static, addr: impl ToSocketAddrs) -> Result<Self> {
+        let listener = TcpListener::bind(addr).await?;
+        Ok(Self { node: Box::new(node), listener: Listener::Tcp(listener), tls: None })
+    }
+
+    /// Listen for connections on the Unix domain socket at `path`, which must not exist yet
+    #[cfg(unix)]
+    pub fn bind_unix(node: impl NodeComms + 'static, path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let listener = tokio::net::UnixListener::bind(&path)?;
+        Ok(Self { node: Box::new(node), listener: Listener::Unix(listener, path), tls: None })
+    }
+
+    /// Require TLS on every accepted connection
+    pub fn tls(mut self, config: Arc<rustls::ServerConfig>) -> Self {
+        self.tls = Some(TlsAcceptor::from(config));
+        self
+    }
+
+    /// The bound address of a TCP server; `None` for a Unix domain socket
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        match &self.listener {
+            Listener::Tcp(listener) => listener.local_addr().ok(),
+            #[cfg(unix)]
+            Listener::Unix(..) => None,
+        }
+    }
+
+    /// Accept connections until the task is dropped; a failed accept is logged and skipped
+    pub async fn run(self) -> Result<()> {
+        match &self.listener {
+            Listener::Tcp(listener) => info!("Socket server listening on {}", listener.local_addr()?),
+            #[cfg(unix)]
+            Listener::Unix(_, path) => info!("Socket server listening on {}", path.display()),
+        }
+
+        loop {
+            match &self.listener {
+                Listener::Tcp(listener) => match listener.accept().await {
+                    Ok((stream, addr)) => {
+                        if let Err(e) = stream.set_nodelay(true) {
+                            debug!("Failed to disable Nagle's algorithm for {}: {}", addr, e);
+                        }
+                        self.spawn_connection(stream, addr.to_string());
+                    }
+                    Err(e) => Self::accept_failed(e).await,
+                },
+                #[cfg(unix)]
+                Listener::Unix(listener, path) => match listener.accept().await {
+                    Ok((stream, _)) => self.spawn_connection(stream, path.display().to_string()),
+                    Err(e) => Self::accept_failed(e).await,
+                },
+            }
+        }
+    }
+
+    async fn accept_failed(e: std::io::Error) {
+        error!("Socket server failed to accept a connection: {}", e);
+        tokio::time::sleep(ACCEPT_ERROR_DELAY).await;
+    }
+
+    fn spawn_connection<S>(&self, stream: S, peer: String)
+    where S: AsyncRead + AsyncWrite + Unpin + Send + 'static {
+        let node = self.node.cloned();
+        let tls = self.tls.clone();
+        tokio::spawn(async move {
+            info!("Socket server connected to {}", peer);
+            let result = match tls {
+                Some(acceptor) => match tokio::time::timeout(INITIAL_PRESENCE_TIMEOUT, acceptor.accept(stream)).await {
+                    Ok(Ok(stream)) => connection::run(node.as_ref(), stream, &peer, std::future::pending(), |_| {}).await,
+                    Ok(Err(e)) => Err(anyhow::anyhow!("TLS handshake with {} failed: {}", peer, e)),
+                    Err(_) => Err(anyhow::anyhow!("TLS handshake with {} timed out", peer)),
+                },
+                None => connection::run(node.as_ref(), stream, &peer, std::future::pending(), |_| {}).await,
+            };
+            match result {
+                Ok(_) => debug!("Socket connection {} closed", peer),
+                Err(e) => warn!("Socket connection {} closed: {}", peer, e),
+            }
+        });
+    }
+}
+
+#[cfg(unix)]
+impl Drop for SocketServer {
+    fn drop(&mut self) {
+        // The socket file outlives the listener and would make the next bind fail
+        if let Listener::Unix(_, path) = &self.listener {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
@@ -0,0 +1,27 @@
+//! rustls configurations built from PEM
+//!
+//! Any [`rustls::ServerConfig`] or [`rustls::ClientConfig`] can be handed to the server and
+//! client; these cover the common case of a certificate chain and key on disk and a private CA.
+
+use std::sync::Arc;
+use tokio_rustls::rustls::{
+    self,
+    pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer},
+};
+
+/// A server configuration presenting the certificate chain in `cert_pem` with the private key in `key_pem`
+pub fn server_config(cert_pem: &[u8], key_pem: &[u8]) -> anyhow::Result<Arc<rustls::ServerConfig>> {
+    let certs = CertificateDer::pem_slice_iter(cert_pem).collect::<Result<Vec<_>, _>>()?;
+    let key = PrivateKeyDer::from_pem_slice(key_pem)?;
+    let config = rustls::ServerConfig::builder().with_no_client_auth().with_single_cert(certs, key)?;
+    Ok(Arc::new(config))
+}
+
+/// A client configuration trusting only the certificate authorities in `ca_pem`
+pub fn client_config(ca_pem: &[u8]) -> anyhow::Result<Arc<rustls::ClientConfig>> {
+    let mut roots = rustls::RootCertStore::empty();
+    for cert in CertificateDer::pem_slice_iter(ca_pem) {
+        roots.add(cert?)?;
+    }
+    Ok(Arc::new(rustls::ClientConfig::builder().with_root_certificates(roots).with_no_client_auth()))
+}
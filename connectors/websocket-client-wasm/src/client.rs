@@ -2,6 +2,7 @@ use ankurah::policy::PolicyAgent;
 use ankurah::storage::StorageEngine;
 use ankurah_core::connector::NodeComms;
 use ankurah_core::{action_info, notice_info, Node};
+use ankurah_proto as proto;
 
 use crate::connection_state::*;
 use ankurah::signals::{Mut, Read};
@@ -29,11 +30,14 @@ pub struct WebsocketClient {
 }
 
 pub(crate) struct ClientInner {
-    server_url: String,
+    /// Where to connect; replaced when a server redirects us elsewhere
+    server_url: RefCell<String>,
     connection: RefCell<Option<Connection>>,
     state: Mut<ConnectionState>,
     node: Box<dyn NodeComms>,
     reconnect_delay: RefCell<u64>,
+    /// The delay a server's goodbye asked for, used in place of backoff for the next reconnect
+    planned_reconnect: RefCell<Option<u64>>,
     pending_ready_wakers: RefCell<Vec<Waker>>,
 }
 
@@ -49,11 +53,12 @@ impl WebsocketClient {
     {
         notice_info!("Created new websocket client");
         let inner = Arc::new(ClientInner {
-            server_url: server_url.to_string(),
+            server_url: RefCell::new(server_url.to_string()),
             node: Box::new(node),
             connection: RefCell::new(None),
             state: Mut::new(ConnectionState::None),
             reconnect_delay: RefCell::new(0),
+            planned_reconnect: RefCell::new(None),
             pending_ready_wakers: RefCell::new(Vec::new()),
         });
 
@@ -122,8 +127,17 @@ impl ClientInner {
                     *self.connection.borrow_mut() = None;
                 }
 
-                let next_delay = (*self.reconnect_delay.borrow() + 500).min(MAX_RECONNECT_DELAY);
-                *self.reconnect_delay.borrow_mut() = next_delay;
+                let next_delay = match self.planned_reconnect.borrow_mut().take() {
+                    Some(delay) => {
+                        *self.reconnect_delay.borrow_mut() = 0;
+                        delay
+                    }
+                    None => {
+                        let next_delay = (*self.reconnect_delay.borrow() + 500).min(MAX_RECONNECT_DELAY);
+                        *self.reconnect_delay.borrow_mut() = next_delay;
+                        next_delay
+                    }
+                };
                 self.reconnect(next_delay);
             }
         }
@@ -131,16 +145,25 @@ impl ClientInner {
     }
 
     pub fn connect(self: &Arc<Self>) -> anyhow::Result<()> {
+        let server_url = self.server_url.borrow().clone();
         let connection =
-            Connection::new(self.node.cloned(), self.server_url.clone(), Arc::downgrade(self)).map_err(|e| anyhow::anyhow!("{:?}", e))?;
+            Connection::new(self.node.cloned(), server_url.clone(), Arc::downgrade(self)).map_err(|e| anyhow::anyhow!("{:?}", e))?;
 
-        action_info!(self, "connecting to", "{}", &self.server_url);
+        action_info!(self, "connecting to", "{}", &server_url);
         *self.connection.borrow_mut() = Some(connection);
-        self.state.set(ConnectionState::Connecting { url: self.server_url.clone() });
+        self.state.set(ConnectionState::Connecting { url: server_url });
 
         Ok(())
     }
 
+    /// Follow a server's goodbye: the next reconnect goes where and when it asked, not after backoff
+    pub(crate) fn plan_reconnect(&self, goodbye: proto::Goodbye) {
+        if let Some(redirect) = goodbye.redirect {
+            *self.server_url.borrow_mut() = redirect;
+        }
+        *self.planned_reconnect.borrow_mut() = Some(goodbye.reconnect_after_ms);
+    }
+
     pub fn reconnect(self: &Arc<Self>, delay: u64) {
         info!("reconnect: removing old connection with delay {}ms", delay);
 
@@ -23,6 +23,8 @@ pub struct ConnectionInner {
     ws: Arc<WebSocket>,
     url: String,
     state: RwLock<ConnectionState>,
+    // How frames from the server are encoded: the handshake encoding until its Presence arrives
+    wire: RwLock<proto::Wire>,
     node: Box<dyn NodeComms>,
     client: Weak<ClientInner>,
     _callbacks: Mutex<Option<Vec<Box<dyn std::any::Any>>>>,
@@ -49,6 +51,7 @@ impl Connection {
             ws: Arc::new(ws),
             url,
             state,
+            wire: RwLock::new(proto::Wire::default()),
             node,
             client,
             _callbacks: Mutex::new(None),
@@ -105,6 +108,9 @@ impl Connection {
             node_id: self.node.id(),
             durable: self.node.durable(),
             system_root: self.node.system_root(),
+            wire: proto::WireSupport::all(),
+            features: proto::protocol_features(),
+            min_protocol_version: proto::MIN_PROTOCOL_VERSION,
             protocol_version: proto::PROTOCOL_VERSION,
         };
         if let Err(e) = self.send_message(proto::Message::Presence(presence)) {
@@ -139,20 +145,22 @@ impl Connection {
         let array = Uint8Array::new(&array_buffer);
         let data = array.to_vec();
 
-        if let Ok(message) = bincode::deserialize::<proto::Message>(&data) {
+        let wire = *self.wire.read().unwrap();
+        if let Ok(message) = wire.decode(&data) {
             match message {
-                proto::Message::Presence(server_presence) => {
+                proto::Decoded::Presence(server_presence) => {
                     // Pre-check the version so the server learns why we are
                     // leaving; register_peer re-enforces this for every transport.
-                    if !proto::protocol_compatible(server_presence.protocol_version) {
-                        let rejection =
-                            proto::PresenceRejection { expected: proto::PROTOCOL_VERSION, received: server_presence.protocol_version };
-                        error!("Refusing server {}: {}", self.url, rejection);
-                        let _ = self.send_message(proto::Message::PresenceRejected(rejection.clone()));
-                        self.disconnect();
-                        self.set_state(ConnectionState::Error { message: rejection.to_string() });
-                        return;
-                    }
+                    let wire = match proto::Wire::negotiate(&server_presence) {
+                        Ok(wire) => wire,
+                        Err(rejection) => {
+                            error!("Refusing server {}: {}", self.url, rejection);
+                            let _ = self.send_message(proto::Message::PresenceRejected(rejection.clone()));
+                            self.disconnect();
+                            self.set_state(ConnectionState::Error { message: rejection.to_string() });
+                            return;
+                        }
+                    };
                     let state = { self.state.read().unwrap().clone() };
                     match state {
                         ConnectionState::Connected { .. } => warn!("Received duplicate server presence, ignoring"),
@@ -165,6 +173,7 @@ impl Connection {
                                 Box::new(WebSocketPeerSender {
                                     recipient_node_id: server_presence.node_id,
                                     ws: SendWrapper::new(self.ws.clone()),
+                                    wire,
                                 }),
                             ) {
                                 error!("Refusing server {}: {}", self.url, rejection);
@@ -172,6 +181,7 @@ impl Connection {
                                 self.set_state(ConnectionState::Error { message: rejection.to_string() });
                                 return;
                             }
+                            *self.wire.write().unwrap() = wire;
                             if !self
                                 .set_state(ConnectionState::Connected { url: self.url.clone(), server_presence: server_presence.clone() })
                             {
@@ -199,20 +209,24 @@ impl Connection {
                         }
                     }
                 }
-                proto::Message::PresenceRejected(rejection) => {
+                proto::Decoded::PresenceRejected(rejection) => {
                     error!("Server {} refused connection: {}", self.url, rejection);
                     self.disconnect();
                     self.set_state(ConnectionState::Error { message: rejection.to_string() });
                 }
-                proto::Message::PeerMessage(msg) => {
-                    let node = self.node.cloned();
-                    // TODO: determine the performance implications of spawning a new task for each message
-                    // versus using a channel to send messages to the node.
-                    wasm_bindgen_futures::spawn_local(async move {
-                        if let Err(e) = node.handle_message(msg).await {
-                            info!("Error handling message: {:?}", e);
-                        }
-                    });
+                proto::Decoded::PeerMessage(msg) => self.handle_peer_message(msg),
+                proto::Decoded::Batch(msgs) => {
+                    for msg in msgs {
+                        self.handle_peer_message(msg);
+                    }
+                }
+                proto::Decoded::Goodbye(goodbye) => {
+                    info!("Server {} said goodbye: {}", self.url, goodbye);
+                    if let Some(client) = self.client.upgrade() {
+                        client.plan_reconnect(goodbye);
+                    }
+                    self.disconnect();
+                    self.set_state(ConnectionState::Closed);
                 }
             }
         } else {
@@ -234,6 +248,17 @@ impl Connection {
         }
     }
 
+    fn handle_peer_message(&self, msg: proto::NodeMessage) {
+        let node = self.node.cloned();
+        // TODO: determine the performance implications of spawning a new task for each message
+        // versus using a channel to send messages to the node.
+        wasm_bindgen_futures::spawn_local(async move {
+            if let Err(e) = node.handle_message(msg).await {
+                info!("Error handling message: {:?}", e);
+            }
+        });
+    }
+
     fn send_message(&self, message: proto::Message) -> Result<(), JsValue> {
         let data = bincode::serialize(&message).map_err(|e| {
             info!("Failed to serialize client message: {:?}", e);
@@ -277,13 +302,15 @@ impl PartialEq for Connection {
 struct WebSocketPeerSender {
     recipient_node_id: proto::EntityId,
     ws: SendWrapper<Arc<WebSocket>>,
+    // Each message is sent as it arrives, so only compression applies; there is no queue to batch
+    wire: proto::Wire,
 }
 
 #[async_trait]
 impl PeerSender for WebSocketPeerSender {
     fn send_message(&self, message: proto::NodeMessage) -> Result<(), ankurah_core::connector::SendError> {
         let message = proto::Message::PeerMessage(message);
-        let data = bincode::serialize(&message).map_err(|e| {
+        let data = self.wire.encode(&message).map_err(|e| {
             info!("Failed to serialize client message: {:?}", e);
             ankurah_core::connector::SendError::Other(anyhow!("Serialization error"))
         })?;
@@ -1,5 +1,9 @@
 mod client;
 mod connection;
 mod connection_state;
+#[cfg(feature = "webtransport")]
+mod webtransport;
 
 pub use client::WebsocketClient;
+#[cfg(feature = "webtransport")]
+pub use webtransport::WebTransportClient;
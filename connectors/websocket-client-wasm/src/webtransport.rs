@@ -0,0 +1,481 @@
+//! A client for `ankurah-webtransport-server`, for browsers that support WebTransport.
+//!
+//! On a websocket a large response holds up every message queued behind it. Here the client
+//! sends its Presence and every message other than a request on the session's control stream,
+//! opens a bidirectional stream per request and reads the response back from it, and reads
+//! everything else the server sends from the unidirectional streams the server opens. See the
+//! server crate for the full mapping.
+
+use ankurah::policy::PolicyAgent;
+use ankurah::signals::{Mut, Read};
+use ankurah::storage::StorageEngine;
+use ankurah_core::connector::{NodeComms, PeerSender, SendError};
+use ankurah_core::{action_info, notice_info, Node};
+use ankurah_proto as proto;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use gloo_timers::future::sleep;
+use js_sys::{Reflect, Uint8Array};
+use send_wrapper::SendWrapper;
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+use tracing::{error, info, warn};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::{
+    ReadableStream, ReadableStreamDefaultReader, WebTransport, WebTransportBidirectionalStream, WebTransportHash, WebTransportOptions,
+    WebTransportReceiveStream, WritableStreamDefaultWriter,
+};
+
+use crate::connection_state::*;
+
+/// The path the server accepts sessions at
+const SESSION_PATH: &str = "/ankurah";
+/// Largest frame the client accepts; a longer length prefix ends the stream
+const MAX_FRAME_LENGTH: usize = 64 * 1024 * 1024;
+const MAX_RECONNECT_DELAY: u64 = 10000;
+
+#[derive(Clone)]
+#[wasm_bindgen]
+pub struct WebTransportClient {
+    inner: Rc<ClientInner>,
+}
+
+struct ClientInner {
+    /// Where to connect; replaced when a server redirects us elsewhere
+    server_url: RefCell<String>,
+    /// SHA-256 hashes of self-signed server certificates to accept
+    server_certificate_hashes: Vec<Vec<u8>>,
+    state: Mut<ConnectionState>,
+    node: Box<dyn NodeComms>,
+    reconnect_delay: Cell<u64>,
+    /// The delay a server's goodbye asked for, used in place of backoff for the next reconnect
+    planned_reconnect: Cell<Option<u64>>,
+    pending_ready_wakers: RefCell<Vec<Waker>>,
+}
+
+impl WebTransportClient {
+    pub fn new<SE, PA>(node: Node<SE, PA>, server_url: &str) -> anyhow::Result<WebTransportClient>
+    where
+        SE: StorageEngine + Send + Sync + 'static,
+        PA: PolicyAgent + Send + Sync + 'static,
+    {
+        Self::with_server_certificate_hashes(node, server_url, Vec::new())
+    }
+
+    /// Connect to a server presenting a self-signed certificate with one of these SHA-256
+    /// hashes. Browsers only accept such certificates when they are valid for at most two weeks.
+    pub fn with_server_certificate_hashes<SE, PA>(
+        node: Node<SE, PA>,
+        server_url: &str,
+        server_certificate_hashes: Vec<Vec<u8>>,
+    ) -> anyhow::Result<WebTransportClient>
+    where
+        SE: StorageEngine + Send + Sync + 'static,
+        PA: PolicyAgent + Send + Sync + 'static,
+    {
+        notice_info!("Created new WebTransport client");
+        let inner = Rc::new(ClientInner {
+            server_url: RefCell::new(server_url.to_string()),
+            server_certificate_hashes,
+            state: Mut::new(ConnectionState::None),
+            node: Box::new(node),
+            reconnect_delay: Cell::new(0),
+            planned_reconnect: Cell::new(None),
+            pending_ready_wakers: RefCell::new(Vec::new()),
+        });
+
+        spawn_local(inner.clone().run());
+
+        Ok(WebTransportClient { inner })
+    }
+
+    pub fn connection_state(&self) -> Read<ConnectionState> { self.inner.state.read() }
+}
+
+#[wasm_bindgen]
+impl WebTransportClient {
+    // resolves when we have a connected state
+    pub async fn ready(&self) -> Result<(), String> {
+        ReadyFuture { client: self.inner.clone() }.await;
+        Ok(())
+    }
+
+    #[wasm_bindgen(getter, js_name = "connection_state")]
+    pub fn js_connection_state(&self) -> ConnectionStateEnumSignal {
+        let sig = Box::new(self.inner.state.read().map(|state| state.into()));
+
+        ConnectionStateEnumSignal { sig, handle: Box::new(()) }
+    }
+}
+
+impl fmt::Display for ClientInner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "WebTransportClient") }
+}
+
+impl ClientInner {
+    /// Connect, and reconnect whenever the session ends
+    async fn run(self: Rc<Self>) {
+        loop {
+            let server_url = self.server_url.borrow().clone();
+            let url = if server_url.starts_with("https://") {
+                format!("{}{}", server_url, SESSION_PATH)
+            } else {
+                format!("https://{}{}", server_url, SESSION_PATH)
+            };
+
+            action_info!(self, "connecting to", "{}", &url);
+            self.set_state(ConnectionState::Connecting { url: url.clone() });
+            let state = match self.connect(&url).await {
+                Ok(()) => ConnectionState::Closed,
+                Err(message) => {
+                    error!("WebTransport session with {} failed: {}", url, message);
+                    ConnectionState::Error { message }
+                }
+            };
+            self.set_state(state);
+
+            let delay = match self.planned_reconnect.take() {
+                Some(delay) => {
+                    self.reconnect_delay.set(0);
+                    delay
+                }
+                None => {
+                    let delay = (self.reconnect_delay.get() + 500).min(MAX_RECONNECT_DELAY);
+                    self.reconnect_delay.set(delay);
+                    delay
+                }
+            };
+            info!("reconnect: sleeping for {}ms", delay);
+            sleep(Duration::from_millis(delay)).await;
+        }
+    }
+
+    fn set_state(&self, new_state: ConnectionState) {
+        action_info!(self, "state changed", "{}", &new_state);
+        self.state.set(new_state.clone());
+        if let ConnectionState::Connected { .. } = new_state {
+            self.reconnect_delay.set(0);
+            for waker in std::mem::take(&mut *self.pending_ready_wakers.borrow_mut()) {
+                waker.wake();
+            }
+        }
+    }
+
+    fn open_transport(&self, url: &str) -> Result<WebTransport, JsValue> {
+        if self.server_certificate_hashes.is_empty() {
+            return WebTransport::new(url);
+        }
+        let hashes = js_sys::Array::new();
+        for hash in &self.server_certificate_hashes {
+            let entry = WebTransportHash::new();
+            entry.set_algorithm("sha-256");
+            entry.set_value(&Uint8Array::from(hash.as_slice()));
+            hashes.push(&entry);
+        }
+        let options = WebTransportOptions::new();
+        options.set_server_certificate_hashes(&hashes);
+        WebTransport::new_with_options(url, &options)
+    }
+
+    /// Run one session from opening the transport until either end closes it
+    async fn connect(self: &Rc<Self>, url: &str) -> Result<(), String> {
+        let transport = self.open_transport(url).map_err(js_error)?;
+        JsFuture::from(transport.ready()).await.map_err(js_error)?;
+        let control: WebTransportBidirectionalStream =
+            JsFuture::from(transport.create_bidirectional_stream()).await.map_err(js_error)?.unchecked_into();
+
+        let session = Rc::new(Session {
+            writer: control.writable().get_writer().map_err(js_error)?,
+            transport,
+            url: url.to_string(),
+            node: self.node.cloned(),
+            wire: Cell::new(proto::Wire::default()),
+        });
+        let mut frames = FrameReader::new(&control.readable());
+
+        let mut registered = None;
+        let result = self.run_control(&session, &mut frames, &mut registered).await;
+        if let Some(server_id) = registered {
+            self.node.deregister_peer(server_id);
+        }
+        session.transport.close();
+        result
+    }
+
+    async fn run_control(
+        self: &Rc<Self>,
+        session: &Rc<Session>,
+        frames: &mut FrameReader,
+        registered: &mut Option<proto::EntityId>,
+    ) -> Result<(), String> {
+        let presence = proto::Presence {
+            node_id: self.node.id(),
+            durable: self.node.durable(),
+            system_root: self.node.system_root(),
+            wire: proto::WireSupport::all(),
+            features: proto::protocol_features(),
+            min_protocol_version: proto::MIN_PROTOCOL_VERSION,
+            protocol_version: proto::PROTOCOL_VERSION,
+        };
+        let data = bincode::serialize(&proto::Message::Presence(presence)).map_err(|e| e.to_string())?;
+        session.write_frame(&data);
+
+        while let Some(data) = frames.next().await.map_err(js_error)? {
+            let message = match session.wire.get().decode(&data) {
+                Ok(message) => message,
+                Err(_) if registered.is_none() => {
+                    // A handshake we cannot read will never establish; close instead
+                    // of idling on a dead session.
+                    return Err(if proto::is_version0_presence(&data) {
+                        format!("Server {} speaks a pre-versioning (0.9.x or older) protocol; refusing", session.url)
+                    } else {
+                        format!("Failed to deserialize handshake message from {}; closing", session.url)
+                    });
+                }
+                Err(e) => {
+                    warn!("Failed to deserialize message from server: {}", e);
+                    continue;
+                }
+            };
+
+            match message {
+                proto::Decoded::Presence(server_presence) => {
+                    if registered.is_some() {
+                        warn!("Received duplicate server presence, ignoring");
+                        continue;
+                    }
+                    // Pre-check the version so the server learns why we are
+                    // leaving; register_peer re-enforces this for every transport.
+                    let wire = match proto::Wire::negotiate(&server_presence) {
+                        Ok(wire) => wire,
+                        Err(rejection) => {
+                            if let Ok(data) = bincode::serialize(&proto::Message::PresenceRejected(rejection.clone())) {
+                                session.write_frame(&data);
+                            }
+                            return Err(rejection.to_string());
+                        }
+                    };
+                    session.wire.set(wire);
+                    let sender =
+                        WebTransportPeerSender { recipient_node_id: server_presence.node_id, session: SendWrapper::new(session.clone()) };
+                    // Register BEFORE publishing Connected: observers of the state must
+                    // never see a connection whose peer is not registered.
+                    self.node.register_peer(server_presence.clone(), Box::new(sender)).map_err(|rejection| rejection.to_string())?;
+                    *registered = Some(server_presence.node_id);
+                    spawn_local(session.clone().read_incoming_streams());
+                    self.set_state(ConnectionState::Connected { url: session.url.clone(), server_presence });
+                }
+                proto::Decoded::PresenceRejected(rejection) => {
+                    return Err(format!("Server {} refused connection: {}", session.url, rejection));
+                }
+                proto::Decoded::PeerMessage(_) | proto::Decoded::Batch(_) if registered.is_none() => {
+                    return Err(format!("Server {} sent application traffic before presence negotiation completed", session.url));
+                }
+                proto::Decoded::PeerMessage(msg) => session.handle_peer_message(msg),
+                proto::Decoded::Batch(msgs) => {
+                    for msg in msgs {
+                        session.handle_peer_message(msg);
+                    }
+                }
+                proto::Decoded::Goodbye(goodbye) => {
+                    info!("Server {} said goodbye: {}", session.url, goodbye);
+                    if let Some(redirect) = goodbye.redirect {
+                        *self.server_url.borrow_mut() = redirect;
+                    }
+                    self.planned_reconnect.set(Some(goodbye.reconnect_after_ms));
+                    return Ok(());
+                }
+            }
+        }
+        info!("Server {} closed the control stream", session.url);
+        Ok(())
+    }
+}
+
+/// One WebTransport session and the control stream's writer
+struct Session {
+    transport: WebTransport,
+    writer: WritableStreamDefaultWriter,
+    url: String,
+    node: Box<dyn NodeComms>,
+    /// How frames are encoded: the handshake encoding until the server's Presence arrives
+    wire: Cell<proto::Wire>,
+}
+
+impl Session {
+    /// Queue a frame on the control stream; the stream writes queued frames in order
+    fn write_frame(&self, data: &[u8]) { write_frame(&self.writer, data) }
+
+    fn handle_peer_message(&self, msg: proto::NodeMessage) {
+        let node = self.node.cloned();
+        spawn_local(async move {
+            if let Err(e) = node.handle_message(msg).await {
+                info!("Error handling message: {:?}", e);
+            }
+        });
+    }
+
+    /// Hand the node everything the server sends on the unidirectional streams it opens
+    async fn read_incoming_streams(self: Rc<Self>) {
+        let streams: ReadableStreamDefaultReader = self.transport.incoming_unidirectional_streams().get_reader().unchecked_into();
+        loop {
+            let stream = match read_chunk(&streams).await {
+                Ok(Some(stream)) => stream.unchecked_into::<WebTransportReceiveStream>(),
+                Ok(None) => break,
+                Err(e) => {
+                    info!("Stopped accepting streams from {}: {}", self.url, js_error(e));
+                    break;
+                }
+            };
+            spawn_local(self.clone().read_stream(FrameReader::new(&stream)));
+        }
+    }
+
+    async fn read_stream(self: Rc<Self>, mut frames: FrameReader) {
+        loop {
+            match frames.next().await {
+                Ok(Some(data)) => match self.wire.get().decode(&data) {
+                    Ok(proto::Decoded::PeerMessage(msg)) => self.handle_peer_message(msg),
+                    Ok(proto::Decoded::Batch(msgs)) => {
+                        for msg in msgs {
+                            self.handle_peer_message(msg);
+                        }
+                    }
+                    Ok(_) => warn!("Received a session message outside the control stream from {}", self.url),
+                    Err(e) => warn!("Failed to deserialize message from server: {}", e),
+                },
+                Ok(None) => break,
+                Err(e) => {
+                    info!("Stream from {} failed: {}", self.url, js_error(e));
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Send a request on a bidirectional stream of its own and hand the node the response read back from it
+    async fn request(self: Rc<Self>, data: Vec<u8>) {
+        let result = async {
+            let stream: WebTransportBidirectionalStream =
+                JsFuture::from(self.transport.create_bidirectional_stream()).await?.unchecked_into();
+            let writer = stream.writable().get_writer()?;
+            write_frame(&writer, &data);
+            JsFuture::from(writer.close()).await?;
+            Ok::<_, JsValue>(FrameReader::new(&stream.readable()))
+        };
+        match result.await {
+            Ok(frames) => self.read_stream(frames).await,
+            Err(e) => warn!("Failed to send request to {}: {}", self.url, js_error(e)),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct WebTransportPeerSender {
+    recipient_node_id: proto::EntityId,
+    session: SendWrapper<Rc<Session>>,
+}
+
+#[async_trait]
+impl PeerSender for WebTransportPeerSender {
+    fn send_message(&self, message: proto::NodeMessage) -> Result<(), SendError> {
+        let is_request = matches!(message, proto::NodeMessage::Request { .. });
+        let data = self.session.wire.get().encode(&proto::Message::PeerMessage(message)).map_err(|e| {
+            info!("Failed to serialize client message: {:?}", e);
+            SendError::Other(anyhow!("Serialization error"))
+        })?;
+
+        if is_request {
+            spawn_local((*self.session).clone().request(data));
+        } else {
+            self.session.write_frame(&data);
+        }
+        Ok(())
+    }
+
+    fn recipient_node_id(&self) -> proto::EntityId { self.recipient_node_id }
+
+    fn cloned(&self) -> Box<dyn PeerSender> { Box::new(self.clone()) }
+}
+
+/// Queue `data` behind a four byte big-endian length prefix
+fn write_frame(writer: &WritableStreamDefaultWriter, data: &[u8]) {
+    let mut frame = Vec::with_capacity(4 + data.len());
+    frame.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    frame.extend_from_slice(data);
+    let written = writer.write_with_chunk(&Uint8Array::from(frame.as_slice()));
+    spawn_local(async move {
+        if let Err(e) = JsFuture::from(written).await {
+            warn!("Failed to write to WebTransport stream: {}", js_error(e));
+        }
+    });
+}
+
+/// The next chunk of a readable stream, or `None` once it is done
+async fn read_chunk(reader: &ReadableStreamDefaultReader) -> Result<Option<JsValue>, JsValue> {
+    let result = JsFuture::from(reader.read()).await?;
+    if Reflect::get(&result, &"done".into())?.as_bool().unwrap_or(false) {
+        return Ok(None);
+    }
+    Ok(Some(Reflect::get(&result, &"value".into())?))
+}
+
+/// Reads length prefixed frames from a stream of byte chunks
+struct FrameReader {
+    reader: ReadableStreamDefaultReader,
+    buffer: Vec<u8>,
+}
+
+impl FrameReader {
+    fn new(stream: &ReadableStream) -> Self { Self { reader: stream.get_reader().unchecked_into(), buffer: Vec::new() } }
+
+    async fn next(&mut self) -> Result<Option<Vec<u8>>, JsValue> {
+        loop {
+            if self.buffer.len() >= 4 {
+                let len = u32::from_be_bytes([self.buffer[0], self.buffer[1], self.buffer[2], self.buffer[3]]) as usize;
+                if len > MAX_FRAME_LENGTH {
+                    return Err(JsValue::from_str(&format!("frame of {} bytes exceeds the limit", len)));
+                }
+                if self.buffer.len() >= 4 + len {
+                    let frame = self.buffer[4..4 + len].to_vec();
+                    self.buffer.drain(..4 + len);
+                    return Ok(Some(frame));
+                }
+            }
+            match read_chunk(&self.reader).await? {
+                Some(chunk) => self.buffer.extend(chunk.unchecked_into::<Uint8Array>().to_vec()),
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+fn js_error(e: JsValue) -> String {
+    match e.dyn_ref::<js_sys::Error>() {
+        Some(error) => error.message().into(),
+        None => e.as_string().unwrap_or_else(|| format!("{:?}", e)),
+    }
+}
+
+struct ReadyFuture {
+    client: Rc<ClientInner>,
+}
+
+impl Future for ReadyFuture {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let ConnectionState::Connected { .. } = self.client.state.value() {
+            Poll::Ready(())
+        } else {
+            self.client.pending_ready_wakers.borrow_mut().push(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
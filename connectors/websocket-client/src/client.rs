@@ -103,7 +103,8 @@ where
     PA: PolicyAgent + Send + Sync + 'static,
 {
     node: Node<SE, PA>,
-    server_url: String,
+    /// Where to connect; replaced when a server redirects us elsewhere
+    server_url: std::sync::RwLock<String>,
     config: Option<WebSocketConfig>,
     disable_nagle: bool,
     connector: Option<Connector>,
@@ -150,7 +151,7 @@ where
 
         let inner = Arc::new(Inner {
             node,
-            server_url: ws_url,
+            server_url: std::sync::RwLock::new(ws_url),
             config,
             disable_nagle,
             connector,
@@ -221,7 +222,7 @@ where
     /// Main connection loop with automatic reconnection
     async fn run_connection_loop(inner: Arc<Inner<SE, PA>>) {
         let mut backoff = INITIAL_BACKOFF;
-        info!("Starting websocket connection loop to {}", inner.server_url);
+        info!("Starting websocket connection loop to {}", inner.server_url());
 
         loop {
             select! {
@@ -231,8 +232,25 @@ where
                 }
                 result = Self::connect_once(&inner) => {
                     match result {
-                        Ok(()) => {
-                            info!("Connection to {} completed normally", inner.server_url);
+                        Ok(Some(goodbye)) => {
+                            // A planned disconnect: come back as soon as the server asks
+                            // rather than backing off
+                            info!("Server {} said goodbye: {}", inner.server_url(), goodbye);
+                            backoff = INITIAL_BACKOFF;
+                            if let Some(redirect) = &goodbye.redirect {
+                                *inner.server_url.write().unwrap_or_else(|e| e.into_inner()) = Self::normalize_url(redirect);
+                            }
+                            if inner.shutdown_requested.load(Ordering::Acquire) {
+                                break;
+                            }
+                            inner.connection_state.set(ConnectionState::Disconnected);
+                            select! {
+                                _ = inner.shutdown.notified() => break,
+                                _ = sleep(Duration::from_millis(goodbye.reconnect_after_ms)) => {}
+                            }
+                        }
+                        Ok(None) => {
+                            info!("Connection to {} completed normally", inner.server_url());
                             backoff = INITIAL_BACKOFF;
                             if inner.shutdown_requested.load(Ordering::Acquire) {
                                 info!("Shutdown requested, stopping reconnection attempts");
@@ -240,7 +258,7 @@ where
                             }
                         }
                         Err(e) => {
-                            error!("Connection to {} failed: {}", inner.server_url, e);
+                            error!("Connection to {} failed: {}", inner.server_url(), e);
                             inner.connection_state.set(ConnectionState::Error(ConnectionError::General(e.to_string())));
                             inner.connected.store(false, Ordering::Release);
 
@@ -260,14 +278,14 @@ where
         inner.connected.store(false, Ordering::Release);
     }
 
-    /// Attempt a single connection
-    async fn connect_once(inner: &Arc<Inner<SE, PA>>) -> Result<()> {
-        info!("Attempting to connect to {}", inner.server_url);
-        inner.connection_state.set(ConnectionState::Connecting { url: inner.server_url.clone() });
+    /// Attempt a single connection, returning the server's goodbye if it ended the connection with one
+    async fn connect_once(inner: &Arc<Inner<SE, PA>>) -> Result<Option<proto::Goodbye>> {
+        info!("Attempting to connect to {}", inner.server_url());
+        inner.connection_state.set(ConnectionState::Connecting { url: inner.server_url() });
 
-        let request = inner.server_url.as_str().into_client_request()?;
+        let request = inner.server_url().as_str().into_client_request()?;
         let (ws_stream, _) = connect_async_tls_with_config(request, inner.config, inner.disable_nagle, inner.connector.clone()).await?;
-        info!("WebSocket handshake completed with {}", inner.server_url);
+        info!("WebSocket handshake completed with {}", inner.server_url());
 
         let (mut sink, mut stream) = ws_stream.split();
         debug!("Starting connection handling");
@@ -277,6 +295,9 @@ where
             node_id: inner.node.id,
             durable: inner.node.durable,
             system_root: inner.node.system.root(),
+            wire: proto::WireSupport::all(),
+            features: proto::protocol_features(),
+            min_protocol_version: proto::MIN_PROTOCOL_VERSION,
             protocol_version: proto::PROTOCOL_VERSION,
         });
 
@@ -285,6 +306,8 @@ where
 
         let mut peer_sender: Option<WebsocketPeerSender> = None;
         let mut outgoing_rx: Option<tokio::sync::mpsc::UnboundedReceiver<proto::NodeMessage>> = None;
+        let mut wire = proto::Wire::default();
+        let mut goodbye = None;
 
         loop {
             select! {
@@ -298,14 +321,19 @@ where
                         None => std::future::pending().await,
                     }
                 } => {
-                    if Self::handle_outgoing_message(&mut sink, msg).await.is_err() {
+                    let server = peer_sender.as_ref().map(|sender| sender.recipient_node_id());
+                    if Self::handle_outgoing_message(&mut sink, &wire, server, &mut outgoing_rx, msg).await.is_err() {
                         break;
                     }
                 }
                 msg = stream.next() => {
-                    match Self::handle_incoming_message(inner, msg, &mut peer_sender, &mut outgoing_rx, &mut sink).await? {
+                    match Self::handle_incoming_message(inner, msg, &mut peer_sender, &mut outgoing_rx, &mut wire, &mut sink).await? {
                         MessageResult::Continue => continue,
                         MessageResult::Break => break,
+                        MessageResult::Goodbye(message) => {
+                            goodbye = Some(message);
+                            break;
+                        }
                     }
                 }
             }
@@ -317,7 +345,7 @@ where
             inner.node.deregister_peer(sender.recipient_node_id());
             debug!("Deregistered peer {}", sender.recipient_node_id());
         }
-        Ok(())
+        Ok(goodbye)
     }
 
     async fn handle_outgoing_message(
@@ -325,13 +353,30 @@ where
             tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>,
             Message,
         >,
+        wire: &proto::Wire,
+        server: Option<proto::EntityId>,
+        outgoing_rx: &mut Option<tokio::sync::mpsc::UnboundedReceiver<proto::NodeMessage>>,
         msg: Option<proto::NodeMessage>,
     ) -> Result<()> {
         if let Some(node_message) = msg {
-            let proto_message = proto::Message::PeerMessage(node_message);
-            match bincode::serialize(&proto_message) {
-                Ok(data) => {
-                    sink.send(Message::Binary(data.into())).await?;
+            // Batch whatever else is already queued behind this message
+            let mut batch = vec![node_message];
+            if let Some(rx) = outgoing_rx {
+                while batch.len() < proto::wire::MAX_BATCH_LEN {
+                    match rx.try_recv() {
+                        Ok(node_message) => batch.push(node_message),
+                        Err(_) => break,
+                    }
+                }
+            }
+            match wire.encode_all(batch) {
+                Ok(frames) => {
+                    for frame in frames {
+                        if let Some(server) = server {
+                            ankurah_core::metrics::bytes_sent(server, frame.len());
+                        }
+                        sink.send(Message::Binary(frame.into())).await?;
+                    }
                 }
                 Err(e) => error!("Failed to serialize outgoing message: {}", e),
             }
@@ -344,50 +389,70 @@ where
         msg: Option<Result<Message, tokio_tungstenite::tungstenite::Error>>,
         peer_sender: &mut Option<WebsocketPeerSender>,
         outgoing_rx: &mut Option<tokio::sync::mpsc::UnboundedReceiver<proto::NodeMessage>>,
+        wire: &mut proto::Wire,
         sink: &mut futures_util::stream::SplitSink<
             tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>,
             Message,
         >,
     ) -> Result<MessageResult> {
+        if let (Some(Ok(Message::Binary(data))), Some(sender)) = (&msg, peer_sender.as_ref()) {
+            ankurah_core::metrics::bytes_received(sender.recipient_node_id(), data.len());
+        }
         match msg {
-            Some(Ok(Message::Binary(data))) => match bincode::deserialize(&data) {
-                Ok(proto::Message::Presence(server_presence)) => {
-                    match Self::handle_server_presence(inner, server_presence, peer_sender, outgoing_rx).await {
-                        Ok(()) => Ok(MessageResult::Continue),
+            Some(Ok(Message::Binary(data))) => match wire.decode(&data) {
+                Ok(proto::Decoded::Presence(server_presence)) => {
+                    let negotiated = match proto::Wire::negotiate(&server_presence) {
+                        Ok(server_wire) => {
+                            Self::handle_server_presence(inner, server_presence, peer_sender, outgoing_rx).await.map(|()| server_wire)
+                        }
+                        Err(rejection) => Err(rejection),
+                    };
+                    match negotiated {
+                        Ok(server_wire) => {
+                            *wire = server_wire;
+                            Ok(MessageResult::Continue)
+                        }
                         Err(rejection) => {
                             // register_peer is the enforcement point. The connector's
                             // only extra job is to explain the refusal while its raw
                             // transport sink is still available.
                             let reason = rejection.to_string();
-                            error!("Refusing server {}: {}", inner.server_url, rejection);
+                            error!("Refusing server {}: {}", inner.server_url(), rejection);
                             if let Ok(bytes) = bincode::serialize(&proto::Message::PresenceRejected(rejection)) {
                                 let _ = sink.send(Message::Binary(bytes.into())).await;
                             }
-                            Err(anyhow!("server {} refused connection: {}", inner.server_url, reason))
+                            Err(anyhow!("server {} refused connection: {}", inner.server_url(), reason))
                         }
                     }
                 }
-                Ok(proto::Message::PresenceRejected(rejection)) => {
-                    Err(anyhow!("server {} refused connection: {}", inner.server_url, rejection))
+                Ok(proto::Decoded::PresenceRejected(rejection)) => {
+                    Err(anyhow!("server {} refused connection: {}", inner.server_url(), rejection))
                 }
-                Ok(proto::Message::PeerMessage(node_msg)) => {
-                    if peer_sender.is_none() {
-                        // Application traffic before a compatible Presence:
-                        // negotiation has not admitted this server, so nothing
-                        // it says may reach the node.
-                        return Err(anyhow!("server {} sent application traffic before presence negotiation completed", inner.server_url));
-                    }
+                Ok(proto::Decoded::PeerMessage(_) | proto::Decoded::Batch(_)) if peer_sender.is_none() => {
+                    // Application traffic before a compatible Presence:
+                    // negotiation has not admitted this server, so nothing
+                    // it says may reach the node.
+                    Err(anyhow!("server {} sent application traffic before presence negotiation completed", inner.server_url()))
+                }
+                Ok(proto::Decoded::PeerMessage(node_msg)) => {
                     Self::handle_peer_message(inner, node_msg).await;
                     Ok(MessageResult::Continue)
                 }
+                Ok(proto::Decoded::Batch(node_msgs)) => {
+                    for node_msg in node_msgs {
+                        Self::handle_peer_message(inner, node_msg).await;
+                    }
+                    Ok(MessageResult::Continue)
+                }
+                Ok(proto::Decoded::Goodbye(goodbye)) => Ok(MessageResult::Goodbye(goodbye)),
                 Err(e) => {
                     if peer_sender.is_none() {
                         // A handshake we cannot read will never establish; close
                         // instead of idling on a dead connection.
                         if proto::is_version0_presence(&data) {
-                            return Err(anyhow!("server {} speaks a pre-versioning (0.9.x or older) protocol", inner.server_url));
+                            return Err(anyhow!("server {} speaks a pre-versioning (0.9.x or older) protocol", inner.server_url()));
                         } else {
-                            return Err(anyhow!("failed to deserialize handshake message from {}: {}", inner.server_url, e));
+                            return Err(anyhow!("failed to deserialize handshake message from {}: {}", inner.server_url(), e));
                         }
                     }
                     warn!("Failed to deserialize message: {}", e);
@@ -438,14 +503,15 @@ where
         info!("Received server presence: {}", server_presence.node_id);
 
         let (sender, rx) = WebsocketPeerSender::new(server_presence.node_id);
-        inner.node.register_peer(server_presence.clone(), Box::new(sender.clone()))?;
+        let protocol_version = inner.node.register_peer(server_presence.clone(), Box::new(sender.clone()))?;
+        debug!("Speaking protocol v{} with server {}", protocol_version, server_presence.node_id);
 
         *outgoing_rx = Some(rx);
         *peer_sender = Some(sender);
 
-        inner.connection_state.set(ConnectionState::Connected { url: inner.server_url.to_string(), server_presence });
+        inner.connection_state.set(ConnectionState::Connected { url: inner.server_url(), server_presence });
         inner.connected.store(true, Ordering::Release);
-        info!("Successfully connected to server {}", inner.server_url);
+        info!("Successfully connected to server {}", inner.server_url());
         Ok(())
     }
 
@@ -464,6 +530,15 @@ where
 enum MessageResult {
     Continue,
     Break,
+    Goodbye(proto::Goodbye),
+}
+
+impl<SE, PA> Inner<SE, PA>
+where
+    SE: StorageEngine + Send + Sync + 'static,
+    PA: PolicyAgent + Send + Sync + 'static,
+{
+    fn server_url(&self) -> String { self.server_url.read().unwrap_or_else(|e| e.into_inner()).clone() }
 }
 
 impl<SE, PA> Drop for WebsocketClient<SE, PA>
@@ -0,0 +1,44 @@
+use std::sync::Arc;
+
+use ankurah_core::{node::Node, policy::PolicyAgent, storage::StorageEngine};
+use ankurah_proto as proto;
+use tokio::sync::watch;
+
+/// The goodbye every connection of a server waits on; `None` until the server drains
+pub(crate) type GoodbyeSignal = Arc<watch::Sender<Option<proto::Goodbye>>>;
+
+/// Drains a running [`WebsocketServer`](crate::WebsocketServer) ahead of a shutdown or deploy
+pub struct DrainHandle<SE, PA>
+where
+    SE: StorageEngine + Send + Sync + 'static,
+    PA: PolicyAgent + Send + Sync + 'static,
+{
+    node: Node<SE, PA>,
+    goodbye: GoodbyeSignal,
+}
+
+impl<SE, PA> Clone for DrainHandle<SE, PA>
+where
+    SE: StorageEngine + Send + Sync + 'static,
+    PA: PolicyAgent + Send + Sync + 'static,
+{
+    fn clone(&self) -> Self { Self { node: self.node.clone(), goodbye: self.goodbye.clone() } }
+}
+
+impl<SE, PA> DrainHandle<SE, PA>
+where
+    SE: StorageEngine + Send + Sync + 'static,
+    PA: PolicyAgent + Send + Sync + 'static,
+{
+    pub(crate) fn new(node: Node<SE, PA>, goodbye: GoodbyeSignal) -> Self { Self { node, goodbye } }
+
+    /// Refuse new connections and subscriptions, wait for the commits already underway to
+    /// finish, then send every client `goodbye` and wait for its connection to close.
+    ///
+    /// Clients that ignore the goodbye are disconnected after a short grace period.
+    pub async fn drain(&self, goodbye: proto::Goodbye) {
+        self.node.drain().await;
+        self.goodbye.send_replace(Some(goodbye));
+        self.goodbye.closed().await;
+    }
+}
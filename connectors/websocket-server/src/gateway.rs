@@ -0,0 +1,299 @@
+//! HTTP/JSON access to a node's collections for clients that don't speak the Ankurah protocol.
+//!
+//! Each request is authenticated the way a peer's is: its `Authorization` header (less any
+//! `Bearer ` prefix) becomes the request's [`proto::AuthData`], which the node's
+//! [`PolicyAgent::check_request`] turns into the context the request acts as. Entities are
+//! JSON objects of their property values plus their base64 `id`.
+//!
+//! | Route                            | Action                                                   |
+//! |----------------------------------|----------------------------------------------------------|
+//! | `GET /{collection}?q=`           | Fetch the entities matching an AnkQL selection           |
+//! | `POST /{collection}`             | Create an entity from a JSON property map                |
+//! | `GET /{collection}/{id}`         | Get an entity by id                                      |
+//! | `PATCH /{collection}/{id}`       | Set the properties in a JSON property map                |
+//! | `GET /{collection}/live?q=`      | Stream a live query as server-sent events                |
+//!
+//! A live query first sends an `initial` event carrying the matching entities, then an `add`,
+//! `update` or `remove` event carrying each entity as it changes.
+
+use std::convert::Infallible;
+
+use ankurah_core::{
+    changes::ItemChange,
+    context::Context,
+    entity::Entity,
+    error::{MutationError, RetrievalError},
+    node::{MatchArgs, Node},
+    policy::PolicyAgent,
+    storage::StorageEngine,
+    value::Value,
+};
+use ankurah_proto as proto;
+use ankurah_signals::{Subscribe, With};
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::get,
+    Json, Router,
+};
+use futures_util::{stream, Stream, StreamExt};
+use serde::Deserialize;
+use serde_json::{Map, Value as JsonValue};
+
+/// The gateway's routes over `node`, for nesting into an application's router
+pub fn gateway_router<SE, PA>(node: Node<SE, PA>) -> Router
+where
+    SE: StorageEngine + Send + Sync + 'static,
+    PA: PolicyAgent + Send + Sync + 'static,
+{
+    Router::new()
+        .route("/{collection}", get(fetch::<SE, PA>).post(create::<SE, PA>))
+        .route("/{collection}/live", get(live::<SE, PA>))
+        .route("/{collection}/{id}", get(get_by_id::<SE, PA>).patch(update::<SE, PA>))
+        .with_state(node)
+}
+
+#[derive(Deserialize)]
+struct SelectionQuery {
+    q: Option<String>,
+}
+
+impl SelectionQuery {
+    fn match_args(self) -> Result<MatchArgs, GatewayError> {
+        let selection = self.q.unwrap_or_else(|| "true".to_owned());
+        Ok(TryInto::<MatchArgs>::try_into(selection.as_str()).map_err(RetrievalError::from)?)
+    }
+}
+
+async fn fetch<SE, PA>(
+    State(node): State<Node<SE, PA>>,
+    Path(collection): Path<String>,
+    Query(selection): Query<SelectionQuery>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<JsonValue>>, GatewayError>
+where
+    SE: StorageEngine + Send + Sync + 'static,
+    PA: PolicyAgent + Send + Sync + 'static,
+{
+    let collection = proto::CollectionId::from(collection);
+    let args = selection.match_args()?;
+    let body = proto::NodeRequestBody::Fetch { collection: collection.clone(), selection: args.selection.clone(), known_matches: vec![] };
+    let context = authorize(&node, &headers, body).await?;
+    let entities = context.fetch_entities(&collection, args).await?;
+    Ok(Json(entities.iter().map(entity_json).collect()))
+}
+
+async fn get_by_id<SE, PA>(
+    State(node): State<Node<SE, PA>>,
+    Path((collection, id)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<Json<JsonValue>, GatewayError>
+where
+    SE: StorageEngine + Send + Sync + 'static,
+    PA: PolicyAgent + Send + Sync + 'static,
+{
+    let collection = proto::CollectionId::from(collection);
+    let id = parse_id(&id)?;
+    let context = authorize(&node, &headers, proto::NodeRequestBody::Get { collection: collection.clone(), ids: vec![id] }).await?;
+    let entity = context.get_entity(&collection, id).await?;
+    Ok(Json(entity_json(&entity)))
+}
+
+async fn create<SE, PA>(
+    State(node): State<Node<SE, PA>>,
+    Path(collection): Path<String>,
+    headers: HeaderMap,
+    Json(properties): Json<Map<String, JsonValue>>,
+) -> Result<(StatusCode, Json<JsonValue>), GatewayError>
+where
+    SE: StorageEngine + Send + Sync + 'static,
+    PA: PolicyAgent + Send + Sync + 'static,
+{
+    let collection = proto::CollectionId::from(collection);
+    let context = authorize(&node, &headers, commit_body()).await?;
+    let trx = context.begin();
+    let created = entity_json(trx.create_entity(&collection, property_values(properties)).await?);
+    trx.commit().await?;
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+async fn update<SE, PA>(
+    State(node): State<Node<SE, PA>>,
+    Path((collection, id)): Path<(String, String)>,
+    headers: HeaderMap,
+    Json(properties): Json<Map<String, JsonValue>>,
+) -> Result<Json<JsonValue>, GatewayError>
+where
+    SE: StorageEngine + Send + Sync + 'static,
+    PA: PolicyAgent + Send + Sync + 'static,
+{
+    let collection = proto::CollectionId::from(collection);
+    let id = parse_id(&id)?;
+    let context = authorize(&node, &headers, commit_body()).await?;
+    let trx = context.begin();
+    let updated = entity_json(trx.update_entity(&collection, id, property_values(properties)).await?);
+    trx.commit().await?;
+    Ok(Json(updated))
+}
+
+async fn live<SE, PA>(
+    State(node): State<Node<SE, PA>>,
+    Path(collection): Path<String>,
+    Query(selection): Query<SelectionQuery>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, GatewayError>
+where
+    SE: StorageEngine + Send + Sync + 'static,
+    PA: PolicyAgent + Send + Sync + 'static,
+{
+    let collection = proto::CollectionId::from(collection);
+    let args = selection.match_args()?;
+    let body = proto::NodeRequestBody::SubscribeQuery {
+        query_id: proto::QueryId::new(),
+        collection: collection.clone(),
+        selection: args.selection.clone(),
+        version: 0,
+        known_matches: vec![],
+    };
+    let context = authorize(&node, &headers, body).await?;
+    let query = context.query_entities(collection, args)?;
+
+    // Subscribe before initialization so no change slips between the initial event and the rest
+    let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+    let subscription = query.subscribe(move |changes: Vec<ItemChange<Entity>>| {
+        for change in changes {
+            let kind = match change {
+                ItemChange::Initial { .. } => continue,
+                ItemChange::Add { .. } => "add",
+                ItemChange::Update { .. } => "update",
+                ItemChange::Remove { .. } => "remove",
+            };
+            let _ = sender.send(Event::default().event(kind).data(entity_json(change.entity()).to_string()));
+        }
+    });
+    query.wait_initialized().await;
+    if let Some(error) = query.error().with(|error| error.as_ref().map(|e| e.to_string())) {
+        return Err(GatewayError(StatusCode::INTERNAL_SERVER_ERROR, error));
+    }
+    let initial: Vec<JsonValue> = query.resultset().read().iter_entities().map(|(_, entity)| entity_json(entity)).collect();
+    let initial = Event::default().event("initial").data(JsonValue::from(initial).to_string());
+
+    // The stream owns the query and its subscription, so both end when the client goes away
+    let changes = stream::unfold((receiver, query, subscription), |(mut receiver, query, subscription)| async move {
+        receiver.recv().await.map(|event| (event, (receiver, query, subscription)))
+    });
+    Ok(Sse::new(stream::once(async { initial }).chain(changes).map(Ok)).keep_alive(KeepAlive::default()))
+}
+
+/// Check the request's credential with the node's policy agent and build the context it acts as
+async fn authorize<SE, PA>(node: &Node<SE, PA>, headers: &HeaderMap, body: proto::NodeRequestBody) -> Result<Context, GatewayError>
+where
+    SE: StorageEngine + Send + Sync + 'static,
+    PA: PolicyAgent + Send + Sync + 'static,
+{
+    let credential = match headers.get(header::AUTHORIZATION) {
+        Some(value) => value.as_bytes().strip_prefix(b"Bearer ").unwrap_or(value.as_bytes()).to_vec(),
+        None => Vec::new(),
+    };
+    // The gateway answers on the node's behalf, so the request is addressed from and to it
+    let request = proto::NodeRequest { id: proto::RequestId::new(), to: node.id, from: node.id, body };
+    let cdata = node
+        .policy_agent()
+        .check_request(node, &vec![proto::AuthData(credential)], &request)
+        .await
+        .map_err(|e| GatewayError(StatusCode::UNAUTHORIZED, e.to_string()))?;
+    let Some(cdata) = cdata.into_iter().next() else {
+        return Err(GatewayError(StatusCode::UNAUTHORIZED, "no credential was accepted".to_owned()));
+    };
+    node.context(cdata).map_err(|e| GatewayError(StatusCode::SERVICE_UNAVAILABLE, e.to_string()))
+}
+
+/// Writes are checked entity by entity as they are made, so the request carries no events
+fn commit_body() -> proto::NodeRequestBody { proto::NodeRequestBody::CommitTransaction { id: proto::TransactionId::new(), events: vec![] } }
+
+fn parse_id(id: &str) -> Result<proto::EntityId, GatewayError> {
+    proto::EntityId::from_base64(id).map_err(|_| GatewayError(StatusCode::BAD_REQUEST, format!("invalid entity id: {id}")))
+}
+
+/// JSON property values as the nearest [`Value`], which the write casts to each property's type
+fn property_values(properties: Map<String, JsonValue>) -> Vec<(String, Option<Value>)> {
+    properties
+        .into_iter()
+        .map(|(name, json)| {
+            let value = match json {
+                JsonValue::Null => None,
+                JsonValue::Bool(b) => Some(Value::Bool(b)),
+                JsonValue::Number(n) => n.as_i64().map(Value::I64).or_else(|| n.as_f64().map(Value::F64)),
+                JsonValue::String(s) => Some(Value::String(s)),
+                json => Some(Value::Json(json)),
+            };
+            (name, value)
+        })
+        .collect()
+}
+
+fn entity_json(entity: &Entity) -> JsonValue {
+    let mut object = Map::new();
+    object.insert("id".to_owned(), entity.id().to_base64().into());
+    for (name, value) in entity.values() {
+        object.insert(name, value.map_or(JsonValue::Null, value_json));
+    }
+    JsonValue::Object(object)
+}
+
+fn value_json(value: Value) -> JsonValue {
+    match value {
+        Value::I16(n) => n.into(),
+        Value::I32(n) => n.into(),
+        Value::I64(n) => n.into(),
+        Value::F64(n) => n.into(),
+        Value::Bool(b) => b.into(),
+        Value::String(s) => s.into(),
+        Value::EntityId(id) => id.to_base64().into(),
+        Value::Object(bytes) | Value::Binary(bytes) => bytes.into(),
+        Value::Json(json) => json,
+        Value::Timestamp(t) => t.to_string().into(),
+        Value::Date(d) => d.to_string().into(),
+        Value::Decimal(d) => d.to_string().into(),
+        Value::Uuid(u) => u.to_string().into(),
+        Value::U32(n) => n.into(),
+        Value::U64(n) => n.into(),
+        // JSON numbers stop at 64 bits in serde_json, so 128-bit integers travel as text
+        Value::I128(n) => i64::try_from(n).map_or_else(|_| n.to_string().into(), Into::into),
+    }
+}
+
+struct GatewayError(StatusCode, String);
+
+impl IntoResponse for GatewayError {
+    fn into_response(self) -> Response { (self.0, Json(serde_json::json!({ "error": self.1 }))).into_response() }
+}
+
+impl From<RetrievalError> for GatewayError {
+    fn from(e: RetrievalError) -> Self {
+        let status = match &e {
+            RetrievalError::EntityNotFound(_) | RetrievalError::CollectionNotFound(_) => StatusCode::NOT_FOUND,
+            RetrievalError::ParseError(_) => StatusCode::BAD_REQUEST,
+            RetrievalError::AccessDenied(_) => StatusCode::FORBIDDEN,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        Self(status, e.to_string())
+    }
+}
+
+impl From<MutationError> for GatewayError {
+    fn from(e: MutationError) -> Self {
+        match e {
+            MutationError::RetrievalError(e) => e.into(),
+            MutationError::AccessDenied(_) => Self(StatusCode::FORBIDDEN, e.to_string()),
+            MutationError::UnregisteredModel(_) | MutationError::UnknownProperty { .. } | MutationError::PropertyError(_) => {
+                Self(StatusCode::UNPROCESSABLE_ENTITY, e.to_string())
+            }
+            e => Self(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        }
+    }
+}
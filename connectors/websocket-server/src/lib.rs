@@ -1,8 +1,17 @@
 mod client_ip;
+mod drain;
+#[cfg(feature = "gateway")]
+mod gateway;
 mod sender;
 mod server;
 mod state;
 mod user_agent;
 
+pub use drain::DrainHandle;
+#[cfg(feature = "gateway")]
+pub use gateway::gateway_router;
 pub use server::*;
 pub use user_agent::OptionalUserAgent;
+
+#[cfg(feature = "prometheus")]
+pub use metrics_exporter_prometheus;
@@ -9,11 +9,12 @@ use tracing::{debug, error};
 // PeerSender for sending messages to a websocket client
 #[derive(Clone)]
 pub struct WebSocketClientSender {
-    tx: mpsc::Sender<axum::extract::ws::Message>,
+    tx: mpsc::Sender<proto::Message>,
     inner: Arc<Inner>,
 }
 struct Inner {
     pub(crate) recipient_node_id: proto::EntityId,
+    wire: proto::Wire,
     handle: Arc<tokio::task::JoinHandle<()>>,
 }
 
@@ -21,28 +22,73 @@ impl WebSocketClientSender {
     pub fn new(
         node_id: proto::EntityId,
         mut sender: futures_util::stream::SplitSink<axum::extract::ws::WebSocket, axum::extract::ws::Message>,
+        wire: proto::Wire,
     ) -> Self {
         let (tx, mut rx) = mpsc::channel(32);
         use futures_util::SinkExt;
         let handle = tokio::spawn(async move {
-            while let Some(msg) = rx.recv().await {
-                if sender.send(msg).await.is_err() {
-                    error!("Failed to send message through websocket, breaking send loop");
+            // A message drained while filling a batch that cannot join it
+            let mut pending = None;
+            loop {
+                let message = match pending.take() {
+                    Some(message) => message,
+                    None => match rx.recv().await {
+                        Some(message) => message,
+                        None => break,
+                    },
+                };
+                // A goodbye is the last thing the client hears from us
+                let closing = matches!(message, proto::Message::Goodbye(_));
+                // Batch whatever peer messages are already queued behind this one
+                let frames = match message {
+                    proto::Message::PeerMessage(message) if wire.batching => {
+                        let mut batch = vec![message];
+                        while batch.len() < proto::wire::MAX_BATCH_LEN {
+                            match rx.try_recv() {
+                                Ok(proto::Message::PeerMessage(message)) => batch.push(message),
+                                Ok(other) => {
+                                    pending = Some(other);
+                                    break;
+                                }
+                                Err(_) => break,
+                            }
+                        }
+                        wire.encode_all(batch)
+                    }
+                    message => wire.encode(&message).map(|frame| vec![frame]),
+                };
+                let frames = match frames {
+                    Ok(frames) => frames,
+                    Err(e) => {
+                        error!("Failed to encode message for websocket: {}", e);
+                        continue;
+                    }
+                };
+                for frame in frames {
+                    debug!(bytes = frame.len(), "Sending frame through websocket");
+                    ankurah_core::metrics::bytes_sent(node_id, frame.len());
+                    if sender.send(axum::extract::ws::Message::Binary(frame.into())).await.is_err() {
+                        error!("Failed to send message through websocket, breaking send loop");
+                        return;
+                    }
+                }
+                if closing {
+                    let _ = sender.close().await;
                     break;
                 }
             }
             debug!("WebSocket sender task completed");
         });
-        Self { tx, inner: Arc::new(Inner { recipient_node_id: node_id, handle: Arc::new(handle) }) }
+        Self { tx, inner: Arc::new(Inner { recipient_node_id: node_id, wire, handle: Arc::new(handle) }) }
     }
 
+    /// The encoding settled with this client
+    pub fn wire(&self) -> proto::Wire { self.inner.wire }
+
     #[tracing::instrument(skip(self, message), fields(recipient = %self.inner.recipient_node_id, msg = %message))]
     pub fn send_message(&self, message: proto::Message) -> Result<(), SendError> {
-        debug!("Serializing message");
-        let data = bincode::serialize(&message).map_err(|e| SendError::Other(anyhow::anyhow!("Serialization error: {}", e)))?;
-
-        debug!(bytes = data.len(), "Sending message through channel");
-        self.tx.try_send(axum::extract::ws::Message::Binary(data.into())).map_err(|_| SendError::Unknown)?;
+        debug!("Sending message through channel");
+        self.tx.try_send(message).map_err(|_| SendError::Unknown)?;
         debug!("Message sent successfully");
 
         Ok(())
@@ -6,24 +6,29 @@ use axum::{
         ws::{WebSocket, WebSocketUpgrade},
         State,
     },
+    http::StatusCode,
     response::{IntoResponse, Response},
     routing::get,
     Router,
 };
 use axum_extra::{headers, TypedHeader};
-use bincode::deserialize;
 use futures_util::StreamExt;
 use std::net::IpAddr;
-use std::{future::Future, net::SocketAddr, ops::ControlFlow, pin::Pin, time::Duration};
+use std::{future::Future, net::SocketAddr, ops::ControlFlow, pin::Pin, sync::Arc, time::Duration};
+use tokio::sync::watch;
 use tower::ServiceBuilder;
 use tower_http::trace::{DefaultMakeSpan, DefaultOnRequest, DefaultOnResponse, TraceLayer};
 #[cfg(feature = "instrument")]
 use tracing::instrument;
 use tracing::{debug, error, info, warn, Level};
 
-use ankurah_core::{node::Node, policy::PolicyAgent};
+use ankurah_core::{connector::PeerSender, node::Node, policy::PolicyAgent};
 
-use crate::{client_ip::SmartClientIp, OptionalUserAgent};
+use crate::{
+    client_ip::SmartClientIp,
+    drain::{DrainHandle, GoodbyeSignal},
+    OptionalUserAgent,
+};
 
 use super::state::Connection;
 
@@ -31,12 +36,20 @@ use super::state::Connection;
 // pre-establishment state must not be allowed to live for the socket lifetime.
 const INITIAL_PRESENCE_TIMEOUT: Duration = Duration::from_secs(10);
 
+// How long a client told to go away gets to close its connection before we close it.
+const GOODBYE_GRACE: Duration = Duration::from_secs(5);
+
 pub struct WebsocketServer<SE, PA>
 where
     SE: StorageEngine + Send + Sync + 'static,
     PA: PolicyAgent + Send + Sync + 'static,
 {
     node: Option<Node<SE, PA>>,
+    goodbye: GoodbyeSignal,
+    #[cfg(feature = "prometheus")]
+    prometheus: Option<metrics_exporter_prometheus::PrometheusHandle>,
+    #[cfg(feature = "gateway")]
+    gateway: bool,
 }
 
 impl<SE, PA> WebsocketServer<SE, PA>
@@ -44,17 +57,50 @@ where
     SE: StorageEngine + Send + Sync + 'static,
     PA: PolicyAgent + Send + Sync + 'static,
 {
-    pub fn new(node: Node<SE, PA>) -> Self { Self { node: Some(node) } }
+    pub fn new(node: Node<SE, PA>) -> Self {
+        Self {
+            node: Some(node),
+            goodbye: Arc::new(watch::Sender::new(None)),
+            #[cfg(feature = "prometheus")]
+            prometheus: None,
+            #[cfg(feature = "gateway")]
+            gateway: false,
+        }
+    }
+
+    /// A handle for draining the server's connections once it runs
+    pub fn drain_handle(&self) -> DrainHandle<SE, PA> {
+        let node = self.node.as_ref().expect("websocket server cannot produce a drain handle after being run").clone();
+        DrainHandle::new(node, self.goodbye.clone())
+    }
+
+    /// Serve the metrics recorded by `handle`'s recorder at `/metrics` when the server runs
+    #[cfg(feature = "prometheus")]
+    pub fn prometheus(mut self, handle: metrics_exporter_prometheus::PrometheusHandle) -> Self {
+        ankurah_core::metrics::describe();
+        self.prometheus = Some(handle);
+        self
+    }
+
+    /// Serve the node's collections over HTTP/JSON at `/api` when the server runs; see
+    /// [`gateway_router`](crate::gateway_router) for the routes
+    #[cfg(feature = "gateway")]
+    pub fn gateway(mut self) -> Self {
+        self.gateway = true;
+        self
+    }
 
     pub fn route_handler(
         &self,
     ) -> impl Clone + Send + 'static + Fn(WebSocketUpgrade, SmartClientIp, OptionalUserAgent) -> Pin<Box<dyn Future<Output = Response> + Send>>
     {
         let node = self.node.as_ref().expect("websocket server cannot produce a route after being run").clone();
+        let goodbye = self.goodbye.clone();
 
         move |ws: WebSocketUpgrade, SmartClientIp(client_ip): SmartClientIp, OptionalUserAgent(user_agent)| {
             let node = node.clone();
-            Box::pin(async move { upgrade_connection(ws, client_ip, user_agent, node) })
+            let goodbye = goodbye.clone();
+            Box::pin(async move { upgrade_connection(ws, client_ip, user_agent, node, &goodbye) })
         }
     }
 
@@ -62,7 +108,20 @@ where
         let Some(node) = self.node.take() else {
             return Err(anyhow::anyhow!("Already been run"));
         };
-        let app = Router::new().route("/ws", get(ws_handler)).with_state(node).layer(
+        #[cfg(feature = "gateway")]
+        let gateway = self.gateway.then(|| crate::gateway_router(node.clone()));
+        let app = Router::new().route("/ws", get(ws_handler)).with_state((node, self.goodbye.clone()));
+        #[cfg(feature = "gateway")]
+        let app = match gateway {
+            Some(gateway) => app.nest("/api", gateway),
+            None => app,
+        };
+        #[cfg(feature = "prometheus")]
+        let app = match self.prometheus.clone() {
+            Some(handle) => app.route("/metrics", get(move || std::future::ready(handle.render()))),
+            None => app,
+        };
+        let app = app.layer(
             ServiceBuilder::new()
                 .layer(
                     TraceLayer::new_for_http()
@@ -86,32 +145,47 @@ async fn ws_handler<SE, PA>(
     ws: WebSocketUpgrade,
     SmartClientIp(client_ip): SmartClientIp,
     user_agent: Option<TypedHeader<headers::UserAgent>>,
-    State(node): State<Node<SE, PA>>,
+    State((node, goodbye)): State<(Node<SE, PA>, GoodbyeSignal)>,
 ) -> impl IntoResponse
 where
     SE: StorageEngine + Send + Sync + 'static,
     PA: PolicyAgent + Send + Sync + 'static,
 {
     let user_agent = user_agent.map(|TypedHeader(user_agent)| user_agent.to_string());
-    upgrade_connection(ws, client_ip, user_agent, node)
+    upgrade_connection(ws, client_ip, user_agent, node, &goodbye)
 }
 
-fn upgrade_connection<SE, PA>(ws: WebSocketUpgrade, client_ip: IpAddr, user_agent: Option<String>, node: Node<SE, PA>) -> Response
+fn upgrade_connection<SE, PA>(
+    ws: WebSocketUpgrade,
+    client_ip: IpAddr,
+    user_agent: Option<String>,
+    node: Node<SE, PA>,
+    goodbye: &GoodbyeSignal,
+) -> Response
 where
     SE: StorageEngine + Send + Sync + 'static,
     PA: PolicyAgent + Send + Sync + 'static,
 {
-    debug!("Websocket server upgrading connection");
     let user_agent = format_user_agent(user_agent);
+    if node.is_draining() {
+        debug!("Turning away `{user_agent}` at {client_ip}: draining");
+        return StatusCode::SERVICE_UNAVAILABLE.into_response();
+    }
+    debug!("Websocket server upgrading connection");
     debug!("`{user_agent}` at {client_ip} connected.");
-    ws.on_upgrade(move |socket| handle_websocket(socket, client_ip, node))
+    let goodbye = goodbye.subscribe();
+    ws.on_upgrade(move |socket| handle_websocket(socket, client_ip, node, goodbye))
 }
 
 fn format_user_agent(user_agent: Option<String>) -> String { user_agent.unwrap_or_else(|| String::from("Unknown browser")) }
 
 #[cfg_attr(feature = "instrument", instrument(level = "debug", skip_all, fields(client_ip = %client_ip)))]
-async fn handle_websocket<SE, PA>(socket: WebSocket, client_ip: IpAddr, node: Node<SE, PA>)
-where
+async fn handle_websocket<SE, PA>(
+    socket: WebSocket,
+    client_ip: IpAddr,
+    node: Node<SE, PA>,
+    mut goodbye: watch::Receiver<Option<proto::Goodbye>>,
+) where
     SE: StorageEngine + Send + Sync + 'static,
     PA: PolicyAgent + Send + Sync + 'static,
 {
@@ -127,6 +201,9 @@ where
             node_id: node.id,
             durable: node.durable,
             system_root: node.system.root(),
+            wire: proto::WireSupport::all(),
+            features: proto::protocol_features(),
+            min_protocol_version: proto::MIN_PROTOCOL_VERSION,
             protocol_version: proto::PROTOCOL_VERSION,
         }))
         .await
@@ -135,17 +212,36 @@ where
         return;
     }
 
+    // Set once the client has been told to go away
+    let mut closing_deadline = None;
     loop {
-        let next = if matches!(conn, Connection::Initial(_)) {
-            match tokio::time::timeout_at(initial_presence_deadline, receiver.next()).await {
-                Ok(next) => next,
-                Err(_) => {
-                    warn!("Peer at {client_ip} did not send presence within {INITIAL_PRESENCE_TIMEOUT:?}; closing");
+        let deadline = match conn {
+            Connection::Initial(_) => Some(initial_presence_deadline),
+            Connection::Established(_) => closing_deadline,
+        };
+        let next = tokio::select! {
+            next = async {
+                match deadline {
+                    Some(deadline) => tokio::time::timeout_at(deadline, receiver.next()).await.ok(),
+                    None => Some(receiver.next().await),
+                }
+            } => next,
+            Ok(()) = goodbye.changed(), if closing_deadline.is_none() => {
+                let Some(message) = goodbye.borrow_and_update().clone() else { continue };
+                info!("Asking {client_ip} to {message}");
+                if conn.send(proto::Message::Goodbye(message)).await.is_err() || matches!(conn, Connection::Initial(_)) {
                     break;
                 }
+                closing_deadline = Some(tokio::time::Instant::now() + GOODBYE_GRACE);
+                continue;
             }
-        } else {
-            receiver.next().await
+        };
+        let Some(next) = next else {
+            match closing_deadline {
+                Some(_) => warn!("Peer at {client_ip} did not close within {GOODBYE_GRACE:?} of goodbye; closing"),
+                None => warn!("Peer at {client_ip} did not send presence within {INITIAL_PRESENCE_TIMEOUT:?}; closing"),
+            }
+            break;
         };
         let Some(msg) = next else { break };
 
@@ -182,20 +278,24 @@ where
     match msg {
         axum::extract::ws::Message::Binary(d) => {
             debug!(">>> {} sent {} bytes", client_ip, d.len());
+            if let Connection::Established(sender) = state {
+                ankurah_core::metrics::bytes_received(sender.recipient_node_id(), d.len());
+            }
 
-            if let Ok(message) = deserialize::<proto::Message>(&d) {
+            if let Ok(message) = state.wire().decode(&d) {
                 match message {
-                    proto::Message::Presence(presence) => {
+                    proto::Decoded::Presence(presence) => {
                         // Pre-check the version while we can still reply through the
                         // connection and await the flush; register_peer re-enforces
                         // this for every transport.
-                        if !proto::protocol_compatible(presence.protocol_version) {
-                            let rejection =
-                                proto::PresenceRejection { expected: proto::PROTOCOL_VERSION, received: presence.protocol_version };
-                            warn!("Refusing peer at {client_ip}: {rejection}");
-                            let _ = state.send(proto::Message::PresenceRejected(rejection)).await;
-                            return ControlFlow::Break(());
-                        }
+                        let wire = match proto::Wire::negotiate(&presence) {
+                            Ok(wire) => wire,
+                            Err(rejection) => {
+                                warn!("Refusing peer at {client_ip}: {rejection}");
+                                let _ = state.send(proto::Message::PresenceRejected(rejection)).await;
+                                return ControlFlow::Break(());
+                            }
+                        };
                         match state {
                             Connection::Initial(sender) => {
                                 if let Some(sender) = sender.take() {
@@ -203,10 +303,10 @@ where
 
                                     use super::sender::WebSocketClientSender;
                                     // Register peer sender for this client
-                                    let sender = WebSocketClientSender::new(presence.node_id, sender);
+                                    let sender = WebSocketClientSender::new(presence.node_id, sender, wire);
 
                                     match node.register_peer(presence, Box::new(sender.clone())) {
-                                        Ok(()) => *state = Connection::Established(sender),
+                                        Ok(_) => *state = Connection::Established(sender),
                                         Err(rejection) => {
                                             warn!("Refusing peer at {client_ip}: {rejection}");
                                             let _ = sender.send_message(proto::Message::PresenceRejected(rejection));
@@ -218,21 +318,23 @@ where
                             _ => warn!("Received presence from {} but already have a peer sender - ignoring", client_ip),
                         }
                     }
-                    proto::Message::PeerMessage(msg) => {
-                        if let Connection::Established(_) = state {
-                            tokio::spawn(async move {
-                                if let Err(e) = node.handle_message(msg).await {
-                                    error!("Error handling message from {}: {:?}", client_ip, e);
-                                }
-                            });
-                        } else {
-                            warn!("Received peer message from {} but not connected as a peer", client_ip);
+                    proto::Decoded::PeerMessage(_) | proto::Decoded::Batch(_) if !matches!(state, Connection::Established(_)) => {
+                        warn!("Received peer message from {} but not connected as a peer", client_ip);
+                    }
+                    proto::Decoded::PeerMessage(msg) => handle_peer_message(node, client_ip, msg),
+                    proto::Decoded::Batch(messages) => {
+                        for msg in messages {
+                            handle_peer_message(node.clone(), client_ip, msg);
                         }
                     }
-                    proto::Message::PresenceRejected(rejection) => {
+                    proto::Decoded::PresenceRejected(rejection) => {
                         warn!("Peer at {} refused our presence: {}", client_ip, rejection);
                         return ControlFlow::Break(());
                     }
+                    proto::Decoded::Goodbye(goodbye) => {
+                        info!("Peer at {} is leaving: {}", client_ip, goodbye);
+                        return ControlFlow::Break(());
+                    }
                 }
             } else if let Connection::Initial(_) = state {
                 // A peer whose handshake we cannot read will never establish;
@@ -267,3 +369,15 @@ where
     }
     ControlFlow::Continue(())
 }
+
+fn handle_peer_message<SE, PA>(node: Node<SE, PA>, client_ip: IpAddr, msg: proto::NodeMessage)
+where
+    SE: StorageEngine + Send + Sync + 'static,
+    PA: PolicyAgent + Send + Sync + 'static,
+{
+    tokio::spawn(async move {
+        if let Err(e) = node.handle_message(msg).await {
+            error!("Error handling message from {}: {:?}", client_ip, e);
+        }
+    });
+}
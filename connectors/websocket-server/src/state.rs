@@ -9,6 +9,14 @@ pub enum Connection {
 }
 
 impl Connection {
+    /// How frames from this client are encoded: the handshake encoding until its Presence arrives
+    pub fn wire(&self) -> proto::Wire {
+        match self {
+            Connection::Initial(_) => proto::Wire::default(),
+            Connection::Established(peer_sender) => peer_sender.wire(),
+        }
+    }
+
     pub async fn send(&mut self, message: proto::Message) -> Result<(), SendError> {
         match self {
             Connection::Initial(sender) => {
@@ -0,0 +1,53 @@
+//! # Ankurah WebTransport Server
+//!
+//! Serves browser nodes over WebTransport (HTTP/3 over QUIC). A websocket delivers every
+//! message in order on one stream, so a large `QuerySubscribed` holds up the small updates
+//! queued behind it; here each request gets a stream of its own.
+//!
+//! A client opens a WebTransport session at [`SESSION_PATH`] and maps the peer protocol onto
+//! the session's streams. Every stream carries [`proto::Message`](ankurah_proto::Message)s as
+//! four byte big-endian length prefixed frames, encoded as negotiated in the Presence exchange
+//! (see [`ankurah_proto::wire`]):
+//!
+//! - The **control** stream is the first bidirectional stream the client opens. Both ends send
+//!   their Presence on it, a peer speaking an incompatible protocol version is refused on it
+//!   with `PresenceRejected`, and it carries `Goodbye` and every message from the client that
+//!   is not a request.
+//! - Each **request** from the client opens a bidirectional stream of its own. The server sends
+//!   the response back on that stream and finishes it.
+//! - The server sends subscription updates, in order, on one **updates** unidirectional stream,
+//!   and each request (or response to a request that did not arrive on a stream of its own) on
+//!   a unidirectional stream per message.
+//!
+//! ## Basic Usage
+//!
+//! ```rust,no_run
+//! # use ankurah_core::{Node, policy::PermissiveAgent, storage::StorageEngine};
+//! # use ankurah_webtransport_server::{rustls, WebTransportServer};
+//! # use std::sync::Arc;
+//! # async fn example<SE: StorageEngine + Send + Sync + 'static>(storage: Arc<SE>, tls: Arc<rustls::ServerConfig>) -> anyhow::Result<()> {
+//! let node = Node::new_durable(storage, PermissiveAgent::new());
+//! let server = WebTransportServer::bind(node, "0.0.0.0:9898".parse()?, tls)?;
+//! tokio::spawn(server.run());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Browsers only open WebTransport sessions over TLS. A certificate from a CA the browser
+//! trusts works as is; a self-signed one must be short lived and have its hash passed to the
+//! client (see `serverCertificateHashes` in the WebTransport API).
+
+mod sender;
+mod server;
+mod session;
+
+pub use sender::WebTransportPeerSender;
+pub use server::WebTransportServer;
+
+pub use quinn::rustls;
+
+/// The path a client opens its WebTransport session at
+pub const SESSION_PATH: &str = "/ankurah";
+
+/// Largest frame either end accepts; a longer length prefix closes the stream
+pub const MAX_FRAME_LENGTH: usize = 64 * 1024 * 1024;
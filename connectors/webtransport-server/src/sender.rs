@@ -0,0 +1,86 @@
+use ankurah_core::connector::{PeerSender, SendError};
+use ankurah_proto as proto;
+use async_trait::async_trait;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::{mpsc, oneshot};
+use tracing::{debug, warn};
+
+use crate::session::{self, Responders};
+
+/// PeerSender implementation for WebTransport sessions
+///
+/// Routes each message to the stream it belongs on: a response to the request stream it
+/// answers, subscription updates to the session's updates stream, and anything else to a
+/// unidirectional stream of its own.
+#[derive(Clone)]
+pub struct WebTransportPeerSender {
+    recipient_node_id: proto::EntityId,
+    connection: quinn::Connection,
+    session_id: u64,
+    wire: proto::Wire,
+    updates: mpsc::UnboundedSender<proto::NodeMessage>,
+    responders: Responders,
+}
+
+impl WebTransportPeerSender {
+    pub(crate) fn new(
+        recipient_node_id: proto::EntityId,
+        connection: quinn::Connection,
+        session_id: u64,
+        wire: proto::Wire,
+    ) -> (Self, mpsc::UnboundedReceiver<proto::NodeMessage>) {
+        let (updates, rx) = mpsc::unbounded_channel();
+        let responders = Arc::new(Mutex::new(HashMap::new()));
+        (Self { recipient_node_id, connection, session_id, wire, updates, responders }, rx)
+    }
+
+    /// Route the response to `request_id` to the request stream it arrived on
+    pub(crate) fn await_response(&self, request_id: proto::RequestId) -> oneshot::Receiver<proto::NodeMessage> {
+        let (tx, rx) = oneshot::channel();
+        self.responders.lock().unwrap().insert(request_id, tx);
+        rx
+    }
+}
+
+#[async_trait]
+impl PeerSender for WebTransportPeerSender {
+    fn send_message(&self, message: proto::NodeMessage) -> Result<(), SendError> {
+        let message = match message {
+            proto::NodeMessage::Response(response) => match self.responders.lock().unwrap().remove(&response.request_id) {
+                Some(responder) => {
+                    debug!("Answering request {} from {} on its stream", response.request_id, self.recipient_node_id);
+                    return responder.send(proto::NodeMessage::Response(response)).map_err(|_| SendError::ConnectionClosed);
+                }
+                None => proto::NodeMessage::Response(response),
+            },
+            message => message,
+        };
+
+        match message {
+            proto::NodeMessage::Request { .. } | proto::NodeMessage::Response(_) => {
+                if self.connection.close_reason().is_some() {
+                    return Err(SendError::ConnectionClosed);
+                }
+                let connection = self.connection.clone();
+                let (session_id, wire, recipient) = (self.session_id, self.wire, self.recipient_node_id);
+                tokio::spawn(async move {
+                    if let Err(e) = session::send_on_own_stream(&connection, session_id, &wire, recipient, message).await {
+                        warn!("Failed to send message to peer {}: {}", recipient, e);
+                    }
+                });
+                Ok(())
+            }
+            message => self.updates.send(message).map_err(|_| {
+                warn!("Failed to send message to peer {} - session closed", self.recipient_node_id);
+                SendError::ConnectionClosed
+            }),
+        }
+    }
+
+    fn recipient_node_id(&self) -> proto::EntityId { self.recipient_node_id }
+
+    fn cloned(&self) -> Box<dyn PeerSender> { Box::new(self.clone()) }
+}
@@ -0,0 +1,53 @@
+use ankurah_core::connector::NodeComms;
+use anyhow::Result;
+use quinn::crypto::rustls::QuicServerConfig;
+use std::{net::SocketAddr, sync::Arc};
+use tracing::{debug, info, warn};
+
+use crate::{rustls, session};
+
+/// Accepts WebTransport sessions from browser nodes over QUIC
+pub struct WebTransportServer {
+    node: Box<dyn NodeComms>,
+    endpoint: quinn::Endpoint,
+}
+
+impl WebTransportServer {
+    /// Listen for QUIC connections on the UDP address `addr`, presenting the certificate in `tls`
+    ///
+    /// The configuration's ALPN protocols are replaced with HTTP/3's.
+    pub fn bind(node: impl NodeComms + 'static, addr: SocketAddr, tls: Arc<rustls::ServerConfig>) -> Result<Self> {
+        let mut tls = (*tls).clone();
+        tls.alpn_protocols = vec![b"h3".to_vec()];
+        let config = quinn::ServerConfig::with_crypto(Arc::new(QuicServerConfig::try_from(tls)?));
+        let endpoint = quinn::Endpoint::server(config, addr)?;
+        Ok(Self { node: Box::new(node), endpoint })
+    }
+
+    /// The bound UDP address
+    pub fn local_addr(&self) -> Result<SocketAddr> { Ok(self.endpoint.local_addr()?) }
+
+    /// Accept connections until the endpoint is closed
+    pub async fn run(self) -> Result<()> {
+        info!("WebTransport server listening on {}", self.endpoint.local_addr()?);
+
+        while let Some(incoming) = self.endpoint.accept().await {
+            let node = self.node.cloned();
+            tokio::spawn(async move {
+                let peer = incoming.remote_address().to_string();
+                let result = match incoming.await {
+                    Ok(connection) => {
+                        info!("WebTransport server connected to {}", peer);
+                        session::run(node.as_ref(), connection, &peer).await
+                    }
+                    Err(e) => Err(anyhow::anyhow!("QUIC handshake with {} failed: {}", peer, e)),
+                };
+                match result {
+                    Ok(()) => debug!("WebTransport session {} closed", peer),
+                    Err(e) => warn!("WebTransport session {} closed: {}", peer, e),
+                }
+            });
+        }
+        Ok(())
+    }
+}
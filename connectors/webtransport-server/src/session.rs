@@ -0,0 +1,402 @@
+use ankurah_core::connector::{NodeComms, PeerSender};
+use ankurah_proto as proto;
+use anyhow::{anyhow, bail, Result};
+use bytes::{Bytes, BytesMut};
+use futures_util::{SinkExt, StreamExt};
+use h3::{ext::Protocol, frame::FrameStream, proto::frame::Frame, server::RequestStream, stream::BufRecvStream, webtransport::SessionId};
+use http::{Method, Response, StatusCode};
+use std::{
+    collections::HashMap,
+    future::poll_fn,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    select,
+    sync::{mpsc, oneshot},
+};
+use tokio_util::codec::{Framed, FramedWrite, LengthDelimitedCodec};
+use tracing::{debug, info, warn};
+
+use crate::{sender::WebTransportPeerSender, MAX_FRAME_LENGTH, SESSION_PATH};
+
+/// Where the response to each in-flight request goes: the stream the request arrived on
+pub(crate) type Responders = Arc<Mutex<HashMap<proto::RequestId, oneshot::Sender<proto::NodeMessage>>>>;
+
+type H3Connection = h3::server::Connection<h3_quinn::Connection, Bytes>;
+type SessionStream = BufRecvStream<h3_quinn::BidiStream<Bytes>, Bytes>;
+
+// Closing a QUIC connection discards whatever it has not delivered, so a session that ends
+// with a last word (such as PresenceRejected) waits this long for the peer to close first.
+const CLOSE_GRACE_PERIOD: Duration = Duration::from_secs(1);
+
+// A peer that cannot decode our Presence will never send its own, so the
+// pre-establishment state must not be allowed to live for the connection lifetime.
+const INITIAL_PRESENCE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The stream type that opens a WebTransport unidirectional stream, followed by its session id
+const WEBTRANSPORT_UNI_STREAM: u64 = 0x54;
+
+fn codec() -> LengthDelimitedCodec { LengthDelimitedCodec::builder().max_frame_length(MAX_FRAME_LENGTH).new_codec() }
+
+fn framed<S: AsyncRead + AsyncWrite>(stream: S) -> Framed<S, LengthDelimitedCodec> { Framed::new(stream, codec()) }
+
+async fn send<S: AsyncRead + AsyncWrite + Unpin>(framed: &mut Framed<S, LengthDelimitedCodec>, message: &proto::Message) -> Result<()> {
+    let data = bincode::serialize(message)?;
+    framed.send(Bytes::from(data)).await?;
+    Ok(())
+}
+
+/// Encode messages as the peer negotiated and write them to a stream
+async fn send_node_messages<W: futures_util::Sink<Bytes, Error = std::io::Error> + Unpin>(
+    sink: &mut W,
+    wire: &proto::Wire,
+    recipient: proto::EntityId,
+    messages: Vec<proto::NodeMessage>,
+) -> Result<()> {
+    for frame in wire.encode_all(messages)? {
+        ankurah_core::metrics::bytes_sent(recipient, frame.len());
+        sink.feed(frame.into()).await?;
+    }
+    sink.flush().await?;
+    Ok(())
+}
+
+fn handle_peer_message(node: &dyn NodeComms, peer: &str, message: proto::NodeMessage) {
+    let node = node.cloned();
+    let peer = peer.to_string();
+    tokio::spawn(async move {
+        if let Err(e) = node.handle_message(message).await {
+            warn!("Error handling message from {}: {}", peer, e);
+        }
+    });
+}
+
+async fn next_frame(control: &mut Option<Framed<SessionStream, LengthDelimitedCodec>>) -> Option<std::io::Result<BytesMut>> {
+    match control {
+        Some(framed) => framed.next().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Drive one QUIC connection from its WebTransport session's CONNECT until either end closes it
+///
+/// Returns `Ok` when the client closes the session or connection and an error when the peer is
+/// refused, refuses us, or breaks the protocol.
+pub(crate) async fn run(node: &dyn NodeComms, connection: quinn::Connection, peer: &str) -> Result<()> {
+    let mut h3: H3Connection = h3::server::builder()
+        .enable_webtransport(true)
+        .enable_extended_connect(true)
+        .enable_datagram(true)
+        .max_webtransport_sessions(1)
+        .build(h3_quinn::Connection::new(connection.clone()))
+        .await?;
+
+    let (mut connect, session_id) = tokio::time::timeout(INITIAL_PRESENCE_TIMEOUT, accept_session(&mut h3, peer))
+        .await
+        .map_err(|_| anyhow!("{} did not open a session within {:?}", peer, INITIAL_PRESENCE_TIMEOUT))??;
+    let session = SessionId::try_from(session_id).map_err(|_| anyhow!("invalid session id {}", session_id))?;
+    debug!("Opened WebTransport session {} with {}", session_id, peer);
+
+    let initial_presence_deadline = tokio::time::Instant::now() + INITIAL_PRESENCE_TIMEOUT;
+    let (streams_tx, mut streams_rx) = mpsc::unbounded_channel();
+    let mut control: Option<Framed<SessionStream, LengthDelimitedCodec>> = None;
+    let mut peer_sender: Option<WebTransportPeerSender> = None;
+    let mut updates: Option<tokio::task::JoinHandle<()>> = None;
+    let mut wire = proto::Wire::default();
+
+    let result = loop {
+        select! {
+            _ = tokio::time::sleep_until(initial_presence_deadline), if peer_sender.is_none() => {
+                break Err(anyhow!("{} did not send presence within {:?}", peer, INITIAL_PRESENCE_TIMEOUT));
+            }
+            _ = connection.closed() => {
+                debug!("Connection to {} closed", peer);
+                break Ok(());
+            }
+            closing = connect.recv_data() => {
+                // The session lives as long as its CONNECT stream; the client ends it by closing that
+                if !matches!(closing, Ok(Some(_))) {
+                    debug!("{} closed the session", peer);
+                    break Ok(());
+                }
+            }
+            accepted = poll_fn(|cx| h3.poll_accept_request_stream(cx)) => match accepted {
+                Ok(Some(stream)) => {
+                    let streams_tx = streams_tx.clone();
+                    tokio::spawn(async move {
+                        if let Some(stream) = session_stream(stream, session).await {
+                            let _ = streams_tx.send(stream);
+                        }
+                    });
+                }
+                Ok(None) => break Ok(()),
+                Err(e) => break Err(e.into()),
+            },
+            Some(stream) = streams_rx.recv() => {
+                if control.is_none() {
+                    let mut stream = framed(stream);
+                    if let Err(e) = send(&mut stream, &proto::Message::Presence(presence(node))).await {
+                        break Err(e);
+                    }
+                    debug!("Sent presence to {}", peer);
+                    control = Some(stream);
+                } else if let Some(sender) = &peer_sender {
+                    tokio::spawn(serve_request(node.cloned(), framed(stream), wire, sender.clone(), peer.to_string()));
+                } else {
+                    // Negotiation has not admitted this peer, so nothing it says may reach the node
+                    break Err(anyhow!("{} opened a request stream before presence negotiation completed", peer));
+                }
+            }
+            frame = next_frame(&mut control) => {
+                let frame = match frame {
+                    Some(Ok(frame)) => frame,
+                    Some(Err(e)) => break Err(e.into()),
+                    None => {
+                        debug!("{} closed the control stream", peer);
+                        break Ok(());
+                    }
+                };
+                debug!(">>> {} sent {} bytes", peer, frame.len());
+                if let Some(sender) = &peer_sender {
+                    ankurah_core::metrics::bytes_received(sender.recipient_node_id(), frame.len());
+                }
+
+                match wire.decode(&frame) {
+                    Ok(proto::Decoded::Presence(presence)) => {
+                        if peer_sender.is_some() {
+                            warn!("Received presence from {} but already have a peer sender - ignoring", peer);
+                            continue;
+                        }
+                        // Pre-check the version while we can still reply on the control
+                        // stream; register_peer re-enforces this for every transport.
+                        let registered = proto::Wire::negotiate(&presence).and_then(|negotiated| {
+                            let (sender, rx) = WebTransportPeerSender::new(presence.node_id, connection.clone(), session_id, negotiated);
+                            node.register_peer(presence.clone(), Box::new(sender.clone())).map(|_| (negotiated, sender, rx))
+                        });
+                        match registered {
+                            Ok((negotiated, sender, rx)) => {
+                                debug!("Registered peer {} at {} speaking protocol v{}", presence.node_id, peer, negotiated.version);
+                                updates = Some(tokio::spawn(send_updates(connection.clone(), session_id, negotiated, presence.node_id, rx)));
+                                peer_sender = Some(sender);
+                                wire = negotiated;
+                            }
+                            Err(rejection) => {
+                                let reason = rejection.to_string();
+                                let control = control.as_mut().expect("frames come from the control stream");
+                                let _ = send(control, &proto::Message::PresenceRejected(rejection)).await;
+                                break Err(anyhow!("refused {}: {}", peer, reason));
+                            }
+                        }
+                    }
+                    Ok(proto::Decoded::PresenceRejected(rejection)) => {
+                        break Err(anyhow!("{} refused our presence: {}", peer, rejection));
+                    }
+                    Ok(proto::Decoded::PeerMessage(_) | proto::Decoded::Batch(_)) if peer_sender.is_none() => {
+                        break Err(anyhow!("{} sent application traffic before presence negotiation completed", peer));
+                    }
+                    Ok(proto::Decoded::PeerMessage(message)) => handle_peer_message(node, peer, message),
+                    Ok(proto::Decoded::Batch(messages)) => {
+                        for message in messages {
+                            handle_peer_message(node, peer, message);
+                        }
+                    }
+                    Ok(proto::Decoded::Goodbye(goodbye)) => {
+                        info!("{} is closing the session: {}", peer, goodbye);
+                        break Ok(());
+                    }
+                    Err(e) => {
+                        if peer_sender.is_none() {
+                            // A handshake we cannot read will never establish; close
+                            // instead of idling on a dead session.
+                            if proto::is_version0_presence(&frame) {
+                                break Err(anyhow!("{} speaks a pre-versioning (0.9.x or older) protocol", peer));
+                            }
+                            break Err(anyhow!("failed to deserialize handshake message from {}: {}", peer, e));
+                        }
+                        warn!("Failed to deserialize message from {}: {}", peer, e);
+                    }
+                }
+            }
+        }
+    };
+
+    if let Some(updates) = updates {
+        updates.abort();
+    }
+    if let Some(sender) = peer_sender {
+        node.deregister_peer(sender.recipient_node_id());
+        debug!("Deregistered peer {}", sender.recipient_node_id());
+    }
+    if let Some(mut control) = control {
+        let _ = SinkExt::<Bytes>::close(&mut control).await;
+    }
+    let _ = tokio::time::timeout(CLOSE_GRACE_PERIOD, connection.closed()).await;
+    connection.close(0u32.into(), b"");
+    result
+}
+
+fn presence(node: &dyn NodeComms) -> proto::Presence {
+    proto::Presence {
+        node_id: node.id(),
+        durable: node.durable(),
+        system_root: node.system_root(),
+        wire: proto::WireSupport::all(),
+        features: proto::protocol_features(),
+        min_protocol_version: proto::MIN_PROTOCOL_VERSION,
+        protocol_version: proto::PROTOCOL_VERSION,
+    }
+}
+
+/// Serve HTTP/3 requests until one opens a WebTransport session at [`SESSION_PATH`], returning
+/// the session's CONNECT stream and id
+async fn accept_session(h3: &mut H3Connection, peer: &str) -> Result<(RequestStream<h3_quinn::BidiStream<Bytes>, Bytes>, u64)> {
+    loop {
+        let Some(stream) = poll_fn(|cx| h3.poll_accept_request_stream(cx)).await? else {
+            bail!("{} closed the connection before opening a session", peer);
+        };
+        let mut frames = FrameStream::new(BufRecvStream::new(stream));
+        let frame = poll_fn(|cx| frames.poll_next(cx)).await;
+        if !matches!(frame, Ok(Some(Frame::Headers(_)))) {
+            // A stream of a session that is not open; dropping it resets it
+            continue;
+        }
+        let (request, mut stream) = h3.create_resolver(frames).accept_with_frame(frame)?.resolve().await?;
+
+        let opens_session = request.method() == Method::CONNECT && request.extensions().get::<Protocol>() == Some(&Protocol::WEB_TRANSPORT);
+        if opens_session && request.uri().path() == SESSION_PATH {
+            let response = Response::builder().status(StatusCode::OK).header("sec-webtransport-http3-draft", "draft02").body(())?;
+            stream.send_response(response).await?;
+            let session_id = stream.id().into_inner();
+            return Ok((stream, session_id));
+        }
+
+        debug!("Refusing {} {} from {}", request.method(), request.uri(), peer);
+        let response = Response::builder().status(StatusCode::NOT_FOUND).body(())?;
+        if stream.send_response(response).await.is_ok() {
+            let _ = stream.finish().await;
+        }
+    }
+}
+
+/// Read the WebTransport header of a bidirectional stream, yielding the stream when it belongs to `session`
+async fn session_stream(stream: h3_quinn::BidiStream<Bytes>, session: SessionId) -> Option<SessionStream> {
+    let mut frames = FrameStream::<_, Bytes>::new(BufRecvStream::new(stream));
+    match poll_fn(|cx| frames.poll_next(cx)).await {
+        Ok(Some(Frame::WebTransportStream(id))) if id == session => Some(frames.into_inner()),
+        // Requests once the session is open, or streams of another session; dropping the stream resets it
+        _ => None,
+    }
+}
+
+/// Hand the request a client opened `stream` for to the node, and send the response back on it
+async fn serve_request(
+    node: Box<dyn NodeComms>,
+    mut stream: Framed<SessionStream, LengthDelimitedCodec>,
+    wire: proto::Wire,
+    sender: WebTransportPeerSender,
+    peer: String,
+) {
+    let recipient = sender.recipient_node_id();
+    let frame = match stream.next().await {
+        Some(Ok(frame)) => frame,
+        Some(Err(e)) => {
+            warn!("Failed to read request stream from {}: {}", peer, e);
+            return;
+        }
+        None => return,
+    };
+    ankurah_core::metrics::bytes_received(recipient, frame.len());
+
+    let messages = match wire.decode(&frame) {
+        Ok(proto::Decoded::PeerMessage(message)) => vec![message],
+        Ok(proto::Decoded::Batch(messages)) => messages,
+        Ok(_) => {
+            warn!("Received a session message on a request stream from {}", peer);
+            return;
+        }
+        Err(e) => {
+            warn!("Failed to deserialize request from {}: {}", peer, e);
+            return;
+        }
+    };
+    let mut responses = Vec::new();
+    for message in messages {
+        if let proto::NodeMessage::Request { request, .. } = &message {
+            responses.push(sender.await_response(request.id.clone()));
+        }
+        handle_peer_message(node.as_ref(), &peer, message);
+    }
+    for response in responses {
+        // The responder is dropped if the session closes before the node answers
+        let Ok(response) = response.await else { return };
+        if let Err(e) = send_node_messages(&mut stream, &wire, recipient, vec![response]).await {
+            warn!("Failed to send response to {}: {}", peer, e);
+            return;
+        }
+    }
+    let _ = SinkExt::<Bytes>::close(&mut stream).await;
+}
+
+/// Open a unidirectional stream of the session
+async fn open_uni(connection: &quinn::Connection, session_id: u64) -> Result<FramedWrite<quinn::SendStream, LengthDelimitedCodec>> {
+    let mut stream = connection.open_uni().await?;
+    let mut header = Vec::with_capacity(16);
+    encode_varint(WEBTRANSPORT_UNI_STREAM, &mut header);
+    encode_varint(session_id, &mut header);
+    stream.write_all(&header).await?;
+    Ok(FramedWrite::new(stream, codec()))
+}
+
+/// Send one message on a unidirectional stream of its own
+pub(crate) async fn send_on_own_stream(
+    connection: &quinn::Connection,
+    session_id: u64,
+    wire: &proto::Wire,
+    recipient: proto::EntityId,
+    message: proto::NodeMessage,
+) -> Result<()> {
+    let mut stream = open_uni(connection, session_id).await?;
+    send_node_messages(&mut stream, wire, recipient, vec![message]).await?;
+    stream.into_inner().finish()?;
+    Ok(())
+}
+
+/// Send subscription updates, in order, on the session's updates stream, batching whatever is
+/// already queued behind each one
+async fn send_updates(
+    connection: quinn::Connection,
+    session_id: u64,
+    wire: proto::Wire,
+    recipient: proto::EntityId,
+    mut rx: mpsc::UnboundedReceiver<proto::NodeMessage>,
+) {
+    let result = async {
+        let mut stream = open_uni(&connection, session_id).await?;
+        while let Some(first) = rx.recv().await {
+            let mut batch = vec![first];
+            while batch.len() < proto::wire::MAX_BATCH_LEN {
+                match rx.try_recv() {
+                    Ok(message) => batch.push(message),
+                    Err(_) => break,
+                }
+            }
+            send_node_messages(&mut stream, &wire, recipient, batch).await?;
+        }
+        anyhow::Ok(())
+    };
+    if let Err(e) = result.await {
+        warn!("Updates stream to {} failed: {}", recipient, e);
+    }
+}
+
+/// Append `value` as a QUIC variable-length integer (RFC 9000, section 16)
+fn encode_varint(value: u64, buf: &mut Vec<u8>) {
+    match value {
+        0..=0x3f => buf.push(value as u8),
+        0x40..=0x3fff => buf.extend_from_slice(&(0x4000 | value as u16).to_be_bytes()),
+        0x4000..=0x3fff_ffff => buf.extend_from_slice(&(0x8000_0000 | value as u32).to_be_bytes()),
+        _ => buf.extend_from_slice(&(0xc000_0000_0000_0000 | value).to_be_bytes()),
+    }
+}
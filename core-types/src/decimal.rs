@@ -0,0 +1,370 @@
+use serde::{Deserialize, Serialize};
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+use crate::ScalarParseError;
+
+/// An exact base-10 number, for money and other quantities `f64` would round.
+///
+/// The value is `mantissa × 10^-scale`, kept normalized without trailing
+/// fractional zeros so that equal numbers are equal values (`1.50` is stored as
+/// `1.5`). The `i128` mantissa holds 38 significant digits. The text form is
+/// plain decimal notation such as `-1234.5`; parsing also accepts an exponent
+/// (`1.5e3`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Decimal {
+    mantissa: i128,
+    scale: u32,
+}
+
+impl Decimal {
+    /// Zero.
+    pub const ZERO: Self = Self { mantissa: 0, scale: 0 };
+
+    /// Construct `mantissa × 10^-scale`.
+    pub fn new(mut mantissa: i128, mut scale: u32) -> Self {
+        if mantissa == 0 {
+            return Self::ZERO;
+        }
+        while scale > 0 && mantissa % 10 == 0 {
+            mantissa /= 10;
+            scale -= 1;
+        }
+        Self { mantissa, scale }
+    }
+
+    /// The normalized mantissa.
+    pub fn mantissa(&self) -> i128 { self.mantissa }
+
+    /// The number of digits after the decimal point.
+    pub fn scale(&self) -> u32 { self.scale }
+
+    /// Whether this is zero.
+    pub fn is_zero(&self) -> bool { self.mantissa == 0 }
+
+    /// Whether this is less than zero.
+    pub fn is_sign_negative(&self) -> bool { self.mantissa < 0 }
+
+    /// The significant digits of the magnitude and its decimal exponent, such
+    /// that the magnitude is `0.<digits> × 10^exponent`.
+    ///
+    /// The digits have no leading or trailing zeros, so for numbers of the same
+    /// sign, comparing exponents and then digit strings orders magnitudes.
+    /// Zero has no digits and exponent 0.
+    pub fn digits(&self) -> (String, i64) {
+        if self.mantissa == 0 {
+            return (String::new(), 0);
+        }
+        let digits = self.mantissa.unsigned_abs().to_string();
+        let exponent = digits.len() as i64 - i64::from(self.scale);
+        (digits.trim_end_matches('0').to_owned(), exponent)
+    }
+
+    /// The whole number this decimal equals, if it is integral and in range.
+    pub fn to_i64(&self) -> Option<i64> { self.to_i128().and_then(|n| i64::try_from(n).ok()) }
+
+    /// The whole number this decimal equals, if it is integral.
+    pub fn to_i128(&self) -> Option<i128> {
+        if self.scale == 0 {
+            Some(self.mantissa)
+        } else {
+            None
+        }
+    }
+
+    /// The nearest `f64`.
+    pub fn to_f64(&self) -> f64 { self.to_string().parse().unwrap_or(f64::NAN) }
+}
+
+impl Default for Decimal {
+    fn default() -> Self { Self::ZERO }
+}
+
+macro_rules! decimal_from_integer {
+    ($($type:ty),*) => {$(
+        impl From<$type> for Decimal {
+            fn from(value: $type) -> Self { Self::new(value.into(), 0) }
+        }
+    )*};
+}
+
+decimal_from_integer!(i16, i32, i64, u32, u64, i128);
+
+/// Converts through the shortest decimal text that round-trips the float, so
+/// `0.1` becomes exactly `0.1`. Non-finite floats and magnitudes past 38
+/// digits are rejected.
+impl TryFrom<f64> for Decimal {
+    type Error = ScalarParseError;
+
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        let text = value.to_string();
+        if !value.is_finite() {
+            return Err(ScalarParseError::new("decimal", &text));
+        }
+        text.parse()
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let by_sign = self.mantissa.signum().cmp(&other.mantissa.signum());
+        if by_sign != Ordering::Equal || self.mantissa == 0 {
+            return by_sign;
+        }
+        if self.scale == other.scale {
+            return self.mantissa.cmp(&other.mantissa);
+        }
+        let ((digits, exponent), (other_digits, other_exponent)) = (self.digits(), other.digits());
+        let by_magnitude = exponent.cmp(&other_exponent).then_with(|| digits.cmp(&other_digits));
+        if self.mantissa < 0 {
+            by_magnitude.reverse()
+        } else {
+            by_magnitude
+        }
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.mantissa < 0 {
+            f.write_str("-")?;
+        }
+        let digits = self.mantissa.unsigned_abs().to_string();
+        let scale = self.scale as usize;
+        if scale == 0 {
+            f.write_str(&digits)
+        } else if digits.len() > scale {
+            let (integer, fraction) = digits.split_at(digits.len() - scale);
+            write!(f, "{integer}.{fraction}")
+        } else {
+            write!(f, "0.{}{digits}", "0".repeat(scale - digits.len()))
+        }
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = ScalarParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ScalarParseError::new("decimal", s);
+        let (body, exponent) = match s.split_once(['e', 'E']) {
+            Some((body, exponent)) => (body, exponent.parse::<i64>().map_err(|_| invalid())?),
+            None => (s, 0),
+        };
+        let (negative, body) = match body.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, body.strip_prefix('+').unwrap_or(body)),
+        };
+        let (integer, fraction) = body.split_once('.').unwrap_or((body, ""));
+        if (integer.is_empty() && fraction.is_empty()) || !integer.bytes().chain(fraction.bytes()).all(|byte| byte.is_ascii_digit()) {
+            return Err(invalid());
+        }
+
+        let fraction = fraction.trim_end_matches('0');
+        let mut mantissa: i128 = 0;
+        for digit in integer.bytes().chain(fraction.bytes()) {
+            mantissa = mantissa.checked_mul(10).and_then(|m| m.checked_add(i128::from(digit - b'0'))).ok_or_else(invalid)?;
+        }
+        if mantissa == 0 {
+            return Ok(Self::ZERO);
+        }
+        let mut scale = (fraction.len() as i64).checked_sub(exponent).ok_or_else(invalid)?;
+        while scale < 0 {
+            mantissa = mantissa.checked_mul(10).ok_or_else(invalid)?;
+            scale += 1;
+        }
+        let scale = u32::try_from(scale).map_err(|_| invalid())?;
+        Ok(Self::new(if negative { -mantissa } else { mantissa }, scale))
+    }
+}
+
+impl Serialize for Decimal {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: serde::Serializer {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            (self.mantissa, self.scale).serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Decimal {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: serde::Deserializer<'de> {
+        if deserializer.is_human_readable() {
+            String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+        } else {
+            let (mantissa, scale) = <(i128, u32)>::deserialize(deserializer)?;
+            Ok(Self::new(mantissa, scale))
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+mod wasm {
+    use super::Decimal;
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen]
+    extern "C" {
+        #[wasm_bindgen(typescript_type = "string")]
+        pub type JsDecimal;
+    }
+
+    // JavaScript numbers would round, so decimals cross as their text
+    impl From<Decimal> for JsValue {
+        fn from(decimal: Decimal) -> Self { JsValue::from_str(&decimal.to_string()) }
+    }
+
+    impl TryFrom<JsValue> for Decimal {
+        type Error = JsValue;
+
+        fn try_from(value: JsValue) -> Result<Self, Self::Error> { value.as_string().and_then(|text| text.parse().ok()).ok_or(value) }
+    }
+
+    crate::wasm_abi_via_js!(Decimal, JsDecimal);
+}
+
+#[cfg(feature = "uniffi")]
+::uniffi::custom_type!(Decimal, String, {
+    lower: |decimal| decimal.to_string(),
+    try_lift: |text| text.parse().map_err(Into::into),
+});
+
+#[cfg(feature = "postgres")]
+mod postgres {
+    use super::Decimal;
+    use bytes::{Buf, BufMut, BytesMut};
+    use postgres_types::{FromSql, IsNull, ToSql, Type};
+    use std::error::Error;
+
+    const NUMERIC_POSITIVE: u16 = 0x0000;
+    const NUMERIC_NEGATIVE: u16 = 0x4000;
+
+    // The binary numeric format is a run of base-10000 digits, the power of
+    // 10000 of the first one (its weight), a sign word and the display scale.
+    impl ToSql for Decimal {
+        fn to_sql(&self, _: &Type, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+            let digits = self.mantissa.unsigned_abs().to_string();
+            let scale = self.scale as usize;
+            let (integer, fraction) = if digits.len() > scale {
+                let (integer, fraction) = digits.split_at(digits.len() - scale);
+                (integer.to_owned(), fraction.to_owned())
+            } else {
+                (String::new(), "0".repeat(scale - digits.len()) + &digits)
+            };
+            // Pad both sides of the point out to whole base-10000 digits
+            let integer = "0".repeat((4 - integer.len() % 4) % 4) + &integer;
+            let padding = "0".repeat((4 - fraction.len() % 4) % 4);
+            let fraction = fraction + &padding;
+            let mut groups: Vec<i16> = format!("{integer}{fraction}")
+                .as_bytes()
+                .chunks(4)
+                .map(|chunk| chunk.iter().fold(0, |total, digit| total * 10 + i16::from(digit - b'0')))
+                .collect();
+
+            let leading_zeros = groups.iter().take_while(|group| **group == 0).count();
+            groups.drain(..leading_zeros);
+            while groups.last() == Some(&0) {
+                groups.pop();
+            }
+            let weight = if groups.is_empty() { 0 } else { (integer.len() / 4) as i64 - 1 - leading_zeros as i64 };
+
+            out.put_i16(i16::try_from(groups.len())?);
+            out.put_i16(i16::try_from(weight)?);
+            out.put_u16(if self.mantissa < 0 { NUMERIC_NEGATIVE } else { NUMERIC_POSITIVE });
+            out.put_u16(u16::try_from(self.scale)?);
+            for group in groups {
+                out.put_i16(group);
+            }
+            Ok(IsNull::No)
+        }
+
+        fn accepts(ty: &Type) -> bool { *ty == Type::NUMERIC }
+
+        postgres_types::to_sql_checked!();
+    }
+
+    impl<'a> FromSql<'a> for Decimal {
+        fn from_sql(_: &Type, mut raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+            let count = raw.try_get_i16()?;
+            let weight = i64::from(raw.try_get_i16()?);
+            let sign = raw.try_get_u16()?;
+            let _display_scale = raw.try_get_u16()?;
+            let groups = (0..count).map(|_| raw.try_get_i16()).collect::<Result<Vec<_>, _>>()?;
+            let negative = match sign {
+                NUMERIC_POSITIVE => false,
+                NUMERIC_NEGATIVE => true,
+                _ => return Err("NaN and infinite numerics have no Decimal value".into()),
+            };
+
+            let group = |index: i64| if index < 0 { 0 } else { groups.get(index as usize).copied().unwrap_or(0) };
+            let mut text = String::from(if negative { "-" } else { "" });
+            if weight < 0 {
+                text.push('0');
+            }
+            for index in 0..=weight {
+                text += &format!("{:04}", group(index));
+            }
+            text.push('.');
+            for index in (weight + 1)..i64::from(count) {
+                text += &format!("{:04}", group(index));
+            }
+            Ok(text.parse()?)
+        }
+
+        fn accepts(ty: &Type) -> bool { *ty == Type::NUMERIC }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decimal(text: &str) -> Decimal { text.parse().unwrap() }
+
+    #[test]
+    fn parsing_normalizes_and_display_round_trips() {
+        assert_eq!(decimal("1.50"), Decimal::new(15, 1));
+        assert_eq!(decimal("1.50").to_string(), "1.5");
+        assert_eq!(decimal("-0.0012").to_string(), "-0.0012");
+        assert_eq!(decimal("+1200").to_string(), "1200");
+        assert_eq!(decimal("1.5e3").to_string(), "1500");
+        assert_eq!(decimal("15E-4").to_string(), "0.0015");
+        assert_eq!(decimal("-0.000"), Decimal::ZERO);
+        assert_eq!(decimal(".5"), decimal("0.5"));
+        for invalid in ["", ".", "1.2.3", "1e", "abc", "1_000", "123456789012345678901234567890123456789012"] {
+            assert!(invalid.parse::<Decimal>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn ordering_is_numeric_across_scales() {
+        let ascending = ["-100", "-2.5", "-2.25", "-0.001", "0", "0.001", "0.01", "0.1", "1", "1.05", "1.5", "10", "99.99", "100"];
+        for pair in ascending.windows(2) {
+            assert!(decimal(pair[0]) < decimal(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+    }
+
+    #[test]
+    fn converts_from_numbers_exactly() {
+        assert_eq!(Decimal::try_from(0.1).unwrap(), decimal("0.1"));
+        assert_eq!(Decimal::from(-42_i64).to_i64(), Some(-42));
+        assert_eq!(decimal("4.2").to_i64(), None);
+        assert_eq!(decimal("4.25").to_f64(), 4.25);
+        assert!(Decimal::try_from(f64::NAN).is_err());
+        assert!(Decimal::try_from(1e300).is_err());
+    }
+
+    #[test]
+    fn json_and_bincode_encodings_are_pinned() {
+        let price = decimal("19.99");
+        assert_eq!(serde_json::to_string(&price).unwrap(), "\"19.99\"");
+        assert_eq!(serde_json::from_str::<Decimal>("\"19.990\"").unwrap(), price);
+        assert_eq!(bincode::deserialize::<Decimal>(&bincode::serialize(&price).unwrap()).unwrap(), price);
+    }
+}
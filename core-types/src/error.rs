@@ -59,3 +59,18 @@ impl From<DecodeError> for IdParseError {
         }
     }
 }
+
+/// A failure to parse the text form of a [`Timestamp`](crate::Timestamp),
+/// [`Date`](crate::Date), [`Decimal`](crate::Decimal), or [`Uuid`](crate::Uuid).
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("invalid {ty} '{value}'")]
+pub struct ScalarParseError {
+    /// The type that was being parsed.
+    pub ty: &'static str,
+    /// The rejected input.
+    pub value: String,
+}
+
+impl ScalarParseError {
+    pub(crate) fn new(ty: &'static str, value: &str) -> Self { Self { ty, value: value.to_owned() } }
+}
@@ -6,16 +6,64 @@
 #[cfg(feature = "uniffi")]
 uniffi::setup_scaffolding!();
 
+/// Implement the wasm-bindgen ABI traits for a value type through its
+/// `JsValue` conversions, so it can cross as a field getter or argument typed
+/// in TypeScript by `$js_type`.
+#[cfg(feature = "wasm")]
+macro_rules! wasm_abi_via_js {
+    ($type:ty, $js_type:ty) => {
+        impl wasm_bindgen::describe::WasmDescribe for $type {
+            fn describe() { <$js_type as wasm_bindgen::describe::WasmDescribe>::describe() }
+        }
+
+        impl wasm_bindgen::convert::IntoWasmAbi for $type {
+            type Abi = <$js_type as wasm_bindgen::convert::IntoWasmAbi>::Abi;
+
+            fn into_abi(self) -> Self::Abi {
+                <$js_type as wasm_bindgen::JsCast>::unchecked_from_js(wasm_bindgen::JsValue::from(self)).into_abi()
+            }
+        }
+
+        impl wasm_bindgen::convert::OptionIntoWasmAbi for $type {
+            fn none() -> Self::Abi { <$js_type as wasm_bindgen::convert::OptionIntoWasmAbi>::none() }
+        }
+
+        impl wasm_bindgen::convert::FromWasmAbi for $type {
+            type Abi = <$js_type as wasm_bindgen::convert::FromWasmAbi>::Abi;
+
+            unsafe fn from_abi(js: Self::Abi) -> Self {
+                let value: wasm_bindgen::JsValue = <$js_type as wasm_bindgen::convert::FromWasmAbi>::from_abi(js).into();
+                match Self::try_from(value) {
+                    Ok(value) => value,
+                    Err(_) => wasm_bindgen::throw_str(concat!("invalid ", stringify!($type))),
+                }
+            }
+        }
+
+        impl wasm_bindgen::convert::OptionFromWasmAbi for $type {
+            fn is_none(abi: &Self::Abi) -> bool { <$js_type as wasm_bindgen::convert::OptionFromWasmAbi>::is_none(abi) }
+        }
+    };
+}
+#[cfg(feature = "wasm")]
+pub(crate) use wasm_abi_via_js;
+
+mod decimal;
 mod entity_id;
 mod error;
 mod model_id;
 mod property_id;
+mod temporal;
+mod uuid;
 mod value;
 mod value_type;
 
+pub use decimal::Decimal;
 pub use entity_id::EntityId;
-pub use error::{DecodeError, IdParseError};
+pub use error::{DecodeError, IdParseError, ScalarParseError};
 pub use model_id::{ModelId, SystemModel};
 pub use property_id::{PropertyId, SystemProperty};
+pub use temporal::{Date, Timestamp};
+pub use uuid::Uuid;
 pub use value::{CastError, Value, ValueParseError};
 pub use value_type::ValueType;
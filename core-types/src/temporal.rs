@@ -0,0 +1,493 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    str::FromStr,
+    time::{Duration, SystemTime},
+};
+
+use crate::ScalarParseError;
+
+const MICROS_PER_SECOND: i64 = 1_000_000;
+const MICROS_PER_DAY: i64 = 86_400 * MICROS_PER_SECOND;
+
+/// An instant in UTC with microsecond precision.
+///
+/// Stored as microseconds since 1970-01-01T00:00:00Z, the resolution of
+/// PostgreSQL's `timestamptz`. The text form is RFC 3339 in UTC, e.g.
+/// `2024-05-06T07:08:09.123456Z`. Parsing also accepts a numeric offset, which
+/// is folded into the instant, a missing offset (read as UTC), and a bare date
+/// (read as midnight UTC). Digits beyond microseconds are truncated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Timestamp(i64);
+
+impl Timestamp {
+    /// 1970-01-01T00:00:00Z.
+    pub const UNIX_EPOCH: Self = Self(0);
+
+    /// Construct an instant from microseconds since the Unix epoch.
+    pub const fn from_micros(micros: i64) -> Self { Self(micros) }
+
+    /// Return microseconds since the Unix epoch.
+    pub const fn as_micros(self) -> i64 { self.0 }
+
+    /// Construct an instant from milliseconds since the Unix epoch, saturating
+    /// at the representable range.
+    pub const fn from_millis(millis: i64) -> Self { Self(millis.saturating_mul(1000)) }
+
+    /// Return whole milliseconds since the Unix epoch, rounding toward the past.
+    pub const fn as_millis(self) -> i64 { self.0.div_euclid(1000) }
+
+    /// The current instant.
+    #[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+    pub fn now() -> Self { Self::from(SystemTime::now()) }
+
+    /// The current instant.
+    #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+    pub fn now() -> Self { Self((js_sys::Date::now() * 1000.0) as i64) }
+
+    /// The UTC calendar date containing this instant.
+    pub fn date(self) -> Date { Date(self.0.div_euclid(MICROS_PER_DAY) as i32) }
+}
+
+impl From<SystemTime> for Timestamp {
+    fn from(time: SystemTime) -> Self {
+        let micros = match time.duration_since(SystemTime::UNIX_EPOCH) {
+            Ok(after) => i64::try_from(after.as_micros()).unwrap_or(i64::MAX),
+            Err(before) => {
+                let before = before.duration();
+                // Round toward the past so sub-microsecond instants stay ordered
+                let partial = i128::from(before.subsec_nanos() % 1000 != 0);
+                i64::try_from(-(before.as_micros() as i128) - partial).unwrap_or(i64::MIN)
+            }
+        };
+        Self(micros)
+    }
+}
+
+impl From<Timestamp> for SystemTime {
+    fn from(timestamp: Timestamp) -> Self {
+        let offset = Duration::from_micros(timestamp.0.unsigned_abs());
+        if timestamp.0 >= 0 {
+            SystemTime::UNIX_EPOCH + offset
+        } else {
+            SystemTime::UNIX_EPOCH - offset
+        }
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let micros_of_day = self.0.rem_euclid(MICROS_PER_DAY);
+        write_date(f, self.0.div_euclid(MICROS_PER_DAY))?;
+        let seconds = micros_of_day / MICROS_PER_SECOND;
+        write!(f, "T{:02}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)?;
+        match micros_of_day % MICROS_PER_SECOND {
+            0 => {}
+            fraction => write!(f, ".{fraction:06}")?,
+        }
+        f.write_str("Z")
+    }
+}
+
+impl FromStr for Timestamp {
+    type Err = ScalarParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_timestamp(&mut Cursor::new(s)).map(Self).ok_or_else(|| ScalarParseError::new("timestamp", s))
+    }
+}
+
+/// A calendar date with no time of day or zone.
+///
+/// Stored as days since 1970-01-01 in the proleptic Gregorian calendar. The
+/// text form is ISO 8601, `YYYY-MM-DD`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Date(i32);
+
+impl Date {
+    /// 1970-01-01.
+    pub const UNIX_EPOCH: Self = Self(0);
+
+    /// Construct a date from days since 1970-01-01.
+    pub const fn from_days_since_epoch(days: i32) -> Self { Self(days) }
+
+    /// Return days since 1970-01-01.
+    pub const fn days_since_epoch(self) -> i32 { self.0 }
+
+    /// Construct a date from its year, month (1-12) and day of month, or
+    /// `None` if no such day exists.
+    pub fn from_ymd(year: i32, month: u32, day: u32) -> Option<Self> {
+        if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year.into(), month) {
+            return None;
+        }
+        i32::try_from(days_from_civil(year.into(), month, day)).ok().map(Self)
+    }
+
+    /// Return the year, month (1-12) and day of month.
+    pub fn ymd(self) -> (i32, u32, u32) {
+        let (year, month, day) = civil_from_days(self.0.into());
+        (year as i32, month, day)
+    }
+
+    /// The first instant of this date in UTC, or `None` if it lies beyond the
+    /// range of [`Timestamp`].
+    pub fn at_midnight(self) -> Option<Timestamp> { i64::from(self.0).checked_mul(MICROS_PER_DAY).map(Timestamp) }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write_date(f, self.0.into()) }
+}
+
+impl FromStr for Date {
+    type Err = ScalarParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut cursor = Cursor::new(s);
+        parse_date(&mut cursor)
+            .filter(|_| cursor.is_done())
+            .and_then(|days| i32::try_from(days).ok())
+            .map(Self)
+            .ok_or_else(|| ScalarParseError::new("date", s))
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: serde::Serializer {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            self.0.serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: serde::Deserializer<'de> {
+        if deserializer.is_human_readable() {
+            String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+        } else {
+            Ok(Self(i64::deserialize(deserializer)?))
+        }
+    }
+}
+
+impl Serialize for Date {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: serde::Serializer {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            self.0.serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Date {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: serde::Deserializer<'de> {
+        if deserializer.is_human_readable() {
+            String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+        } else {
+            Ok(Self(i32::deserialize(deserializer)?))
+        }
+    }
+}
+
+fn is_leap_year(year: i64) -> bool { year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) }
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Howard Hinnant's days_from_civil / civil_from_days, valid across the whole
+// proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = i64::from(month);
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Years outside 0000-9999 use the ISO 8601 expanded form, e.g. `+10000-01-01`.
+fn write_date(f: &mut fmt::Formatter<'_>, days: i64) -> fmt::Result {
+    let (year, month, day) = civil_from_days(days);
+    if (0..=9999).contains(&year) {
+        write!(f, "{year:04}-{month:02}-{day:02}")
+    } else {
+        write!(f, "{year:+05}-{month:02}-{day:02}")
+    }
+}
+
+struct Cursor<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(input: &'a str) -> Self { Self { bytes: input.as_bytes(), position: 0 } }
+
+    fn is_done(&self) -> bool { self.position == self.bytes.len() }
+
+    fn eat(&mut self, options: &[u8]) -> Option<u8> {
+        let byte = *self.bytes.get(self.position).filter(|byte| options.contains(byte))?;
+        self.position += 1;
+        Some(byte)
+    }
+
+    /// Consume a run of at least `min` and at most `max` ASCII digits.
+    fn digits(&mut self, min: usize, max: usize) -> Option<&'a [u8]> {
+        let rest = &self.bytes[self.position..];
+        let count = rest.iter().take(max).take_while(|byte| byte.is_ascii_digit()).count();
+        if count < min {
+            return None;
+        }
+        self.position += count;
+        Some(&rest[..count])
+    }
+
+    fn number(&mut self, width: usize) -> Option<i64> { self.digits(width, width).map(to_number) }
+}
+
+fn to_number(digits: &[u8]) -> i64 { digits.iter().fold(0, |total, digit| total * 10 + i64::from(digit - b'0')) }
+
+fn parse_date(cursor: &mut Cursor) -> Option<i64> {
+    let negative = cursor.eat(b"+-") == Some(b'-');
+    // Eleven digits is already far past the range of a Date
+    let year = to_number(cursor.digits(4, 11)?);
+    let year = if negative { -year } else { year };
+    cursor.eat(b"-")?;
+    let month = cursor.number(2)? as u32;
+    cursor.eat(b"-")?;
+    let day = cursor.number(2)? as u32;
+    if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+        return None;
+    }
+    Some(days_from_civil(year, month, day))
+}
+
+fn parse_timestamp(cursor: &mut Cursor) -> Option<i64> {
+    let days = parse_date(cursor)?;
+    if cursor.is_done() {
+        return days.checked_mul(MICROS_PER_DAY);
+    }
+    cursor.eat(b"Tt ")?;
+    let hour = cursor.number(2).filter(|hour| *hour < 24)?;
+    cursor.eat(b":")?;
+    let minute = cursor.number(2).filter(|minute| *minute < 60)?;
+    let mut second = 0;
+    let mut fraction = 0;
+    if cursor.eat(b":").is_some() {
+        second = cursor.number(2).filter(|second| *second < 60)?;
+        if cursor.eat(b".").is_some() {
+            let digits = cursor.digits(1, usize::MAX)?;
+            let kept = &digits[..digits.len().min(6)];
+            fraction = to_number(kept) * 10_i64.pow(6 - kept.len() as u32);
+        }
+    }
+    let offset_seconds = match cursor.eat(b"Zz+-") {
+        None | Some(b'Z' | b'z') => 0,
+        Some(sign) => {
+            let hours = cursor.number(2).filter(|hours| *hours < 24)?;
+            cursor.eat(b":");
+            let minutes = cursor.number(2).filter(|minutes| *minutes < 60)?;
+            let offset = hours * 3600 + minutes * 60;
+            if sign == b'-' {
+                -offset
+            } else {
+                offset
+            }
+        }
+    };
+    if !cursor.is_done() {
+        return None;
+    }
+    let seconds_of_day = hour * 3600 + minute * 60 + second - offset_seconds;
+    days.checked_mul(MICROS_PER_DAY)?.checked_add(seconds_of_day * MICROS_PER_SECOND + fraction)
+}
+
+#[cfg(feature = "wasm")]
+mod wasm {
+    use super::{Date, Timestamp};
+    use wasm_bindgen::{prelude::*, JsCast};
+
+    #[wasm_bindgen]
+    extern "C" {
+        #[wasm_bindgen(typescript_type = "Date")]
+        pub type JsTimestamp;
+        #[wasm_bindgen(typescript_type = "string")]
+        pub type JsCalendarDate;
+    }
+
+    impl From<Timestamp> for JsValue {
+        fn from(timestamp: Timestamp) -> Self { js_sys::Date::new(&JsValue::from_f64(timestamp.as_micros() as f64 / 1000.0)).into() }
+    }
+
+    /// Accepts a JavaScript `Date` or anything [`Timestamp`] parses from text.
+    impl TryFrom<JsValue> for Timestamp {
+        type Error = JsValue;
+
+        fn try_from(value: JsValue) -> Result<Self, Self::Error> {
+            if let Some(date) = value.dyn_ref::<js_sys::Date>() {
+                let millis = date.get_time();
+                return if millis.is_finite() { Ok(Self::from_micros((millis * 1000.0) as i64)) } else { Err(value) };
+            }
+            value.as_string().and_then(|text| text.parse().ok()).ok_or(value)
+        }
+    }
+
+    impl From<Date> for JsValue {
+        fn from(date: Date) -> Self { JsValue::from_str(&date.to_string()) }
+    }
+
+    impl TryFrom<JsValue> for Date {
+        type Error = JsValue;
+
+        fn try_from(value: JsValue) -> Result<Self, Self::Error> { value.as_string().and_then(|text| text.parse().ok()).ok_or(value) }
+    }
+
+    crate::wasm_abi_via_js!(Timestamp, JsTimestamp);
+    crate::wasm_abi_via_js!(Date, JsCalendarDate);
+}
+
+// Swift and Kotlin see a Timestamp as their native instant (Date / Instant)
+#[cfg(feature = "uniffi")]
+::uniffi::custom_type!(Timestamp, SystemTime, {
+    lower: |timestamp| timestamp.into(),
+    try_lift: |time| Ok(time.into()),
+});
+
+#[cfg(feature = "uniffi")]
+::uniffi::custom_type!(Date, String, {
+    lower: |date| date.to_string(),
+    try_lift: |text| text.parse().map_err(Into::into),
+});
+
+#[cfg(feature = "postgres")]
+mod postgres {
+    use super::{Date, Timestamp, MICROS_PER_DAY};
+    use bytes::{Buf, BufMut, BytesMut};
+    use postgres_types::{FromSql, IsNull, ToSql, Type};
+    use std::error::Error;
+
+    /// PostgreSQL counts from 2000-01-01, 10957 days after the Unix epoch.
+    const POSTGRES_EPOCH_DAYS: i64 = 10_957;
+
+    impl ToSql for Timestamp {
+        fn to_sql(&self, _: &Type, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+            out.put_i64(self.as_micros().checked_sub(POSTGRES_EPOCH_DAYS * MICROS_PER_DAY).ok_or("timestamp out of range")?);
+            Ok(IsNull::No)
+        }
+
+        fn accepts(ty: &Type) -> bool { matches!(*ty, Type::TIMESTAMPTZ | Type::TIMESTAMP) }
+
+        postgres_types::to_sql_checked!();
+    }
+
+    impl<'a> FromSql<'a> for Timestamp {
+        fn from_sql(_: &Type, mut raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+            let micros = raw.try_get_i64()?;
+            Ok(Self::from_micros(micros.checked_add(POSTGRES_EPOCH_DAYS * MICROS_PER_DAY).ok_or("timestamp out of range")?))
+        }
+
+        fn accepts(ty: &Type) -> bool { matches!(*ty, Type::TIMESTAMPTZ | Type::TIMESTAMP) }
+    }
+
+    impl ToSql for Date {
+        fn to_sql(&self, _: &Type, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+            out.put_i32(i32::try_from(i64::from(self.days_since_epoch()) - POSTGRES_EPOCH_DAYS)?);
+            Ok(IsNull::No)
+        }
+
+        fn accepts(ty: &Type) -> bool { *ty == Type::DATE }
+
+        postgres_types::to_sql_checked!();
+    }
+
+    impl<'a> FromSql<'a> for Date {
+        fn from_sql(_: &Type, mut raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+            let days = raw.try_get_i32()?;
+            Ok(Self::from_days_since_epoch(i32::try_from(i64::from(days) + POSTGRES_EPOCH_DAYS)?))
+        }
+
+        fn accepts(ty: &Type) -> bool { *ty == Type::DATE }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calendar_arithmetic_matches_known_dates() {
+        assert_eq!(Date::from_ymd(1970, 1, 1), Some(Date::UNIX_EPOCH));
+        assert_eq!(Date::from_ymd(2000, 3, 1).unwrap().days_since_epoch(), 11_017);
+        assert_eq!(Date::from_ymd(1969, 12, 31).unwrap().days_since_epoch(), -1);
+        assert_eq!(Date::from_ymd(2024, 2, 29).unwrap().ymd(), (2024, 2, 29));
+        assert_eq!(Date::from_ymd(2023, 2, 29), None);
+        assert_eq!(Date::from_ymd(1900, 2, 29), None);
+        assert_eq!(Date::from_days_since_epoch(-719_528).to_string(), "0000-01-01");
+        assert_eq!(Date::from_days_since_epoch(-719_529).to_string(), "-0001-12-31");
+    }
+
+    #[test]
+    fn timestamps_print_and_parse_rfc_3339() {
+        let timestamp: Timestamp = "2024-05-06T07:08:09.123456Z".parse().unwrap();
+        assert_eq!(timestamp.as_micros(), 1_714_979_289_123_456);
+        assert_eq!(timestamp.to_string(), "2024-05-06T07:08:09.123456Z");
+        assert_eq!(Timestamp::from_micros(-1).to_string(), "1969-12-31T23:59:59.999999Z");
+        assert_eq!(Timestamp::UNIX_EPOCH.to_string(), "1970-01-01T00:00:00Z");
+
+        // Offsets fold into the instant; extra precision is truncated
+        assert_eq!("2024-05-06T09:08:09.123456789+02:00".parse::<Timestamp>().unwrap(), timestamp);
+        assert_eq!("2024-05-06 07:08:09.123456".parse::<Timestamp>().unwrap(), timestamp);
+        assert_eq!("2024-05-06".parse::<Timestamp>().unwrap(), Timestamp::from_micros(1_714_953_600_000_000));
+        assert_eq!(timestamp.date(), "2024-05-06".parse().unwrap());
+
+        for invalid in ["2024-05-06T24:00:00Z", "2024-13-01", "2024-05-06T07:08Zjunk", "yesterday"] {
+            assert!(invalid.parse::<Timestamp>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn json_and_bincode_encodings_are_pinned() {
+        let timestamp = Timestamp::from_micros(1_714_979_289_123_456);
+        assert_eq!(serde_json::to_string(&timestamp).unwrap(), "\"2024-05-06T07:08:09.123456Z\"");
+        assert_eq!(bincode::serialize(&timestamp).unwrap(), 1_714_979_289_123_456_i64.to_le_bytes());
+        assert_eq!(serde_json::from_str::<Timestamp>("\"2024-05-06T07:08:09.123456Z\"").unwrap(), timestamp);
+
+        let date = Date::from_ymd(2024, 5, 6).unwrap();
+        assert_eq!(serde_json::to_string(&date).unwrap(), "\"2024-05-06\"");
+        assert_eq!(bincode::deserialize::<Date>(&bincode::serialize(&date).unwrap()).unwrap(), date);
+    }
+
+    #[test]
+    fn system_time_round_trips_to_the_microsecond() {
+        let before_epoch = SystemTime::UNIX_EPOCH - Duration::from_nanos(1_500);
+        assert_eq!(Timestamp::from(before_epoch).as_micros(), -2);
+        let timestamp = Timestamp::from_micros(1_714_979_289_123_456);
+        assert_eq!(Timestamp::from(SystemTime::from(timestamp)), timestamp);
+    }
+}
@@ -0,0 +1,177 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+use crate::ScalarParseError;
+
+/// A 128-bit universally unique identifier (RFC 9562).
+///
+/// The text form is the lowercase hyphenated `8-4-4-4-12` hex layout; parsing
+/// also accepts uppercase and the 32-digit form without hyphens. Binary
+/// serializers receive the 16 raw bytes, which also give the ordering.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Uuid([u8; 16]);
+
+impl Uuid {
+    /// The all-zero UUID.
+    pub const NIL: Self = Self([0; 16]);
+
+    /// Draw a random (version 4) UUID.
+    pub fn new_v4() -> Self {
+        let mut bytes = [0u8; 16];
+        rand::RngCore::fill_bytes(&mut rand::rng(), &mut bytes);
+        bytes[6] = (bytes[6] & 0x0f) | 0x40;
+        bytes[8] = (bytes[8] & 0x3f) | 0x80;
+        Self(bytes)
+    }
+
+    /// Construct a UUID from its 16 bytes.
+    pub const fn from_bytes(bytes: [u8; 16]) -> Self { Self(bytes) }
+
+    /// Return the 16 bytes.
+    pub const fn to_bytes(&self) -> [u8; 16] { self.0 }
+}
+
+impl fmt::Display for Uuid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, byte) in self.0.iter().enumerate() {
+            if matches!(index, 4 | 6 | 8 | 10) {
+                f.write_str("-")?;
+            }
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Uuid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { fmt::Display::fmt(self, f) }
+}
+
+impl FromStr for Uuid {
+    type Err = ScalarParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ScalarParseError::new("uuid", s);
+        let hex: Vec<u8> = match s.len() {
+            32 => s.bytes().collect(),
+            36 if s.bytes().enumerate().all(|(index, byte)| (byte == b'-') == matches!(index, 8 | 13 | 18 | 23)) => {
+                s.bytes().filter(|byte| *byte != b'-').collect()
+            }
+            _ => return Err(invalid()),
+        };
+        let mut bytes = [0u8; 16];
+        for (byte, pair) in bytes.iter_mut().zip(hex.chunks(2)) {
+            let pair = std::str::from_utf8(pair).map_err(|_| invalid())?;
+            *byte = u8::from_str_radix(pair, 16).map_err(|_| invalid())?;
+        }
+        Ok(Self(bytes))
+    }
+}
+
+impl Serialize for Uuid {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: serde::Serializer {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            self.0.serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Uuid {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: serde::Deserializer<'de> {
+        if deserializer.is_human_readable() {
+            String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+        } else {
+            Ok(Self(<[u8; 16]>::deserialize(deserializer)?))
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+mod wasm {
+    use super::Uuid;
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen]
+    extern "C" {
+        #[wasm_bindgen(typescript_type = "string")]
+        pub type JsUuid;
+    }
+
+    impl From<Uuid> for JsValue {
+        fn from(uuid: Uuid) -> Self { JsValue::from_str(&uuid.to_string()) }
+    }
+
+    impl TryFrom<JsValue> for Uuid {
+        type Error = JsValue;
+
+        fn try_from(value: JsValue) -> Result<Self, Self::Error> { value.as_string().and_then(|text| text.parse().ok()).ok_or(value) }
+    }
+
+    crate::wasm_abi_via_js!(Uuid, JsUuid);
+}
+
+#[cfg(feature = "uniffi")]
+::uniffi::custom_type!(Uuid, String, {
+    lower: |uuid| uuid.to_string(),
+    try_lift: |text| text.parse().map_err(Into::into),
+});
+
+#[cfg(feature = "postgres")]
+impl postgres_types::ToSql for Uuid {
+    fn to_sql(
+        &self,
+        _: &postgres_types::Type,
+        out: &mut bytes::BytesMut,
+    ) -> Result<postgres_types::IsNull, Box<dyn std::error::Error + Sync + Send>> {
+        out.extend_from_slice(&self.0);
+        Ok(postgres_types::IsNull::No)
+    }
+
+    fn accepts(ty: &postgres_types::Type) -> bool { *ty == postgres_types::Type::UUID }
+
+    postgres_types::to_sql_checked!();
+}
+
+#[cfg(feature = "postgres")]
+impl<'a> postgres_types::FromSql<'a> for Uuid {
+    fn from_sql(_: &postgres_types::Type, raw: &'a [u8]) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
+        Ok(Self(raw.try_into()?))
+    }
+
+    fn accepts(ty: &postgres_types::Type) -> bool { *ty == postgres_types::Type::UUID }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_forms_parse_to_the_same_bytes() {
+        let uuid: Uuid = "67e55044-10b1-426f-9247-bb680e5fe0c8".parse().unwrap();
+        assert_eq!(uuid.to_string(), "67e55044-10b1-426f-9247-bb680e5fe0c8");
+        assert_eq!("67E5504410B1426F9247BB680E5FE0C8".parse::<Uuid>().unwrap(), uuid);
+        for invalid in ["67e55044-10b1-426f-9247-bb680e5fe0c", "67e5504410b1-426f-9247-bb680e5fe0c8-", "67e55044-10b1-426f-9247-bb680e5fe0cg"] {
+            assert!(invalid.parse::<Uuid>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn random_uuids_carry_the_version_and_variant() {
+        let uuid = Uuid::new_v4();
+        assert_eq!(uuid.to_bytes()[6] >> 4, 4);
+        assert_eq!(uuid.to_bytes()[8] >> 6, 0b10);
+        assert_ne!(uuid, Uuid::new_v4());
+    }
+
+    #[test]
+    fn json_and_bincode_encodings_are_pinned() {
+        let uuid = Uuid::from_bytes([0xab; 16]);
+        assert_eq!(serde_json::to_string(&uuid).unwrap(), "\"abababab-abab-abab-abab-abababababab\"");
+        assert_eq!(bincode::serialize(&uuid).unwrap(), [0xab; 16]);
+        assert_eq!(serde_json::from_str::<Uuid>(&serde_json::to_string(&uuid).unwrap()).unwrap(), uuid);
+    }
+}
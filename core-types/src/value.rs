@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 use std::fmt;
 use thiserror::Error;
 
-use crate::{EntityId, ValueType};
+use crate::{Date, Decimal, EntityId, Timestamp, Uuid, ValueType};
 
 mod json_as_bytes {
     use serde::{Deserialize, Deserializer, Serialize, Serializer};
@@ -43,6 +43,20 @@ pub enum Value {
     /// A structured JSON value.
     #[serde(with = "json_as_bytes")]
     Json(serde_json::Value),
+    /// A UTC instant with microsecond precision.
+    Timestamp(Timestamp),
+    /// A calendar date.
+    Date(Date),
+    /// An exact base-10 number.
+    Decimal(Decimal),
+    /// A 128-bit UUID.
+    Uuid(Uuid),
+    /// An unsigned 32-bit integer.
+    U32(u32),
+    /// An unsigned 64-bit integer.
+    U64(u64),
+    /// A signed 128-bit integer.
+    I128(i128),
 }
 
 impl From<&Value> for Value {
@@ -65,6 +79,13 @@ from_value_variant!(f64, F64);
 from_value_variant!(bool, Bool);
 from_value_variant!(Vec<u8>, Binary);
 from_value_variant!(EntityId, EntityId);
+from_value_variant!(Timestamp, Timestamp);
+from_value_variant!(Date, Date);
+from_value_variant!(Decimal, Decimal);
+from_value_variant!(Uuid, Uuid);
+from_value_variant!(u32, U32);
+from_value_variant!(u64, U64);
+from_value_variant!(i128, I128);
 
 impl From<&str> for Value {
     fn from(value: &str) -> Self { Self::String(value.to_owned()) }
@@ -172,6 +193,26 @@ impl Value {
             Self::Object(_) => ValueType::Object,
             Self::Binary(_) => ValueType::Binary,
             Self::Json(_) => ValueType::Json,
+            Self::Timestamp(_) => ValueType::Timestamp,
+            Self::Date(_) => ValueType::Date,
+            Self::Decimal(_) => ValueType::Decimal,
+            Self::Uuid(_) => ValueType::Uuid,
+            Self::U32(_) => ValueType::U32,
+            Self::U64(_) => ValueType::U64,
+            Self::I128(_) => ValueType::I128,
+        }
+    }
+
+    /// The integer this value holds, widened to `i128`, which holds every integer variant.
+    fn as_i128(&self) -> Option<i128> {
+        match self {
+            Self::I16(n) => Some((*n).into()),
+            Self::I32(n) => Some((*n).into()),
+            Self::I64(n) => Some((*n).into()),
+            Self::U32(n) => Some((*n).into()),
+            Self::U64(n) => Some((*n).into()),
+            Self::I128(n) => Some(*n),
+            _ => None,
         }
     }
 
@@ -251,6 +292,65 @@ impl Value {
             (Self::Json(serde_json::Value::Number(n)), ValueType::F64) => n.as_f64().map(Self::F64).ok_or_else(|| overflow(n, target_type)),
             (Self::Json(serde_json::Value::Bool(b)), ValueType::Bool) => Ok(Self::Bool(*b)),
 
+            (Self::String(s), ValueType::Timestamp) => parse(s, target_type, Self::Timestamp),
+            (Self::String(s), ValueType::Date) => parse(s, target_type, Self::Date),
+            (Self::String(s), ValueType::Decimal) => parse(s, target_type, Self::Decimal),
+            (Self::String(s), ValueType::Uuid) => parse(s, target_type, Self::Uuid),
+            (Self::Timestamp(t), ValueType::String) => Ok(Self::String(t.to_string())),
+            (Self::Date(d), ValueType::String) => Ok(Self::String(d.to_string())),
+            (Self::Decimal(d), ValueType::String) => Ok(Self::String(d.to_string())),
+            (Self::Uuid(u), ValueType::String) => Ok(Self::String(u.to_string())),
+
+            (Self::I64(n), ValueType::Timestamp) => Ok(Self::Timestamp(Timestamp::from_micros(*n))),
+            (Self::Timestamp(t), ValueType::I64) => Ok(Self::I64(t.as_micros())),
+            (Self::Timestamp(t), ValueType::Date) => Ok(Self::Date(t.date())),
+            (Self::Date(d), ValueType::Timestamp) => d.at_midnight().map(Self::Timestamp).ok_or_else(|| overflow(d, target_type)),
+
+            (Self::I16(n), ValueType::Decimal) => Ok(Self::Decimal((*n).into())),
+            (Self::I32(n), ValueType::Decimal) => Ok(Self::Decimal((*n).into())),
+            (Self::I64(n), ValueType::Decimal) => Ok(Self::Decimal((*n).into())),
+            (Self::F64(n), ValueType::Decimal) if !n.is_finite() => Err(invalid(n, target_type)),
+            (Self::F64(n), ValueType::Decimal) => Decimal::try_from(*n).map(Self::Decimal).map_err(|_| overflow(n, target_type)),
+            (Self::Decimal(d), ValueType::I64) => d.to_i64().map(Self::I64).ok_or_else(|| overflow(d, target_type)),
+            (Self::Decimal(d), ValueType::F64) => Ok(Self::F64(d.to_f64())),
+
+            (Self::Timestamp(t), ValueType::Json) => Ok(Self::Json(serde_json::Value::String(t.to_string()))),
+            (Self::Date(d), ValueType::Json) => Ok(Self::Json(serde_json::Value::String(d.to_string()))),
+            (Self::Decimal(d), ValueType::Json) => Ok(Self::Json(serde_json::Value::String(d.to_string()))),
+            (Self::Uuid(u), ValueType::Json) => Ok(Self::Json(serde_json::Value::String(u.to_string()))),
+            (Self::Json(serde_json::Value::String(s)), ValueType::Timestamp) => parse(s, target_type, Self::Timestamp),
+            (Self::Json(serde_json::Value::String(s)), ValueType::Date) => parse(s, target_type, Self::Date),
+            (Self::Json(serde_json::Value::String(s)), ValueType::Decimal) => parse(s, target_type, Self::Decimal),
+            (Self::Json(serde_json::Value::Number(n)), ValueType::Decimal) => parse(&n.to_string(), target_type, Self::Decimal),
+            (Self::Json(serde_json::Value::String(s)), ValueType::Uuid) => parse(s, target_type, Self::Uuid),
+
+            // Unsigned and 128-bit integers convert through i128, checking the target's range
+            (_, ValueType::I16 | ValueType::I32 | ValueType::I64 | ValueType::U32 | ValueType::U64 | ValueType::I128)
+                if self.as_i128().is_some() =>
+            {
+                from_i128(self.as_i128().unwrap_or_default(), target_type)
+            }
+            (
+                Self::U32(_) | Self::U64(_) | Self::I128(_),
+                ValueType::F64 | ValueType::String | ValueType::Bool | ValueType::Decimal | ValueType::Json,
+            ) => from_i128(self.as_i128().unwrap_or_default(), target_type),
+            // 2^127 is exactly representable and out of range, so the upper bound is exclusive
+            (Self::F64(n), ValueType::U32 | ValueType::U64 | ValueType::I128) if n.is_finite() && n.abs() < 2f64.powi(127) => {
+                from_i128(*n as i128, target_type)
+            }
+            (Self::F64(n), ValueType::U32 | ValueType::U64 | ValueType::I128) => Err(overflow(*n, target_type)),
+            (Self::String(s), ValueType::U32) => parse(s, target_type, Self::U32),
+            (Self::String(s), ValueType::U64) => parse(s, target_type, Self::U64),
+            (Self::String(s), ValueType::I128) => parse(s, target_type, Self::I128),
+            (Self::Bool(b), ValueType::U32 | ValueType::U64 | ValueType::I128) => from_i128((*b).into(), target_type),
+            (Self::Decimal(d), ValueType::U32 | ValueType::U64 | ValueType::I128) => {
+                d.to_i128().ok_or_else(|| overflow(d, target_type)).and_then(|n| from_i128(n, target_type))
+            }
+            (Self::Json(serde_json::Value::Number(n)), ValueType::U32 | ValueType::U64 | ValueType::I128) => {
+                let integer = n.as_i64().map(i128::from).or_else(|| n.as_u64().map(i128::from));
+                integer.ok_or_else(|| overflow(n, target_type)).and_then(|n| from_i128(n, target_type))
+            }
+
             _ => Err(CastError::IncompatibleTypes { from: source_type, to: target_type }),
         }
     }
@@ -279,6 +379,20 @@ impl Value {
         Some(from_json_scalar(current))
     }
 
+    /// The members of a collection value (a JSON array), or `None` if this is not one
+    pub fn elements(&self) -> Option<Vec<Self>> {
+        let json = match self {
+            Self::Json(json) => json.clone(),
+            Self::Binary(bytes) | Self::Object(bytes) => serde_json::from_slice(bytes).ok()?,
+            Self::String(string) => serde_json::from_str(string).ok()?,
+            _ => return None,
+        };
+        match json {
+            serde_json::Value::Array(items) => Some(items.iter().map(from_json_scalar).collect()),
+            _ => None,
+        }
+    }
+
     /// Return whether this value compares greater than `other`.
     pub fn gt(&self, other: &Self) -> bool { self.partial_cmp(other) == Some(std::cmp::Ordering::Greater) }
     /// Return whether this value compares greater than or equal to `other`.
@@ -303,6 +417,13 @@ impl PartialOrd for Value {
             (Self::EntityId(a), Self::EntityId(b)) => a.to_bytes().partial_cmp(&b.to_bytes()),
             (Self::Object(a), Self::Object(b)) | (Self::Binary(a), Self::Binary(b)) => a.partial_cmp(b),
             (Self::Json(a), Self::Json(b)) => a.to_string().partial_cmp(&b.to_string()),
+            (Self::Timestamp(a), Self::Timestamp(b)) => a.partial_cmp(b),
+            (Self::Date(a), Self::Date(b)) => a.partial_cmp(b),
+            (Self::Decimal(a), Self::Decimal(b)) => a.partial_cmp(b),
+            (Self::Uuid(a), Self::Uuid(b)) => a.partial_cmp(b),
+            (Self::U32(a), Self::U32(b)) => a.partial_cmp(b),
+            (Self::U64(a), Self::U64(b)) => a.partial_cmp(b),
+            (Self::I128(a), Self::I128(b)) => a.partial_cmp(b),
             _ => None,
         }
     }
@@ -320,6 +441,14 @@ impl fmt::Display for Value {
             Self::EntityId(v) => fmt::Display::fmt(v, f),
             Self::Object(v) | Self::Binary(v) => write!(f, "{v:?}"),
             Self::Json(v) => fmt::Display::fmt(v, f),
+            // Quoted like strings, which is how they are written as literals
+            Self::Timestamp(v) => write!(f, "{:?}", v.to_string()),
+            Self::Date(v) => write!(f, "{:?}", v.to_string()),
+            Self::Decimal(v) => write!(f, "{:?}", v.to_string()),
+            Self::Uuid(v) => write!(f, "{:?}", v.to_string()),
+            Self::U32(v) => write!(f, "{v:?}"),
+            Self::U64(v) => write!(f, "{v:?}"),
+            Self::I128(v) => write!(f, "{v:?}"),
         }
     }
 }
@@ -329,6 +458,32 @@ where T: std::str::FromStr {
     source.parse().map(wrap).map_err(|_| invalid(source, target_type))
 }
 
+/// Convert an integer to an integer, float, text, boolean, decimal or JSON value.
+fn from_i128(n: i128, target_type: ValueType) -> Result<Value, CastError> {
+    let in_range = |converted: Option<Value>| converted.ok_or_else(|| overflow(n, target_type));
+    match target_type {
+        ValueType::I16 => in_range(i16::try_from(n).ok().map(Value::I16)),
+        ValueType::I32 => in_range(i32::try_from(n).ok().map(Value::I32)),
+        ValueType::I64 => in_range(i64::try_from(n).ok().map(Value::I64)),
+        ValueType::U32 => in_range(u32::try_from(n).ok().map(Value::U32)),
+        ValueType::U64 => in_range(u64::try_from(n).ok().map(Value::U64)),
+        ValueType::I128 => Ok(Value::I128(n)),
+        ValueType::F64 => Ok(Value::F64(n as f64)),
+        ValueType::String => Ok(Value::String(n.to_string())),
+        ValueType::Bool => Ok(Value::Bool(n != 0)),
+        ValueType::Decimal => Ok(Value::Decimal(n.into())),
+        // JSON numbers hold 64-bit integers
+        ValueType::Json => in_range(
+            i64::try_from(n)
+                .map(serde_json::Number::from)
+                .or_else(|_| u64::try_from(n).map(serde_json::Number::from))
+                .ok()
+                .map(|number| Value::Json(number.into())),
+        ),
+        _ => Err(CastError::IncompatibleTypes { from: ValueType::I128, to: target_type }),
+    }
+}
+
 fn invalid(value: impl ToString, target_type: ValueType) -> CastError { CastError::InvalidFormat { value: value.to_string(), target_type } }
 
 fn overflow(value: impl ToString, target_type: ValueType) -> CastError {
@@ -339,9 +494,12 @@ fn from_json_scalar(json: &serde_json::Value) -> Value {
     match json {
         serde_json::Value::Null => Value::Json(serde_json::Value::Null),
         serde_json::Value::Bool(value) => Value::Bool(*value),
-        serde_json::Value::Number(value) => {
-            value.as_i64().map(Value::I64).or_else(|| value.as_f64().map(Value::F64)).unwrap_or_else(|| Value::String(value.to_string()))
-        }
+        serde_json::Value::Number(value) => value
+            .as_i64()
+            .map(Value::I64)
+            .or_else(|| value.as_u64().map(Value::U64))
+            .or_else(|| value.as_f64().map(Value::F64))
+            .unwrap_or_else(|| Value::String(value.to_string())),
         serde_json::Value::String(value) => Value::String(value.clone()),
         serde_json::Value::Array(_) | serde_json::Value::Object(_) => Value::Json(json.clone()),
     }
@@ -368,6 +526,14 @@ mod wasm {
                 Value::EntityId(v) => JsValue::from_str(&v.to_base64()),
                 Value::Object(v) | Value::Binary(v) => js_sys::Uint8Array::from(v.as_slice()).into(),
                 Value::Json(v) => serde_wasm_bindgen::to_value(v).unwrap_or(JsValue::NULL),
+                Value::Timestamp(v) => (*v).into(),
+                Value::Date(v) => (*v).into(),
+                Value::Decimal(v) => (*v).into(),
+                Value::Uuid(v) => (*v).into(),
+                Value::U32(v) => JsValue::from_f64(*v as f64),
+                // Past 2^53 a JS number loses precision, so the wide integers cross as BigInt
+                Value::U64(v) => (*v).into(),
+                Value::I128(v) => (*v).into(),
             }
         }
     }
@@ -379,6 +545,9 @@ mod wasm {
             if value.is_null() || value.is_undefined() {
                 return Err(value);
             }
+            if value.is_instance_of::<js_sys::Date>() {
+                return crate::Timestamp::try_from(value).map(Self::Timestamp);
+            }
             if let Some(v) = value.as_string() {
                 return Ok(Self::String(v));
             }
@@ -393,6 +562,14 @@ mod wasm {
                     Self::F64(v)
                 });
             }
+            if value.is_bigint() {
+                // The narrowest of I64, U64 and I128 that holds it
+                let integer = i128::try_from(value)?;
+                return Ok(i64::try_from(integer)
+                    .map(Self::I64)
+                    .or_else(|_| u64::try_from(integer).map(Self::U64))
+                    .unwrap_or(Self::I128(integer)));
+            }
             if value.is_instance_of::<js_sys::Uint8Array>() {
                 let array: js_sys::Uint8Array = value.unchecked_into();
                 let mut bytes = vec![0; array.length() as usize];
@@ -424,8 +601,15 @@ mod tests {
             Value::Json(serde_json::json!(1)),
             Value::Json(serde_json::json!("s")),
             Value::Json(serde_json::json!(true)),
+            Value::Timestamp(crate::Timestamp::from_micros(1)),
+            Value::Date(crate::Date::from_days_since_epoch(1)),
+            Value::Decimal(crate::Decimal::from(1)),
+            Value::Uuid(crate::Uuid::NIL),
+            Value::U32(1),
+            Value::U64(1),
+            Value::I128(1),
         ];
-        let types = [I16, I32, I64, F64, Bool, String, EntityId, Object, Binary, Json];
+        let types = [I16, I32, I64, F64, Bool, String, EntityId, Object, Binary, Json, Timestamp, Date, Decimal, Uuid, U32, U64, I128];
         for source in types {
             let values: Vec<_> = representatives.iter().filter(|value| value.value_type() == source).collect();
             for target in types {
@@ -487,6 +671,53 @@ mod tests {
         assert_eq!(Value::I32(42).cast_to(ValueType::I32).unwrap(), Value::I32(42));
     }
 
+    #[test]
+    fn temporal_decimal_and_uuid_values_cast_through_their_text() {
+        let timestamp = Value::String("2024-05-06T07:08:09Z".to_owned()).cast_to(ValueType::Timestamp).unwrap();
+        assert_eq!(timestamp, Value::Timestamp(crate::Timestamp::from_micros(1_714_979_289_000_000)));
+        assert_eq!(timestamp.cast_to(ValueType::I64).unwrap(), Value::I64(1_714_979_289_000_000));
+        assert_eq!(timestamp.cast_to(ValueType::Date).unwrap().cast_to(ValueType::String).unwrap(), Value::String("2024-05-06".to_owned()));
+        assert!(matches!(Value::String("next tuesday".to_owned()).cast_to(ValueType::Date), Err(CastError::InvalidFormat { .. })));
+
+        let price = Value::String("19.990".to_owned()).cast_to(ValueType::Decimal).unwrap();
+        assert_eq!(price.cast_to(ValueType::String).unwrap(), Value::String("19.99".to_owned()));
+        assert_eq!(Value::F64(0.1).cast_to(ValueType::Decimal).unwrap(), Value::Decimal("0.1".parse().unwrap()));
+        assert!(matches!(price.cast_to(ValueType::I64), Err(CastError::NumericOverflow { .. })));
+        assert!(Value::Decimal("10".parse().unwrap()).gt(&Value::Decimal("9.99".parse().unwrap())));
+
+        let uuid = Value::String("67E55044-10B1-426F-9247-BB680E5FE0C8".to_owned()).cast_to(ValueType::Uuid).unwrap();
+        assert_eq!(uuid.cast_to(ValueType::String).unwrap(), Value::String("67e55044-10b1-426f-9247-bb680e5fe0c8".to_owned()));
+    }
+
+    #[test]
+    fn new_variants_append_to_the_binary_encoding() {
+        // Stored state buffers carry Value variant indexes, so new variants go last
+        assert_eq!(bincode::serialize(&Value::Timestamp(crate::Timestamp::UNIX_EPOCH)).unwrap()[..4], 10_u32.to_le_bytes());
+        assert_eq!(bincode::serialize(&Value::Uuid(crate::Uuid::NIL)).unwrap()[..4], 13_u32.to_le_bytes());
+        let decimal = Value::Decimal("-12.5".parse().unwrap());
+        assert_eq!(bincode::deserialize::<Value>(&bincode::serialize(&decimal).unwrap()).unwrap(), decimal);
+    }
+
+    #[test]
+    fn wide_integers_cast_within_range() {
+        assert_eq!(Value::U64(u64::MAX).cast_to(ValueType::I128).unwrap(), Value::I128(u64::MAX.into()));
+        assert_eq!(Value::I64(7).cast_to(ValueType::U32).unwrap(), Value::U32(7));
+        assert_eq!(Value::String("18446744073709551615".into()).cast_to(ValueType::U64).unwrap(), Value::U64(u64::MAX));
+        assert_eq!(Value::U64(u64::MAX).cast_to(ValueType::Json).unwrap(), Value::Json(serde_json::json!(u64::MAX)));
+        assert_eq!(Value::Decimal("42".parse().unwrap()).cast_to(ValueType::U64).unwrap(), Value::U64(42));
+        for (value, target) in [
+            (Value::I32(-1), ValueType::U64),
+            (Value::U64(u64::MAX), ValueType::I64),
+            (Value::I128(i128::MAX), ValueType::Json),
+            (Value::F64(-1.0), ValueType::U32),
+            (Value::F64(2f64.powi(127)), ValueType::I128),
+            (Value::Decimal("1.5".parse().unwrap()), ValueType::U64),
+        ] {
+            assert!(matches!(value.cast_to(target), Err(CastError::NumericOverflow { .. })), "{value:?} -> {target:?}");
+        }
+        assert_eq!(Value::Json(serde_json::json!({ "n": u64::MAX })).extract_at_path(&["n".to_owned()]), Some(Value::U64(u64::MAX)));
+    }
+
     #[test]
     fn extract_at_path_walks_json_and_stops_at_anything_else() {
         let value = Value::Json(serde_json::json!({ "context": { "user": { "name": "Alice" } }, "count": 42 }));
@@ -500,6 +731,15 @@ mod tests {
         assert_eq!(Value::String("not json".to_owned()).extract_at_path(&["field".to_owned()]), None);
     }
 
+    #[test]
+    fn elements_of_arrays_only() {
+        let tags = Value::Json(serde_json::json!(["a", 2, {"b": true}]));
+        assert_eq!(tags.elements(), Some(vec![Value::String("a".to_owned()), Value::I64(2), Value::Json(serde_json::json!({"b": true}))]));
+        assert_eq!(Value::String(r#"["a"]"#.to_owned()).elements(), Some(vec![Value::String("a".to_owned())]));
+        assert_eq!(Value::Json(serde_json::json!({"a": 1})).elements(), None);
+        assert_eq!(Value::I64(1).elements(), None);
+    }
+
     #[test]
     fn f64_to_i64_boundary_overflows_instead_of_saturating() {
         let two_pow_63 = 9_223_372_036_854_775_808.0_f64; // i64::MAX as f64 rounds up to exactly this
@@ -26,6 +26,20 @@ pub enum ValueType {
     Binary,
     /// A structured JSON value.
     Json,
+    /// A UTC instant with microsecond precision.
+    Timestamp,
+    /// A calendar date.
+    Date,
+    /// An exact base-10 number.
+    Decimal,
+    /// A 128-bit UUID.
+    Uuid,
+    /// An unsigned 32-bit integer.
+    U32,
+    /// An unsigned 64-bit integer.
+    U64,
+    /// A signed 128-bit integer.
+    I128,
 }
 
 impl ValueType {
@@ -42,6 +56,13 @@ impl ValueType {
             "object" => Self::Object,
             "binary" => Self::Binary,
             "json" => Self::Json,
+            "timestamp" => Self::Timestamp,
+            "date" => Self::Date,
+            "decimal" => Self::Decimal,
+            "uuid" => Self::Uuid,
+            "u32" => Self::U32,
+            "u64" => Self::U64,
+            "i128" => Self::I128,
             _ => return None,
         })
     }
@@ -99,7 +120,47 @@ impl ValueType {
                 | (Json, I64)
                 | (Json, F64)
                 | (Json, Bool)
-        )
+                | (String, Timestamp)
+                | (String, Date)
+                | (String, Decimal)
+                | (String, Uuid)
+                | (Timestamp, String)
+                | (Date, String)
+                | (Decimal, String)
+                | (Uuid, String)
+                | (I64, Timestamp)
+                | (Timestamp, I64)
+                | (Timestamp, Date)
+                | (Date, Timestamp)
+                | (I16, Decimal)
+                | (I32, Decimal)
+                | (I64, Decimal)
+                | (F64, Decimal)
+                | (Decimal, I64)
+                | (Decimal, F64)
+                | (Timestamp, Json)
+                | (Date, Json)
+                | (Decimal, Json)
+                | (Uuid, Json)
+                | (Json, Timestamp)
+                | (Json, Date)
+                | (Json, Decimal)
+                | (Json, Uuid)
+        ) || self.wide_integer_castable_to(target)
+    }
+
+    /// Cast paths for the unsigned and 128-bit integers: every integer converts to every
+    /// other (subject to range), and they share the scalar conversions of the signed ones.
+    fn wide_integer_castable_to(self, target: Self) -> bool {
+        use ValueType::*;
+        let integer = |ty: Self| matches!(ty, I16 | I32 | I64 | U32 | U64 | I128);
+        let wide = |ty: Self| matches!(ty, U32 | U64 | I128);
+        match (wide(self), wide(target)) {
+            (false, false) => false,
+            _ if integer(self) && integer(target) => true,
+            (true, _) => matches!(target, F64 | String | Bool | Decimal | Json),
+            (_, true) => matches!(self, F64 | String | Bool | Decimal | Json),
+        }
     }
 
     /// Whether values can be converted in both directions between two types.
@@ -121,5 +182,15 @@ mod tests {
         assert!(!ValueType::mutually_castable(EntityId, I64));
         assert!(!ValueType::mutually_castable(Object, Json));
         assert!(!ValueType::mutually_castable(EntityId, Json));
+        assert!(ValueType::mutually_castable(Timestamp, String));
+        assert!(ValueType::mutually_castable(Timestamp, I64));
+        assert!(ValueType::mutually_castable(Decimal, F64));
+        assert!(!ValueType::mutually_castable(Decimal, I32));
+        assert!(!ValueType::mutually_castable(Uuid, EntityId));
+        assert!(ValueType::mutually_castable(U64, I64));
+        assert!(ValueType::mutually_castable(I128, U32));
+        assert!(ValueType::mutually_castable(U64, Decimal));
+        assert!(ValueType::mutually_castable(I128, String));
+        assert!(!ValueType::mutually_castable(U64, Timestamp));
     }
 }
@@ -8,6 +8,7 @@ use tokio::sync::RwLock;
 
 use crate::{
     error::{MutationError, RetrievalError},
+    metrics,
     storage::{StorageCollectionWrapper, StorageEngine},
 };
 
@@ -32,7 +33,8 @@ impl<SE: StorageEngine> CollectionSet<SE> {
         }
         drop(collections);
 
-        let collection = StorageCollectionWrapper::new(self.0.storage_engine.collection(id).await?);
+        let collection =
+            StorageCollectionWrapper::new(Arc::new(metrics::TimedCollection::new(self.0.storage_engine.collection(id).await?)));
 
         let mut collections = self.0.collections.write().await;
 
@@ -51,6 +53,11 @@ impl<SE: StorageEngine> CollectionSet<SE> {
         Ok(memory_collections.keys().cloned().collect())
     }
 
+    /// Every collection in storage, including those not opened since the node started
+    pub async fn list_stored_collections(&self) -> Result<Vec<CollectionId>, RetrievalError> {
+        self.0.storage_engine.list_collections().await
+    }
+
     pub async fn delete_all_collections(&self) -> Result<bool, MutationError> {
         // Clear in-memory collections first
         {
@@ -7,13 +7,14 @@ use crate::{
     model::View,
     node::{MatchArgs, Node},
     policy::{AccessDenied, PolicyAgent},
+    schema::catalog::PropertyDef,
     storage::{StorageCollectionWrapper, StorageEngine},
     transaction::Transaction,
 };
 use ankurah_proto::{self as proto, Attested, Clock, CollectionId, EntityState, Event};
 use async_trait::async_trait;
 use std::sync::{atomic::AtomicBool, Arc};
-use tracing::debug;
+use tracing::{debug, warn};
 #[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
@@ -77,6 +78,9 @@ pub trait TContext {
     async fn commit_local_trx(&self, trx: &Transaction) -> Result<Vec<Event>, MutationError>;
     fn query(&self, collection_id: proto::CollectionId, args: MatchArgs) -> Result<EntityLiveQuery, RetrievalError>;
     async fn collection(&self, id: &proto::CollectionId) -> Result<StorageCollectionWrapper, RetrievalError>;
+    /// The model registered for `collection` and its properties, for untyped
+    /// writes that have no compiled descriptor to register from.
+    fn registered_model(&self, collection: &proto::CollectionId) -> Option<(proto::ModelId, Vec<PropertyDef>)>;
 }
 
 #[async_trait]
@@ -117,6 +121,8 @@ impl<SE: StorageEngine + Send + Sync + 'static, PA: PolicyAgent + Send + Sync +
         if trx.alive.compare_exchange(true, false, Ordering::AcqRel, Ordering::Acquire).is_err() {
             return Err(MutationError::General("Transaction already committed or rolled back".into()));
         }
+        // Held until the commit has applied and relayed, so Node::drain waits it out
+        let _commit = self.node.commits.try_acquire();
 
         // One credential snapshot for the whole commit: a session update
         // mid-commit must not mix credentials across its phases.
@@ -229,10 +235,15 @@ impl<SE: StorageEngine + Send + Sync + 'static, PA: PolicyAgent + Send + Sync +
                 entity.commit_head(Clock::new([last.payload.id()]));
             }
         }
-        // Relay to peers and wait for confirmation
-        self.node.relay_to_required_peers(&cdata, trx_id, &attested_events).await?;
-
-        // All peers confirmed, persist state to storage
+        // Relay to peers and wait for confirmation. A durable node applies nothing its peers
+        // refused; an ephemeral one has queued the transaction in its outbox by now, so it is
+        // applied either way and a rejection is returned once it has been (see Outbox)
+        let relayed = match self.node.relay_to_required_peers(&self.sessions, &cdata, trx_id, &attested_events).await {
+            Err(e) if self.node.durable => return Err(e),
+            relayed => relayed,
+        };
+
+        // Persist state to storage
         let mut changes: Vec<EntityChange> = Vec::new();
         for (entity, events) in entity_attested_events {
             let collection = self.node.collections.get(entity.collection()).await?;
@@ -265,6 +276,7 @@ impl<SE: StorageEngine + Send + Sync + 'static, PA: PolicyAgent + Send + Sync +
         // Notify reactor of ALL changes
         self.node.reactor.notify_change(changes).await;
 
+        relayed?;
         Ok(attested_events.into_iter().map(|a| a.payload).collect())
     }
     fn query(&self, collection_id: proto::CollectionId, args: MatchArgs) -> Result<EntityLiveQuery, RetrievalError> {
@@ -273,6 +285,13 @@ impl<SE: StorageEngine + Send + Sync + 'static, PA: PolicyAgent + Send + Sync +
     async fn collection(&self, id: &proto::CollectionId) -> Result<StorageCollectionWrapper, RetrievalError> {
         self.node.system.collection(id).await
     }
+    fn registered_model(&self, collection: &proto::CollectionId) -> Option<(proto::ModelId, Vec<PropertyDef>)> {
+        let catalog = &self.node.catalog;
+        let model = catalog.model_by_label(collection.as_str())?;
+        let properties =
+            catalog.memberships_of(&model.id).iter().filter_map(|membership| catalog.property_by_id(&membership.property)).collect();
+        Some((proto::ModelId::EntityId(model.id), properties))
+    }
 }
 
 // This whole impl is conditionalized by the wasm feature flag
@@ -316,6 +335,16 @@ impl Context {
         // it the continuous superset of every session backing a context
         // (a no-op when the source IS the registry).
         node.sessions.attach(&sessions);
+        // Transactions queued before a restart may have found no credential
+        // to replay them with; this context may provide one.
+        if !node.durable && node.outbox.unsynced().value() > 0 {
+            let node = Node::clone(&node);
+            crate::task::spawn(async move {
+                if let Err(e) = node.outbox.flush(&node).await {
+                    warn!("Node({}) failed to flush outbox: {}", node.id, e);
+                }
+            });
+        }
         Self(Arc::new(NodeAndContext { node, sessions }))
     }
 
@@ -400,6 +429,32 @@ impl Context {
     pub async fn collection(&self, id: &proto::CollectionId) -> Result<StorageCollectionWrapper, RetrievalError> {
         self.0.collection(id).await
     }
+
+    /// Get an entity of `collection` without a compiled model. Unlike [`Self::get`], this does
+    /// not register anything: the collection is read as the catalog already describes it.
+    pub async fn get_entity(&self, collection: &CollectionId, id: proto::EntityId) -> Result<Entity, RetrievalError> {
+        self.0.get_entity(id, collection, false).await
+    }
+
+    /// Fetch the entities of `collection` matching a selection, without a compiled model
+    pub async fn fetch_entities(
+        &self,
+        collection: &CollectionId,
+        args: impl TryInto<MatchArgs, Error = impl Into<RetrievalError>>,
+    ) -> Result<Vec<Entity>, RetrievalError> {
+        let args: MatchArgs = args.try_into().map_err(|e| e.into())?;
+        self.0.fetch_entities(collection, args).await
+    }
+
+    /// Subscribe to the entities of `collection` matching a selection, without a compiled model
+    pub fn query_entities(
+        &self,
+        collection: CollectionId,
+        args: impl TryInto<MatchArgs, Error = impl Into<RetrievalError>>,
+    ) -> Result<EntityLiveQuery, RetrievalError> {
+        let args: MatchArgs = args.try_into().map_err(|e| e.into())?;
+        self.0.query(collection, args)
+    }
 }
 
 impl<SE, PA> NodeAndContext<SE, PA>
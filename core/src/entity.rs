@@ -424,7 +424,9 @@ impl Entity {
                     debug!("Equal - skip");
                     return Ok(false);
                 }
-                AbstractCausalRelation::StrictDescends { .. } => {
+                // A child of the current head; anything further ahead has events in between
+                // that must be applied too, which the layered path below does
+                AbstractCausalRelation::StrictDescends { .. } if event.parent == head => {
                     debug!("Descends - apply (attempt {})", attempt + 1);
                     let new_head: Clock = event.id().into();
                     let event_id = event.id();
@@ -443,10 +445,17 @@ impl Entity {
                     debug!("StrictAscends - incoming event is older, ignoring");
                     return Ok(false);
                 }
-                AbstractCausalRelation::DivergedSince { ref meet, .. } => {
-                    debug!("DivergedSince - true concurrency, applying via layers (attempt {})", attempt + 1);
-
-                    let meet = meet.clone();
+                AbstractCausalRelation::StrictDescends { .. } | AbstractCausalRelation::DivergedSince { .. } => {
+                    let meet = match &comparison_result.relation {
+                        AbstractCausalRelation::DivergedSince { meet, .. } => {
+                            debug!("DivergedSince - true concurrency, applying via layers (attempt {})", attempt + 1);
+                            meet.clone()
+                        }
+                        _ => {
+                            debug!("Descends past the head - applying the events between via layers (attempt {})", attempt + 1);
+                            head.as_slice().to_vec()
+                        }
+                    };
 
                     // Decompose the result to get the accumulator.
                     // The event is already in the accumulated DAG (found via staging in BFS).
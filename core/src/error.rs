@@ -49,6 +49,9 @@ pub enum RetrievalError {
     RequestError(RequestError),
     #[error("Apply error: {0}")]
     ApplyError(ApplyError),
+    /// A backlink lookup named a field that does not reference the target model.
+    #[error("{collection}.{field} is not a reference to {target}")]
+    NotAReference { collection: CollectionId, field: String, target: CollectionId },
 }
 
 impl From<RequestError> for RetrievalError {
@@ -103,6 +106,8 @@ pub enum RequestError {
     UnexpectedResponse(ankurah_proto::NodeResponseBody),
     #[error("Access denied: {0}")]
     AccessDenied(AccessDenied),
+    #[error("Peer is draining")]
+    PeerDraining,
 }
 
 impl From<AccessDenied> for RequestError {
@@ -187,6 +192,12 @@ pub enum MutationError {
     Anyhow(anyhow::Error),
     #[error("TOCTOU attempts exhausted")]
     TOCTOUAttemptsExhausted,
+    /// An untyped write named a collection that has no registered model.
+    #[error("no model is registered for collection {0}")]
+    UnregisteredModel(ankurah_proto::CollectionId),
+    /// An untyped write named a property its collection's model does not have.
+    #[error("collection {collection} has no property {property}")]
+    UnknownProperty { collection: ankurah_proto::CollectionId, property: String },
 }
 
 impl From<ankurah_proto::EventStructureError> for MutationError {
@@ -15,7 +15,7 @@ use ankurah_proto::{Event, EventId};
 
 /// Causal relation types for event layer comparison.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub(crate) enum CausalRelation {
+pub enum CausalRelation {
     Descends,
     Ascends,
     Concurrent,
@@ -142,10 +142,10 @@ impl<E: GetEvents> EventLayers<E> {
 /// clones. The `compare()` method is infallible since it only traverses
 /// parent pointers, treating missing entries as dead ends.
 ///
-/// `pub` only so `PropertyBackend::apply_layer` (a public trait method) has a
-/// reachable parameter type; its constructor and accessors remain
-/// crate-internal while the external backend API surface is designed
-/// (ankurah#267).
+/// Re-exported from `property::backend` so backends outside core can
+/// implement `PropertyBackend::apply_layer`. They read a layer through its
+/// accessors; constructing one stays crate-internal while the rest of the
+/// external backend API surface is designed (ankurah#267).
 #[derive(Debug, Clone)]
 pub struct EventLayer {
     pub(crate) already_applied: Vec<Event>,
@@ -160,15 +160,21 @@ impl EventLayer {
         Self { already_applied, to_apply, dag }
     }
 
+    /// The layer's events already reflected in the backend's state.
+    pub fn already_applied(&self) -> &[Event] { &self.already_applied }
+
+    /// The layer's events the backend has yet to apply.
+    pub fn to_apply(&self) -> &[Event] { &self.to_apply }
+
     /// Check whether an event_id is present in the accumulated DAG.
     /// Used by LWW to implement the "older than meet" rule.
-    pub(crate) fn dag_contains(&self, id: &EventId) -> bool { self.dag.contains_key(id) }
+    pub fn dag_contains(&self, id: &EventId) -> bool { self.dag.contains_key(id) }
 
     /// Compare two event IDs using accumulated DAG context.
     ///
     /// Returns the causal relation between `a` and `b`. Missing entries
     /// are treated as dead ends (below the meet), not errors.
-    pub(crate) fn compare(&self, a: &EventId, b: &EventId) -> CausalRelation {
+    pub fn compare(&self, a: &EventId, b: &EventId) -> CausalRelation {
         if a == b {
             return CausalRelation::Descends;
         }
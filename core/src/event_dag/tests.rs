@@ -2629,14 +2629,14 @@ mod quick_check_disjoint_verify {
     }
 }
 
-/// V4: the StrictDescends "gap-jump" in entity.rs applies ONLY the incoming
-/// event's operations and jumps the head, so a bridge batch applied out of
-/// causal order silently loses the skipped ancestors' operations. The
-/// remediation defense is ordering: batches are topologically sorted
-/// (parents first) before application on both the producer and receiver
-/// sides. The entity-level defense (replaying staged-but-unapplied ancestors
-/// inside the StrictDescends arm) is deliberately NOT part of this
-/// remediation; it is the B3 follow-up issue.
+/// V4: the StrictDescends "gap-jump" in entity.rs applied ONLY the incoming
+/// event's operations and jumped the head, so a bridge batch applied out of
+/// causal order silently lost the skipped ancestors' operations. The first
+/// defense is ordering: batches are topologically sorted (parents first)
+/// before application on both the producer and receiver sides. Ordering
+/// cannot cover two transactions committing to one entity at once (a durable
+/// peer being reconciled while the next transaction arrives), so the
+/// StrictDescends arm also replays stored-but-unapplied ancestors (B3).
 #[cfg(test)]
 mod strict_descends_gap_jump {
     use super::*;
@@ -2714,6 +2714,33 @@ mod strict_descends_gap_jump {
             "CONSISTENCY VIOLATION: head descends X but X's write (p1) is missing. p1={p1:?}"
         );
     }
+
+    /// B3: a descendant applied while its parent is stored but not yet applied
+    /// (another transaction committed X and has yet to apply it) brings X's
+    /// operations along instead of jumping over them.
+    #[tokio::test]
+    async fn test_strict_descends_gap_replays_skipped_ancestors() {
+        let mut entity_id_bytes = [0u8; 32];
+        entity_id_bytes[0] = 43;
+        let entity = Entity::create(EntityId::from_bytes(entity_id_bytes), "test".into());
+
+        let mut retriever = MockRetriever::new();
+        let ev_a = make_lww_event_with_parent(1, vec![("p0", "genesis")], &[]);
+        let ev_x = make_lww_event_with_parent(2, vec![("p1", "written_by_X")], &[ev_a.id()]);
+        let ev_b = make_lww_event_with_parent(3, vec![("p2", "written_by_B")], &[ev_x.id()]);
+        for event in [&ev_a, &ev_x, &ev_b] {
+            retriever.add_event(event.clone());
+        }
+        assert!(entity.apply_event(&retriever, &ev_a).await.unwrap());
+
+        // B arrives first; X is applied late, after B has already brought it in
+        assert!(entity.apply_event(&retriever, &ev_b).await.unwrap(), "B applies over the gap");
+        assert!(!entity.apply_event(&retriever, &ev_x).await.unwrap(), "X is already part of B's history");
+
+        assert_eq!(entity.head(), Clock::from(vec![ev_b.id()]));
+        assert_eq!(read_lww(&entity, "p1"), Some(Value::String("written_by_X".into())), "X's write came along with B");
+        assert_eq!(read_lww(&entity, "p2"), Some(Value::String("written_by_B".into())));
+    }
 }
 
 // ============================================================================
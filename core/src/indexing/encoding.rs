@@ -92,6 +92,34 @@ fn encode_value_component(value: &Value, expected_type: ValueType, descending: b
                 Ok(bytes.into_iter().map(|b| 0xFFu8.wrapping_sub(b)).collect())
             }
         }
+        (Value::U32(_), ValueType::U32) | (Value::U64(_), ValueType::U64) | (Value::I128(_), ValueType::I128) => {
+            // Fixed-width big-endian (sign-flipped for i128). DESC: invert payload bytes.
+            let bytes = value.to_bytes();
+            if !descending {
+                Ok(bytes)
+            } else {
+                Ok(bytes.into_iter().map(|b| 0xFFu8.wrapping_sub(b)).collect())
+            }
+        }
+        (Value::Timestamp(_), ValueType::Timestamp) | (Value::Date(_), ValueType::Date) | (Value::Uuid(_), ValueType::Uuid) => {
+            // Fixed-width collation bytes (sign-flipped for timestamps and dates). DESC: invert payload bytes.
+            let bytes = value.to_bytes();
+            if !descending {
+                Ok(bytes)
+            } else {
+                Ok(bytes.into_iter().map(|b| 0xFFu8.wrapping_sub(b)).collect())
+            }
+        }
+        (Value::Decimal(_), ValueType::Decimal) => {
+            // Variable-width but prefix-free (digits are never 0x00 and the encoding ends in 0x00),
+            // so no escaping is needed. DESC: invert payload bytes.
+            let bytes = value.to_bytes();
+            if !descending {
+                Ok(bytes)
+            } else {
+                Ok(bytes.into_iter().map(|b| 0xFFu8.wrapping_sub(b)).collect())
+            }
+        }
         (Value::Object(bytes) | Value::Binary(bytes), ValueType::Binary | ValueType::Object) => {
             if !descending {
                 // ASC: [escaped bytes][0x00] - terminator needed for variable-width
@@ -218,4 +246,26 @@ mod tests {
         // ASC: "a" should sort before "b"
         assert!(a < b);
     }
+
+    #[test]
+    fn decimal_keys_order_numerically_in_both_directions() {
+        let values = ["-12.5", "-1.25", "0", "0.001", "1.25", "1.250001", "12.5"];
+        for descending in [false, true] {
+            let keys: Vec<Vec<u8>> = values
+                .iter()
+                .map(|d| {
+                    // Follow each key with a larger component to check that the decimal is self-delimiting
+                    let mut key = encode_component_typed(&Value::Decimal(d.parse().unwrap()), ValueType::Decimal, descending).unwrap();
+                    key.extend(encode_component_typed(&Value::I32(i32::MAX), ValueType::I32, false).unwrap());
+                    key
+                })
+                .collect();
+            let mut sorted = keys.clone();
+            sorted.sort();
+            if descending {
+                sorted.reverse();
+            }
+            assert_eq!(sorted, keys, "descending: {descending}");
+        }
+    }
 }
@@ -18,6 +18,10 @@ pub enum NullsOrder {
     Last,
 }
 
+/// The sub-path step of a keypart over each element of a collection property
+/// (`tags.[]`). Query paths are identifiers, so no JSON key can collide with it.
+pub const ELEMENT_STEP: &str = "[]";
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct IndexKeyPart {
     pub column: String,
@@ -74,6 +78,15 @@ impl IndexKeyPart {
         Self { column, sub_path, direction, value_type, nulls: None, collation: None }
     }
 
+    /// Create an ascending keypart over the elements of the collection property `column`.
+    ///
+    /// An index with one is multi-entry: an entity has a key per element, so
+    /// `column CONTAINS ?` is an equality scan on it.
+    pub fn asc_element(column: &str, value_type: ValueType) -> Self { Self::asc_path(&element_path(column), value_type) }
+
+    /// Whether this keypart is over the elements of a collection rather than a single value
+    pub fn is_element(&self) -> bool { self.sub_path.as_deref().is_some_and(|sub_path| sub_path == [ELEMENT_STEP]) }
+
     /// Create ascending keypart from flat path
     pub fn asc_path(path: &str, value_type: ValueType) -> Self { Self::from_flat_path(path, IndexDirection::Asc, value_type) }
 
@@ -81,6 +94,9 @@ impl IndexKeyPart {
     pub fn desc_path(path: &str, value_type: ValueType) -> Self { Self::from_flat_path(path, IndexDirection::Desc, value_type) }
 }
 
+/// The flat path of the elements of the collection property `column` (`tags.[]`)
+pub fn element_path(column: &str) -> String { format!("{}.{}", column, ELEMENT_STEP) }
+
 impl IndexDirection {
     pub fn is_desc(&self) -> bool { matches!(self, IndexDirection::Desc) }
 }
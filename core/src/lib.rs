@@ -18,14 +18,18 @@ pub mod event_dag;
 pub mod bench_support;
 pub mod indexing;
 pub mod livequery;
+pub mod metrics;
 pub mod model;
 pub mod node;
 pub mod node_applier;
+pub mod outbox;
 pub mod peer_subscription;
 pub mod policy;
 pub mod property;
 pub mod query_value;
+pub mod quorum;
 pub mod reactor;
+pub mod replication;
 pub mod resultset;
 pub mod retrieval;
 pub mod selection;
@@ -14,7 +14,7 @@ use ankurah_signals::{
 use tracing::{debug, warn};
 
 use crate::{
-    changes::ChangeSet,
+    changes::{ChangeSet, ItemChange},
     entity::Entity,
     error::RetrievalError,
     model::View,
@@ -472,15 +472,26 @@ where R: Clone + Send + Sync + 'static
     }
 }
 
+// Untyped callers (such as gateways serving non-Ankurah clients) see the same changes as entities
+impl Subscribe<Vec<ItemChange<Entity>>> for EntityLiveQuery {
+    fn subscribe<L>(&self, listener: L) -> SubscriptionGuard
+    where L: IntoSubscribeListener<Vec<ItemChange<Entity>>> {
+        let listener = listener.into_subscribe_listener();
+        self.0.subscription.subscribe(move |reactor_update: ReactorUpdate| listener(item_changes_from(reactor_update, |entity| entity)))
+    }
+}
+
 /// Notably, this function does not filter by query_id, because it should only be used by LiveQuery, which entails a single-predicate subscription
 fn livequery_change_set_from<R: View>(resultset: ResultSet<R>, reactor_update: ReactorUpdate) -> ChangeSet<R>
 where R: View {
-    use crate::changes::{ChangeSet, ItemChange};
+    ChangeSet { changes: item_changes_from(reactor_update, R::from_entity), resultset }
+}
 
+fn item_changes_from<I>(reactor_update: ReactorUpdate, wrap: impl Fn(Entity) -> I) -> Vec<ItemChange<I>> {
     let mut changes = Vec::new();
 
     for item in reactor_update.items {
-        let view = R::from_entity(item.entity);
+        let view = wrap(item.entity);
 
         // Determine the change type based on predicate relevance
         // ignore the query_id, because it should only be used by LiveQuery, which entails a single-predicate subscription
@@ -502,5 +513,5 @@ where R: View {
         }
     }
 
-    ChangeSet { changes, resultset }
+    changes
 }
@@ -0,0 +1,151 @@
+//! Metrics emitted through the [`metrics`] facade
+//!
+//! Nothing is recorded until the application installs a recorder, such as the Prometheus
+//! exporter the websocket server can serve. Per-peer series are labelled with the short form
+//! of the peer's node id; they outlive the connection, so a server with heavy client churn
+//! may prefer a recorder that expires idle series.
+
+use std::sync::Arc;
+
+use ankurah_proto as proto;
+use async_trait::async_trait;
+use metrics::{counter, describe_counter, describe_gauge, describe_histogram, gauge, histogram, Unit};
+
+#[cfg(target_family = "wasm")]
+use web_time::Instant;
+
+#[cfg(not(target_family = "wasm"))]
+use std::time::Instant;
+
+use crate::{
+    connector::{PeerSender, SendError},
+    error::{MutationError, RetrievalError},
+    storage::StorageCollection,
+};
+
+/// Counter of peer messages received, labelled by `peer` and message `kind`
+pub const MESSAGES_RECEIVED: &str = "ankurah_peer_messages_received_total";
+/// Counter of peer messages sent, labelled by `peer` and message `kind`
+pub const MESSAGES_SENT: &str = "ankurah_peer_messages_sent_total";
+/// Counter of encoded bytes received from each `peer`, recorded by the connectors
+pub const BYTES_RECEIVED: &str = "ankurah_peer_bytes_received_total";
+/// Counter of encoded bytes sent to each `peer`, recorded by the connectors
+pub const BYTES_SENT: &str = "ankurah_peer_bytes_sent_total";
+/// Histogram of the time from sending a request to its response, labelled by request `kind`
+pub const REQUEST_DURATION: &str = "ankurah_request_duration_seconds";
+/// Gauge of the queries each `peer` holds subscribed on this node
+pub const PEER_SUBSCRIPTIONS: &str = "ankurah_peer_subscriptions";
+/// Histogram of how many reactor subscriptions one batch of changes is evaluated against
+pub const NOTIFICATION_FANOUT: &str = "ankurah_reactor_notification_fanout";
+/// Histogram of storage collection operation latency, labelled by `op`
+pub const STORAGE_DURATION: &str = "ankurah_storage_op_duration_seconds";
+
+/// Register units and descriptions for every metric above with the installed recorder
+pub fn describe() {
+    describe_counter!(MESSAGES_RECEIVED, "Peer messages received");
+    describe_counter!(MESSAGES_SENT, "Peer messages sent");
+    describe_counter!(BYTES_RECEIVED, Unit::Bytes, "Encoded bytes received from peers");
+    describe_counter!(BYTES_SENT, Unit::Bytes, "Encoded bytes sent to peers");
+    describe_histogram!(REQUEST_DURATION, Unit::Seconds, "Time from sending a request to a peer until its response");
+    describe_gauge!(PEER_SUBSCRIPTIONS, "Queries each peer holds subscribed on this node");
+    describe_histogram!(NOTIFICATION_FANOUT, "Reactor subscriptions evaluated per batch of changes");
+    describe_histogram!(STORAGE_DURATION, Unit::Seconds, "Storage collection operation latency");
+}
+
+fn peer_label(peer: proto::EntityId) -> String { peer.to_base64_short() }
+
+/// Record `bytes` of encoded frames received from `peer`
+pub fn bytes_received(peer: proto::EntityId, bytes: usize) { counter!(BYTES_RECEIVED, "peer" => peer_label(peer)).increment(bytes as u64) }
+
+/// Record `bytes` of encoded frames sent to `peer`
+pub fn bytes_sent(peer: proto::EntityId, bytes: usize) { counter!(BYTES_SENT, "peer" => peer_label(peer)).increment(bytes as u64) }
+
+pub(crate) fn message_received(peer: proto::EntityId, message: &proto::NodeMessage) {
+    counter!(MESSAGES_RECEIVED, "peer" => peer_label(peer), "kind" => message.kind()).increment(1)
+}
+
+pub(crate) fn request_completed(kind: &'static str, started: Instant) {
+    histogram!(REQUEST_DURATION, "kind" => kind).record(started.elapsed().as_secs_f64())
+}
+
+pub(crate) fn peer_subscriptions(peer: proto::EntityId, count: usize) {
+    gauge!(PEER_SUBSCRIPTIONS, "peer" => peer_label(peer)).set(count as f64)
+}
+
+pub(crate) fn notification_fanout(subscriptions: usize) { histogram!(NOTIFICATION_FANOUT).record(subscriptions as f64) }
+
+pub(crate) fn now() -> Instant { Instant::now() }
+
+/// Counts the messages a peer sender carries
+pub(crate) struct MeteredSender(Box<dyn PeerSender>);
+
+impl MeteredSender {
+    pub fn new(sender: Box<dyn PeerSender>) -> Self { Self(sender) }
+}
+
+#[async_trait]
+impl PeerSender for MeteredSender {
+    fn send_message(&self, message: proto::NodeMessage) -> Result<(), SendError> {
+        let kind = message.kind();
+        self.0.send_message(message)?;
+        counter!(MESSAGES_SENT, "peer" => peer_label(self.0.recipient_node_id()), "kind" => kind).increment(1);
+        Ok(())
+    }
+
+    fn recipient_node_id(&self) -> proto::EntityId { self.0.recipient_node_id() }
+
+    fn cloned(&self) -> Box<dyn PeerSender> { Box::new(MeteredSender(self.0.cloned())) }
+}
+
+/// Times every operation on a storage collection
+pub(crate) struct TimedCollection(Arc<dyn StorageCollection>);
+
+impl TimedCollection {
+    pub fn new(collection: Arc<dyn StorageCollection>) -> Self { Self(collection) }
+}
+
+async fn timed<T>(op: &'static str, f: impl std::future::Future<Output = T>) -> T {
+    let started = Instant::now();
+    let result = f.await;
+    histogram!(STORAGE_DURATION, "op" => op).record(started.elapsed().as_secs_f64());
+    result
+}
+
+#[async_trait]
+impl StorageCollection for TimedCollection {
+    async fn set_state(&self, state: proto::Attested<proto::EntityState>) -> Result<bool, MutationError> {
+        timed("set_state", self.0.set_state(state)).await
+    }
+
+    async fn get_state(&self, id: proto::EntityId) -> Result<proto::Attested<proto::EntityState>, RetrievalError> {
+        timed("get_state", self.0.get_state(id)).await
+    }
+
+    async fn fetch_states(&self, selection: &ankql::ast::Selection) -> Result<Vec<proto::Attested<proto::EntityState>>, RetrievalError> {
+        timed("fetch_states", self.0.fetch_states(selection)).await
+    }
+
+    async fn set_states(&self, states: Vec<proto::Attested<proto::EntityState>>) -> Result<(), MutationError> {
+        timed("set_states", self.0.set_states(states)).await
+    }
+
+    async fn get_states(&self, ids: Vec<proto::EntityId>) -> Result<Vec<proto::Attested<proto::EntityState>>, RetrievalError> {
+        timed("get_states", self.0.get_states(ids)).await
+    }
+
+    async fn add_event(&self, entity_event: &proto::Attested<proto::Event>) -> Result<bool, MutationError> {
+        timed("add_event", self.0.add_event(entity_event)).await
+    }
+
+    async fn get_events(&self, event_ids: Vec<proto::EventId>) -> Result<Vec<proto::Attested<proto::Event>>, RetrievalError> {
+        timed("get_events", self.0.get_events(event_ids)).await
+    }
+
+    async fn dump_entity_events(&self, id: proto::EntityId) -> Result<Vec<proto::Attested<proto::Event>>, RetrievalError> {
+        timed("dump_entity_events", self.0.dump_entity_events(id)).await
+    }
+
+    async fn verify(&self, options: crate::storage::VerifyOptions) -> Result<crate::storage::VerifyReport, RetrievalError> {
+        timed("verify", self.0.verify(options)).await
+    }
+}
@@ -4,8 +4,10 @@ use std::sync::Arc;
 
 use ankurah_proto::{CollectionId, EntityId, ModelId, State};
 
+use crate::context::Context;
 use crate::entity::{Entity, ProvisionalEntity};
-use crate::error::StateError;
+use crate::error::{RetrievalError, StateError};
+use crate::livequery::LiveQuery;
 
 use crate::property::PropertyError;
 
@@ -60,6 +62,50 @@ pub trait View {
     fn entity(&self) -> &Entity;
     fn from_entity(inner: Entity) -> Self;
     fn to_model(&self) -> Result<Self::Model, PropertyError>;
+
+    /// Fetch the entities of model `S` whose reference field `field` points at this entity.
+    ///
+    /// `field` is a `Ref<Self::Model>` (or `Option<..>`) or `Vec<Ref<Self::Model>>` field of
+    /// `S`. The lookup is an ordinary query on that field, so it uses the field's index in
+    /// each storage engine:
+    ///
+    /// ```rust,ignore
+    /// let tracks: Vec<TrackView> = album.backlinks::<Track>(&ctx, "album").await?;
+    /// ```
+    fn backlinks<S: Model>(&self, ctx: &Context, field: &str) -> impl std::future::Future<Output = Result<Vec<S::View>, RetrievalError>>
+    where Self: Sized {
+        let predicate = backlink_predicate::<S, Self::Model>(field, self.id());
+        async move { ctx.fetch(predicate?).await }
+    }
+
+    /// Like [`View::backlinks`], but as a [`LiveQuery`] that follows references as they are
+    /// added, retargeted or removed.
+    fn query_backlinks<S: Model>(
+        &self,
+        ctx: &Context,
+        field: &str,
+    ) -> impl std::future::Future<Output = Result<LiveQuery<S::View>, RetrievalError>>
+    where
+        Self: Sized,
+    {
+        let predicate = backlink_predicate::<S, Self::Model>(field, self.id());
+        async move { ctx.query_wait(predicate?).await }
+    }
+}
+
+/// `field CONTAINS target` for a reference set (`Vec<Ref<T>>`), else `field = target`
+fn backlink_predicate<S: Model, T: Model>(field: &str, target: EntityId) -> Result<ankql::ast::Predicate, RetrievalError> {
+    use ankql::ast::{ComparisonOperator, Expr, PathExpr, Predicate};
+    let property = S::descriptor()
+        .field_by_name(field)
+        .filter(|property| property.target_label == Some(T::descriptor().label))
+        .ok_or_else(|| RetrievalError::NotAReference { collection: S::collection(), field: field.to_string(), target: T::collection() })?;
+    let operator = if property.reference_set { ComparisonOperator::Contains } else { ComparisonOperator::Equal };
+    Ok(Predicate::Comparison {
+        left: Box::new(Expr::Path(PathExpr::simple(field))),
+        operator,
+        right: Box::new(Expr::Literal(crate::value::Value::EntityId(target))),
+    })
 }
 
 /// A lifetime-constrained wrapper around a Mutable for compile-time transaction safety
@@ -191,6 +237,24 @@ pub fn js_preprocess_ref_field(obj: &wasm_bindgen::JsValue, field_name: &str) ->
     Ok(())
 }
 
+// Helper function for Timestamp field preprocessing in tsify from_wasm_abi.
+// Replaces a JS Date with its RFC 3339 text, which Timestamp deserializes from.
+// Strings are left for deserialization to validate.
+#[doc(hidden)]
+#[cfg(feature = "wasm")]
+pub fn js_preprocess_timestamp_field(obj: &wasm_bindgen::JsValue, field_name: &str) -> Result<(), wasm_bindgen::JsValue> {
+    use wasm_bindgen::JsCast;
+    let field_key = wasm_bindgen::JsValue::from_str(field_name);
+    if let Ok(v) = js_sys::Reflect::get(obj, &field_key) {
+        if v.is_instance_of::<js_sys::Date>() {
+            let timestamp = crate::value::Timestamp::try_from(v)
+                .map_err(|_| wasm_bindgen::JsValue::from_str(&format!("Field '{}' must be a valid Date", field_name)))?;
+            js_sys::Reflect::set(obj, &field_key, &wasm_bindgen::JsValue::from_str(&timestamp.to_string()))?;
+        }
+    }
+    Ok(())
+}
+
 // Populate AnkQL placeholders from variadic JS substitution values, mapping
 // EntityId instances to typed EntityId literals so Ref-field comparisons
 // collate consistently across fetch and live-update paths
@@ -1,5 +1,11 @@
 use crate::selection::filter::Filterable;
-use crate::{schema::catalog::CatalogManager, session::SessionSet};
+use crate::{
+    outbox::Outbox,
+    quorum::{CommitOutcome, CommitPolicy},
+    replication::{ReplicationHandle, ReplicationReport},
+    schema::catalog::CatalogManager,
+    session::SessionSet,
+};
 use ankurah_proto::{self as proto, Attested, CollectionId, EntityState};
 use anyhow::anyhow;
 
@@ -8,7 +14,10 @@ use rand::rngs::SmallRng;
 use std::{
     fmt,
     ops::Deref,
-    sync::{Arc, Mutex, Weak},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, Weak,
+    },
 };
 use tokio::sync::oneshot;
 
@@ -20,14 +29,14 @@ use crate::{
     context::Context,
     entity::{Entity, WeakEntitySet},
     error::{MutationError, RequestError, RetrievalError},
-    notice_info,
+    metrics, notice_info,
     peer_subscription::{SubscriptionHandler, SubscriptionRelay},
     policy::{AccessDenied, PolicyAgent},
     reactor::{AbstractEntity, Reactor},
     retrieval::{LocalEventGetter, LocalStateGetter, SuspenseEvents},
     storage::StorageEngine,
     system::SystemManager,
-    util::{safemap::SafeMap, safeset::SafeSet, Iterable},
+    util::{request_fence::RequestFence, safemap::SafeMap, safeset::SafeSet, Iterable},
 };
 use itertools::Itertools;
 #[cfg(feature = "instrument")]
@@ -130,6 +139,11 @@ where PA: PolicyAgent
     pub(crate) entities: WeakEntitySet,
     peer_connections: SafeMap<proto::EntityId, Arc<PeerState<PA::ContextData>>>,
     durable_peers: SafeSet<proto::EntityId>,
+    /// Durable peers declared with [`Node::expect_durable_peer`]. Commit quorums are sized over
+    /// these together with the connected durable peers, so a declared peer that is down still
+    /// counts against them. Peers are not remembered merely for having connected: node ids
+    /// change with every process, so a restarted or redirected server would count twice.
+    expected_durable_peers: SafeSet<proto::EntityId>,
 
     /// Per-node source of randomness for peer selection. Seeded from entropy in production;
     /// an explicit seed can be injected at construction so the simulation harness and tests
@@ -137,6 +151,9 @@ where PA: PolicyAgent
     /// RNG state and Node is shared across tasks; the lock is only ever held for a single draw.
     rng: Mutex<SmallRng>,
 
+    /// How many durable peers must accept a transaction before its commit returns
+    commit_policy: std::sync::RwLock<CommitPolicy>,
+
     /// The continuous superset of every session backing a context on
     /// this node: each context attaches its credential source here at
     /// construction, so any state the node acts under is enumerable
@@ -152,10 +169,21 @@ where PA: PolicyAgent
     /// maintains it; nothing resolves through it yet).
     pub catalog: CatalogManager<SE, PA>,
 
+    /// Transactions committed on this (ephemeral) node that a durable peer has not confirmed
+    /// yet; empty on durable nodes
+    pub outbox: Outbox<PA::ContextData>,
+
     pub(crate) subscription_relay: Option<SubscriptionRelay<PA::ContextData, crate::livequery::WeakEntityLiveQuery>>,
 
     /// Type resolver for AST preparation (temporary heuristic until Phase 3 schema)
     pub(crate) type_resolver: crate::TypeResolver,
+
+    /// Set once [`Node::drain`] is called; peers' new subscriptions are refused from then on
+    draining: AtomicBool,
+    /// Leased by each commit while it applies, whether a peer's or one made locally (including
+    /// gateway writes), so draining can wait them out. Never invalidated: commits keep being
+    /// admitted while the node drains.
+    pub(crate) commits: RequestFence,
 }
 
 impl<SE, PA> Node<SE, PA>
@@ -197,15 +225,20 @@ where
             entities: entityset,
             peer_connections: SafeMap::new(),
             durable_peers: SafeSet::new(),
+            expected_durable_peers: SafeSet::new(),
             rng: Mutex::new(rng),
+            commit_policy: std::sync::RwLock::new(CommitPolicy::default()),
             reactor,
             durable,
             policy_agent,
             system: system_manager,
             catalog: catalog.clone(),
             sessions: SessionSet::new(),
+            outbox: Outbox::new(),
             subscription_relay,
             type_resolver: crate::TypeResolver::new(),
+            draining: AtomicBool::new(false),
+            commits: RequestFence::new(),
         }));
 
         // Set up the message sender for the subscription relay
@@ -219,31 +252,40 @@ where
         node.policy_agent.on_node_ready(node.weak());
         node.catalog.start(node.weak());
 
+        // Publish whatever a previous run left unsynced
+        if !durable {
+            let me = node.clone();
+            crate::task::spawn(async move {
+                if let Err(e) = me.outbox.load(&me).await {
+                    error!("Node({}) failed to load outbox: {}", me.id, e);
+                }
+            });
+        }
+
         node
     }
     pub fn weak(&self) -> WeakNode<SE, PA> { WeakNode(Arc::downgrade(&self.0)) }
 
     /// Register a peer connection after its Presence handshake.
     ///
-    /// Refuses (without registering anything) when the peer's protocol
-    /// version is incompatible; the connector should relay the returned
-    /// rejection best-effort and close the connection. Enforced here, not
-    /// in connectors, so every transport inherits it.
+    /// Returns the protocol version the pair settled on: the highest both
+    /// ends speak. Refuses (without registering anything) when the peer
+    /// speaks no version in common with ours; the connector should relay the
+    /// returned rejection best-effort and close the connection. Enforced
+    /// here, not in connectors, so every transport inherits it.
     #[cfg_attr(feature = "instrument", instrument(level = "debug", skip_all, fields(node_id = %presence.node_id.to_base64_short(), durable = %presence.durable)))]
-    pub fn register_peer(&self, presence: proto::Presence, sender: Box<dyn PeerSender>) -> Result<(), proto::PresenceRejection> {
+    pub fn register_peer(&self, presence: proto::Presence, sender: Box<dyn PeerSender>) -> Result<u32, proto::PresenceRejection> {
         action_info!(self, "register_peer", "{}", &presence);
 
-        if !proto::protocol_compatible(presence.protocol_version) {
-            let rejection = proto::PresenceRejection { expected: proto::PROTOCOL_VERSION, received: presence.protocol_version };
+        let protocol_version = presence.negotiate_version().inspect_err(|rejection| {
             warn!("Node({}) refusing peer {}: {}", self.id, presence.node_id, rejection);
-            return Err(rejection);
-        }
+        })?;
 
         let subscription_handler = SubscriptionHandler::new(presence.node_id, self);
         self.peer_connections.insert(
             presence.node_id,
             Arc::new(PeerState {
-                sender,
+                sender: Box::new(metrics::MeteredSender::new(sender)),
                 _durable: presence.durable,
                 subscription_handler,
                 pending_requests: SafeMap::new(),
@@ -263,10 +305,14 @@ where
                     action_info!(self, "received system root", "{}", &system_root.payload);
                     let me = self.clone();
                     crate::task::spawn(async move {
-                        if let Err(e) = me.system.join_system(system_root).await {
+                        if let Err(e) = me.outbox.while_joining(&me, me.system.join_system(system_root)).await {
                             action_error!(me, "failed to join system", "{}", &e);
                         } else {
                             action_info!(me, "successfully joined system");
+                            // Replay what was committed while disconnected
+                            if let Err(e) = me.outbox.flush(&me).await {
+                                action_error!(me, "failed to flush outbox", "{}", &e);
+                            }
                         }
                     });
                 } else {
@@ -275,7 +321,7 @@ where
             }
         }
         // TODO send hello message to the peer, including present head state for all relevant collections
-        Ok(())
+        Ok(protocol_version)
     }
     #[cfg_attr(feature = "instrument", instrument(level = "debug", skip_all, fields(node_id = %node_id.to_base64_short())))]
     pub fn deregister_peer(&self, node_id: proto::EntityId) {
@@ -293,6 +339,18 @@ where
             relay.notify_peer_disconnected(node_id);
         }
     }
+
+    /// Start draining ahead of a shutdown: refuse new subscriptions from peers, then wait
+    /// for the commits already underway, local or from peers, to finish. Everything else keeps being served,
+    /// so the connector can tell its peers to reconnect elsewhere once this returns.
+    pub async fn drain(&self) {
+        notice_info!("Node({:#}) draining", self.id);
+        self.draining.store(true, Ordering::Release);
+        self.commits.wait_drained().await;
+    }
+
+    /// Whether [`Node::drain`] has been called
+    pub fn is_draining(&self) -> bool { self.draining.load(Ordering::Acquire) }
     #[cfg_attr(feature = "instrument", instrument(skip_all, fields(node_id = %node_id, request_body = %request_body)))]
     pub async fn request<'a, C>(
         &self,
@@ -312,11 +370,15 @@ where
         // Get the peer connection
         let connection = self.peer_connections.get(&node_id).ok_or(RequestError::PeerNotConnected)?;
 
+        let kind = request.body.kind();
         connection.pending_requests.insert(request_id, response_tx);
+        let started = metrics::now();
         connection.send_message(proto::NodeMessage::Request { auth, request })?;
 
         // Wait for response
-        response_rx.await.map_err(|_| RequestError::InternalChannelClosed)?
+        let response = response_rx.await.map_err(|_| RequestError::InternalChannelClosed)?;
+        metrics::request_completed(kind, started);
+        response
     }
 
     // TODO LATER: rework this to be retried in the background some number of times
@@ -352,6 +414,7 @@ where
     // Not if its signed by a node key.
     #[cfg_attr(feature = "instrument", instrument(level = "debug", skip_all, fields(message = %message)))]
     pub async fn handle_message(&self, message: proto::NodeMessage) -> anyhow::Result<()> {
+        metrics::message_received(message.from(), &message);
         match message {
             proto::NodeMessage::Update(update) => {
                 debug!("Node({}) received update {}", self.id, update);
@@ -457,6 +520,7 @@ where
                 // With moderate potential for duplication, while not creating message loops
                 // Doing so would be a secondary/tertiary/etc hop for this message
                 let cdata = cdata.iterable().exactly_one().map_err(|_| anyhow!("Only one cdata is permitted for CommitTransaction"))?;
+                let _commit = self.commits.try_acquire();
                 match self.commit_remote_transaction(cdata, id.clone(), events).await {
                     Ok(_) => Ok(proto::NodeResponseBody::CommitComplete { id }),
                     Err(e) => Ok(proto::NodeResponseBody::Error(e.to_string())),
@@ -534,7 +598,69 @@ where
 
                 Ok(proto::NodeResponseBody::GetEvents(events))
             }
+            proto::NodeRequestBody::ListCollections => {
+                if !self.durable {
+                    return Ok(proto::NodeResponseBody::Error("only durable nodes serve replication".to_string()));
+                }
+                let collections = self
+                    .collections
+                    .list_stored_collections()
+                    .await?
+                    .into_iter()
+                    .filter(|collection| !crate::storage::local::is_node_local(collection))
+                    .filter(|collection| self.policy_agent.can_access_collection(cdata, collection).is_ok())
+                    .collect();
+                Ok(proto::NodeResponseBody::Collections(collections))
+            }
+            proto::NodeRequestBody::CollectionHeads { collection, after, limit } => {
+                if !self.durable {
+                    return Ok(proto::NodeResponseBody::Error("only durable nodes serve replication".to_string()));
+                }
+                self.policy_agent.can_access_collection(cdata, &collection)?;
+                let storage_collection = self.collections.get(&collection).await?;
+                let mut selection = crate::replication::heads_selection(after, limit);
+                selection.predicate = self.policy_agent.filter_predicate(cdata, &collection, selection.predicate)?;
+
+                let states = storage_collection.fetch_states(&selection).await?;
+                // A full page may not be the last one; the next starts after its last entity,
+                // whether or not that one is readable
+                let next = match selection.limit {
+                    Some(limit) if states.len() as u64 >= limit => states.last().map(|state| state.payload.entity_id),
+                    _ => None,
+                };
+                let heads = states
+                    .into_iter()
+                    .filter(|state| {
+                        self.policy_agent.check_read(cdata, &state.payload.entity_id, &collection, &state.payload.state).is_ok()
+                    })
+                    .map(|state| proto::KnownEntity { entity_id: state.payload.entity_id, head: state.payload.state.head })
+                    .collect();
+                Ok(proto::NodeResponseBody::CollectionHeads { heads, next })
+            }
+            proto::NodeRequestBody::Replicate { collection, known } => {
+                if !self.durable {
+                    return Ok(proto::NodeResponseBody::Error("only durable nodes serve replication".to_string()));
+                }
+                self.policy_agent.can_access_collection(cdata, &collection)?;
+                let storage_collection = self.collections.get(&collection).await?;
+                let known: std::collections::HashMap<_, _> = known.into_iter().map(|k| (k.entity_id, k.head)).collect();
+
+                let mut deltas = Vec::new();
+                for state in storage_collection.get_states(known.keys().copied().collect()).await? {
+                    if self.policy_agent.check_read(cdata, &state.payload.entity_id, &collection, &state.payload.state).is_err() {
+                        continue;
+                    }
+                    let head = known.get(&state.payload.entity_id).cloned().unwrap_or_default();
+                    if let Some(delta) = crate::replication::replication_delta(self, &storage_collection, &head, state, cdata).await? {
+                        deltas.push(delta);
+                    }
+                }
+                Ok(proto::NodeResponseBody::Replicated(deltas))
+            }
             proto::NodeRequestBody::SubscribeQuery { query_id, collection, selection, version, known_matches } => {
+                if self.is_draining() {
+                    return Ok(proto::NodeResponseBody::Draining);
+                }
                 let peer_state = self.peer_connections.get(&request.from).ok_or_else(|| anyhow!("Peer {} not connected", request.from))?;
                 // Reads may act under many credentials (the union), and a
                 // context can already hold several: what is missing is
@@ -564,30 +690,84 @@ where
         }
     }
 
+    /// Relay a locally committed transaction to the durable peers.
+    ///
+    /// Durable nodes relay directly, waiting for as many peers as the node's [`CommitPolicy`]
+    /// requires. Ephemeral nodes queue the transaction in the [`Outbox`]
+    /// first, so it is replayed on reconnect if no durable peer can confirm it now. Only an
+    /// explicit rejection fails the commit, and only when the transaction was relayed right away
+    /// (it stays applied locally either way); one queued behind earlier transactions returns once
+    /// persisted and reports its outcome through [`Outbox::statuses`].
     pub(crate) async fn relay_to_required_peers(
         &self,
+        sessions: &SessionSet<PA::ContextData>,
         cdata: &PA::ContextData,
         id: proto::TransactionId,
         events: &[Attested<proto::Event>],
     ) -> Result<(), MutationError> {
-        // TODO determine how many durable peers need to respond before we can proceed. The others should continue in the background.
-        // as of this writing, we only have one durable peer, so we can just await the response from "all" of them
-        for peer_id in self.get_durable_peers() {
-            match self.request(peer_id, cdata, proto::NodeRequestBody::CommitTransaction { id: id.clone(), events: events.to_vec() }).await
-            {
-                Ok(proto::NodeResponseBody::CommitComplete { .. }) => (),
-                Ok(proto::NodeResponseBody::Error(e)) => {
-                    return Err(MutationError::General(Box::new(std::io::Error::other(format!("Peer {} rejected: {}", peer_id, e)))));
-                }
-                _ => {
-                    return Err(MutationError::General(Box::new(std::io::Error::other(format!(
-                        "Peer {} returned unexpected response",
-                        peer_id
-                    )))));
-                }
+        if !self.durable {
+            return self.outbox.commit(self, sessions, id, events).await;
+        }
+
+        if self.durable_quorum_size() == 0 {
+            return Ok(());
+        }
+        let peers = self.get_durable_peers();
+        match crate::quorum::relay_commit(self, peers, self.commit_policy(), cdata, &id, events).await {
+            CommitOutcome::Confirmed => Ok(()),
+            CommitOutcome::Rejected(reason) | CommitOutcome::Undelivered(reason) => {
+                Err(MutationError::General(Box::new(std::io::Error::other(reason))))
             }
         }
-        Ok(())
+    }
+
+    /// How many durable peers must accept a transaction committed on this node
+    pub fn commit_policy(&self) -> CommitPolicy { *self.commit_policy.read().unwrap() }
+
+    /// Set how many durable peers must accept a transaction before its commit returns.
+    /// Defaults to [`CommitPolicy::All`].
+    pub fn set_commit_policy(&self, policy: CommitPolicy) { *self.commit_policy.write().unwrap() = policy; }
+
+    /// Count `peer_id` towards commit quorums whether or not it is connected. Without any
+    /// expected peers, quorums are sized over the durable peers connected at the time.
+    pub fn expect_durable_peer(&self, peer_id: proto::EntityId) { self.expected_durable_peers.insert(peer_id); }
+
+    /// Stop counting `peer_id` towards commit quorums while it is disconnected
+    pub fn forget_durable_peer(&self, peer_id: proto::EntityId) { self.expected_durable_peers.remove(&peer_id); }
+
+    /// Number of durable peers commit quorums are sized over: the expected ones plus any other
+    /// connected durable peer
+    pub fn durable_quorum_size(&self) -> usize {
+        let mut peers: std::collections::HashSet<_> = self.expected_durable_peers.to_vec().into_iter().collect();
+        peers.extend(self.durable_peers.to_vec());
+        peers.len()
+    }
+
+    /// Pull everything a connected durable peer stores that this durable node lacks, resuming
+    /// each collection's sweep where the last one stopped. See [`crate::replication`].
+    pub async fn replicate_from(&self, peer_id: proto::EntityId, cdata: &PA::ContextData) -> Result<ReplicationReport, RetrievalError> {
+        crate::replication::replicate(self, peer_id, cdata, crate::replication::PAGE_SIZE, None).await
+    }
+
+    /// TEST ONLY: Replicate from a durable peer in pages of `page_size`, stopping after `pages`
+    /// pages as if interrupted.
+    ///
+    /// Requires the `test-helpers` feature to be enabled.
+    #[cfg(feature = "test-helpers")]
+    pub async fn replicate_pages_from(
+        &self,
+        peer_id: proto::EntityId,
+        cdata: &PA::ContextData,
+        page_size: u64,
+        pages: usize,
+    ) -> Result<ReplicationReport, RetrievalError> {
+        crate::replication::replicate(self, peer_id, cdata, page_size, Some(pages)).await
+    }
+
+    /// Replicate from a connected durable peer every `interval`, until the returned handle is
+    /// dropped or the peer disconnects.
+    pub fn spawn_replication(&self, peer_id: proto::EntityId, cdata: PA::ContextData, interval: std::time::Duration) -> ReplicationHandle {
+        ReplicationHandle::spawn(self, peer_id, cdata, interval)
     }
 
     /// Does all the things necessary to commit a remote transaction
@@ -696,7 +876,13 @@ where
                 let attestation = self.policy_agent.attest_state(self, &entity_state);
                 let attested = Attested::opt(entity_state, attestation);
                 collection.set_state(attested).await?;
-                changes.push(EntityChange::new(entity.clone(), vec![event.clone()])?);
+                // A concurrent commit to the same entity (a reconciliation racing the next
+                // transaction, say) may already have moved the head past this event. It is
+                // applied and stored regardless; that commit notifies the newer state.
+                match EntityChange::new(entity.clone(), vec![event.clone()]) {
+                    Ok(change) => changes.push(change),
+                    Err(_) => debug!("{self} event {} of transaction {id} was superseded by a concurrent commit", event.payload.id()),
+                }
             }
         }
 
@@ -848,6 +1034,10 @@ where
     /// persisted state.
     pub fn get_resident_entity(&self, id: proto::EntityId) -> Option<crate::entity::Entity> { self.entities.get(&id) }
 
+    /// The agent this node checks requests and writes with, for servers that accept requests over
+    /// transports of their own and must authenticate them the way a peer's would be.
+    pub fn policy_agent(&self) -> &PA { &self.policy_agent }
+
     /// Build a context over its credential source: bare ContextData, an
     /// existing session handle, or a whole [`SessionSet`] —
     /// attached to the node's registry at construction.
@@ -0,0 +1,529 @@
+//! Durable outbox for transactions committed on ephemeral nodes
+//!
+//! An ephemeral node commits locally first and then relays the transaction to its durable
+//! peers. Without an outbox, a transaction committed while no durable peer is connected was
+//! never relayed at all. Instead, every relayed transaction is queued here, persisted through
+//! the node's own [`StorageEngine`] (so the queue survives a restart or a browser reload), and
+//! replayed in commit order whenever a durable peer is (re)connected.
+//!
+//! Queued transactions are stored as [node-local records](crate::storage::local) in the reserved
+//! [`OUTBOX_COLLECTION_ID`] collection, one slot per transaction. Storage engines cannot delete
+//! states, so settled slots are marked free and reused by later transactions; the collection
+//! never grows past the largest number of transactions that were unsynced at once.
+//!
+//! Slots carry a sequence number, and the queue is read from storage once per node start and
+//! then kept in memory; a replay reads only the events of the slot it is about to relay. A commit
+//! is relayed before it returns only when nothing older is pending, so it never waits on a backlog;
+//! otherwise it is persisted and left to the background replay, in order.
+//!
+//! A transaction the durable peer rejects is reported as [`TransactionStatus::Rejected`] and
+//! kept until dismissed. Its events remain applied locally whether it was rejected in the
+//! background or while its commit waited (that commit still fails with the rejection): the
+//! outbox surfaces the rejection, it does not roll the local state back.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use ankurah_proto::{self as proto, Attested, CollectionId, EntityId, EntityState, Event};
+use ankurah_signals::{Mut, Read};
+use tracing::{debug, warn};
+
+use crate::error::{MutationError, RetrievalError};
+use crate::node::Node;
+use crate::policy::PolicyAgent;
+use crate::quorum::{relay_commit, CommitOutcome};
+use crate::session::{ContextData, SessionSet};
+use crate::storage::{local, StorageCollectionWrapper, StorageEngine};
+use crate::value::Value;
+
+/// Reserved collection holding the outbox slots of an ephemeral node
+pub const OUTBOX_COLLECTION_ID: &str = "_ankurah_outbox";
+
+/// How many confirmed transactions keep reporting [`TransactionStatus::Confirmed`]
+const RECENT_CONFIRMED: usize = 256;
+
+/// Sync status of a locally committed transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionStatus {
+    /// Committed locally; not yet confirmed by a durable peer
+    Pending,
+    /// Confirmed by the durable peers connected when it was relayed
+    Confirmed,
+    /// Refused by a durable peer, with its reason
+    Rejected(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SlotStatus {
+    Free,
+    Pending,
+    Rejected,
+}
+
+impl SlotStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SlotStatus::Free => "free",
+            SlotStatus::Pending => "pending",
+            SlotStatus::Rejected => "rejected",
+        }
+    }
+
+    fn parse(status: &str) -> Option<Self> {
+        match status {
+            "free" => Some(SlotStatus::Free),
+            "pending" => Some(SlotStatus::Pending),
+            "rejected" => Some(SlotStatus::Rejected),
+            _ => None,
+        }
+    }
+}
+
+/// One persisted outbox slot
+#[derive(Debug, Clone)]
+struct Slot {
+    id: EntityId,
+    seq: u64,
+    status: SlotStatus,
+    transaction: Option<proto::TransactionId>,
+    reason: Option<String>,
+    events: Vec<Attested<Event>>,
+}
+
+impl Slot {
+    fn to_state(&self, collection: &CollectionId) -> Result<Attested<EntityState>, MutationError> {
+        let transaction = self.transaction.as_ref().map(bincode::serialize).transpose()?;
+        local::record_state(
+            self.id,
+            collection,
+            [
+                ("seq", Some(Value::I64(self.seq as i64))),
+                ("status", Some(Value::String(self.status.as_str().to_string()))),
+                ("transaction", transaction.map(Value::Binary)),
+                ("reason", self.reason.clone().map(Value::String)),
+                ("events", Some(Value::Binary(bincode::serialize(&self.events)?))),
+            ],
+        )
+    }
+
+    fn from_state(state: &Attested<EntityState>) -> Result<Self, RetrievalError> {
+        let malformed = |what: &str| RetrievalError::Other(format!("malformed outbox slot {}: {}", state.payload.entity_id, what));
+        let values = local::record_values(state)?;
+        let value = |name: &str| values.get(name).cloned().flatten();
+
+        let seq = match value("seq") {
+            Some(Value::I64(seq)) => seq as u64,
+            _ => return Err(malformed("seq")),
+        };
+        let status = match value("status") {
+            Some(Value::String(status)) => SlotStatus::parse(&status).ok_or_else(|| malformed("status"))?,
+            _ => return Err(malformed("status")),
+        };
+        let transaction = match value("transaction") {
+            Some(Value::Binary(bytes)) => Some(bincode::deserialize(&bytes)?),
+            _ => None,
+        };
+        let reason = match value("reason") {
+            Some(Value::String(reason)) => Some(reason),
+            _ => None,
+        };
+        let events = match value("events") {
+            Some(Value::Binary(bytes)) => bincode::deserialize(&bytes)?,
+            _ => Vec::new(),
+        };
+        Ok(Slot { id: state.payload.entity_id, seq, status, transaction, reason, events })
+    }
+}
+
+/// Queue of transactions awaiting confirmation by a durable peer. See the [module docs](self).
+pub struct Outbox<CD: ContextData>(Arc<Inner<CD>>);
+
+impl<CD: ContextData> Clone for Outbox<CD> {
+    fn clone(&self) -> Self { Self(self.0.clone()) }
+}
+
+/// The persisted slots as last read or written, without their events
+#[derive(Default)]
+struct Table {
+    /// Whether storage has been read since the node started
+    loaded: bool,
+    /// Pending and rejected slots by sequence number
+    queued: BTreeMap<u64, Slot>,
+    /// Settled slots, reused by later transactions
+    free: Vec<EntityId>,
+    next_seq: u64,
+}
+
+impl Table {
+    fn fill(&mut self, slots: Vec<Slot>) {
+        for mut slot in slots {
+            self.next_seq = self.next_seq.max(slot.seq + 1);
+            if slot.status == SlotStatus::Free {
+                self.free.push(slot.id);
+            } else {
+                slot.events.clear();
+                self.queued.insert(slot.seq, slot);
+            }
+        }
+        self.loaded = true;
+    }
+
+    /// The oldest transaction still waiting to be relayed
+    fn next_pending(&self) -> Option<&Slot> { self.queued.values().find(|slot| slot.status == SlotStatus::Pending) }
+}
+
+/// What one replay step did
+enum Step {
+    /// Settled the oldest pending transaction (or found storage no longer holds it)
+    Settled,
+    /// Nothing is pending, or the oldest pending transaction cannot be delivered now
+    Stopped,
+}
+
+struct Inner<CD: ContextData> {
+    /// Serializes replays, so transactions are relayed strictly in commit order
+    relay: tokio::sync::Mutex<()>,
+    /// Held while the table and the slots in storage are brought in line, never across a relay
+    table: tokio::sync::Mutex<Table>,
+    /// Sessions of transactions committed since this node started, used to replay them under
+    /// their committer's current credential
+    sessions: Mutex<HashMap<proto::TransactionId, SessionSet<CD>>>,
+    recent_confirmed: Mutex<VecDeque<proto::TransactionId>>,
+    statuses: Mut<BTreeMap<proto::TransactionId, TransactionStatus>>,
+    unsynced: Mut<usize>,
+}
+
+impl<CD: ContextData> Default for Outbox<CD> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<CD: ContextData> Outbox<CD> {
+    pub fn new() -> Self {
+        Self(Arc::new(Inner {
+            relay: tokio::sync::Mutex::new(()),
+            table: tokio::sync::Mutex::new(Table::default()),
+            sessions: Mutex::new(HashMap::new()),
+            recent_confirmed: Mutex::new(VecDeque::new()),
+            statuses: Mut::new(BTreeMap::new()),
+            unsynced: Mut::new(0),
+        }))
+    }
+
+    /// Number of locally committed transactions not yet confirmed by a durable peer
+    pub fn unsynced(&self) -> Read<usize> { self.0.unsynced.read() }
+
+    /// Status of every pending and rejected transaction, plus recently confirmed ones
+    pub fn statuses(&self) -> Read<BTreeMap<proto::TransactionId, TransactionStatus>> { self.0.statuses.read() }
+
+    /// Status of one transaction, if it is pending, rejected or was recently confirmed
+    pub fn status(&self, id: &proto::TransactionId) -> Option<TransactionStatus> {
+        self.0.statuses.with(|statuses| statuses.get(id).cloned())
+    }
+
+    async fn collection<SE, PA>(node: &Node<SE, PA>) -> Result<StorageCollectionWrapper, RetrievalError>
+    where
+        SE: StorageEngine + Send + Sync + 'static,
+        PA: PolicyAgent<ContextData = CD> + Send + Sync + 'static,
+    {
+        node.collections.get(&CollectionId::fixed_name(OUTBOX_COLLECTION_ID)).await
+    }
+
+    /// Read every persisted slot into the table, once per node start
+    async fn ensure_loaded<SE, PA>(&self, node: &Node<SE, PA>, table: &mut Table) -> Result<(), RetrievalError>
+    where
+        SE: StorageEngine + Send + Sync + 'static,
+        PA: PolicyAgent<ContextData = CD> + Send + Sync + 'static,
+    {
+        if table.loaded {
+            return Ok(());
+        }
+        let all = ankql::ast::Selection { predicate: ankql::ast::Predicate::True, order_by: None, limit: None };
+        let mut slots = Vec::new();
+        for state in Self::collection(node).await?.fetch_states(&all).await? {
+            match Slot::from_state(&state) {
+                Ok(slot) => slots.push(slot),
+                Err(e) => warn!("Skipping outbox slot: {}", e),
+            }
+        }
+        table.fill(slots);
+        self.publish(table);
+        Ok(())
+    }
+
+    /// Publish the statuses of the queued slots (keeping recently confirmed transactions)
+    fn publish(&self, table: &Table) {
+        let mut statuses = BTreeMap::new();
+        for id in self.0.recent_confirmed.lock().unwrap().iter() {
+            statuses.insert(id.clone(), TransactionStatus::Confirmed);
+        }
+        let mut unsynced = 0;
+        for slot in table.queued.values() {
+            let Some(id) = slot.transaction.clone() else { continue };
+            match slot.status {
+                SlotStatus::Free => {}
+                SlotStatus::Pending => {
+                    statuses.insert(id, TransactionStatus::Pending);
+                    unsynced += 1;
+                }
+                SlotStatus::Rejected => {
+                    statuses.insert(id, TransactionStatus::Rejected(slot.reason.clone().unwrap_or_default()));
+                }
+            }
+        }
+        self.0.statuses.set(statuses);
+        if self.0.unsynced.value() != unsynced {
+            self.0.unsynced.set(unsynced);
+        }
+    }
+
+    /// Load the persisted outbox and publish its statuses (after a restart or reload)
+    pub(crate) async fn load<SE, PA>(&self, node: &Node<SE, PA>) -> Result<(), RetrievalError>
+    where
+        SE: StorageEngine + Send + Sync + 'static,
+        PA: PolicyAgent<ContextData = CD> + Send + Sync + 'static,
+    {
+        let mut table = self.0.table.lock().await;
+        self.ensure_loaded(node, &mut table).await
+    }
+
+    /// Join a system with the outbox loaded beforehand and held still throughout. A join that
+    /// replaces a mismatched root wipes storage; a replay drops the slots it no longer finds.
+    pub(crate) async fn while_joining<SE, PA, F>(&self, node: &Node<SE, PA>, join: F) -> F::Output
+    where
+        SE: StorageEngine + Send + Sync + 'static,
+        PA: PolicyAgent<ContextData = CD> + Send + Sync + 'static,
+        F: std::future::Future,
+    {
+        let _relay = self.0.relay.lock().await;
+        let mut table = self.0.table.lock().await;
+        if let Err(e) = self.ensure_loaded(node, &mut table).await {
+            warn!("Outbox: failed to load before joining: {}", e);
+        }
+        join.await
+    }
+
+    /// Queue a locally committed transaction and relay it.
+    ///
+    /// The transaction is relayed before this returns only if nothing older is pending; a
+    /// rejection then fails the commit, which is still applied locally. Otherwise it takes its turn in a background replay, and
+    /// its outcome is reported through [`Outbox::statuses`].
+    pub(crate) async fn commit<SE, PA>(
+        &self,
+        node: &Node<SE, PA>,
+        sessions: &SessionSet<CD>,
+        id: proto::TransactionId,
+        events: &[Attested<Event>],
+    ) -> Result<(), MutationError>
+    where
+        SE: StorageEngine + Send + Sync + 'static,
+        PA: PolicyAgent<ContextData = CD> + Send + Sync + 'static,
+    {
+        if self.enqueue(node, sessions, id.clone(), events).await? {
+            // Nothing older is queued, so a replay under way can only be relaying this one
+            let _relay = self.0.relay.lock().await;
+            if self.status(&id) == Some(TransactionStatus::Pending) {
+                self.relay_next(node).await?;
+            }
+            return match self.status(&id) {
+                Some(TransactionStatus::Rejected(reason)) => Err(MutationError::General(Box::new(std::io::Error::other(reason)))),
+                _ => Ok(()),
+            };
+        }
+        if !node.get_durable_peers().is_empty() {
+            let (outbox, node) = (self.clone(), node.clone());
+            crate::task::spawn(async move {
+                if let Err(e) = outbox.flush(&node).await {
+                    warn!("Node({}) failed to flush outbox: {}", node.id, e);
+                }
+            });
+        }
+        Ok(())
+    }
+
+    /// Persist a transaction as the newest pending entry, returning whether nothing older is pending
+    async fn enqueue<SE, PA>(
+        &self,
+        node: &Node<SE, PA>,
+        sessions: &SessionSet<CD>,
+        id: proto::TransactionId,
+        events: &[Attested<Event>],
+    ) -> Result<bool, MutationError>
+    where
+        SE: StorageEngine + Send + Sync + 'static,
+        PA: PolicyAgent<ContextData = CD> + Send + Sync + 'static,
+    {
+        let mut table = self.0.table.lock().await;
+        self.ensure_loaded(node, &mut table).await?;
+        let collection = Self::collection(node).await?;
+        let seq = table.next_seq;
+        let slot_id = table.free.last().copied().unwrap_or_else(EntityId::random);
+        let mut slot =
+            Slot { id: slot_id, seq, status: SlotStatus::Pending, transaction: Some(id.clone()), reason: None, events: events.to_vec() };
+        collection.set_state(slot.to_state(&CollectionId::fixed_name(OUTBOX_COLLECTION_ID))?).await?;
+
+        table.free.pop_if(|free| *free == slot_id);
+        table.next_seq += 1;
+        slot.events.clear();
+        table.queued.insert(seq, slot);
+        self.0.sessions.lock().unwrap().insert(id, sessions.clone());
+        self.publish(&table);
+        Ok(table.next_pending().map(|slot| slot.seq) == Some(seq))
+    }
+
+    /// Relay pending transactions, oldest first, to the connected durable peers
+    ///
+    /// Stops at the first transaction that cannot be delivered (no durable peer connected, the
+    /// connection dropped, or no credential to send it with); it stays pending for the next
+    /// flush. A transaction is confirmed once the durable peers required by the node's
+    /// [`CommitPolicy`](crate::quorum::CommitPolicy) have accepted it.
+    pub(crate) async fn flush<SE, PA>(&self, node: &Node<SE, PA>) -> Result<(), MutationError>
+    where
+        SE: StorageEngine + Send + Sync + 'static,
+        PA: PolicyAgent<ContextData = CD> + Send + Sync + 'static,
+    {
+        let _relay = self.0.relay.lock().await;
+        while let Step::Settled = self.relay_next(node).await? {}
+        Ok(())
+    }
+
+    /// Relay the oldest pending transaction. Callers hold the relay lock.
+    async fn relay_next<SE, PA>(&self, node: &Node<SE, PA>) -> Result<Step, MutationError>
+    where
+        SE: StorageEngine + Send + Sync + 'static,
+        PA: PolicyAgent<ContextData = CD> + Send + Sync + 'static,
+    {
+        let (seq, slot_id, id) = {
+            let mut table = self.0.table.lock().await;
+            // Nothing queued: leave storage alone (the collection need not exist)
+            if table.loaded && table.next_pending().is_none() {
+                return Ok(Step::Stopped);
+            }
+            self.ensure_loaded(node, &mut table).await?;
+            match table.next_pending() {
+                Some(Slot { seq, id: slot_id, transaction: Some(id), .. }) => (*seq, *slot_id, id.clone()),
+                _ => return Ok(Step::Stopped),
+            }
+        };
+        let peers = node.get_durable_peers();
+        if peers.is_empty() {
+            return Ok(Step::Stopped);
+        }
+
+        // Replay under the committer's current credential; transactions loaded from storage
+        // fall back to the node's single write credential, if it has exactly one
+        let session = self.0.sessions.lock().unwrap().get(&id).cloned();
+        let cdata = match session.as_ref().map_or_else(|| node.sessions.write_credential(), |sessions| sessions.write_credential()) {
+            Ok(cdata) => cdata,
+            Err(e) => {
+                warn!("Outbox: transaction {} stays pending, no credential to relay it with: {}", id, e);
+                return Ok(Step::Stopped);
+            }
+        };
+
+        // Only this slot's events are read; storage may have been reset since it was queued
+        let collection = Self::collection(node).await?;
+        let stored = match collection.get_state(slot_id).await {
+            Ok(state) => Some(Slot::from_state(&state)?),
+            Err(RetrievalError::EntityNotFound(_)) => None,
+            Err(e) => return Err(e.into()),
+        };
+        let Some(stored) = stored.filter(|stored| stored.seq == seq && stored.status == SlotStatus::Pending) else {
+            debug!("Outbox: transaction {} is no longer stored, dropping it", id);
+            let mut table = self.0.table.lock().await;
+            table.queued.remove(&seq);
+            self.0.sessions.lock().unwrap().remove(&id);
+            self.publish(&table);
+            return Ok(Step::Settled);
+        };
+
+        let outcome = match relay_commit(node, peers, node.commit_policy(), &cdata, &id, &stored.events).await {
+            CommitOutcome::Confirmed => TransactionStatus::Confirmed,
+            CommitOutcome::Rejected(reason) => TransactionStatus::Rejected(reason),
+            CommitOutcome::Undelivered(reason) => {
+                debug!("Outbox: transaction {} not delivered: {}", id, reason);
+                return Ok(Step::Stopped);
+            }
+        };
+
+        let mut table = self.0.table.lock().await;
+        let mut slot = Slot { events: Vec::new(), ..stored };
+        match outcome {
+            TransactionStatus::Rejected(reason) => {
+                warn!("Outbox: transaction {} rejected: {}", id, reason);
+                slot.status = SlotStatus::Rejected;
+                slot.reason = Some(reason);
+            }
+            _ => {
+                slot.status = SlotStatus::Free;
+                slot.transaction = None;
+                let mut recent = self.0.recent_confirmed.lock().unwrap();
+                recent.push_back(id.clone());
+                if recent.len() > RECENT_CONFIRMED {
+                    recent.pop_front();
+                }
+            }
+        }
+        collection.set_state(slot.to_state(&CollectionId::fixed_name(OUTBOX_COLLECTION_ID))?).await?;
+        if slot.status == SlotStatus::Free {
+            table.queued.remove(&seq);
+            table.free.push(slot_id);
+        } else {
+            table.queued.insert(seq, slot);
+        }
+        self.0.sessions.lock().unwrap().remove(&id);
+        self.publish(&table);
+        Ok(Step::Settled)
+    }
+
+    /// Forget a rejected transaction. Returns false if it is not rejected.
+    pub async fn dismiss<SE, PA>(&self, node: &Node<SE, PA>, id: &proto::TransactionId) -> Result<bool, MutationError>
+    where
+        SE: StorageEngine + Send + Sync + 'static,
+        PA: PolicyAgent<ContextData = CD> + Send + Sync + 'static,
+    {
+        let mut table = self.0.table.lock().await;
+        self.ensure_loaded(node, &mut table).await?;
+        let Some(seq) = table
+            .queued
+            .values()
+            .find(|slot| slot.status == SlotStatus::Rejected && slot.transaction.as_ref() == Some(id))
+            .map(|slot| slot.seq)
+        else {
+            return Ok(false);
+        };
+        let slot = Slot { id: table.queued[&seq].id, seq, status: SlotStatus::Free, transaction: None, reason: None, events: Vec::new() };
+        Self::collection(node).await?.set_state(slot.to_state(&CollectionId::fixed_name(OUTBOX_COLLECTION_ID))?).await?;
+        table.queued.remove(&seq);
+        table.free.push(slot.id);
+        self.publish(&table);
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slot_round_trip() {
+        let collection = CollectionId::fixed_name(OUTBOX_COLLECTION_ID);
+        let slot = Slot {
+            id: EntityId::random(),
+            seq: 7,
+            status: SlotStatus::Rejected,
+            transaction: Some(proto::TransactionId::new()),
+            reason: Some("denied".into()),
+            events: Vec::new(),
+        };
+        let restored = Slot::from_state(&slot.to_state(&collection).unwrap()).unwrap();
+        assert_eq!(restored.id, slot.id);
+        assert_eq!(restored.seq, 7);
+        assert_eq!(restored.status, SlotStatus::Rejected);
+        assert_eq!(restored.transaction, slot.transaction);
+        assert_eq!(restored.reason.as_deref(), Some("denied"));
+
+        let free = Slot { id: slot.id, seq: 8, status: SlotStatus::Free, transaction: None, reason: None, events: Vec::new() };
+        let restored = Slot::from_state(&free.to_state(&collection).unwrap()).unwrap();
+        assert_eq!((restored.status, restored.transaction, restored.reason), (SlotStatus::Free, None, None));
+    }
+}
@@ -430,6 +430,7 @@ impl<CD: ContextData, Q: RemoteQuerySubscriber> SubscriptionRelay<CD, Q> {
                 RequestError::ConnectionLost => true,
                 RequestError::SendError(_) => true,
                 RequestError::InternalChannelClosed => true,
+                RequestError::PeerDraining => true,
                 RequestError::ServerError(_) => false,
                 RequestError::UnexpectedResponse(_) => false,
                 RequestError::AccessDenied(_) => false,
@@ -524,6 +525,7 @@ where
         {
             ankurah_proto::NodeResponseBody::QuerySubscribed { query_id: _response_query_id, deltas } => deltas,
             ankurah_proto::NodeResponseBody::Error(e) => return Err(RetrievalError::RequestError(RequestError::ServerError(e))),
+            ankurah_proto::NodeResponseBody::Draining => return Err(RetrievalError::RequestError(RequestError::PeerDraining)),
             other => return Err(RetrievalError::RequestError(RequestError::UnexpectedResponse(other))),
         };
 
@@ -4,6 +4,7 @@ use tracing::warn;
 use crate::{
     entity::Entity,
     error::SubscriptionError,
+    metrics,
     node::Node,
     policy::PolicyAgent,
     reactor::{
@@ -24,7 +25,7 @@ use std::sync::Mutex;
 /// query's subscriber session, the typed owner of credential state the
 /// reactor reads but does not manage.
 pub struct SubscriptionHandler<CD: ContextData> {
-    _peer_id: proto::EntityId,
+    peer_id: proto::EntityId,
     subscription: ReactorSubscription,
     _guard: SubscriptionGuard,
     /// Each standing query's credential source, shared with its gap
@@ -57,7 +58,7 @@ impl<CD: ContextData> SubscriptionHandler<CD> {
             }
         });
 
-        Self { _peer_id: peer_id, subscription, _guard: guard, queries: Mutex::new(HashMap::new()) }
+        Self { peer_id, subscription, _guard: guard, queries: Mutex::new(HashMap::new()) }
     }
 
     /// Get the subscription ID for this peer.
@@ -75,7 +76,9 @@ impl<CD: ContextData> SubscriptionHandler<CD> {
     /// peer disconnects.
     pub fn remove_predicate(&self, query_id: proto::QueryId) -> Result<(), SubscriptionError> {
         let removed = self.subscription.remove_predicate(query_id);
-        self.queries.lock().unwrap_or_else(|e| e.into_inner()).remove(&query_id);
+        let mut queries = self.queries.lock().unwrap_or_else(|e| e.into_inner());
+        queries.remove(&query_id);
+        metrics::peer_subscriptions(self.peer_id, queries.len());
         removed
     }
 
@@ -103,7 +106,11 @@ impl<CD: ContextData> SubscriptionHandler<CD> {
                 }
                 (sessions, false)
             }
-            Entry::Vacant(v) => (v.insert(cdata.clone().into()).clone(), true),
+            Entry::Vacant(v) => {
+                let sessions = v.insert(cdata.clone().into()).clone();
+                metrics::peer_subscriptions(self.peer_id, queries.len());
+                (sessions, true)
+            }
         }
     }
 
@@ -159,6 +166,7 @@ impl<CD: ContextData> SubscriptionHandler<CD> {
             // generation-guarded fix rides the claw-back work in #426.)
             if queries.get(&query_id).is_some_and(|entry| entry.ptr_eq(&sessions)) {
                 queries.remove(&query_id);
+                metrics::peer_subscriptions(self.peer_id, queries.len());
                 drop(queries);
                 // The reactor may already hold the query from an upsert
                 // that succeeded before the failure; tear it down too,
@@ -250,6 +258,10 @@ impl<CD: ContextData> SubscriptionHandler<CD> {
     }
 }
 
+impl<CD: ContextData> Drop for SubscriptionHandler<CD> {
+    fn drop(&mut self) { metrics::peer_subscriptions(self.peer_id, 0) }
+}
+
 /// Convert a single ReactorUpdateItem to a SubscriptionUpdateItem.
 fn convert_item<SE, PA>(
     node: &Node<SE, PA>,
@@ -1,12 +1,9 @@
 //! Backend conformance kit: executable laws every `PropertyBackend` must satisfy.
 //!
-//! TEST CODE ONLY. This entire module is `#![cfg(test)]`: it compiles into
-//! core's own test builds and contributes nothing to the shipped library. It
-//! lives inside core (rather than the integration-test crate) because the
-//! laws exercise `apply_layer`, whose `EventLayer` parameter is crate
-//! private; when the #267 remainder designs the public layer-view API (D8),
-//! the kit can graduate to a standalone test-support crate that external
-//! backend authors import directly.
+//! The kit is test support, not runtime code: core's own test builds compile
+//! it, and everyone else gets it through the `conformance` feature, so a
+//! backend crate can enable it under `[dev-dependencies]` and run the same laws
+//! from its own tests.
 //!
 //! The property backend boundary is a contract that any
 //! implementation, including ones written outside this crate, must honor. This
@@ -76,12 +73,14 @@
 //! # Adopting the kit
 //!
 //! A backend adopts every law by implementing [`ConformanceBackend`] (a
-//! test-only description of how to produce events and fresh instances for that
-//! backend) and calling the law functions from a `#[test]`. The reference
-//! implementations at the bottom of this file (LWW and Yrs) are the worked
-//! examples; a third backend mirrors them.
-
-#![cfg(test)]
+//! description of how to produce events and fresh instances for that
+//! backend) and calling the law functions from a `#[test]`. The kit constructs
+//! every instance through the backend registry, the way storage and event
+//! application do, so a backend must be registered (built-ins always are; a
+//! downstream one calls `register_backend`) before its laws run. The reference
+//! implementations at the bottom of this file (LWW, Yrs and the counter) are
+//! the worked examples; the `max` backend in the integration tests
+//! (`tests/tests/custom_backend.rs`) adopts the kit from outside core.
 
 use std::collections::BTreeMap;
 use std::sync::Arc;
@@ -89,7 +88,7 @@ use std::sync::Arc;
 use ankurah_proto::{BackendOperation as Operation, Clock, EntityId, Event, EventId, OperationSet};
 
 use crate::event_dag::EventLayer;
-use crate::property::backend::PropertyBackend;
+use crate::property::backend::{backend_from_string, PropertyBackend};
 
 /// The property backend under test, described for the conformance kit.
 ///
@@ -102,18 +101,22 @@ use crate::property::backend::PropertyBackend;
 /// register backend it is a `(field, value)` pair; for a sequence backend it is
 /// a text insertion. The kit never inspects a `Write`; it only round-trips it
 /// through [`ConformanceBackend::stage_write`].
-pub(crate) trait ConformanceBackend {
+pub trait ConformanceBackend {
     /// A single abstract edit this backend understands.
     type Write: Clone;
 
     /// The backend name, matching `PropertyBackend::property_backend_name`.
     fn backend_name() -> &'static str;
 
-    /// A fresh, empty backend instance.
-    fn new_backend() -> Arc<dyn PropertyBackend>;
+    /// A fresh, empty backend instance, constructed by name through the registry.
+    fn new_backend() -> Arc<dyn PropertyBackend> {
+        backend_from_string(Self::backend_name(), None).expect("the backend under test must be registered")
+    }
 
-    /// Reconstruct a backend from a state buffer.
-    fn from_state_buffer(buffer: &[u8]) -> Arc<dyn PropertyBackend>;
+    /// Reconstruct a backend from a state buffer, through the registry.
+    fn from_state_buffer(buffer: &[u8]) -> Arc<dyn PropertyBackend> {
+        backend_from_string(Self::backend_name(), Some(&buffer.to_vec())).expect("from_state_buffer")
+    }
 
     /// Apply the abstract write to a fresh backend and return the resulting
     /// operations, so the kit can package them into an event. The backend passed
@@ -205,7 +208,7 @@ fn layer_from_events(already_applied: &[&Event], to_apply: &[&Event], context: &
 /// read-visible properties match. This certifies the state buffer carries
 /// everything the backend needs to reconstruct itself, including the provenance
 /// (LWW event ids) required for future conflict resolution.
-pub(crate) fn law_state_buffer_round_trip<B: ConformanceBackend>() {
+pub fn law_state_buffer_round_trip<B: ConformanceBackend>() {
     let backend = B::new_backend();
 
     // Apply a linear sequence of writes, each committed under a distinct event id
@@ -238,7 +241,7 @@ pub(crate) fn law_state_buffer_round_trip<B: ConformanceBackend>() {
 /// byte-identical state buffers. This is the wire path (`to_operations` on the
 /// writer, `apply_operations` on the reader) between two nodes that never share
 /// in-memory state.
-pub(crate) fn law_operation_round_trip_across_nodes<B: ConformanceBackend>() {
+pub fn law_operation_round_trip_across_nodes<B: ConformanceBackend>() {
     let node_a = B::new_backend();
     let node_b = B::new_backend();
 
@@ -282,7 +285,7 @@ pub(crate) fn law_operation_round_trip_across_nodes<B: ConformanceBackend>() {
 /// contract requires LWW to track the writing event (so `apply_layer` can resolve
 /// concurrent writes by causal dominance) but explicitly permits a CRDT to ignore
 /// it.
-pub(crate) fn law_provenance<B: ConformanceBackend>() {
+pub fn law_provenance<B: ConformanceBackend>() {
     let write = B::linear_writes().into_iter().next().expect("linear_writes must be non-empty");
     let scratch = B::new_backend();
     let ops = B::stage_write(&scratch, &write);
@@ -317,7 +320,7 @@ pub(crate) fn law_provenance<B: ConformanceBackend>() {
 /// A backend that fails this law (a Yjs-family sequence CRDT can be
 /// integration-order sensitive; see the module docs and verdict 267-B) is a
 /// finding to pin, not a law to relax for other backends.
-pub(crate) fn law_within_layer_permutation_invariance<B: ConformanceBackend>() {
+pub fn law_within_layer_permutation_invariance<B: ConformanceBackend>() {
     let writes = B::concurrent_writes();
     assert!(writes.len() >= 2, "[{}] concurrent_writes must supply at least two events", B::backend_name());
 
@@ -379,7 +382,7 @@ pub(crate) fn law_within_layer_permutation_invariance<B: ConformanceBackend>() {
 /// Both schedules deliver the same events with the same parent structure, so a
 /// backend whose resolution depends only on graph facts (never arrival order)
 /// must reach the same final state buffer.
-pub(crate) fn law_cross_order_determinism<B: ConformanceBackend>() {
+pub fn law_cross_order_determinism<B: ConformanceBackend>() {
     let writes = B::concurrent_writes();
     assert!(writes.len() >= 3, "[{}] cross-order law needs at least three writes", B::backend_name());
 
@@ -455,6 +458,7 @@ fn rotations<T: Clone>(items: &[T]) -> Vec<Vec<T>> {
 // Reference implementation: LWW
 // ============================================================================
 
+#[cfg(test)]
 mod lww_conformance {
     use super::*;
     use crate::event_dag::CausalRelation;
@@ -469,12 +473,6 @@ mod lww_conformance {
 
         fn backend_name() -> &'static str { LWWBackend::property_backend_name() }
 
-        fn new_backend() -> Arc<dyn PropertyBackend> { Arc::new(LWWBackend::new()) }
-
-        fn from_state_buffer(buffer: &[u8]) -> Arc<dyn PropertyBackend> {
-            Arc::new(LWWBackend::from_state_buffer(&buffer.to_vec()).expect("LWW from_state_buffer"))
-        }
-
         fn stage_write(_backend: &Arc<dyn PropertyBackend>, write: &Self::Write) -> Vec<Operation> {
             // Stage on a fresh backend so to_operations returns exactly this write.
             let scratch = LWWBackend::new();
@@ -578,6 +576,7 @@ mod lww_conformance {
 // Reference implementation: Yrs
 // ============================================================================
 
+#[cfg(test)]
 mod yrs_conformance {
     use super::*;
     use crate::property::backend::yrs::YrsBackend;
@@ -590,12 +589,6 @@ mod yrs_conformance {
 
         fn backend_name() -> &'static str { YrsBackend::property_backend_name() }
 
-        fn new_backend() -> Arc<dyn PropertyBackend> { Arc::new(YrsBackend::new()) }
-
-        fn from_state_buffer(buffer: &[u8]) -> Arc<dyn PropertyBackend> {
-            Arc::new(YrsBackend::from_state_buffer(&buffer.to_vec()).expect("Yrs from_state_buffer"))
-        }
-
         fn stage_write(_backend: &Arc<dyn PropertyBackend>, write: &Self::Write) -> Vec<Operation> {
             // A fresh doc's diff against its empty starting state is a self-contained
             // update inserting the text; this is the wire form of one edit.
@@ -716,3 +709,75 @@ mod yrs_conformance {
         assert!(lr.contains("xxx") && lr.contains("yyy"), "each run must appear contiguously");
     }
 }
+
+// ============================================================================
+// Reference implementation: counter
+// ============================================================================
+
+#[cfg(test)]
+mod counter_conformance {
+    use super::*;
+    use crate::property::backend::counter::CounterBackend;
+    use crate::value::Value;
+
+    /// Counter adopter. A `Write` adds an amount to a field.
+    struct CounterAdopter;
+
+    impl ConformanceBackend for CounterAdopter {
+        type Write = (&'static str, i64);
+
+        fn backend_name() -> &'static str { CounterBackend::property_backend_name() }
+
+        fn stage_write(_backend: &Arc<dyn PropertyBackend>, write: &Self::Write) -> Vec<Operation> {
+            let scratch = CounterBackend::new();
+            scratch.increment(write.0, write.1);
+            scratch.to_operations().expect("to_operations").expect("an increment must produce operations")
+        }
+
+        fn concurrent_writes() -> Vec<Self::Write> { vec![("likes", 1), ("likes", 2), ("likes", -4)] }
+
+        fn linear_writes() -> Vec<Self::Write> { vec![("likes", 1), ("views", 10), ("likes", 1), ("likes", -1)] }
+
+        fn check_provenance(backend: &Arc<dyn PropertyBackend>, event_id: &EventId) {
+            // The counter de-duplicates on the event id, so it must be the key the increment is kept under
+            let counter = backend.clone().as_arc_dyn_any().downcast::<CounterBackend>().expect("backend is a counter");
+            let increments = counter.increments(&"likes".to_string());
+            assert_eq!(increments.keys().collect::<Vec<_>>(), vec![event_id], "the counter must key each increment by its event id");
+        }
+    }
+
+    #[test]
+    fn state_buffer_round_trip() { law_state_buffer_round_trip::<CounterAdopter>(); }
+
+    #[test]
+    fn operation_round_trip_across_nodes() { law_operation_round_trip_across_nodes::<CounterAdopter>(); }
+
+    #[test]
+    fn provenance() { law_provenance::<CounterAdopter>(); }
+
+    #[test]
+    fn within_layer_permutation_invariance() { law_within_layer_permutation_invariance::<CounterAdopter>(); }
+
+    #[test]
+    fn cross_order_determinism() { law_cross_order_determinism::<CounterAdopter>(); }
+
+    /// Intent law for the counter: no concurrent increment is lost, and
+    /// re-delivering events, alone or inside a later layer, does not count
+    /// them again. This is what LWW cannot promise for a like count.
+    #[test]
+    fn concurrent_increments_sum_exactly_once() {
+        let scratch = CounterAdopter::new_backend();
+        let root = make_event(5000, CounterAdopter::backend_name(), CounterAdopter::stage_write(&scratch, &("likes", 10)), &[]);
+        let left = make_event(5001, CounterAdopter::backend_name(), CounterAdopter::stage_write(&scratch, &("likes", 1)), &[root.id()]);
+        let right = make_event(5002, CounterAdopter::backend_name(), CounterAdopter::stage_write(&scratch, &("likes", 1)), &[root.id()]);
+
+        let backend = CounterAdopter::new_backend();
+        apply_committed_root::<CounterAdopter>(&backend, &root);
+        let ops: Vec<_> = left.operations().backend_operations(CounterAdopter::backend_name()).cloned().collect();
+        backend.apply_operations_with_event(&ops, left.id()).unwrap();
+        // The right branch arrives concurrently, in a layer that redelivers the left one too
+        backend.apply_layer(&layer_from_events(&[], &[&left, &right], &[&root])).unwrap();
+
+        assert_eq!(backend.property_value(&"likes".to_string()), Some(Value::I64(12)));
+    }
+}
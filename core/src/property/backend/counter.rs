@@ -0,0 +1,293 @@
+use std::{
+    any::Any,
+    collections::BTreeMap,
+    fmt::Debug,
+    sync::{Arc, Mutex, RwLock},
+};
+
+use ankurah_proto::{BackendOperation as Operation, EventId};
+use ankurah_signals::signal::Listener;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{MutationError, StateError},
+    event_dag::EventLayer,
+    property::{backend::PropertyBackend, PropertyName, Value},
+};
+
+const COUNTER_DIFF_VERSION: u8 = 1;
+
+/// Version header for serialized counter state buffers, as with LWW's.
+const COUNTER_STATE_VERSION_1: u8 = 0xC1;
+
+/// Integer properties that count: concurrent increments all survive.
+///
+/// A counter keeps each committed increment under the id of the event that
+/// made it, and its value is their sum. Recording an event's increments
+/// replaces whatever was recorded for that event, so applying the same event
+/// twice, or through both `apply_operations_with_event` and a later layer,
+/// counts it once; and since a sum does not depend on the order of its terms,
+/// every replica holding the same events agrees on the value and on the state
+/// buffer. The price is one entry per incrementing event, kept for as long as
+/// the entity lives.
+#[derive(Debug, Default)]
+pub struct CounterBackend {
+    /// Each property's committed increments, keyed by the event that made them
+    committed: RwLock<BTreeMap<PropertyName, BTreeMap<EventId, i64>>>,
+    /// Local increments that `to_operations` has not yet extracted
+    uncommitted: Mutex<BTreeMap<PropertyName, i64>>,
+    /// Increments extracted by `to_operations`, or applied without an event, that no event has recorded yet
+    pending: Mutex<BTreeMap<PropertyName, i64>>,
+    field_broadcasts: Mutex<BTreeMap<PropertyName, ankurah_signals::broadcast::Broadcast>>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CounterDiff {
+    version: u8,
+    data: Vec<u8>,
+}
+
+impl CounterBackend {
+    pub fn new() -> CounterBackend { Self::default() }
+
+    /// Add `amount`, which may be negative, to a property
+    pub fn increment(&self, property_name: impl AsRef<str>, amount: i64) {
+        let mut uncommitted = self.uncommitted.lock().unwrap();
+        let total = uncommitted.entry(property_name.as_ref().to_owned()).or_default();
+        *total = total.wrapping_add(amount);
+    }
+
+    /// The current count, or `None` if the property was never incremented
+    pub fn get(&self, property_name: &PropertyName) -> Option<i64> {
+        let committed = self.committed.read().unwrap();
+        let committed = committed.get(property_name).map(|increments| sum(increments.values()));
+        let pending = self.pending.lock().unwrap().get(property_name).copied();
+        let uncommitted = self.uncommitted.lock().unwrap().get(property_name).copied();
+        if committed.is_none() && pending.is_none() && uncommitted.is_none() {
+            return None;
+        }
+        Some(sum([committed, pending, uncommitted].iter().flatten()))
+    }
+
+    /// The committed increments to a property, by the event that made each of them
+    pub fn increments(&self, property_name: &PropertyName) -> BTreeMap<EventId, i64> {
+        self.committed.read().unwrap().get(property_name).cloned().unwrap_or_default()
+    }
+
+    /// Get the broadcast ID for a specific field, creating the broadcast if necessary
+    pub fn field_broadcast_id(&self, field_name: &PropertyName) -> ankurah_signals::broadcast::BroadcastId {
+        let mut field_broadcasts = self.field_broadcasts.lock().expect("other thread panicked, panic here too");
+        let broadcast = field_broadcasts.entry(field_name.clone()).or_default();
+        broadcast.id()
+    }
+
+    /// Record `event_id`'s increments, replacing any recorded for it before
+    fn record(&self, event_id: &EventId, increments: BTreeMap<PropertyName, i64>) -> Vec<PropertyName> {
+        let mut committed = self.committed.write().unwrap();
+        let mut pending = self.pending.lock().unwrap();
+        let mut changed = Vec::new();
+        for (property_name, amount) in increments {
+            // Our own extracted increments come back under their event; they are no longer pending
+            if let Some(staged) = pending.get_mut(&property_name) {
+                *staged = staged.wrapping_sub(amount);
+                if *staged == 0 {
+                    pending.remove(&property_name);
+                }
+            }
+            if committed.entry(property_name.clone()).or_default().insert(event_id.clone(), amount) != Some(amount) {
+                changed.push(property_name);
+            }
+        }
+        changed
+    }
+}
+
+/// Wrapping, so that the total is the same in whatever order the increments are added
+fn sum<'a>(amounts: impl IntoIterator<Item = &'a i64>) -> i64 {
+    amounts.into_iter().fold(0i64, |total, amount| total.wrapping_add(*amount))
+}
+
+/// The increments per property carried by some operations, totalled
+fn decode(operations: &[Operation]) -> Result<BTreeMap<PropertyName, i64>, MutationError> {
+    let mut increments: BTreeMap<PropertyName, i64> = BTreeMap::new();
+    for operation in operations {
+        let CounterDiff { version, data } = bincode::deserialize(&operation.diff)?;
+        match version {
+            1 => {
+                let changes: BTreeMap<PropertyName, i64> = bincode::deserialize(&data)?;
+                for (property_name, amount) in changes {
+                    let total = increments.entry(property_name).or_default();
+                    *total = total.wrapping_add(amount);
+                }
+            }
+            version => return Err(MutationError::UpdateFailed(anyhow::anyhow!("Unknown counter operation version: {:?}", version).into())),
+        }
+    }
+    Ok(increments)
+}
+
+impl PropertyBackend for CounterBackend {
+    fn as_arc_dyn_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync + 'static> { self as Arc<dyn Any + Send + Sync + 'static> }
+
+    fn as_debug(&self) -> &dyn Debug { self as &dyn Debug }
+
+    fn fork(&self) -> Arc<dyn PropertyBackend> {
+        Arc::new(Self {
+            committed: RwLock::new(self.committed.read().unwrap().clone()),
+            uncommitted: Mutex::new(self.uncommitted.lock().unwrap().clone()),
+            pending: Mutex::new(self.pending.lock().unwrap().clone()),
+            // Create fresh broadcasts (don't clone the existing ones for transaction isolation)
+            field_broadcasts: Mutex::new(BTreeMap::new()),
+        })
+    }
+
+    fn properties(&self) -> Vec<PropertyName> {
+        let mut properties: Vec<PropertyName> = self.committed.read().unwrap().keys().cloned().collect();
+        properties.extend(self.pending.lock().unwrap().keys().cloned());
+        properties.extend(self.uncommitted.lock().unwrap().keys().cloned());
+        properties.sort();
+        properties.dedup();
+        properties
+    }
+
+    fn property_value(&self, property_name: &PropertyName) -> Option<Value> { self.get(property_name).map(Value::I64) }
+
+    fn property_values(&self) -> BTreeMap<PropertyName, Option<Value>> {
+        self.properties().into_iter().map(|name| (name.clone(), self.property_value(&name))).collect()
+    }
+
+    fn property_backend_name() -> &'static str { "counter" }
+
+    fn to_state_buffer(&self) -> Result<Vec<u8>, StateError> {
+        // Only increments recorded under an event can be persisted; anything else has no provenance to de-duplicate on
+        let pending = self.pending.lock().unwrap();
+        let uncommitted = self.uncommitted.lock().unwrap();
+        if let Some(name) = pending.keys().chain(uncommitted.keys()).next() {
+            return Err(StateError::SerializationError(Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("counter state requires event_id for increments to property {}", name),
+            ))));
+        }
+        let mut state_buffer = vec![COUNTER_STATE_VERSION_1];
+        bincode::serialize_into(&mut state_buffer, &*self.committed.read().unwrap())?;
+        Ok(state_buffer)
+    }
+
+    fn from_state_buffer(state_buffer: &Vec<u8>) -> std::result::Result<Self, crate::error::RetrievalError>
+    where Self: Sized {
+        match state_buffer.split_first() {
+            Some((&COUNTER_STATE_VERSION_1, payload)) => {
+                Ok(Self { committed: RwLock::new(bincode::deserialize(payload)?), ..Default::default() })
+            }
+            Some((version, _)) => Err(crate::error::RetrievalError::Other(format!(
+                "unknown counter state buffer version {version:#04x} (this binary supports {COUNTER_STATE_VERSION_1:#04x})"
+            ))),
+            None => Err(crate::error::RetrievalError::Other("empty counter state buffer".to_string())),
+        }
+    }
+
+    fn to_operations(&self) -> Result<Option<Vec<Operation>>, MutationError> {
+        let mut uncommitted = self.uncommitted.lock().unwrap();
+        let changes: BTreeMap<PropertyName, i64> =
+            std::mem::take(&mut *uncommitted).into_iter().filter(|(_, amount)| *amount != 0).collect();
+        if changes.is_empty() {
+            return Ok(None);
+        }
+
+        let mut pending = self.pending.lock().unwrap();
+        for (property_name, amount) in &changes {
+            let total = pending.entry(property_name.clone()).or_default();
+            *total = total.wrapping_add(*amount);
+        }
+
+        Ok(Some(vec![Operation {
+            diff: bincode::serialize(&CounterDiff { version: COUNTER_DIFF_VERSION, data: bincode::serialize(&changes)? })?,
+        }]))
+    }
+
+    fn apply_operations(&self, operations: &[Operation]) -> Result<(), MutationError> {
+        // No event to key on, so the increments stay pending until one records them
+        let increments = decode(operations)?;
+        let mut pending = self.pending.lock().unwrap();
+        for (property_name, amount) in &increments {
+            let total = pending.entry(property_name.clone()).or_default();
+            *total = total.wrapping_add(*amount);
+        }
+        drop(pending);
+        super::notify_changed_fields(&self.field_broadcasts, increments.keys());
+        Ok(())
+    }
+
+    fn apply_operations_with_event(&self, operations: &[Operation], event_id: EventId) -> Result<(), MutationError> {
+        let changed = self.record(&event_id, decode(operations)?);
+        super::notify_changed_fields(&self.field_broadcasts, changed.iter());
+        Ok(())
+    }
+
+    fn apply_layer(&self, layer: &EventLayer) -> Result<(), MutationError> {
+        // Increments commute, so neither the order of the layer nor its already-applied events matter
+        let mut changed = Vec::new();
+        for event in &layer.to_apply {
+            let operations: Vec<Operation> = event.operations().backend_operations(Self::property_backend_name()).cloned().collect();
+            if !operations.is_empty() {
+                changed.extend(self.record(&event.id(), decode(&operations)?));
+            }
+        }
+        changed.sort();
+        changed.dedup();
+        super::notify_changed_fields(&self.field_broadcasts, changed.iter());
+        Ok(())
+    }
+
+    fn listen_field(&self, field_name: &PropertyName, listener: Listener) -> ankurah_signals::signal::ListenerGuard {
+        let mut field_broadcasts = self.field_broadcasts.lock().expect("other thread panicked, panic here too");
+        let broadcast = field_broadcasts.entry(field_name.clone()).or_default();
+        broadcast.reference().listen(listener).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn increment_ops(property_name: &str, amount: i64) -> Vec<Operation> {
+        let scratch = CounterBackend::new();
+        scratch.increment(property_name, amount);
+        scratch.to_operations().unwrap().unwrap()
+    }
+
+    #[test]
+    fn concurrent_increments_all_count_and_redelivery_counts_once() {
+        let likes = "likes".to_string();
+        let (a, b) = (EventId::from_bytes([1; 32]), EventId::from_bytes([2; 32]));
+
+        let left = CounterBackend::new();
+        left.apply_operations_with_event(&increment_ops("likes", 3), a.clone()).unwrap();
+        left.apply_operations_with_event(&increment_ops("likes", -1), b.clone()).unwrap();
+        left.apply_operations_with_event(&increment_ops("likes", 3), a.clone()).unwrap();
+
+        let right = CounterBackend::new();
+        right.apply_operations_with_event(&increment_ops("likes", -1), b).unwrap();
+        right.apply_operations_with_event(&increment_ops("likes", 3), a).unwrap();
+
+        assert_eq!(left.get(&likes), Some(2));
+        assert_eq!(left.to_state_buffer().unwrap(), right.to_state_buffer().unwrap());
+    }
+
+    #[test]
+    fn extracted_increments_stay_visible_until_recorded() {
+        let stock = "stock".to_string();
+        let backend = CounterBackend::new();
+        backend.increment("stock", 5);
+        assert_eq!(backend.get(&stock), Some(5));
+
+        let ops = backend.to_operations().unwrap().unwrap();
+        assert_eq!(backend.get(&stock), Some(5));
+        assert!(backend.to_state_buffer().is_err(), "an increment without an event cannot be persisted");
+
+        backend.apply_operations_with_event(&ops, EventId::from_bytes([3; 32])).unwrap();
+        assert_eq!(backend.get(&stock), Some(5));
+        let restored = CounterBackend::from_state_buffer(&backend.to_state_buffer().unwrap()).unwrap();
+        assert_eq!(restored.get(&stock), Some(5));
+    }
+}
@@ -4,21 +4,23 @@ use std::any::Any;
 use std::fmt::Debug;
 use std::{collections::BTreeMap, sync::Arc};
 
-// The dormant pn_counter backend (issues #37/#38, a commutative i64
-// counter) was deleted rather than revived: it predated the July trait
-// factorization by 19 compile errors, and a counter cannot satisfy the
-// conformance kit's cross-order determinism law without being rebuilt as a
-// provenance-tracking, idempotent op-counter (apply_layer summing deltas is
-// non-idempotent and the counter kept no per-event id to de-duplicate on).
-// See issue #267's conformance kit discussion for the full rationale
-// and what a future counter backend must provide.
-#[cfg(test)]
-mod conformance;
+// The dormant pn_counter backend (issues #37/#38) was deleted rather than
+// revived: it summed deltas in apply_layer with no per-event id to
+// de-duplicate on, so it could not satisfy the conformance kit's
+// cross-order determinism law. `counter` is its rebuild as an idempotent,
+// provenance-tracking op-counter keyed by event id (issue #267's conformance
+// kit discussion has the full rationale).
+#[cfg(any(test, feature = "conformance"))]
+pub mod conformance;
+pub mod counter;
 pub mod lww;
+pub mod registry;
 pub mod yrs;
-use crate::error::{MutationError, RetrievalError, StateError};
-use crate::event_dag::EventLayer;
+use crate::error::{MutationError, StateError};
+pub use crate::event_dag::layers::{CausalRelation, EventLayer};
+pub use counter::CounterBackend;
 pub use lww::LWWBackend;
+pub use registry::{backend_from_string, register_backend, BackendNameTaken};
 pub use yrs::YrsBackend;
 
 use super::{PropertyName, Value};
@@ -37,7 +39,7 @@ pub trait PropertyBackend: Any + Send + Sync + Debug + 'static {
     }
     fn property_values(&self) -> BTreeMap<PropertyName, Option<Value>>;
 
-    /// Unique property backend identifier.
+    /// Unique property backend identifier, and the name [`register_backend`] files it under.
     fn property_backend_name() -> &'static str
     where Self: Sized;
 
@@ -101,11 +103,6 @@ pub trait PropertyBackend: Any + Send + Sync + Debug + 'static {
     ) -> ankurah_signals::signal::ListenerGuard;
 }
 
-// This is where this gets a bit tough.
-// PropertyBackends should either have a concrete type of some sort,
-// or if they can take a generic, they should also take a `Vec<u8>`.
-
-// TODO: Implement a property backend type registry rather than this hardcoded nonsense.
 /// Fire the change broadcast for each named field that has subscribers.
 pub(crate) fn notify_changed_fields<'a>(
     field_broadcasts: &std::sync::Mutex<std::collections::BTreeMap<crate::property::PropertyName, ankurah_signals::broadcast::Broadcast>>,
@@ -118,21 +115,3 @@ pub(crate) fn notify_changed_fields<'a>(
         }
     }
 }
-
-pub fn backend_from_string(name: &str, buffer: Option<&Vec<u8>>) -> Result<Arc<dyn PropertyBackend>, RetrievalError> {
-    if name == "yrs" {
-        let backend = match buffer {
-            Some(buffer) => YrsBackend::from_state_buffer(buffer)?,
-            None => YrsBackend::new(),
-        };
-        Ok(Arc::new(backend))
-    } else if name == "lww" {
-        let backend = match buffer {
-            Some(buffer) => LWWBackend::from_state_buffer(buffer)?,
-            None => LWWBackend::new(),
-        };
-        Ok(Arc::new(backend))
-    } else {
-        Err(RetrievalError::Other(format!("unknown backend: {}", name)))
-    }
-}
@@ -0,0 +1,145 @@
+//! The runtime table of property backends, keyed by
+//! [`PropertyBackend::property_backend_name`].
+//!
+//! Every place that meets a backend only by name -- a state buffer read back
+//! from storage, a backend operation carried by an event, the first write to
+//! a fresh entity -- constructs it through [`backend_from_string`], which
+//! looks the name up here. The built-in `yrs`, `lww` and `counter` backends are
+//! always present; a crate shipping its own backend calls [`register_backend`] once
+//! at startup, before any node reads or writes entities that use it.
+
+use std::any::{type_name, TypeId};
+use std::collections::BTreeMap;
+use std::sync::{Arc, LazyLock, RwLock};
+
+use thiserror::Error;
+
+use super::{CounterBackend, LWWBackend, PropertyBackend, YrsBackend};
+use crate::error::RetrievalError;
+
+/// Constructs a backend from its state buffer, or empty when there is none.
+type Constructor = fn(Option<&Vec<u8>>) -> Result<Arc<dyn PropertyBackend>, RetrievalError>;
+
+struct Registration {
+    type_id: TypeId,
+    type_name: &'static str,
+    construct: Constructor,
+}
+
+static BACKENDS: LazyLock<RwLock<BTreeMap<&'static str, Registration>>> = LazyLock::new(|| {
+    let mut backends = BTreeMap::new();
+    backends.insert(YrsBackend::property_backend_name(), Registration::of::<YrsBackend>());
+    backends.insert(LWWBackend::property_backend_name(), Registration::of::<LWWBackend>());
+    backends.insert(CounterBackend::property_backend_name(), Registration::of::<CounterBackend>());
+    RwLock::new(backends)
+});
+
+/// A backend name that another backend type already holds.
+#[derive(Error, Debug)]
+#[error("property backend name {name:?} is already registered to {registered}")]
+pub struct BackendNameTaken {
+    pub name: &'static str,
+    pub registered: &'static str,
+}
+
+impl Registration {
+    fn of<B: PropertyBackend + Default>() -> Self {
+        Self { type_id: TypeId::of::<B>(), type_name: type_name::<B>(), construct: construct::<B> }
+    }
+}
+
+fn construct<B: PropertyBackend + Default>(buffer: Option<&Vec<u8>>) -> Result<Arc<dyn PropertyBackend>, RetrievalError> {
+    let backend = match buffer {
+        Some(buffer) => B::from_state_buffer(buffer)?,
+        None => B::default(),
+    };
+    Ok(Arc::new(backend))
+}
+
+/// Make `B` constructible by its [`PropertyBackend::property_backend_name`].
+///
+/// Registering the same type again is a no-op. A name is part of every state
+/// buffer and event that backend writes, so it can never be handed to a
+/// different type: that is refused with [`BackendNameTaken`].
+pub fn register_backend<B: PropertyBackend + Default>() -> Result<(), BackendNameTaken> {
+    let name = B::property_backend_name();
+    let mut backends = BACKENDS.write().expect("backend registry lock is poisoned");
+    match backends.get(name) {
+        Some(existing) if existing.type_id == TypeId::of::<B>() => Ok(()),
+        Some(existing) => Err(BackendNameTaken { name, registered: existing.type_name }),
+        None => {
+            backends.insert(name, Registration::of::<B>());
+            Ok(())
+        }
+    }
+}
+
+/// Whether a backend is registered under `name`.
+pub fn is_registered(name: &str) -> bool { BACKENDS.read().expect("backend registry lock is poisoned").contains_key(name) }
+
+/// The names of every registered backend, in order.
+pub fn registered_backends() -> Vec<&'static str> { BACKENDS.read().expect("backend registry lock is poisoned").keys().copied().collect() }
+
+/// Construct the backend registered under `name`, from `buffer` when given
+/// and empty otherwise.
+pub fn backend_from_string(name: &str, buffer: Option<&Vec<u8>>) -> Result<Arc<dyn PropertyBackend>, RetrievalError> {
+    let construct = match BACKENDS.read().expect("backend registry lock is poisoned").get(name) {
+        Some(registration) => registration.construct,
+        None => return Err(RetrievalError::Other(format!("unknown backend: {}", name))),
+    };
+    construct(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn built_in_backends_are_always_registered() {
+        assert!(is_registered("yrs"));
+        assert!(is_registered("lww"));
+        assert!(is_registered("counter"));
+        assert!(backend_from_string("lww", None).unwrap().as_arc_dyn_any().downcast::<LWWBackend>().is_ok());
+        assert!(matches!(backend_from_string("nonesuch", None), Err(RetrievalError::Other(_))));
+    }
+
+    #[test]
+    fn registration_is_idempotent_and_names_are_exclusive() {
+        register_backend::<LWWBackend>().unwrap();
+        register_backend::<LWWBackend>().unwrap();
+        assert_eq!(registered_backends().iter().filter(|name| **name == "lww").count(), 1);
+
+        let taken = register_backend::<RenamedYrs>().unwrap_err();
+        assert_eq!(taken.name, "lww");
+        assert!(taken.registered.ends_with("LWWBackend"));
+    }
+
+    /// Yrs under a name LWW already holds.
+    #[derive(Debug, Default)]
+    struct RenamedYrs(YrsBackend);
+
+    impl PropertyBackend for RenamedYrs {
+        fn as_arc_dyn_any(self: Arc<Self>) -> Arc<dyn std::any::Any + Send + Sync + 'static> { self }
+        fn as_debug(&self) -> &dyn std::fmt::Debug { self }
+        fn fork(&self) -> Arc<dyn PropertyBackend> { self.0.fork() }
+        fn properties(&self) -> Vec<crate::property::PropertyName> { self.0.properties() }
+        fn property_values(&self) -> BTreeMap<crate::property::PropertyName, Option<crate::value::Value>> { self.0.property_values() }
+        fn property_backend_name() -> &'static str { "lww" }
+        fn to_state_buffer(&self) -> Result<Vec<u8>, crate::error::StateError> { self.0.to_state_buffer() }
+        fn from_state_buffer(state_buffer: &Vec<u8>) -> Result<Self, RetrievalError> {
+            YrsBackend::from_state_buffer(state_buffer).map(Self)
+        }
+        fn to_operations(&self) -> Result<Option<Vec<super::super::Operation>>, crate::error::MutationError> { self.0.to_operations() }
+        fn apply_operations(&self, operations: &[super::super::Operation]) -> Result<(), crate::error::MutationError> {
+            self.0.apply_operations(operations)
+        }
+        fn apply_layer(&self, layer: &crate::event_dag::EventLayer) -> Result<(), crate::error::MutationError> { self.0.apply_layer(layer) }
+        fn listen_field(
+            &self,
+            field_name: &crate::property::PropertyName,
+            listener: ankurah_signals::signal::Listener,
+        ) -> ankurah_signals::signal::ListenerGuard {
+            self.0.listen_field(field_name, listener)
+        }
+    }
+}
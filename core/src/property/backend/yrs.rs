@@ -5,17 +5,37 @@ use std::{
     sync::{Arc, Mutex},
 };
 
-use yrs::{updates::decoder::Decode, GetString, Observable, ReadTxn, StateVector, Text, Transact};
-use yrs::{Update, WriteTxn};
+use yrs::types::{text::YChange, Attrs, Delta, ToJson};
+use yrs::{updates::decoder::Decode, Array, GetString, Map, MapPrelim, MapRef, Out, ReadTxn, StateVector, Text, Transact};
+use yrs::{Any as YrsAny, In, TransactionMut, Update};
 
 use crate::{
     error::{MutationError, StateError},
     property::{
         backend::{Operation, PropertyBackend},
-        PropertyName, Value,
+        PropertyError, PropertyName, Value,
     },
 };
 
+/// Suffix of the root under which a list property's array is stored.
+///
+/// An update does not record what type a root was created as, so a replica
+/// reading a property needs to know from the name alone whether it is text or
+/// a list: `tags` is text, `tags[]` is the list property `tags`.
+const ARRAY_ROOT_SUFFIX: &str = "[]";
+
+/// Suffix of the root under which a map property is stored, as with [`ARRAY_ROOT_SUFFIX`].
+const MAP_ROOT_SUFFIX: &str = "{}";
+
+/// Suffix of the root under which a set property is stored, as with [`ARRAY_ROOT_SUFFIX`].
+const SET_ROOT_SUFFIX: &str = "<>";
+
+fn array_root(property_name: &str) -> String { format!("{}{}", property_name, ARRAY_ROOT_SUFFIX) }
+
+fn map_root(property_name: &str) -> String { format!("{}{}", property_name, MAP_ROOT_SUFFIX) }
+
+fn set_root(property_name: &str) -> String { format!("{}{}", property_name, SET_ROOT_SUFFIX) }
+
 /// Stores one or more properties of an entity
 #[derive(Debug)]
 pub struct YrsBackend {
@@ -55,25 +75,214 @@ impl YrsBackend {
         Ok(())
     }
 
-    fn apply_update(&self, update: &[u8], changed_fields: &Arc<Mutex<std::collections::HashSet<String>>>) -> Result<(), MutationError> {
-        let mut txn = self.doc.transact_mut();
+    /// The runs of a text property with their formatting attributes, or `None` if it was never written.
+    /// Embedded values, which these methods never insert, are left out.
+    pub fn get_formatted(&self, property_name: impl AsRef<str>) -> Option<Vec<(String, Attrs)>> {
+        let txn = self.doc.transact();
+        let text = txn.get_text(property_name.as_ref())?;
+        let runs = text.diff(&txn, YChange::identity).into_iter().filter_map(|diff| match diff.insert {
+            Out::Any(YrsAny::String(chunk)) => Some((chunk.to_string(), diff.attributes.map(|attrs| *attrs).unwrap_or_default())),
+            _ => None,
+        });
+        Some(runs.collect())
+    }
 
-        // TODO: There's gotta be a better way to do this - but I don't see it at the time of this writing
-        let _subs: Vec<yrs::Subscription> = self
-            .field_broadcasts
-            .lock()
-            .unwrap()
-            .keys()
-            .map(|b| {
-                let changed_fields = changed_fields.clone();
-                let b = b.to_string();
-                txn.get_or_insert_text(b.as_str()).observe(move |_, _| {
-                    let mut changed_fields = changed_fields.lock().unwrap();
-                    changed_fields.insert(b.clone());
-                })
-            })
-            .collect();
+    pub fn insert_formatted(
+        &self,
+        property_name: impl AsRef<str>,
+        index: u32,
+        value: &str,
+        attributes: Attrs,
+    ) -> Result<(), MutationError> {
+        let text = self.doc.get_or_insert_text(property_name.as_ref());
+        let mut ytx = self.doc.transact_mut();
+        check_bounds(index, text.len(&ytx))?;
+        text.insert_with_attributes(&mut ytx, index, value, attributes);
+        Ok(())
+    }
 
+    /// Apply `attributes` to a range of a text property; an attribute set to null is removed
+    pub fn format(&self, property_name: impl AsRef<str>, index: u32, length: u32, attributes: Attrs) -> Result<(), MutationError> {
+        let text = self.doc.get_or_insert_text(property_name.as_ref());
+        let mut ytx = self.doc.transact_mut();
+        check_bounds(index.saturating_add(length), text.len(&ytx))?;
+        text.format(&mut ytx, index, length, attributes);
+        Ok(())
+    }
+
+    /// Apply an editor's change, expressed as retain/insert/delete steps from the start of the text
+    pub fn apply_text_delta(&self, property_name: impl AsRef<str>, delta: Vec<Delta<In>>) -> Result<(), MutationError> {
+        let text = self.doc.get_or_insert_text(property_name.as_ref());
+        let mut ytx = self.doc.transact_mut();
+        // Retained and deleted steps walk over the existing text, which must be long enough
+        let consumed = delta.iter().fold(0u32, |consumed, step| match step {
+            Delta::Retain(count, _) | Delta::Deleted(count) => consumed.saturating_add(*count),
+            Delta::Inserted(..) => consumed,
+        });
+        check_bounds(consumed, text.len(&ytx))?;
+        text.apply_delta(&mut ytx, delta);
+        Ok(())
+    }
+
+    /// The elements of a list property, or `None` if it was never written
+    pub fn get_array(&self, property_name: impl AsRef<str>) -> Option<Vec<YrsAny>> {
+        let txn = self.doc.transact();
+        let array = txn.get_array(array_root(property_name.as_ref()))?;
+        Some(array.iter(&txn).map(|out| out.to_json(&txn)).collect())
+    }
+
+    pub fn array_len(&self, property_name: impl AsRef<str>) -> u32 {
+        let txn = self.doc.transact();
+        txn.get_array(array_root(property_name.as_ref())).map_or(0, |array| array.len(&txn))
+    }
+
+    pub fn array_insert(&self, property_name: impl AsRef<str>, index: u32, values: Vec<YrsAny>) -> Result<(), MutationError> {
+        let array = self.doc.get_or_insert_array(array_root(property_name.as_ref()));
+        let mut ytx = self.doc.transact_mut();
+        check_bounds(index, array.len(&ytx))?;
+        array.insert_range(&mut ytx, index, values);
+        Ok(())
+    }
+
+    pub fn array_remove(&self, property_name: impl AsRef<str>, index: u32, length: u32) -> Result<(), MutationError> {
+        let array = self.doc.get_or_insert_array(array_root(property_name.as_ref()));
+        let mut ytx = self.doc.transact_mut();
+        check_bounds(index.saturating_add(length), array.len(&ytx))?;
+        array.remove_range(&mut ytx, index, length);
+        Ok(())
+    }
+
+    /// Move the element at `from` so that it ends up at index `to`.
+    ///
+    /// This is a CRDT move rather than a remove and re-insert: concurrent
+    /// edits to the element are kept, and concurrent moves of the same
+    /// element settle on one position instead of duplicating it.
+    pub fn array_move(&self, property_name: impl AsRef<str>, from: u32, to: u32) -> Result<(), MutationError> {
+        let array = self.doc.get_or_insert_array(array_root(property_name.as_ref()));
+        let mut ytx = self.doc.transact_mut();
+        let len = array.len(&ytx);
+        check_bounds(from.saturating_add(1), len)?;
+        check_bounds(to.saturating_add(1), len)?;
+        // yrs places the element before whatever is at `target` before the move
+        let target = if to > from { to + 1 } else { to };
+        array.move_to(&mut ytx, from, target);
+        Ok(())
+    }
+
+    /// Replace the whole contents of a list property
+    pub fn array_replace(&self, property_name: impl AsRef<str>, values: Vec<YrsAny>) -> Result<(), MutationError> {
+        let array = self.doc.get_or_insert_array(array_root(property_name.as_ref()));
+        let mut ytx = self.doc.transact_mut();
+        let len = array.len(&ytx);
+        array.remove_range(&mut ytx, 0, len);
+        array.insert_range(&mut ytx, 0, values);
+        Ok(())
+    }
+
+    /// The contents of a map property as a JSON object, or `None` if it was never written
+    pub fn get_map(&self, property_name: impl AsRef<str>) -> Option<serde_json::Value> {
+        let txn = self.doc.transact();
+        let map = txn.get_map(map_root(property_name.as_ref()))?;
+        Some(any_to_json(map.to_json(&txn)))
+    }
+
+    /// Set the value at `path` within a map property, creating the objects along it as needed.
+    ///
+    /// Each object on the path is its own Yrs map, so concurrent writes to
+    /// different keys merge at any depth. JSON objects in `value` become maps
+    /// too; every other value, arrays included, is written as a whole.
+    pub fn map_set(&self, property_name: impl AsRef<str>, path: &[&str], value: serde_json::Value) -> Result<(), MutationError> {
+        let Some((key, parents)) = path.split_last() else {
+            return Err(PropertyError::InvalidValue { value: String::new(), ty: "map path".to_owned() }.into());
+        };
+        let root = self.doc.get_or_insert_map(map_root(property_name.as_ref()));
+        let mut ytx = self.doc.transact_mut();
+        let map = nested_map(&mut ytx, root, parents);
+        map.insert(&mut ytx, *key, json_to_in(value)?);
+        Ok(())
+    }
+
+    /// Remove the value at `path` within a map property; a path that does not exist is left alone
+    pub fn map_remove(&self, property_name: impl AsRef<str>, path: &[&str]) -> Result<(), MutationError> {
+        let Some((key, parents)) = path.split_last() else {
+            return Err(PropertyError::InvalidValue { value: String::new(), ty: "map path".to_owned() }.into());
+        };
+        let root = self.doc.get_or_insert_map(map_root(property_name.as_ref()));
+        let mut ytx = self.doc.transact_mut();
+        let mut map = root;
+        for parent in parents {
+            match map.get(&ytx, parent) {
+                Some(Out::YMap(child)) => map = child,
+                _ => return Ok(()),
+            }
+        }
+        map.remove(&mut ytx, key);
+        Ok(())
+    }
+
+    /// Replace the whole contents of a map property with the entries of `object`
+    pub fn map_replace(
+        &self,
+        property_name: impl AsRef<str>,
+        object: serde_json::Map<String, serde_json::Value>,
+    ) -> Result<(), MutationError> {
+        let root = self.doc.get_or_insert_map(map_root(property_name.as_ref()));
+        let mut ytx = self.doc.transact_mut();
+        root.clear(&mut ytx);
+        for (key, value) in object {
+            root.insert(&mut ytx, key, json_to_in(value)?);
+        }
+        Ok(())
+    }
+
+    /// The members of a set property in sorted order, or `None` if it was never written
+    pub fn get_set(&self, property_name: impl AsRef<str>) -> Option<Vec<String>> {
+        let txn = self.doc.transact();
+        let set = txn.get_map(set_root(property_name.as_ref()))?;
+        let mut members: Vec<String> = set.keys(&txn).map(str::to_owned).collect();
+        members.sort();
+        Some(members)
+    }
+
+    /// Add `member` to a set property.
+    ///
+    /// A set is a Yrs map keyed by member. A remove only deletes the entries
+    /// it has seen, so an add made concurrently with a remove of the same
+    /// member survives the merge (add wins).
+    pub fn set_insert(&self, property_name: impl AsRef<str>, member: &str) -> Result<(), MutationError> {
+        let set = self.doc.get_or_insert_map(set_root(property_name.as_ref()));
+        let mut ytx = self.doc.transact_mut();
+        // Re-adding a present member writes a new entry too, which a concurrent remove has not seen
+        set.insert(&mut ytx, member, true);
+        Ok(())
+    }
+
+    /// Remove `member` from a set property; a member that is not present is left alone
+    pub fn set_remove(&self, property_name: impl AsRef<str>, member: &str) -> Result<(), MutationError> {
+        let set = self.doc.get_or_insert_map(set_root(property_name.as_ref()));
+        let mut ytx = self.doc.transact_mut();
+        set.remove(&mut ytx, member);
+        Ok(())
+    }
+
+    /// Make `members` the contents of a set property, leaving members it keeps untouched
+    pub fn set_replace(&self, property_name: impl AsRef<str>, members: Vec<String>) -> Result<(), MutationError> {
+        let set = self.doc.get_or_insert_map(set_root(property_name.as_ref()));
+        let mut ytx = self.doc.transact_mut();
+        let removed: Vec<String> = set.keys(&ytx).filter(|key| !members.iter().any(|member| member == key)).map(str::to_owned).collect();
+        for member in removed {
+            set.remove(&mut ytx, &member);
+        }
+        for member in members {
+            if !set.contains_key(&ytx, &member) {
+                set.insert(&mut ytx, member, true);
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_update(&self, update: &[u8]) -> Result<(), MutationError> {
+        let mut txn = self.doc.transact_mut();
         let update = Update::decode_v2(update).map_err(|e| StateError::SerializationError(Box::new(e)))?;
         txn.apply_update(update).map_err(|e| MutationError::UpdateFailed(Box::new(e)))?;
         txn.commit();
@@ -81,6 +290,24 @@ impl YrsBackend {
         Ok(())
     }
 
+    /// Apply `updates`, then notify the subscribers of each listened field whose value they changed
+    fn apply_updates<'a>(&self, updates: impl IntoIterator<Item = &'a [u8]>) -> Result<(), MutationError> {
+        // Comparing values rather than observing the roots avoids creating a
+        // root for every listened field, which would have to guess its type
+        let listened: Vec<PropertyName> = self.field_broadcasts.lock().unwrap().keys().cloned().collect();
+        let before: Vec<Option<Value>> = listened.iter().map(|name| self.property_value(name)).collect();
+
+        for update in updates {
+            self.apply_update(update)?;
+        }
+
+        let changed: Vec<&PropertyName> =
+            listened.iter().zip(before).filter(|(name, before)| self.property_value(name) != *before).map(|(name, _)| name).collect();
+        super::notify_changed_fields(&self.field_broadcasts, changed);
+
+        Ok(())
+    }
+
     fn get_property_string(&self, trx: &yrs::Transaction, property_name: &PropertyName) -> Option<Value> {
         let value = match trx.get_text(property_name.clone()) {
             Some(text_ref) => {
@@ -92,6 +319,63 @@ impl YrsBackend {
 
         value.map(Value::String)
     }
+
+    fn get_property_array(&self, trx: &yrs::Transaction, property_name: &str) -> Option<Value> {
+        let array = trx.get_array(array_root(property_name))?;
+        let items = array.iter(trx).map(|out| any_to_json(out.to_json(trx))).collect();
+        Some(Value::Json(serde_json::Value::Array(items)))
+    }
+
+    fn get_property_map(&self, trx: &yrs::Transaction, property_name: &str) -> Option<Value> {
+        let map = trx.get_map(map_root(property_name))?;
+        Some(Value::Json(any_to_json(map.to_json(trx))))
+    }
+
+    /// A set's value is a JSON array of its members in sorted order
+    fn get_property_set(&self, trx: &yrs::Transaction, property_name: &str) -> Option<Value> {
+        let set = trx.get_map(set_root(property_name))?;
+        let mut members: Vec<&str> = set.keys(trx).collect();
+        members.sort();
+        Some(Value::Json(serde_json::Value::Array(
+            members.into_iter().map(|member| serde_json::Value::String(member.to_owned())).collect(),
+        )))
+    }
+
+    fn get_property(&self, trx: &yrs::Transaction, property_name: &PropertyName) -> Option<Value> {
+        self.get_property_array(trx, property_name)
+            .or_else(|| self.get_property_map(trx, property_name))
+            .or_else(|| self.get_property_set(trx, property_name))
+            .or_else(|| self.get_property_string(trx, property_name))
+    }
+}
+
+/// The map at `path` below `map`, replacing anything on the path that is not a map with one
+fn nested_map(txn: &mut TransactionMut, mut map: MapRef, path: &[&str]) -> MapRef {
+    for key in path {
+        map = match map.get(txn, key) {
+            Some(Out::YMap(child)) => child,
+            _ => map.insert(txn, *key, MapPrelim::default()),
+        };
+    }
+    map
+}
+
+fn json_to_in(value: serde_json::Value) -> Result<In, PropertyError> {
+    Ok(match value {
+        serde_json::Value::Object(object) => {
+            In::Map(object.into_iter().map(|(key, value)| Ok((key, json_to_in(value)?))).collect::<Result<MapPrelim, PropertyError>>()?)
+        }
+        value => In::Any(serde_json::from_value(value).map_err(|e| PropertyError::SerializeError(Box::new(e)))?),
+    })
+}
+
+fn any_to_json(any: YrsAny) -> serde_json::Value { serde_json::to_value(any).unwrap_or(serde_json::Value::Null) }
+
+fn check_bounds(index: u32, len: u32) -> Result<(), MutationError> {
+    if index > len {
+        return Err(PropertyError::OutOfBounds { index, len }.into());
+    }
+    Ok(())
 }
 
 impl PropertyBackend for YrsBackend {
@@ -108,13 +392,24 @@ impl PropertyBackend for YrsBackend {
 
     fn properties(&self) -> Vec<String> {
         let trx = Transact::transact(&self.doc);
-        let root_refs = trx.root_refs();
-        root_refs.map(|(name, _)| name.to_owned()).collect()
+        let mut properties: Vec<String> = trx
+            .root_refs()
+            .map(|(name, _)| {
+                [ARRAY_ROOT_SUFFIX, MAP_ROOT_SUFFIX, SET_ROOT_SUFFIX]
+                    .iter()
+                    .find_map(|suffix| name.strip_suffix(suffix))
+                    .unwrap_or(name)
+                    .to_owned()
+            })
+            .collect();
+        properties.sort();
+        properties.dedup();
+        properties
     }
 
     fn property_value(&self, property_name: &PropertyName) -> Option<Value> {
         let trx = Transact::transact(&self.doc);
-        self.get_property_string(&trx, property_name)
+        self.get_property(&trx, property_name)
     }
 
     fn property_values(&self) -> BTreeMap<PropertyName, Option<Value>> {
@@ -123,7 +418,7 @@ impl PropertyBackend for YrsBackend {
         let mut values = BTreeMap::new();
         let trx = Transact::transact(&self.doc);
         for property_name in properties {
-            let value = self.get_property_string(&trx, &property_name);
+            let value = self.get_property(&trx, &property_name);
             values.insert(property_name, value);
         }
 
@@ -168,34 +463,19 @@ impl PropertyBackend for YrsBackend {
     }
 
     fn apply_operations(&self, operations: &[Operation]) -> Result<(), MutationError> {
-        let changed_fields = Arc::new(Mutex::new(std::collections::HashSet::new()));
-        for operation in operations {
-            self.apply_update(&operation.diff, &changed_fields)?;
-        }
-        //Only notify field subscribers for fields that actually changed
-        super::notify_changed_fields(&self.field_broadcasts, changed_fields.lock().unwrap().iter());
-
-        Ok(())
+        self.apply_updates(operations.iter().map(|operation| operation.diff.as_slice()))
     }
 
     fn apply_layer(&self, layer: &crate::event_dag::EventLayer) -> Result<(), MutationError> {
         // Order within layer doesn't matter for CRDTs - they're commutative.
         // Just apply all operations from to_apply events.
-        let changed_fields = Arc::new(Mutex::new(std::collections::HashSet::new()));
-
-        for event in &layer.to_apply {
-            // Extract Yrs operations from this event
-            {
-                for operation in event.operations().backend_operations(Self::property_backend_name()) {
-                    self.apply_update(&operation.diff, &changed_fields)?;
-                }
-            }
-        }
-
-        // Notify field subscribers for fields that actually changed
-        super::notify_changed_fields(&self.field_broadcasts, changed_fields.lock().unwrap().iter());
-
-        Ok(())
+        self.apply_updates(
+            layer
+                .to_apply
+                .iter()
+                .flat_map(|event| event.operations().backend_operations(Self::property_backend_name()))
+                .map(|operation| operation.diff.as_slice()),
+        )
     }
 
     fn listen_field(
@@ -5,9 +5,9 @@ pub mod value;
 use ankurah_proto::EntityId;
 
 pub use traits::{ActiveType, FromActiveType, FromEntity, InitializeWith, PropertyError};
-pub use value::{Json, Ref, YrsString};
+pub use value::{Counter, FetchRefs, Json, ListElement, Ref, RefSet, RichText, TextRun, YrsArray, YrsMap, YrsRichText, YrsString};
 
-use crate::value::Value;
+use crate::value::{Date, Decimal, Timestamp, Uuid, Value};
 
 pub type PropertyName = String;
 
@@ -66,10 +66,17 @@ impl_property!(String => String, "string");
 impl_property!(i16 => I16, "i16");
 impl_property!(i32 => I32, "i32");
 impl_property!(i64 => I64, "i64");
+impl_property!(u32 => U32, "u32");
+impl_property!(u64 => U64, "u64");
+impl_property!(i128 => I128, "i128");
 impl_property!(f64 => F64, "f64");
 impl_property!(bool => Bool, "bool");
 impl_property!(EntityId => EntityId, "entityid");
 impl_property!(Vec<u8> => Binary, "binary");
+impl_property!(Timestamp => Timestamp, "timestamp");
+impl_property!(Date => Date, "date");
+impl_property!(Decimal => Decimal, "decimal");
+impl_property!(Uuid => Uuid, "uuid");
 
 impl<'a> Property for std::borrow::Cow<'a, str> {
     const VALUE_TYPE: &'static str = "string";
@@ -87,8 +94,8 @@ impl<'a> Property for std::borrow::Cow<'a, str> {
 
 #[cfg(test)]
 mod tests {
-    use super::backend::{LWWBackend, PropertyBackend, YrsBackend};
-    use super::value::{YrsString, LWW};
+    use super::backend::{CounterBackend, LWWBackend, PropertyBackend, YrsBackend};
+    use super::value::{Counter, Json, RichText, YrsArray, YrsMap, YrsRichText, YrsString, LWW};
     use super::ActiveType;
 
     /// The active types' declared backend names must match the names those
@@ -98,5 +105,9 @@ mod tests {
     fn active_type_backend_names_match_the_registry() {
         assert_eq!(<LWW<String> as ActiveType>::BACKEND, LWWBackend::property_backend_name());
         assert_eq!(<YrsString<String> as ActiveType>::BACKEND, YrsBackend::property_backend_name());
+        assert_eq!(<YrsArray<String> as ActiveType>::BACKEND, YrsBackend::property_backend_name());
+        assert_eq!(<YrsMap<Json> as ActiveType>::BACKEND, YrsBackend::property_backend_name());
+        assert_eq!(<YrsRichText<RichText> as ActiveType>::BACKEND, YrsBackend::property_backend_name());
+        assert_eq!(<Counter<i64> as ActiveType>::BACKEND, CounterBackend::property_backend_name());
     }
 }
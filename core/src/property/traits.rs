@@ -41,6 +41,8 @@ pub enum PropertyError {
     InvalidValue { value: String, ty: String },
     #[error("transaction is no longer alive")]
     TransactionClosed,
+    #[error("index {index} is out of bounds for length {len}")]
+    OutOfBounds { index: u32, len: u32 },
 
     #[error("cast error: {0}")]
     CastError(CastError),
@@ -0,0 +1,116 @@
+use std::{marker::PhantomData, sync::Arc};
+
+use crate::{
+    entity::{Entity, ProvisionalEntity},
+    error::MutationError,
+    property::{
+        backend::{CounterBackend, PropertyBackend},
+        traits::{FromActiveType, FromEntity, InitializeWith, PropertyError},
+        PropertyName,
+    },
+};
+
+use ankurah_signals::{
+    signal::{Listener, ListenerGuard},
+    Signal,
+};
+
+/// An `i64` property that is changed by adding to it, on the counter backend.
+///
+/// Likes, stock levels and view counts lose updates under LWW: two replicas
+/// that each read 10 and write 11 converge on 11. Increments to a `Counter`
+/// all survive, so the same two edits converge on 12. A count that has never
+/// been incremented reads as 0.
+#[derive(Debug, Clone)]
+pub struct Counter<Projected> {
+    pub property_name: PropertyName,
+    pub backend: Arc<CounterBackend>,
+    pub entity: Entity,
+    phantom: PhantomData<Projected>,
+}
+
+impl<Projected> Counter<Projected> {
+    pub fn new(property_name: PropertyName, backend: Arc<CounterBackend>, entity: Entity) -> Self {
+        Self { property_name, backend, entity, phantom: PhantomData }
+    }
+    pub fn value(&self) -> i64 { self.backend.get(&self.property_name).unwrap_or_default() }
+    /// Add `amount`, which may be negative
+    pub fn increment(&self, amount: i64) -> Result<(), MutationError> {
+        self.check_writable()?;
+        self.backend.increment(&self.property_name, amount);
+        Ok(())
+    }
+    pub fn decrement(&self, amount: i64) -> Result<(), MutationError> { self.increment(amount.wrapping_neg()) }
+
+    fn check_writable(&self) -> Result<(), MutationError> {
+        if !self.entity.is_writable() {
+            return Err(PropertyError::TransactionClosed.into());
+        }
+        Ok(())
+    }
+}
+
+impl<Projected> crate::property::traits::ActiveType for Counter<Projected> {
+    const BACKEND: &'static str = "counter";
+}
+
+impl<Projected> FromEntity for Counter<Projected> {
+    fn from_entity(property_name: PropertyName, entity: &Entity) -> Self {
+        let backend = entity.get_backend::<CounterBackend>().expect("CounterBackend should exist");
+        Self::new(property_name, backend, entity.clone())
+    }
+}
+
+impl<Projected> FromActiveType<Counter<Projected>> for i64 {
+    fn from_active(active: Counter<Projected>) -> Result<Self, PropertyError> { Ok(active.value()) }
+}
+
+impl<Projected> FromActiveType<Counter<Projected>> for Option<i64> {
+    fn from_active(active: Counter<Projected>) -> Result<Self, PropertyError> { Ok(active.backend.get(&active.property_name)) }
+}
+
+impl<Projected> InitializeWith<i64> for Counter<Projected> {
+    fn initialize_with(provisional: &mut ProvisionalEntity, property_name: PropertyName, value: &i64) {
+        // The initial count is the genesis event's increment
+        let backend = provisional.get_backend::<CounterBackend>().expect("CounterBackend should exist");
+        backend.increment(&property_name, *value);
+    }
+}
+
+impl<Projected> InitializeWith<Option<i64>> for Counter<Projected> {
+    fn initialize_with(provisional: &mut ProvisionalEntity, property_name: PropertyName, value: &Option<i64>) {
+        let backend = provisional.get_backend::<CounterBackend>().expect("CounterBackend should exist");
+        if let Some(value) = value {
+            backend.increment(&property_name, *value);
+        }
+    }
+}
+
+impl<Projected> ankurah_signals::Signal for Counter<Projected> {
+    fn listen(&self, listener: Listener) -> ListenerGuard { self.backend.listen_field(&self.property_name, listener) }
+
+    fn broadcast_id(&self) -> ankurah_signals::broadcast::BroadcastId { self.backend.field_broadcast_id(&self.property_name) }
+}
+
+impl<Projected> ankurah_signals::Subscribe<i64> for Counter<Projected>
+where Projected: Clone + Send + Sync + 'static
+{
+    fn subscribe<F>(&self, listener: F) -> ankurah_signals::SubscriptionGuard
+    where F: ankurah_signals::subscribe::IntoSubscribeListener<i64> {
+        let listener = listener.into_subscribe_listener();
+        let counter = self.clone();
+        let subscription = self.listen(Arc::new(move |_| listener(counter.value())));
+        ankurah_signals::SubscriptionGuard::new(subscription)
+    }
+}
+
+#[cfg(any(feature = "wasm", feature = "uniffi"))]
+pub mod ffi {
+    //! FFI wrapper types for the counter backend (WASM and UniFFI)
+    #[cfg(feature = "wasm")]
+    use ::wasm_bindgen::prelude::*;
+    use ankurah_derive::impl_provided_wrapper_types;
+    impl_provided_wrapper_types!("src/property/value/counter.ron");
+}
+#[cfg(any(feature = "wasm", feature = "uniffi"))]
+pub use ffi::*;
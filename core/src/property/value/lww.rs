@@ -99,6 +99,7 @@ pub mod ffi {
     //! FFI wrapper types for LWW backend (WASM and UniFFI)
     use super::*;
     use crate::property::Json;
+    use crate::value::{Date, Decimal, Timestamp, Uuid};
     #[cfg(feature = "wasm")]
     use ::wasm_bindgen::prelude::*;
     use ankurah_derive::impl_provided_wrapper_types;
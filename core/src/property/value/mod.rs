@@ -1,9 +1,21 @@
+pub mod counter;
 pub mod entity_ref;
 pub mod json;
 pub mod lww;
+pub mod ref_set;
+pub mod rich_text;
 pub mod yrs;
+pub mod yrs_array;
+pub mod yrs_map;
+pub mod yrs_rich_text;
 
+pub use counter::Counter;
 pub use entity_ref::Ref;
 pub use json::Json;
 pub use lww::LWW;
+pub use ref_set::{FetchRefs, RefSet};
+pub use rich_text::{RichText, TextRun};
 pub use yrs::YrsString;
+pub use yrs_array::{ListElement, YrsArray};
+pub use yrs_map::YrsMap;
+pub use yrs_rich_text::YrsRichText;
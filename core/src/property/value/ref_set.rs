@@ -0,0 +1,242 @@
+//! Multi-valued entity reference property.
+//!
+//! A `Vec<Ref<T>>` field is a set of references with add-wins merge semantics,
+//! which covers many-to-many relations without a join model:
+//!
+//! ```rust,ignore
+//! #[derive(Model)]
+//! pub struct Task {
+//!     pub title: String,
+//!     pub assignees: Vec<Ref<User>>,
+//! }
+//!
+//! task.edit(&trx)?.assignees().insert(&alice)?;
+//! let tasks: Vec<TaskView> = ctx.fetch(selection!("assignees CONTAINS {}", alice.id())).await?;
+//! let assignees: Vec<UserView> = task.assignees()?.fetch_all(&ctx).await?;
+//! ```
+
+use std::{future::Future, marker::PhantomData, sync::Arc};
+
+use crate::{
+    context::Context,
+    entity::{Entity, ProvisionalEntity},
+    error::{MutationError, RetrievalError},
+    model::Model,
+    property::{
+        backend::{PropertyBackend, YrsBackend},
+        traits::{FromActiveType, FromEntity, InitializeWith, PropertyError},
+        Property, PropertyName,
+    },
+    value::Value,
+};
+
+use ankurah_signals::{
+    signal::{Listener, ListenerGuard},
+    Signal,
+};
+
+use super::Ref;
+
+/// A set of references to entities of model `T`, merged as a CRDT by the Yrs backend.
+///
+/// Members are kept by id, so each is present at most once and the set has no
+/// order; [`RefSet::value`] lists them sorted by id. When one replica adds a
+/// member while another removes it, the add wins. Its value is a JSON array
+/// of base64 ids, which `CONTAINS` (or `? IN field`) queries test membership
+/// of.
+#[derive(Debug)]
+pub struct RefSet<T> {
+    pub property_name: PropertyName,
+    pub backend: Arc<YrsBackend>,
+    pub entity: Entity,
+    phantom: PhantomData<T>,
+}
+
+// Derived Clone would require T: Clone
+impl<T> Clone for RefSet<T> {
+    fn clone(&self) -> Self { Self::new(self.property_name.clone(), self.backend.clone(), self.entity.clone()) }
+}
+
+impl<T> RefSet<T> {
+    pub fn new(property_name: PropertyName, backend: Arc<YrsBackend>, entity: Entity) -> Self {
+        Self { property_name, backend, entity, phantom: PhantomData }
+    }
+    pub fn value(&self) -> Vec<Ref<T>> { self.members().unwrap_or_default() }
+    pub fn len(&self) -> u32 { self.backend.get_set(&self.property_name).map_or(0, |members| members.len() as u32) }
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+    pub fn contains(&self, member: impl Into<Ref<T>>) -> bool {
+        let member = member.into().to_base64();
+        self.backend.get_set(&self.property_name).is_some_and(|members| members.contains(&member))
+    }
+    pub fn insert(&self, member: impl Into<Ref<T>>) -> Result<(), MutationError> {
+        self.check_writable()?;
+        self.backend.set_insert(&self.property_name, &member.into().to_base64())
+    }
+    pub fn remove(&self, member: impl Into<Ref<T>>) -> Result<(), MutationError> {
+        self.check_writable()?;
+        self.backend.set_remove(&self.property_name, &member.into().to_base64())
+    }
+    pub fn replace(&self, members: Vec<Ref<T>>) -> Result<(), MutationError> {
+        self.check_writable()?;
+        self.backend.set_replace(&self.property_name, to_members(&members))
+    }
+
+    /// The set's members, or `None` if it was never written
+    fn members(&self) -> Option<Vec<Ref<T>>> {
+        let members = self.backend.get_set(&self.property_name)?;
+        // A member that is not an id can only come from a foreign writer; skip it
+        // rather than failing the whole set
+        let mut members: Vec<Ref<T>> = members.iter().filter_map(|member| Ref::from_base64(member).ok()).collect();
+        // The backend orders members by their base64 form, which is not id order
+        members.sort_by(|a, b| a.id_ref().cmp(b.id_ref()));
+        Some(members)
+    }
+
+    fn check_writable(&self) -> Result<(), MutationError> {
+        if !self.entity.is_writable() {
+            return Err(PropertyError::TransactionClosed.into());
+        }
+        Ok(())
+    }
+}
+
+impl<T: Model> RefSet<T> {
+    /// Fetch every member of the set from the given context.
+    pub async fn fetch_all(&self, ctx: &Context) -> Result<Vec<T::View>, RetrievalError> { self.value().fetch_all(ctx).await }
+}
+
+/// Fetching the entities a list of references points to.
+///
+/// Implemented for `[Ref<T>]`, so a view's `Vec<Ref<T>>` field can be resolved
+/// the same way as the [`RefSet`] it was read from.
+pub trait FetchRefs<T: Model> {
+    /// Fetch every referenced entity, in the order of the references.
+    fn fetch_all(&self, ctx: &Context) -> impl Future<Output = Result<Vec<T::View>, RetrievalError>>;
+}
+
+impl<T: Model> FetchRefs<T> for [Ref<T>] {
+    async fn fetch_all(&self, ctx: &Context) -> Result<Vec<T::View>, RetrievalError> {
+        let mut views = Vec::with_capacity(self.len());
+        for member in self {
+            views.push(member.get(ctx).await?);
+        }
+        Ok(views)
+    }
+}
+
+fn to_members<T>(members: &[Ref<T>]) -> Vec<String> { members.iter().map(|member| member.to_base64()).collect() }
+
+impl<T> crate::property::traits::ActiveType for RefSet<T> {
+    const BACKEND: &'static str = "yrs";
+}
+
+impl<T> FromEntity for RefSet<T> {
+    fn from_entity(property_name: PropertyName, entity: &Entity) -> Self {
+        let backend = entity.get_backend::<YrsBackend>().expect("YrsBackend should exist");
+        Self::new(property_name, backend, entity.clone())
+    }
+}
+
+// A set that was never written is empty: Yrs does not persist an empty root, so
+// an entity created with no members looks the same
+impl<T> FromActiveType<RefSet<T>> for Vec<Ref<T>> {
+    fn from_active(active: RefSet<T>) -> Result<Self, PropertyError> { Ok(active.value()) }
+}
+
+impl<T> FromActiveType<RefSet<T>> for Option<Vec<Ref<T>>> {
+    fn from_active(active: RefSet<T>) -> Result<Self, PropertyError> { Ok(active.members()) }
+}
+
+impl<T> InitializeWith<Vec<Ref<T>>> for RefSet<T> {
+    fn initialize_with(provisional: &mut ProvisionalEntity, property_name: PropertyName, value: &Vec<Ref<T>>) {
+        let backend = provisional.get_backend::<YrsBackend>().expect("YrsBackend should exist");
+        backend.set_replace(&property_name, to_members(value)).unwrap();
+    }
+}
+
+impl<T> InitializeWith<Option<Vec<Ref<T>>>> for RefSet<T> {
+    fn initialize_with(provisional: &mut ProvisionalEntity, property_name: PropertyName, value: &Option<Vec<Ref<T>>>) {
+        // As with YrsString, the backend exists even when there is no value
+        let backend = provisional.get_backend::<YrsBackend>().expect("YrsBackend should exist");
+        if let Some(value) = value {
+            backend.set_replace(&property_name, to_members(value)).unwrap();
+        }
+    }
+}
+
+/// A reference set's catalog value is a JSON array of base64 ids
+impl<T> Property for Vec<Ref<T>> {
+    const VALUE_TYPE: &'static str = "json";
+
+    fn into_value(&self) -> Result<Option<Value>, PropertyError> {
+        Ok(Some(Value::Json(serde_json::Value::Array(to_members(self).into_iter().map(serde_json::Value::String).collect()))))
+    }
+
+    fn from_value(value: Option<Value>) -> Result<Self, PropertyError> {
+        match value {
+            Some(Value::Json(serde_json::Value::Array(items))) => items
+                .into_iter()
+                .map(|item| match item {
+                    serde_json::Value::String(id) => {
+                        Ref::from_base64(&id).map_err(|e| PropertyError::InvalidValue { value: id, ty: format!("Ref ({})", e) })
+                    }
+                    other => Err(PropertyError::InvalidValue { value: other.to_string(), ty: "Ref".to_string() }),
+                })
+                .collect(),
+            Some(other) => Err(PropertyError::InvalidVariant { given: other, ty: "Vec<Ref>".to_string() }),
+            None => Err(PropertyError::Missing),
+        }
+    }
+}
+
+impl<T> ankurah_signals::Signal for RefSet<T> {
+    fn listen(&self, listener: Listener) -> ListenerGuard { self.backend.listen_field(&self.property_name, listener) }
+
+    fn broadcast_id(&self) -> ankurah_signals::broadcast::BroadcastId { self.backend.field_broadcast_id(&self.property_name) }
+}
+
+impl<T: Send + Sync + 'static> ankurah_signals::Subscribe<Vec<Ref<T>>> for RefSet<T> {
+    fn subscribe<F>(&self, listener: F) -> ankurah_signals::SubscriptionGuard
+    where F: ankurah_signals::subscribe::IntoSubscribeListener<Vec<Ref<T>>> {
+        let listener = listener.into_subscribe_listener();
+        let set = self.clone();
+        let subscription = self.listen(Arc::new(move |_| listener(set.value())));
+        ankurah_signals::SubscriptionGuard::new(subscription)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ankurah_proto::EntityId;
+    use serde_json::json;
+
+    #[test]
+    fn concurrent_add_wins_over_remove() {
+        let (a, b, c) = (EntityId::random().to_base64(), EntityId::random().to_base64(), EntityId::random().to_base64());
+        let base = YrsBackend::new();
+        base.set_replace("assignees", vec![a.clone(), b.clone()]).unwrap();
+        base.to_operations().unwrap();
+
+        let left = base.fork().as_arc_dyn_any().downcast::<YrsBackend>().unwrap();
+        let right = base.fork().as_arc_dyn_any().downcast::<YrsBackend>().unwrap();
+        // Left removes `a` and `b` while right re-adds `a` and adds `c`
+        left.set_remove("assignees", &a).unwrap();
+        left.set_remove("assignees", &b).unwrap();
+        right.set_remove("assignees", &a).unwrap();
+        right.set_insert("assignees", &a).unwrap();
+        right.set_insert("assignees", &c).unwrap();
+
+        let left_ops = left.to_operations().unwrap().unwrap();
+        let right_ops = right.to_operations().unwrap().unwrap();
+        left.apply_operations(&right_ops).unwrap();
+        right.apply_operations(&left_ops).unwrap();
+
+        let mut expected = vec![a, c];
+        expected.sort();
+        assert_eq!(left.get_set("assignees"), Some(expected.clone()));
+        assert_eq!(right.get_set("assignees"), Some(expected.clone()));
+        assert_eq!(left.property_value(&"assignees".to_string()), Some(Value::Json(json!(expected))));
+        assert_eq!(left.properties(), vec!["assignees".to_string()]);
+    }
+}
@@ -0,0 +1,140 @@
+//! Formatted text, as a value.
+//!
+//! `RichText` is what a rich text property reads as: its text split into
+//! runs that share the same formatting. It serializes to the array of
+//! `{ "insert": ..., "attributes": ... }` operations that Quill-style deltas
+//! use, which is also what ProseMirror and TipTap bindings for Yjs consume.
+//!
+//! As a catalog value it is its plain text, so AnkQL filters on a rich text
+//! property compare against the text without formatting.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::property::{traits::PropertyError, Property};
+use crate::value::Value;
+
+/// Text with formatting, as a sequence of runs.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RichText(pub Vec<TextRun>);
+
+/// A piece of text and the formatting attributes (bold, link, heading, ...) that apply to all of it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextRun {
+    pub insert: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes: BTreeMap<String, serde_json::Value>,
+}
+
+impl RichText {
+    /// Text without any formatting
+    pub fn plain(text: impl Into<String>) -> Self {
+        let text = text.into();
+        if text.is_empty() {
+            return Self::default();
+        }
+        Self(vec![TextRun { insert: text, attributes: BTreeMap::new() }])
+    }
+
+    /// Append a run of `text` formatted with `attributes`
+    pub fn with(mut self, text: impl Into<String>, attributes: impl IntoIterator<Item = (impl Into<String>, serde_json::Value)>) -> Self {
+        let attributes = attributes.into_iter().map(|(name, value)| (name.into(), value)).collect();
+        self.0.push(TextRun { insert: text.into(), attributes });
+        self
+    }
+
+    /// The text with its formatting dropped
+    pub fn to_plain_text(&self) -> String { self.0.iter().map(|run| run.insert.as_str()).collect() }
+
+    pub fn runs(&self) -> &[TextRun] { &self.0 }
+}
+
+impl From<&str> for RichText {
+    fn from(text: &str) -> Self { Self::plain(text) }
+}
+
+impl From<String> for RichText {
+    fn from(text: String) -> Self { Self::plain(text) }
+}
+
+impl std::fmt::Display for RichText {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { f.write_str(&self.to_plain_text()) }
+}
+
+// WASM bindings for RichText type, by way of its delta JSON like Json
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen::prelude::wasm_bindgen(typescript_custom_section)]
+const TS_RICH_TEXT_TYPE: &'static str = r#"
+/** Formatted text as Quill-style delta operations */
+export type RichText = { insert: string, attributes?: Record<string, any> }[];
+"#;
+
+#[cfg(feature = "wasm")]
+impl From<RichText> for JsValue {
+    fn from(text: RichText) -> Self {
+        let serializer = serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true);
+        text.serialize(&serializer).unwrap_or(JsValue::NULL)
+    }
+}
+
+#[cfg(feature = "wasm")]
+impl wasm_bindgen::describe::WasmDescribe for RichText {
+    fn describe() { JsValue::describe() }
+}
+
+#[cfg(feature = "wasm")]
+impl wasm_bindgen::convert::IntoWasmAbi for RichText {
+    type Abi = <JsValue as wasm_bindgen::convert::IntoWasmAbi>::Abi;
+
+    fn into_abi(self) -> Self::Abi { JsValue::from(self).into_abi() }
+}
+
+#[cfg(feature = "wasm")]
+impl wasm_bindgen::convert::FromWasmAbi for RichText {
+    type Abi = <JsValue as wasm_bindgen::convert::FromWasmAbi>::Abi;
+
+    unsafe fn from_abi(js: Self::Abi) -> Self {
+        let js_value = JsValue::from_abi(js);
+        serde_wasm_bindgen::from_value(js_value).unwrap_or_default()
+    }
+}
+
+// UniFFI custom type - maps RichText <-> String (the delta as JSON)
+#[cfg(feature = "uniffi")]
+::uniffi::custom_type!(RichText, String, {
+    lower: |obj| serde_json::to_string(&obj).expect("Failed to serialize rich text"),
+    try_lift: |val| serde_json::from_str(&val).map_err(Into::into),
+});
+
+impl Property for RichText {
+    const VALUE_TYPE: &'static str = "string";
+
+    fn into_value(&self) -> Result<Option<Value>, PropertyError> { Ok(Some(Value::String(self.to_plain_text()))) }
+
+    fn from_value(value: Option<Value>) -> Result<Self, PropertyError> {
+        match value {
+            Some(Value::String(text)) => Ok(Self::plain(text)),
+            Some(other) => Err(PropertyError::InvalidVariant { given: other, ty: "RichText".to_string() }),
+            None => Err(PropertyError::Missing),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn serializes_as_delta_operations() {
+        let text = RichText::plain("Hello ").with("world", [("bold", json!(true))]);
+        assert_eq!(serde_json::to_value(&text).unwrap(), json!([{"insert": "Hello "}, {"insert": "world", "attributes": {"bold": true}}]));
+        assert_eq!(serde_json::from_value::<RichText>(serde_json::to_value(&text).unwrap()).unwrap(), text);
+        assert_eq!(text.into_value().unwrap(), Some(Value::String("Hello world".into())));
+    }
+}
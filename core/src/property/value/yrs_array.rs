@@ -0,0 +1,216 @@
+use std::{marker::PhantomData, sync::Arc};
+
+use serde::{de::DeserializeOwned, Serialize};
+use yrs::encoding::serde::{from_any, to_any};
+
+use crate::{
+    entity::{Entity, ProvisionalEntity},
+    error::MutationError,
+    property::{
+        backend::{PropertyBackend, YrsBackend},
+        traits::{FromActiveType, FromEntity, InitializeWith, PropertyError},
+        Property, PropertyName,
+    },
+    value::Value,
+};
+
+use ankurah_signals::{
+    signal::{Listener, ListenerGuard},
+    Signal,
+};
+
+/// A type that can be an element of a collaborative list.
+///
+/// Elements are stored in the Yrs document as plain values, so only types
+/// with a lossless mapping onto Yrs' `Any` qualify.
+pub trait ListElement: Serialize + DeserializeOwned + Clone + Send + Sync + 'static {}
+
+impl ListElement for String {}
+impl ListElement for i16 {}
+impl ListElement for i32 {}
+impl ListElement for i64 {}
+impl ListElement for f64 {}
+impl ListElement for bool {}
+
+/// An ordered list property, merged as a CRDT by the Yrs backend.
+///
+/// Concurrent inserts, removes, and moves from different replicas all survive
+/// the merge, unlike a `Json` array, where the last whole-value write wins.
+#[derive(Debug, Clone)]
+pub struct YrsArray<T> {
+    pub property_name: PropertyName,
+    pub backend: Arc<YrsBackend>,
+    pub entity: Entity,
+    phantom: PhantomData<T>,
+}
+
+impl<T: ListElement> YrsArray<T> {
+    pub fn new(property_name: PropertyName, backend: Arc<YrsBackend>, entity: Entity) -> Self {
+        Self { property_name, backend, entity, phantom: PhantomData }
+    }
+    pub fn value(&self) -> Vec<T> { self.items().unwrap_or_default() }
+    pub fn len(&self) -> u32 { self.backend.array_len(&self.property_name) }
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+    pub fn insert(&self, index: u32, value: T) -> Result<(), MutationError> {
+        self.check_writable()?;
+        self.backend.array_insert(&self.property_name, index, vec![to_element(&value)?])
+    }
+    pub fn push(&self, value: T) -> Result<(), MutationError> {
+        self.check_writable()?;
+        self.backend.array_insert(&self.property_name, self.len(), vec![to_element(&value)?])
+    }
+    pub fn remove(&self, index: u32, length: u32) -> Result<(), MutationError> {
+        self.check_writable()?;
+        self.backend.array_remove(&self.property_name, index, length)
+    }
+    /// Move the element at `from` so that it ends up at index `to`.
+    pub fn move_to(&self, from: u32, to: u32) -> Result<(), MutationError> {
+        self.check_writable()?;
+        self.backend.array_move(&self.property_name, from, to)
+    }
+    pub fn replace(&self, values: Vec<T>) -> Result<(), MutationError> {
+        self.check_writable()?;
+        self.backend.array_replace(&self.property_name, to_elements(&values)?)
+    }
+
+    /// The list's elements, or `None` if it was never written
+    fn items(&self) -> Option<Vec<T>> {
+        let elements = self.backend.get_array(&self.property_name)?;
+        // An element another replica wrote with a different type is skipped
+        // rather than failing the whole list
+        Some(elements.iter().filter_map(|element| from_any(element).ok()).collect())
+    }
+
+    fn check_writable(&self) -> Result<(), MutationError> {
+        if !self.entity.is_writable() {
+            return Err(PropertyError::TransactionClosed.into());
+        }
+        Ok(())
+    }
+}
+
+fn to_element<T: ListElement>(value: &T) -> Result<yrs::Any, PropertyError> {
+    to_any(value).map_err(|e| PropertyError::SerializeError(Box::new(e)))
+}
+
+fn to_elements<T: ListElement>(values: &[T]) -> Result<Vec<yrs::Any>, PropertyError> { values.iter().map(to_element).collect() }
+
+impl<T> crate::property::traits::ActiveType for YrsArray<T> {
+    const BACKEND: &'static str = "yrs";
+}
+
+impl<T: ListElement> FromEntity for YrsArray<T> {
+    fn from_entity(property_name: PropertyName, entity: &Entity) -> Self {
+        let backend = entity.get_backend::<YrsBackend>().expect("YrsBackend should exist");
+        Self::new(property_name, backend, entity.clone())
+    }
+}
+
+impl<T: ListElement> FromActiveType<YrsArray<T>> for Vec<T> {
+    fn from_active(active: YrsArray<T>) -> Result<Self, PropertyError> { active.items().ok_or(PropertyError::Missing) }
+}
+
+impl<T: ListElement> FromActiveType<YrsArray<T>> for Option<Vec<T>> {
+    fn from_active(active: YrsArray<T>) -> Result<Self, PropertyError> { Ok(active.items()) }
+}
+
+impl<T: ListElement> InitializeWith<Vec<T>> for YrsArray<T> {
+    fn initialize_with(provisional: &mut ProvisionalEntity, property_name: PropertyName, value: &Vec<T>) {
+        let backend = provisional.get_backend::<YrsBackend>().expect("YrsBackend should exist");
+        backend.array_insert(&property_name, 0, to_elements(value).expect("list elements serialize")).unwrap();
+    }
+}
+
+impl<T: ListElement> InitializeWith<Option<Vec<T>>> for YrsArray<T> {
+    fn initialize_with(provisional: &mut ProvisionalEntity, property_name: PropertyName, value: &Option<Vec<T>>) {
+        // As with YrsString, the backend exists even when there is no value
+        let backend = provisional.get_backend::<YrsBackend>().expect("YrsBackend should exist");
+        if let Some(value) = value {
+            backend.array_insert(&property_name, 0, to_elements(value).expect("list elements serialize")).unwrap();
+        }
+    }
+}
+
+/// A list's catalog value is a JSON array of its elements
+impl<T: ListElement> Property for Vec<T> {
+    const VALUE_TYPE: &'static str = "json";
+
+    fn into_value(&self) -> Result<Option<Value>, PropertyError> {
+        let items = serde_json::to_value(self).map_err(|e| PropertyError::SerializeError(Box::new(e)))?;
+        Ok(Some(Value::Json(items)))
+    }
+
+    fn from_value(value: Option<Value>) -> Result<Self, PropertyError> {
+        match value {
+            Some(Value::Json(items @ serde_json::Value::Array(_))) => {
+                serde_json::from_value(items).map_err(|e| PropertyError::DeserializeError(Box::new(e)))
+            }
+            Some(other) => Err(PropertyError::InvalidVariant { given: other, ty: "Vec".to_string() }),
+            None => Err(PropertyError::Missing),
+        }
+    }
+}
+
+impl<T: ListElement> ankurah_signals::Signal for YrsArray<T> {
+    fn listen(&self, listener: Listener) -> ListenerGuard { self.backend.listen_field(&self.property_name, listener) }
+
+    fn broadcast_id(&self) -> ankurah_signals::broadcast::BroadcastId { self.backend.field_broadcast_id(&self.property_name) }
+}
+
+impl<T: ListElement> ankurah_signals::Subscribe<Vec<T>> for YrsArray<T> {
+    fn subscribe<F>(&self, listener: F) -> ankurah_signals::SubscriptionGuard
+    where F: ankurah_signals::subscribe::IntoSubscribeListener<Vec<T>> {
+        let listener = listener.into_subscribe_listener();
+        let array = self.clone();
+        let subscription = self.listen(Arc::new(move |_| listener(array.value())));
+        ankurah_signals::SubscriptionGuard::new(subscription)
+    }
+}
+
+#[cfg(any(feature = "wasm", feature = "uniffi"))]
+pub mod ffi {
+    //! FFI wrapper types for YrsArray backend (WASM and UniFFI)
+    #[cfg(feature = "wasm")]
+    use ::wasm_bindgen::prelude::*;
+    use ankurah_derive::impl_provided_wrapper_types;
+    impl_provided_wrapper_types!("src/property/value/yrs_array.ron");
+}
+#[cfg(any(feature = "wasm", feature = "uniffi"))]
+pub use ffi::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn list_values_round_trip_through_json() {
+        let tags = vec!["a".to_string(), "b".to_string()];
+        let value = tags.into_value().unwrap();
+        assert_eq!(value, Some(Value::Json(serde_json::json!(["a", "b"]))));
+        assert_eq!(Vec::<String>::from_value(value).unwrap(), tags);
+        assert!(matches!(Vec::<i64>::from_value(Some(Value::I64(1))), Err(PropertyError::InvalidVariant { .. })));
+    }
+
+    #[test]
+    fn concurrent_list_edits_merge() {
+        let base = YrsBackend::new();
+        base.array_insert("tags", 0, to_elements(&["a", "b", "c"].map(String::from)).unwrap()).unwrap();
+        base.to_operations().unwrap();
+
+        let left = base.fork().as_arc_dyn_any().downcast::<YrsBackend>().unwrap();
+        let right = base.fork().as_arc_dyn_any().downcast::<YrsBackend>().unwrap();
+        left.array_insert("tags", 1, vec![yrs::Any::from("x")]).unwrap();
+        right.array_move("tags", 0, 2).unwrap();
+        right.array_remove("tags", 0, 1).unwrap();
+
+        let left_ops = left.to_operations().unwrap().unwrap();
+        let right_ops = right.to_operations().unwrap().unwrap();
+        left.apply_operations(&right_ops).unwrap();
+        right.apply_operations(&left_ops).unwrap();
+
+        let expected = Some(Value::Json(serde_json::json!(["x", "c", "a"])));
+        assert_eq!(left.property_value(&"tags".to_string()), expected);
+        assert_eq!(right.property_value(&"tags".to_string()), expected);
+        assert_eq!(left.properties(), vec!["tags".to_string()]);
+    }
+}
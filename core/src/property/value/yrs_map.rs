@@ -0,0 +1,182 @@
+use std::{marker::PhantomData, sync::Arc};
+
+use crate::{
+    entity::{Entity, ProvisionalEntity},
+    error::MutationError,
+    property::{
+        backend::{PropertyBackend, YrsBackend},
+        traits::{FromActiveType, FromEntity, InitializeWith, PropertyError},
+        PropertyName,
+    },
+};
+
+use ankurah_signals::{
+    signal::{Listener, ListenerGuard},
+    Signal,
+};
+
+use super::json::Json;
+
+/// A JSON object property whose keys merge as a CRDT on the Yrs backend.
+///
+/// Unlike [`Json`], which is replaced as a whole (last writer wins), every
+/// object in a `YrsMap` is a Yrs map: concurrent writes to different keys,
+/// at any depth, all survive. Its value is still a JSON object, so dot-path
+/// queries (`licensing.territory = ?`) work as they do for `Json`.
+///
+/// Paths are dot-separated keys. Two replicas that concurrently create the
+/// same missing object each create their own, and only one survives the
+/// merge, along with the keys written into it.
+#[derive(Debug, Clone)]
+pub struct YrsMap<Projected> {
+    pub property_name: PropertyName,
+    pub backend: Arc<YrsBackend>,
+    pub entity: Entity,
+    phantom: PhantomData<Projected>,
+}
+
+impl<Projected> YrsMap<Projected> {
+    pub fn new(property_name: PropertyName, backend: Arc<YrsBackend>, entity: Entity) -> Self {
+        Self { property_name, backend, entity, phantom: PhantomData }
+    }
+    pub fn value(&self) -> Option<Json> { self.backend.get_map(&self.property_name).map(Json) }
+    /// The value at a dot-separated `path`, if there is one
+    pub fn get(&self, path: &str) -> Option<Json> {
+        let value = self.value()?;
+        value.get_path(&path.split('.').collect::<Vec<_>>()).cloned().map(Json)
+    }
+    pub fn set(&self, path: &str, value: Json) -> Result<(), MutationError> {
+        self.check_writable()?;
+        self.backend.map_set(&self.property_name, &path.split('.').collect::<Vec<_>>(), value.0)
+    }
+    pub fn remove(&self, path: &str) -> Result<(), MutationError> {
+        self.check_writable()?;
+        self.backend.map_remove(&self.property_name, &path.split('.').collect::<Vec<_>>())
+    }
+    /// Replace the whole object; `value` must be a JSON object
+    pub fn replace(&self, value: Json) -> Result<(), MutationError> {
+        self.check_writable()?;
+        self.backend.map_replace(&self.property_name, into_object(value)?)
+    }
+
+    fn check_writable(&self) -> Result<(), MutationError> {
+        if !self.entity.is_writable() {
+            return Err(PropertyError::TransactionClosed.into());
+        }
+        Ok(())
+    }
+}
+
+fn into_object(value: Json) -> Result<serde_json::Map<String, serde_json::Value>, PropertyError> {
+    match value.0 {
+        serde_json::Value::Object(object) => Ok(object),
+        other => Err(PropertyError::InvalidValue { value: other.to_string(), ty: "YrsMap".to_owned() }),
+    }
+}
+
+impl<Projected> crate::property::traits::ActiveType for YrsMap<Projected> {
+    const BACKEND: &'static str = "yrs";
+}
+
+impl<Projected> FromEntity for YrsMap<Projected> {
+    fn from_entity(property_name: PropertyName, entity: &Entity) -> Self {
+        let backend = entity.get_backend::<YrsBackend>().expect("YrsBackend should exist");
+        Self::new(property_name, backend, entity.clone())
+    }
+}
+
+impl<Projected, S: FromActiveType<YrsMap<Projected>>> FromActiveType<YrsMap<Projected>> for Option<S> {
+    fn from_active(active: YrsMap<Projected>) -> Result<Self, PropertyError> {
+        match S::from_active(active) {
+            Ok(value) => Ok(Some(value)),
+            Err(PropertyError::Missing) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+impl<Projected> FromActiveType<YrsMap<Projected>> for Json {
+    fn from_active(active: YrsMap<Projected>) -> Result<Self, PropertyError> { active.value().ok_or(PropertyError::Missing) }
+}
+
+impl<Projected> InitializeWith<Json> for YrsMap<Projected> {
+    fn initialize_with(provisional: &mut ProvisionalEntity, property_name: PropertyName, value: &Json) {
+        let backend = provisional.get_backend::<YrsBackend>().expect("YrsBackend should exist");
+        backend.map_replace(&property_name, into_object(value.clone()).expect("a YrsMap holds a JSON object")).unwrap();
+    }
+}
+
+impl<Projected> InitializeWith<Option<Json>> for YrsMap<Projected> {
+    fn initialize_with(provisional: &mut ProvisionalEntity, property_name: PropertyName, value: &Option<Json>) {
+        // As with YrsString, the backend exists even when there is no value
+        let backend = provisional.get_backend::<YrsBackend>().expect("YrsBackend should exist");
+        if let Some(value) = value {
+            backend.map_replace(&property_name, into_object(value.clone()).expect("a YrsMap holds a JSON object")).unwrap();
+        }
+    }
+}
+
+impl<Projected> ankurah_signals::Signal for YrsMap<Projected> {
+    fn listen(&self, listener: Listener) -> ListenerGuard { self.backend.listen_field(&self.property_name, listener) }
+
+    fn broadcast_id(&self) -> ankurah_signals::broadcast::BroadcastId { self.backend.field_broadcast_id(&self.property_name) }
+}
+
+impl<Projected> ankurah_signals::Subscribe<Json> for YrsMap<Projected>
+where Projected: Clone + Send + Sync + 'static
+{
+    fn subscribe<F>(&self, listener: F) -> ankurah_signals::SubscriptionGuard
+    where F: ankurah_signals::subscribe::IntoSubscribeListener<Json> {
+        let listener = listener.into_subscribe_listener();
+        let map = self.clone();
+        let subscription = self.listen(Arc::new(move |_| {
+            if let Some(current_value) = map.value() {
+                listener(current_value);
+            }
+        }));
+        ankurah_signals::SubscriptionGuard::new(subscription)
+    }
+}
+
+#[cfg(any(feature = "wasm", feature = "uniffi"))]
+pub mod ffi {
+    //! FFI wrapper types for YrsMap backend (WASM and UniFFI)
+    use super::*;
+    #[cfg(feature = "wasm")]
+    use ::wasm_bindgen::prelude::*;
+    use ankurah_derive::impl_provided_wrapper_types;
+    impl_provided_wrapper_types!("src/property/value/yrs_map.ron");
+}
+#[cfg(any(feature = "wasm", feature = "uniffi"))]
+pub use ffi::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::Value;
+    use serde_json::json;
+
+    #[test]
+    fn concurrent_writes_to_distinct_keys_merge() {
+        let base = YrsBackend::new();
+        let object = json!({"territory": "US", "rights": {"holder": "Label"}});
+        base.map_replace("licensing", object.as_object().unwrap().clone()).unwrap();
+        base.to_operations().unwrap();
+
+        let left = base.fork().as_arc_dyn_any().downcast::<YrsBackend>().unwrap();
+        let right = base.fork().as_arc_dyn_any().downcast::<YrsBackend>().unwrap();
+        left.map_set("licensing", &["territory"], json!("CA")).unwrap();
+        right.map_set("licensing", &["rights", "type"], json!("exclusive")).unwrap();
+        right.map_remove("licensing", &["rights", "holder"]).unwrap();
+
+        let left_ops = left.to_operations().unwrap().unwrap();
+        let right_ops = right.to_operations().unwrap().unwrap();
+        left.apply_operations(&right_ops).unwrap();
+        right.apply_operations(&left_ops).unwrap();
+
+        let expected = Some(Value::Json(json!({"territory": "CA", "rights": {"type": "exclusive"}})));
+        assert_eq!(left.property_value(&"licensing".to_string()), expected);
+        assert_eq!(right.property_value(&"licensing".to_string()), expected);
+        assert_eq!(left.properties(), vec!["licensing".to_string()]);
+    }
+}
@@ -0,0 +1,217 @@
+use std::{marker::PhantomData, sync::Arc};
+
+use yrs::types::{Attrs, Delta};
+
+use crate::{
+    entity::{Entity, ProvisionalEntity},
+    error::MutationError,
+    property::{
+        backend::{PropertyBackend, YrsBackend},
+        traits::{FromActiveType, FromEntity, InitializeWith, PropertyError},
+        PropertyName,
+    },
+};
+
+use ankurah_signals::{
+    signal::{Listener, ListenerGuard},
+    Signal,
+};
+
+use super::json::Json;
+use super::rich_text::{RichText, TextRun};
+
+/// A text property with formatting attributes, on the Yrs backend.
+///
+/// The text merges exactly like a [`YrsString`](super::YrsString), and
+/// formatting is a set of attributes on ranges of it (`{"bold": true}`,
+/// `{"link": "https://..."}`, `{"heading": 2}`); setting an attribute to null
+/// removes it. Views read the property as [`RichText`]; its catalog value,
+/// and so AnkQL filtering, is the plain text.
+#[derive(Debug, Clone)]
+pub struct YrsRichText<Projected> {
+    pub property_name: PropertyName,
+    pub backend: Arc<YrsBackend>,
+    pub entity: Entity,
+    phantom: PhantomData<Projected>,
+}
+
+impl<Projected> YrsRichText<Projected> {
+    pub fn new(property_name: PropertyName, backend: Arc<YrsBackend>, entity: Entity) -> Self {
+        Self { property_name, backend, entity, phantom: PhantomData }
+    }
+    /// The text without its formatting
+    pub fn value(&self) -> Option<String> { self.backend.get_string(&self.property_name) }
+    /// The text with its formatting, as runs
+    pub fn delta(&self) -> Option<RichText> {
+        let runs = self.backend.get_formatted(&self.property_name)?;
+        Some(RichText(
+            runs.into_iter()
+                .map(|(insert, attrs)| TextRun {
+                    insert,
+                    attributes: attrs.into_iter().map(|(name, value)| (name.to_string(), any_to_json(value))).collect(),
+                })
+                .collect(),
+        ))
+    }
+    pub fn insert(&self, index: u32, value: &str) -> Result<(), MutationError> {
+        self.check_writable()?;
+        self.backend.insert(&self.property_name, index, value)
+    }
+    pub fn insert_formatted(&self, index: u32, value: &str, attributes: Json) -> Result<(), MutationError> {
+        self.check_writable()?;
+        self.backend.insert_formatted(&self.property_name, index, value, attrs_from_json(attributes.0)?)
+    }
+    pub fn delete(&self, index: u32, length: u32) -> Result<(), MutationError> {
+        self.check_writable()?;
+        self.backend.delete(&self.property_name, index, length)
+    }
+    pub fn format(&self, index: u32, length: u32, attributes: Json) -> Result<(), MutationError> {
+        self.check_writable()?;
+        self.backend.format(&self.property_name, index, length, attrs_from_json(attributes.0)?)
+    }
+    /// Apply an editor's change as Quill-style delta operations: `{"retain": n, "attributes"?: {...}}`,
+    /// `{"insert": "text", "attributes"?: {...}}` and `{"delete": n}`, walking from the start of the text.
+    pub fn apply_delta(&self, delta: Json) -> Result<(), MutationError> {
+        self.check_writable()?;
+        self.backend.apply_text_delta(&self.property_name, delta_from_json(delta.0)?)
+    }
+
+    fn check_writable(&self) -> Result<(), MutationError> {
+        if !self.entity.is_writable() {
+            return Err(PropertyError::TransactionClosed.into());
+        }
+        Ok(())
+    }
+}
+
+fn invalid(value: &serde_json::Value, ty: &str) -> PropertyError {
+    PropertyError::InvalidValue { value: value.to_string(), ty: ty.to_owned() }
+}
+
+fn any_to_json(value: yrs::Any) -> serde_json::Value { serde_json::to_value(value).unwrap_or(serde_json::Value::Null) }
+
+fn attrs_from_json(attributes: serde_json::Value) -> Result<Attrs, PropertyError> {
+    match attributes {
+        serde_json::Value::Object(object) => object
+            .into_iter()
+            .map(|(name, value)| {
+                let value = serde_json::from_value(value).map_err(|e| PropertyError::SerializeError(Box::new(e)))?;
+                Ok((name.into(), value))
+            })
+            .collect(),
+        serde_json::Value::Null => Ok(Attrs::new()),
+        other => Err(invalid(&other, "formatting attributes")),
+    }
+}
+
+fn run_attrs(attributes: Option<serde_json::Value>) -> Result<Option<Box<Attrs>>, PropertyError> {
+    match attributes {
+        Some(attributes) => Ok(Some(Box::new(attrs_from_json(attributes)?))),
+        None => Ok(None),
+    }
+}
+
+fn delta_from_json(delta: serde_json::Value) -> Result<Vec<Delta<yrs::In>>, PropertyError> {
+    let serde_json::Value::Array(steps) = delta else { return Err(invalid(&delta, "text delta")) };
+    steps
+        .into_iter()
+        .map(|mut step| {
+            let count = |value: &serde_json::Value| value.as_u64().and_then(|n| u32::try_from(n).ok());
+            let attributes = step.get_mut("attributes").map(serde_json::Value::take);
+            if let Some(text) = step.get("insert").and_then(|v| v.as_str()) {
+                Ok(Delta::Inserted(yrs::In::Any(yrs::Any::from(text)), run_attrs(attributes)?))
+            } else if let Some(n) = step.get("retain").and_then(count) {
+                Ok(Delta::Retain(n, run_attrs(attributes)?))
+            } else if let Some(n) = step.get("delete").and_then(count) {
+                Ok(Delta::Deleted(n))
+            } else {
+                Err(invalid(&step, "text delta step"))
+            }
+        })
+        .collect()
+}
+
+fn initialize(backend: &YrsBackend, property_name: &str, value: &RichText) {
+    let mut index = 0;
+    for run in value.runs() {
+        let attributes = attrs_from_json(serde_json::Value::Object(run.attributes.clone().into_iter().collect()));
+        backend.insert_formatted(property_name, index, &run.insert, attributes.expect("formatting attributes convert")).unwrap();
+        index += run.insert.len() as u32;
+    }
+}
+
+impl<Projected> crate::property::traits::ActiveType for YrsRichText<Projected> {
+    const BACKEND: &'static str = "yrs";
+}
+
+impl<Projected> FromEntity for YrsRichText<Projected> {
+    fn from_entity(property_name: PropertyName, entity: &Entity) -> Self {
+        let backend = entity.get_backend::<YrsBackend>().expect("YrsBackend should exist");
+        Self::new(property_name, backend, entity.clone())
+    }
+}
+
+impl<Projected, S: FromActiveType<YrsRichText<Projected>>> FromActiveType<YrsRichText<Projected>> for Option<S> {
+    fn from_active(active: YrsRichText<Projected>) -> Result<Self, PropertyError> {
+        match S::from_active(active) {
+            Ok(value) => Ok(Some(value)),
+            Err(PropertyError::Missing) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+impl<Projected> FromActiveType<YrsRichText<Projected>> for RichText {
+    fn from_active(active: YrsRichText<Projected>) -> Result<Self, PropertyError> { active.delta().ok_or(PropertyError::Missing) }
+}
+
+impl<Projected> InitializeWith<RichText> for YrsRichText<Projected> {
+    fn initialize_with(provisional: &mut ProvisionalEntity, property_name: PropertyName, value: &RichText) {
+        let backend = provisional.get_backend::<YrsBackend>().expect("YrsBackend should exist");
+        initialize(&backend, &property_name, value);
+    }
+}
+
+impl<Projected> InitializeWith<Option<RichText>> for YrsRichText<Projected> {
+    fn initialize_with(provisional: &mut ProvisionalEntity, property_name: PropertyName, value: &Option<RichText>) {
+        // As with YrsString, the backend exists even when there is no value
+        let backend = provisional.get_backend::<YrsBackend>().expect("YrsBackend should exist");
+        if let Some(value) = value {
+            initialize(&backend, &property_name, value);
+        }
+    }
+}
+
+impl<Projected> ankurah_signals::Signal for YrsRichText<Projected> {
+    fn listen(&self, listener: Listener) -> ListenerGuard { self.backend.listen_field(&self.property_name, listener) }
+
+    fn broadcast_id(&self) -> ankurah_signals::broadcast::BroadcastId { self.backend.field_broadcast_id(&self.property_name) }
+}
+
+impl<Projected> ankurah_signals::Subscribe<RichText> for YrsRichText<Projected>
+where Projected: Clone + Send + Sync + 'static
+{
+    fn subscribe<F>(&self, listener: F) -> ankurah_signals::SubscriptionGuard
+    where F: ankurah_signals::subscribe::IntoSubscribeListener<RichText> {
+        let listener = listener.into_subscribe_listener();
+        let rich_text = self.clone();
+        let subscription = self.listen(Arc::new(move |_| {
+            if let Some(current_value) = rich_text.delta() {
+                listener(current_value);
+            }
+        }));
+        ankurah_signals::SubscriptionGuard::new(subscription)
+    }
+}
+
+#[cfg(any(feature = "wasm", feature = "uniffi"))]
+pub mod ffi {
+    //! FFI wrapper types for YrsRichText backend (WASM and UniFFI)
+    use super::*;
+    #[cfg(feature = "wasm")]
+    use ::wasm_bindgen::prelude::*;
+    use ankurah_derive::impl_provided_wrapper_types;
+    impl_provided_wrapper_types!("src/property/value/yrs_rich_text.ron");
+}
+#[cfg(any(feature = "wasm", feature = "uniffi"))]
+pub use ffi::*;
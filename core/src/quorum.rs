@@ -0,0 +1,217 @@
+//! Commit acknowledgement across several durable peers
+//!
+//! A committed transaction is sent to every connected durable peer at once. The node's
+//! [`CommitPolicy`] decides how many must accept it before the commit returns; the remaining
+//! requests keep running in the background. The quorum is sized over the connected durable peers
+//! plus any the node was told to expect (see [`Node::expect_durable_peer`]), so losing an expected
+//! peer cannot shrink it: with too few of them reachable the transaction is reported
+//! [`CommitOutcome::Undelivered`].
+//!
+//! Once every peer has answered, a transaction that some peers accepted and others refused or
+//! never received leaves the durable peers disagreeing. The refusing peers are then reconciled:
+//! they are sent the full history of the transaction's entities as this node knows it, which
+//! supplies whatever lineage they were missing (the usual reason a peer refuses an event another
+//! peer accepted). A peer that refuses the history as well is left divergent and logged.
+
+use futures::stream::{FuturesUnordered, StreamExt};
+use std::collections::BTreeSet;
+use tracing::{debug, warn};
+
+use ankurah_proto::{self as proto, Attested, Event};
+
+use crate::event_dag::ordering::topo_sort_events;
+use crate::node::Node;
+use crate::policy::PolicyAgent;
+use crate::storage::StorageEngine;
+
+/// How many durable peers must accept a transaction before its commit returns
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CommitPolicy {
+    /// The first durable peer to accept it
+    Any,
+    /// More than half of the durable peers in the quorum
+    Majority,
+    /// Every durable peer in the quorum
+    #[default]
+    All,
+}
+
+impl CommitPolicy {
+    /// Number of acknowledgements required out of a quorum of `peers` durable peers
+    pub fn required(&self, peers: usize) -> usize {
+        match self {
+            CommitPolicy::Any => peers.min(1),
+            CommitPolicy::Majority => peers / 2 + 1,
+            CommitPolicy::All => peers,
+        }
+        .min(peers)
+    }
+}
+
+/// Result of relaying one transaction to the connected durable peers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommitOutcome {
+    /// Enough peers accepted it
+    Confirmed,
+    /// Enough peers refused it that the policy can no longer be met
+    Rejected(String),
+    /// Too few peers could be reached; nothing was refused, so it may be relayed again
+    Undelivered(String),
+}
+
+enum Reply {
+    Accepted,
+    Refused(String),
+    Unreachable(String),
+}
+
+/// Send `events` to the connected `peers` in parallel and wait until `policy`, sized over the
+/// node's [quorum](Node::durable_quorum_size), is met or can no longer be
+pub(crate) async fn relay_commit<SE, PA>(
+    node: &Node<SE, PA>,
+    peers: Vec<proto::EntityId>,
+    policy: CommitPolicy,
+    cdata: &PA::ContextData,
+    id: &proto::TransactionId,
+    events: &[Attested<Event>],
+) -> CommitOutcome
+where
+    SE: StorageEngine + Send + Sync + 'static,
+    PA: PolicyAgent + Send + Sync + 'static,
+{
+    let total = peers.len();
+    let required = policy.required(node.durable_quorum_size().max(total));
+    let mut replies: FuturesUnordered<_> = peers
+        .into_iter()
+        .map(|peer_id| {
+            let node = node.clone();
+            let cdata = cdata.clone();
+            let body = proto::NodeRequestBody::CommitTransaction { id: id.clone(), events: events.to_vec() };
+            async move {
+                let reply = match node.request(peer_id, &cdata, body).await {
+                    Ok(proto::NodeResponseBody::CommitComplete { .. }) => Reply::Accepted,
+                    Ok(proto::NodeResponseBody::Error(e)) => Reply::Refused(format!("Peer {} rejected: {}", peer_id, e)),
+                    Ok(_) => Reply::Refused(format!("Peer {} returned unexpected response", peer_id)),
+                    Err(e) => Reply::Unreachable(format!("Peer {} unreachable: {}", peer_id, e)),
+                };
+                (peer_id, reply)
+            }
+        })
+        .collect();
+
+    let mut accepted = BTreeSet::new();
+    let mut disagreeing = BTreeSet::new();
+    let (mut refused, mut unreachable): (Vec<String>, Vec<String>) = (Vec::new(), Vec::new());
+    let outcome = loop {
+        if accepted.len() >= required {
+            break CommitOutcome::Confirmed;
+        }
+        // Every peer still outstanding could accept; stop once even that falls short
+        let answered = accepted.len() + refused.len() + unreachable.len();
+        if accepted.len() + (total - answered) < required {
+            break match refused.first() {
+                Some(reason) => CommitOutcome::Rejected(reason.clone()),
+                None => CommitOutcome::Undelivered(
+                    unreachable.first().cloned().unwrap_or_else(|| format!("{} of {} required durable peers connected", total, required)),
+                ),
+            };
+        }
+        let Some((peer_id, reply)) = replies.next().await else {
+            break CommitOutcome::Undelivered("too few durable peers connected".to_string());
+        };
+        match reply {
+            Reply::Accepted => {
+                accepted.insert(peer_id);
+            }
+            Reply::Refused(reason) => {
+                disagreeing.insert(peer_id);
+                refused.push(reason);
+            }
+            Reply::Unreachable(reason) => {
+                disagreeing.insert(peer_id);
+                unreachable.push(reason);
+            }
+        }
+    };
+
+    // Let the stragglers finish in the background, then reconcile any disagreement
+    let node = node.clone();
+    let cdata = cdata.clone();
+    let id = id.clone();
+    let entities: BTreeSet<(proto::CollectionId, proto::EntityId)> =
+        events.iter().map(|e| (e.payload.collection.clone(), e.payload.entity_id)).collect();
+    crate::task::spawn(async move {
+        while let Some((peer_id, reply)) = replies.next().await {
+            match reply {
+                Reply::Accepted => {
+                    accepted.insert(peer_id);
+                }
+                Reply::Refused(reason) | Reply::Unreachable(reason) => {
+                    debug!("Transaction {} straggler: {}", id, reason);
+                    disagreeing.insert(peer_id);
+                }
+            }
+        }
+        if !accepted.is_empty() && !disagreeing.is_empty() {
+            reconcile(&node, &cdata, &id, &entities, disagreeing).await;
+        }
+    });
+
+    outcome
+}
+
+/// Send the refusing peers the full local history of the transaction's entities
+async fn reconcile<SE, PA>(
+    node: &Node<SE, PA>,
+    cdata: &PA::ContextData,
+    id: &proto::TransactionId,
+    entities: &BTreeSet<(proto::CollectionId, proto::EntityId)>,
+    peers: BTreeSet<proto::EntityId>,
+) where
+    SE: StorageEngine + Send + Sync + 'static,
+    PA: PolicyAgent + Send + Sync + 'static,
+{
+    let mut history = Vec::new();
+    for (collection_id, entity_id) in entities {
+        let events = match node.collections.get(collection_id).await {
+            Ok(collection) => collection.dump_entity_events(*entity_id).await,
+            Err(e) => Err(e),
+        };
+        match events.map_err(|e| e.to_string()).and_then(|events| topo_sort_events(events).map_err(|e| e.to_string())) {
+            Ok(events) => history.extend(events),
+            Err(e) => {
+                warn!("Transaction {}: cannot reconcile durable peers, history of {} unavailable: {}", id, entity_id, e);
+                return;
+            }
+        }
+    }
+
+    for peer_id in peers {
+        if !node.get_durable_peers().contains(&peer_id) {
+            continue;
+        }
+        let body = proto::NodeRequestBody::CommitTransaction { id: proto::TransactionId::new(), events: history.clone() };
+        match node.request(peer_id, cdata, body).await {
+            Ok(proto::NodeResponseBody::CommitComplete { .. }) => debug!("Transaction {}: reconciled peer {}", id, peer_id),
+            Ok(response) => warn!("Transaction {}: peer {} remains divergent: {:?}", id, peer_id, response),
+            Err(e) => warn!("Transaction {}: peer {} remains divergent: {}", id, peer_id, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_required_acknowledgements() {
+        assert_eq!(CommitPolicy::Any.required(0), 0);
+        assert_eq!(CommitPolicy::Any.required(3), 1);
+        assert_eq!(CommitPolicy::Majority.required(0), 0);
+        assert_eq!(CommitPolicy::Majority.required(1), 1);
+        assert_eq!(CommitPolicy::Majority.required(2), 2);
+        assert_eq!(CommitPolicy::Majority.required(3), 2);
+        assert_eq!(CommitPolicy::Majority.required(4), 3);
+        assert_eq!(CommitPolicy::All.required(3), 3);
+    }
+}
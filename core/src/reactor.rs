@@ -355,6 +355,7 @@ impl<E: AbstractEntity + Filterable + Send + 'static, Ev: Clone + Send + 'static
                 watcher_set.accumulate_interested_watchers(change.entity(), offset, &changes, &mut candidates_by_sub);
             }
         }
+        crate::metrics::notification_fanout(candidates_by_sub.len());
 
         // Parallelize evaluate_changes calls across subscriptions
         // First, collect all the evaluation futures while holding the lock
@@ -16,10 +16,14 @@ pub(crate) struct ComparisonIndex<T> {
     pub(crate) ne: HashMap<Vec<u8>, Vec<T>>,
     pub(crate) gt: BTreeMap<Vec<u8>, Vec<T>>,
     pub(crate) lt: BTreeMap<Vec<u8>, Vec<T>>,
+    /// Membership watchers, keyed by the member a collection value must contain
+    pub(crate) contains: HashMap<Vec<u8>, Vec<T>>,
 }
 
 impl<T> Default for ComparisonIndex<T> {
-    fn default() -> Self { Self { eq: HashMap::new(), ne: HashMap::new(), gt: BTreeMap::new(), lt: BTreeMap::new() } }
+    fn default() -> Self {
+        Self { eq: HashMap::new(), ne: HashMap::new(), gt: BTreeMap::new(), lt: BTreeMap::new(), contains: HashMap::new() }
+    }
 }
 
 impl<T: Clone + Eq + Hash + Ord> ComparisonIndex<T> {
@@ -66,6 +70,10 @@ impl<T: Clone + Eq + Hash + Ord> ComparisonIndex<T> {
                     f(entry);
                 }
             }
+            ast::ComparisonOperator::Contains => {
+                let entry = self.contains.entry(value.to_bytes()).or_default();
+                f(entry);
+            }
             _ => panic!("Unsupported operator: {:?}", op),
         }
     }
@@ -114,6 +122,19 @@ impl<T: Clone + Eq + Hash + Ord> ComparisonIndex<T> {
         // Should just return the BTreeSet but this sucks for test cases
         result.into_iter()
     }
+
+    /// Find the membership watchers for any of the members of a collection value
+    pub fn find_containing<V: Collatable>(&self, members: impl IntoIterator<Item = V>) -> std::collections::btree_set::IntoIter<T> {
+        let mut result = BTreeSet::new();
+        if !self.contains.is_empty() {
+            for member in members {
+                if let Some(subs) = self.contains.get(&member.to_bytes()) {
+                    result.extend(subs.iter().cloned());
+                }
+            }
+        }
+        result.into_iter()
+    }
 }
 
 #[cfg(test)]
@@ -177,4 +198,20 @@ mod tests {
         assert_eq!(index.find_matching(Value::I64(8)).collect::<Vec<_>>(), vec![]);
         assert_eq!(index.find_matching(Value::I64(9)).collect::<Vec<_>>(), vec![sub0]);
     }
+
+    #[test]
+    fn test_field_index_contains() {
+        let mut index = ComparisonIndex::<proto::QueryId>::new();
+
+        let sub0 = proto::QueryId::test(0);
+        index.add(Value::String("urgent".into()), ast::ComparisonOperator::Contains, sub0);
+
+        // Membership watchers only match members, never the collection value itself
+        assert_eq!(index.find_matching(Value::String("urgent".into())).collect::<Vec<_>>(), vec![]);
+        assert_eq!(
+            index.find_containing(vec![Value::String("ops".into()), Value::String("urgent".into())]).collect::<Vec<_>>(),
+            vec![sub0]
+        );
+        assert_eq!(index.find_containing(vec![Value::String("ops".into())]).collect::<Vec<_>>(), vec![]);
+    }
 }
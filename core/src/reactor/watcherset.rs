@@ -34,7 +34,8 @@ impl WatcherSet {
             if *collection_id == AbstractEntity::collection(entity) {
                 // Extract value at the property path (handles both simple fields and JSON paths)
                 if let Some(value) = property_path.extract_value(entity) {
-                    for (subscription_id, query_id) in index_ref.find_matching(value) {
+                    let members = value.elements().unwrap_or_default();
+                    for (subscription_id, query_id) in index_ref.find_matching(value).chain(index_ref.find_containing(members)) {
                         candidates_by_sub
                             .entry(subscription_id)
                             .or_insert_with(|| CandidateChanges::new(changes_arc.clone()))
@@ -175,13 +176,20 @@ impl WatcherSet {
                     // accumulate_interested_watchers will extract the value at this path.
                     let property_path = PropertyPath::from_path(path);
                     let index = self.index_watchers.entry((collection_id.clone(), property_path)).or_default();
+                    // Collection members are stored as JSON scalars, so ids are watched by their base64 form
+                    let literal = match (operator, literal) {
+                        (ankql::ast::ComparisonOperator::Contains, crate::value::Value::EntityId(id)) => {
+                            crate::value::Value::String(id.to_base64())
+                        }
+                        _ => literal.clone(),
+                    };
 
                     match op {
                         WatcherOp::Add => {
-                            index.add((*literal).clone(), operator.clone(), watcher_id);
+                            index.add(literal, operator.clone(), watcher_id);
                         }
                         WatcherOp::Remove => {
-                            index.remove((*literal).clone(), operator.clone(), watcher_id);
+                            index.remove(literal, operator.clone(), watcher_id);
                         }
                     }
                 } else {
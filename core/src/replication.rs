@@ -0,0 +1,406 @@
+//! Durable-to-durable replication
+//!
+//! A durable node can replicate from another durable node of the same system, to run as a hot
+//! standby or as a geographically separate server that converges with its source. Replication
+//! is anti-entropy and pull-based: the replica sweeps every collection the source stores in
+//! entity id order, one page of entity heads at a time, and asks for the events behind each head
+//! that differs from its own. The source answers with [`DeltaContent::EventBridge`]s, from the
+//! replica's head when it can bridge from it and from genesis when it cannot (the replica lacks
+//! the entity, or holds events the source does not), which the replica applies and stores like
+//! any other received events. Two nodes that replicate from each other converge.
+//!
+//! Each collection's progress is a cursor persisted as a [node-local record](crate::storage::local)
+//! in the reserved [`REPLICATION_COLLECTION_ID`] collection: the last entity id of the last page
+//! applied, or that the collection has been swept to the end. A replica that restarts mid-sweep
+//! skips the collections it finished and resumes the others after their cursors instead of
+//! starting over; a completed sweep resets every cursor for the next one. Cursors are kept per collection, not per
+//! source, so a replica of several sources resumes its sweep from whichever it pulls next.
+//!
+//! The catalog collections are swept first, and the model rows they bring are folded into the
+//! replica's catalog map, so the collections swept after them resolve against the same models
+//! as on the source. The system collection is not replicated: both nodes must already share the
+//! system root, which the Presence handshake establishes for every node.
+//!
+//! The replica pulls under the credential it is given, and the source serves it through its
+//! ordinary read policy: a credential that cannot read an entity or event leaves it out.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use ankql::ast::{ComparisonOperator, Expr, OrderByItem, OrderDirection, PathExpr, Predicate, Selection};
+use ankurah_proto::{self as proto, Attested, Clock, CollectionId, EntityId, EntityState};
+use tracing::{debug, warn};
+
+use crate::error::{MutationError, RetrievalError};
+use crate::event_dag::ordering::topo_sort_events;
+use crate::node::Node;
+use crate::node_applier::NodeApplier;
+use crate::policy::{AccessDenied, PolicyAgent};
+use crate::retrieval::{LocalEventGetter, LocalStateGetter};
+use crate::storage::{local, StorageCollectionWrapper, StorageEngine};
+use crate::util::Iterable;
+use crate::value::Value;
+
+/// Reserved collection holding a durable node's replication cursors
+pub const REPLICATION_COLLECTION_ID: &str = "_ankurah_replication";
+
+/// How many entity heads a replica asks for per page
+pub(crate) const PAGE_SIZE: u64 = 256;
+
+/// What one replication pass compared and pulled
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReplicationReport {
+    /// Collections swept to the end
+    pub collections: usize,
+    /// Entity heads compared against the replica's
+    pub compared: usize,
+    /// Entities whose events were pulled because their heads differed
+    pub pulled: usize,
+}
+
+/// One persisted replication cursor
+#[derive(Debug, Clone, PartialEq)]
+struct Cursor {
+    id: EntityId,
+    collection: CollectionId,
+    after: Option<EntityId>,
+    /// Swept to the end in the current sweep
+    done: bool,
+}
+
+impl Cursor {
+    fn to_state(&self) -> Result<Attested<EntityState>, MutationError> {
+        local::record_state(
+            self.id,
+            &CollectionId::fixed_name(REPLICATION_COLLECTION_ID),
+            [
+                ("collection", Some(Value::String(self.collection.to_string()))),
+                ("after", self.after.map(Value::EntityId)),
+                ("done", Some(Value::Bool(self.done))),
+            ],
+        )
+    }
+
+    fn from_state(state: &Attested<EntityState>) -> Result<Self, RetrievalError> {
+        let values = local::record_values(state)?;
+        let collection = match values.get("collection").cloned().flatten() {
+            Some(Value::String(collection)) => CollectionId::from(collection.as_str()),
+            _ => return Err(RetrievalError::Other(format!("malformed replication cursor {}: collection", state.payload.entity_id))),
+        };
+        let after = match values.get("after").cloned().flatten() {
+            Some(Value::EntityId(after)) => Some(after),
+            _ => None,
+        };
+        let done = matches!(values.get("done").cloned().flatten(), Some(Value::Bool(true)));
+        Ok(Cursor { id: state.payload.entity_id, collection, after, done })
+    }
+}
+
+/// The replication cursors of a node, by collection
+struct Cursors {
+    storage: StorageCollectionWrapper,
+    cursors: BTreeMap<CollectionId, Cursor>,
+}
+
+impl Cursors {
+    async fn load<SE, PA>(node: &Node<SE, PA>) -> Result<Self, RetrievalError>
+    where
+        SE: StorageEngine + Send + Sync + 'static,
+        PA: PolicyAgent + Send + Sync + 'static,
+    {
+        let storage = node.collections.get(&CollectionId::fixed_name(REPLICATION_COLLECTION_ID)).await?;
+        let all = Selection { predicate: Predicate::True, order_by: None, limit: None };
+        let mut cursors = BTreeMap::new();
+        for state in storage.fetch_states(&all).await? {
+            match Cursor::from_state(&state) {
+                Ok(cursor) => {
+                    cursors.insert(cursor.collection.clone(), cursor);
+                }
+                Err(e) => warn!("Skipping replication cursor: {}", e),
+            }
+        }
+        Ok(Self { storage, cursors })
+    }
+
+    fn get(&self, collection: &CollectionId) -> (Option<EntityId>, bool) {
+        self.cursors.get(collection).map_or((None, false), |cursor| (cursor.after, cursor.done))
+    }
+
+    async fn set(&mut self, collection: &CollectionId, after: Option<EntityId>, done: bool) -> Result<(), MutationError> {
+        if self.get(collection) == (after, done) {
+            return Ok(());
+        }
+        let cursor = self.cursors.entry(collection.clone()).or_insert_with(|| Cursor {
+            id: EntityId::random(),
+            collection: collection.clone(),
+            after: None,
+            done: false,
+        });
+        cursor.after = after;
+        cursor.done = done;
+        self.storage.set_state(cursor.to_state()?).await?;
+        Ok(())
+    }
+}
+
+/// The collections a replica sweeps, in sweep order: the catalog first, so that the models of
+/// the collections after it resolve, then every other replicated collection by name
+fn sweep_order(collections: Vec<CollectionId>) -> Vec<CollectionId> {
+    let catalog: Vec<CollectionId> =
+        [crate::schema::MODEL_COLLECTION_ID, crate::schema::PROPERTY_COLLECTION_ID, crate::schema::MODEL_PROPERTY_COLLECTION_ID]
+            .into_iter()
+            .map(CollectionId::fixed_name)
+            .collect();
+    let mut rest: Vec<CollectionId> = collections
+        .into_iter()
+        .filter(|collection| {
+            !catalog.contains(collection) && !local::is_node_local(collection) && collection.as_str() != crate::system::SYSTEM_COLLECTION_ID
+        })
+        .collect();
+    rest.sort();
+    rest.dedup();
+    catalog.into_iter().chain(rest).collect()
+}
+
+/// Source side: the page of a collection's entities after `after`, in entity id order
+pub(crate) fn heads_selection(after: Option<EntityId>, limit: u64) -> Selection {
+    let predicate = match after {
+        Some(after) => Predicate::Comparison {
+            left: Box::new(Expr::Path(PathExpr::simple("id"))),
+            operator: ComparisonOperator::GreaterThan,
+            right: Box::new(Expr::from(after)),
+        },
+        None => Predicate::True,
+    };
+    let order_by = vec![OrderByItem { path: PathExpr::simple("id"), direction: OrderDirection::Asc }];
+    Selection { predicate, order_by: Some(order_by), limit: Some(limit.max(1)) }
+}
+
+/// Source side: the events that take a replica at `known` to the head of `state`, or `None` if
+/// the replica is current or the credential may not read every event it would need
+pub(crate) async fn replication_delta<SE, PA, C>(
+    node: &Node<SE, PA>,
+    storage: &StorageCollectionWrapper,
+    known: &Clock,
+    state: Attested<EntityState>,
+    cdata: &C,
+) -> anyhow::Result<Option<proto::EntityDelta>>
+where
+    SE: StorageEngine + Send + Sync + 'static,
+    PA: PolicyAgent + Send + Sync + 'static,
+    C: Iterable<PA::ContextData>,
+{
+    let EntityState { entity_id, collection, state } = state.payload;
+    if *known == state.head {
+        return Ok(None);
+    }
+
+    // A bridge from the replica's head when this node has every event of it and descends from
+    // it; otherwise the whole history, of which the replica applies what it lacks
+    let bridgeable = !known.is_empty() && storage.get_events(known.as_slice().to_vec()).await?.len() == known.len();
+    let bridge = if bridgeable { node.collect_event_bridge(storage, known, &state.head, cdata).await? } else { Vec::new() };
+    let events = if !bridge.is_empty() {
+        bridge
+    } else {
+        let events = storage.dump_entity_events(entity_id).await?;
+        for event in &events {
+            match node.policy_agent.check_read_event(cdata, event) {
+                Ok(()) => {}
+                Err(AccessDenied::ByPolicy(_)) => return Ok(None),
+                Err(e) => return Err(anyhow::anyhow!("check_read_event failed while building replication history: {}", e)),
+            }
+        }
+        topo_sort_events(events)?
+    };
+    if events.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(proto::EntityDelta {
+        entity_id,
+        collection,
+        content: proto::DeltaContent::EventBridge { events: events.into_iter().map(|event| event.into()).collect() },
+    }))
+}
+
+/// Replica side: sweep every collection `peer_id` stores, pulling what this node lacks, `page_size`
+/// entities at a time. With `max_pages`, stop after that many pages as if interrupted.
+pub(crate) async fn replicate<SE, PA>(
+    node: &Node<SE, PA>,
+    peer_id: EntityId,
+    cdata: &PA::ContextData,
+    page_size: u64,
+    mut max_pages: Option<usize>,
+) -> Result<ReplicationReport, RetrievalError>
+where
+    SE: StorageEngine + Send + Sync + 'static,
+    PA: PolicyAgent + Send + Sync + 'static,
+{
+    if !node.durable {
+        return Err(RetrievalError::Other("only durable nodes replicate".to_string()));
+    }
+    let collections = match node.request(peer_id, cdata, proto::NodeRequestBody::ListCollections).await? {
+        proto::NodeResponseBody::Collections(collections) => collections,
+        proto::NodeResponseBody::Error(e) => {
+            return Err(RetrievalError::Other(format!("Peer {} refused to list collections: {}", peer_id, e)))
+        }
+        _ => return Err(RetrievalError::Other("Unexpected response type".to_string())),
+    };
+
+    let mut cursors = Cursors::load(node).await?;
+    let mut report = ReplicationReport::default();
+    let collections = sweep_order(collections);
+    for collection_id in &collections {
+        let (mut after, done) = cursors.get(collection_id);
+        if done {
+            continue;
+        }
+        loop {
+            if max_pages == Some(0) {
+                return Ok(report);
+            }
+            max_pages = max_pages.map(|pages| pages - 1);
+            let next = replicate_page(node, peer_id, cdata, collection_id, after, page_size, &mut report).await?;
+            // The page is applied: a restart resumes after it
+            cursors.set(collection_id, next, next.is_none()).await?;
+            match next {
+                Some(next) => after = Some(next),
+                None => break,
+            }
+        }
+        report.collections += 1;
+    }
+
+    // The sweep is complete; the next one starts over
+    let swept: Vec<CollectionId> = cursors.cursors.keys().cloned().collect();
+    for collection_id in &swept {
+        cursors.set(collection_id, None, false).await?;
+    }
+    Ok(report)
+}
+
+/// Compare and pull one page of entities after `after`; returns where the next page starts
+async fn replicate_page<SE, PA>(
+    node: &Node<SE, PA>,
+    peer_id: EntityId,
+    cdata: &PA::ContextData,
+    collection_id: &CollectionId,
+    after: Option<EntityId>,
+    page_size: u64,
+    report: &mut ReplicationReport,
+) -> Result<Option<EntityId>, RetrievalError>
+where
+    SE: StorageEngine + Send + Sync + 'static,
+    PA: PolicyAgent + Send + Sync + 'static,
+{
+    let collection = node.collections.get(collection_id).await?;
+    let request = proto::NodeRequestBody::CollectionHeads { collection: collection_id.clone(), after, limit: page_size };
+    let (heads, next) = match node.request(peer_id, cdata, request).await? {
+        proto::NodeResponseBody::CollectionHeads { heads, next } => (heads, next),
+        proto::NodeResponseBody::Error(e) => {
+            return Err(RetrievalError::Other(format!("Peer {} refused heads of {}: {}", peer_id, collection_id, e)))
+        }
+        _ => return Err(RetrievalError::Other("Unexpected response type".to_string())),
+    };
+    report.compared += heads.len();
+
+    let local: HashMap<EntityId, Clock> = collection
+        .get_states(heads.iter().map(|head| head.entity_id).collect())
+        .await?
+        .into_iter()
+        .map(|state| (state.payload.entity_id, state.payload.state.head))
+        .collect();
+    let known: Vec<proto::KnownEntity> = heads
+        .into_iter()
+        .filter(|head| local.get(&head.entity_id) != Some(&head.head))
+        .map(|head| proto::KnownEntity { entity_id: head.entity_id, head: local.get(&head.entity_id).cloned().unwrap_or_default() })
+        .collect();
+    if known.is_empty() {
+        return Ok(next);
+    }
+
+    let ids: Vec<EntityId> = known.iter().map(|known| known.entity_id).collect();
+    let request = proto::NodeRequestBody::Replicate { collection: collection_id.clone(), known };
+    let deltas = match node.request(peer_id, cdata, request).await? {
+        proto::NodeResponseBody::Replicated(deltas) => deltas,
+        proto::NodeResponseBody::Error(e) => {
+            return Err(RetrievalError::Other(format!("Peer {} refused to replicate {}: {}", peer_id, collection_id, e)))
+        }
+        _ => return Err(RetrievalError::Other("Unexpected response type".to_string())),
+    };
+    report.pulled += deltas.len();
+    debug!("Replicating {} entities of {} from {}", deltas.len(), collection_id, peer_id);
+
+    let event_getter = LocalEventGetter::new(collection.clone(), true);
+    let state_getter = LocalStateGetter::new(collection.clone());
+    NodeApplier::apply_deltas(node, &peer_id, deltas, &event_getter, &state_getter).await?;
+    node.catalog.fold_replicated(collection_id, &collection.get_states(ids).await?).await;
+    Ok(next)
+}
+
+/// Replicates from one durable peer on an interval until dropped. See [`Node::spawn_replication`].
+pub struct ReplicationHandle {
+    stop: Arc<AtomicBool>,
+}
+
+impl ReplicationHandle {
+    pub(crate) fn spawn<SE, PA>(node: &Node<SE, PA>, peer_id: EntityId, cdata: PA::ContextData, interval: Duration) -> Self
+    where
+        SE: StorageEngine + Send + Sync + 'static,
+        PA: PolicyAgent + Send + Sync + 'static,
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let weak = node.weak();
+        let stopped = stop.clone();
+        crate::task::spawn(async move {
+            loop {
+                let Some(node) = weak.upgrade() else { break };
+                if stopped.load(Ordering::Acquire) || !node.get_durable_peers().contains(&peer_id) {
+                    break;
+                }
+                match replicate(&node, peer_id, &cdata, PAGE_SIZE, None).await {
+                    Ok(report) => debug!("Replicated from {}: {:?}", peer_id, report),
+                    Err(e) => warn!("Replication from {} failed: {}", peer_id, e),
+                }
+                drop(node);
+                futures_timer::Delay::new(interval).await;
+            }
+            debug!("Replication from {} stopped", peer_id);
+        });
+        Self { stop }
+    }
+}
+
+impl Drop for ReplicationHandle {
+    fn drop(&mut self) { self.stop.store(true, Ordering::Release); }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = Cursor { id: EntityId::random(), collection: "albums".into(), after: Some(EntityId::random()), done: false };
+        assert_eq!(Cursor::from_state(&cursor.to_state().unwrap()).unwrap(), cursor);
+
+        let reset = Cursor { after: None, done: true, ..cursor };
+        assert_eq!(Cursor::from_state(&reset.to_state().unwrap()).unwrap(), reset);
+    }
+
+    #[test]
+    fn test_sweep_order() {
+        let collections = vec![
+            "tracks".into(),
+            CollectionId::fixed_name(crate::outbox::OUTBOX_COLLECTION_ID),
+            CollectionId::fixed_name(crate::system::SYSTEM_COLLECTION_ID),
+            "albums".into(),
+            CollectionId::fixed_name(crate::schema::MODEL_COLLECTION_ID),
+            CollectionId::fixed_name(REPLICATION_COLLECTION_ID),
+        ];
+        let order: Vec<String> = sweep_order(collections).iter().map(|c| c.to_string()).collect();
+        assert_eq!(order, vec!["_ankurah_model", "_ankurah_property", "_ankurah_model_property", "albums", "tracks"]);
+    }
+}
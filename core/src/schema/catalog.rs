@@ -12,8 +12,9 @@
 //!   (`fetch_states` with `Predicate::True`, the same move
 //!   `SystemManager::load_system_catalog` makes for the system collection).
 //!   Afterward the registration executor folds its own commits into the map
-//!   synchronously under the allocator mutex; no other catalog writer
-//!   exists this phase. The POLICY-FREE reactor subscription that keeps the
+//!   synchronously under the allocator mutex; the only other catalog
+//!   writer, durable-to-durable replication, folds the rows it pulls from
+//!   its source the same way. The POLICY-FREE reactor subscription that keeps the
 //!   map fresh under the read flip returns with that PR (the map is node
 //!   infrastructure like `SystemManager`, which reads storage with no
 //!   policy; mutation stays gated by `check_event` in the executor).
@@ -330,6 +331,24 @@ where
         Ok(())
     }
 
+    /// Fold catalog states that durable-to-durable replication stored into
+    /// the map. Replication is the one catalog writer besides the
+    /// registration executor: a replica pulls the catalog rows its source
+    /// allocated, and must resolve them as if it had allocated them itself.
+    /// Serialized with registration on the allocator mutex.
+    pub(crate) async fn fold_replicated(&self, collection: &proto::CollectionId, states: &[proto::Attested<proto::EntityState>]) {
+        let Some(model) = crate::schema::system_model_id(collection.as_str()).filter(crate::schema::is_catalog_collection) else {
+            return;
+        };
+        let _allocator = self.0.allocator.lock().await;
+        let mut map = self.0.map.write().unwrap();
+        for state in states {
+            if let Some(entry) = parse_state(&model, state.payload.entity_id, &state.payload) {
+                apply_entry(&mut map, entry);
+            }
+        }
+    }
+
     // -- readiness ----------------------------------------------------------
 
     /// The owning node, while it lives: present from `start` (called in
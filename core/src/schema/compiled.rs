@@ -66,18 +66,22 @@ pub struct StructProperty {
     /// lookup-or-create, guarded; the attribute is removable once every
     /// target system has seen it.
     pub renamed_from: Option<&'static str>,
-    /// Backend registry name, "yrs" or "lww", per the active type the
-    /// backend registry resolved for this field.
+    /// Backend registry name ("yrs", "lww", or one registered at runtime),
+    /// per the active type the backend registry resolved for this field.
     pub backend: &'static str,
     /// Language-agnostic value type (a lowercased `core::value::ValueType`
     /// variant, e.g. "string", "i64", "entityid"), taken from the field's
     /// ORIGINAL Rust type before active-type wrapping.
     pub value_type: &'static str,
-    /// The referenced model's source label for `Ref<T>` / `Option<Ref<T>>`.
+    /// The referenced model's source label for `Ref<T>` / `Option<Ref<T>>` /
+    /// `Vec<Ref<T>>`.
     /// Registration resolves this label to the catalog model id stored as
     /// `target_model`; non-reference fields carry `None`. The field name is
     /// retained for source/API compatibility.
     pub target_label: Option<&'static str>,
+    /// `true` for a `Vec<Ref<T>>` reference set, whose entity may point at
+    /// many targets; `false` for single references and non-reference fields.
+    pub reference_set: bool,
     /// `true` for `Option<T>` fields. Feeds the MEMBERSHIP record's
     /// `optional`, NOT the property identity (flipping optionality must not
     /// re-key).
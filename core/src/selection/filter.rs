@@ -184,6 +184,14 @@ pub fn evaluate_predicate<I: Filterable>(item: &I, predicate: &Predicate) -> Res
                     let list = right_val.as_list().ok_or_else(|| Error::PropertyNotFound("Expected list for IN right operand".into()))?;
                     list.iter().any(|item| item.as_value().map(|v| compare_values_with_cast(value, v, |a, b| a == b)).unwrap_or(false))
                 }
+                ComparisonOperator::Contains => {
+                    let value = right_val
+                        .as_value()
+                        .ok_or_else(|| Error::PropertyNotFound("Expected single value for CONTAINS right operand".into()))?;
+                    // Anything that is not a collection contains nothing
+                    let members = left_val.as_value().and_then(Value::elements).unwrap_or_default();
+                    members.iter().any(|member| compare_values_with_cast(member, value, |a, b| a == b))
+                }
                 ComparisonOperator::Between => return Err(Error::UnsupportedOperator("BETWEEN operator not yet supported")),
             })
         }
@@ -408,6 +416,42 @@ mod tests {
         assert_eq!(evaluate_predicate(&row, &selection.predicate), Ok(true), "the row's own id, as a string, must still match");
     }
 
+    /// A row whose `assignees` is a set of references, held as a JSON array of ids
+    struct AssignedItem {
+        assignees: Vec<ankurah_proto::EntityId>,
+    }
+
+    impl Filterable for AssignedItem {
+        fn collection(&self) -> &str { "tasks" }
+
+        fn value(&self, name: &str) -> Option<Value> {
+            match name {
+                "assignees" => Some(Value::Json(self.assignees.iter().map(|id| id.to_base64()).collect())),
+                "title" => Some(Value::String("chores".to_owned())),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn test_contains_tests_membership() {
+        let (alice, bob) = (ankurah_proto::EntityId::random(), ankurah_proto::EntityId::random());
+        let row = AssignedItem { assignees: vec![alice] };
+        let contains = |id: ankurah_proto::EntityId| Predicate::Comparison {
+            left: Box::new(Expr::Path(ankql::ast::PathExpr::simple("assignees"))),
+            operator: ComparisonOperator::Contains,
+            right: Box::new(Expr::Literal(Value::EntityId(id))),
+        };
+        assert_eq!(evaluate_predicate(&row, &contains(alice)), Ok(true));
+        assert_eq!(evaluate_predicate(&row, &contains(bob)), Ok(false));
+
+        let selection = parse_selection(&format!("'{}' IN assignees", alice.to_base64())).unwrap();
+        assert_eq!(evaluate_predicate(&row, &selection.predicate), Ok(true));
+        // A scalar is not a collection
+        let selection = parse_selection("title CONTAINS 'chores'").unwrap();
+        assert_eq!(evaluate_predicate(&row, &selection.predicate), Ok(false));
+    }
+
     // JSON path traversal tests
     mod json_tests {
         use super::*;
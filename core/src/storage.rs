@@ -1,14 +1,19 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use async_trait::async_trait;
-use futures::Stream;
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
 use tracing::warn;
 
 use crate::error::{MutationError, RetrievalError};
 use ankurah_proto::{Attested, CollectionId, EntityId, EntityState, Event, EventId};
 
+pub mod local;
+pub mod verify;
+pub use verify::{Inconsistency, VerifyOptions, VerifyReport};
+
 /// One raw logical record emitted by a storage dump.
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub enum StorageDumpItem {
     Event(Attested<Event>),
     State(Attested<EntityState>),
@@ -26,6 +31,55 @@ pub trait StorageDump: StorageEngine {
     async fn dump(&self) -> Result<Self::DumpStream, RetrievalError>;
 }
 
+/// Record counts written by [`restore_dump`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RestoreSummary {
+    pub events: usize,
+    pub states: usize,
+}
+
+/// Replay a logical dump into `engine`, which need not be the engine kind
+/// that produced it.
+///
+/// Items are written in stream order. [`StorageDump`] emits every event
+/// before any state, so an engine that materializes or indexes states never
+/// sees a head whose events are missing.
+pub async fn restore_dump<SE, S>(engine: &SE, items: S) -> Result<RestoreSummary, MutationError>
+where
+    SE: StorageEngine + ?Sized,
+    S: Stream<Item = Result<StorageDumpItem, RetrievalError>>,
+{
+    let mut collections: HashMap<CollectionId, Arc<dyn StorageCollection>> = HashMap::new();
+    let mut summary = RestoreSummary::default();
+    futures::pin_mut!(items);
+    while let Some(item) = items.next().await {
+        let item = item?;
+        let collection_id = match &item {
+            StorageDumpItem::Event(event) => &event.payload.collection,
+            StorageDumpItem::State(state) => &state.payload.collection,
+        };
+        let collection = match collections.get(collection_id) {
+            Some(collection) => collection.clone(),
+            None => {
+                let collection = engine.collection(collection_id).await?;
+                collections.insert(collection_id.clone(), collection.clone());
+                collection
+            }
+        };
+        match item {
+            StorageDumpItem::Event(event) => {
+                collection.add_event(&event).await?;
+                summary.events += 1;
+            }
+            StorageDumpItem::State(state) => {
+                collection.set_state(state).await?;
+                summary.states += 1;
+            }
+        }
+    }
+    Ok(summary)
+}
+
 pub fn state_name(name: &str) -> String { format!("{}_state", name) }
 
 pub fn event_name(name: &str) -> String { format!("{}_event", name) }
@@ -37,6 +91,11 @@ pub trait StorageEngine: Send + Sync {
     async fn collection(&self, id: &CollectionId) -> Result<Arc<dyn StorageCollection>, RetrievalError>;
     // Delete all collections and their data from the storage engine
     async fn delete_all_collections(&self) -> Result<bool, MutationError>;
+    /// The collections this engine holds. A durable node serves the list to the durable peers
+    /// replicating from it; engines that cannot enumerate their collections refuse.
+    async fn list_collections(&self) -> Result<Vec<CollectionId>, RetrievalError> {
+        Err(RetrievalError::Other("this storage engine cannot list its collections".to_string()))
+    }
 }
 
 #[async_trait]
@@ -75,6 +134,14 @@ pub trait StorageCollection: Send + Sync {
 
     /// Retrieve all events from the collection
     async fn dump_entity_events(&self, id: EntityId) -> Result<Vec<Attested<Event>>, RetrievalError>;
+
+    /// Check the collection's stored events and states for internal
+    /// consistency, optionally repairing what can be derived again from the
+    /// event log. See [`verify::verify_collection`] for the portable checks;
+    /// engines override this to also cover their own derived structures.
+    async fn verify(&self, options: VerifyOptions) -> Result<VerifyReport, RetrievalError> {
+        verify::verify_collection(self, options).await
+    }
 }
 
 /// Manages the storage and state of the collection without any knowledge of the model type
@@ -0,0 +1,50 @@
+//! Node-local bookkeeping records
+//!
+//! Some node machinery keeps its own records in reserved collections of the node's storage
+//! engine: the outbox of an ephemeral node and the replication cursors of a durable one. These
+//! records describe the node that wrote them, not the system. They are stored as plain LWW
+//! states with no event log behind them, and are never relayed or replicated to other nodes.
+
+use std::collections::BTreeMap;
+
+use ankurah_proto::{self as proto, AttestationSet, Attested, Clock, CollectionId, EntityId, EntityState, EventId, State};
+
+use crate::error::{MutationError, RetrievalError};
+use crate::property::backend::{LWWBackend, PropertyBackend};
+use crate::value::Value;
+
+/// Node-local records are not event-sourced; their LWW values carry this placeholder provenance
+const UNSOURCED_EVENT_ID: [u8; 32] = [0; 32];
+
+/// Whether `collection` holds node-local records rather than replicated entities
+pub fn is_node_local(collection: &CollectionId) -> bool {
+    matches!(collection.as_str(), crate::outbox::OUTBOX_COLLECTION_ID | crate::replication::REPLICATION_COLLECTION_ID)
+}
+
+/// Encode a record's values as the state stored under `id`
+pub(crate) fn record_state(
+    id: EntityId,
+    collection: &CollectionId,
+    values: impl IntoIterator<Item = (&'static str, Option<Value>)>,
+) -> Result<Attested<EntityState>, MutationError> {
+    let backend = LWWBackend::new();
+    for (name, value) in values {
+        backend.set(name.into(), value);
+    }
+    if let Some(operations) = backend.to_operations()? {
+        backend.apply_operations_with_event(&operations, EventId::from_bytes(UNSOURCED_EVENT_ID))?;
+    }
+
+    let mut state_buffers = BTreeMap::new();
+    state_buffers.insert(LWWBackend::property_backend_name().to_string(), backend.to_state_buffer()?);
+    let state = State { state_buffers: proto::StateBuffers(state_buffers), memberships: Default::default(), head: Clock::default() };
+    Ok(Attested { payload: EntityState { entity_id: id, collection: collection.clone(), state }, attestations: AttestationSet::default() })
+}
+
+/// Decode the values of a record stored by [`record_state`]
+pub(crate) fn record_values(state: &Attested<EntityState>) -> Result<BTreeMap<String, Option<Value>>, RetrievalError> {
+    let buffer = state.payload.state.state_buffers.get(LWWBackend::property_backend_name()).ok_or_else(|| {
+        RetrievalError::Other(format!("malformed {} record {}: no LWW state", state.payload.collection, state.payload.entity_id))
+    })?;
+    Ok(LWWBackend::from_state_buffer(buffer)?.property_values())
+}
@@ -0,0 +1,233 @@
+//! Integrity verification for a collection's stored events and states.
+//!
+//! The checks only hold for storage that is definitive for its collection,
+//! i.e. a durable node's. An ephemeral node legitimately holds states that
+//! arrived as snapshots without the events behind them.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use ankurah_proto::{Attested, EntityId, EntityState, Event, EventId};
+use async_trait::async_trait;
+
+use super::StorageCollection;
+use crate::{entity::Entity, error::RetrievalError, event_dag::ordering::topo_sort_events, retrieval::GetEvents};
+
+/// What [`StorageCollection::verify`] should do besides reporting.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VerifyOptions {
+    /// Rewrite whatever can be derived again from the event log: states
+    /// that disagree with a replay of their history, and engine-maintained
+    /// structures such as materializations and indexes.
+    pub repair: bool,
+}
+
+/// One inconsistency found by [`StorageCollection::verify`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Inconsistency {
+    /// An event is not retrievable under the id its body hashes to.
+    /// `stored` is the key it was found under, when the engine can tell.
+    EventIdMismatch { entity_id: EntityId, computed: EventId, stored: Option<EventId> },
+    /// An event whose shape contradicts itself (see `Event::validate_structure`).
+    MalformedEvent { entity_id: EntityId, event: EventId, reason: String },
+    /// An event names a parent that is not stored.
+    MissingParent { entity_id: EntityId, event: EventId, parent: EventId },
+    /// A state head names an event that is not stored.
+    MissingHead { entity_id: EntityId, event: EventId },
+    /// A state head's ancestry does not reach the entity's own genesis.
+    UnreachableHead { entity_id: EntityId },
+    /// Replaying the head's history could not complete.
+    ReplayFailed { entity_id: EntityId, reason: String },
+    /// Replaying the head's history produced a different state.
+    StateMismatch { entity_id: EntityId },
+    /// An engine-maintained structure (materialization, index) disagrees
+    /// with the stored state.
+    Derived { entity_id: EntityId, structure: String, detail: String },
+}
+
+impl Inconsistency {
+    pub fn entity_id(&self) -> EntityId {
+        match self {
+            Inconsistency::EventIdMismatch { entity_id, .. }
+            | Inconsistency::MalformedEvent { entity_id, .. }
+            | Inconsistency::MissingParent { entity_id, .. }
+            | Inconsistency::MissingHead { entity_id, .. }
+            | Inconsistency::UnreachableHead { entity_id }
+            | Inconsistency::ReplayFailed { entity_id, .. }
+            | Inconsistency::StateMismatch { entity_id }
+            | Inconsistency::Derived { entity_id, .. } => *entity_id,
+        }
+    }
+}
+
+impl std::fmt::Display for Inconsistency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Inconsistency::EventIdMismatch { entity_id, computed, stored: Some(stored) } => {
+                write!(f, "{entity_id}: event {computed} is stored under {stored}")
+            }
+            Inconsistency::EventIdMismatch { entity_id, computed, stored: None } => {
+                write!(f, "{entity_id}: event {computed} is not retrievable by its id")
+            }
+            Inconsistency::MalformedEvent { entity_id, event, reason } => write!(f, "{entity_id}: event {event} is malformed: {reason}"),
+            Inconsistency::MissingParent { entity_id, event, parent } => {
+                write!(f, "{entity_id}: event {event} parent {parent} is not stored")
+            }
+            Inconsistency::MissingHead { entity_id, event } => write!(f, "{entity_id}: head event {event} is not stored"),
+            Inconsistency::UnreachableHead { entity_id } => write!(f, "{entity_id}: head does not descend from the entity's genesis"),
+            Inconsistency::ReplayFailed { entity_id, reason } => write!(f, "{entity_id}: replay failed: {reason}"),
+            Inconsistency::StateMismatch { entity_id } => write!(f, "{entity_id}: stored state differs from a replay of its events"),
+            Inconsistency::Derived { entity_id, structure, detail } => write!(f, "{entity_id}: {structure}: {detail}"),
+        }
+    }
+}
+
+/// The outcome of [`StorageCollection::verify`].
+#[derive(Debug, Default)]
+pub struct VerifyReport {
+    pub events: usize,
+    pub states: usize,
+    pub problems: Vec<Inconsistency>,
+    /// How many of `problems` a repair resolved.
+    pub repaired: usize,
+}
+
+impl VerifyReport {
+    pub fn is_clean(&self) -> bool { self.problems.is_empty() }
+}
+
+/// The portable check behind the default [`StorageCollection::verify`]:
+/// every stored state, the events of its entity, and a replay of the head.
+///
+/// Events are enumerated per entity through `dump_entity_events`, so events
+/// of an entity with no stored state are not visited. Engines that can scan
+/// their event log directly override `verify` to cover those too.
+pub async fn verify_collection<C>(collection: &C, options: VerifyOptions) -> Result<VerifyReport, RetrievalError>
+where C: StorageCollection + ?Sized {
+    let all = ankql::ast::Selection { predicate: ankql::ast::Predicate::True, order_by: None, limit: None };
+    let mut report = VerifyReport::default();
+    for state in collection.fetch_states(&all).await? {
+        let entity_id = state.payload.entity_id;
+        let events = collection.dump_entity_events(entity_id).await?;
+        let ids: Vec<EventId> = events.iter().map(|e| e.payload.id()).collect();
+        let found: HashSet<EventId> = collection.get_events(ids.clone()).await?.into_iter().map(|e| e.payload.id()).collect();
+        for computed in ids.into_iter().filter(|id| !found.contains(id)) {
+            report.problems.push(Inconsistency::EventIdMismatch { entity_id, computed, stored: None });
+        }
+
+        report.events += events.len();
+        report.states += 1;
+        let checked = check_entity(&state, events).await;
+        report.problems.extend(checked.problems);
+        if let (true, Some(replayed)) = (options.repair, checked.replayed) {
+            collection.set_state(replayed).await?;
+            report.repaired += 1;
+        }
+    }
+    Ok(report)
+}
+
+/// The engine-independent findings for one entity.
+pub struct EntityCheck {
+    pub problems: Vec<Inconsistency>,
+    /// The replayed state, present only when it differs from the stored one.
+    pub replayed: Option<Attested<EntityState>>,
+}
+
+/// Check one stored state against the full set of its entity's events:
+/// well-formed events, complete parent clocks, a head that is stored and
+/// descends from the genesis, and a replay of the head's history that
+/// reproduces the stored state buffers, memberships and head.
+pub async fn check_entity(state: &Attested<EntityState>, events: Vec<Attested<Event>>) -> EntityCheck {
+    let EntityState { entity_id, collection, state: stored } = &state.payload;
+    let entity_id = *entity_id;
+    let mut problems = Vec::new();
+    let by_id: BTreeMap<EventId, Attested<Event>> = events.into_iter().map(|e| (e.payload.id(), e)).collect();
+
+    for (id, event) in &by_id {
+        if let Err(e) = event.payload.validate_structure() {
+            problems.push(Inconsistency::MalformedEvent { entity_id, event: id.clone(), reason: e.to_string() });
+        }
+        for parent in event.payload.parent.iter() {
+            if !by_id.contains_key(parent) {
+                problems.push(Inconsistency::MissingParent { entity_id, event: id.clone(), parent: parent.clone() });
+            }
+        }
+    }
+
+    // Walk the head's ancestry; only a complete one can be replayed.
+    let mut ancestry = BTreeSet::new();
+    let mut frontier: Vec<EventId> = stored.head.iter().cloned().collect();
+    let mut complete = true;
+    let mut rooted = false;
+    while let Some(id) = frontier.pop() {
+        if !ancestry.insert(id.clone()) {
+            continue;
+        }
+        match by_id.get(&id) {
+            Some(event) => {
+                if event.payload.is_entity_create() && EntityId::from(id.clone()) == entity_id {
+                    rooted = true;
+                }
+                frontier.extend(event.payload.parent.iter().cloned());
+            }
+            None => {
+                complete = false;
+                if stored.head.as_slice().contains(&id) {
+                    problems.push(Inconsistency::MissingHead { entity_id, event: id });
+                }
+            }
+        }
+    }
+    if complete && !rooted {
+        problems.push(Inconsistency::UnreachableHead { entity_id });
+    }
+    if !complete || !rooted {
+        return EntityCheck { problems, replayed: None };
+    }
+
+    let history: Vec<Attested<Event>> = ancestry.iter().filter_map(|id| by_id.get(id).cloned()).collect();
+    let getter = EventMap(by_id.into_iter().map(|(id, e)| (id, e.payload)).collect());
+    match replay(entity_id, collection, history, &getter).await {
+        Ok(replayed) => {
+            if replayed.state_buffers == stored.state_buffers && replayed.memberships == stored.memberships && replayed.head == stored.head
+            {
+                EntityCheck { problems, replayed: None }
+            } else {
+                problems.push(Inconsistency::StateMismatch { entity_id });
+                let payload = EntityState { entity_id, collection: collection.clone(), state: replayed };
+                EntityCheck { problems, replayed: Some(Attested { payload, attestations: state.attestations.clone() }) }
+            }
+        }
+        Err(e) => {
+            problems.push(Inconsistency::ReplayFailed { entity_id, reason: e.to_string() });
+            EntityCheck { problems, replayed: None }
+        }
+    }
+}
+
+async fn replay(
+    entity_id: EntityId,
+    collection: &ankurah_proto::CollectionId,
+    history: Vec<Attested<Event>>,
+    getter: &EventMap,
+) -> Result<ankurah_proto::State, RetrievalError> {
+    let entity = Entity::create(entity_id, collection.clone());
+    for event in topo_sort_events(history)? {
+        entity.apply_event(getter, &event.payload).await?;
+    }
+    Ok(entity.to_state()?)
+}
+
+/// A complete, in-memory event set for one entity.
+struct EventMap(BTreeMap<EventId, Event>);
+
+#[async_trait]
+impl GetEvents for EventMap {
+    async fn get_event(&self, event_id: &EventId) -> Result<Event, RetrievalError> {
+        self.0.get(event_id).cloned().ok_or_else(|| RetrievalError::EventNotFound(event_id.clone()))
+    }
+
+    async fn event_stored(&self, event_id: &EventId) -> Result<bool, RetrievalError> { Ok(self.0.contains_key(event_id)) }
+
+    fn storage_is_definitive(&self) -> bool { true }
+}
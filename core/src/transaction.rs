@@ -10,7 +10,14 @@ use crate::{
     entity::{Entity, ProvisionalEntity},
     error::MutationError,
     model::{Model, MutableBorrow},
+    property::{
+        backend::{CounterBackend, LWWBackend, PropertyBackend, YrsBackend},
+        PropertyError,
+    },
+    schema::catalog::PropertyDef,
+    value::{Value, ValueType},
 };
+use std::collections::BTreeMap;
 
 use append_only_vec::AppendOnlyVec;
 
@@ -55,6 +62,9 @@ impl Transaction {
         }
     }
 
+    /// This transaction's id, under which an ephemeral node's outbox reports its sync status
+    pub fn id(&self) -> &proto::TransactionId { &self.id }
+
     pub(crate) fn add_entity(&self, entity: Entity) -> &Entity {
         let index = self.entities.push(entity);
         &self.entities[index]
@@ -124,6 +134,73 @@ impl Transaction {
         Ok(MutableBorrow::new(self.add_entity(entity.snapshot(self.alive.clone()))))
     }
 
+    /// Create an entity of `collection` from property values by name, for callers without a
+    /// compiled model. The collection's model must already be registered; each value is cast to
+    /// its property's registered type, and registered properties left out are initialized empty,
+    /// except lists and objects, which stay unset until first written.
+    pub async fn create_entity(
+        &self,
+        collection: &proto::CollectionId,
+        values: impl IntoIterator<Item = (String, Option<Value>)>,
+    ) -> Result<&Entity, MutationError> {
+        let (model_id, properties) = self.registered_model(collection)?;
+        let mut values: BTreeMap<String, Option<Value>> = values.into_iter().collect();
+
+        let mut provisional = ProvisionalEntity::new();
+        provisional.add_membership(model_id);
+        for property in &properties {
+            write_property(&mut provisional, property, values.remove(&property.name).flatten())?;
+        }
+        if let Some(property) = values.into_keys().next() {
+            return Err(MutationError::UnknownProperty { collection: collection.clone(), property });
+        }
+        let system = self.dyncontext.system_id().ok_or(MutationError::SystemNotReady)?;
+        let genesis = proto::Event::genesis(collection.clone(), Some(system), proto::AuthorId::Unknown, provisional.extract_operations()?);
+
+        let entity = self.dyncontext.create_transaction_entity(collection.clone(), &genesis, self.alive.clone())?;
+        self.dyncontext.check_write(&entity)?;
+        if self.genesis_events.write().unwrap().insert(entity.id, genesis).is_some() {
+            return Err(MutationError::AlreadyExists);
+        }
+        Ok(self.add_entity(entity))
+    }
+
+    /// Set property values by name on an entity of `collection`, the untyped counterpart of
+    /// editing it through a model's mutable. A `None` value clears the property.
+    pub async fn update_entity(
+        &self,
+        collection: &proto::CollectionId,
+        id: EntityId,
+        values: impl IntoIterator<Item = (String, Option<Value>)>,
+    ) -> Result<&Entity, MutationError> {
+        let (_, properties) = self.registered_model(collection)?;
+        let entity = match self.get_trx_entity(&id) {
+            Some(entity) => entity,
+            None => {
+                let retrieved_entity = self.dyncontext.get_entity(id, collection, false).await?;
+                match self.get_trx_entity(&retrieved_entity.id) {
+                    Some(entity) => entity,
+                    None => {
+                        self.dyncontext.check_write(&retrieved_entity)?;
+                        self.add_entity(retrieved_entity.snapshot(self.alive.clone()))
+                    }
+                }
+            }
+        };
+        let mut target = entity;
+        for (name, value) in values {
+            let Some(property) = properties.iter().find(|property| property.name == name) else {
+                return Err(MutationError::UnknownProperty { collection: collection.clone(), property: name });
+            };
+            write_property(&mut target, property, value)?;
+        }
+        Ok(entity)
+    }
+
+    fn registered_model(&self, collection: &proto::CollectionId) -> Result<(proto::ModelId, Vec<PropertyDef>), MutationError> {
+        self.dyncontext.registered_model(collection).ok_or_else(|| MutationError::UnregisteredModel(collection.clone()))
+    }
+
     #[must_use]
     pub async fn commit(self) -> Result<(), MutationError> {
         let _ = self.dyncontext.commit_local_trx(&self).await?;
@@ -160,6 +237,92 @@ impl Transaction {
     */
 }
 
+/// Where an untyped property write lands: the provisional vessel of a new entity, or an entity
+/// already in the transaction
+trait PropertyTarget {
+    fn backend<P: PropertyBackend>(&mut self) -> Result<Arc<P>, RetrievalError>;
+}
+
+impl PropertyTarget for ProvisionalEntity {
+    fn backend<P: PropertyBackend>(&mut self) -> Result<Arc<P>, RetrievalError> { self.get_backend::<P>() }
+}
+
+impl PropertyTarget for &Entity {
+    fn backend<P: PropertyBackend>(&mut self) -> Result<Arc<P>, RetrievalError> {
+        if !self.is_writable() {
+            return Err(PropertyError::TransactionClosed.into());
+        }
+        self.get_backend::<P>()
+    }
+}
+
+/// Cast `value` to `property`'s registered type and write it to the property's backend
+fn write_property(target: &mut impl PropertyTarget, property: &PropertyDef, value: Option<Value>) -> Result<(), MutationError> {
+    let value = match (value, ValueType::from_property_str(&property.value_type)) {
+        (Some(value), Some(value_type)) => Some(value.cast_to(value_type).map_err(PropertyError::CastError)?),
+        (value, _) => value,
+    };
+    match property.backend.as_str() {
+        "lww" => target.backend::<LWWBackend>()?.set(property.name.clone(), value),
+        // Setting a count adds the difference, so increments made concurrently still count
+        "counter" => {
+            let count = match value.map(|value| value.cast_to(ValueType::I64)).transpose().map_err(PropertyError::CastError)? {
+                Some(Value::I64(count)) => count,
+                None => 0,
+                Some(_) => unreachable!("a cast to I64 yields an I64"),
+            };
+            let backend = target.backend::<CounterBackend>()?;
+            backend.increment(&property.name, count.wrapping_sub(backend.get(&property.name).unwrap_or_default()));
+        }
+        // A yrs property with a target model is a set of references (RefSet)
+        "yrs" if property.target_model.is_some() => {
+            let members = match value {
+                Some(Value::Json(serde_json::Value::Array(items))) => items
+                    .into_iter()
+                    .map(|item| match item {
+                        serde_json::Value::String(id) => Ok(id),
+                        other => Err(PropertyError::InvalidValue { value: other.to_string(), ty: "entity id".to_owned() }),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+                None => Vec::new(),
+                Some(other) => return Err(PropertyError::InvalidVariant { given: other, ty: "list of entity ids".to_owned() }.into()),
+            };
+            target.backend::<YrsBackend>()?.set_replace(&property.name, members)?;
+        }
+        // A yrs property registered as json is a list (YrsArray) or an object (YrsMap)
+        "yrs" if ValueType::from_property_str(&property.value_type) == Some(ValueType::Json) => {
+            let backend = target.backend::<YrsBackend>()?;
+            match value {
+                Some(Value::Json(serde_json::Value::Array(items))) => {
+                    let items = items.into_iter().map(serde_json::from_value).collect::<Result<Vec<yrs::Any>, _>>();
+                    backend.array_replace(&property.name, items.map_err(|e| PropertyError::DeserializeError(Box::new(e)))?)?;
+                }
+                Some(Value::Json(serde_json::Value::Object(object))) => backend.map_replace(&property.name, object)?,
+                // The registration doesn't say which of the two it is, so clearing empties whichever
+                // root exists and creates none; an array root would shadow later map writes
+                None if backend.get_array(&property.name).is_some() => backend.array_replace(&property.name, Vec::new())?,
+                None if backend.get_map(&property.name).is_some() => backend.map_replace(&property.name, Default::default())?,
+                None => {}
+                Some(other) => return Err(PropertyError::InvalidVariant { given: other, ty: "list or object".to_owned() }.into()),
+            }
+        }
+        "yrs" => {
+            let text = match value {
+                Some(value) => match value.cast_to(ValueType::String).map_err(PropertyError::CastError)? {
+                    Value::String(text) => text,
+                    _ => unreachable!("a cast to String yields a String"),
+                },
+                None => String::new(),
+            };
+            let backend = target.backend::<YrsBackend>()?;
+            backend.delete(&property.name, 0, backend.get_string(&property.name).unwrap_or_default().len() as u32)?;
+            backend.insert(&property.name, 0, &text)?;
+        }
+        backend => return Err(MutationError::FailedToSetProperty("unsupported backend", backend.to_owned())),
+    }
+    Ok(())
+}
+
 impl Drop for Transaction {
     fn drop(&mut self) {
         // Mark transaction as no longer alive when dropped
@@ -1,6 +1,10 @@
 use crate::collation::Collatable;
 use crate::value::Value;
-use ankurah_core_types::EntityId;
+use ankurah_core_types::{Decimal, EntityId};
+
+const DECIMAL_NEGATIVE: u8 = 0x01;
+const DECIMAL_ZERO: u8 = 0x02;
+const DECIMAL_POSITIVE: u8 = 0x03;
 
 // Collation for Value (single value). Tuple framing (type tags/lengths) is handled by higher-level encoders.
 impl Collatable for Value {
@@ -30,6 +34,14 @@ impl Collatable for Value {
             Value::Object(bytes) | Value::Binary(bytes) => bytes.clone(),
             // For JSON, serialize to bytes
             Value::Json(json) => serde_json::to_vec(json).unwrap_or_default(),
+            Value::Timestamp(t) => signed_i64_bytes(t.as_micros()),
+            Value::Date(d) => signed_i32_bytes(d.days_since_epoch()),
+            Value::Decimal(d) => decimal_bytes(d),
+            Value::Uuid(u) => u.to_bytes().to_vec(),
+            // Unsigned values are already ordered as big-endian bytes
+            Value::U32(x) => x.to_be_bytes().to_vec(),
+            Value::U64(x) => x.to_be_bytes().to_vec(),
+            Value::I128(x) => signed_i128_bytes(*x),
         }
     }
 
@@ -77,20 +89,20 @@ impl Collatable for Value {
                     Some(vec![1])
                 }
             }
-            Value::EntityId(entity_id) => {
-                let mut bytes = entity_id.to_bytes();
-                // Increment the byte array (big-endian arithmetic)
-                for i in (0..bytes.len()).rev() {
-                    if bytes[i] == 0xFF {
-                        bytes[i] = 0;
-                    } else {
-                        bytes[i] += 1;
-                        return Some(bytes.to_vec());
-                    }
-                }
-                None // Overflow - already at maximum
-            }
+            Value::EntityId(entity_id) => increment_bytes(entity_id.to_bytes().to_vec()),
             Value::Object(_) | Value::Binary(_) | Value::Json(_) => None,
+            Value::Timestamp(t) => t.as_micros().checked_add(1).map(signed_i64_bytes),
+            Value::Date(d) => d.days_since_epoch().checked_add(1).map(signed_i32_bytes),
+            // Nothing collates between a prefix-free key and the key extended by 0x00
+            Value::Decimal(d) => {
+                let mut bytes = decimal_bytes(d);
+                bytes.push(0);
+                Some(bytes)
+            }
+            Value::Uuid(u) => increment_bytes(u.to_bytes().to_vec()),
+            Value::U32(x) => x.checked_add(1).map(|x| x.to_be_bytes().to_vec()),
+            Value::U64(x) => x.checked_add(1).map(|x| x.to_be_bytes().to_vec()),
+            Value::I128(x) => x.checked_add(1).map(signed_i128_bytes),
         }
     }
 
@@ -141,24 +153,16 @@ impl Collatable for Value {
                     None
                 }
             }
-            Value::EntityId(entity_id) => {
-                let mut bytes = entity_id.to_bytes();
-                if bytes == [0u8; EntityId::BYTE_LEN] {
-                    None // Already at minimum
-                } else {
-                    // Decrement the byte array (big-endian arithmetic)
-                    for i in (0..bytes.len()).rev() {
-                        if bytes[i] == 0 {
-                            bytes[i] = 0xFF;
-                        } else {
-                            bytes[i] -= 1;
-                            return Some(bytes.to_vec());
-                        }
-                    }
-                    None // Should never reach here since we checked for zero above
-                }
-            }
+            Value::EntityId(entity_id) => decrement_bytes(entity_id.to_bytes().to_vec()),
             Value::Object(_) | Value::Binary(_) | Value::Json(_) => None,
+            Value::Timestamp(t) => t.as_micros().checked_sub(1).map(signed_i64_bytes),
+            Value::Date(d) => d.days_since_epoch().checked_sub(1).map(signed_i32_bytes),
+            // Arbitrarily many decimals lie just below any other, so there is no immediate predecessor
+            Value::Decimal(_) => None,
+            Value::Uuid(u) => decrement_bytes(u.to_bytes().to_vec()),
+            Value::U32(x) => x.checked_sub(1).map(|x| x.to_be_bytes().to_vec()),
+            Value::U64(x) => x.checked_sub(1).map(|x| x.to_be_bytes().to_vec()),
+            Value::I128(x) => x.checked_sub(1).map(signed_i128_bytes),
         }
     }
 
@@ -172,6 +176,13 @@ impl Collatable for Value {
             Value::Bool(b) => !b,
             Value::EntityId(entity_id) => entity_id.to_bytes() == [0u8; EntityId::BYTE_LEN],
             Value::Object(_) | Value::Binary(_) | Value::Json(_) => false,
+            Value::Timestamp(t) => t.as_micros() == i64::MIN,
+            Value::Date(d) => d.days_since_epoch() == i32::MIN,
+            Value::Decimal(_) => false,
+            Value::Uuid(u) => u.to_bytes() == [0u8; 16],
+            Value::U32(x) => *x == 0,
+            Value::U64(x) => *x == 0,
+            Value::I128(x) => *x == i128::MIN,
         }
     }
 
@@ -185,10 +196,71 @@ impl Collatable for Value {
             Value::Bool(b) => *b,
             Value::EntityId(entity_id) => entity_id.to_bytes() == [0xFFu8; EntityId::BYTE_LEN],
             Value::Object(_) | Value::Binary(_) | Value::Json(_) => false,
+            Value::Timestamp(t) => t.as_micros() == i64::MAX,
+            Value::Date(d) => d.days_since_epoch() == i32::MAX,
+            Value::Decimal(_) => false,
+            Value::Uuid(u) => u.to_bytes() == [0xFFu8; 16],
+            Value::U32(x) => *x == u32::MAX,
+            Value::U64(x) => *x == u64::MAX,
+            Value::I128(x) => *x == i128::MAX,
         }
     }
 }
 
+// Sign-flipped big-endian, so negative values sort before positive ones
+fn signed_i64_bytes(x: i64) -> Vec<u8> { ((x as u64) ^ (1 << 63)).to_be_bytes().to_vec() }
+
+fn signed_i32_bytes(x: i32) -> Vec<u8> { ((x as u32) ^ (1 << 31)).to_be_bytes().to_vec() }
+
+fn signed_i128_bytes(x: i128) -> Vec<u8> { ((x as u128) ^ (1 << 127)).to_be_bytes().to_vec() }
+
+/// A decimal collates as a sign tag, its decimal exponent, one byte per
+/// significant digit and a 0x00 terminator, which keeps the encoding
+/// prefix-free. Bytes after the tag are inverted for negative numbers so that
+/// larger magnitudes sort first.
+fn decimal_bytes(decimal: &Decimal) -> Vec<u8> {
+    if decimal.is_zero() {
+        return vec![DECIMAL_ZERO];
+    }
+    let (digits, exponent) = decimal.digits();
+    let mut bytes = Vec::with_capacity(digits.len() + 10);
+    bytes.push(DECIMAL_POSITIVE);
+    bytes.extend(signed_i64_bytes(exponent));
+    bytes.extend(digits.bytes().map(|digit| digit - b'0' + 1));
+    bytes.push(0x00);
+    if decimal.is_sign_negative() {
+        bytes[0] = DECIMAL_NEGATIVE;
+        bytes[1..].iter_mut().for_each(|byte| *byte = !*byte);
+    }
+    bytes
+}
+
+/// Big-endian increment of a fixed-width key; `None` when it is already all ones
+fn increment_bytes(mut bytes: Vec<u8>) -> Option<Vec<u8>> {
+    for i in (0..bytes.len()).rev() {
+        if bytes[i] == 0xFF {
+            bytes[i] = 0;
+        } else {
+            bytes[i] += 1;
+            return Some(bytes);
+        }
+    }
+    None
+}
+
+/// Big-endian decrement of a fixed-width key; `None` when it is already all zeros
+fn decrement_bytes(mut bytes: Vec<u8>) -> Option<Vec<u8>> {
+    for i in (0..bytes.len()).rev() {
+        if bytes[i] == 0 {
+            bytes[i] = 0xFF;
+        } else {
+            bytes[i] -= 1;
+            return Some(bytes);
+        }
+    }
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -224,4 +296,45 @@ mod tests {
         assert!(max.is_maximum());
         assert!(max.successor_bytes().is_none());
     }
+
+    #[test]
+    fn timestamps_and_dates_collate_chronologically_across_the_epoch() {
+        use ankurah_core_types::{Date, Timestamp};
+        let instants = [i64::MIN, -86_400_000_000, -1, 0, 1, 1_714_979_289_123_456];
+        for pair in instants.windows(2) {
+            let (earlier, later) = (Value::Timestamp(Timestamp::from_micros(pair[0])), Value::Timestamp(Timestamp::from_micros(pair[1])));
+            assert!(earlier.to_bytes() < later.to_bytes(), "{earlier} < {later}");
+        }
+        let day = Value::Date(Date::from_days_since_epoch(-1));
+        assert!(day.to_bytes() < Value::Date(Date::UNIX_EPOCH).to_bytes());
+        assert_eq!(day.successor_bytes(), Some(Value::Date(Date::UNIX_EPOCH).to_bytes()));
+    }
+
+    #[test]
+    fn decimals_collate_numerically_regardless_of_scale() {
+        let ascending =
+            ["-1000", "-10.5", "-10", "-9.99", "-0.01", "0", "0.001", "0.01", "0.1", "0.15", "1", "1.5", "9.99", "10", "10.5", "1000"];
+        let bytes: Vec<Vec<u8>> = ascending.iter().map(|d| Value::Decimal(d.parse().unwrap()).to_bytes()).collect();
+        for (pair, text) in bytes.windows(2).zip(ascending.windows(2)) {
+            assert!(pair[0] < pair[1], "{} < {}", text[0], text[1]);
+        }
+        // The successor sorts after the value and before everything above it
+        let ten = Value::Decimal("10".parse().unwrap());
+        let successor = ten.successor_bytes().unwrap();
+        assert!(successor > ten.to_bytes() && successor < Value::Decimal("10.0000001".parse().unwrap()).to_bytes());
+    }
+
+    #[test]
+    fn wide_integers_collate_numerically() {
+        let ascending =
+            [Value::I128(i128::MIN), Value::I128(-1), Value::I128(0), Value::I128(u64::MAX as i128 + 1), Value::I128(i128::MAX)];
+        for pair in ascending.windows(2) {
+            assert!(pair[0].to_bytes() < pair[1].to_bytes(), "{} < {}", pair[0], pair[1]);
+        }
+        assert!(Value::U64(255).to_bytes() < Value::U64(256).to_bytes());
+        assert!(Value::U64(i64::MAX as u64).to_bytes() < Value::U64(i64::MAX as u64 + 1).to_bytes());
+        assert_eq!(Value::U32(41).successor_bytes(), Some(Value::U32(42).to_bytes()));
+        assert!(Value::U64(0).is_minimum() && Value::U64(0).predecessor_bytes().is_none());
+        assert!(Value::I128(i128::MAX).successor_bytes().is_none());
+    }
 }
@@ -1,6 +1,6 @@
 mod collatable;
 
-pub use ankurah_core_types::{CastError, Value, ValueParseError, ValueType};
+pub use ankurah_core_types::{CastError, Date, Decimal, ScalarParseError, Timestamp, Uuid, Value, ValueParseError, ValueType};
 
 impl From<CastError> for crate::property::PropertyError {
     fn from(error: CastError) -> Self { Self::CastError(error) }
@@ -52,7 +52,10 @@ pub fn derive_model(input: TokenStream) -> TokenStream {
     #[cfg(any(not(feature = "uniffi"), feature = "wasm"))]
     let uniffi_impl = quote! {};
 
+    let config_tracking = desc.backend_registry.track_config_files();
+
     let expanded = quote! {
+        #config_tracking
         mod #hygiene_module {
             use super::*;
             #wasm_imports
@@ -53,6 +53,10 @@ pub struct ValueConfig {
     pub type_pattern: String,
     pub fully_qualified_type: String,
     pub accepts: String,
+    /// Whether a field whose type matches `accepts` gets this value type without an
+    /// `#[active_type]` attribute. When false, `accepts` only supplies the generic params.
+    #[serde(default = "default_true")]
+    pub infer: bool,
     pub generic_params: Vec<String>,
     pub materialized_pattern: String,
     pub methods: Vec<Method>,
@@ -191,7 +195,7 @@ impl ActiveTypeDesc {
     ///
     /// Returns `(methods, ts_override)` - ts_override provides union type for set().
     fn wasm_methods(&self, context: &str) -> (Vec<TokenStream>, Option<String>) {
-        let value_config = &self.backend_config.values[0];
+        let value_config = &self.value_config;
         let substitute_fn = |backend: &ActiveTypeDesc, pattern: &str| backend.do_substitutions(pattern, context);
 
         let mut ts_set_override: Option<String> = None;
@@ -546,6 +550,7 @@ mod tests {
             type_pattern: "LWW(?:<(.+)>)?".to_string(),
             fully_qualified_type: "LWW<{T}>".to_string(),
             accepts: ".*".to_string(),
+            infer: true,
             generic_params: vec!["T".to_string()],
             materialized_pattern: "LWW{T}".to_string(),
             methods: vec![],
@@ -6,6 +6,8 @@ use crate::model::backend::{ActiveTypeDesc, BackendConfig};
 /// Global backend configuration manager
 pub struct BackendRegistry {
     pub(crate) configs: Vec<BackendConfig>, // Ordered list to ensure "first refusal" precedence
+    /// Model-supplied config files, relative to the model crate's manifest, in load order
+    config_files: Vec<String>,
 }
 
 impl BackendRegistry {
@@ -19,12 +21,73 @@ impl BackendRegistry {
             .map_err(|e| syn::Error::new(proc_macro2::Span::call_site(), format!("Failed to parse yrs.ron: {}", e)))?;
         configs.push(yrs_config);
 
+        let yrs_array_bytes = include_bytes!("../../default_backends/yrs_array.ron");
+        let yrs_array_config: BackendConfig = ron::de::from_bytes(yrs_array_bytes)
+            .map_err(|e| syn::Error::new(proc_macro2::Span::call_site(), format!("Failed to parse yrs_array.ron: {}", e)))?;
+        configs.push(yrs_array_config);
+
+        let yrs_map_bytes = include_bytes!("../../default_backends/yrs_map.ron");
+        let yrs_map_config: BackendConfig = ron::de::from_bytes(yrs_map_bytes)
+            .map_err(|e| syn::Error::new(proc_macro2::Span::call_site(), format!("Failed to parse yrs_map.ron: {}", e)))?;
+        configs.push(yrs_map_config);
+
+        let yrs_rich_text_bytes = include_bytes!("../../default_backends/yrs_rich_text.ron");
+        let yrs_rich_text_config: BackendConfig = ron::de::from_bytes(yrs_rich_text_bytes)
+            .map_err(|e| syn::Error::new(proc_macro2::Span::call_site(), format!("Failed to parse yrs_rich_text.ron: {}", e)))?;
+        configs.push(yrs_rich_text_config);
+
+        let ref_set_bytes = include_bytes!("../../default_backends/ref_set.ron");
+        let ref_set_config: BackendConfig = ron::de::from_bytes(ref_set_bytes)
+            .map_err(|e| syn::Error::new(proc_macro2::Span::call_site(), format!("Failed to parse ref_set.ron: {}", e)))?;
+        configs.push(ref_set_config);
+
+        let counter_bytes = include_bytes!("../../default_backends/counter.ron");
+        let counter_config: BackendConfig = ron::de::from_bytes(counter_bytes)
+            .map_err(|e| syn::Error::new(proc_macro2::Span::call_site(), format!("Failed to parse counter.ron: {}", e)))?;
+        configs.push(counter_config);
+
         let lww_bytes = include_bytes!("../../default_backends/lww.ron");
         let lww_config: BackendConfig = ron::de::from_bytes(lww_bytes)
             .map_err(|e| syn::Error::new(proc_macro2::Span::call_site(), format!("Failed to parse lww.ron: {}", e)))?;
         configs.push(lww_config);
 
-        Ok(Self { configs })
+        Ok(Self { configs, config_files: Vec::new() })
+    }
+
+    /// Add the configs named by `#[model(backend_config = "path.ron")]` on a model struct, for
+    /// backends registered at runtime outside ankurah. They take precedence over the built-ins,
+    /// so a config's `accepts` pattern can claim a field type by inference.
+    pub fn with_model_configs(mut self, attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut custom = Vec::new();
+        for attr in attrs {
+            if !attr.path().is_ident("model") {
+                continue;
+            }
+            // Skip the flag form and other keys; only backend_config is ours
+            let _ = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("backend_config") {
+                    let lit: syn::LitStr = meta.value()?.parse()?;
+                    custom.push(lit);
+                } else if meta.input.peek(syn::Token![=]) {
+                    let _: syn::Expr = meta.value()?.parse()?;
+                }
+                Ok(())
+            });
+        }
+        for (i, lit) in custom.iter().enumerate() {
+            let config = load_config_file(&lit.value()).map_err(|e| syn::Error::new(lit.span(), e.to_string()))?;
+            self.configs.insert(i, config);
+            self.config_files.push(lit.value());
+        }
+        Ok(self)
+    }
+
+    /// Make the model crate rebuild when one of its backend config files changes
+    pub fn track_config_files(&self) -> proc_macro2::TokenStream {
+        let files = &self.config_files;
+        quote! {
+            #(const _: &[u8] = include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/", #files));)*
+        }
     }
 
     /// Resolve an active type from either explicit attribute content or field type for inference
@@ -60,9 +123,13 @@ impl BackendRegistry {
                         let mut concrete_types = HashMap::new();
 
                         // Extract generic parameters from the match
+                        let field_captures = accepts_pattern.captures(&field_type_str);
                         for (i, param) in value_config.generic_params.iter().enumerate() {
                             if let Some(capture) = captures.get(i + 1) {
                                 concrete_types.insert(param.clone(), capture.as_str().to_string());
+                            } else if let Some(capture) = field_captures.as_ref().and_then(|c| c.get(i + 1)) {
+                                // Take it from the field type where `accepts` captures it (Vec<T> -> T)
+                                concrete_types.insert(param.clone(), capture.as_str().to_string());
                             } else {
                                 // Infer from field type if not captured in attribute
                                 concrete_types.insert(param.clone(), field_type_str.clone());
@@ -74,9 +141,18 @@ impl BackendRegistry {
                 }
 
                 // If no explicit attribute or no match, try inference from field type
-                if attr_content.is_none() && accepts_pattern.is_match(&field_type_str) && value_config.generic_params.len() == 1 {
+                if attr_content.is_none()
+                    && value_config.infer
+                    && accepts_pattern.is_match(&field_type_str)
+                    && value_config.generic_params.len() == 1
+                {
+                    // Where `accepts` captures the param it is that (Vec<Ref<T>> -> T), else the field type
+                    let concrete_type = accepts_pattern
+                        .captures(&field_type_str)
+                        .and_then(|captures| captures.get(1))
+                        .map_or_else(|| field_type_str.clone(), |capture| capture.as_str().to_string());
                     let mut concrete_types = HashMap::new();
-                    concrete_types.insert(value_config.generic_params[0].clone(), field_type_str.clone());
+                    concrete_types.insert(value_config.generic_params[0].clone(), concrete_type);
                     return Some(ActiveTypeDesc::new(config.clone(), value_config.clone(), concrete_types));
                 }
             }
@@ -85,3 +161,16 @@ impl BackendRegistry {
         None
     }
 }
+
+/// Load a backend config from a RON file relative to the manifest of the crate being compiled
+pub(crate) fn load_config_file(config_filename: &str) -> syn::Result<BackendConfig> {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR")
+        .map_err(|_| syn::Error::new(proc_macro2::Span::call_site(), "CARGO_MANIFEST_DIR not available"))?;
+
+    let config_path = std::path::Path::new(&manifest_dir).join(config_filename);
+    let config_bytes = std::fs::read(&config_path)
+        .map_err(|e| syn::Error::new(proc_macro2::Span::call_site(), format!("Failed to read config file {:?}: {}", config_path, e)))?;
+
+    ron::de::from_bytes(&config_bytes)
+        .map_err(|e| syn::Error::new(proc_macro2::Span::call_site(), format!("Failed to parse config file {:?}: {}", config_path, e)))
+}
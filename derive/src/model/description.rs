@@ -43,7 +43,7 @@ impl ModelDescription {
         }
 
         // Load backend configurations at compile time
-        let backend_registry = crate::model::backend_registry::BackendRegistry::new()?;
+        let backend_registry = crate::model::backend_registry::BackendRegistry::new()?.with_model_configs(&input.attrs)?;
 
         Ok(Self { name, active_fields, ephemeral_fields, backend_registry, struct_attrs: input.attrs.clone() })
     }
@@ -164,6 +164,7 @@ impl ModelDescription {
     ///
     /// - `Ref<T>` → `TRef` (via `<T as Model>::RefWrapper`)
     /// - `Option<Ref<T>>` → `Option<TRef>`
+    /// - `Vec<Ref<T>>` → `Vec<TRef>`
     /// - Other → direct value
     ///
     /// Uses `__wasm_` prefix + `#[doc(hidden)]` to hide from Rust callers.
@@ -211,6 +212,16 @@ impl ModelDescription {
                             .map(|opt| opt.map(|r| <#inner_model as ::ankurah::model::Model>::RefWrapper::from(r)))
                     }
                 }
+            } else if let Some(inner_model) = Self::extract_vec_ref_inner_type(&field.ty) {
+                // Vec<Ref<T>> field: return Vec<RefModel>
+                quote! {
+                    #[doc(hidden)]
+                    #[wasm_bindgen(getter, js_name = #field_name)]
+                    pub fn #wasm_method_name(&self) -> Result<Vec<<#inner_model as ::ankurah::model::Model>::RefWrapper>, JsValue> {
+                        ::ankurah::core::model::wasm_prop(self.#field_name(), #field_name_str, #model_name_str)
+                            .map(|refs| refs.into_iter().map(<#inner_model as ::ankurah::model::Model>::RefWrapper::from).collect())
+                    }
+                }
             } else {
                 // Non-Ref field: simple wrapper
                 let projected_type = &field.ty;
@@ -260,6 +271,23 @@ impl ModelDescription {
         None
     }
 
+    /// Extract the inner model type from Vec<Ref<T>>, returning T as a syn::Type
+    #[cfg(feature = "wasm")]
+    fn extract_vec_ref_inner_type(ty: &Type) -> Option<Type> {
+        if let Type::Path(type_path) = ty {
+            let segment = type_path.path.segments.last()?;
+            if segment.ident == "Vec" {
+                if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+                    if let Some(syn::GenericArgument::Type(inner_type)) = args.args.first() {
+                        // inner_type is Ref<T>, extract T
+                        return Self::extract_ref_inner_type(inner_type);
+                    }
+                }
+            }
+        }
+        None
+    }
+
     /// Generate UniFFI getter methods for View fields.
     /// Returns projected field values (String, i64, etc.) via the underlying View methods.
     /// Ref<T> fields return String (base64 EntityId) since UniFFI doesn't support generics.
@@ -276,7 +304,7 @@ impl ModelDescription {
                 let projected_type = &field.ty;
                 let type_str = quote!(#projected_type).to_string();
 
-                // Check Option<Ref<T>> before Ref<T> since "Ref <" matches both
+                // Check Option<Ref<T>> and Vec<Ref<T>> before Ref<T> since "Ref <" matches all three
                 if type_str.contains("Option < Ref") || type_str.contains("Option<Ref") {
                     // Option<Ref<T>> -> return Option<String>
                     quote! {
@@ -285,6 +313,14 @@ impl ModelDescription {
                             self.#field_name().map(|opt| opt.map(|r| r.id().to_base64()))
                         }
                     }
+                } else if type_str.contains("Vec < Ref") || type_str.contains("Vec<Ref") {
+                    // Vec<Ref<T>> -> return Vec<String>
+                    quote! {
+                        #[uniffi::method(name = #field_name_str)]
+                        pub fn #uniffi_method_name(&self) -> Result<Vec<String>, ::ankurah::property::PropertyError> {
+                            self.#field_name().map(|refs| refs.iter().map(|r| r.id().to_base64()).collect())
+                        }
+                    }
                 } else if type_str.contains("Ref <") || type_str.starts_with("Ref<") {
                     // Ref<T> -> return base64 String
                     quote! {
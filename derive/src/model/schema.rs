@@ -105,9 +105,10 @@ pub fn schema_impl(model: &ModelDescription) -> syn::Result<TokenStream> {
         // Ref<T> names its target model by source label in the registration
         // descriptor. Source model labels are the lowercased model type name
         // (ModelDescription::collection_str), so derive the same static value
-        // from T here; Option<Ref<T>> unwraps through reference_target.
+        // from T here; Option<Ref<T>> and Vec<Ref<T>> unwrap through reference_target.
         let target_collection = reference_target(&field.ty).and_then(type_head).map(|name| name.to_lowercase());
         let target_collection_tokens = option_str_tokens(target_collection.as_deref());
+        let reference_set = is_reference_set(&field.ty);
 
         // The active type declares which backend stores it (an associated
         // const, resolved inside the static initializer). Like value_type,
@@ -137,6 +138,7 @@ pub fn schema_impl(model: &ModelDescription) -> syn::Result<TokenStream> {
                 backend: #backend,
                 value_type: #value_type,
                 target_label: #target_collection_tokens,
+                reference_set: #reference_set,
                 optional: #optional,
                 explicit_id: #explicit_id_tokens,
             }
@@ -196,29 +198,30 @@ fn type_head(ty: &Type) -> Option<String> {
 fn type_head_is(ty: &Type, name: &str) -> bool { type_head(ty).as_deref() == Some(name) }
 
 /// If `ty` is `Option<Inner>`, return `Inner`.
-fn option_inner(ty: &Type) -> Option<&Type> {
+fn option_inner(ty: &Type) -> Option<&Type> { wrapped_inner(ty, "Option") }
+
+/// If `ty` is `Wrapper<Inner>` for the named `wrapper`, return `Inner`.
+fn wrapped_inner<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
     let Type::Path(p) = ty else { return None };
     let seg = p.path.segments.last()?;
-    if seg.ident != "Option" {
+    if seg.ident != wrapper {
         return None;
     }
     let syn::PathArguments::AngleBracketed(args) = &seg.arguments else { return None };
     args.args.iter().find_map(|a| if let syn::GenericArgument::Type(t) = a { Some(t) } else { None })
 }
 
-/// If `ty` is `Ref<T>` or `Option<Ref<T>>`, return `T`.
+/// If `ty` is `Ref<T>`, `Option<Ref<T>>` or the reference set `Vec<Ref<T>>`, return `T`.
 fn reference_target(ty: &Type) -> Option<&Type> {
     let ty = option_inner(ty).unwrap_or(ty);
-    let Type::Path(path) = ty else { return None };
-    let segment = path.path.segments.last()?;
-    if segment.ident != "Ref" {
-        return None;
-    }
-    let syn::PathArguments::AngleBracketed(args) = &segment.arguments else { return None };
-    args.args.iter().find_map(|arg| match arg {
-        syn::GenericArgument::Type(target) => Some(target),
-        _ => None,
-    })
+    let ty = wrapped_inner(ty, "Vec").unwrap_or(ty);
+    wrapped_inner(ty, "Ref")
+}
+
+/// Whether `ty` is a `Vec<Ref<T>>` (optionally wrapped in `Option`).
+fn is_reference_set(ty: &Type) -> bool {
+    let ty = option_inner(ty).unwrap_or(ty);
+    wrapped_inner(ty, "Vec").and_then(|inner| wrapped_inner(inner, "Ref")).is_some()
 }
 
 /// Parse a `#[property(key = "value")]` string attribute off a field. There
@@ -177,6 +177,21 @@ fn is_option_ref_type(ty: &syn::Type) -> Option<Ident> {
     None
 }
 
+/// Check if a type is Vec<Ref<T>> and extract the inner type name
+fn is_vec_ref_type(ty: &syn::Type) -> Option<Ident> {
+    if let syn::Type::Path(type_path) = ty {
+        let segment = type_path.path.segments.last()?;
+        if segment.ident == "Vec" {
+            if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+                if let Some(syn::GenericArgument::Type(inner_type)) = args.args.first() {
+                    return is_ref_type(inner_type);
+                }
+            }
+        }
+    }
+    None
+}
+
 /// Main UniFFI implementation generator for a Model.
 /// Generates Ref wrapper, Ops singleton, and ResultSet wrapper for UniFFI consumption.
 /// These are generated in the same hygiene module as the View/Mutable types so that
@@ -271,7 +286,7 @@ fn uniffi_ref_wrapper(ref_name: &Ident, model_name: &Ident, view_name: &Ident) -
 fn uniffi_input_record(model: &crate::model::description::ModelDescription, input_name: &Ident, model_name: &Ident) -> TokenStream {
     let fields = model.active_fields();
 
-    // Generate Input Record fields - Ref<T> and Option<Ref<T>> become String/Option<String> (base64 EntityId)
+    // Generate Input Record fields - Ref<T>, Option<Ref<T>> and Vec<Ref<T>> become String/Option<String>/Vec<String> (base64 EntityId)
     let input_fields: Vec<_> = fields
         .iter()
         .map(|field| {
@@ -280,6 +295,8 @@ fn uniffi_input_record(model: &crate::model::description::ModelDescription, inpu
 
             if is_option_ref_type(&field.ty).is_some() {
                 quote! { #field_vis #field_name: Option<String> }
+            } else if is_vec_ref_type(&field.ty).is_some() {
+                quote! { #field_vis #field_name: Vec<String> }
             } else if is_ref_type(&field.ty).is_some() {
                 quote! { #field_vis #field_name: String }
             } else {
@@ -306,6 +323,14 @@ fn uniffi_input_record(model: &crate::model::description::ModelDescription, inpu
                         None => None,
                     }
                 }
+            } else if is_vec_ref_type(&field.ty).is_some() {
+                // Vec<Ref<T>> - parse each base64 string
+                quote! {
+                    #field_name: input.#field_name
+                        .iter()
+                        .map(|s| ::ankurah::proto::EntityId::from_base64(s).map(::ankurah::property::Ref::from))
+                        .collect::<Result<Vec<_>, _>>()?
+                }
             } else if is_ref_type(&field.ty).is_some() {
                 // Parse base64 string to EntityId, propagating errors
                 quote! {
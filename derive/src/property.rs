@@ -1,10 +1,41 @@
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, DeriveInput};
+use syn::{parse_macro_input, Data, DeriveInput};
 
 pub fn derive_property_impl(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let name = input.ident.clone();
+    let is_enum = matches!(input.data, Data::Enum(_));
+
+    // Enums store unit variants by their bare serialized name so that queries can compare
+    // against it (`status = 'Open'`); data-carrying variants, and every other type, keep
+    // their JSON text
+    let into_string = if is_enum {
+        quote! {
+            let json_str = match ::ankurah::derive_deps::serde_json::to_value(self) {
+                Ok(::ankurah::derive_deps::serde_json::Value::String(variant)) => variant,
+                Ok(other) => other.to_string(),
+                Err(err) => return Err(::ankurah::property::PropertyError::SerializeError(Box::new(err))),
+            };
+        }
+    } else {
+        quote! {
+            let json_str = match ::ankurah::derive_deps::serde_json::to_string(self) {
+                Ok(s) => s,
+                Err(err) => return Err(::ankurah::property::PropertyError::SerializeError(Box::new(err))),
+            };
+        }
+    };
+    let from_string = if is_enum {
+        // JSON text first: data-carrying variants, and unit variants written before they were
+        // stored bare ("\"Open\"")
+        quote! {
+            ::ankurah::derive_deps::serde_json::from_str(&s)
+                .or_else(|_| ::ankurah::derive_deps::serde_json::from_value(::ankurah::derive_deps::serde_json::Value::String(s)))
+        }
+    } else {
+        quote! { ::ankurah::derive_deps::serde_json::from_str(&s) }
+    };
 
     // Generate the Property trait implementation
     let property_impl = quote! {
@@ -16,10 +47,7 @@ pub fn derive_property_impl(input: TokenStream) -> TokenStream {
             const VALUE_TYPE: &'static str = "string";
 
             fn into_value(&self) -> std::result::Result<Option<::ankurah::value::Value>, ::ankurah::property::PropertyError> {
-                let json_str = match ::ankurah::derive_deps::serde_json::to_string(self) {
-                    Ok(s) => s,
-                    Err(err) => return Err(::ankurah::property::PropertyError::SerializeError(Box::new(err))),
-                };
+                #into_string
 
                 Ok(Some(::ankurah::value::Value::String(json_str)))
             }
@@ -27,7 +55,7 @@ pub fn derive_property_impl(input: TokenStream) -> TokenStream {
             fn from_value(value: Option<::ankurah::value::Value>) -> Result<Self, ::ankurah::property::PropertyError> {
                 match value {
                     Some(::ankurah::value::Value::String(s)) => {
-                        match ::ankurah::derive_deps::serde_json::from_str(&s) {
+                        match #from_string {
                             Ok(value) => Ok(value),
                             Err(err) => Err(::ankurah::property::PropertyError::DeserializeError(Box::new(err))),
                         }
@@ -42,6 +70,25 @@ pub fn derive_property_impl(input: TokenStream) -> TokenStream {
         }
     };
 
+    // Enum values can be used as selection! placeholders (`selection!({status})`)
+    let expr_impl = is_enum.then(|| {
+        quote! {
+            impl ::std::convert::From<#name> for ::ankurah::ankql::ast::Expr {
+                fn from(value: #name) -> Self { (&value).into() }
+            }
+
+            impl ::std::convert::From<&#name> for ::ankurah::ankql::ast::Expr {
+                fn from(value: &#name) -> Self {
+                    match ::ankurah::Property::into_value(value) {
+                        Ok(Some(value)) => ::ankurah::ankql::ast::Expr::Literal(value),
+                        Ok(None) => unreachable!("derived Property values are never empty"),
+                        Err(err) => panic!("{} failed to serialize: {}", stringify!(#name), err),
+                    }
+                }
+            }
+        }
+    });
+
     // Generate WASM wrapper types for this custom type
     let wrapper_impl = match crate::wrapper_macros::impl_wrapper_type_impl(&syn::Type::Path(syn::TypePath {
         qself: None,
@@ -51,11 +98,95 @@ pub fn derive_property_impl(input: TokenStream) -> TokenStream {
         Err(_) => quote! {}, // If wrapper generation fails, just skip it
     };
 
+    // An enum that already carries its own bindings keeps them; generating ours too would
+    // conflict with its wasm ABI or UniFFI impls
+    let ffi_impl = if is_enum && !has_own_ffi_bindings(&input.attrs) {
+        enum_ffi_impl(&input)
+    } else {
+        quote! {}
+    };
+
     let expanded: proc_macro::TokenStream = quote! {
         #property_impl
+        #expr_impl
         #wrapper_impl
+        #ffi_impl
     }
     .into();
 
     expanded
 }
+
+/// Whether the type is already exposed by `#[wasm_bindgen]`, tsify or UniFFI. Only attributes
+/// that follow `#[derive(Property)]` are visible here, so those must come after it.
+fn has_own_ffi_bindings(attrs: &[syn::Attribute]) -> bool {
+    let is_binding = |path: &syn::Path| {
+        let first = path.segments.first().map(|segment| segment.ident.to_string());
+        let last = path.segments.last().map(|segment| segment.ident.to_string());
+        matches!(first.as_deref(), Some("wasm_bindgen" | "tsify" | "uniffi")) || matches!(last.as_deref(), Some("Tsify"))
+    };
+
+    attrs.iter().any(|attr| {
+        if attr.path().is_ident("derive") {
+            let mut found = false;
+            let _ = attr.parse_nested_meta(|meta| {
+                found |= is_binding(&meta.path);
+                Ok(())
+            });
+            found
+        } else {
+            is_binding(attr.path())
+        }
+    })
+}
+
+/// Enums cross into TypeScript as a union of their serialized forms (string literals for
+/// unit variants), with the wasm ABI going through serde like model POJOs do.
+#[cfg(feature = "wasm")]
+fn enum_ffi_impl(input: &DeriveInput) -> proc_macro2::TokenStream {
+    let tsify_impl =
+        crate::model::wasm::expand_ts_model_type(input, input.ident.to_string()).unwrap_or_else(syn::Error::into_compile_error);
+    let hygiene_module = quote::format_ident!("__ankurah_property_impl_{}", crate::to_snake_case(&input.ident.to_string()));
+
+    quote! {
+        mod #hygiene_module {
+            use super::*;
+            use ::ankurah::derive_deps::wasm_bindgen::prelude::*;
+
+            #tsify_impl
+        }
+    }
+}
+
+/// Enums cross into Swift and Kotlin as native enums. The remote form implements the UniFFI
+/// traits for the existing type from a copy of its definition.
+#[cfg(all(feature = "uniffi", not(feature = "wasm")))]
+fn enum_ffi_impl(input: &DeriveInput) -> proc_macro2::TokenStream {
+    let mut definition = input.clone();
+    // serde and other helper attributes mean nothing to UniFFI
+    definition.attrs.retain(|attr| attr.path().is_ident("doc"));
+    if let Data::Enum(data) = &mut definition.data {
+        for variant in &mut data.variants {
+            variant.attrs.retain(|attr| attr.path().is_ident("doc"));
+            for field in &mut variant.fields {
+                field.attrs.retain(|attr| attr.path().is_ident("doc"));
+            }
+        }
+    }
+    let syn::DeriveInput { attrs, vis, ident, generics, data, .. } = definition;
+    let Data::Enum(data) = data else { unreachable!() };
+    let variants = data.variants;
+
+    quote! {
+        #[::uniffi::remote(Enum)]
+        #(#attrs)*
+        #vis enum #ident #generics {
+            #variants
+        }
+    }
+}
+
+#[cfg(not(any(feature = "wasm", feature = "uniffi")))]
+fn enum_ffi_impl(_input: &DeriveInput) -> proc_macro2::TokenStream {
+    quote! {}
+}
@@ -491,6 +491,15 @@ fn generate_literal_code_with_replacements(
         Value::I32(i) => {
             quote! { ::ankql::ast::Expr::Literal(::ankql::ast::Value::I32(#i)) }
         }
+        Value::U32(i) => {
+            quote! { ::ankql::ast::Expr::Literal(::ankql::ast::Value::U32(#i)) }
+        }
+        Value::U64(i) => {
+            quote! { ::ankql::ast::Expr::Literal(::ankql::ast::Value::U64(#i)) }
+        }
+        Value::I128(i) => {
+            quote! { ::ankql::ast::Expr::Literal(::ankql::ast::Value::I128(#i)) }
+        }
         Value::EntityId(id) => {
             // An entity id is 32 hash bytes with no narrower form to lift it
             // through, so the literal is emitted as its exact byte array.
@@ -513,6 +522,10 @@ fn generate_literal_code_with_replacements(
             // Json literals are not parsed from query syntax; created by AST preparation pass
             unreachable!("Json literals cannot appear in parsed queries")
         }
+        Value::Timestamp(_) | Value::Date(_) | Value::Decimal(_) | Value::Uuid(_) => {
+            // Query text spells these as strings; the typed literals only come from casting
+            unreachable!("Timestamp, Date, Decimal and Uuid literals cannot appear in parsed queries")
+        }
     }
 }
 
@@ -586,6 +599,7 @@ fn generate_comparison_op_code(operator: &ankql::ast::ComparisonOperator) -> pro
         ankql::ast::ComparisonOperator::LessThan => quote! { ::ankql::ast::ComparisonOperator::LessThan },
         ankql::ast::ComparisonOperator::LessThanOrEqual => quote! { ::ankql::ast::ComparisonOperator::LessThanOrEqual },
         ankql::ast::ComparisonOperator::In => quote! { ::ankql::ast::ComparisonOperator::In },
+        ankql::ast::ComparisonOperator::Contains => quote! { ::ankql::ast::ComparisonOperator::Contains },
         ankql::ast::ComparisonOperator::Between => quote! { ::ankql::ast::ComparisonOperator::Between },
     }
 }
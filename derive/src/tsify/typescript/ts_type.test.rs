@@ -17,20 +17,20 @@ fn test_basic_types() {
     let config = TypeGenerationConfig::default();
     if cfg!(feature = "js") {
         assert_ts!(config, (), "undefined");
-        assert_ts!(config, u128 | i128, "bigint");
         assert_ts!(config, HashMap<String, i32> | BTreeMap<String, i32>, "Map<string, number>");
         assert_ts!(config, Option<i32>, "number | undefined");
         assert_ts!(config, Vec<Option<T>> | VecDeque<Option<T>> | LinkedList<Option<T>> | &'a [Option<T>], "(T | undefined)[]");
     } else {
         assert_ts!(config, (), "null");
-        assert_ts!(config, u128 | i128, "number");
         assert_ts!(config, HashMap<String, i32> | BTreeMap<String, i32>, "Record<string, number>");
         assert_ts!(config, Option<i32>, "number | null");
         assert_ts!(config, Vec<Option<T>> | VecDeque<Option<T>> | LinkedList<Option<T>> | &'a [Option<T>], "(T | null)[]");
         assert_ts!(config, ByteBuf, "number[]");
     }
 
-    assert_ts!(config, u8 | u16 | u32 | u64 | usize | i8 | i16 | i32 | i64 | isize | f32 | f64, "number");
+    assert_ts!(config, u8 | u16 | u32 | usize | i8 | i16 | i32 | i64 | isize | f32 | f64, "number");
+    assert_ts!(config, u64, "number | bigint");
+    assert_ts!(config, u128 | i128, "bigint");
     assert_ts!(config, String | str | char | Path | PathBuf, "string");
     assert_ts!(config, bool, "boolean");
     assert_ts!(config, Box<i32> | Rc<i32> | Arc<i32> | Cell<i32> | RefCell<i32> | Cow<'a, i32>, "number");
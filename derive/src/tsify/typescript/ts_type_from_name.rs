@@ -26,7 +26,7 @@ impl TsType {
         match ident {
             "u8" | "u16" | "u32" | "i8" | "i16" | "i32" | "f64" | "f32" => Self::NUMBER,
 
-            "usize" | "isize" | "u64" | "i64" => {
+            "usize" | "isize" | "i64" => {
                 if cfg!(feature = "js") && config.large_number_types_as_bigints {
                     Self::BIGINT
                 } else {
@@ -34,14 +34,18 @@ impl TsType {
                 }
             }
 
-            "u128" | "i128" => {
-                if cfg!(feature = "js") {
+            // serde_wasm_bindgen reads a u64 from either, and ids past 2^53 only fit in a BigInt
+            "u64" => {
+                if cfg!(feature = "js") && config.large_number_types_as_bigints {
                     Self::BIGINT
                 } else {
-                    Self::NUMBER
+                    Self::Union(vec![Self::NUMBER, Self::BIGINT])
                 }
             }
 
+            // serde_wasm_bindgen always crosses 128-bit integers as BigInt
+            "u128" | "i128" => Self::BIGINT,
+
             "String" | "str" | "char" | "Path" | "PathBuf" => Self::STRING,
 
             "bool" => Self::BOOLEAN,
@@ -115,6 +119,13 @@ impl TsType {
                 Self::Fn { params, type_ann: Box::new(type_ann) }
             }
 
+            // Timestamp → Date | string
+            // create() accepts JS Dates (converted by js_preprocess_timestamp_field) or RFC 3339 text
+            "Timestamp" => Self::Union(vec![Self::Ref { name: "Date".to_string(), type_params: vec![] }, Self::STRING]),
+
+            // Calendar dates, decimals and uuids cross as their text form
+            "Date" | "Decimal" | "Uuid" => Self::STRING,
+
             // Ref<Model> → ModelRef | ModelView | string
             // Allows create() to accept View, Ref wrapper, or base64 string
             // Note: EntityId objects not supported (serde can't handle WASM pointers)
@@ -34,6 +34,29 @@ fn extract_ref_inner_type(ty: &syn::Type) -> Option<syn::Ident> {
     Some(inner.path.segments.last()?.ident.clone())
 }
 
+/// Fields typed `Timestamp` or `Option<Timestamp>`, by serialized name
+fn extract_timestamp_fields(cont: &Container) -> Vec<String> {
+    let Data::Struct(_, fields) = cont.serde_data() else {
+        return Vec::new();
+    };
+
+    fields.iter().filter(|field| is_timestamp_type(field.ty)).map(|field| field.attrs.name().serialize_name().to_owned()).collect()
+}
+
+/// Matches `Timestamp` and `Option<Timestamp>`.
+fn is_timestamp_type(ty: &syn::Type) -> bool {
+    let syn::Type::Path(type_path) = ty else { return false };
+    let Some(segment) = type_path.path.segments.last() else { return false };
+    if segment.ident == "Timestamp" {
+        return true;
+    }
+    if segment.ident != "Option" {
+        return false;
+    }
+    let syn::PathArguments::AngleBracketed(args) = &segment.arguments else { return false };
+    matches!(args.args.first(), Some(syn::GenericArgument::Type(inner)) if is_timestamp_type(inner))
+}
+
 pub fn expand(cont: &Container, decl: Decl) -> TokenStream {
     let attrs = &cont.attrs;
     let ident = cont.ident();
@@ -230,7 +253,7 @@ fn expand_from_wasm_abi(cont: &Container) -> TokenStream {
     // This runs before serde deserialization since serde can't handle WASM object pointers.
     // This is a pretty gross hack but we'll clean it up later
     let ref_fields = extract_ref_fields(cont);
-    let preprocess_calls: Vec<TokenStream> = ref_fields
+    let mut preprocess_calls: Vec<TokenStream> = ref_fields
         .iter()
         .map(|(field_name, _)| {
             quote! {
@@ -238,6 +261,12 @@ fn expand_from_wasm_abi(cont: &Container) -> TokenStream {
             }
         })
         .collect();
+    // Likewise Timestamp fields: JS Date objects → RFC 3339 strings
+    preprocess_calls.extend(extract_timestamp_fields(cont).iter().map(|field_name| {
+        quote! {
+            ::ankurah::core::model::js_preprocess_timestamp_field(&js_value, #field_name).unwrap_throw();
+        }
+    }));
 
     let from_abi_body = if preprocess_calls.is_empty() {
         quote! {
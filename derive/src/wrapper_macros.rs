@@ -2,25 +2,13 @@ use proc_macro2::TokenStream;
 use quote::quote;
 
 use crate::model::backend::ActiveTypeDesc;
-
-/// Load backend config from RON file
-fn load_backend_config(config_filename: &str) -> syn::Result<crate::model::backend::BackendConfig> {
-    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR")
-        .map_err(|_| syn::Error::new(proc_macro2::Span::call_site(), "CARGO_MANIFEST_DIR not available"))?;
-
-    let config_path = std::path::Path::new(&manifest_dir).join(config_filename);
-    let config_bytes = std::fs::read(&config_path)
-        .map_err(|e| syn::Error::new(proc_macro2::Span::call_site(), format!("Failed to read config file {:?}: {}", config_path, e)))?;
-
-    ron::de::from_bytes(&config_bytes)
-        .map_err(|e| syn::Error::new(proc_macro2::Span::call_site(), format!("Failed to parse config file {:?}: {}", config_path, e)))
-}
+use crate::model::backend_registry::load_config_file;
 
 /// Implementation for impl_provided_wrapper_types!() macro (WASM version)
 /// Note: Generates code wrapped in #[cfg(feature = "wasm")] so it's conditionally compiled
 /// in the target crate, not the derive crate.
 pub fn impl_provided_wrapper_types_impl(config_filename: &str) -> syn::Result<TokenStream> {
-    let config = load_backend_config(config_filename)?;
+    let config = load_config_file(config_filename)?;
 
     let mut all_wrappers = Vec::new();
 
@@ -58,7 +46,7 @@ pub fn impl_provided_wrapper_types_impl(config_filename: &str) -> syn::Result<To
 /// Note: Generates code wrapped in #[cfg(feature = "uniffi")] so it's conditionally compiled
 /// in the target crate, not the derive crate.
 pub fn impl_provided_wrapper_types_uniffi_impl(config_filename: &str) -> syn::Result<TokenStream> {
-    let config = load_backend_config(config_filename)?;
+    let config = load_config_file(config_filename)?;
 
     let mut all_wrappers = Vec::new();
 
@@ -111,6 +99,11 @@ pub fn impl_wrapper_type_impl(custom_type: &syn::Type) -> syn::Result<TokenStrea
                 continue; // Skip - this backend provides this type
             }
 
+            // Value types that are never inferred only wrap the types they provide
+            if !value_config.infer {
+                continue;
+            }
+
             // Check if this custom type matches the backend's accepts pattern
             let accepts_regex = regex::Regex::new(&value_config.accepts).unwrap();
             if !accepts_regex.is_match(&custom_type_str) {
@@ -165,6 +158,11 @@ pub fn impl_wrapper_type_uniffi_impl(custom_type: &syn::Type) -> syn::Result<Tok
                 continue; // Skip - this backend provides this type
             }
 
+            // Value types that are never inferred only wrap the types they provide
+            if !value_config.infer {
+                continue;
+            }
+
             // Check if this custom type matches the backend's accepts pattern
             let accepts_regex = regex::Regex::new(&value_config.accepts).unwrap();
             if !accepts_regex.is_match(&custom_type_str) {
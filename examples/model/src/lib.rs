@@ -9,12 +9,8 @@ pub struct Flags {
 }
 
 use ankurah::Property;
-#[cfg(feature = "wasm")]
-use tsify::Tsify;
 
 #[derive(Property, Serialize, Deserialize, Debug)]
-#[cfg_attr(feature = "wasm", derive(Tsify))]
-#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
 pub enum Level {
     Trace,
     Debug,
@@ -24,8 +20,6 @@ pub enum Level {
 }
 
 #[derive(Property, Serialize, Deserialize, Debug)]
-#[cfg_attr(feature = "wasm", derive(Tsify))]
-#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
 pub enum Payload {
     Text(String),
     Json(serde_json::Value),
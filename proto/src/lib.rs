@@ -17,6 +17,7 @@ pub mod sys;
 pub mod time;
 pub mod transaction;
 pub mod update;
+pub mod wire;
 
 #[cfg(feature = "postgres")]
 pub mod postgres;
@@ -41,3 +42,4 @@ pub use request::*;
 pub use subscription::QueryId;
 pub use transaction::*;
 pub use update::*;
+pub use wire::{Compression, Decoded, Wire, WireError, WireSupport};
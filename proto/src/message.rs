@@ -3,10 +3,11 @@ use serde::{Deserialize, Serialize};
 use crate::{
     auth::AuthData,
     id::EntityId,
-    peering::{Presence, PresenceRejection},
+    peering::{Goodbye, Presence, PresenceRejection},
     request::{NodeRequest, NodeResponse},
     subscription::QueryId,
     update::{NodeUpdate, NodeUpdateAck},
+    wire::Compression,
 };
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -16,6 +17,16 @@ pub enum Message {
     /// Best-effort notice that the sender is refusing the connection over
     /// a protocol version mismatch; the connection closes right after.
     PresenceRejected(PresenceRejection),
+    /// Several peer messages in one frame, sent only to peers that accept
+    /// batches (see [`crate::wire`])
+    Batch(Vec<NodeMessage>),
+    /// Another message, compressed with a codec the receiver advertised
+    Compressed {
+        codec: Compression,
+        data: Vec<u8>,
+    },
+    /// The sender is closing the connection on purpose; see [`Goodbye`]
+    Goodbye(Goodbye),
     // TODO RPC messages
 }
 
@@ -28,12 +39,39 @@ pub enum NodeMessage {
     UnsubscribeQuery { from: EntityId, query_id: QueryId },
 }
 
+impl NodeMessage {
+    /// The node that sent this message
+    pub fn from(&self) -> EntityId {
+        match self {
+            NodeMessage::Request { request, .. } => request.from,
+            NodeMessage::Response(response) => response.from,
+            NodeMessage::Update(update) => update.from,
+            NodeMessage::UpdateAck(update_ack) => update_ack.from,
+            NodeMessage::UnsubscribeQuery { from, .. } => *from,
+        }
+    }
+
+    /// A short name for the message type, for metrics and logs
+    pub fn kind(&self) -> &'static str {
+        match self {
+            NodeMessage::Request { .. } => "request",
+            NodeMessage::Response(_) => "response",
+            NodeMessage::Update(_) => "update",
+            NodeMessage::UpdateAck(_) => "update_ack",
+            NodeMessage::UnsubscribeQuery { .. } => "unsubscribe_query",
+        }
+    }
+}
+
 impl std::fmt::Display for Message {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Message::Presence(presence) => write!(f, "Presence: {}", presence),
             Message::PeerMessage(node_message) => write!(f, "PeerMessage: {}", node_message),
             Message::PresenceRejected(rejection) => write!(f, "PresenceRejected: {}", rejection),
+            Message::Batch(messages) => write!(f, "Batch: {} messages", messages.len()),
+            Message::Compressed { codec, data } => write!(f, "Compressed: {} {} bytes", codec, data.len()),
+            Message::Goodbye(goodbye) => write!(f, "Goodbye: {}", goodbye),
         }
     }
 }
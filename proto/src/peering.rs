@@ -1,14 +1,16 @@
 use bincode::Options;
 use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
 
-use crate::{clock::Clock, id::EntityId, Attested, CollectionId, EntityState, StateBuffers};
+use crate::{clock::Clock, id::EntityId, wire::WireSupport, Attested, CollectionId, EntityState, StateBuffers};
 
-/// The wire protocol version this binary speaks.
+/// The newest wire protocol version this binary speaks.
 ///
-/// Carried in the [`Presence`] handshake and compared with
-/// [`protocol_compatible`]; nodes refuse peers whose version is not
-/// compatible. Bump this whenever any wire or persisted format changes
-/// incompatibly (event or state encodings, request shapes, message
+/// Carried in the [`Presence`] handshake alongside [`MIN_PROTOCOL_VERSION`];
+/// the pair settles on the highest version both ends speak (see
+/// [`Presence::negotiate_version`]) and refuses the connection when their
+/// ranges do not overlap. Bump this whenever any wire or persisted format
+/// changes incompatibly (event or state encodings, request shapes, message
 /// framing).
 ///
 /// Versions number RELEASES, not development steps: one bump per published
@@ -23,19 +25,48 @@ use crate::{clock::Clock, id::EntityId, Attested, CollectionId, EntityState, Sta
 ///   contract is incompatible with 0.9.x.
 pub const PROTOCOL_VERSION: u32 = 1;
 
-/// Whether a peer advertising `remote` can interoperate with this binary.
+/// The oldest wire protocol version this binary still speaks.
 ///
-/// Exact match for now: refuse on mismatch was the #294 decision, because
-/// serving an older version would require maintaining dual codecs for every
-/// changed message. Isolated here so a future version can widen acceptance
-/// to a range without touching the handshake again.
-pub fn protocol_compatible(remote: u32) -> bool { remote == PROTOCOL_VERSION }
+/// Kept at `PROTOCOL_VERSION - 1` once there is an older version to speak, so
+/// servers can be rolled out ahead of their clients: a release that bumps
+/// [`PROTOCOL_VERSION`] keeps encoding and decoding the previous version's
+/// messages (see [`crate::wire`]) until the release after it. Version 1 is
+/// the first versioned release, so for now there is nothing older to speak.
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
 
+/// Optional capabilities this binary advertises in its [`Presence`].
+///
+/// A feature lets a peer use something new without a version bump when
+/// peers lacking it can still be served without it; check for one with
+/// [`Presence::has_feature`].
+pub const PROTOCOL_FEATURES: &[&str] = &[];
+
+/// [`PROTOCOL_FEATURES`] as carried in a [`Presence`]
+pub fn protocol_features() -> Vec<String> { PROTOCOL_FEATURES.iter().map(|f| f.to_string()).collect() }
+
+/// The highest version in both ranges, if they overlap
+fn highest_common_version(local: RangeInclusive<u32>, remote: RangeInclusive<u32>) -> Option<u32> {
+    let version = (*local.end()).min(*remote.end());
+    (version >= *local.start() && version >= *remote.start()).then_some(version)
+}
+
+/// The handshake each end sends as soon as a connection opens.
+///
+/// Every protocol version must be able to decode every other version's
+/// Presence to negotiate at all, so its shape (and the shapes it contains)
+/// is frozen from version 1 on. New capabilities are advertised through
+/// `features` instead of new fields.
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Presence {
     pub node_id: EntityId,
     pub durable: bool,
     pub system_root: Option<Attested<EntityState>>,
+    /// The optional encodings this peer can receive
+    pub wire: WireSupport,
+    /// See [`PROTOCOL_FEATURES`]
+    pub features: Vec<String>,
+    /// See [`MIN_PROTOCOL_VERSION`]
+    pub min_protocol_version: u32,
     /// See [`PROTOCOL_VERSION`]. Kept as the LAST field, which used to make a
     /// pre-#294 ephemeral Presence a strict prefix of this one. That prefix
     /// property ended when the entity id widened to 32 bytes: `node_id` is
@@ -46,45 +77,94 @@ pub struct Presence {
     pub protocol_version: u32,
 }
 
+impl Presence {
+    /// The protocol versions this peer speaks
+    pub fn protocol_versions(&self) -> RangeInclusive<u32> { self.min_protocol_version..=self.protocol_version }
+
+    /// The highest protocol version both this binary and the peer speak, or
+    /// the rejection to relay when there is none
+    pub fn negotiate_version(&self) -> Result<u32, PresenceRejection> {
+        let supported = MIN_PROTOCOL_VERSION..=PROTOCOL_VERSION;
+        highest_common_version(supported.clone(), self.protocol_versions())
+            .ok_or(PresenceRejection { supported, offered: self.protocol_versions() })
+    }
+
+    /// Whether the peer advertised `feature`
+    pub fn has_feature(&self, feature: &str) -> bool { self.features.iter().any(|f| f == feature) }
+}
+
 impl std::fmt::Display for Presence {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match &self.system_root {
-            Some(r) => {
-                write!(
-                    f,
-                    "Presence[{}: durable {} proto v{} system_root: {}]",
-                    self.node_id.to_base64_short(),
-                    self.durable,
-                    self.protocol_version,
-                    r.payload
-                )
-            }
-            None => {
-                write!(f, "Presence[{}: durable {} proto v{}]", self.node_id.to_base64_short(), self.durable, self.protocol_version)
-            }
+        write!(
+            f,
+            "Presence[{}: durable {} proto {}",
+            self.node_id.to_base64_short(),
+            self.durable,
+            DisplayVersions(&self.protocol_versions())
+        )?;
+        if let Some(r) = &self.system_root {
+            write!(f, " system_root: {}", r.payload)?;
         }
+        write!(f, "]")
     }
 }
 
-/// Sent best-effort before closing when a peer's Presence advertises an
-/// incompatible protocol version. Pre-versioning (0.9.x) peers cannot
+struct DisplayVersions<'a>(&'a RangeInclusive<u32>);
+
+impl std::fmt::Display for DisplayVersions<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.0.start() == self.0.end() {
+            write!(f, "v{}", self.0.end())
+        } else {
+            write!(f, "v{}-{}", self.0.start(), self.0.end())
+        }
+    }
+}
+
+/// Sent best-effort before closing when a peer's Presence advertises no
+/// protocol version in common with ours. Pre-versioning (0.9.x) peers cannot
 /// decode this message; they only observe the close.
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct PresenceRejection {
-    /// The protocol version the refusing node requires.
-    pub expected: u32,
-    /// The version the refused peer offered (0 = pre-versioning peer).
-    pub received: u32,
+    /// The protocol versions the refusing node speaks.
+    pub supported: RangeInclusive<u32>,
+    /// The versions the refused peer offered (0 = pre-versioning peer).
+    pub offered: RangeInclusive<u32>,
 }
 
 impl std::fmt::Display for PresenceRejection {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "incompatible protocol version (required {}, offered {})", self.expected, self.received)
+        write!(
+            f,
+            "incompatible protocol version (supported {}, offered {})",
+            DisplayVersions(&self.supported),
+            DisplayVersions(&self.offered)
+        )
     }
 }
 
 impl std::error::Error for PresenceRejection {}
 
+/// Sent before closing a connection on purpose, such as when a server drains
+/// for a deploy: tells the peer where and when to reconnect, so it can come
+/// back promptly instead of backing off as it would after an unexpected drop.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct Goodbye {
+    /// Where to reconnect instead; `None` to come back to the same address.
+    pub redirect: Option<String>,
+    /// How long to wait before reconnecting, in milliseconds.
+    pub reconnect_after_ms: u64,
+}
+
+impl std::fmt::Display for Goodbye {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.redirect {
+            Some(redirect) => write!(f, "reconnect to {} in {}ms", redirect, self.reconnect_after_ms),
+            None => write!(f, "reconnect in {}ms", self.reconnect_after_ms),
+        }
+    }
+}
+
 /// The Presence shape that pre-#294 binaries (0.9.x and earlier) send: no
 /// protocol_version field. Used only to classify an undecodable handshake,
 /// never constructed.
@@ -146,7 +226,15 @@ mod tests {
     use super::*;
 
     fn presence() -> Presence {
-        Presence { node_id: EntityId::random(), durable: true, system_root: None, protocol_version: PROTOCOL_VERSION }
+        Presence {
+            node_id: EntityId::random(),
+            durable: true,
+            system_root: None,
+            wire: WireSupport::all(),
+            features: protocol_features(),
+            min_protocol_version: MIN_PROTOCOL_VERSION,
+            protocol_version: PROTOCOL_VERSION,
+        }
     }
 
     /// The 0.9.x wire shapes, mirrored for compatibility tests.
@@ -222,9 +310,22 @@ mod tests {
     }
 
     #[test]
-    fn compatibility_is_exact_match() {
-        assert!(protocol_compatible(PROTOCOL_VERSION));
-        assert!(!protocol_compatible(0));
-        assert!(!protocol_compatible(PROTOCOL_VERSION + 1));
+    fn negotiation_settles_on_the_highest_common_version() {
+        assert_eq!(highest_common_version(1..=1, 1..=1), Some(1));
+        // A server rolled out ahead speaks its clients' older version
+        assert_eq!(highest_common_version(1..=2, 1..=1), Some(1));
+        assert_eq!(highest_common_version(1..=1, 1..=2), Some(1));
+        assert_eq!(highest_common_version(2..=3, 1..=4), Some(3));
+        assert_eq!(highest_common_version(2..=3, 1..=1), None);
+        assert_eq!(highest_common_version(1..=1, 0..=0), None);
+
+        let mut p = presence();
+        assert_eq!(p.negotiate_version(), Ok(PROTOCOL_VERSION));
+        p.protocol_version = PROTOCOL_VERSION + 5;
+        assert_eq!(p.negotiate_version(), Ok(PROTOCOL_VERSION));
+        p.min_protocol_version = PROTOCOL_VERSION + 1;
+        let rejection = p.negotiate_version().unwrap_err();
+        assert_eq!(rejection.supported, MIN_PROTOCOL_VERSION..=PROTOCOL_VERSION);
+        assert_eq!(rejection.offered, PROTOCOL_VERSION + 1..=PROTOCOL_VERSION + 5);
     }
 }
@@ -156,6 +156,24 @@ pub enum NodeRequestBody {
     RegisterSchema {
         models: Vec<RegisterModel>,
     },
+    /// Durable-to-durable replication: the collections the receiver stores.
+    /// Answered with [`NodeResponseBody::Collections`].
+    ListCollections,
+    /// Durable-to-durable replication: one page of a collection's entity
+    /// heads, in entity id order, starting after `after`. Answered with
+    /// [`NodeResponseBody::CollectionHeads`].
+    CollectionHeads {
+        collection: CollectionId,
+        after: Option<EntityId>,
+        limit: u64,
+    },
+    /// Durable-to-durable replication: for each entity, the events that take
+    /// the requester from its `head` (empty if it lacks the entity) to the
+    /// receiver's. Answered with [`NodeResponseBody::Replicated`].
+    Replicate {
+        collection: CollectionId,
+        known: Vec<KnownEntity>,
+    },
 }
 
 /// A response from one node to another
@@ -187,6 +205,18 @@ pub enum NodeResponseBody {
     SchemaRegistered {
         models: Vec<RegisteredModel>,
     },
+    Collections(Vec<CollectionId>),
+    /// A page of entity heads; `next` is where the following page starts, or
+    /// `None` after the last page.
+    CollectionHeads {
+        heads: Vec<KnownEntity>,
+        next: Option<EntityId>,
+    },
+    /// Only [`DeltaContent::EventBridge`] deltas: a replica stores every event.
+    Replicated(Vec<EntityDelta>),
+    /// The responder is draining for shutdown and refused to take on new
+    /// work; retry once reconnected, here or elsewhere.
+    Draining,
     Success,
     Error(String),
 }
@@ -203,6 +233,23 @@ impl std::fmt::Display for NodeResponse {
     }
 }
 
+impl NodeRequestBody {
+    /// A short name for the request type, for metrics and logs
+    pub fn kind(&self) -> &'static str {
+        match self {
+            NodeRequestBody::CommitTransaction { .. } => "commit_transaction",
+            NodeRequestBody::Get { .. } => "get",
+            NodeRequestBody::GetEvents { .. } => "get_events",
+            NodeRequestBody::Fetch { .. } => "fetch",
+            NodeRequestBody::SubscribeQuery { .. } => "subscribe_query",
+            NodeRequestBody::RegisterSchema { .. } => "register_schema",
+            NodeRequestBody::ListCollections => "list_collections",
+            NodeRequestBody::CollectionHeads { .. } => "collection_heads",
+            NodeRequestBody::Replicate { .. } => "replicate",
+        }
+    }
+}
+
 impl std::fmt::Display for NodeRequestBody {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -224,6 +271,12 @@ impl std::fmt::Display for NodeRequestBody {
             NodeRequestBody::RegisterSchema { models } => {
                 write!(f, "RegisterSchema models:{} properties:{}", models.len(), models.iter().map(|m| m.properties.len()).sum::<usize>())
             }
+            NodeRequestBody::ListCollections => write!(f, "ListCollections"),
+            NodeRequestBody::CollectionHeads { collection, after, limit } => match after {
+                Some(after) => write!(f, "CollectionHeads {collection} after:{} limit:{limit}", after.to_base64_short()),
+                None => write!(f, "CollectionHeads {collection} limit:{limit}"),
+            },
+            NodeRequestBody::Replicate { collection, known } => write!(f, "Replicate {collection} known:{}", known.len()),
         }
     }
 }
@@ -249,6 +302,12 @@ impl std::fmt::Display for NodeResponseBody {
                     models.iter().map(|m| m.properties.len()).sum::<usize>()
                 )
             }
+            NodeResponseBody::Collections(collections) => {
+                write!(f, "Collections [{}]", collections.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(", "))
+            }
+            NodeResponseBody::CollectionHeads { heads, next } => write!(f, "CollectionHeads [{}] more:{}", heads.len(), next.is_some()),
+            NodeResponseBody::Replicated(deltas) => write!(f, "Replicated [{}]", deltas.len()),
+            NodeResponseBody::Draining => write!(f, "Draining"),
             NodeResponseBody::Success => write!(f, "Success"),
             NodeResponseBody::Error(e) => write!(f, "Error: {e}"),
         }
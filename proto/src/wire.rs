@@ -0,0 +1,337 @@
+//! Frame encoding: the negotiated protocol version, compression and message batching
+//!
+//! Frames are encoded in the shape of the protocol version the pair settled on in the
+//! Presence exchange, which may be older than [`PROTOCOL_VERSION`] while servers are rolled
+//! out ahead of their clients. A release that bumps the version keeps the previous version's
+//! message shapes as frozen mirrors, converted to and from in `serialize` and
+//! `deserialize`, until the release after it.
+//!
+//! Each peer advertises in its [`Presence`](crate::Presence) which encodings it can receive.
+//! Once both Presences are exchanged, a sender may batch queued [`NodeMessage`]s into a
+//! single [`Message::Batch`] frame and wrap any frame large enough to benefit in
+//! [`Message::Compressed`], using a codec the receiver advertised. Frames stay
+//! self-describing, so a receiver never needs to know what the sender decided: [`Wire::decode`]
+//! expands a compressed frame and hands back the [`Decoded`] message it carried.
+//!
+//! Presence and PresenceRejected frames are always plain bincode: they are exchanged before
+//! either side knows what the other supports.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    message::{Message, NodeMessage},
+    peering::{Goodbye, Presence, PresenceRejection, PROTOCOL_VERSION},
+};
+
+/// Frames smaller than this are sent uncompressed; the codec overhead outweighs the savings
+pub const COMPRESSION_THRESHOLD: usize = 1024;
+
+/// Largest payload a compressed frame may expand to
+pub const MAX_DECOMPRESSED_LEN: usize = 64 * 1024 * 1024;
+
+/// Most messages drained into a single [`Message::Batch`]
+pub const MAX_BATCH_LEN: usize = 256;
+
+/// A compression codec for [`Message::Compressed`] frames
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Zstd,
+    Lz4,
+}
+
+impl Compression {
+    /// Codecs this binary was built with, most preferred first
+    pub fn supported() -> Vec<Compression> {
+        vec![
+            #[cfg(feature = "zstd")]
+            Compression::Zstd,
+            #[cfg(feature = "lz4")]
+            Compression::Lz4,
+        ]
+    }
+
+    #[cfg_attr(not(any(feature = "zstd", feature = "lz4")), allow(unused_variables))]
+    fn compress(&self, data: &[u8]) -> Result<Vec<u8>, WireError> {
+        match self {
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => zstd::bulk::compress(data, 3).map_err(|e| WireError::Codec(*self, e.to_string())),
+            #[cfg(feature = "lz4")]
+            Compression::Lz4 => Ok(lz4_flex::compress_prepend_size(data)),
+            #[allow(unreachable_patterns)]
+            _ => Err(WireError::Unsupported(*self)),
+        }
+    }
+
+    #[cfg_attr(not(any(feature = "zstd", feature = "lz4")), allow(unused_variables))]
+    fn decompress(&self, data: &[u8]) -> Result<Vec<u8>, WireError> {
+        match self {
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => {
+                use std::io::Read;
+                let mut decoded = Vec::new();
+                zstd::stream::read::Decoder::new(data)
+                    .and_then(|decoder| decoder.take(MAX_DECOMPRESSED_LEN as u64 + 1).read_to_end(&mut decoded))
+                    .map_err(|e| WireError::Codec(*self, e.to_string()))?;
+                if decoded.len() > MAX_DECOMPRESSED_LEN {
+                    return Err(WireError::TooLarge);
+                }
+                Ok(decoded)
+            }
+            #[cfg(feature = "lz4")]
+            Compression::Lz4 => {
+                let len = data.get(..4).map(|prefix| u32::from_le_bytes(prefix.try_into().unwrap()) as usize);
+                if len.is_some_and(|len| len > MAX_DECOMPRESSED_LEN) {
+                    return Err(WireError::TooLarge);
+                }
+                lz4_flex::decompress_size_prepended(data).map_err(|e| WireError::Codec(*self, e.to_string()))
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(WireError::Unsupported(*self)),
+        }
+    }
+}
+
+impl std::fmt::Display for Compression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Compression::Zstd => write!(f, "zstd"),
+            Compression::Lz4 => write!(f, "lz4"),
+        }
+    }
+}
+
+/// The optional encodings a peer can receive, advertised in its Presence
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct WireSupport {
+    /// Codecs the peer decompresses, most preferred first
+    pub compression: Vec<Compression>,
+    /// Whether the peer accepts [`Message::Batch`]
+    pub batching: bool,
+}
+
+impl WireSupport {
+    /// Every encoding this binary can receive
+    pub fn all() -> Self { Self { compression: Compression::supported(), batching: true } }
+}
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum WireError {
+    #[error("serialization error: {0}")]
+    Serialization(String),
+    #[error("compression {0} is not supported by this build")]
+    Unsupported(Compression),
+    #[error("{0} codec error: {1}")]
+    Codec(Compression, String),
+    #[error("compressed frame expands beyond {MAX_DECOMPRESSED_LEN} bytes")]
+    TooLarge,
+    #[error("compressed frame nests another compressed frame")]
+    NestedCompression,
+    #[error("protocol version {0} is not spoken by this build")]
+    UnsupportedVersion(u32),
+}
+
+impl From<bincode::Error> for WireError {
+    fn from(e: bincode::Error) -> Self { WireError::Serialization(e.to_string()) }
+}
+
+/// Serialize a message in the shape protocol `version` gives it
+fn serialize(version: u32, message: &Message) -> Result<Vec<u8>, WireError> {
+    match version {
+        PROTOCOL_VERSION => Ok(bincode::serialize(message)?),
+        version => Err(WireError::UnsupportedVersion(version)),
+    }
+}
+
+/// Deserialize a message from the shape protocol `version` gives it
+fn deserialize(version: u32, data: &[u8]) -> Result<Message, WireError> {
+    match version {
+        PROTOCOL_VERSION => Ok(bincode::deserialize(data)?),
+        version => Err(WireError::UnsupportedVersion(version)),
+    }
+}
+
+/// A received frame's message, with compression already undone
+///
+/// Mirrors [`Message`] without its `Compressed` variant: a compressed frame always decodes to
+/// the message it carries, and one carrying another compressed frame is refused.
+#[derive(Debug)]
+pub enum Decoded {
+    Presence(Presence),
+    PeerMessage(NodeMessage),
+    PresenceRejected(PresenceRejection),
+    Batch(Vec<NodeMessage>),
+    Goodbye(Goodbye),
+}
+
+impl TryFrom<Message> for Decoded {
+    type Error = WireError;
+    fn try_from(message: Message) -> Result<Self, WireError> {
+        Ok(match message {
+            Message::Presence(presence) => Decoded::Presence(presence),
+            Message::PeerMessage(message) => Decoded::PeerMessage(message),
+            Message::PresenceRejected(rejection) => Decoded::PresenceRejected(rejection),
+            Message::Batch(messages) => Decoded::Batch(messages),
+            Message::Compressed { .. } => return Err(WireError::NestedCompression),
+            Message::Goodbye(goodbye) => Decoded::Goodbye(goodbye),
+        })
+    }
+}
+
+impl std::fmt::Display for Decoded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Decoded::Presence(presence) => write!(f, "Presence: {}", presence),
+            Decoded::PeerMessage(node_message) => write!(f, "PeerMessage: {}", node_message),
+            Decoded::PresenceRejected(rejection) => write!(f, "PresenceRejected: {}", rejection),
+            Decoded::Batch(messages) => write!(f, "Batch: {} messages", messages.len()),
+            Decoded::Goodbye(goodbye) => write!(f, "Goodbye: {}", goodbye),
+        }
+    }
+}
+
+/// The encoding settled with one peer
+///
+/// The default is the handshake encoding, used until the peer's Presence arrives: the newest
+/// protocol version, uncompressed and unbatched. Presence frames decode the same under every
+/// version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Wire {
+    pub version: u32,
+    pub compression: Option<Compression>,
+    pub batching: bool,
+}
+
+impl Default for Wire {
+    fn default() -> Self { Self { version: PROTOCOL_VERSION, compression: None, batching: false } }
+}
+
+impl Wire {
+    /// How to talk to the peer that sent `remote`: the highest protocol version we both speak,
+    /// our most preferred codec it can decompress, and batches if it accepts them
+    pub fn negotiate(remote: &Presence) -> Result<Self, PresenceRejection> {
+        let version = remote.negotiate_version()?;
+        let compression = Compression::supported().into_iter().find(|codec| remote.wire.compression.contains(codec));
+        Ok(Self { version, compression, batching: remote.wire.batching })
+    }
+
+    /// Encode a single message as one frame
+    pub fn encode(&self, message: &Message) -> Result<Vec<u8>, WireError> {
+        let data = serialize(self.version, message)?;
+        let Some(codec) = self.compression else { return Ok(data) };
+        if data.len() < COMPRESSION_THRESHOLD {
+            return Ok(data);
+        }
+        let compressed = codec.compress(&data)?;
+        if compressed.len() >= data.len() {
+            return Ok(data);
+        }
+        serialize(self.version, &Message::Compressed { codec, data: compressed })
+    }
+
+    /// Encode queued messages as frames: one [`Message::Batch`] when the peer accepts
+    /// batches, otherwise one frame per message
+    pub fn encode_all(&self, mut messages: Vec<NodeMessage>) -> Result<Vec<Vec<u8>>, WireError> {
+        if messages.len() == 1 {
+            return Ok(vec![self.encode(&Message::PeerMessage(messages.pop().unwrap()))?]);
+        }
+        if self.batching {
+            return Ok(vec![self.encode(&Message::Batch(messages))?]);
+        }
+        messages.into_iter().map(|message| self.encode(&Message::PeerMessage(message))).collect()
+    }
+
+    /// Decode one frame, expanding [`Message::Compressed`] into the message it carries
+    pub fn decode(&self, data: &[u8]) -> Result<Decoded, WireError> {
+        match deserialize(self.version, data)? {
+            Message::Compressed { codec, data } => deserialize(self.version, &codec.decompress(&data)?)?.try_into(),
+            message => message.try_into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{peering::MIN_PROTOCOL_VERSION, EntityId, QueryId};
+
+    fn unsubscribe() -> NodeMessage { NodeMessage::UnsubscribeQuery { from: EntityId::random(), query_id: QueryId::new() } }
+
+    fn presence(wire: WireSupport) -> Presence {
+        Presence {
+            node_id: EntityId::random(),
+            durable: false,
+            system_root: None,
+            wire,
+            features: vec![],
+            min_protocol_version: MIN_PROTOCOL_VERSION,
+            protocol_version: PROTOCOL_VERSION,
+        }
+    }
+
+    fn node_messages(message: Decoded) -> Vec<NodeMessage> {
+        match message {
+            Decoded::PeerMessage(message) => vec![message],
+            Decoded::Batch(messages) => messages,
+            other => panic!("expected peer traffic, got {other}"),
+        }
+    }
+
+    #[test]
+    fn negotiation_picks_a_codec_both_ends_support() {
+        assert_eq!(Wire::negotiate(&presence(WireSupport::default())), Ok(Wire::default()));
+        let wire = Wire::negotiate(&presence(WireSupport::all())).unwrap();
+        assert_eq!(wire.compression, Compression::supported().first().copied());
+        assert!(wire.batching);
+    }
+
+    #[test]
+    fn batches_round_trip() {
+        let messages: Vec<NodeMessage> = (0..100).map(|_| unsubscribe()).collect();
+        let batched = Wire { compression: None, batching: true, ..Wire::default() }.encode_all(messages).unwrap();
+        assert_eq!(batched.len(), 1);
+        assert_eq!(node_messages(Wire::default().decode(&batched[0]).unwrap()).len(), 100);
+
+        let unbatched = Wire::default().encode_all(vec![unsubscribe(), unsubscribe()]).unwrap();
+        assert_eq!(unbatched.len(), 2);
+        assert_eq!(node_messages(Wire::default().decode(&unbatched[1]).unwrap()).len(), 1);
+    }
+
+    #[test]
+    fn compressed_frames_round_trip() {
+        for codec in Compression::supported() {
+            let batch = Message::Batch((0..100).map(|_| unsubscribe()).collect());
+            let plain = bincode::serialize(&batch).unwrap();
+            let frame = Wire { compression: Some(codec), batching: true, ..Wire::default() }.encode(&batch).unwrap();
+            assert!(matches!(bincode::deserialize(&frame).unwrap(), Message::Compressed { codec: c, .. } if c == codec));
+            assert!(frame.len() < plain.len(), "{codec} did not shrink the batch");
+            assert_eq!(node_messages(Wire::default().decode(&frame).unwrap()).len(), 100);
+
+            // Small frames are not worth compressing
+            let small =
+                Wire { compression: Some(codec), batching: true, ..Wire::default() }.encode(&Message::PeerMessage(unsubscribe())).unwrap();
+            assert!(matches!(bincode::deserialize(&small).unwrap(), Message::PeerMessage(_)));
+        }
+    }
+
+    #[test]
+    fn nested_compression_is_refused() {
+        for codec in Compression::supported() {
+            let inner = bincode::serialize(&Message::Compressed { codec, data: vec![] }).unwrap();
+            let frame = bincode::serialize(&Message::Compressed { codec, data: codec.compress(&inner).unwrap() }).unwrap();
+            assert_eq!(Wire::default().decode(&frame).unwrap_err(), WireError::NestedCompression);
+        }
+    }
+
+    #[test]
+    fn frames_follow_the_negotiated_version() {
+        let mut remote = presence(WireSupport::default());
+        remote.protocol_version = PROTOCOL_VERSION + 1;
+        assert_eq!(Wire::negotiate(&remote).unwrap().version, PROTOCOL_VERSION);
+
+        remote.min_protocol_version = PROTOCOL_VERSION + 1;
+        assert!(Wire::negotiate(&remote).is_err());
+
+        let unknown = Wire { version: PROTOCOL_VERSION + 1, ..Wire::default() };
+        assert_eq!(unknown.encode(&Message::PeerMessage(unsubscribe())), Err(WireError::UnsupportedVersion(PROTOCOL_VERSION + 1)));
+    }
+}
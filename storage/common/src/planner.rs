@@ -1,21 +1,21 @@
 use crate::{KeyBounds, predicate::ConjunctFinder, types::*};
 use ankql::ast::{ComparisonOperator, Expr, Predicate};
-use ankurah_core::indexing::{IndexKeyPart, KeySpec};
+use ankurah_core::indexing::{IndexKeyPart, KeySpec, element_path};
 use ankurah_core_types::{Value, ValueType};
 use indexmap::IndexMap;
 
 #[derive(Debug, Clone)]
 pub struct PlannerConfig {
     /// Whether the storage backend supports descending indexes
-    /// false for IndexedDB, true for engines with real DESC indexes
+    /// false for ASC-only engines, which rely on reverse scans and spill sorts for DESC
     pub supports_desc_indexes: bool,
 }
 
 impl PlannerConfig {
     pub fn new(supports_desc_indexes: bool) -> Self { Self { supports_desc_indexes } }
 
-    /// IndexedDB configuration
-    pub fn indexeddb() -> Self { Self::new(false) }
+    /// IndexedDB configuration (DESC key parts are indexed as inverted keys)
+    pub fn indexeddb() -> Self { Self::new(true) }
 
     /// Generic storage with full index support
     pub fn full_support() -> Self { Self::new(true) }
@@ -132,14 +132,31 @@ impl Planner {
         // Keyparts: EQ prefix (using asc_path for multi-step path support)
         let mut index_keyparts: Vec<IndexKeyPart> = equalities.iter().map(|(f, v)| IndexKeyPart::asc_path(f, ValueType::of(v))).collect();
 
+        // ORDER BY fields default to String keys. Timestamp, date, decimal, uuid and wide integer
+        // text does not collate in value order, so an inequality of one of those types on the field
+        // types its key instead (such literals, unlike i16/i32/i64 query text, share the stored
+        // value's type)
+        let order_type = |name: &str| match inequalities.get(name).and_then(|ops| ops.first()).map(|(_, v)| ValueType::of(v)) {
+            Some(
+                ty @ (ValueType::Timestamp
+                | ValueType::Date
+                | ValueType::Decimal
+                | ValueType::Uuid
+                | ValueType::U32
+                | ValueType::U64
+                | ValueType::I128),
+            ) => ty,
+            _ => ValueType::String,
+        };
+
         // Append ORDER BY fields per capability
         if self.config.supports_desc_indexes {
             for item in order_by {
                 if item.path.is_simple() {
                     let name = item.path.first();
                     index_keyparts.push(match item.direction {
-                        ankql::ast::OrderDirection::Asc => IndexKeyPart::asc(name.to_string(), ValueType::String),
-                        ankql::ast::OrderDirection::Desc => IndexKeyPart::desc(name.to_string(), ValueType::String),
+                        ankql::ast::OrderDirection::Asc => IndexKeyPart::asc(name.to_string(), order_type(name)),
+                        ankql::ast::OrderDirection::Desc => IndexKeyPart::desc(name.to_string(), order_type(name)),
                     });
                 }
             }
@@ -151,7 +168,7 @@ impl Planner {
                 if item.path.is_simple() {
                     let name = item.path.first();
                     if !broke && item.direction == first_dir {
-                        index_keyparts.push(IndexKeyPart::asc(name.to_string(), ValueType::String));
+                        index_keyparts.push(IndexKeyPart::asc(name.to_string(), order_type(name)));
                     } else {
                         broke = true;
                     }
@@ -316,6 +333,15 @@ impl Planner {
                     ComparisonOperator::Equal => {
                         equalities.push((field, value));
                     }
+                    // Membership is an equality on the collection's elements, served by a
+                    // multi-entry index. Only one per collection: an entity has a key per
+                    // element, not per combination of them
+                    ComparisonOperator::Contains if !field.contains('.') => {
+                        let path = element_path(&field);
+                        if !equalities.iter().any(|(eq_field, _)| *eq_field == path) {
+                            equalities.push((path, value));
+                        }
+                    }
                     ComparisonOperator::GreaterThan
                     | ComparisonOperator::GreaterThanOrEqual
                     | ComparisonOperator::LessThan
@@ -323,7 +349,7 @@ impl Planner {
                         inequalities.entry(field).or_default().push((op, value));
                     }
                     _ => {
-                        // NotEqual, In, Between - not supported for index ranges
+                        // NotEqual, In, Between, nested CONTAINS - not supported for index ranges
                         // These remain in the remaining_predicate
                     }
                 }
@@ -642,13 +668,18 @@ impl Planner {
             let mut consumed = false;
 
             // Check if this conjunct is consumed by equalities
-            if let Some((field, _, _)) = self.extract_comparison(conjunct) {
+            if let Some((field, op, value)) = self.extract_comparison(conjunct) {
                 // Check if it's a consumed equality
-                for (eq_field, _) in consumed_equalities {
+                for (eq_field, eq_value) in consumed_equalities {
                     if field == *eq_field {
                         consumed = true;
                         break;
                     }
+                    // Other memberships of the same collection are not in the index bounds
+                    if op == ComparisonOperator::Contains && element_path(&field) == *eq_field && value == *eq_value {
+                        consumed = true;
+                        break;
+                    }
                 }
 
                 // Check if it's a consumed inequality
@@ -938,11 +969,11 @@ mod tests {
     use ankurah_core_types::Value;
     use ankurah_derive::selection;
 
-    // FIX_ME: rename to plan_indexeddb
+    // ASC-only index support: DESC is served by reverse scans and spill sorts
     macro_rules! plan {
         ($($selection:tt)*) => {{
             let selection = selection!($($selection)*);
-            let planner = Planner::new(PlannerConfig::indexeddb());
+            let planner = Planner::new(PlannerConfig::new(false));
             planner.plan(&selection, "id")
         }};
     }
@@ -1129,6 +1160,17 @@ mod tests {
             );
         }
 
+        #[test]
+        fn order_by_with_covered_timestamp_inequality() {
+            let since: ankurah_core_types::Timestamp = "2024-03-01T00:00:00Z".parse().unwrap();
+            let selection = selection!("__collection = 'album' AND released > {} ORDER BY released DESC", since);
+            let Plan::Index { index_spec, .. } = &Planner::new(PlannerConfig::full_support()).plan(&selection, "id")[0] else {
+                panic!("expected an index plan");
+            };
+            // Typed by the literal, so the keys collate chronologically rather than as text
+            assert_eq!(index_spec, &KeySpec::new(vec![asc!("__collection", ValueType::String), desc!("released", ValueType::Timestamp)]));
+        }
+
         #[test]
         fn order_by_with_covered_inequality() {
             assert_eq!(
@@ -2115,6 +2157,40 @@ mod tests {
         }
     }
 
+    mod membership_tests {
+        use super::*;
+
+        #[test]
+        fn test_contains_scans_element_keys() {
+            // The second membership of the same collection stays in the remaining predicate
+            assert_eq!(
+                plan!("__collection = 'task' AND tags CONTAINS 'urgent' AND 'ops' IN tags AND priority >= 2"),
+                vec![
+                    Plan::Index {
+                        index_spec: KeySpec::new(vec![
+                            asc!("__collection", ValueType::String),
+                            IndexKeyPart::asc_element("tags", ValueType::String),
+                            asc!("priority", ValueType::I32)
+                        ]),
+                        scan_direction: ScanDirection::Forward,
+                        bounds: bounds!("__collection" => ("task"..="task"), "tags.[]" => ("urgent"..="urgent"), "priority" => (2..)),
+                        remaining_predicate: selection!("tags CONTAINS 'ops'").predicate,
+                        order_by_spill: order_by_components!()
+                    },
+                    Plan::TableScan {
+                        bounds: KeyBounds::empty(),
+                        scan_direction: ScanDirection::Forward,
+                        remaining_predicate: selection!(
+                            "__collection = 'task' AND tags CONTAINS 'urgent' AND 'ops' IN tags AND priority >= 2"
+                        )
+                        .predicate,
+                        order_by_spill: order_by_components!()
+                    }
+                ]
+            );
+        }
+    }
+
     // Test cases for mixed scenarios
     mod mixed_tests {
         use super::*;
@@ -2806,7 +2882,7 @@ mod tests {
             // ORDER BY a ASC, b DESC LIMIT 10 with index on (a)
             // Spill should still contain [b DESC]
             let selection = selection!("__collection = 'album' ORDER BY a ASC, b DESC LIMIT 10");
-            let planner = Planner::new(PlannerConfig::indexeddb());
+            let planner = Planner::new(PlannerConfig::new(false));
             let plans = planner.plan(&selection, "id");
             let index_plan = &plans[0];
 
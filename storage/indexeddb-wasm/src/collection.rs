@@ -97,7 +97,7 @@ impl StorageCollection for IndexedDBBucket {
             entity.set(&*ATTESTATIONS_KEY, &state.attestations)?;
 
             // Extract all fields for indexing
-            extract_all_fields(&entity, &state.payload)?;
+            extract_all_fields(&entity, state.payload.entity_id, &state.payload.state.state_buffers)?;
 
             // Put the entity in the store
             let request = store.put_with_key(&entity, &state.payload.entity_id.to_string().into()).require("put entity in store")?;
@@ -166,9 +166,21 @@ impl StorageCollection for IndexedDBBucket {
                 return Ok(Vec::new());
             }
             Plan::Index { index_spec, bounds, scan_direction, remaining_predicate, order_by_spill } => {
+                // Membership plans are served by a multi-entry index on the member alone
+                let (index_spec, remaining_predicate, order_by_spill, member) =
+                    match crate::planner_integration::element_lookup(index_spec, bounds) {
+                        Some((element_spec, member)) => (
+                            element_spec,
+                            &amended_selection.predicate,
+                            OrderByComponents { presort: Vec::new(), spill: selection.order_by.clone().unwrap_or_default() },
+                            Some(member),
+                        ),
+                        None => (index_spec.clone(), remaining_predicate, order_by_spill.clone(), None),
+                    };
+
                 // Step 4: Ensure index exists using plan's IndexSpec
                 self.db
-                    .assure_index_exists(index_spec)
+                    .assure_index_exists(&index_spec)
                     .await
                     .map_err(|e| RetrievalError::StorageError(format!("ensure index exists: {}", e).into()))?;
 
@@ -185,11 +197,21 @@ impl StorageCollection for IndexedDBBucket {
                     let index = store.index(&index_spec.name_with("", "__")).require("get index")?;
 
                     // Convert plan bounds to IndexedDB key range using new pipeline
-                    let (key_range, upper_open_ended, eq_prefix_len, eq_prefix_values) =
-                        crate::planner_integration::plan_bounds_to_idb_range(bounds, scan_direction)
-                            .map_err(|e| RetrievalError::StorageError(format!("bounds conversion: {}", e).into()))?;
-                    // Convert scan direction to cursor direction
-                    let cursor_direction = crate::planner_integration::scan_direction_to_cursor_direction(scan_direction);
+                    let (key_range, upper_open_ended, eq_prefix_len, eq_prefix_values, cursor_direction) = match member {
+                        Some(member) => {
+                            let key: JsValue = crate::idb_value::IdbValue::from(&member).into();
+                            let key_range = web_sys::IdbKeyRange::only(&key).require("create member key range")?;
+                            (key_range, false, 0, Vec::new(), web_sys::IdbCursorDirection::Next)
+                        }
+                        None => {
+                            let (key_range, upper_open_ended, eq_prefix_len, eq_prefix_values) =
+                                crate::planner_integration::plan_bounds_to_idb_range(&index_spec, bounds, scan_direction)
+                                    .map_err(|e| RetrievalError::StorageError(format!("bounds conversion: {}", e).into()))?;
+                            // Convert scan direction to cursor direction
+                            let cursor_direction = crate::planner_integration::scan_direction_to_cursor_direction(scan_direction);
+                            (key_range, upper_open_ended, eq_prefix_len, eq_prefix_values, cursor_direction)
+                        }
+                    };
 
                     let results = self
                         .execute_plan_query(
@@ -533,15 +555,25 @@ fn js_object_to_entity_state(
     Ok(attested_state)
 }
 
-/// Extract all fields from entity state and set them directly on the IndexedDB entity object
-fn extract_all_fields(entity_obj: &Object, entity_state: &EntityState) -> Result<(), MutationError> {
+/// Extract all fields from entity state and set them directly on the IndexedDB entity object,
+/// along with their inverted twins under `__desc` for descending key parts
+pub(crate) fn extract_all_fields(
+    entity_obj: &Object,
+    entity_id: proto::EntityId,
+    state_buffers: &proto::StateBuffers,
+) -> Result<(), MutationError> {
     use ankurah_core::property::backend::backend_from_string;
+    use ankurah_core::value::Value;
     use std::collections::HashSet;
 
     let mut seen_fields = HashSet::new();
+    let desc = Object::new(js_sys::Object::new().into());
+    if let Some(twin) = crate::descending::twin(&Value::EntityId(entity_id)) {
+        desc.set(&*ID_KEY, twin)?;
+    }
 
     // Process all property values from state buffers
-    for (backend_name, state_buffer) in entity_state.state.state_buffers.iter() {
+    for (backend_name, state_buffer) in state_buffers.iter() {
         let backend = backend_from_string(backend_name, Some(state_buffer)).map_err(|e| MutationError::General(Box::new(e)))?;
 
         for (field_name, value) in backend.property_values() {
@@ -557,8 +589,14 @@ fn extract_all_fields(entity_obj: &Object, entity_state: &EntityState) -> Result
                 None => JsValue::NULL,
             };
             entity_obj.set(&field_name, js_value)?;
+
+            // Fields without a valid key are left out of the twin, just as IndexedDB leaves them out of ASC indexes
+            if let Some(twin) = value.as_ref().and_then(crate::descending::twin) {
+                desc.set(&field_name, twin)?;
+            }
         }
     }
+    entity_obj.set(&*DESC_KEY, (*desc).clone())?;
 
     Ok(())
 }
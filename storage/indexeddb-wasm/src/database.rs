@@ -12,7 +12,9 @@ use wasm_bindgen::closure::Closure;
 use wasm_bindgen::{prelude::*, JsCast};
 use web_sys::{window, IdbDatabase, IdbFactory, IdbOpenDbRequest, IdbVersionChangeEvent};
 
-use crate::util::{cb_future::CBFuture, cb_race::CBRace, require::WBGRequire};
+use crate::statics::{DESC_KEY, ID_KEY, STATE_BUFFER_KEY};
+use crate::util::{cb_future::CBFuture, cb_race::CBRace, cb_stream::cb_stream, object::Object, require::WBGRequire};
+use futures::StreamExt;
 
 #[derive(Debug, Clone)]
 pub struct Database(Arc<Inner>);
@@ -168,8 +170,27 @@ impl Connection {
             let transaction = open_request.transaction().require("get upgrade transaction")?;
             let store = transaction.object_store("entities").require("get entities store during upgrade")?;
             // Use full_path() to support JSON sub-paths (e.g., "context.session_id")
-            let key_path: Vec<JsValue> = index_spec.keyparts.iter().map(|kp| kp.full_path().into()).collect();
+            // Descending key parts index the inverted twin of the path instead
+            let key_path: Vec<JsValue> = index_spec
+                .keyparts
+                .iter()
+                .map(|kp| if kp.direction.is_desc() { crate::descending::key_path(&kp.full_path()) } else { kp.full_path() }.into())
+                .collect();
+            if let [element] = index_spec.keyparts.as_slice() {
+                if element.is_element() {
+                    // One index entry per member of the array stored under the field
+                    let params = web_sys::IdbIndexParameters::new();
+                    params.set_multi_entry(true);
+                    store
+                        .create_index_with_str_and_optional_parameters(&index_name, &element.column, &params)
+                        .require("create multi-entry index")?;
+                    return Ok(());
+                }
+            }
             store.create_index_with_str_sequence(&index_name, &key_path.into()).require("create index")?;
+            if index_spec.keyparts.iter().any(|kp| kp.direction.is_desc()) {
+                backfill_descending_keys(transaction, store)?;
+            }
             Ok(())
         })
         .await
@@ -209,3 +230,42 @@ impl Connection {
         Ok(Self { db: SendWrapper::new(db), _onversionchange: Some(SendWrapper::new(onversionchange)), stale })
     }
 }
+
+/// Entities stored before inverted twins were written have nothing for a descending index to
+/// cover, so give them their twins within the upgrade transaction that creates the index.
+/// The open request only succeeds once the transaction (and with it the backfill) completes.
+fn backfill_descending_keys(transaction: web_sys::IdbTransaction, store: web_sys::IdbObjectStore) -> Result<(), RetrievalError> {
+    let request = store.open_cursor().require("open backfill cursor")?;
+    let mut stream = cb_stream(&request, "success", "error");
+    wasm_bindgen_futures::spawn_local(async move {
+        while let Some(result) = stream.next().await {
+            match backfill_next(result) {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(e) => {
+                    warn!("Failed to backfill descending keys: {}", e);
+                    let _ = transaction.abort();
+                    break;
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+fn backfill_next(result: Result<JsValue, web_sys::Event>) -> Result<bool> {
+    let cursor_result = result.require("backfill cursor")?;
+    if cursor_result.is_null() || cursor_result.is_undefined() {
+        return Ok(false);
+    }
+    let cursor = cursor_result.dyn_into::<web_sys::IdbCursorWithValue>().require("cast cursor")?;
+    let entity = Object::new(cursor.value().require("get cursor value")?);
+    if entity.get_opt::<JsValue>(&DESC_KEY)?.is_none() {
+        let id: ankurah_proto::EntityId = entity.get(&ID_KEY)?;
+        let state_buffers: ankurah_proto::StateBuffers = entity.get(&STATE_BUFFER_KEY)?;
+        crate::collection::extract_all_fields(&entity, id, &state_buffers)?;
+        cursor.update(&entity).require("update entity")?;
+    }
+    cursor.continue_().require("advance cursor")?;
+    Ok(true)
+}
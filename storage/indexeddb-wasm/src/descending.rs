@@ -0,0 +1,175 @@
+//! Inverted keys for descending index key parts
+//!
+//! IndexedDB indexes can only be walked in one direction as a whole, so a key like
+//! `(room ASC, timestamp DESC)` cannot be served by a plain compound index. Instead, every
+//! entity carries a `__desc` object mirroring its fields, where each value is replaced by an
+//! inverted binary key (as `ankurah_core::indexing::encoding` does for sled). Descending key
+//! parts index `__desc.<path>`, and ascending order over the inverted keys is descending
+//! order over the original values.
+//!
+//! Values are inverted in their IndexedDB representation (see [`crate::idb_value`]), so the
+//! cross-type ordering IndexedDB applies (number < string < binary < array) is reversed too.
+
+use ankurah_core::collation::Collatable;
+use ankurah_core::indexing::encode_component_typed;
+use ankurah_core::value::{Value, ValueType};
+use wasm_bindgen::JsValue;
+
+use crate::idb_value::{large_integer_key, MAX_SAFE_INTEGER};
+
+/// Property holding the inverted twin of every field
+pub const DESC_FIELD: &str = "__desc";
+
+// IndexedDB orders keys by type before value; the tags keep that order (and invert with the payload)
+const TAG_NUMBER: u8 = 0x10;
+const TAG_STRING: u8 = 0x20;
+const TAG_BINARY: u8 = 0x30;
+const TAG_ARRAY: u8 = 0x40;
+
+/// Key path indexing the inverted twin of `path`
+pub fn key_path(path: &str) -> String { format!("{}.{}", DESC_FIELD, path) }
+
+/// The inverted key for a value, or None if the value is not a valid IndexedDB key
+///
+/// Used both for the stored twin and for query bounds, so the two always agree.
+pub fn invert(value: &Value) -> Option<Value> {
+    let (tag, payload) = match value {
+        Value::I16(x) => number(*x as f64)?,
+        Value::I32(x) => number(*x as f64)?,
+        // Mirrors IdbValue: positive i64 beyond the safe range is stored as a zero-padded string
+        Value::I64(x) if *x > MAX_SAFE_INTEGER => string(&format!("{:020}", x)),
+        Value::I64(x) => number(*x as f64)?,
+        Value::U32(x) => number(*x as f64)?,
+        Value::U64(x) => match large_integer_key(*x as i128, 20) {
+            Some(key) => string(&key),
+            None => number(*x as f64)?,
+        },
+        Value::I128(x) => match large_integer_key(*x, 39) {
+            Some(key) => string(&key),
+            None => number(*x as f64)?,
+        },
+        Value::F64(x) => number(*x)?,
+        Value::Bool(b) => number(if *b { 1.0 } else { 0.0 })?,
+        Value::String(s) => string(s),
+        Value::EntityId(id) => string(&id.to_base64()),
+        Value::Binary(bytes) | Value::Object(bytes) => binary(bytes),
+        Value::Timestamp(t) => return invert(&Value::I64(t.as_micros())),
+        Value::Date(d) => number(d.days_since_epoch() as f64)?,
+        // Mirrors IdbValue: decimals are keyed by their collation bytes
+        Value::Decimal(_) => binary(&value.to_bytes()),
+        Value::Uuid(u) => string(&u.to_string()),
+        Value::Json(json) => return invert_json_scalar(json),
+    };
+    let mut out = Vec::with_capacity(payload.len() + 1);
+    out.push(0xFF - tag);
+    out.extend(payload);
+    Some(Value::Binary(out))
+}
+
+/// The twin stored under `__desc` for a field value. Json objects keep their shape so that
+/// sub-paths (`__desc.data.field`) resolve the same way they do on the original value.
+pub fn twin(value: &Value) -> Option<JsValue> {
+    match value {
+        Value::Json(json) => json_twin(json),
+        other => invert(other).map(|v| crate::idb_value::IdbValue::from(v).into()),
+    }
+}
+
+fn json_twin(json: &serde_json::Value) -> Option<JsValue> {
+    match json {
+        serde_json::Value::Object(map) => {
+            let obj = js_sys::Object::new();
+            for (key, value) in map {
+                if let Some(twin) = json_twin(value) {
+                    js_sys::Reflect::set(&obj, &key.into(), &twin).ok()?;
+                }
+            }
+            Some(obj.into())
+        }
+        scalar => invert_json_scalar(scalar).map(|v| crate::idb_value::IdbValue::from(v).into()),
+    }
+}
+
+fn invert_json_scalar(json: &serde_json::Value) -> Option<Value> {
+    match json {
+        // null is not a valid key, so the entity stays out of the index (as it does for ASC)
+        serde_json::Value::Null | serde_json::Value::Object(_) => None,
+        // Stored with bools as 0/1 (see idb_value::convert_json_bools_to_numbers)
+        serde_json::Value::Bool(b) => invert(&Value::F64(if *b { 1.0 } else { 0.0 })),
+        serde_json::Value::Number(n) => invert(&Value::F64(n.as_f64()?)),
+        serde_json::Value::String(s) => invert(&Value::String(s.clone())),
+        // Arrays sort after every other key type; their elements are not ordered here
+        serde_json::Value::Array(_) => Some(Value::Binary(vec![0xFF - TAG_ARRAY])),
+    }
+}
+
+fn number(x: f64) -> Option<(u8, Vec<u8>)> {
+    if x.is_nan() {
+        return None;
+    }
+    // IndexedDB treats -0 and 0 as the same key
+    let x = if x == 0.0 { 0.0 } else { x };
+    Some((TAG_NUMBER, encode_component_typed(&Value::F64(x), ValueType::F64, true).ok()?))
+}
+
+fn string(s: &str) -> (u8, Vec<u8>) {
+    (TAG_STRING, encode_component_typed(&Value::String(s.to_string()), ValueType::String, true).expect("string encodes as string"))
+}
+
+fn binary(bytes: &[u8]) -> (u8, Vec<u8>) {
+    (TAG_BINARY, encode_component_typed(&Value::Binary(bytes.to_vec()), ValueType::Binary, true).expect("binary encodes as binary"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(value: Value) -> Vec<u8> {
+        match invert(&value) {
+            Some(Value::Binary(bytes)) => bytes,
+            other => panic!("expected an inverted key, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_invert_reverses_order_within_type() {
+        assert!(key(Value::I64(10)) > key(Value::I64(20)));
+        assert!(key(Value::F64(-1.5)) > key(Value::F64(0.0)));
+        assert!(key(Value::String("a".into())) > key(Value::String("ab".into())));
+        assert!(key(Value::String("a".into())) > key(Value::String("a\0".into())));
+        assert!(key(Value::String("b".into())) < key(Value::String("ab".into())));
+        assert!(key(Value::Binary(vec![1])) > key(Value::Binary(vec![1, 0])));
+        assert!(key(Value::Bool(false)) > key(Value::Bool(true)));
+        // Beyond f64 precision
+        let decimal = |s: &str| key(Value::Decimal(s.parse().unwrap()));
+        assert!(decimal("0.1000000000000000001") > decimal("0.1000000000000000002"));
+        assert!(decimal("-5") > decimal("0.1"));
+        assert_eq!(decimal("12.50"), decimal("12.5"));
+    }
+
+    #[test]
+    fn test_invert_reverses_order_across_types() {
+        // IndexedDB: numbers < strings < binary; inverted: binary < strings < numbers
+        assert!(key(Value::I32(1_000_000)) > key(Value::String("0".into())));
+        assert!(key(Value::String("zzz".into())) > key(Value::Binary(vec![0])));
+        // Large i64 values are strings in IndexedDB, so they still sort after every number
+        assert!(key(Value::I64(MAX_SAFE_INTEGER)) > key(Value::I64(MAX_SAFE_INTEGER + 1)));
+    }
+
+    #[test]
+    fn test_invert_agrees_across_numeric_types() {
+        assert_eq!(key(Value::I16(7)), key(Value::I64(7)));
+        assert_eq!(key(Value::I32(7)), key(Value::F64(7.0)));
+        assert_eq!(key(Value::Bool(true)), key(Value::I32(1)));
+        assert_eq!(key(Value::F64(-0.0)), key(Value::F64(0.0)));
+        assert_eq!(invert(&Value::Json(serde_json::json!(7))), invert(&Value::I64(7)));
+        assert_eq!(invert(&Value::Json(serde_json::json!("x"))), invert(&Value::String("x".into())));
+    }
+
+    #[test]
+    fn test_invert_skips_invalid_keys() {
+        assert_eq!(invert(&Value::F64(f64::NAN)), None);
+        assert_eq!(invert(&Value::Json(serde_json::Value::Null)), None);
+        assert_eq!(invert(&Value::Json(serde_json::json!({"a": 1}))), None);
+    }
+}
@@ -17,6 +17,7 @@
 //!
 //! For values requiring full i64 range, consider alternative encoding strategies.
 
+use ankurah_core::collation::Collatable;
 use ankurah_core::value::Value;
 use wasm_bindgen::JsValue;
 
@@ -42,6 +43,11 @@ pub const MAX_SAFE_INTEGER: i64 = 9_007_199_254_740_991;
 #[allow(unused)]
 pub const MIN_SAFE_INTEGER: i64 = -9_007_199_254_740_991;
 
+/// Zero-padded string form of a positive integer beyond the safe range, or None if it
+/// fits in a number. Every type pads to a fixed width (20 digits for i64/u64, 39 for
+/// i128) so that string keys within a field order numerically.
+pub fn large_integer_key(x: i128, digits: usize) -> Option<String> { (x > MAX_SAFE_INTEGER as i128).then(|| format!("{:0digits$}", x)) }
+
 /// IndexedDB-compatible value wrapper
 ///
 /// Provides symmetric encoding/decoding between Ankurah `Value` and JavaScript `JsValue`
@@ -101,11 +107,21 @@ impl From<IdbValue> for JsValue {
                     JsValue::from_str(&format!("{:020}", x))
                 }
             }
+            Value::U32(x) => JsValue::from_f64(x as f64),
+            // Wide integers follow the i64 rules above
+            Value::U64(x) => large_integer_key(x as i128, 20).map_or_else(|| JsValue::from_f64(x as f64), |s| JsValue::from_str(&s)),
+            Value::I128(x) => large_integer_key(x, 39).map_or_else(|| JsValue::from_f64(x as f64), |s| JsValue::from_str(&s)),
             Value::F64(x) => JsValue::from_f64(x),
             Value::Bool(b) => JsValue::from_f64(if b { 1.0 } else { 0.0 }), // IndexedDB keys don't support boolean
             Value::String(s) => JsValue::from_str(&s),
             Value::EntityId(entity_id) => JsValue::from_str(&entity_id.to_base64()),
             Value::Binary(bytes) | Value::Object(bytes) => js_sys::Uint8Array::from(bytes.as_slice()).into(),
+            // Timestamps are keyed by their microseconds, following the i64 rules above
+            Value::Timestamp(t) => IdbValue(Value::I64(t.as_micros())).into(),
+            Value::Date(d) => JsValue::from_f64(d.days_since_epoch() as f64),
+            // Keyed by their order-preserving collation bytes, which keep every digit
+            Value::Decimal(d) => js_sys::Uint8Array::from(Value::Decimal(d).to_bytes().as_slice()).into(),
+            Value::Uuid(u) => JsValue::from_str(&u.to_string()),
             // Json is stored as a parsed JS object to enable IndexedDB's native nested property indexing.
             // IMPORTANT: We must use serialize_maps_as_objects(true) to create plain JS objects,
             // not ES2015 Maps. IndexedDB keyPath traversal only works with plain objects.
@@ -1,5 +1,6 @@
 mod collection;
 pub mod database;
+mod descending;
 mod engine;
 pub(crate) mod error;
 pub mod idb_value;
@@ -1,5 +1,7 @@
-use ankurah_core::value::Value;
-use ankurah_storage_common::{CanonicalRange, Endpoint, KeyBounds, KeyDatum, ScanDirection};
+use ankurah_core::collation::Collatable;
+use ankurah_core::indexing::KeySpec;
+use ankurah_core::value::{Date, Timestamp, Value, ValueType};
+use ankurah_storage_common::{CanonicalRange, Endpoint, KeyBoundComponent, KeyBounds, KeyDatum, ScanDirection};
 use anyhow::Result;
 use wasm_bindgen::JsValue;
 
@@ -10,6 +12,9 @@ fn next_upper_bound(value: &Value) -> Option<(Value, bool)> {
         Value::I16(v) => Some((Value::I16(v.saturating_add(1)), true)),
         Value::I32(v) => Some((Value::I32(v.saturating_add(1)), true)),
         Value::I64(v) => Some((Value::I64(v.saturating_add(1)), true)),
+        Value::U32(v) => Some((Value::U32(v.saturating_add(1)), true)),
+        Value::U64(v) => Some((Value::U64(v.saturating_add(1)), true)),
+        Value::I128(v) => Some((Value::I128(v.saturating_add(1)), true)),
         Value::F64(v) => {
             if v.is_nan() || v.is_infinite() {
                 None
@@ -30,8 +35,62 @@ fn next_upper_bound(value: &Value) -> Option<(Value, bool)> {
             bumped.push('\u{0000}');
             Some((Value::String(bumped), true))
         }
-        Value::Object(_) | Value::Binary(_) | Value::Json(_) => None,
+        Value::Binary(bytes) => {
+            let mut bumped = bytes.clone();
+            bumped.push(0x00);
+            Some((Value::Binary(bumped), true))
+        }
+        Value::Timestamp(t) => Some((Value::Timestamp(Timestamp::from_micros(t.as_micros().saturating_add(1))), true)),
+        Value::Date(d) => Some((Value::Date(Date::from_days_since_epoch(d.days_since_epoch().saturating_add(1))), true)),
+        // Decimals are keyed by their prefix-free collation bytes, so the key extended by 0x00
+        // is the next one up
+        Value::Decimal(_) => value.successor_bytes().map(|bytes| (Value::Binary(bytes), true)),
+        Value::Uuid(u) => {
+            let mut bumped = u.to_string();
+            bumped.push('\u{0000}');
+            Some((Value::String(bumped), true))
+        }
+        Value::Object(_) | Value::Json(_) => None,
+    }
+}
+
+/// Rewrite the bounds of descending key parts in terms of their inverted keys
+///
+/// Descending key parts are indexed on `__desc.<path>` (see [`crate::descending`]), where the
+/// order is reversed: the low endpoint becomes the high one and values are replaced by their
+/// inverted keys.
+pub fn invert_desc_bounds(bounds: &KeyBounds, index_spec: &KeySpec) -> Result<KeyBounds> {
+    let mut keyparts = Vec::with_capacity(bounds.keyparts.len());
+    for bound in &bounds.keyparts {
+        let desc = index_spec.keyparts.iter().any(|kp| kp.full_path() == bound.column && kp.direction.is_desc());
+        if !desc {
+            keyparts.push(bound.clone());
+            continue;
+        }
+        keyparts.push(KeyBoundComponent {
+            column: bound.column.clone(),
+            low: invert_endpoint(&bound.high)?,
+            high: invert_endpoint(&bound.low)?,
+        });
     }
+    Ok(KeyBounds::new(keyparts))
+}
+
+fn invert_endpoint(endpoint: &Endpoint) -> Result<Endpoint> {
+    Ok(match endpoint {
+        Endpoint::UnboundedLow(_) => Endpoint::UnboundedHigh(ValueType::Binary),
+        Endpoint::UnboundedHigh(_) => Endpoint::UnboundedLow(ValueType::Binary),
+        Endpoint::Value { datum, inclusive } => {
+            let datum = match datum {
+                KeyDatum::Val(value) => KeyDatum::Val(
+                    crate::descending::invert(value).ok_or_else(|| anyhow::anyhow!("{:?} cannot bound a descending key part", value))?,
+                ),
+                KeyDatum::NegInfinity(_) => KeyDatum::PosInfinity(ValueType::Binary),
+                KeyDatum::PosInfinity(_) => KeyDatum::NegInfinity(ValueType::Binary),
+            };
+            Endpoint::Value { datum, inclusive: *inclusive }
+        }
+    })
 }
 
 /// Normalize IndexBounds to CanonicalRange following the playbook algorithm
@@ -181,16 +240,21 @@ fn idb_key_tuple(parts: &[Value]) -> Result<JsValue> {
 /// Convert Plan bounds to IndexedDB IdbKeyRange using the new IR pipeline
 /// Returns (IdbKeyRange, upper_open_ended_flag, eq_prefix_len, eq_prefix_values)
 ///
+/// Bounds on descending key parts are inverted to match the `__desc` key paths they index, so
+/// the returned equality prefix values are in index key space.
+///
 /// The `scan_direction` parameter is critical for handling DESC ordering correctly:
 /// - For Reverse (DESC) scans with open-ended lower bounds (e.g., timestamp >= X),
 ///   we must cap the upper bound at the equality prefix boundary to prevent the
 ///   cursor from starting outside the intended key range.
 pub fn plan_bounds_to_idb_range(
+    index_spec: &KeySpec,
     bounds: &KeyBounds,
     scan_direction: &ScanDirection,
 ) -> Result<(web_sys::IdbKeyRange, bool, usize, Vec<Value>)> {
     // Step 1: Normalize IR to CanonicalRange
-    let (canonical_range, eq_prefix_len, eq_prefix_values) = normalize(bounds);
+    let bounds = invert_desc_bounds(bounds, index_spec)?;
+    let (canonical_range, eq_prefix_len, eq_prefix_values) = normalize(&bounds);
 
     // Step 2: For Reverse scans with open-ended upper bound and equality prefix,
     // we need to cap the range to stay within the equality prefix.
@@ -226,6 +290,23 @@ pub fn plan_bounds_to_idb_range(
     Ok((idb_range, upper_open_ended, eq_prefix_len, eq_prefix_values))
 }
 
+/// The member lookup serving a membership (`CONTAINS`) plan, if the plan is one
+///
+/// Such plans key an entity once per member of a collection field. A compound IndexedDB
+/// key path cannot fan out over an array, but a `multiEntry` index over the bare field
+/// can, so these plans are served by looking up the member in that single-part index and
+/// filtering with the whole predicate.
+pub fn element_lookup(index_spec: &KeySpec, bounds: &KeyBounds) -> Option<(KeySpec, Value)> {
+    let element = index_spec.keyparts.iter().find(|kp| kp.is_element())?;
+    let bound = bounds.keyparts.iter().find(|bound| bound.column == element.full_path())?;
+    match (&bound.low, &bound.high) {
+        (Endpoint::Value { datum: KeyDatum::Val(low), .. }, Endpoint::Value { datum: KeyDatum::Val(high), .. }) if low == high => {
+            Some((KeySpec::new(vec![element.clone()]), low.clone()))
+        }
+        _ => None,
+    }
+}
+
 #[allow(unused)]
 pub fn plan_bounds_to_idb_range_syntax(bounds: &KeyBounds) -> Result<String> {
     use std::fmt::Write;
@@ -321,10 +402,42 @@ fn values_to_js_array(values: &[Value]) -> Result<String> {
                 result.push_str(&entity_id.to_base64());
                 result.push('"');
             }
-            Value::Object(_) | Value::Binary(_) | Value::Json(_) => {
+            Value::Timestamp(t) => {
+                // Same as IdbValue: microseconds, zero-padded string beyond the safe range
+                let micros = t.as_micros();
+                if micros > crate::idb_value::MAX_SAFE_INTEGER {
+                    result.push_str(&format!("\"{:020}\"", micros));
+                } else {
+                    result.push_str(&micros.to_string());
+                }
+            }
+            Value::U32(x) => {
+                // Same as: JsValue::from_f64(*x as f64)
+                result.push_str(&x.to_string());
+            }
+            Value::U64(x) => match crate::idb_value::large_integer_key(*x as i128, 20) {
+                // Same as IdbValue: zero-padded string beyond the safe range
+                Some(key) => result.push_str(&format!("\"{}\"", key)),
+                None => result.push_str(&x.to_string()),
+            },
+            Value::I128(x) => match crate::idb_value::large_integer_key(*x, 39) {
+                Some(key) => result.push_str(&format!("\"{}\"", key)),
+                None => result.push_str(&x.to_string()),
+            },
+            Value::Date(d) => {
+                // Same as: JsValue::from_f64(d.days_since_epoch() as f64)
+                result.push_str(&d.days_since_epoch().to_string());
+            }
+            Value::Uuid(u) => {
+                // Same as: JsValue::from_str(&u.to_string())
+                result.push('"');
+                result.push_str(&u.to_string());
+                result.push('"');
+            }
+            Value::Object(_) | Value::Binary(_) | Value::Decimal(_) | Value::Json(_) => {
                 // Same as idb_key_tuple: converts to ArrayBuffer
                 // For syntax generation, we can't easily represent this
-                return Err(anyhow::anyhow!("Object, Binary and Json values not supported in key syntax generation: {:?}", value));
+                return Err(anyhow::anyhow!("Object, Binary, Decimal and Json values not supported in key syntax generation: {:?}", value));
             }
         }
     }
@@ -345,9 +458,8 @@ pub fn scan_direction_to_cursor_direction(scan_direction: &ScanDirection) -> web
 mod tests {
     use super::*;
     use ankql::ast::Predicate;
-    use ankurah_core::indexing::{IndexKeyPart, KeySpec};
-    use ankurah_core::value::ValueType;
-    use ankurah_storage_common::{KeyBoundComponent, OrderByComponents, Plan};
+    use ankurah_core::indexing::IndexKeyPart;
+    use ankurah_storage_common::{OrderByComponents, Plan};
 
     #[test]
     fn test_plan_index_spec_name() {
@@ -434,6 +546,71 @@ mod tests {
         assert_eq!(canonical_range.upper, None);
     }
 
+    #[test]
+    fn test_invert_desc_bounds() {
+        // __collection = 'album' AND year >= 2000 with year DESC in the index
+        let index_spec =
+            KeySpec::new(vec![IndexKeyPart::asc("__collection", ValueType::String), IndexKeyPart::desc("year", ValueType::I64)]);
+        let collection = KeyBoundComponent {
+            column: "__collection".to_string(),
+            low: Endpoint::incl(Value::String("album".to_string())),
+            high: Endpoint::incl(Value::String("album".to_string())),
+        };
+        let bounds = KeyBounds::new(vec![
+            collection.clone(),
+            KeyBoundComponent {
+                column: "year".to_string(),
+                low: Endpoint::incl(Value::I64(2000)),
+                high: Endpoint::UnboundedHigh(ValueType::I64),
+            },
+        ]);
+
+        let inverted = invert_desc_bounds(&bounds, &index_spec).unwrap();
+        let year_2000 = crate::descending::invert(&Value::I64(2000)).unwrap();
+        assert_eq!(inverted.keyparts[0], collection);
+        assert_eq!(
+            inverted.keyparts[1],
+            KeyBoundComponent {
+                column: "year".to_string(),
+                low: Endpoint::UnboundedLow(ValueType::Binary),
+                high: Endpoint::incl(year_2000.clone())
+            }
+        );
+
+        // Only an upper bound in the inverted space: scan from the start of the equality prefix
+        let (canonical_range, eq_prefix_len, _) = normalize(&inverted);
+        assert_eq!(eq_prefix_len, 1);
+        assert_eq!(canonical_range.lower, Some((vec![Value::String("album".to_string())], false)));
+        assert_eq!(canonical_range.upper, Some((vec![Value::String("album".to_string()), year_2000], false)));
+    }
+
+    #[test]
+    fn test_normalize_desc_equality_prefix() {
+        // room = 'a' AND deleted = false on (room ASC, deleted DESC): the inverted equality is bumped like any binary key
+        let index_spec = KeySpec::new(vec![IndexKeyPart::asc("room", ValueType::String), IndexKeyPart::desc("deleted", ValueType::Bool)]);
+        let bounds = KeyBounds::new(vec![
+            KeyBoundComponent {
+                column: "room".to_string(),
+                low: Endpoint::incl(Value::String("a".to_string())),
+                high: Endpoint::incl(Value::String("a".to_string())),
+            },
+            KeyBoundComponent {
+                column: "deleted".to_string(),
+                low: Endpoint::incl(Value::Bool(false)),
+                high: Endpoint::incl(Value::Bool(false)),
+            },
+        ]);
+
+        let (canonical_range, eq_prefix_len, eq_prefix_values) = normalize(&invert_desc_bounds(&bounds, &index_spec).unwrap());
+        let Some(Value::Binary(deleted)) = crate::descending::invert(&Value::Bool(false)) else { panic!("expected an inverted key") };
+        let mut bumped = deleted.clone();
+        bumped.push(0x00);
+        assert_eq!(eq_prefix_len, 2);
+        assert_eq!(eq_prefix_values[1], Value::Binary(deleted.clone()));
+        assert_eq!(canonical_range.lower, Some((vec![Value::String("a".to_string()), Value::Binary(deleted)], false)));
+        assert_eq!(canonical_range.upper, Some((vec![Value::String("a".to_string()), Value::Binary(bumped)], true)));
+    }
+
     #[cfg(target_arch = "wasm32")]
     #[test]
     fn test_plan_bounds_to_idb_range() {
@@ -444,7 +621,8 @@ mod tests {
             high: Endpoint::incl(Value::String("album".to_string())),
         }]);
 
-        let result = plan_bounds_to_idb_range(&bounds, &ScanDirection::Forward);
+        let index_spec = KeySpec::new(vec![IndexKeyPart::asc("__collection", ValueType::String)]);
+        let result = plan_bounds_to_idb_range(&index_spec, &bounds, &ScanDirection::Forward);
         assert!(result.is_ok());
 
         let (_idb_range, upper_open_ended, eq_prefix_len, eq_prefix_values) = result.unwrap();
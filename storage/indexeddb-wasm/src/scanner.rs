@@ -118,7 +118,7 @@ async fn get_next_record(state: ScanState) -> Option<(Result<Object, RetrievalEr
                 for i in 0..(eq_prefix_len as u32) {
                     let lhs = key_arr.get(i);
                     let rhs = &eq_prefix_js[i as usize];
-                    if !js_sys::Object::is(&lhs, rhs) {
+                    if !key_component_eq(&lhs, rhs) {
                         // Prefix doesn't match - end of range
                         return None;
                     }
@@ -143,3 +143,23 @@ async fn get_next_record(state: ScanState) -> Option<(Result<Object, RetrievalEr
     // Return the record and continue scanning
     Some((Ok(entity_obj), Some(ScanState::Scanning { stream, eq_prefix_len, eq_prefix_js })))
 }
+
+/// Compare one component of an index key. Binary keys come back from the cursor as fresh
+/// ArrayBuffers, so they are compared by content rather than identity.
+fn key_component_eq(lhs: &JsValue, rhs: &JsValue) -> bool {
+    if js_sys::Object::is(lhs, rhs) {
+        return true;
+    }
+    match (binary_bytes(lhs), binary_bytes(rhs)) {
+        (Some(lhs), Some(rhs)) => lhs == rhs,
+        _ => false,
+    }
+}
+
+fn binary_bytes(value: &JsValue) -> Option<Vec<u8>> {
+    if value.is_instance_of::<js_sys::ArrayBuffer>() || js_sys::ArrayBuffer::is_view(value) {
+        Some(js_sys::Uint8Array::new(value).to_vec())
+    } else {
+        None
+    }
+}
@@ -12,4 +12,5 @@ lazy_static! {
     pub static ref BODY_KEY: Property = Property::new("__body");
     pub static ref ATTESTATIONS_KEY: Property = Property::new("__attestations");
     pub static ref PARENT_KEY: Property = Property::new("__parent");
+    pub static ref DESC_KEY: Property = Property::new(crate::descending::DESC_FIELD);
 }
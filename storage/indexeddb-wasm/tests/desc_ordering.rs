@@ -1,11 +1,12 @@
 //! DESC ordering tests for IndexedDB.
 //!
-//! Unlike Sled, IndexedDB doesn't support DESC indexes natively. Instead:
-//! - DESC ordering is achieved via `ScanDirection::Reverse` cursor
-//! - Multi-column mixed directions use `order_by_spill` for in-memory sorting
+//! IndexedDB can only walk an index in one direction, so DESC key parts index an inverted
+//! twin of the field (`__desc.<field>`), like Sled's inverted key encoding:
+//! - DESC ordering is a forward scan over the inverted keys
+//! - Multi-column mixed directions are satisfied by a single compound index, without spill sorts
 //!
-//! These tests verify that inequality predicates work correctly with DESC ordering
-//! when using the Reverse scan direction approach.
+//! These tests verify that inequality predicates work correctly with DESC ordering,
+//! where bounds on DESC key parts are inverted (and their endpoints swapped).
 
 mod common;
 
@@ -85,7 +86,7 @@ async fn create_messages(ctx: &ankurah::Context, messages: Vec<(&str, bool, i64,
 // ============================================================================
 
 /// No equality prefix: DESC ordering with inequality on timestamp
-/// Tests Reverse scan direction
+/// Tests the inverted bounds on the DESC key part
 #[wasm_bindgen_test]
 pub async fn test_desc_inequality_no_equality_prefix() -> Result<(), anyhow::Error> {
     let (ctx, db_name) = setup_context().await?;
@@ -492,3 +493,78 @@ pub async fn test_pagination_pattern_desc() -> Result<(), anyhow::Error> {
     IndexedDBStorageEngine::cleanup(&db_name).await?;
     Ok(())
 }
+
+// ============================================================================
+// SECTION 9: Mixed Directions and Inverted Keys
+// ============================================================================
+
+/// Mixed-direction ORDER BY is planned as one compound index with nothing spilled
+#[wasm_bindgen_test]
+pub fn test_mixed_direction_plan_has_no_spill() {
+    use ankurah_storage_common::planner::{Planner, PlannerConfig};
+    use ankurah_storage_common::Plan;
+
+    let planner = Planner::new(PlannerConfig::indexeddb());
+    let selection = ankql::parser::parse_selection("deleted = false ORDER BY room ASC, timestamp DESC").expect("parse selection");
+    let plans = planner.plan(&selection, "id");
+
+    let Some(Plan::Index { index_spec, order_by_spill, .. }) = plans.first() else { panic!("Expected an index plan, got {:?}", plans) };
+    let directions: Vec<_> = index_spec.keyparts.iter().map(|kp| (kp.column.as_str(), kp.direction.is_desc())).collect();
+    assert_eq!(directions, vec![("deleted", false), ("room", false), ("timestamp", true)]);
+    assert!(order_by_spill.spill.is_empty(), "Nothing should be sorted in memory: {:?}", order_by_spill);
+}
+
+/// ORDER BY room ASC, timestamp DESC across several rooms
+#[wasm_bindgen_test]
+pub async fn test_mixed_direction_order_by() -> Result<(), anyhow::Error> {
+    let (ctx, db_name) = setup_context().await?;
+
+    create_messages(
+        &ctx,
+        vec![
+            ("room_b", false, TIMESTAMP_BASE + 1000, "b1"),
+            ("room_a", false, TIMESTAMP_BASE + 1000, "a1"),
+            ("room_b", false, TIMESTAMP_BASE + 3000, "b3"),
+            ("room_a", true, TIMESTAMP_BASE + 4000, "a4 (deleted)"),
+            ("room_a", false, TIMESTAMP_BASE + 2000, "a2"),
+            ("room_b", false, TIMESTAMP_BASE + 2000, "b2"),
+        ],
+    )
+    .await?;
+
+    let results = ctx.fetch::<MessageView>("deleted = false ORDER BY room ASC, timestamp DESC").await?;
+    let texts: Vec<String> = results.iter().map(|m| m.text().unwrap()).collect();
+    assert_eq!(texts, vec!["a2", "a1", "b3", "b2", "b1"]);
+
+    // LIMIT stops the scan; there is no spill sort to fill first
+    let results = ctx.fetch::<MessageView>("deleted = false ORDER BY room ASC, timestamp DESC LIMIT 3").await?;
+    let texts: Vec<String> = results.iter().map(|m| m.text().unwrap()).collect();
+    assert_eq!(texts, vec!["a2", "a1", "b3"]);
+
+    IndexedDBStorageEngine::cleanup(&db_name).await?;
+    Ok(())
+}
+
+/// DESC strings where one value is a prefix of another
+#[wasm_bindgen_test]
+pub async fn test_desc_string_prefixes() -> Result<(), anyhow::Error> {
+    let (ctx, db_name) = setup_context().await?;
+
+    create_log_events(
+        &ctx,
+        vec![("logs", TIMESTAMP_BASE, "a"), ("logs", TIMESTAMP_BASE, "ab"), ("logs", TIMESTAMP_BASE, "b"), ("logs", TIMESTAMP_BASE, "")],
+    )
+    .await?;
+
+    let results = ctx.fetch::<LogEventView>("category = 'logs' ORDER BY level DESC").await?;
+    let levels: Vec<String> = results.iter().map(|e| e.level().unwrap()).collect();
+    assert_eq!(levels, vec!["b", "ab", "a", ""]);
+
+    // Bounds on the DESC key part swap ends in the inverted key space
+    let results = ctx.fetch::<LogEventView>("category = 'logs' AND level >= 'a' AND level < 'b' ORDER BY level DESC").await?;
+    let levels: Vec<String> = results.iter().map(|e| e.level().unwrap()).collect();
+    assert_eq!(levels, vec!["ab", "a"]);
+
+    IndexedDBStorageEngine::cleanup(&db_name).await?;
+    Ok(())
+}
@@ -0,0 +1,506 @@
+//! Btree and GIN indexes on collection state tables
+//!
+//! Indexes are either declared up front with [`Postgres::create_index`](crate::Postgres::create_index)
+//! or derived from the shape of the queries a bucket serves (see [`PostgresIndex::for_selection`]).
+//! JSON paths are indexed as expressions built by [`path_sql`], so a query like
+//! `licensing.territory = 'US'` (emitted as `"licensing"->'territory' = '"US"'::jsonb`) matches
+//! the indexed expression exactly and can use the index.
+//!
+//! Indexes are built and dropped `CONCURRENTLY` so that doing so does not block writers. A
+//! concurrent build waits out every transaction holding an older snapshot, including sessions
+//! blocked on an advisory lock, so builds never hold the collection's DDL lock (which writers
+//! take to add columns). Builds of the same index are serialized under a lock of their own, which
+//! is only ever tried: a session that finds it held leaves the build to its holder.
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, RwLock};
+
+use ankql::ast::{ComparisonOperator, Expr, OrderDirection, PathExpr, Predicate, Selection};
+use ankurah_core::error::{RetrievalError, StateError};
+use ankurah_proto::CollectionId;
+use bb8_postgres::{tokio_postgres::NoTls, PostgresConnectionManager};
+use tracing::{debug, info, warn};
+
+use crate::sql_builder::path_sql;
+use crate::{release_ddl_lock, try_index_build_lock, PostgresColumn};
+
+/// PostgreSQL limits an index to 32 key columns
+const MAX_INDEX_KEYS: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexMethod {
+    /// Equality, range and ORDER BY on columns and JSON paths
+    BTree,
+    /// Containment and key-existence on `jsonb` columns and JSON paths
+    Gin,
+}
+
+/// One key of an index: a column, or a JSON path into a `jsonb` column
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexKey {
+    pub path: PathExpr,
+    pub direction: OrderDirection,
+}
+
+impl IndexKey {
+    /// Ascending key for a dotted path like `year` or `licensing.territory`
+    pub fn asc(path: &str) -> Self { Self { path: dotted(path), direction: OrderDirection::Asc } }
+
+    /// Descending key for a dotted path like `year` or `licensing.territory`
+    pub fn desc(path: &str) -> Self { Self { path: dotted(path), direction: OrderDirection::Desc } }
+
+    /// The indexed column or expression, in the form `CREATE INDEX` expects
+    fn sql(&self) -> String {
+        if self.path.is_simple() {
+            path_sql(&self.path)
+        } else {
+            // Expressions must be parenthesized in an index definition
+            format!("({})", path_sql(&self.path))
+        }
+    }
+}
+
+fn dotted(path: &str) -> PathExpr { PathExpr { steps: path.split('.').map(|step| step.to_string()).collect() } }
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PostgresIndex {
+    pub method: IndexMethod,
+    pub keys: Vec<IndexKey>,
+}
+
+/// An existing index on a state table
+#[derive(Debug, Clone)]
+pub struct IndexInfo {
+    pub name: String,
+    pub definition: String,
+    /// False while a concurrent build is in progress, or after one failed
+    pub valid: bool,
+}
+
+impl PostgresIndex {
+    pub fn btree(keys: Vec<IndexKey>) -> Self { Self { method: IndexMethod::BTree, keys } }
+
+    /// GIN index over a `jsonb` column or JSON path (e.g. `licensing` or `licensing.rights`)
+    pub fn gin(path: &str) -> Self { Self { method: IndexMethod::Gin, keys: vec![IndexKey::asc(path)] } }
+
+    /// The btree index that would serve a query, if any
+    ///
+    /// Keys are the equality (and IN) paths of the top-level conjunction, followed by either the
+    /// first range path or, when there is none, the ORDER BY paths. A selection with no btree
+    /// keys but a membership test (`CONTAINS`, emitted as `@>`) gets a GIN index on the tested
+    /// path instead. Only the pushed-down part of a selection should be passed here, since that
+    /// is what PostgreSQL evaluates.
+    pub fn for_selection(selection: &Selection) -> Option<Self> {
+        let mut equalities: Vec<PathExpr> = Vec::new();
+        let mut range: Option<PathExpr> = None;
+        let mut containment: Option<PathExpr> = None;
+        collect_comparisons(&selection.predicate, &mut equalities, &mut range, &mut containment);
+        // A range path that is also constrained by equality is just another equality key
+        range = range.filter(|path| !equalities.contains(path));
+
+        // Lookups by id are already served by the primary key
+        if equalities.iter().any(|path| path.is_simple() && path.first() == "id") {
+            return None;
+        }
+
+        let mut keys: Vec<IndexKey> = equalities.into_iter().map(|path| IndexKey { path, direction: OrderDirection::Asc }).collect();
+        match range {
+            Some(path) => keys.push(IndexKey { path, direction: OrderDirection::Asc }),
+            None => {
+                let mut order_keys: Vec<IndexKey> = Vec::new();
+                for item in selection.order_by.iter().flatten() {
+                    // ORDER BY is only emitted correctly for columns (see SqlBuilder::order_by_item)
+                    if !item.path.is_simple() || keys.iter().chain(order_keys.iter()).any(|key| key.path == item.path) {
+                        continue;
+                    }
+                    order_keys.push(IndexKey { path: item.path.clone(), direction: item.direction.clone() });
+                }
+                // A btree can be walked backwards, so normalize to an ascending leading order key
+                // and let `ORDER BY a DESC, b ASC` share the index of `ORDER BY a ASC, b DESC`
+                if matches!(order_keys.first(), Some(IndexKey { direction: OrderDirection::Desc, .. })) {
+                    for key in order_keys.iter_mut() {
+                        key.direction = match key.direction {
+                            OrderDirection::Asc => OrderDirection::Desc,
+                            OrderDirection::Desc => OrderDirection::Asc,
+                        };
+                    }
+                }
+                keys.extend(order_keys);
+            }
+        }
+
+        keys.truncate(MAX_INDEX_KEYS);
+        match keys.as_slice() {
+            [] => containment.map(|path| Self { method: IndexMethod::Gin, keys: vec![IndexKey { path, direction: OrderDirection::Asc }] }),
+            [only] if only.path.is_simple() && only.path.first() == "id" => None,
+            _ => Some(Self::btree(keys)),
+        }
+    }
+
+    /// Columns of the state table this index reads
+    pub fn columns(&self) -> Vec<&str> {
+        let mut columns: Vec<&str> = Vec::new();
+        for key in &self.keys {
+            if !columns.contains(&key.path.first()) {
+                columns.push(key.path.first());
+            }
+        }
+        columns
+    }
+
+    fn keys_sql(&self) -> String {
+        self.keys
+            .iter()
+            .map(|key| match (self.method, &key.direction) {
+                (IndexMethod::BTree, OrderDirection::Desc) => format!("{} DESC", key.sql()),
+                _ => key.sql(),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn method_sql(&self) -> &'static str {
+        match self.method {
+            IndexMethod::BTree => "btree",
+            IndexMethod::Gin => "gin",
+        }
+    }
+
+    /// Deterministic name for this index on `table`
+    ///
+    /// Index names share a namespace with tables and are limited to 63 bytes, so the name is a
+    /// readable prefix of the table plus a stable hash of the table and definition.
+    pub fn name(&self, table: &str) -> String {
+        let mut prefix_len = table.len().min(40);
+        while !table.is_char_boundary(prefix_len) {
+            prefix_len -= 1;
+        }
+        let hash = fnv1a(format!("{}:{}:{}", table, self.method_sql(), self.keys_sql()).as_bytes());
+        format!("{}_{:016x}_idx", &table[..prefix_len], hash)
+    }
+
+    pub fn create_sql(&self, table: &str) -> String {
+        format!(
+            r#"CREATE INDEX CONCURRENTLY IF NOT EXISTS "{}" ON "{}" USING {} ({})"#,
+            self.name(table).replace('"', "\"\""),
+            table.replace('"', "\"\""),
+            self.method_sql(),
+            self.keys_sql()
+        )
+    }
+}
+
+fn collect_comparisons(
+    predicate: &Predicate,
+    equalities: &mut Vec<PathExpr>,
+    range: &mut Option<PathExpr>,
+    containment: &mut Option<PathExpr>,
+) {
+    match predicate {
+        Predicate::And(left, right) => {
+            collect_comparisons(left, equalities, range, containment);
+            collect_comparisons(right, equalities, range, containment);
+        }
+        Predicate::Comparison { left, operator, right } => {
+            let path = match (left.as_ref(), right.as_ref()) {
+                (Expr::Path(path), Expr::Literal(_) | Expr::ExprList(_)) | (Expr::Literal(_), Expr::Path(path)) => path,
+                _ => return,
+            };
+            match operator {
+                ComparisonOperator::Equal | ComparisonOperator::In => {
+                    if !equalities.contains(path) {
+                        equalities.push(path.clone());
+                    }
+                }
+                ComparisonOperator::GreaterThan
+                | ComparisonOperator::GreaterThanOrEqual
+                | ComparisonOperator::LessThan
+                | ComparisonOperator::LessThanOrEqual => {
+                    if range.is_none() {
+                        *range = Some(path.clone());
+                    }
+                }
+                ComparisonOperator::Contains => {
+                    if containment.is_none() {
+                        *containment = Some(path.clone());
+                    }
+                }
+                ComparisonOperator::NotEqual | ComparisonOperator::Between => {}
+            }
+        }
+        // OR, NOT and IS NULL are not served by a single btree range
+        _ => {}
+    }
+}
+
+/// 64-bit FNV-1a, used because index names must stay stable across builds (unlike `DefaultHasher`)
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// Creates indexes for declared and observed query shapes, shared by the engine and its buckets
+#[derive(Clone)]
+pub(crate) struct Indexer {
+    pool: bb8::Pool<PostgresConnectionManager<NoTls>>,
+    /// Whether buckets create indexes for the query shapes they serve
+    pub(crate) automatic: bool,
+    /// Index names known to exist and be valid
+    known: Arc<RwLock<HashSet<String>>>,
+    /// Observed index names being built or already built (or deliberately dropped), so each is
+    /// built once. A failed build is forgotten so that a later query retries it.
+    attempted: Arc<Mutex<HashSet<String>>>,
+    /// Declared indexes waiting for their columns to be added
+    pending: Arc<Mutex<Vec<(CollectionId, PostgresIndex)>>>,
+}
+
+impl Indexer {
+    pub(crate) fn new(pool: bb8::Pool<PostgresConnectionManager<NoTls>>) -> Self {
+        Self {
+            pool,
+            automatic: false,
+            known: Arc::new(RwLock::new(HashSet::new())),
+            attempted: Arc::new(Mutex::new(HashSet::new())),
+            pending: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Create a declared index now if its columns exist, or once they are added
+    pub(crate) async fn declare(
+        &self,
+        collection_id: &CollectionId,
+        index: PostgresIndex,
+        columns: &[PostgresColumn],
+    ) -> Result<String, StateError> {
+        let table = collection_id.as_str();
+        let name = index.name(table);
+        let missing: Vec<&str> = index.columns().into_iter().filter(|column| !columns.iter().any(|c| c.name == *column)).collect();
+        if missing.is_empty() {
+            self.ensure(collection_id, &index).await?;
+        } else {
+            info!("Index {} on {} deferred until columns {:?} exist", name, table, missing);
+            let mut pending = self.pending.lock().unwrap();
+            if !pending.iter().any(|(id, pending)| id == collection_id && pending.name(table) == name) {
+                pending.push((collection_id.clone(), index));
+            }
+        }
+        Ok(name)
+    }
+
+    /// Build, in the background, the index serving a query shape (if automatic indexing is on)
+    pub(crate) fn observe(&self, collection_id: &CollectionId, selection: &Selection, columns: &[PostgresColumn]) {
+        if !self.automatic {
+            return;
+        }
+        let Some(index) = PostgresIndex::for_selection(selection) else { return };
+        if !index.columns().iter().all(|column| columns.iter().any(|c| c.name == *column)) {
+            return;
+        }
+        let name = index.name(collection_id.as_str());
+        if self.known.read().unwrap().contains(&name) || !self.attempted.lock().unwrap().insert(name) {
+            return;
+        }
+        self.spawn(collection_id.clone(), index);
+    }
+
+    /// Build, in the background, any declared indexes whose columns now exist
+    pub(crate) fn columns_added(&self, collection_id: &CollectionId, columns: &[PostgresColumn]) {
+        let ready: Vec<PostgresIndex> = {
+            let mut pending = self.pending.lock().unwrap();
+            let (ready, waiting) = pending.drain(..).partition(|(id, index)| {
+                id == collection_id && index.columns().iter().all(|column| columns.iter().any(|c| c.name == *column))
+            });
+            *pending = waiting;
+            ready.into_iter().map(|(_, index)| index).collect()
+        };
+        for index in ready {
+            self.spawn(collection_id.clone(), index);
+        }
+    }
+
+    fn spawn(&self, collection_id: CollectionId, index: PostgresIndex) {
+        let indexer = self.clone();
+        tokio::spawn(async move {
+            if let Err(err) = indexer.ensure(&collection_id, &index).await {
+                let name = index.name(collection_id.as_str());
+                warn!("Failed to create index {} on {}: {}", name, collection_id, err);
+                indexer.attempted.lock().unwrap().remove(&name);
+            }
+        });
+    }
+
+    /// Create the index unless a valid one already exists, or another session is building it.
+    /// Returns true if it was built.
+    pub(crate) async fn ensure(&self, collection_id: &CollectionId, index: &PostgresIndex) -> Result<bool, StateError> {
+        let table = collection_id.as_str();
+        let name = index.name(table);
+        if self.known.read().unwrap().contains(&name) {
+            return Ok(false);
+        }
+
+        let client = self.pool.get().await.map_err(|err| StateError::DDLError(Box::new(err)))?;
+
+        let Some(lock_key) = try_index_build_lock(&client, &name).await? else {
+            debug!("PostgresIndex({}): {} is being built by another session", table, name);
+            return Ok(false);
+        };
+
+        let result = async {
+            // Re-check after acquiring the lock (another session may have built it)
+            let existing = client
+                .query_opt(
+                    r#"SELECT i.indisvalid FROM pg_class c JOIN pg_index i ON i.indexrelid = c.oid
+                       WHERE c.relname = $1 AND pg_table_is_visible(c.oid)"#,
+                    &[&name],
+                )
+                .await
+                .map_err(|err| StateError::DDLError(Box::new(err)))?;
+            match existing.map(|row| row.get::<_, bool>(0)) {
+                Some(true) => return Ok(false),
+                Some(false) => {
+                    // Left behind by an interrupted concurrent build; IF NOT EXISTS would keep it forever
+                    let drop_query = format!(r#"DROP INDEX CONCURRENTLY IF EXISTS "{}""#, name);
+                    info!("PostgresIndex({}): dropping invalid index: {}", table, drop_query);
+                    client.execute(&drop_query, &[]).await.map_err(|err| StateError::DDLError(Box::new(err)))?;
+                }
+                None => {}
+            }
+
+            let create_query = index.create_sql(table);
+            info!("PostgresIndex({}): {}", table, create_query);
+            client.execute(&create_query, &[]).await.map_err(|err| StateError::DDLError(Box::new(err)))?;
+            Ok::<_, StateError>(true)
+        }
+        .await;
+
+        // Always release the lock, even if DDL failed
+        release_ddl_lock(&client, lock_key).await?;
+
+        let created = result?;
+        self.known.write().unwrap().insert(name);
+        Ok(created)
+    }
+
+    pub(crate) async fn list(&self, collection_id: &CollectionId) -> Result<Vec<IndexInfo>, RetrievalError> {
+        let client = self.pool.get().await.map_err(RetrievalError::storage)?;
+        let rows = client
+            .query(
+                r#"SELECT c.relname, pg_get_indexdef(c.oid), i.indisvalid
+                   FROM pg_index i
+                   JOIN pg_class c ON c.oid = i.indexrelid
+                   JOIN pg_class t ON t.oid = i.indrelid
+                   WHERE t.relname = $1 AND pg_table_is_visible(t.oid) AND NOT i.indisprimary
+                   ORDER BY c.relname"#,
+                &[&collection_id.as_str()],
+            )
+            .await
+            .map_err(RetrievalError::storage)?;
+        Ok(rows.into_iter().map(|row| IndexInfo { name: row.get(0), definition: row.get(1), valid: row.get(2) }).collect())
+    }
+
+    /// Drop a (non-primary) index of a state table. Returns false if there was no such index.
+    pub(crate) async fn drop_index(&self, collection_id: &CollectionId, name: &str) -> Result<bool, StateError> {
+        let indexes = self.list(collection_id).await.map_err(|err| StateError::DDLError(Box::new(err)))?;
+        if !indexes.iter().any(|index| index.name == name) {
+            return Ok(false);
+        }
+
+        // No advisory lock: the table lock the drop takes already orders it against a build
+        let client = self.pool.get().await.map_err(|err| StateError::DDLError(Box::new(err)))?;
+        let drop_query = format!(r#"DROP INDEX CONCURRENTLY IF EXISTS "{}""#, name.replace('"', "\"\""));
+        debug!("PostgresIndex({}): {}", collection_id, drop_query);
+        client.execute(&drop_query, &[]).await.map_err(|err| StateError::DDLError(Box::new(err)))?;
+
+        self.known.write().unwrap().remove(name);
+        // Dropping an index is deliberate; don't let the next matching query rebuild it
+        self.attempted.lock().unwrap().insert(name.to_string());
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ankql::parser::parse_selection;
+
+    fn index_for(query: &str) -> Option<PostgresIndex> { PostgresIndex::for_selection(&parse_selection(query).unwrap()) }
+
+    #[test]
+    fn test_json_path_expression_index() {
+        let index = index_for("licensing.territory = 'US'").unwrap();
+        assert_eq!(index, PostgresIndex::btree(vec![IndexKey::asc("licensing.territory")]));
+        let sql = index.create_sql("track");
+        assert!(sql.starts_with(r#"CREATE INDEX CONCURRENTLY IF NOT EXISTS "track_"#), "{}", sql);
+        assert!(sql.ends_with(r#"ON "track" USING btree (("licensing"->'territory'))"#), "{}", sql);
+    }
+
+    #[test]
+    fn test_index_expression_matches_query_expression() {
+        // PostgreSQL only uses an expression index for the exact expression in the query
+        let selection = parse_selection("licensing.rights.holder = 'Label'").unwrap();
+        let mut builder = crate::sql_builder::SqlBuilder::new();
+        builder.selection(&selection).unwrap();
+        let (where_clause, _) = builder.build_where_clause();
+        let index = PostgresIndex::for_selection(&selection).unwrap();
+        let expression = index.keys[0].sql();
+        assert!(where_clause.starts_with(&expression[1..expression.len() - 1]), "{} vs {}", where_clause, expression);
+    }
+
+    #[test]
+    fn test_equalities_then_range() {
+        let index = index_for("year > '2000' AND name = 'Dark Side' AND licensing.territory IN ('US', 'UK')").unwrap();
+        assert_eq!(index.keys, vec![IndexKey::asc("name"), IndexKey::asc("licensing.territory"), IndexKey::asc("year")]);
+        assert_eq!(index.columns(), vec!["name", "licensing", "year"]);
+    }
+
+    #[test]
+    fn test_order_by_keys() {
+        let index = index_for("room = 'a' ORDER BY timestamp DESC, author ASC").unwrap();
+        assert_eq!(index.keys, vec![IndexKey::asc("room"), IndexKey::asc("timestamp"), IndexKey::desc("author")]);
+        assert!(index.create_sql("message").ends_with(r#"USING btree ("room", "timestamp", "author" DESC)"#));
+
+        // Same index for the reversed order
+        assert_eq!(index_for("room = 'a' ORDER BY timestamp ASC, author DESC"), Some(index));
+
+        // A range key takes precedence over ORDER BY
+        let index = index_for("year > '2000' ORDER BY name").unwrap();
+        assert_eq!(index.keys, vec![IndexKey::asc("year")]);
+    }
+
+    #[test]
+    fn test_no_index_for_unindexable_shapes() {
+        assert_eq!(index_for("id = 'D6JdTcX6zwX4FszpjRzUVD46aRE9QMGw7bsS1BcXTI8'"), None);
+        assert_eq!(index_for("name = 'a' OR name = 'b'"), None);
+        assert_eq!(index_for("name <> 'a'"), None);
+        assert_eq!(index_for("true"), None);
+    }
+
+    #[test]
+    fn test_gin_index() {
+        let sql = PostgresIndex::gin("licensing").create_sql("track");
+        assert!(sql.ends_with(r#"ON "track" USING gin ("licensing")"#), "{}", sql);
+    }
+
+    #[test]
+    fn test_membership_uses_gin_index() {
+        assert_eq!(index_for("tags CONTAINS 'urgent'"), Some(PostgresIndex::gin("tags")));
+        // Btree keys take precedence
+        assert_eq!(index_for("room = 'a' AND tags CONTAINS 'urgent'"), Some(PostgresIndex::btree(vec![IndexKey::asc("room")])));
+    }
+
+    #[test]
+    fn test_index_names() {
+        let a = PostgresIndex::btree(vec![IndexKey::asc("name")]);
+        let b = PostgresIndex::btree(vec![IndexKey::desc("name")]);
+        assert_eq!(a.name("album"), a.name("album"));
+        assert_ne!(a.name("album"), b.name("album"));
+        assert_ne!(a.name("album"), a.name("track"));
+
+        let long = "a".repeat(100);
+        assert!(a.name(&long).len() <= 63);
+    }
+}
@@ -14,9 +14,11 @@ use ankurah_proto::{Attestation, AttestationSet, Attested, EntityState, EventBod
 
 use futures_util::{pin_mut, TryStreamExt};
 
+pub mod index;
 pub mod sql_builder;
 pub mod value;
 
+use index::{IndexInfo, Indexer, PostgresIndex};
 use value::PGValue;
 
 use ankurah_proto::{Clock, CollectionId, EntityId, Event};
@@ -36,10 +38,21 @@ pub const DEFAULT_CONNECTION_TIMEOUT_SECS: u64 = 30;
 
 pub struct Postgres {
     pool: bb8::Pool<PostgresConnectionManager<NoTls>>,
+    indexer: Indexer,
 }
 
 impl Postgres {
-    pub fn new(pool: bb8::Pool<PostgresConnectionManager<NoTls>>) -> anyhow::Result<Self> { Ok(Self { pool }) }
+    pub fn new(pool: bb8::Pool<PostgresConnectionManager<NoTls>>) -> anyhow::Result<Self> {
+        Ok(Self { indexer: Indexer::new(pool.clone()), pool })
+    }
+
+    /// Whether collections build btree and GIN indexes for the query shapes they serve. Off by
+    /// default: every distinct shape would otherwise get an index of its own, and each index
+    /// costs every write. Declared indexes ([`Postgres::create_index`]) are created either way.
+    pub fn auto_index(mut self, enabled: bool) -> Self {
+        self.indexer.automatic = enabled;
+        self
+    }
 
     pub async fn open(uri: &str) -> anyhow::Result<Self> {
         let manager = PostgresConnectionManager::new_from_stringlike(uri, NoTls)?;
@@ -51,6 +64,83 @@ impl Postgres {
         Self::new(pool)
     }
 
+    /// List all collections by looking for tables with a matching `_event` table
+    pub async fn list_collections(&self) -> Result<Vec<CollectionId>, RetrievalError> {
+        let client = self.pool.get().await.map_err(RetrievalError::storage)?;
+        let rows = client
+            .query(
+                r#"SELECT s.table_name FROM information_schema.tables s
+                   JOIN information_schema.tables e ON e.table_schema = s.table_schema AND e.table_name = s.table_name || '_event'
+                   WHERE s.table_schema = 'public'
+                   ORDER BY s.table_name"#,
+                &[],
+            )
+            .await
+            .map_err(RetrievalError::storage)?;
+        Ok(rows.into_iter().map(|row| CollectionId::from(row.get::<_, String>("table_name").as_str())).collect())
+    }
+
+    /// Create an index on a collection's state table, returning its name.
+    ///
+    /// If a column the index reads has not been added yet (columns are added as properties are
+    /// first written), the index is created once it is.
+    pub async fn create_index(&self, collection_id: &CollectionId, index: PostgresIndex) -> Result<String, StateError> {
+        let bucket = self.bucket(collection_id).await.map_err(|err| StateError::DDLError(Box::new(err)))?;
+        let columns = bucket.columns.read().unwrap().clone();
+        self.indexer.declare(collection_id, index, &columns).await
+    }
+
+    /// List the secondary indexes of a collection's state table
+    pub async fn list_indexes(&self, collection_id: &CollectionId) -> Result<Vec<IndexInfo>, RetrievalError> {
+        self.indexer.list(collection_id).await
+    }
+
+    /// Drop a secondary index of a collection's state table. Returns false if there was no such
+    /// index. Dropped indexes are not rebuilt automatically until the engine is reopened.
+    pub async fn drop_index(&self, collection_id: &CollectionId, name: &str) -> Result<bool, StateError> {
+        self.indexer.drop_index(collection_id, name).await
+    }
+
+    async fn bucket(&self, collection_id: &CollectionId) -> Result<PostgresBucket, RetrievalError> {
+        if !Postgres::sane_name(collection_id.as_str()) {
+            return Err(RetrievalError::InvalidBucketName);
+        }
+
+        let mut client = self.pool.get().await.map_err(RetrievalError::storage)?;
+
+        // get the current schema from the database
+        let schema = client.query_one("SELECT current_database()", &[]).await.map_err(RetrievalError::storage)?;
+        let schema = schema.get("current_database");
+
+        let bucket = PostgresBucket {
+            pool: self.pool.clone(),
+            schema,
+            collection_id: collection_id.clone(),
+            columns: Arc::new(RwLock::new(Vec::new())),
+            indexer: self.indexer.clone(),
+            #[cfg(debug_assertions)]
+            last_spilled_predicate: Arc::new(RwLock::new(None)),
+        };
+
+        // Acquire advisory lock to serialize DDL operations for this collection
+        let lock_key = acquire_ddl_lock(&client, collection_id.as_str()).await?;
+
+        // Create tables if they don't exist (protected by advisory lock)
+        let result = async {
+            bucket.create_state_table(&mut client).await?;
+            bucket.create_event_table(&mut client).await?;
+            bucket.rebuild_columns_cache(&mut client).await?;
+            Ok::<_, StateError>(())
+        }
+        .await;
+
+        // Always release the lock, even if DDL failed
+        release_ddl_lock(&client, lock_key).await?;
+
+        result?;
+        Ok(bucket)
+    }
+
     // TODO: newtype this to `BucketName(&str)` with a constructor that
     // only accepts a subset of characters.
     pub fn sane_name(collection: &str) -> bool {
@@ -85,6 +175,18 @@ async fn acquire_ddl_lock(client: &tokio_postgres::Client, collection_id: &str)
     Ok(lock_key)
 }
 
+/// Try to take the advisory lock serializing builds of one index, without waiting for it.
+/// Returns `None` if another session holds it.
+async fn try_index_build_lock(client: &tokio_postgres::Client, index_name: &str) -> Result<Option<i64>, StateError> {
+    let lock_key = advisory_lock_key(&format!("ankurah_index:{}", index_name));
+    debug!("Trying advisory lock {} for index {}", lock_key, index_name);
+    let row = client.query_one("SELECT pg_try_advisory_lock($1)", &[&lock_key]).await.map_err(|err| {
+        error!("Failed to try advisory lock for index {}: {:?}", index_name, err);
+        StateError::DDLError(Box::new(err))
+    })?;
+    Ok(row.get::<_, bool>(0).then_some(lock_key))
+}
+
 /// Release a PostgreSQL advisory lock
 async fn release_ddl_lock(client: &tokio_postgres::Client, lock_key: i64) -> Result<(), StateError> {
     debug!("Releasing advisory lock {}", lock_key);
@@ -100,44 +202,11 @@ impl StorageEngine for Postgres {
     type Value = PGValue;
 
     async fn collection(&self, collection_id: &CollectionId) -> Result<std::sync::Arc<dyn StorageCollection>, RetrievalError> {
-        if !Postgres::sane_name(collection_id.as_str()) {
-            return Err(RetrievalError::InvalidBucketName);
-        }
-
-        let mut client = self.pool.get().await.map_err(RetrievalError::storage)?;
-
-        // get the current schema from the database
-        let schema = client.query_one("SELECT current_database()", &[]).await.map_err(RetrievalError::storage)?;
-        let schema = schema.get("current_database");
-
-        let bucket = PostgresBucket {
-            pool: self.pool.clone(),
-            schema,
-            collection_id: collection_id.clone(),
-            columns: Arc::new(RwLock::new(Vec::new())),
-            #[cfg(debug_assertions)]
-            last_spilled_predicate: Arc::new(RwLock::new(None)),
-        };
-
-        // Acquire advisory lock to serialize DDL operations for this collection
-        let lock_key = acquire_ddl_lock(&client, collection_id.as_str()).await?;
-
-        // Create tables if they don't exist (protected by advisory lock)
-        let result = async {
-            bucket.create_state_table(&mut client).await?;
-            bucket.create_event_table(&mut client).await?;
-            bucket.rebuild_columns_cache(&mut client).await?;
-            Ok::<_, StateError>(())
-        }
-        .await;
-
-        // Always release the lock, even if DDL failed
-        release_ddl_lock(&client, lock_key).await?;
-
-        result?;
-        Ok(Arc::new(bucket))
+        Ok(Arc::new(self.bucket(collection_id).await?))
     }
 
+    async fn list_collections(&self) -> Result<Vec<CollectionId>, RetrievalError> { Postgres::list_collections(self).await }
+
     async fn delete_all_collections(&self) -> Result<bool, MutationError> {
         let mut client = self.pool.get().await.map_err(|err| MutationError::General(Box::new(err)))?;
 
@@ -182,6 +251,7 @@ pub struct PostgresBucket {
     collection_id: CollectionId,
     schema: String,
     columns: Arc<RwLock<Vec<PostgresColumn>>>,
+    indexer: Indexer,
     /// Tracks the last predicate that spilled to post-filtering (debug builds only)
     #[cfg(debug_assertions)]
     last_spilled_predicate: Arc<RwLock<Option<ankql::ast::Predicate>>>,
@@ -336,6 +406,9 @@ impl PostgresBucket {
         // Always release the lock
         release_ddl_lock(client, lock_key).await?;
 
+        if result.is_ok() {
+            self.indexer.columns_added(&self.collection_id, &self.columns.read().unwrap());
+        }
         result
     }
 }
@@ -409,6 +482,10 @@ impl StorageCollection for PostgresBucket {
                     PGValue::Bytea(bytes) => params.push(bytes),
                     PGValue::Boolean(bool) => params.push(bool),
                     PGValue::Jsonb(json_val) => params.push(json_val),
+                    PGValue::TimestampTz(timestamp) => params.push(timestamp),
+                    PGValue::Date(date) => params.push(date),
+                    PGValue::Numeric(decimal) => params.push(decimal),
+                    PGValue::Uuid(uuid) => params.push(uuid),
                 },
                 None => params.push(&UntypedNull),
             }
@@ -588,6 +665,9 @@ impl StorageCollection for PostgresBucket {
             },
         };
 
+        // Build (in the background) an index for this query shape if there isn't one yet
+        self.indexer.observe(&self.collection_id, &sql_selection, &self.columns.read().unwrap());
+
         let mut results = Vec::new();
         let mut builder = SqlBuilder::with_fields(vec!["id", "state_buffer", "memberships", "head", "attestations"]);
         builder.table_name(self.state_table());
@@ -1,6 +1,6 @@
-use ankql::ast::{ComparisonOperator, Expr, OrderByItem, OrderDirection, Predicate, Selection};
+use ankql::ast::{ComparisonOperator, Expr, OrderByItem, OrderDirection, PathExpr, Predicate, Selection};
 use ankurah_core::error::RetrievalError;
-use ankurah_core_types::Value;
+use ankurah_core_types::{Decimal, Value};
 use thiserror::Error;
 use tokio_postgres::types::ToSql;
 
@@ -182,6 +182,23 @@ fn can_pushdown_expr(expr: &Expr) -> bool {
     }
 }
 
+/// SQL for a path expression: a quoted column for single-step paths, JSONB traversal otherwise.
+///
+/// Expression indexes (see [`crate::index`]) are built from this same text, since PostgreSQL
+/// only uses an expression index when the query repeats the indexed expression exactly.
+pub fn path_sql(path: &PathExpr) -> String {
+    // Single-step path: regular column reference "column_name"
+    let mut sql = format!(r#""{}""#, path.first().replace('"', "\"\""));
+    // Multi-step path: JSONB traversal "column"->'nested'->'path'
+    // Use -> for ALL steps to preserve JSONB type for proper comparison semantics.
+    // The comparison will use ::jsonb cast on literals to ensure type-aware comparison.
+    for step in path.steps.iter().skip(1) {
+        // Always use -> to keep as JSONB (not ->> which extracts as text)
+        sql += &format!("->'{}'", step.replace('\'', "''"));
+    }
+    sql
+}
+
 impl From<SqlGenerationError> for RetrievalError {
     fn from(err: SqlGenerationError) -> Self { RetrievalError::StorageError(Box::new(err)) }
 }
@@ -289,26 +306,15 @@ impl SqlBuilder {
                 Value::Object(bytes) => self.arg(bytes.clone()),
                 Value::Binary(bytes) => self.arg(bytes.clone()),
                 Value::Json(json) => self.arg(json.clone()),
+                Value::Timestamp(timestamp) => self.arg(*timestamp),
+                Value::Date(date) => self.arg(*date),
+                Value::Decimal(decimal) => self.arg(*decimal),
+                Value::Uuid(uuid) => self.arg(*uuid),
+                Value::U32(i) => self.arg(i64::from(*i)),
+                Value::U64(i) => self.arg(Decimal::from(*i)),
+                Value::I128(i) => self.arg(Decimal::from(*i)),
             },
-            Expr::Path(path) => {
-                if path.is_simple() {
-                    // Single-step path: regular column reference "column_name"
-                    let escaped = path.first().replace('"', "\"\"");
-                    self.sql(format!(r#""{}""#, escaped));
-                } else {
-                    // Multi-step path: JSONB traversal "column"->'nested'->'path'
-                    // Use -> for ALL steps to preserve JSONB type for proper comparison semantics.
-                    // The comparison will use ::jsonb cast on literals to ensure type-aware comparison.
-                    let first = path.first().replace('"', "\"\"");
-                    self.sql(format!(r#""{}""#, first));
-
-                    for step in path.steps.iter().skip(1) {
-                        let escaped = step.replace('\'', "''");
-                        // Always use -> to keep as JSONB (not ->> which extracts as text)
-                        self.sql(format!("->'{}'", escaped));
-                    }
-                }
-            }
+            Expr::Path(path) => self.sql(path_sql(path)),
             Expr::ExprList(exprs) => {
                 self.sql("(");
                 for (i, expr) in exprs.iter().enumerate() {
@@ -328,6 +334,13 @@ impl SqlBuilder {
                             Value::Object(bytes) => self.arg(bytes.clone()),
                             Value::Binary(bytes) => self.arg(bytes.clone()),
                             Value::Json(json) => self.arg(json.clone()),
+                            Value::Timestamp(timestamp) => self.arg(*timestamp),
+                            Value::Date(date) => self.arg(*date),
+                            Value::Decimal(decimal) => self.arg(*decimal),
+                            Value::Uuid(uuid) => self.arg(*uuid),
+                            Value::U32(i) => self.arg(i64::from(*i)),
+                            Value::U64(i) => self.arg(Decimal::from(*i)),
+                            Value::I128(i) => self.arg(Decimal::from(*i)),
                         },
                         _ => {
                             return Err(SqlGenerationError::UnsupportedExpression(
@@ -364,6 +377,9 @@ impl SqlBuilder {
                     Value::Bool(b) => self.sql(format!("'{}'::jsonb", b)),
                     Value::I16(n) => self.sql(format!("'{}'::jsonb", n)),
                     Value::I32(n) => self.sql(format!("'{}'::jsonb", n)),
+                    Value::U32(n) => self.sql(format!("'{}'::jsonb", n)),
+                    Value::U64(n) => self.sql(format!("'{}'::jsonb", n)),
+                    Value::I128(n) => self.sql(format!("'{}'::jsonb", n)),
                     // EntityId and binary types don't make sense as JSONB
                     Value::EntityId(_) | Value::Object(_) | Value::Binary(_) => {
                         // Fall back to regular expression (will likely fail comparison, but that's correct)
@@ -371,6 +387,12 @@ impl SqlBuilder {
                     }
                     // JSON literal is already properly typed
                     Value::Json(json) => self.sql(format!("'{}'::jsonb", json)),
+                    // JSON documents hold these as strings (their text never needs escaping)
+                    // and decimals as numbers
+                    Value::Timestamp(timestamp) => self.sql(format!("'\"{}\"'::jsonb", timestamp)),
+                    Value::Date(date) => self.sql(format!("'\"{}\"'::jsonb", date)),
+                    Value::Uuid(uuid) => self.sql(format!("'\"{}\"'::jsonb", uuid)),
+                    Value::Decimal(decimal) => self.sql(format!("'{}'::jsonb", decimal)),
                 }
                 Ok(())
             }
@@ -386,6 +408,18 @@ impl SqlBuilder {
 
     pub fn predicate(&mut self, predicate: &Predicate) -> Result<(), SqlGenerationError> {
         match predicate {
+            // Collections are jsonb arrays, so membership is array containment
+            Predicate::Comparison { left, operator: ComparisonOperator::Contains, right } => {
+                self.expr(left)?;
+                self.sql(" @> jsonb_build_array(");
+                match right.as_ref() {
+                    // JSON documents hold ids as base64 strings (which never need escaping)
+                    Expr::Literal(Value::EntityId(id)) => self.sql(format!("'\"{}\"'::jsonb", id.to_base64())),
+                    Expr::Literal(_) => self.expr_as_jsonb(right)?,
+                    _ => return Err(SqlGenerationError::UnsupportedExpression("CONTAINS requires a literal member")),
+                }
+                self.sql(")");
+            }
             Predicate::Comparison { left, operator, right } => {
                 // Check if either side is a JSONB path (multi-step path)
                 // TODO: Replace path depth heuristic with schema metadata when available.
@@ -498,6 +532,7 @@ fn comparison_op_to_sql(op: &ComparisonOperator) -> Result<&'static str, SqlGene
         ComparisonOperator::LessThanOrEqual => "<=",
         ComparisonOperator::In => "IN",
         ComparisonOperator::Between => return Err(SqlGenerationError::UnsupportedOperator("BETWEEN operator is not yet supported")),
+        ComparisonOperator::Contains => return Err(SqlGenerationError::UnsupportedOperator("CONTAINS is emitted as jsonb containment")),
     })
 }
 
@@ -605,6 +640,22 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_contains_operator() -> Result<()> {
+        let selection = parse_selection("tags CONTAINS 'urgent' AND 'ops' IN tags").unwrap();
+        let mut sql = SqlBuilder::with_fields(vec!["id"]);
+        sql.table_name("tasks");
+        sql.selection(&selection)?;
+        let (sql_string, args) = sql.build()?;
+
+        assert_eq!(
+            sql_string,
+            r#"SELECT "id" FROM "tasks" WHERE "tags" @> jsonb_build_array('"urgent"'::jsonb) AND "tags" @> jsonb_build_array('"ops"'::jsonb)"#
+        );
+        assert!(args.is_empty());
+        Ok(())
+    }
+
     #[test]
     fn test_placeholder_error() {
         let mut sql = SqlBuilder::with_fields(vec!["id"]);
@@ -1,6 +1,6 @@
 // use tokio_postgres::types::ToSql;
 
-use ankurah_core::value::Value;
+use ankurah_core::value::{Date, Decimal, Timestamp, Uuid, Value};
 
 #[derive(Debug)]
 pub enum PGValue {
@@ -14,6 +14,10 @@ pub enum PGValue {
     /// JSON value - stored as PostgreSQL's native jsonb type for query support.
     /// Uses serde_json::Value for proper type conversion via tokio-postgres.
     Jsonb(serde_json::Value),
+    TimestampTz(Timestamp),
+    Date(Date),
+    Numeric(Decimal),
+    Uuid(Uuid),
 }
 
 impl PGValue {
@@ -27,6 +31,10 @@ impl PGValue {
             PGValue::Bytea(_) => "bytea",
             PGValue::Boolean(_) => "boolean",
             PGValue::Jsonb(_) => "jsonb",
+            PGValue::TimestampTz(_) => "timestamptz",
+            PGValue::Date(_) => "date",
+            PGValue::Numeric(_) => "numeric",
+            PGValue::Uuid(_) => "uuid",
         }
     }
 }
@@ -45,6 +53,14 @@ impl From<Value> for PGValue {
             Value::Binary(items) => PGValue::Bytea(items),
             // Value::Json already contains serde_json::Value
             Value::Json(json) => PGValue::Jsonb(json),
+            Value::Timestamp(timestamp) => PGValue::TimestampTz(timestamp),
+            Value::Date(date) => PGValue::Date(date),
+            Value::Decimal(decimal) => PGValue::Numeric(decimal),
+            Value::Uuid(uuid) => PGValue::Uuid(uuid),
+            Value::U32(integer) => PGValue::BigInt(integer.into()),
+            // Past the range of int8, so they go to NUMERIC
+            Value::U64(integer) => PGValue::Numeric(integer.into()),
+            Value::I128(integer) => PGValue::Numeric(integer.into()),
         }
     }
 }
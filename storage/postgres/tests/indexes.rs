@@ -0,0 +1,138 @@
+//! PostgreSQL Index Tests
+//!
+//! Verifies that indexes are created on state tables, both declared up front with
+//! `Postgres::create_index` and derived from the shape of queries as they are served,
+//! and that JSON path expression indexes are actually used by the queries `sql_builder` emits.
+
+mod common;
+
+use ankurah::property::Json;
+use ankurah::proto::CollectionId;
+use ankurah::{policy::DEFAULT_CONTEXT as c, Model, Node, PermissiveAgent};
+use ankurah_storage_postgres::index::{IndexKey, PostgresIndex};
+use ankurah_storage_postgres::Postgres;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Model, Debug, Serialize, Deserialize, Clone)]
+pub struct Track {
+    pub name: String,
+    pub licensing: Json,
+}
+
+async fn connect(container: &testcontainers::ContainerAsync<testcontainers_modules::postgres::Postgres>) -> Result<tokio_postgres::Client> {
+    let host = container.get_host().await?;
+    let port = container.get_host_port_ipv4(5432).await?;
+    let (client, connection) =
+        tokio_postgres::connect(&format!("host={host} port={port} user=postgres password=postgres dbname=ankurah"), tokio_postgres::NoTls)
+            .await?;
+    tokio::spawn(async move {
+        let _ = connection.await;
+    });
+    Ok(client)
+}
+
+/// Wait for a background index build to show up
+async fn wait_for_index(storage: &Postgres, collection: &CollectionId, name: &str) -> Result<()> {
+    for _ in 0..100 {
+        if storage.list_indexes(collection).await?.iter().any(|index| index.name == name && index.valid) {
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    anyhow::bail!("index {} was not created", name)
+}
+
+async fn create_tracks(ctx: &ankurah::Context, count: usize) -> Result<()> {
+    let trx = ctx.begin();
+    for i in 0..count {
+        let territory = if i % 2 == 0 { "US" } else { "UK" };
+        trx.create(&Track { name: format!("Track {i}"), licensing: Json::new(serde_json::json!({"territory": territory, "plays": i})) })
+            .await?;
+    }
+    trx.commit().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_declared_json_path_index_is_used() -> Result<()> {
+    let (container, storage) = common::create_postgres_container().await?;
+    let storage = Arc::new(storage);
+    let node = Node::new_durable(storage.clone(), PermissiveAgent::new());
+    node.system.create().await?;
+    let ctx = node.context_async(c).await;
+
+    create_tracks(&ctx, 20).await?;
+
+    let collection = CollectionId::from("track");
+    let name = storage.create_index(&collection, PostgresIndex::btree(vec![IndexKey::asc("licensing.territory")])).await?;
+    let indexes = storage.list_indexes(&collection).await?;
+    let index = indexes.iter().find(|index| index.name == name).expect("declared index should exist");
+    assert!(index.valid);
+    assert!(index.definition.contains("(licensing -> 'territory'::text)"), "{}", index.definition);
+
+    // Declaring it again is a no-op
+    assert_eq!(storage.create_index(&collection, PostgresIndex::btree(vec![IndexKey::asc("licensing.territory")])).await?, name);
+    assert_eq!(storage.list_indexes(&collection).await?.len(), indexes.len());
+
+    // The planner uses the expression index for the exact expression sql_builder emits
+    let client = connect(&container).await?;
+    client.execute("SET enable_seqscan = off", &[]).await?;
+    let plan: Vec<String> = client
+        .query(r#"EXPLAIN SELECT "id" FROM "track" WHERE "licensing"->'territory' = '"US"'::jsonb"#, &[])
+        .await?
+        .iter()
+        .map(|row| row.get(0))
+        .collect();
+    assert!(plan.iter().any(|line| line.contains(&name)), "expected {} in plan: {:#?}", name, plan);
+
+    let us_tracks: Vec<TrackView> = ctx.fetch("licensing.territory = 'US'").await?;
+    assert_eq!(us_tracks.len(), 10);
+
+    assert!(storage.drop_index(&collection, &name).await?);
+    assert!(!storage.drop_index(&collection, &name).await?);
+    assert!(storage.list_indexes(&collection).await?.iter().all(|index| index.name != name));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_index_created_for_observed_query() -> Result<()> {
+    let (_container, storage) = common::create_postgres_container().await?;
+    let storage = Arc::new(storage.auto_index(true));
+    let node = Node::new_durable(storage.clone(), PermissiveAgent::new());
+    node.system.create().await?;
+    let ctx = node.context_async(c).await;
+
+    create_tracks(&ctx, 4).await?;
+
+    let collection = CollectionId::from("track");
+    let expected = PostgresIndex::btree(vec![IndexKey::asc("licensing.territory"), IndexKey::asc("name")]).name("track");
+
+    let tracks: Vec<TrackView> = ctx.fetch("licensing.territory = 'UK' ORDER BY name DESC").await?;
+    assert_eq!(tracks.iter().map(|t| t.name().unwrap()).collect::<Vec<_>>(), vec!["Track 3", "Track 1"]);
+    wait_for_index(&storage, &collection, &expected).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_declared_index_waits_for_column() -> Result<()> {
+    let (_container, storage) = common::create_postgres_container().await?;
+    let storage = Arc::new(storage);
+    let node = Node::new_durable(storage.clone(), PermissiveAgent::new());
+    node.system.create().await?;
+    let ctx = node.context_async(c).await;
+
+    // The licensing column doesn't exist until a track is written
+    let collection = CollectionId::from("track");
+    let name = storage.create_index(&collection, PostgresIndex::gin("licensing")).await?;
+    assert!(storage.list_indexes(&collection).await?.iter().all(|index| index.name != name));
+
+    create_tracks(&ctx, 1).await?;
+    wait_for_index(&storage, &collection, &name).await?;
+
+    Ok(())
+}
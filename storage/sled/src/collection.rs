@@ -2,14 +2,14 @@
 use std::sync::atomic::AtomicBool;
 
 use ankql::ast::Predicate;
-use ankurah_core::indexing::KeySpec;
+use ankurah_core::indexing::IndexSpecMatch;
 use ankurah_core::{
     entity::TemporaryEntity,
     error::{MutationError, RetrievalError},
-    storage::StorageCollection,
+    storage::{StorageCollection, VerifyOptions, VerifyReport},
     EntityId,
 };
-use ankurah_proto::{Attested, CollectionId, EntityState, Event, EventId, StateFragment};
+use ankurah_proto::{Attested, CollectionId, EntityState, Event, EventId, State, StateFragment};
 use ankurah_storage_common::{filtering::ValueSetStream, KeyBounds, OrderByComponents, Plan, Planner, PlannerConfig, ScanDirection};
 use async_trait::async_trait;
 use futures::StreamExt;
@@ -25,6 +25,7 @@ use crate::scan_index::SledIndexScanner;
 use crate::{
     database::Database,
     error::{sled_error, SledRetrievalError},
+    index::Index,
 };
 use ankurah_storage_common::traits::{EntityIdStream, EntityStateStream};
 
@@ -92,11 +93,18 @@ impl StorageCollection for SledStorageCollection {
         let inner = self.0.clone();
         Ok(task::spawn_blocking(move || inner.dump_entity_events_blocking(entity_id)).await??)
     }
+
+    async fn verify(&self, options: VerifyOptions) -> Result<VerifyReport, RetrievalError> {
+        let (database, collection_id) = (self.0.database.clone(), self.0.collection_id.clone());
+        let mut scanned =
+            task::spawn_blocking(move || crate::verify::scan_events_blocking(&database, Some(&collection_id), options)).await??;
+        self.0.verify_scanned(scanned.remove(&self.0.collection_id).unwrap_or_default(), options).await
+    }
 }
 
 impl SledStorageCollectionInner {
     // I think this one is done - did it myself
-    fn set_state_blocking(&self, state: Attested<EntityState>) -> Result<bool, MutationError> {
+    pub(crate) fn set_state_blocking(&self, state: Attested<EntityState>) -> Result<bool, MutationError> {
         let (entity_id, collection, sfrag) = state.to_parts();
         if self.collection_id != collection {
             return Err(MutationError::General(anyhow::anyhow!("Collection ID mismatch").into()));
@@ -109,15 +117,7 @@ impl SledStorageCollectionInner {
         let changed = if let Some(last_bytes) = last { last_bytes != binary_state } else { true };
 
         // 2) Write-time materialization into collection_{collection}
-        let entity = TemporaryEntity::new(entity_id, collection, &sfrag.state)?;
-
-        // Compact property IDs and materialized list
-        let mut mat: Vec<(u32, ankurah_core::value::Value)> = Vec::new();
-        for (name, opt_val) in entity.values().into_iter() {
-            if let Some(val) = opt_val {
-                mat.push((self.database.property_manager.get_property_id(&name)?, val));
-            }
-        }
+        let mat = self.materialize(entity_id, &sfrag.state)?;
 
         let mat_bytes = bincode::serialize(&mat)?;
         let old_mat_bytes = self.tree.insert(entity_id.to_bytes(), mat_bytes).map_err(|e| MutationError::UpdateFailed(Box::new(e)))?;
@@ -131,6 +131,17 @@ impl SledStorageCollectionInner {
 
         Ok(changed)
     }
+    /// The compact `(property id, value)` list stored in the collection tree for a state.
+    pub(crate) fn materialize(&self, entity_id: EntityId, state: &State) -> Result<Vec<(u32, ankurah_core::value::Value)>, RetrievalError> {
+        let entity = TemporaryEntity::new(entity_id, self.collection_id.clone(), state)?;
+        let mut mat = Vec::new();
+        for (name, opt_val) in entity.values().into_iter() {
+            if let Some(val) = opt_val {
+                mat.push((self.database.property_manager.get_property_id(&name)?, val));
+            }
+        }
+        Ok(mat)
+    }
     // I think this one is done - did it myself
     fn get_state_blocking(&self, id: EntityId) -> Result<Attested<EntityState>, RetrievalError> {
         match self.database.entities_tree.get(id.to_bytes()).map_err(sled_error)? {
@@ -158,29 +169,39 @@ impl SledStorageCollectionInner {
         // Type resolution (Value -> Json for non-simple paths) is handled by TypeResolver
         // at the entry points (Context/Node). The selection here is already type-resolved.
 
-        // Generate query plans and choose the first non-empty one
+        // Generate query plans and choose the first one we can execute now
         let plans = Planner::new(PlannerConfig::full_support()).plan(&selection, "id");
 
-        let plan = plans.into_iter().next().ok_or_else(|| RetrievalError::StorageError("No plan generated".into()))?;
-
         // Execute the chosen plan using streaming pipeline architecture
-        match plan {
-            Plan::EmptyScan => Ok(Vec::new()),
-
-            Plan::Index { index_spec, bounds, scan_direction, remaining_predicate, order_by_spill } =>
-            //
-            {
-                self.exec_index_scan_plan(index_spec, bounds, scan_direction, remaining_predicate, order_by_spill, selection.limit)
-            }
+        for plan in plans {
+            match plan {
+                Plan::EmptyScan => return Ok(Vec::new()),
+
+                Plan::Index { index_spec, bounds, scan_direction, remaining_predicate, order_by_spill } => {
+                    // An index still building in the background can't answer yet; the
+                    // planner always ends with a table scan to fall back on.
+                    let Some(index) = self.database.index_manager.find_ready(
+                        self.collection_id.as_str(),
+                        &index_spec,
+                        &self.database.db,
+                        &self.database.property_manager,
+                    )?
+                    else {
+                        continue;
+                    };
+                    return self.exec_index_scan_plan(index, bounds, scan_direction, remaining_predicate, order_by_spill, selection.limit);
+                }
 
-            Plan::TableScan { bounds, scan_direction, remaining_predicate, order_by_spill } => {
-                self.exec_table_scan_plan(bounds, scan_direction, remaining_predicate, order_by_spill, selection.limit)
+                Plan::TableScan { bounds, scan_direction, remaining_predicate, order_by_spill } => {
+                    return self.exec_table_scan_plan(bounds, scan_direction, remaining_predicate, order_by_spill, selection.limit);
+                }
             }
         }
+        Err(RetrievalError::StorageError("No plan generated".into()))
     }
     fn exec_index_scan_plan(
         &self,
-        index_spec: KeySpec,
+        (index, match_type): (Index, IndexSpecMatch),
         bounds: KeyBounds,
         scan_direction: ScanDirection,
         remaining_predicate: Predicate,
@@ -198,13 +219,6 @@ impl SledStorageCollectionInner {
             false
         };
 
-        let (index, match_type) = self.database.index_manager.assure_index_exists(
-            self.collection_id.as_str(),
-            &index_spec,
-            &self.database.db,
-            &self.database.property_manager,
-        )?;
-
         let ids = SledIndexScanner::new(&index, &bounds, scan_direction, match_type, prefix_guard_disabled)?;
 
         if remaining_predicate == Predicate::True && order_by_spill.is_satisfied() {
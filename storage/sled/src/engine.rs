@@ -6,13 +6,19 @@ use std::sync::{Arc, Mutex};
 
 use ankurah_core::{
     error::{MutationError, RetrievalError},
-    storage::{StorageCollection, StorageEngine},
+    storage::{local::is_node_local, StorageCollection, StorageEngine, VerifyOptions, VerifyReport},
 };
 use ankurah_proto::CollectionId;
 use async_trait::async_trait;
 use sled::Config;
 
-use crate::{collection::SledStorageCollection, database::Database, error::SledRetrievalError};
+use crate::{
+    collection::{SledStorageCollection, SledStorageCollectionInner},
+    database::Database,
+    error::SledRetrievalError,
+    index::IndexInfo,
+    verify::scan_events_blocking,
+};
 
 pub struct SledStorageEngine {
     pub database: Mutex<Arc<Database>>,
@@ -80,6 +86,53 @@ impl SledStorageEngine {
             .collect();
         Ok(collections)
     }
+
+    /// Discard and backfill every secondary index, or only those of `collection`.
+    /// Returns the number of indexes rebuilt.
+    pub async fn rebuild_indexes(&self, collection: Option<&CollectionId>) -> Result<usize, RetrievalError> {
+        let database = self.database.lock().unwrap().clone();
+        let collection = collection.map(|c| c.as_str().to_owned());
+        tokio::task::spawn_blocking(move || database.index_manager.rebuild(collection.as_deref(), &database.db)).await?
+    }
+
+    /// Every secondary index, or only those of `collection`, with its build
+    /// status, size and progress.
+    pub async fn list_indexes(&self, collection: Option<&CollectionId>) -> Result<Vec<IndexInfo>, RetrievalError> {
+        let database = self.database.lock().unwrap().clone();
+        let collection = collection.map(|c| c.as_str().to_owned());
+        tokio::task::spawn_blocking(move || database.index_manager.list(collection.as_deref())).await?
+    }
+
+    /// Drop a secondary index by id, stopping any build in progress.
+    /// Returns false if there is no such index.
+    pub async fn drop_index(&self, id: u32) -> Result<bool, RetrievalError> {
+        let database = self.database.lock().unwrap().clone();
+        tokio::task::spawn_blocking(move || database.index_manager.drop_index(id, &database.db)).await?
+    }
+
+    /// Verify every collection, reading the global events tree once for all
+    /// of them rather than once per collection as `StorageCollection::verify`
+    /// has to. Node-local collections (outbox, replication cursors) hold
+    /// records with no event log behind them and are skipped.
+    pub async fn verify(&self, options: VerifyOptions) -> Result<Vec<(CollectionId, VerifyReport)>, RetrievalError> {
+        let database = self.database.lock().unwrap().clone();
+        let scan_database = database.clone();
+        let mut scanned = tokio::task::spawn_blocking(move || scan_events_blocking(&scan_database, None, options)).await??;
+        let mut reports = Vec::new();
+        for collection_id in self.list_collections()?.into_iter().filter(|c| !is_node_local(c)) {
+            let tree = database.db.open_tree(format!("collection_{collection_id}")).map_err(SledRetrievalError::StorageError)?;
+            let inner = SledStorageCollectionInner {
+                collection_id: collection_id.clone(),
+                database: database.clone(),
+                tree,
+                #[cfg(debug_assertions)]
+                prefix_guard_disabled: self.prefix_guard_disabled.clone(),
+            };
+            let report = inner.verify_scanned(scanned.remove(&collection_id).unwrap_or_default(), options).await?;
+            reports.push((collection_id, report));
+        }
+        Ok(reports)
+    }
 }
 
 #[async_trait]
@@ -100,6 +153,8 @@ impl StorageEngine for SledStorageEngine {
         )))
     }
 
+    async fn list_collections(&self) -> Result<Vec<CollectionId>, RetrievalError> { SledStorageEngine::list_collections(self) }
+
     async fn delete_all_collections(&self) -> Result<bool, MutationError> {
         let mut any_deleted = false;
 
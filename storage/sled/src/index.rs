@@ -2,10 +2,13 @@ use ankurah_core::error::{MutationError, RetrievalError};
 use ankurah_core::indexing::IndexSpecMatch;
 use ankurah_proto::EntityId;
 use serde::{Deserialize, Serialize};
-use sled::{Db, Tree};
-use std::collections::HashMap;
+use sled::transaction::{ConflictableTransactionError, TransactionError, TransactionalTree};
+use sled::{Db, Transactional, Tree};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 // use std::ops::Deref;
-use std::sync::{Arc, Mutex, RwLock};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex, RwLock};
+use tracing::warn;
 
 use crate::{error::IndexError, planner_integration::encode_tuple_values_with_key_spec, property::PropertyManager};
 
@@ -24,8 +27,54 @@ pub struct IndexRecord {
     pub spec: ankurah_core::indexing::KeySpec,
     pub created_at_unix_ms: i64,
     pub build_status: BuildStatus,
+    /// Keys in the index tree and their total size, updated in the same
+    /// transaction as the keys themselves
+    pub entries: u64,
+    pub bytes: u64,
 }
 
+/// An [`IndexRecord`] as stored before it kept its entry counts
+#[derive(Deserialize)]
+struct LegacyIndexRecord {
+    id: u32,
+    collection: String,
+    name: String,
+    spec: ankurah_core::indexing::KeySpec,
+    created_at_unix_ms: i64,
+    build_status: BuildStatus,
+}
+
+/// How far a build has got through the collection's materialized entities.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BuildProgress {
+    pub scanned: u64,
+    /// Entities in the collection when the build (or its resumption) started
+    pub total: u64,
+}
+
+/// A snapshot of one index, as returned by [`IndexManager::list`].
+#[derive(Debug, Clone)]
+pub struct IndexInfo {
+    pub id: u32,
+    pub collection: String,
+    pub name: String,
+    pub spec: ankurah_core::indexing::KeySpec,
+    pub status: BuildStatus,
+    pub created_at_unix_ms: i64,
+    pub entries: u64,
+    /// Total size of the index keys, in bytes
+    pub bytes: u64,
+    /// Present while a build is under way in this process
+    pub progress: Option<BuildProgress>,
+}
+
+/// Entities backfilled per build batch, each batch holding the build lock.
+pub const DEFAULT_BUILD_BATCH_SIZE: usize = 1024;
+
+/// Persisted build cursors, keyed by index id: the last entity backfilled
+/// by an unfinished build, so it can resume after a restart.
+const BUILD_CURSOR_TREE: &str = "index_build_cursor";
+
 #[derive(Clone)]
 pub struct Index(Arc<IndexInner>);
 
@@ -37,15 +86,32 @@ struct IndexInner {
     pub spec: ankurah_core::indexing::KeySpec,
     pub created_at_unix_ms: i64,
     build_status: Mutex<BuildStatus>,
+    /// Held for each build batch; guards `cursor`.
     pub build_lock: Mutex<()>,
+    /// The last entity a build has backfilled. Writers maintain a building
+    /// index only up to here; the build picks up everything after it.
+    cursor: Mutex<Option<EntityId>>,
+    /// Set once a build batch has run in this process
+    progress: Mutex<Option<BuildProgress>>,
+    builder_running: AtomicBool,
+    dropped: AtomicBool,
+    /// Keys in `tree` and their total size, loaded from the index record and
+    /// moved by the same deltas every write stores in it.
+    entries: AtomicU64,
+    bytes: AtomicU64,
     pub tree: Tree,
     pub index_config_tree: Tree,
+    cursor_tree: Tree,
     pub property_manager: PropertyManager,
 }
 
 pub struct IndexManager {
     pub index_config_tree: Tree,
     pub indexes: RwLock<HashMap<u32, Index>>,
+    build_batch_size: AtomicUsize,
+    /// Queue of the single background builder thread, started on first use.
+    /// Builds run one at a time however many query shapes ask for indexes.
+    builder: Mutex<Option<mpsc::Sender<(Index, usize)>>>,
 }
 
 impl IndexManager {
@@ -54,13 +120,28 @@ impl IndexManager {
         for item in index_config_tree.iter() {
             let (key, bytes) = item?;
             let key = u32::from_be_bytes(key.as_ref().try_into().map_err(|_| IndexError::InvalidKeyLength)?);
-            if let Ok(mut rec) = bincode::deserialize::<IndexRecord>(&bytes) {
-                // Trust key as source of truth for id
-                rec.id = key;
-                indexes.insert(key, Index::from_record(rec, db, index_config_tree.clone(), property_manager.clone())?);
-            }
+            let mut rec = match bincode::deserialize::<IndexRecord>(&bytes) {
+                Ok(rec) => rec,
+                Err(_) => match bincode::deserialize::<LegacyIndexRecord>(&bytes) {
+                    // Counted once, then stored with the record
+                    Ok(legacy) => {
+                        let rec = legacy.counted(db)?;
+                        index_config_tree.insert(key.to_be_bytes(), bincode::serialize(&rec)?)?;
+                        rec
+                    }
+                    Err(_) => continue,
+                },
+            };
+            // Trust key as source of truth for id
+            rec.id = key;
+            indexes.insert(key, Index::from_record(rec, db, index_config_tree.clone(), property_manager.clone())?);
         }
-        Ok(Self { index_config_tree, indexes: RwLock::new(indexes) })
+        Ok(Self {
+            index_config_tree,
+            indexes: RwLock::new(indexes),
+            build_batch_size: AtomicUsize::new(DEFAULT_BUILD_BATCH_SIZE),
+            builder: Mutex::new(None),
+        })
     }
 
     pub fn next_index_id(&self) -> Result<u32, IndexError> {
@@ -72,12 +153,83 @@ impl IndexManager {
         }
     }
 
+    /// Entities backfilled per build batch. Smaller batches hold the build
+    /// lock (and so stall concurrent writers to the index) for less time.
+    pub fn set_build_batch_size(&self, size: usize) { self.build_batch_size.store(size.max(1), Ordering::Relaxed); }
+    pub fn build_batch_size(&self) -> usize { self.build_batch_size.load(Ordering::Relaxed) }
+
+    /// Find or create an index matching `spec` and build it to completion
+    /// before returning.
     pub fn assure_index_exists(
         &self,
         collection: &str,
         spec: &ankurah_core::indexing::KeySpec,
         db: &Db,
         property_manager: &PropertyManager,
+    ) -> Result<(Index, IndexSpecMatch), RetrievalError> {
+        let (index, match_type) = self.find_or_create(collection, spec, db, property_manager)?;
+        index.build_if_needed(db, self.build_batch_size())?;
+        Ok((index, match_type))
+    }
+
+    /// Find or create an index matching `spec`, returning it only once it is
+    /// ready to scan. A new index backfills its first batch inline, so small
+    /// collections are indexed on first use; otherwise the build carries on
+    /// in the background and `None` tells the caller to scan the collection.
+    pub fn find_ready(
+        &self,
+        collection: &str,
+        spec: &ankurah_core::indexing::KeySpec,
+        db: &Db,
+        property_manager: &PropertyManager,
+    ) -> Result<Option<(Index, IndexSpecMatch)>, RetrievalError> {
+        let (index, match_type) = self.find_or_create(collection, spec, db, property_manager)?;
+        if index.status() == BuildStatus::Ready {
+            return Ok(Some((index, match_type)));
+        }
+        if index.0.builder_running.load(Ordering::Acquire) {
+            return Ok(None);
+        }
+        if index.build_batch(db, self.build_batch_size())? {
+            return Ok(Some((index, match_type)));
+        }
+        self.queue_build(index, db);
+        Ok(None)
+    }
+
+    /// Hand an index to the background builder, which finishes one build
+    /// before starting the next.
+    fn queue_build(&self, index: Index, db: &Db) {
+        if index.0.builder_running.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let mut builder = self.builder.lock().unwrap();
+        if builder.is_none() {
+            let (sender, receiver) = mpsc::channel::<(Index, usize)>();
+            let db = db.clone();
+            let spawned = std::thread::Builder::new().name("sled-index-builder".to_owned()).spawn(move || {
+                for (index, batch_size) in receiver {
+                    index.build_in_background(&db, batch_size);
+                }
+            });
+            match spawned {
+                Ok(_) => *builder = Some(sender),
+                Err(e) => warn!("Could not start the index builder: {}", e),
+            }
+        }
+        let queued = builder.as_ref().is_some_and(|sender| sender.send((index.clone(), self.build_batch_size())).is_ok());
+        if !queued {
+            *builder = None;
+            index.0.builder_running.store(false, Ordering::Release);
+        }
+    }
+
+    fn find_or_create(
+        &self,
+        collection: &str,
+        spec: &ankurah_core::indexing::KeySpec,
+        db: &Db,
+        property_manager: &PropertyManager,
     ) -> Result<(Index, IndexSpecMatch), RetrievalError> {
         // Try existing matching index
         if let Some((_, existing, match_type)) = self.indexes.read().unwrap().iter().find_map(|(id, idx)| {
@@ -88,22 +240,54 @@ impl IndexManager {
             }
             None
         }) {
-            existing.build_if_needed(db)?;
             return Ok((existing, match_type));
         }
 
         // Create new index
-        let index = {
-            let id = self.next_index_id()?;
-            let mut w = self.indexes.write().unwrap();
-            let index =
-                Index::new_from_spec(collection, spec.clone(), db, id, self.index_config_tree.clone(), property_manager.clone()).unwrap();
-            w.insert(id, index.clone());
-            index
+        let id = self.next_index_id()?;
+        let mut w = self.indexes.write().unwrap();
+        let index = Index::new_from_spec(collection, spec.clone(), db, id, self.index_config_tree.clone(), property_manager.clone())?;
+        w.insert(id, index.clone());
+        Ok((index, ankurah_core::indexing::IndexSpecMatch::Match))
+    }
+
+    /// Snapshot every index, or only those of `collection`, ordered by id.
+    pub fn list(&self, collection: Option<&str>) -> Result<Vec<IndexInfo>, RetrievalError> {
+        let mut indexes: Vec<Index> = {
+            let guard = self.indexes.read().unwrap();
+            guard.values().filter(|idx| collection.is_none_or(|c| idx.collection() == c)).cloned().collect()
         };
+        indexes.sort_by_key(|idx| idx.id());
+        Ok(indexes.iter().map(Index::info).collect::<Result<_, _>>()?)
+    }
 
-        index.build_if_needed(db)?;
-        Ok((index, ankurah_core::indexing::IndexSpecMatch::Match))
+    /// Drop an index and its tree, stopping any build in progress. A later
+    /// query that wants the index creates it afresh. Returns false if no
+    /// index has this id.
+    pub fn drop_index(&self, id: u32, db: &Db) -> Result<bool, RetrievalError> {
+        let Some(index) = self.indexes.write().unwrap().remove(&id) else {
+            return Ok(false);
+        };
+        index.0.dropped.store(true, Ordering::Release);
+        // Wait out an in-flight build batch
+        let _guard = index.0.build_lock.lock().unwrap();
+        self.index_config_tree.remove(id.to_be_bytes()).map_err(IndexError::from)?;
+        index.0.cursor_tree.remove(id.to_be_bytes()).map_err(IndexError::from)?;
+        db.drop_tree(format!("index_{}", id)).map_err(IndexError::from)?;
+        Ok(true)
+    }
+
+    /// Discard and backfill every index, or only those of `collection`.
+    /// Returns the number of indexes rebuilt.
+    pub fn rebuild(&self, collection: Option<&str>, db: &Db) -> Result<usize, RetrievalError> {
+        let indexes: Vec<Index> = {
+            let guard = self.indexes.read().unwrap();
+            guard.values().filter(|idx| collection.is_none_or(|c| idx.collection() == c)).cloned().collect()
+        };
+        for index in indexes.iter() {
+            index.rebuild(db, self.build_batch_size())?;
+        }
+        Ok(indexes.len())
     }
 }
 
@@ -114,21 +298,22 @@ impl Index {
     pub fn name(&self) -> &str { &self.0.name }
     pub fn spec(&self) -> &ankurah_core::indexing::KeySpec { &self.0.spec }
     pub fn created_at_unix_ms(&self) -> i64 { self.0.created_at_unix_ms }
-    /// Build the index key for an entity given a materialized property map.
-    /// Returns Ok(None) if any required key part is missing and the entity should not be indexed.
-    pub fn build_key(&self, eid: &EntityId, properties: &[(u32, ankurah_core::value::Value)]) -> Result<Option<Vec<u8>>, IndexError> {
-        use std::collections::BTreeMap;
+    /// Build the index keys for an entity given a materialized property map.
+    ///
+    /// An entity has one key, or one per element of the collection under each
+    /// element keypart (a multi-entry index). Returns no keys if any required
+    /// key part is missing and the entity should not be indexed.
+    pub fn build_keys(&self, eid: &EntityId, properties: &[(u32, ankurah_core::value::Value)]) -> Result<BTreeSet<Vec<u8>>, IndexError> {
         let map: BTreeMap<_, _> = properties.iter().cloned().collect();
-        self.build_key_from_map(eid, &map)
+        self.build_keys_from_map(eid, &map)
     }
 
-    /// Internal helper to build index key from a property map.
-    /// Shared between build_key and backfill to avoid duplication.
-    fn build_key_from_map(
+    /// Internal helper to build index keys from a property map.
+    fn build_keys_from_map(
         &self,
         eid: &EntityId,
-        property_map: &std::collections::BTreeMap<u32, ankurah_core::value::Value>,
-    ) -> Result<Option<Vec<u8>>, IndexError> {
+        property_map: &BTreeMap<u32, ankurah_core::value::Value>,
+    ) -> Result<BTreeSet<Vec<u8>>, IndexError> {
         // Resolve pids using PropertyManager
         let mut pids: Vec<u32> = Vec::with_capacity(self.0.spec.keyparts.len());
         for kp in &self.0.spec.keyparts {
@@ -138,34 +323,53 @@ impl Index {
             }
         }
 
-        // Build composite key using per-keypart direction from spec
-        let mut tuple_values: Vec<ankurah_core::value::Value> = Vec::with_capacity(self.0.spec.keyparts.len());
+        // Every combination of the keypart values, in keypart order
+        let mut tuples: Vec<Vec<ankurah_core::value::Value>> = vec![Vec::with_capacity(self.0.spec.keyparts.len())];
         for (pid, kp) in pids.iter().zip(self.0.spec.keyparts.iter()) {
-            if let Some(val) = property_map.get(pid).cloned() {
-                // If keypart has a sub_path, extract the value at that path
-                let extracted = match &kp.sub_path {
-                    None => Some(val),
-                    Some(path) => val.extract_at_path(path),
-                };
-                match extracted {
-                    Some(v) => tuple_values.push(v),
-                    None => {
-                        // Missing sub_path value - don't index this entity
-                        return Ok(None);
-                    }
-                }
-            } else {
+            let Some(val) = property_map.get(pid) else {
                 // Missing required property for this index
-                return Ok(None);
-            }
+                return Ok(BTreeSet::new());
+            };
+            // An element keypart takes each element of the collection, one with a
+            // sub_path the value at that path
+            let values = if kp.is_element() {
+                val.elements()
+            } else {
+                match &kp.sub_path {
+                    None => Some(vec![val.clone()]),
+                    Some(path) => val.extract_at_path(path).map(|v| vec![v]),
+                }
+            };
+            // Missing sub_path value, or not a collection - don't index this entity
+            let Some(values) = values else { return Ok(BTreeSet::new()) };
+            tuples = tuples
+                .into_iter()
+                .flat_map(|tuple| {
+                    values.iter().map(move |v| {
+                        let mut tuple = tuple.clone();
+                        tuple.push(v.clone());
+                        tuple
+                    })
+                })
+                .collect();
         }
 
-        let mut key = encode_tuple_values_with_key_spec(&tuple_values, &self.0.spec)?;
-        // No separator needed - KeySpec provides structure info for parsing
-        key.extend_from_slice(&eid.to_bytes());
-        Ok(Some(key))
+        let mut keys = BTreeSet::new();
+        for tuple_values in tuples {
+            let mut key = encode_tuple_values_with_key_spec(&tuple_values, &self.0.spec)?;
+            // No separator needed - KeySpec provides structure info for parsing
+            key.extend_from_slice(&eid.to_bytes());
+            keys.insert(key);
+        }
+        Ok(keys)
     }
     pub fn from_record(rec: IndexRecord, db: &Db, index_config_tree: Tree, property_manager: PropertyManager) -> Result<Self, IndexError> {
+        let cursor_tree = db.open_tree(BUILD_CURSOR_TREE)?;
+        let tree = db.open_tree(format!("index_{}", rec.id))?;
+        let cursor = match cursor_tree.get(rec.id.to_be_bytes())? {
+            Some(bytes) => Some(EntityId::from_bytes(bytes.as_ref().try_into().map_err(|_| IndexError::InvalidKeyLength)?)),
+            None => None,
+        };
         Ok(Self(Arc::new(IndexInner {
             id: rec.id,
             collection: rec.collection,
@@ -174,8 +378,15 @@ impl Index {
             created_at_unix_ms: rec.created_at_unix_ms,
             build_status: Mutex::new(rec.build_status),
             build_lock: Mutex::new(()),
-            tree: db.open_tree(format!("index_{}", rec.id))?,
+            cursor: Mutex::new(cursor),
+            progress: Mutex::new(None),
+            builder_running: AtomicBool::new(false),
+            dropped: AtomicBool::new(false),
+            entries: AtomicU64::new(rec.entries),
+            bytes: AtomicU64::new(rec.bytes),
+            tree,
             index_config_tree,
+            cursor_tree,
             property_manager,
         })))
     }
@@ -196,55 +407,224 @@ impl Index {
             created_at_unix_ms: chrono::Utc::now().timestamp_millis(),
             build_status: Mutex::new(BuildStatus::NotBuilt),
             build_lock: Mutex::new(()),
+            cursor: Mutex::new(None),
+            progress: Mutex::new(None),
+            builder_running: AtomicBool::new(false),
+            dropped: AtomicBool::new(false),
+            entries: AtomicU64::new(0),
+            bytes: AtomicU64::new(0),
             tree: db.open_tree(format!("index_{}", id))?,
             index_config_tree,
+            cursor_tree: db.open_tree(BUILD_CURSOR_TREE)?,
             property_manager,
         })))
     }
 
-    pub fn backfill(&self, db: &Db) -> Result<(), IndexError> {
-        let coll_tree = db.open_tree(format!("collection_{}", &self.0.collection))?;
+    /// Backfill the next batch of entities after the cursor, recording the
+    /// new cursor in the same transaction as the keys. Returns true once
+    /// the whole collection has been backfilled and the index is ready.
+    pub fn build_batch(&self, db: &Db, batch_size: usize) -> Result<bool, RetrievalError> {
+        let _guard = self.0.build_lock.lock().unwrap();
+        if self.0.dropped.load(Ordering::Acquire) {
+            return Ok(true);
+        }
+        let coll_tree = db.open_tree(format!("collection_{}", self.0.collection)).map_err(IndexError::from)?;
+        let mut cursor = self.0.cursor.lock().unwrap();
+        if self.status() != BuildStatus::Building {
+            if self.status() == BuildStatus::Ready {
+                return Ok(true);
+            }
+            self.set_status(BuildStatus::Building);
+            self.persist_snapshot()?;
+        }
+        let mut progress = self.0.progress.lock().unwrap();
+        if progress.is_none() {
+            // Starting, or resuming after a restart
+            let scanned = match *cursor {
+                Some(c) => coll_tree.range(..=c.to_bytes()).count() as u64,
+                None => 0,
+            };
+            *progress = Some(BuildProgress { scanned, total: coll_tree.len() as u64 });
+        }
 
-        for item in coll_tree.iter() {
-            let (k, v) = item?;
+        let range = match *cursor {
+            Some(c) => coll_tree.range((std::ops::Bound::Excluded(c.to_bytes()), std::ops::Bound::Unbounded)),
+            None => coll_tree.iter(),
+        };
+        let mut keys = Vec::new();
+        let mut last = None;
+        let mut scanned = 0u64;
+        for item in range.take(batch_size) {
+            let (k, v) = item.map_err(IndexError::from)?;
             let eid = EntityId::from_bytes(k.as_ref().try_into().map_err(|_| IndexError::InvalidKeyLength)?);
-            let mat: Vec<(u32, ankurah_core::value::Value)> = bincode::deserialize(&v)?;
-            let map: std::collections::BTreeMap<_, _> = mat.into_iter().collect();
+            let mat: Vec<(u32, ankurah_core::value::Value)> = bincode::deserialize(&v).map_err(IndexError::from)?;
+            keys.extend(self.build_keys(&eid, &mat)?);
+            last = Some(eid);
+            scanned += 1;
+        }
+
+        let id = self.0.id.to_be_bytes();
+        let (added, added_bytes) = (&self.0.tree, &self.0.cursor_tree, &self.0.index_config_tree)
+            .transaction(|(tree, cursor_tree, config)| {
+                let (mut added, mut added_bytes) = (0, 0);
+                for key in &keys {
+                    if tree.insert(key.as_slice(), &[])?.is_none() {
+                        added += 1;
+                        added_bytes += key.len() as i64;
+                    }
+                }
+                if let Some(last) = last {
+                    cursor_tree.insert(&id, &last.to_bytes()[..])?;
+                }
+                add_to_record(config, self.0.id, added, added_bytes)?;
+                Ok((added, added_bytes))
+            })
+            .map_err(transaction_error)?;
+        self.add_counts(added, added_bytes);
+        if last.is_some() {
+            *cursor = last;
+        }
+        if let Some(progress) = progress.as_mut() {
+            progress.scanned += scanned;
+        }
+
+        if scanned < batch_size as u64 {
+            self.set_status(BuildStatus::Ready);
+            self.persist_snapshot()?;
+            self.0.cursor_tree.remove(id).map_err(IndexError::from)?;
+            *cursor = None;
+            *progress = None;
+            return Ok(true);
+        }
+        Ok(false)
+    }
 
-            // Use shared key building logic
-            if let Some(key) = self.build_key_from_map(&eid, &map)? {
-                self.0.tree.insert(key, &[])?;
+    /// Continue the build one batch at a time, on the builder thread.
+    fn build_in_background(&self, db: &Db, batch_size: usize) {
+        loop {
+            match self.build_batch(db, batch_size) {
+                Ok(true) => break,
+                Ok(false) => continue,
+                Err(e) => {
+                    warn!("Building index {} on {} failed: {}", self.name(), self.collection(), e);
+                    break;
+                }
             }
         }
+        self.0.builder_running.store(false, Ordering::Release);
+    }
+
+    /// Remove and insert keys, storing the change in the counts in the same transaction
+    fn update_keys(&self, removed: &[&Vec<u8>], inserted: &[&Vec<u8>]) -> Result<(), IndexError> {
+        if removed.is_empty() && inserted.is_empty() {
+            return Ok(());
+        }
+        let (entries, bytes) = (&self.0.tree, &self.0.index_config_tree)
+            .transaction(|(tree, config)| {
+                let (mut entries, mut bytes) = (0, 0);
+                for key in removed {
+                    if tree.remove(key.as_slice())?.is_some() {
+                        entries -= 1;
+                        bytes -= key.len() as i64;
+                    }
+                }
+                for key in inserted {
+                    if tree.insert(key.as_slice(), &[])?.is_none() {
+                        entries += 1;
+                        bytes += key.len() as i64;
+                    }
+                }
+                add_to_record(config, self.0.id, entries, bytes)?;
+                Ok((entries, bytes))
+            })
+            .map_err(transaction_error)?;
+        self.add_counts(entries, bytes);
         Ok(())
     }
 
+    fn add_counts(&self, entries: i64, bytes: i64) {
+        let add = |count: &AtomicU64, delta: i64| {
+            let _ = count.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |c| Some(c.saturating_add_signed(delta)));
+        };
+        add(&self.0.entries, entries);
+        add(&self.0.bytes, bytes);
+    }
+
     pub fn status(&self) -> BuildStatus { *self.0.build_status.lock().unwrap() }
     pub fn set_status(&self, status: BuildStatus) { *self.0.build_status.lock().unwrap() = status; }
 
+    /// Store the build status, keeping the counts writers have stored alongside it
     pub fn persist_snapshot(&self) -> Result<(), IndexError> {
-        let bytes = bincode::serialize(&IndexRecord {
+        let status = self.status();
+        self.0
+            .index_config_tree
+            .transaction(|config| {
+                let record = match config.get(self.0.id.to_be_bytes())? {
+                    Some(stored) => IndexRecord { build_status: status, ..decode_record(&stored)? },
+                    None => IndexRecord {
+                        id: self.0.id,
+                        collection: self.0.collection.clone(),
+                        name: self.0.name.clone(),
+                        spec: self.0.spec.clone(),
+                        created_at_unix_ms: self.0.created_at_unix_ms,
+                        build_status: status,
+                        entries: self.0.entries.load(Ordering::Relaxed),
+                        bytes: self.0.bytes.load(Ordering::Relaxed),
+                    },
+                };
+                config.insert(&self.0.id.to_be_bytes(), encode_record(&record)?)?;
+                Ok(())
+            })
+            .map_err(transaction_error)
+    }
+    /// Build progress, once a build of this index has run in this process.
+    pub fn progress(&self) -> Option<BuildProgress> { *self.0.progress.lock().unwrap() }
+
+    pub fn info(&self) -> Result<IndexInfo, IndexError> {
+        Ok(IndexInfo {
             id: self.0.id,
             collection: self.0.collection.clone(),
             name: self.0.name.clone(),
             spec: self.0.spec.clone(),
+            status: self.status(),
             created_at_unix_ms: self.0.created_at_unix_ms,
-            build_status: self.status(),
-        })?;
-        self.0.index_config_tree.insert(self.0.id.to_be_bytes(), bytes)?;
-        Ok(())
+            entries: self.0.entries.load(Ordering::Relaxed),
+            bytes: self.0.bytes.load(Ordering::Relaxed),
+            progress: self.progress(),
+        })
     }
-    pub fn build_if_needed(&self, db: &Db) -> Result<(), RetrievalError> {
-        let _guard = self.0.build_lock.lock().unwrap();
-        if matches!(self.status(), BuildStatus::Ready) {
-            return Ok(());
+
+    /// Clear the index tree and backfill it from the materialized collection.
+    /// Writers keep the index current behind the build as it goes.
+    pub fn rebuild(&self, db: &Db, batch_size: usize) -> Result<(), RetrievalError> {
+        {
+            let _guard = self.0.build_lock.lock().unwrap();
+            let mut cursor = self.0.cursor.lock().unwrap();
+            self.set_status(BuildStatus::Building);
+            self.persist_snapshot()?;
+            self.0.tree.clear().map_err(IndexError::from)?;
+            self.0.entries.store(0, Ordering::Relaxed);
+            self.0.bytes.store(0, Ordering::Relaxed);
+            self.0
+                .index_config_tree
+                .transaction(|config| {
+                    let Some(stored) = config.get(self.0.id.to_be_bytes())? else { return Ok(()) };
+                    let record = IndexRecord { entries: 0, bytes: 0, ..decode_record(&stored)? };
+                    config.insert(&self.0.id.to_be_bytes(), encode_record(&record)?)?;
+                    Ok(())
+                })
+                .map_err(transaction_error)?;
+            self.0.cursor_tree.remove(self.0.id.to_be_bytes()).map_err(IndexError::from)?;
+            *cursor = None;
+            *self.0.progress.lock().unwrap() = None;
         }
+        while !self.build_batch(db, batch_size)? {}
+        Ok(())
+    }
 
-        self.set_status(BuildStatus::Building);
-        self.persist_snapshot()?;
-        self.backfill(db)?;
-        self.set_status(BuildStatus::Ready);
-        self.persist_snapshot()?;
+    /// Build to completion, resuming a build already in progress.
+    pub fn build_if_needed(&self, db: &Db, batch_size: usize) -> Result<(), RetrievalError> {
+        while !self.build_batch(db, batch_size)? {}
         Ok(())
     }
 }
@@ -267,33 +647,84 @@ impl IndexManager {
         };
 
         for index in indexes.iter() {
-            let old_key = match old_mat {
-                Some(mat) => index.build_key(eid, mat)?,
-                None => None,
+            if index.0.dropped.load(Ordering::Acquire) {
+                continue;
+            }
+            // A building index is only maintained up to its cursor; the build
+            // reads everything after it from the collection tree.
+            let _guard = match index.status() {
+                BuildStatus::Ready => None,
+                _ => {
+                    let guard = index.0.build_lock.lock().unwrap();
+                    let built = index.status() == BuildStatus::Ready || index.0.cursor.lock().unwrap().is_some_and(|c| *eid <= c);
+                    if !built {
+                        continue;
+                    }
+                    Some(guard)
+                }
             };
-            let new_key = index.build_key(eid, new_mat)?;
+            let old_keys = match old_mat {
+                Some(mat) => index.build_keys(eid, mat)?,
+                None => BTreeSet::new(),
+            };
+            let new_keys = index.build_keys(eid, new_mat)?;
 
-            match (old_key.as_ref(), new_key.as_ref()) {
-                (Some(ok), Some(nk)) if ok == nk => {
-                    // No change for this index
-                }
-                (Some(ok), Some(nk)) => {
-                    // Key changed: remove old, insert new
-                    index.tree().remove(ok).map_err(IndexError::from)?;
-                    index.tree().insert(nk, &[]).map_err(IndexError::from)?;
-                }
-                (Some(ok), None) => {
-                    // No longer matches index
-                    index.tree().remove(ok).map_err(IndexError::from)?;
-                }
-                (None, Some(nk)) => {
-                    // Newly matches index
-                    index.tree().insert(nk, &[]).map_err(IndexError::from)?;
-                }
-                (None, None) => {}
-            }
+            // Remove the entries that no longer apply and insert the ones that now do
+            let removed: Vec<_> = old_keys.difference(&new_keys).collect();
+            let inserted: Vec<_> = new_keys.difference(&old_keys).collect();
+            index.update_keys(&removed, &inserted)?;
         }
 
         Ok(())
     }
 }
+
+impl LegacyIndexRecord {
+    /// The current record, with the counts taken from the index tree
+    fn counted(self, db: &Db) -> Result<IndexRecord, IndexError> {
+        let (mut entries, mut bytes) = (0, 0);
+        for item in db.open_tree(format!("index_{}", self.id))?.iter() {
+            let (key, _) = item?;
+            entries += 1;
+            bytes += key.len() as u64;
+        }
+        Ok(IndexRecord {
+            id: self.id,
+            collection: self.collection,
+            name: self.name,
+            spec: self.spec,
+            created_at_unix_ms: self.created_at_unix_ms,
+            build_status: self.build_status,
+            entries,
+            bytes,
+        })
+    }
+}
+
+fn decode_record(bytes: &[u8]) -> Result<IndexRecord, ConflictableTransactionError<IndexError>> {
+    bincode::deserialize(bytes).map_err(|e| ConflictableTransactionError::Abort(e.into()))
+}
+
+fn encode_record(record: &IndexRecord) -> Result<Vec<u8>, ConflictableTransactionError<IndexError>> {
+    bincode::serialize(record).map_err(|e| ConflictableTransactionError::Abort(e.into()))
+}
+
+/// Add to the counts in an index's stored record. A dropped index has no record left to update.
+fn add_to_record(config: &TransactionalTree, id: u32, entries: i64, bytes: i64) -> Result<(), ConflictableTransactionError<IndexError>> {
+    if entries == 0 && bytes == 0 {
+        return Ok(());
+    }
+    let Some(stored) = config.get(id.to_be_bytes())? else { return Ok(()) };
+    let mut record = decode_record(&stored)?;
+    record.entries = record.entries.saturating_add_signed(entries);
+    record.bytes = record.bytes.saturating_add_signed(bytes);
+    config.insert(&id.to_be_bytes(), encode_record(&record)?)?;
+    Ok(())
+}
+
+fn transaction_error(e: TransactionError<IndexError>) -> IndexError {
+    match e {
+        TransactionError::Abort(e) => e,
+        TransactionError::Storage(e) => IndexError::StorageError(e),
+    }
+}
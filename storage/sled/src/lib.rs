@@ -10,4 +10,5 @@ pub mod planner_integration;
 pub mod property;
 pub mod scan_collection;
 pub mod scan_index;
+mod verify;
 pub use engine::*;
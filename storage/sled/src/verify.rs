@@ -0,0 +1,215 @@
+//! Sled's override of `StorageCollection::verify`: the portable per-entity
+//! checks plus the parts only this engine can see, namely event keys, events
+//! with no state, the materialized collection tree and secondary indexes.
+//!
+//! Events live in one global tree, so they are read in a single pass that
+//! sorts them by collection; [`SledStorageEngine::verify`] shares that pass
+//! across every collection rather than repeating it per collection.
+//!
+//! [`SledStorageEngine::verify`]: crate::SledStorageEngine::verify
+
+use std::collections::{BTreeSet, HashMap};
+
+use ankurah_core::{
+    error::RetrievalError,
+    storage::{verify::check_entity, Inconsistency, VerifyOptions, VerifyReport},
+    value::Value,
+};
+use ankurah_proto::{Attested, CollectionId, EntityId, EntityState, Event, EventId, StateFragment};
+use tokio::task;
+
+use crate::{collection::SledStorageCollectionInner, database::Database, error::sled_error, index::BuildStatus};
+
+/// One collection's share of the events tree: its events by entity, and what
+/// reading them found.
+#[derive(Default)]
+pub(crate) struct ScannedEvents {
+    report: VerifyReport,
+    events: HashMap<EntityId, Vec<Attested<Event>>>,
+}
+
+/// Read the global events tree once, sorting events by collection. Only
+/// `collection`'s are kept when given. The tree is keyed by event id, so a
+/// key that disagrees with the payload hash makes the event unreachable.
+pub(crate) fn scan_events_blocking(
+    database: &Database,
+    collection: Option<&CollectionId>,
+    options: VerifyOptions,
+) -> Result<HashMap<CollectionId, ScannedEvents>, RetrievalError> {
+    let mut scanned: HashMap<CollectionId, ScannedEvents> = HashMap::new();
+    for item in database.events_tree.iter() {
+        let (key, bytes) = item.map_err(sled_error)?;
+        let event: Attested<Event> = bincode::deserialize(&bytes)?;
+        if collection.is_some_and(|c| *c != event.payload.collection) {
+            continue;
+        }
+        let entry = scanned.entry(event.payload.collection.clone()).or_default();
+        entry.report.events += 1;
+        let computed = event.payload.id();
+        if key.as_ref() != computed.as_bytes() {
+            let stored = <[u8; 32]>::try_from(key.as_ref()).ok().map(EventId::from_bytes);
+            entry.report.problems.push(Inconsistency::EventIdMismatch {
+                entity_id: event.payload.entity_id,
+                computed: computed.clone(),
+                stored,
+            });
+            if options.repair {
+                database.events_tree.insert(computed.as_bytes(), bytes).map_err(sled_error)?;
+                database.events_tree.remove(key).map_err(sled_error)?;
+                entry.report.repaired += 1;
+            }
+        }
+        entry.events.entry(event.payload.entity_id).or_default().push(event);
+    }
+    Ok(scanned)
+}
+
+/// A stored state with its materialization, as read from the collection tree.
+struct StoredEntity {
+    state: Attested<EntityState>,
+    materialization: Vec<u8>,
+}
+
+impl SledStorageCollectionInner {
+    /// Verify this collection against its share of an events scan. The sled
+    /// work runs on blocking threads; the replays in between run here.
+    pub(crate) async fn verify_scanned(&self, scanned: ScannedEvents, options: VerifyOptions) -> Result<VerifyReport, RetrievalError> {
+        let ScannedEvents { report, mut events } = scanned;
+
+        let inner = self.clone();
+        let (stored, mut report) = task::spawn_blocking(move || inner.load_states_blocking(report, options)).await??;
+
+        // Every entity in the materialized collection tree must have a
+        // canonical state that its events reproduce.
+        let mut checked = Vec::with_capacity(stored.len());
+        for entity in stored {
+            let check = check_entity(&entity.state, events.remove(&entity.state.payload.entity_id).unwrap_or_default()).await;
+            report.problems.extend(check.problems);
+            checked.push((entity, check.replayed));
+        }
+
+        // Events whose entity has no state were never made visible; there is
+        // no head to replay them to, so they are reported but left alone.
+        for entity_id in events.into_keys() {
+            report.problems.push(derived(entity_id, "state", "events are stored but the entity has no state"));
+        }
+
+        let inner = self.clone();
+        task::spawn_blocking(move || inner.finish_verify_blocking(report, checked, options)).await?
+    }
+
+    /// The states behind the materialized collection tree. A materialization
+    /// with no stored state is reported (and removed on repair) here.
+    fn load_states_blocking(
+        &self,
+        mut report: VerifyReport,
+        options: VerifyOptions,
+    ) -> Result<(Vec<StoredEntity>, VerifyReport), RetrievalError> {
+        let mut stored = Vec::new();
+        for item in self.tree.iter() {
+            let (key, materialization) = item.map_err(sled_error)?;
+            let entity_id = EntityId::from_bytes(key.as_ref().try_into().map_err(|_| RetrievalError::Other("invalid entity key".into()))?);
+            let Some(state_bytes) = self.database.entities_tree.get(key.as_ref()).map_err(sled_error)? else {
+                report.problems.push(derived(entity_id, "materialization", "entity has no stored state"));
+                if options.repair {
+                    self.remove_materialization(entity_id)?;
+                    report.repaired += 1;
+                }
+                continue;
+            };
+            report.states += 1;
+            let sfrag: StateFragment = bincode::deserialize(&state_bytes)?;
+            let state = Attested::<EntityState>::from_parts(entity_id, self.collection_id.clone(), sfrag);
+            stored.push(StoredEntity { state, materialization: materialization.to_vec() });
+        }
+        Ok((stored, report))
+    }
+
+    /// Apply replayed states, then check materializations against the stored
+    /// states and each ready index against the materializations.
+    fn finish_verify_blocking(
+        &self,
+        mut report: VerifyReport,
+        checked: Vec<(StoredEntity, Option<Attested<EntityState>>)>,
+        options: VerifyOptions,
+    ) -> Result<VerifyReport, RetrievalError> {
+        for (entity, replayed) in checked {
+            if let (true, Some(replayed)) = (options.repair, replayed) {
+                // Also rewrites the materialization and index entries
+                self.set_state_blocking(replayed)?;
+                report.repaired += 1;
+                continue;
+            }
+
+            let entity_id = entity.state.payload.entity_id;
+            let stored: Vec<(u32, Value)> = bincode::deserialize(&entity.materialization)?;
+            if stored != self.materialize(entity_id, &entity.state.payload.state)? {
+                report.problems.push(derived(entity_id, "materialization", "differs from the stored state"));
+                if options.repair {
+                    self.set_state_blocking(entity.state)?;
+                    report.repaired += 1;
+                }
+            }
+        }
+
+        // Each ready index must hold exactly the keys the materialized
+        // collection produces.
+        let indexes: Vec<_> = {
+            let guard = self.database.index_manager.indexes.read().unwrap();
+            guard.values().filter(|idx| idx.collection() == self.collection_id.as_str()).cloned().collect()
+        };
+        for index in indexes {
+            if index.status() != BuildStatus::Ready {
+                continue;
+            }
+            let structure = format!("index {}", index.name());
+            let mut expected = BTreeSet::new();
+            for item in self.tree.iter() {
+                let (key, mat_bytes) = item.map_err(sled_error)?;
+                let entity_id =
+                    EntityId::from_bytes(key.as_ref().try_into().map_err(|_| RetrievalError::Other("invalid entity key".into()))?);
+                let mat: Vec<(u32, Value)> = bincode::deserialize(&mat_bytes)?;
+                expected.extend(index.build_keys(&entity_id, &mat)?);
+            }
+            let mut found = 0;
+            for item in index.tree().iter() {
+                let (key, _) = item.map_err(sled_error)?;
+                if !expected.remove(key.as_ref()) {
+                    report.problems.push(derived(index_entity(&key), &structure, "stale entry"));
+                    found += 1;
+                }
+            }
+            for key in &expected {
+                report.problems.push(derived(index_entity(key), &structure, "missing entry"));
+                found += 1;
+            }
+            if options.repair && found > 0 {
+                index.rebuild(&self.database.db, self.database.index_manager.build_batch_size())?;
+                report.repaired += found;
+            }
+        }
+
+        Ok(report)
+    }
+
+    fn remove_materialization(&self, entity_id: EntityId) -> Result<(), RetrievalError> {
+        if let Some(old) = self.tree.remove(entity_id.to_bytes()).map_err(sled_error)? {
+            let old: Vec<(u32, Value)> = bincode::deserialize(&old)?;
+            self.database.index_manager.update_indexes_for_entity(self.collection_id.as_str(), &entity_id, Some(&old), &[])?;
+        }
+        Ok(())
+    }
+}
+
+fn derived(entity_id: EntityId, structure: &str, detail: &str) -> Inconsistency {
+    Inconsistency::Derived { entity_id, structure: structure.to_owned(), detail: detail.to_owned() }
+}
+
+/// Index keys end with the 32-byte entity id.
+fn index_entity(key: &[u8]) -> EntityId {
+    let mut bytes = [0u8; 32];
+    if key.len() >= 32 {
+        bytes.copy_from_slice(&key[key.len() - 32..]);
+    }
+    EntityId::from_bytes(bytes)
+}
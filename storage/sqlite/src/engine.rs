@@ -60,6 +60,25 @@ impl SqliteStorageEngine {
 
     /// Get a reference to the connection pool (for testing/diagnostics)
     pub fn pool(&self) -> &bb8::Pool<SqliteConnectionManager> { &self.pool }
+
+    /// List all collections by looking for state tables with a matching `_event` table
+    pub async fn list_collections(&self) -> Result<Vec<CollectionId>, RetrievalError> {
+        let conn = self.pool.get().await.map_err(|e| SqliteError::Pool(e.to_string()))?;
+        let tables = conn
+            .with_connection(|c| {
+                let mut stmt = c.prepare("SELECT name FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%' ORDER BY name")?;
+                let tables: Vec<String> = stmt.query_map([], |row| row.get(0))?.filter_map(|r| r.ok()).collect();
+                Ok(tables)
+            })
+            .await?;
+        Ok(collections_from_tables(&tables))
+    }
+}
+
+/// Collections are the tables `t` for which `t_event` also exists.
+fn collections_from_tables(tables: &[String]) -> Vec<CollectionId> {
+    let names: std::collections::HashSet<&str> = tables.iter().map(String::as_str).collect();
+    tables.iter().filter(|t| names.contains(format!("{t}_event").as_str())).map(|t| CollectionId::from(t.as_str())).collect()
 }
 
 #[async_trait]
@@ -90,6 +109,8 @@ impl StorageEngine for SqliteStorageEngine {
         Ok(Arc::new(bucket))
     }
 
+    async fn list_collections(&self) -> Result<Vec<CollectionId>, RetrievalError> { SqliteStorageEngine::list_collections(self).await }
+
     async fn delete_all_collections(&self) -> Result<bool, MutationError> {
         let conn = self.pool.get().await.map_err(|e| MutationError::General(Box::new(SqliteError::Pool(e.to_string()))))?;
 
@@ -3,7 +3,9 @@
 //! Converts AnkQL predicates to SQLite-compatible SQL WHERE clauses.
 
 use crate::error::SqliteError;
+use crate::value::SqliteValue;
 use ankql::ast::{ComparisonOperator, Expr, OrderByItem, OrderDirection, Predicate, Selection};
+use ankurah_core::collation::Collatable;
 use ankurah_core_types::Value;
 use thiserror::Error;
 
@@ -221,6 +223,14 @@ impl SqlBuilder {
             Value::EntityId(id) => self.push_param(rusqlite::types::Value::Text(id.to_base64())),
             Value::Object(bytes) => self.push_param(rusqlite::types::Value::Blob(bytes.clone())),
             Value::Binary(bytes) => self.push_param(rusqlite::types::Value::Blob(bytes.clone())),
+            // Bound the same way SqliteValue stores them
+            Value::Timestamp(t) => self.push_param(rusqlite::types::Value::Integer(t.as_micros())),
+            Value::Date(d) => self.push_param(rusqlite::types::Value::Text(d.to_string())),
+            Value::Decimal(_) => self.push_param(rusqlite::types::Value::Blob(lit.to_bytes())),
+            Value::Uuid(u) => self.push_param(rusqlite::types::Value::Text(u.to_string())),
+            Value::U32(i) => self.push_param(rusqlite::types::Value::Integer(*i as i64)),
+            Value::U64(i) => self.push_param(SqliteValue::wide_integer(*i as i128).to_sql()),
+            Value::I128(i) => self.push_param(SqliteValue::wide_integer(*i).to_sql()),
             // For JSON literals, extract the raw SQL value since json_extract() returns SQL types.
             // json.to_string() would produce "US" (with quotes) but we need just US.
             Value::Json(json) => match json {
@@ -250,6 +260,14 @@ impl SqlBuilder {
 
     pub fn predicate(&mut self, predicate: &Predicate) -> Result<(), SqlGenerationError> {
         match predicate {
+            // Collections are JSONB arrays, so membership scans their elements
+            Predicate::Comparison { left, operator: ComparisonOperator::Contains, right } => {
+                self.push_sql("EXISTS (SELECT 1 FROM json_each(");
+                self.expr(left)?;
+                self.push_sql(") WHERE value = ");
+                self.expr(right)?;
+                self.push_sql(")");
+            }
             Predicate::Comparison { left, operator, right } => {
                 // Emit: left op right
                 // JSONB paths use json_extract() which returns SQL values, so direct comparison works
@@ -350,6 +368,7 @@ fn comparison_op_to_sql(op: &ComparisonOperator) -> Result<&'static str, SqlGene
         ComparisonOperator::LessThanOrEqual => "<=",
         ComparisonOperator::In => "IN",
         ComparisonOperator::Between => return Err(SqlGenerationError::UnsupportedOperator("BETWEEN operator is not yet supported")),
+        ComparisonOperator::Contains => return Err(SqlGenerationError::UnsupportedOperator("CONTAINS is emitted as a json_each scan")),
     })
 }
 
@@ -1,5 +1,6 @@
 //! SQLite value type conversions
 
+use ankurah_core::collation::Collatable;
 use ankurah_core::value::Value;
 
 /// SQLite value wrapper for type mapping
@@ -36,6 +37,15 @@ impl SqliteValue {
         }
     }
 
+    /// An integer that may exceed SQLite's 64-bit INTEGER. Out-of-range values are
+    /// stored as REAL, which still compares numerically against INTEGER columns.
+    pub fn wide_integer(integer: i128) -> Self {
+        match i64::try_from(integer) {
+            Ok(integer) => SqliteValue::Integer(integer),
+            Err(_) => SqliteValue::Real(integer as f64),
+        }
+    }
+
     /// Check if this value is a JSONB type that needs special SQL handling
     pub fn is_jsonb(&self) -> bool { matches!(self, SqliteValue::Jsonb(_)) }
 
@@ -75,6 +85,17 @@ impl From<Value> for SqliteValue {
             Value::Object(bytes) => SqliteValue::Blob(bytes),
             Value::Binary(bytes) => SqliteValue::Blob(bytes),
             Value::Json(json) => SqliteValue::Jsonb(json),
+            // SQLite has no temporal, decimal or uuid storage classes. Timestamps
+            // keep their microseconds, and ISO dates and hyphenated uuids order
+            // correctly as text. Decimals are stored as their collation bytes,
+            // which BLOB comparison (memcmp) orders numerically at full precision.
+            Value::Timestamp(t) => SqliteValue::Integer(t.as_micros()),
+            Value::Date(d) => SqliteValue::Text(d.to_string()),
+            Value::Decimal(d) => SqliteValue::Blob(Value::Decimal(d).to_bytes()),
+            Value::Uuid(u) => SqliteValue::Text(u.to_string()),
+            Value::U32(i) => SqliteValue::Integer(i as i64),
+            Value::U64(i) => SqliteValue::wide_integer(i as i128),
+            Value::I128(i) => SqliteValue::wide_integer(i),
         }
     }
 }
@@ -10,7 +10,7 @@
 mod common;
 
 use ankurah::signals::Subscribe;
-use ankurah::{policy::DEFAULT_CONTEXT as c, Mutable, Node, PermissiveAgent};
+use ankurah::{policy::DEFAULT_CONTEXT as c, selection, Decimal, Model, Mutable, Node, PermissiveAgent};
 use ankurah_storage_sqlite::SqliteStorageEngine;
 use anyhow::Result;
 use common::{Album, AlbumView, TestWatcher};
@@ -169,3 +169,35 @@ async fn test_sqlite_query_with_subscription() -> Result<()> {
 
     Ok(())
 }
+
+#[derive(Model, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Payment {
+    pub name: String,
+    pub amount: Decimal,
+}
+
+#[tokio::test]
+async fn test_sqlite_decimals_compare_at_full_precision() -> Result<()> {
+    let storage = SqliteStorageEngine::open_in_memory().await?;
+    let node = Node::new_durable(Arc::new(storage), PermissiveAgent::new());
+    node.system.create().await?;
+    let ctx = node.context_async(c).await;
+
+    // The first two are the same f64
+    let trx = ctx.begin();
+    for (name, amount) in [("low", "0.1000000000000000001"), ("high", "0.1000000000000000002"), ("negative", "-5"), ("large", "12.50")] {
+        trx.create(&Payment { name: name.into(), amount: amount.parse()? }).await?;
+    }
+    trx.commit().await?;
+
+    let names = |payments: Vec<PaymentView>| payments.iter().map(|p| p.name().unwrap()).collect::<Vec<_>>();
+    let all: Vec<PaymentView> = ctx.fetch("name != '' ORDER BY amount ASC").await?;
+    assert_eq!(names(all), vec!["negative", "low", "high", "large"]);
+
+    let bound: Decimal = "0.1000000000000000001".parse()?;
+    assert_eq!(names(ctx.fetch(selection!("amount > {} ORDER BY amount DESC", bound)).await?), vec!["large", "high"]);
+    let exact: Decimal = "12.5".parse()?;
+    assert_eq!(names(ctx.fetch(selection!("amount = {}", exact)).await?), vec!["large"]);
+
+    Ok(())
+}
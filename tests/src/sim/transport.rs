@@ -135,6 +135,11 @@ fn request_descriptor(body: &proto::NodeRequestBody) -> String {
         proto::NodeRequestBody::RegisterSchema { models } => {
             format!("registerschema {}m {}p", models.len(), models.iter().map(|m| m.properties.len()).sum::<usize>())
         }
+        proto::NodeRequestBody::ListCollections => "listcollections".to_string(),
+        proto::NodeRequestBody::CollectionHeads { collection, after, .. } => {
+            format!("collectionheads {} {}", collection, after.map(|a| a.to_base64_short()).unwrap_or_default())
+        }
+        proto::NodeRequestBody::Replicate { collection, known } => format!("replicate {} {}", collection, known.len()),
     }
 }
 
@@ -152,6 +157,10 @@ fn response_descriptor(body: &proto::NodeResponseBody) -> String {
         }
         proto::NodeResponseBody::GetEvents(events) => format!("getevents {}", event_ids(events)),
         proto::NodeResponseBody::QuerySubscribed { deltas, .. } => format!("subscribed {}", deltas.len()),
+        proto::NodeResponseBody::Collections(collections) => format!("collections {}", collections.len()),
+        proto::NodeResponseBody::CollectionHeads { heads, .. } => format!("collectionheads {}", heads.len()),
+        proto::NodeResponseBody::Replicated(deltas) => format!("replicated {}", deltas.len()),
+        proto::NodeResponseBody::Draining => "draining".to_string(),
         proto::NodeResponseBody::Success => "success".to_string(),
         // Include the error text so two distinct rejections are distinguishable
         // in the trace (advisory path, but keeps the digest faithful).
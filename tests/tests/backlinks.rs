@@ -0,0 +1,111 @@
+mod common;
+use ankurah::error::RetrievalError;
+use ankurah::{Mutable, Ref};
+use anyhow::Result;
+use common::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Model, Debug, Serialize, Deserialize)]
+pub struct Album {
+    pub name: String,
+}
+
+#[derive(Model, Debug, Serialize, Deserialize)]
+pub struct Track {
+    pub name: String,
+    pub album: Ref<Album>,
+}
+
+#[derive(Model, Debug, Serialize, Deserialize)]
+pub struct Playlist {
+    pub name: String,
+    pub tracks: Vec<Ref<Track>>,
+}
+
+fn track_names(tracks: &[TrackView]) -> Vec<String> {
+    let mut names: Vec<String> = tracks.iter().map(|t| t.name().unwrap()).collect();
+    names.sort();
+    names
+}
+
+fn playlist_names(playlists: &[PlaylistView]) -> Vec<String> {
+    let mut names: Vec<String> = playlists.iter().map(|p| p.name().unwrap()).collect();
+    names.sort();
+    names
+}
+
+#[tokio::test]
+async fn backlinks_follow_references() -> Result<()> {
+    let node = durable_sled_setup().await?;
+    let ctx = node.context(DEFAULT_CONTEXT)?;
+
+    let trx = ctx.begin();
+    let ok_computer = trx.create(&Album { name: "OK Computer".into() }).await?.read();
+    let kid_a = trx.create(&Album { name: "Kid A".into() }).await?.read();
+    trx.create(&Track { name: "Airbag".into(), album: ok_computer.r() }).await?;
+    let paranoid = trx.create(&Track { name: "Paranoid Android".into(), album: ok_computer.r() }).await?.read();
+    let idioteque = trx.create(&Track { name: "Idioteque".into(), album: kid_a.r() }).await?.read();
+    trx.commit().await?;
+
+    assert_eq!(track_names(&ok_computer.backlinks::<Track>(&ctx, "album").await?), vec!["Airbag", "Paranoid Android"]);
+    assert_eq!(track_names(&kid_a.backlinks::<Track>(&ctx, "album").await?), vec!["Idioteque"]);
+
+    let query = ok_computer.query_backlinks::<Track>(&ctx, "album").await?;
+    assert_eq!(track_names(&query.peek()), vec!["Airbag", "Paranoid Android"]);
+    let watcher = TestWatcher::changeset();
+    let _guard = query.subscribe(&watcher);
+
+    // Pointing a track at the album brings it into the backlinks
+    let trx = ctx.begin();
+    idioteque.edit(&trx)?.album().set(&ok_computer.r())?;
+    trx.commit().await?;
+    assert!(watcher.wait().await, "retargeting a reference adds the backlink");
+    assert_eq!(track_names(&query.peek()), vec!["Airbag", "Idioteque", "Paranoid Android"]);
+
+    // and pointing it away takes it out
+    let trx = ctx.begin();
+    paranoid.edit(&trx)?.album().set(&kid_a.r())?;
+    trx.commit().await?;
+    assert!(watcher.wait().await, "retargeting a reference removes the backlink");
+    assert_eq!(track_names(&query.peek()), vec!["Airbag", "Idioteque"]);
+
+    // Only reference fields that point at the album's model can be followed back
+    assert!(matches!(ok_computer.backlinks::<Track>(&ctx, "name").await, Err(RetrievalError::NotAReference { .. })));
+    assert!(matches!(ok_computer.backlinks::<Playlist>(&ctx, "tracks").await, Err(RetrievalError::NotAReference { .. })));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn backlinks_through_reference_sets() -> Result<()> {
+    let node = durable_sled_setup().await?;
+    let ctx = node.context(DEFAULT_CONTEXT)?;
+
+    let trx = ctx.begin();
+    let album = trx.create(&Album { name: "In Rainbows".into() }).await?.read();
+    let nude = trx.create(&Track { name: "Nude".into(), album: album.r() }).await?.read();
+    let reckoner = trx.create(&Track { name: "Reckoner".into(), album: album.r() }).await?.read();
+    trx.create(&Playlist { name: "late night".into(), tracks: vec![nude.r(), reckoner.r()] }).await?;
+    let focus = trx.create(&Playlist { name: "focus".into(), tracks: vec![reckoner.r()] }).await?.read();
+    trx.commit().await?;
+
+    assert_eq!(playlist_names(&nude.backlinks::<Playlist>(&ctx, "tracks").await?), vec!["late night"]);
+
+    let query = nude.query_backlinks::<Playlist>(&ctx, "tracks").await?;
+    let watcher = TestWatcher::changeset();
+    let _guard = query.subscribe(&watcher);
+
+    let trx = ctx.begin();
+    focus.edit(&trx)?.tracks().insert(&nude)?;
+    trx.commit().await?;
+    assert!(watcher.wait().await, "adding the track to a playlist adds the backlink");
+    assert_eq!(playlist_names(&query.peek()), vec!["focus", "late night"]);
+
+    let trx = ctx.begin();
+    focus.edit(&trx)?.tracks().remove(&nude)?;
+    trx.commit().await?;
+    assert!(watcher.wait().await, "removing the track from a playlist removes the backlink");
+    assert_eq!(playlist_names(&query.peek()), vec!["late night"]);
+
+    Ok(())
+}
@@ -0,0 +1,253 @@
+//! Commits relayed to two durable peers under the different commit policies,
+//! and reconciliation of a durable peer that refused a transaction the other
+//! accepted.
+
+mod common;
+use ankurah::core::outbox::TransactionStatus;
+use ankurah::core::quorum::CommitPolicy;
+use ankurah::core::storage::{restore_dump, StorageDump};
+use common::*;
+use std::sync::Arc;
+use std::time::Duration;
+
+type TestNode = Node<SledStorageEngine, PermissiveAgent>;
+
+/// Two durable nodes of the same system: the second starts from a dump of the first
+async fn durable_pair() -> anyhow::Result<(TestNode, TestNode)> {
+    let engine = Arc::new(SledStorageEngine::new_test()?);
+    let first = Node::new_durable(engine.clone(), PermissiveAgent::new());
+    first.system.create().await?;
+    // Register the model while there is only one durable node
+    let trx = first.context(DEFAULT_CONTEXT)?.begin();
+    trx.create(&Album { name: "Seed".into(), year: "1999".into() }).await?;
+    trx.commit().await?;
+
+    let storage = SledStorageEngine::new_test()?;
+    restore_dump(&storage, engine.dump().await?).await?;
+    let second = Node::new_durable(Arc::new(storage), PermissiveAgent::new());
+    second.system.wait_system_ready().await;
+    assert_eq!(first.system.root(), second.system.root());
+    Ok((first, second))
+}
+
+async fn album_on(node: &TestNode, id: EntityId) -> anyhow::Result<Option<(String, String)>> {
+    let albums: Vec<AlbumView> = node.context(DEFAULT_CONTEXT)?.fetch("name != ''").await?;
+    Ok(albums.into_iter().find(|album| album.id() == id).map(|album| (album.name().unwrap(), album.year().unwrap())))
+}
+
+async fn wait_for_album(node: &TestNode, id: EntityId, expected: (&str, &str)) -> anyhow::Result<()> {
+    let expected = (expected.0.to_string(), expected.1.to_string());
+    let deadline = std::time::Instant::now() + Duration::from_secs(5);
+    loop {
+        let found = album_on(node, id).await?;
+        if found.as_ref() == Some(&expected) {
+            return Ok(());
+        }
+        if std::time::Instant::now() >= deadline {
+            anyhow::bail!("expected {:?} on durable node, found {:?}", expected, found);
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+#[tokio::test]
+async fn commit_reaches_every_durable_peer() -> anyhow::Result<()> {
+    let (first, second) = durable_pair().await?;
+    let client = ephemeral_sled_setup().await?;
+    let _conn_first = LocalProcessConnection::new(&first, &client).await?;
+    let _conn_second = LocalProcessConnection::new(&second, &client).await?;
+    client.system.wait_system_ready().await;
+    assert_eq!(client.get_durable_peers().len(), 2);
+
+    let ctx = client.context_async(DEFAULT_CONTEXT).await;
+    for (policy, name) in [(CommitPolicy::All, "all"), (CommitPolicy::Majority, "majority"), (CommitPolicy::Any, "any")] {
+        client.set_commit_policy(policy);
+        let trx = ctx.begin();
+        let id = trx.create(&Album { name: name.into(), year: "2024".into() }).await?.id();
+        trx.commit().await?;
+
+        // All and Majority (two of two) return only once both durable peers have it
+        if policy != CommitPolicy::Any {
+            assert_eq!(album_on(&first, id).await?, Some((name.to_string(), "2024".to_string())));
+            assert_eq!(album_on(&second, id).await?, Some((name.to_string(), "2024".to_string())));
+        }
+        // The straggler under Any completes in the background
+        wait_for_album(&first, id, (name, "2024")).await?;
+        wait_for_album(&second, id, (name, "2024")).await?;
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn refusing_durable_peer_is_reconciled() -> anyhow::Result<()> {
+    let (first, second) = durable_pair().await?;
+    let client = ephemeral_sled_setup().await?;
+    let _conn_first = LocalProcessConnection::new(&first, &client).await?;
+    client.system.wait_system_ready().await;
+
+    // Created while only the first durable node is connected
+    let ctx = client.context_async(DEFAULT_CONTEXT).await;
+    let trx = ctx.begin();
+    let id = trx.create(&Album { name: "Before".into(), year: "2000".into() }).await?.id();
+    trx.commit().await?;
+    let album = ctx.get::<AlbumView>(id).await?;
+    assert_eq!(album_on(&second, id).await?, None);
+
+    let _conn_second = LocalProcessConnection::new(&second, &client).await?;
+    let deadline = std::time::Instant::now() + Duration::from_secs(5);
+    while client.get_durable_peers().len() < 2 {
+        if std::time::Instant::now() >= deadline {
+            anyhow::bail!("client did not connect to the second durable node");
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    // Requiring both fails: the second node lacks the entity's genesis
+    let trx = ctx.begin();
+    album.edit(&trx)?.year().replace("2001")?;
+    assert!(trx.commit().await.is_err());
+    // The refused edit stays applied locally and the next one builds on it, so let the first
+    // node finish storing it (the commit returned as soon as the second refused)
+    wait_for_album(&first, id, ("Before", "2001")).await?;
+
+    // One acknowledgement suffices, and the refusing node is sent the history it was missing
+    client.set_commit_policy(CommitPolicy::Any);
+    let trx = ctx.begin();
+    album.edit(&trx)?.name().replace("After")?;
+    trx.commit().await?;
+    wait_for_album(&first, id, ("After", "2001")).await?;
+    wait_for_album(&second, id, ("After", "2001")).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn rejected_commit_stays_applied_locally() -> anyhow::Result<()> {
+    let (first, second) = durable_pair().await?;
+    let client = ephemeral_sled_setup().await?;
+    let conn = LocalProcessConnection::new(&first, &client).await?;
+    client.system.wait_system_ready().await;
+
+    let ctx = client.context_async(DEFAULT_CONTEXT).await;
+    let trx = ctx.begin();
+    let id = trx.create(&Album { name: "Before".into(), year: "2000".into() }).await?.id();
+    trx.commit().await?;
+    let album = ctx.get::<AlbumView>(id).await?;
+
+    // Only the second durable node remains, and it lacks the entity's genesis
+    drop(conn);
+    let _conn = LocalProcessConnection::new(&second, &client).await?;
+    let deadline = std::time::Instant::now() + Duration::from_secs(5);
+    while client.get_durable_peers().len() != 1 || client.get_durable_peers()[0] != second.id {
+        if std::time::Instant::now() >= deadline {
+            anyhow::bail!("client did not switch to the second durable node");
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    // Nothing older is pending, so the commit waits for the refusal and fails with it
+    let trx = ctx.begin();
+    album.edit(&trx)?.year().replace("2001")?;
+    let rejected = trx.id().clone();
+    assert!(trx.commit().await.is_err());
+    assert!(matches!(client.outbox.status(&rejected), Some(TransactionStatus::Rejected(_))));
+
+    // As with a rejection in the background, the edit is applied locally all the same
+    assert_eq!(ctx.get::<AlbumView>(id).await?.year()?, "2001");
+    assert!(client.outbox.dismiss(&client, &rejected).await?);
+    Ok(())
+}
+
+#[tokio::test]
+async fn expected_durable_peer_counts_towards_quorum_while_disconnected() -> anyhow::Result<()> {
+    let (first, second) = durable_pair().await?;
+    let client = ephemeral_sled_setup().await?;
+    client.expect_durable_peer(first.id);
+    client.expect_durable_peer(second.id);
+    let _conn_first = LocalProcessConnection::new(&first, &client).await?;
+    let conn_second = LocalProcessConnection::new(&second, &client).await?;
+    client.system.wait_system_ready().await;
+    assert_eq!(client.durable_quorum_size(), 2);
+
+    drop(conn_second);
+    let deadline = std::time::Instant::now() + Duration::from_secs(5);
+    while client.get_durable_peers().len() > 1 {
+        if std::time::Instant::now() >= deadline {
+            anyhow::bail!("client still connected to the second durable node");
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(client.durable_quorum_size(), 2);
+
+    // A majority of two is both, so one connected peer cannot confirm it
+    client.set_commit_policy(CommitPolicy::Majority);
+    let ctx = client.context_async(DEFAULT_CONTEXT).await;
+    let trx = ctx.begin();
+    let id = trx.create(&Album { name: "Majority".into(), year: "2024".into() }).await?.id();
+    let majority = trx.id().clone();
+    trx.commit().await?;
+    assert_eq!(client.outbox.status(&majority), Some(TransactionStatus::Pending));
+
+    // Any connected peer suffices, and the backlog is relayed first
+    client.set_commit_policy(CommitPolicy::Any);
+    let trx = ctx.begin();
+    ctx.get::<AlbumView>(id).await?.edit(&trx)?.year().replace("2025")?;
+    let any = trx.id().clone();
+    trx.commit().await?;
+    let deadline = std::time::Instant::now() + Duration::from_secs(5);
+    while client.outbox.unsynced().value() != 0 {
+        if std::time::Instant::now() >= deadline {
+            anyhow::bail!("outbox still has {} unsynced transactions", client.outbox.unsynced().value());
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(client.outbox.status(&majority), Some(TransactionStatus::Confirmed));
+    assert_eq!(client.outbox.status(&any), Some(TransactionStatus::Confirmed));
+    wait_for_album(&first, id, ("Majority", "2025")).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn commit_after_reconnecting_to_restarted_server() -> anyhow::Result<()> {
+    let engine = Arc::new(SledStorageEngine::new_test()?);
+    let server = Node::new_durable(engine.clone(), PermissiveAgent::new());
+    server.system.create().await?;
+    let client = ephemeral_sled_setup().await?;
+    let conn = LocalProcessConnection::new(&server, &client).await?;
+    client.system.wait_system_ready().await;
+
+    let ctx = client.context_async(DEFAULT_CONTEXT).await;
+    let trx = ctx.begin();
+    let id = trx.create(&Album { name: "Before".into(), year: "2000".into() }).await?.id();
+    trx.commit().await?;
+
+    // The restarted server comes back under a new node id; the old one no longer counts
+    drop(conn);
+    drop(server);
+    let deadline = std::time::Instant::now() + Duration::from_secs(5);
+    while !client.get_durable_peers().is_empty() {
+        if std::time::Instant::now() >= deadline {
+            anyhow::bail!("client still connected to the stopped server");
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    let restarted: TestNode = Node::new_durable(engine, PermissiveAgent::new());
+    restarted.system.wait_system_ready().await;
+    let _conn = LocalProcessConnection::new(&restarted, &client).await?;
+    let deadline = std::time::Instant::now() + Duration::from_secs(5);
+    while client.get_durable_peers().is_empty() {
+        if std::time::Instant::now() >= deadline {
+            anyhow::bail!("client did not connect to the restarted server");
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(client.durable_quorum_size(), 1);
+
+    // Every commit is confirmed under the default policy of all durable peers
+    let trx = ctx.begin();
+    ctx.get::<AlbumView>(id).await?.edit(&trx)?.year().replace("2001")?;
+    let after = trx.id().clone();
+    trx.commit().await?;
+    assert_eq!(client.outbox.status(&after), Some(TransactionStatus::Confirmed));
+    wait_for_album(&restarted, id, ("Before", "2001")).await?;
+    Ok(())
+}
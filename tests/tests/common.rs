@@ -19,6 +19,7 @@ pub use ankurah::{
     },
     Context, EntityId, LiveQuery, Model, Node, PermissiveAgent,
 };
+use ankurah_websocket_server::WebsocketServer;
 use serde::{Deserialize, Serialize};
 use std::{
     str::FromStr,
@@ -157,6 +158,9 @@ impl GatedConnection {
                     node_id: gated.id,
                     durable: gated.durable,
                     system_root: gated.system.root(),
+                    wire: proto::WireSupport::default(),
+                    features: proto::protocol_features(),
+                    min_protocol_version: proto::MIN_PROTOCOL_VERSION,
                     protocol_version: proto::PROTOCOL_VERSION,
                 },
                 Box::new(GatedSender { sender: gated_tx, node_id: gated.id }),
@@ -168,6 +172,9 @@ impl GatedConnection {
                     node_id: other.id,
                     durable: other.durable,
                     system_root: other.system.root(),
+                    wire: proto::WireSupport::default(),
+                    features: proto::protocol_features(),
+                    min_protocol_version: proto::MIN_PROTOCOL_VERSION,
                     protocol_version: proto::PROTOCOL_VERSION,
                 },
                 Box::new(GatedSender { sender: other_tx, node_id: other.id }),
@@ -466,6 +473,59 @@ pub async fn ephemeral_sled_setup() -> Result<Node<SledStorageEngine, Permissive
     Ok(node)
 }
 
+#[allow(unused)]
+pub type SledConnection = LocalProcessConnection<SledStorageEngine, PermissiveAgent, SledStorageEngine, PermissiveAgent>;
+
+/// A durable server and an ephemeral client connected in process; keep the connection alive
+#[allow(unused)]
+pub async fn connected_pair() -> Result<(Context, Context, SledConnection), anyhow::Error> {
+    let server = durable_sled_setup().await?;
+    let client = ephemeral_sled_setup().await?;
+    let conn = LocalProcessConnection::new(&server, &client).await?;
+    client.system.wait_system_ready().await;
+    Ok((server.context(DEFAULT_CONTEXT)?, client.context(DEFAULT_CONTEXT)?, conn))
+}
+
+/// Edits entity `id` on both sides of a [`connected_pair`] before either sees the other's change,
+/// then checks that both sides and the client's live query for `predicate` agree on `expected`.
+/// The predicate should match once the server's edit has landed.
+#[allow(unused)]
+pub async fn converge<V, T>(
+    server: &Context,
+    client: &Context,
+    id: EntityId,
+    predicate: &str,
+    server_edit: impl FnOnce(&V, &ankurah::transaction::Transaction) -> Result<(), anyhow::Error>,
+    client_edit: impl FnOnce(&V, &ankurah::transaction::Transaction) -> Result<(), anyhow::Error>,
+    value: impl Fn(&V) -> T,
+    expected: T,
+) -> Result<(), anyhow::Error>
+where
+    V: View + Clone + Send + Sync + 'static,
+    T: PartialEq + std::fmt::Debug,
+{
+    let query = client.query_wait::<V>(nocache(predicate)?).await?;
+    let _loaded = client.get::<V>(id).await?;
+
+    let server_trx = server.begin();
+    let client_trx = client.begin();
+    server_edit(&server.get::<V>(id).await?, &server_trx)?;
+    client_edit(&client.get::<V>(id).await?, &client_trx)?;
+
+    let watcher = TestWatcher::changeset();
+    let _guard = query.subscribe(&watcher);
+    server_trx.commit().await?;
+    assert!(watcher.wait().await, "the server's edit reaches the client's live query");
+    client_trx.commit().await?;
+
+    assert_eq!(value(&client.get::<V>(id).await?), expected, "client");
+    assert_eq!(value(&server.get::<V>(id).await?), expected, "server");
+    let found = query.peek();
+    assert_eq!(found.iter().map(|v| v.id()).collect::<Vec<_>>(), vec![id]);
+    assert_eq!(value(&found[0]), expected, "client live query");
+    Ok(())
+}
+
 // ============================================================================
 // DAG STRUCTURE VERIFICATION (TestDag and assert_dag! macro)
 // ============================================================================
@@ -625,15 +685,38 @@ macro_rules! clock_eq {
 /// Start a test websocket server and return the server node, URL, and task handle
 #[allow(unused)]
 pub async fn start_test_server() -> anyhow::Result<(Node<SledStorageEngine, PermissiveAgent>, String, tokio::task::JoinHandle<()>)> {
-    use ankurah_websocket_server::WebsocketServer;
-    use rand::Rng;
-    use tracing::info;
+    start_test_server_with(|server| server).await
+}
 
+/// Start a test websocket server as [`start_test_server`] does, letting `configure` adjust it before it runs
+#[allow(unused)]
+pub async fn start_test_server_with(
+    configure: impl Fn(WebsocketServer<SledStorageEngine, PermissiveAgent>) -> WebsocketServer<SledStorageEngine, PermissiveAgent>
+        + Clone
+        + Send
+        + 'static,
+) -> anyhow::Result<(Node<SledStorageEngine, PermissiveAgent>, String, tokio::task::JoinHandle<()>)> {
     // Create and initialize server node
     let server_storage = Arc::new(SledStorageEngine::new_test()?);
     let server_node = Node::new_durable(server_storage, PermissiveAgent::new());
     server_node.system.create().await?;
 
+    let (server_url, server_task) = serve_test_node(server_node.clone(), configure).await?;
+    Ok((server_node, server_url, server_task))
+}
+
+/// Serve `server_node` over websocket on a free local port, returning the URL and task handle
+#[allow(unused)]
+pub async fn serve_test_node(
+    server_node: Node<SledStorageEngine, PermissiveAgent>,
+    configure: impl Fn(WebsocketServer<SledStorageEngine, PermissiveAgent>) -> WebsocketServer<SledStorageEngine, PermissiveAgent>
+        + Clone
+        + Send
+        + 'static,
+) -> anyhow::Result<(String, tokio::task::JoinHandle<()>)> {
+    use rand::Rng;
+    use tracing::info;
+
     let mut rng = rand::thread_rng();
 
     // Retry logic for port conflicts
@@ -650,9 +733,10 @@ pub async fn start_test_server() -> anyhow::Result<(Node<SledStorageEngine, Perm
         // Start server in background task
         let server_node_clone = server_node.clone();
         let bind_addr_clone = bind_addr.clone();
+        let configure = configure.clone();
 
         let server_task = tokio::spawn(async move {
-            let mut server = WebsocketServer::new(server_node_clone);
+            let mut server = configure(WebsocketServer::new(server_node_clone));
             if let Err(e) = server.run(&bind_addr_clone).await {
                 tracing::warn!("Test server error on {}: {}", bind_addr_clone, e);
             }
@@ -688,7 +772,7 @@ pub async fn start_test_server() -> anyhow::Result<(Node<SledStorageEngine, Perm
         // Server task is still running, which means TcpListener::bind() succeeded
         // The server is now listening and ready for connections
         info!("Successfully started websocket server on {} (attempt {})", server_url, attempt + 1);
-        return Ok((server_node, server_url, server_task));
+        return Ok((server_url, server_task));
     }
 
     Err(anyhow::anyhow!("Failed to start test server after {} attempts. Last error: {:?}", MAX_PORT_RETRIES, last_error))
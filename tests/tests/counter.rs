@@ -0,0 +1,64 @@
+mod common;
+use anyhow::Result;
+use common::*;
+use serde::{Deserialize, Serialize};
+
+/// A model whose like count adds up concurrent likes instead of keeping the last write
+#[derive(Model, Debug, Serialize, Deserialize)]
+pub struct Post {
+    pub title: String,
+    #[active_type(Counter)]
+    pub likes: i64,
+}
+
+#[tokio::test]
+async fn concurrent_increments_are_all_counted() -> Result<()> {
+    let node = durable_sled_setup().await?;
+    let ctx = node.context(DEFAULT_CONTEXT)?;
+
+    let trx = ctx.begin();
+    let post = trx.create(&Post { title: "hello".into(), likes: 10 }).await?;
+    let id = post.id();
+    trx.commit().await?;
+
+    let post = ctx.get::<PostView>(id).await?;
+    assert_eq!(post.likes()?, 10);
+
+    // Both transactions start from 10; under LWW one of them would be lost
+    let trx1 = ctx.begin();
+    let trx2 = ctx.begin();
+    post.edit(&trx1)?.likes().increment(1)?;
+    let edited = post.edit(&trx2)?;
+    edited.likes().increment(3)?;
+    edited.likes().decrement(1)?;
+    assert_eq!(edited.likes().value(), 12, "a transaction sees its own increments");
+    trx1.commit().await?;
+    trx2.commit().await?;
+
+    assert_eq!(ctx.get::<PostView>(id).await?.likes()?, 13);
+    let found: Vec<PostView> = ctx.fetch("likes > 12").await?;
+    assert_eq!(found.iter().map(|p| p.id()).collect::<Vec<_>>(), vec![id]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn increments_from_two_nodes_converge() -> Result<()> {
+    let (server, client, _conn) = connected_pair().await?;
+    let trx = server.begin();
+    let id = trx.create(&Post { title: "popular".into(), likes: 0 }).await?.id();
+    trx.commit().await?;
+
+    // Each side likes the post before seeing the other's like
+    converge(
+        &server,
+        &client,
+        id,
+        "likes >= 1",
+        |post: &PostView, trx| Ok(post.edit(trx)?.likes().increment(1)?),
+        |post: &PostView, trx| Ok(post.edit(trx)?.likes().increment(1)?),
+        |post: &PostView| post.likes().unwrap(),
+        2,
+    )
+    .await
+}
@@ -210,6 +210,8 @@ impl<E: StorageEngine + CrashFlushable + 'static> StorageEngine for CrashStorage
     }
 
     async fn delete_all_collections(&self) -> Result<bool, MutationError> { self.inner.delete_all_collections().await }
+
+    async fn list_collections(&self) -> Result<Vec<CollectionId>, RetrievalError> { self.inner.list_collections().await }
 }
 
 /// Wraps a storage collection, deferring to the shared engine-level counters so
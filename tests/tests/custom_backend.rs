@@ -0,0 +1,272 @@
+//! A property backend from outside ankurah: registered at runtime, described to the derive by
+//! its own .ron config, constructed by name from state buffers and event operations, and held to
+//! the conformance kit's laws.
+
+mod common;
+use ankurah::{
+    entity::{Entity, ProvisionalEntity},
+    error::{MutationError, RetrievalError, StateError},
+    property::{
+        backend::{
+            conformance::{self, ConformanceBackend},
+            register_backend, EventLayer, PropertyBackend,
+        },
+        ActiveType, FromActiveType, FromEntity, InitializeWith, PropertyError, PropertyName,
+    },
+    proto::BackendOperation,
+    signals::{
+        broadcast::Broadcast,
+        signal::{Listener, ListenerGuard},
+    },
+};
+use common::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    any::Any,
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+/// Each property holds the largest `i64` ever written to it
+#[derive(Debug, Default)]
+pub struct MaxBackend {
+    values: Mutex<BTreeMap<PropertyName, i64>>,
+    /// Raises made locally since the last `to_operations`
+    pending: Mutex<BTreeMap<PropertyName, i64>>,
+    field_broadcasts: Mutex<BTreeMap<PropertyName, Broadcast>>,
+}
+
+impl MaxBackend {
+    pub fn raise(&self, property_name: &str, value: i64) {
+        let raised = BTreeMap::from([(property_name.to_string(), value)]);
+        let changed = merge(&mut self.values.lock().unwrap(), &raised);
+        merge(&mut self.pending.lock().unwrap(), &raised);
+        self.notify(changed);
+    }
+
+    pub fn get(&self, property_name: &str) -> Option<i64> { self.values.lock().unwrap().get(property_name).copied() }
+
+    fn apply_diff(&self, diff: &[u8]) -> Result<Vec<PropertyName>, MutationError> {
+        let raised: BTreeMap<PropertyName, i64> = bincode::deserialize(diff).map_err(StateError::from)?;
+        Ok(merge(&mut self.values.lock().unwrap(), &raised))
+    }
+
+    fn notify(&self, changed: Vec<PropertyName>) {
+        let broadcasts = self.field_broadcasts.lock().unwrap();
+        for name in changed {
+            if let Some(broadcast) = broadcasts.get(&name) {
+                broadcast.send(());
+            }
+        }
+    }
+}
+
+/// Raise each property of `into` to its value in `from`, returning the properties that rose
+fn merge(into: &mut BTreeMap<PropertyName, i64>, from: &BTreeMap<PropertyName, i64>) -> Vec<PropertyName> {
+    let mut changed = Vec::new();
+    for (name, value) in from {
+        let current = into.entry(name.clone()).or_insert(i64::MIN);
+        if *value > *current {
+            *current = *value;
+            changed.push(name.clone());
+        }
+    }
+    changed
+}
+
+impl PropertyBackend for MaxBackend {
+    fn as_arc_dyn_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync + 'static> { self }
+
+    fn as_debug(&self) -> &dyn std::fmt::Debug { self }
+
+    fn fork(&self) -> Arc<dyn PropertyBackend> {
+        Arc::new(Self {
+            values: Mutex::new(self.values.lock().unwrap().clone()),
+            pending: Mutex::new(self.pending.lock().unwrap().clone()),
+            field_broadcasts: Default::default(),
+        })
+    }
+
+    fn properties(&self) -> Vec<PropertyName> { self.values.lock().unwrap().keys().cloned().collect() }
+
+    fn property_values(&self) -> BTreeMap<PropertyName, Option<ankurah::Value>> {
+        self.values.lock().unwrap().iter().map(|(name, value)| (name.clone(), Some(ankurah::Value::I64(*value)))).collect()
+    }
+
+    fn property_backend_name() -> &'static str { "max" }
+
+    fn to_state_buffer(&self) -> Result<Vec<u8>, StateError> { Ok(bincode::serialize(&*self.values.lock().unwrap())?) }
+
+    fn from_state_buffer(state_buffer: &Vec<u8>) -> Result<Self, RetrievalError> {
+        Ok(Self { values: Mutex::new(bincode::deserialize(state_buffer)?), ..Default::default() })
+    }
+
+    fn to_operations(&self) -> Result<Option<Vec<BackendOperation>>, MutationError> {
+        let pending = std::mem::take(&mut *self.pending.lock().unwrap());
+        if pending.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(vec![BackendOperation { diff: bincode::serialize(&pending).map_err(StateError::from)? }]))
+    }
+
+    fn apply_operations(&self, operations: &[BackendOperation]) -> Result<(), MutationError> {
+        let mut changed = Vec::new();
+        for operation in operations {
+            changed.extend(self.apply_diff(&operation.diff)?);
+        }
+        self.notify(changed);
+        Ok(())
+    }
+
+    fn apply_layer(&self, layer: &EventLayer) -> Result<(), MutationError> {
+        let mut changed = Vec::new();
+        for event in layer.to_apply() {
+            for operation in event.operations().backend_operations(Self::property_backend_name()) {
+                changed.extend(self.apply_diff(&operation.diff)?);
+            }
+        }
+        self.notify(changed);
+        Ok(())
+    }
+
+    fn listen_field(&self, field_name: &PropertyName, listener: Listener) -> ListenerGuard {
+        let mut field_broadcasts = self.field_broadcasts.lock().unwrap();
+        field_broadcasts.entry(field_name.clone()).or_default().reference().listen(listener).into()
+    }
+}
+
+/// Runs the core conformance laws against the max backend. A `Write` raises a field to a value.
+struct MaxAdopter;
+
+impl ConformanceBackend for MaxAdopter {
+    type Write = (&'static str, i64);
+
+    fn backend_name() -> &'static str { MaxBackend::property_backend_name() }
+
+    fn stage_write(_backend: &Arc<dyn PropertyBackend>, write: &Self::Write) -> Vec<BackendOperation> {
+        let scratch = MaxBackend::default();
+        scratch.raise(write.0, write.1);
+        scratch.to_operations().expect("to_operations").expect("a raise must produce operations")
+    }
+
+    fn concurrent_writes() -> Vec<Self::Write> { vec![("score", 3), ("score", 7), ("score", 5)] }
+
+    fn linear_writes() -> Vec<Self::Write> { vec![("score", 1), ("best", 4), ("score", 2)] }
+}
+
+#[test]
+fn max_backend_state_buffer_round_trip() {
+    register_backend::<MaxBackend>().expect("the max backend's name is free");
+    conformance::law_state_buffer_round_trip::<MaxAdopter>();
+}
+
+#[test]
+fn max_backend_operation_round_trip_across_nodes() {
+    register_backend::<MaxBackend>().expect("the max backend's name is free");
+    conformance::law_operation_round_trip_across_nodes::<MaxAdopter>();
+}
+
+#[test]
+fn max_backend_provenance() {
+    register_backend::<MaxBackend>().expect("the max backend's name is free");
+    conformance::law_provenance::<MaxAdopter>();
+}
+
+#[test]
+fn max_backend_within_layer_permutation_invariance() {
+    register_backend::<MaxBackend>().expect("the max backend's name is free");
+    conformance::law_within_layer_permutation_invariance::<MaxAdopter>();
+}
+
+#[test]
+fn max_backend_cross_order_determinism() {
+    register_backend::<MaxBackend>().expect("the max backend's name is free");
+    conformance::law_cross_order_determinism::<MaxAdopter>();
+}
+
+/// The active type a `#[active_type(Max)]` field compiles to
+#[derive(Clone)]
+pub struct Max {
+    property_name: PropertyName,
+    backend: Arc<MaxBackend>,
+    entity: Entity,
+}
+
+impl Max {
+    pub fn raise(&self, value: i64) -> Result<(), PropertyError> {
+        if !self.entity.is_writable() {
+            return Err(PropertyError::TransactionClosed);
+        }
+        self.backend.raise(&self.property_name, value);
+        Ok(())
+    }
+
+    pub fn get(&self) -> Result<i64, PropertyError> { self.backend.get(&self.property_name).ok_or(PropertyError::Missing) }
+}
+
+impl ActiveType for Max {
+    const BACKEND: &'static str = "max";
+}
+
+impl FromEntity for Max {
+    fn from_entity(property_name: PropertyName, entity: &Entity) -> Self {
+        let backend = entity.get_backend::<MaxBackend>().expect("max backend is registered");
+        Self { property_name, backend, entity: entity.clone() }
+    }
+}
+
+impl FromActiveType<Max> for i64 {
+    fn from_active(active: Max) -> Result<Self, PropertyError> { active.get() }
+}
+
+impl InitializeWith<i64> for Max {
+    fn initialize_with(provisional: &mut ProvisionalEntity, property_name: PropertyName, value: &i64) {
+        provisional.get_backend::<MaxBackend>().expect("max backend is registered").raise(&property_name, *value);
+    }
+}
+
+#[derive(Model, Debug, Serialize, Deserialize)]
+#[model(backend_config = "tests/fixtures/max.ron")]
+pub struct Score {
+    pub player: String,
+    #[active_type(Max)]
+    pub best: i64,
+}
+
+#[tokio::test]
+async fn registered_backend_round_trips_through_storage_and_peers() -> anyhow::Result<()> {
+    register_backend::<MaxBackend>()?;
+
+    let server = durable_sled_setup().await?;
+    let client = ephemeral_sled_setup().await?;
+    let _conn = LocalProcessConnection::new(&server, &client).await?;
+    client.system.wait_system_ready().await;
+    let server = server.context(DEFAULT_CONTEXT)?;
+    let client = client.context(DEFAULT_CONTEXT)?;
+
+    let trx = server.begin();
+    let score = trx.create(&Score { player: "ada".into(), best: 5 }).await?;
+    let id = score.id();
+    trx.commit().await?;
+
+    // The client builds the backend from the server's state buffer
+    let query = client.query_wait::<ScoreView>(nocache("player = 'ada'")?).await?;
+    assert_eq!(query.peek().first().map(|s| s.best().unwrap()), Some(5));
+
+    // and from the operations of each later event
+    let trx = server.begin();
+    let score = server.get::<ScoreView>(id).await?.edit(&trx)?;
+    score.best().raise(3)?;
+    score.best().raise(9)?;
+    trx.commit().await?;
+
+    let watcher = TestWatcher::changeset();
+    let _guard = query.subscribe(&watcher);
+    if query.peek().first().map(|s| s.best().unwrap()) != Some(9) {
+        assert!(watcher.wait().await, "the raise reaches the client");
+    }
+    assert_eq!(client.get::<ScoreView>(id).await?.best()?, 9);
+    assert_eq!(server.get::<ScoreView>(id).await?.best()?, 9);
+
+    Ok(())
+}
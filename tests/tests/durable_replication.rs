@@ -0,0 +1,130 @@
+//! Durable-to-durable replication: a replica pulls what its source stores
+//! (including models registered after they parted), two durable nodes
+//! replicating from each other converge, and an interrupted sweep resumes
+//! from its persisted cursor.
+
+mod common;
+use ankurah::core::replication::REPLICATION_COLLECTION_ID;
+use ankurah::core::storage::{restore_dump, StorageDump, VerifyOptions};
+use common::*;
+use std::sync::Arc;
+
+type TestNode = Node<SledStorageEngine, PermissiveAgent>;
+
+/// A source and a replica of the same system: the replica's storage starts as a dump of the source
+async fn source_and_replica_storage() -> anyhow::Result<(TestNode, Arc<SledStorageEngine>)> {
+    let engine = Arc::new(SledStorageEngine::new_test()?);
+    let source = Node::new_durable(engine.clone(), PermissiveAgent::new());
+    source.system.create().await?;
+    // Register the model before the replica is split off
+    let trx = source.context(DEFAULT_CONTEXT)?.begin();
+    trx.create(&Album { name: "Seed".into(), year: "1999".into() }).await?;
+    trx.commit().await?;
+
+    let storage = Arc::new(SledStorageEngine::new_test()?);
+    restore_dump(storage.as_ref(), engine.dump().await?).await?;
+    Ok((source, storage))
+}
+
+async fn durable_node(storage: Arc<SledStorageEngine>) -> TestNode {
+    let node = Node::new_durable(storage, PermissiveAgent::new());
+    node.system.wait_system_ready().await;
+    node
+}
+
+async fn albums(node: &TestNode) -> anyhow::Result<Vec<(String, String)>> {
+    let albums: Vec<AlbumView> = node.context(DEFAULT_CONTEXT)?.fetch("name != ''").await?;
+    let mut albums: Vec<_> = albums.into_iter().map(|album| (album.name().unwrap(), album.year().unwrap())).collect();
+    albums.sort();
+    Ok(albums)
+}
+
+#[tokio::test]
+async fn durable_nodes_converge() -> anyhow::Result<()> {
+    let (source, storage) = source_and_replica_storage().await?;
+    let replica = durable_node(storage).await;
+    assert_eq!(source.system.root(), replica.system.root());
+
+    // Both take writes while apart, including the shared seed album and a model the replica never registered
+    let on_source = source.context(DEFAULT_CONTEXT)?;
+    create_albums(&on_source, [2001, 2002, 2003]).await?;
+    let trx = on_source.begin();
+    trx.create(&Pet { name: "Rex".into(), age: "3".into() }).await?;
+    trx.commit().await?;
+    let on_replica = replica.context(DEFAULT_CONTEXT)?;
+    create_albums(&on_replica, [2004]).await?;
+    let seed: AlbumView = on_replica.fetch("name = 'Seed'").await?.pop().unwrap();
+    let trx = on_replica.begin();
+    seed.edit(&trx)?.year().replace("2000")?;
+    trx.commit().await?;
+
+    let _conn = LocalProcessConnection::new(&source, &replica).await?;
+    let pulled = replica.replicate_from(source.id, &DEFAULT_CONTEXT).await?;
+    // Three albums and a pet, plus the catalog rows of the pet's model
+    assert!(pulled.pulled > 4, "pulled {:?}", pulled);
+    let pushed = source.replicate_from(replica.id, &DEFAULT_CONTEXT).await?;
+    assert_eq!(pushed.pulled, 2, "an album and the seed edit");
+
+    let expected: Vec<(String, String)> =
+        [("Album 2001", "2001"), ("Album 2002", "2002"), ("Album 2003", "2003"), ("Album 2004", "2004"), ("Seed", "2000")]
+            .into_iter()
+            .map(|(name, year)| (name.to_string(), year.to_string()))
+            .collect();
+    assert_eq!(albums(&source).await?, expected);
+    assert_eq!(albums(&replica).await?, expected);
+    let pets: Vec<PetView> = replica.context(DEFAULT_CONTEXT)?.fetch("name = 'Rex'").await?;
+    assert_eq!(pets.len(), 1);
+
+    // Converged: another pass compares every head and pulls nothing
+    let again = replica.replicate_from(source.id, &DEFAULT_CONTEXT).await?;
+    assert_eq!(again.pulled, 0);
+    assert!(again.compared >= expected.len());
+    Ok(())
+}
+
+#[tokio::test]
+async fn interrupted_replication_resumes_after_restart() -> anyhow::Result<()> {
+    let (source, storage) = source_and_replica_storage().await?;
+    create_albums(&source.context(DEFAULT_CONTEXT)?, 2001..=2010).await?;
+
+    // Albums alone take six pages of two, so four pages stop partway through the sweep
+    let replica = durable_node(storage.clone()).await;
+    let conn = LocalProcessConnection::new(&source, &replica).await?;
+    let interrupted = replica.replicate_pages_from(source.id, &DEFAULT_CONTEXT, 2, 4).await?;
+    assert!(interrupted.pulled < 10);
+    drop(conn);
+    drop(replica);
+
+    // A restarted replica continues from its cursor rather than comparing everything again
+    let replica = durable_node(storage).await;
+    let _conn = LocalProcessConnection::new(&source, &replica).await?;
+    let resumed = replica.replicate_from(source.id, &DEFAULT_CONTEXT).await?;
+    assert_eq!(interrupted.pulled + resumed.pulled, 10);
+    assert_eq!(albums(&replica).await?.len(), 11);
+
+    // The finished sweep reset the cursors: the next one covers every collection from the start
+    let full = replica.replicate_from(source.id, &DEFAULT_CONTEXT).await?;
+    assert_eq!(full.pulled, 0);
+    assert_eq!(interrupted.compared + resumed.compared, full.compared);
+    Ok(())
+}
+
+#[tokio::test]
+async fn replica_verifies_clean_after_replication() -> anyhow::Result<()> {
+    let (source, storage) = source_and_replica_storage().await?;
+    let replica = durable_node(storage.clone()).await;
+    create_albums(&source.context(DEFAULT_CONTEXT)?, [2001, 2002]).await?;
+
+    let _conn = LocalProcessConnection::new(&source, &replica).await?;
+    let pulled = replica.replicate_from(source.id, &DEFAULT_CONTEXT).await?;
+    assert_eq!(pulled.pulled, 2);
+    assert!(storage.list_collections()?.iter().any(|c| c.as_str() == REPLICATION_COLLECTION_ID));
+
+    // The cursors are node-local records with no event log, so they are not verified
+    let reports = storage.verify(VerifyOptions::default()).await?;
+    assert!(reports.iter().all(|(collection, _)| collection.as_str() != REPLICATION_COLLECTION_ID));
+    for (collection, report) in &reports {
+        assert!(report.is_clean(), "{collection}: {:?}", report.problems);
+    }
+    Ok(())
+}
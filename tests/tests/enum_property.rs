@@ -0,0 +1,85 @@
+mod common;
+use ankurah::{selection, Property, Value};
+use anyhow::Result;
+use common::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Property, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Status {
+    Open,
+    #[serde(rename = "done")]
+    Closed,
+}
+
+#[derive(Property, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Resolution {
+    Pending,
+    Blocked { reason: String },
+    Fixed(u32),
+}
+
+#[derive(Model, Debug, Serialize, Deserialize)]
+pub struct Ticket {
+    pub title: String,
+    pub status: Status,
+    pub resolution: Resolution,
+}
+
+fn titles(tickets: &[TicketView]) -> Vec<String> {
+    let mut titles: Vec<String> = tickets.iter().map(|t| t.title().unwrap()).collect();
+    titles.sort();
+    titles
+}
+
+#[test]
+fn unit_variants_are_stored_by_name() -> Result<()> {
+    assert_eq!(Status::Open.into_value()?, Some(Value::String("Open".into())));
+    assert_eq!(Status::Closed.into_value()?, Some(Value::String("done".into())));
+    assert_eq!(Resolution::Fixed(7).into_value()?, Some(Value::String(r#"{"Fixed":7}"#.into())));
+    assert_eq!(
+        Resolution::from_value(Some(Value::String(r#"{"Blocked":{"reason":"waiting"}}"#.into())))?,
+        Resolution::Blocked { reason: "waiting".into() }
+    );
+    // Written as JSON text before unit variants were stored bare
+    assert_eq!(Status::from_value(Some(Value::String(r#""Open""#.into())))?, Status::Open);
+    assert!(Status::from_value(Some(Value::String("Reopened".into()))).is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn enum_fields_compare_by_variant_name() -> Result<()> {
+    let node = durable_sled_setup().await?;
+    let ctx = node.context(DEFAULT_CONTEXT)?;
+
+    let trx = ctx.begin();
+    let crash = trx.create(&Ticket { title: "crash".into(), status: Status::Open, resolution: Resolution::Pending }).await?.id();
+    trx.create(&Ticket { title: "typo".into(), status: Status::Closed, resolution: Resolution::Fixed(42) }).await?;
+    trx.create(&Ticket { title: "upgrade".into(), status: Status::Open, resolution: Resolution::Blocked { reason: "vendor".into() } })
+        .await?;
+    trx.commit().await?;
+
+    let loaded = ctx.get::<TicketView>(crash).await?;
+    assert_eq!(loaded.status()?, Status::Open);
+    assert_eq!(loaded.resolution()?, Resolution::Pending);
+
+    assert_eq!(titles(&ctx.fetch("status = 'Open'").await?), vec!["crash", "upgrade"]);
+    assert_eq!(titles(&ctx.fetch("status = 'done'").await?), vec!["typo"]);
+    assert_eq!(titles(&ctx.fetch("resolution = 'Pending' OR status = 'done'").await?), vec!["crash", "typo"]);
+
+    // Enum values work as placeholders, data-carrying variants included
+    let status = Status::Closed;
+    assert_eq!(titles(&ctx.fetch(selection!({ status })).await?), vec!["typo"]);
+    let blocked = Resolution::Blocked { reason: "vendor".into() };
+    assert_eq!(titles(&ctx.fetch(selection!("resolution = {}", blocked)).await?), vec!["upgrade"]);
+
+    let query = ctx.query_wait::<TicketView>("status = 'Open'").await?;
+    let watcher = TestWatcher::changeset();
+    let _guard = query.subscribe(&watcher);
+    let trx = ctx.begin();
+    loaded.edit(&trx)?.status().set(&Status::Closed)?;
+    trx.commit().await?;
+    assert!(watcher.wait().await, "closing the ticket takes it out of the open query");
+    assert_eq!(titles(&query.peek()), vec!["upgrade"]);
+
+    Ok(())
+}
@@ -0,0 +1,117 @@
+use ankurah::policy::DEFAULT_CONTEXT as c;
+use anyhow::Result;
+use serde_json::{json, Value as JsonValue};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+mod common;
+use common::*;
+
+/// Send one HTTP request to the server behind a `ws://` url and return the status and JSON body
+async fn http(server_url: &str, method: &str, path: &str, body: Option<JsonValue>) -> Result<(u16, JsonValue)> {
+    let address = server_url.trim_start_matches("ws://");
+    let mut stream = tokio::net::TcpStream::connect(address).await?;
+    let body = body.map(|body| body.to_string()).unwrap_or_default();
+    let request = format!(
+        "{method} {path} HTTP/1.1\r\nHost: {address}\r\nConnection: close\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(request.as_bytes()).await?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await?;
+    let (head, body) = response.split_once("\r\n\r\n").ok_or_else(|| anyhow::anyhow!("malformed response: {response}"))?;
+    let status = head.split(' ').nth(1).ok_or_else(|| anyhow::anyhow!("malformed status line: {head}"))?.parse()?;
+    Ok((status, serde_json::from_str(body)?))
+}
+
+/// Read a server-sent-events stream until it has delivered an event of `kind`, returning its data
+async fn next_event(stream: &mut tokio::net::TcpStream, buffer: &mut String, kind: &str) -> Result<JsonValue> {
+    let marker = format!("event: {kind}\ndata: ");
+    loop {
+        if let Some(start) = buffer.find(&marker) {
+            let data = &buffer[start + marker.len()..];
+            if let Some(end) = data.find('\n') {
+                let event = serde_json::from_str(&data[..end])?;
+                buffer.replace_range(..start + marker.len() + end, "");
+                return Ok(event);
+            }
+        }
+        let mut chunk = [0u8; 4096];
+        let read = tokio::time::timeout(Duration::from_secs(10), stream.read(&mut chunk)).await??;
+        anyhow::ensure!(read > 0, "stream closed waiting for {kind}: {buffer}");
+        buffer.push_str(&String::from_utf8_lossy(&chunk[..read]));
+    }
+}
+
+#[tokio::test]
+async fn test_gateway_crud_over_json() -> Result<()> {
+    let (server_node, server_url, server_task) = start_test_server_with(|server| server.gateway()).await?;
+    let ctx = server_node.context(c)?;
+    let rumours = {
+        let trx = ctx.begin();
+        let id = trx.create(&Album { name: "Rumours".into(), year: "1977".into() }).await?.id();
+        trx.commit().await?;
+        id
+    };
+
+    let (status, albums) = http(&server_url, "GET", "/api/album?q=year%20%3D%20%271977%27", None).await?;
+    assert_eq!(status, 200, "{albums}");
+    assert_eq!(albums, json!([{ "id": rumours.to_base64(), "name": "Rumours", "year": "1977" }]));
+
+    let (status, created) = http(&server_url, "POST", "/api/album", Some(json!({ "name": "Tusk", "year": 1979 }))).await?;
+    assert_eq!(status, 201, "{created}");
+    let tusk = EntityId::from_base64(created["id"].as_str().unwrap())?;
+    let view: AlbumView = ctx.get(tusk).await?;
+    assert_eq!((view.name()?, view.year()?), ("Tusk".to_string(), "1979".to_string()));
+
+    let (status, updated) =
+        http(&server_url, "PATCH", &format!("/api/album/{}", tusk.to_base64()), Some(json!({ "year": "1980" }))).await?;
+    assert_eq!(status, 200, "{updated}");
+    assert_eq!(ctx.get::<AlbumView>(tusk).await?.year()?, "1980");
+
+    let (status, fetched) = http(&server_url, "GET", &format!("/api/album/{}", tusk.to_base64()), None).await?;
+    assert_eq!(status, 200, "{fetched}");
+    assert_eq!(fetched, json!({ "id": tusk.to_base64(), "name": "Tusk", "year": "1980" }));
+
+    // LWW properties take the same property maps as Yrs ones
+    ctx.register_model::<Record>().await?;
+    let (status, created) = http(&server_url, "POST", "/api/record", Some(json!({ "title": "Dreams", "artist": null }))).await?;
+    assert_eq!(status, 201, "{created}");
+    let dreams = EntityId::from_base64(created["id"].as_str().unwrap())?;
+    assert_eq!(ctx.get::<RecordView>(dreams).await?.title()?, "Dreams");
+
+    let (status, error) = http(&server_url, "POST", "/api/album", Some(json!({ "title": "Mirage" }))).await?;
+    assert_eq!(status, 422, "{error}");
+    let (status, error) = http(&server_url, "GET", "/api/album?q=year%20%3D", None).await?;
+    assert_eq!(status, 400, "{error}");
+
+    server_task.abort();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_gateway_streams_live_query() -> Result<()> {
+    let (server_node, server_url, server_task) = start_test_server_with(|server| server.gateway()).await?;
+    let ctx = server_node.context(c)?;
+    {
+        let trx = ctx.begin();
+        trx.create(&Album { name: "Rumours".into(), year: "1977".into() }).await?;
+        trx.commit().await?;
+    }
+
+    let address = server_url.trim_start_matches("ws://");
+    let mut stream = tokio::net::TcpStream::connect(address).await?;
+    stream.write_all(format!("GET /api/album/live?q=year%20%3D%20%271977%27 HTTP/1.1\r\nHost: {address}\r\n\r\n").as_bytes()).await?;
+    let mut buffer = String::new();
+    let initial = next_event(&mut stream, &mut buffer, "initial").await?;
+    assert_eq!(initial.as_array().map(|albums| albums.len()), Some(1), "{initial}");
+    assert_eq!(initial[0]["name"], "Rumours");
+
+    let (status, created) = http(&server_url, "POST", "/api/album", Some(json!({ "name": "Tusk", "year": "1977" }))).await?;
+    assert_eq!(status, 201, "{created}");
+    let added = next_event(&mut stream, &mut buffer, "add").await?;
+    assert_eq!(added, created);
+
+    server_task.abort();
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use ankurah::{core::metrics, policy::DEFAULT_CONTEXT as c, Node, PermissiveAgent};
+use ankurah_storage_sled::SledStorageEngine;
+use ankurah_websocket_client::WebsocketClient;
+use ankurah_websocket_server::metrics_exporter_prometheus::PrometheusBuilder;
+use anyhow::Result;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+mod common;
+use common::*;
+
+/// GET `path` from the server behind a `ws://` url and return the response body
+async fn http_get(server_url: &str, path: &str) -> Result<String> {
+    let address = server_url.trim_start_matches("ws://");
+    let mut stream = tokio::net::TcpStream::connect(address).await?;
+    stream.write_all(format!("GET {path} HTTP/1.1\r\nHost: {address}\r\nConnection: close\r\n\r\n").as_bytes()).await?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await?;
+    let (head, body) = response.split_once("\r\n\r\n").ok_or_else(|| anyhow::anyhow!("malformed response: {response}"))?;
+    assert!(head.starts_with("HTTP/1.1 200"), "unexpected response: {head}");
+    Ok(body.to_string())
+}
+
+#[tokio::test]
+async fn test_prometheus_route_reports_peer_traffic() -> Result<()> {
+    let handle = PrometheusBuilder::new().install_recorder()?;
+    let (server_node, server_url, server_task) = start_test_server_with(move |server| server.prometheus(handle.clone())).await?;
+    let server_ctx = server_node.context(c)?;
+    {
+        let trx = server_ctx.begin();
+        trx.create(&Album { name: "Rumours".into(), year: "1977".into() }).await?;
+        trx.commit().await?;
+    }
+
+    let client_node = Node::new(Arc::new(SledStorageEngine::new_test()?), PermissiveAgent::new());
+    let client = WebsocketClient::new(client_node.clone(), &server_url).await?;
+    client.wait_connected().await?;
+    client_node.system.wait_system_ready().await;
+    let client_ctx = client_node.context(c)?;
+
+    assert_eq!(client_ctx.fetch::<AlbumView>("year = '1977'").await?.len(), 1);
+    let _live = client_ctx.query_wait::<AlbumView>("year = '1977'").await?;
+    {
+        let trx = server_ctx.begin();
+        trx.create(&Album { name: "Tusk".into(), year: "1979".into() }).await?;
+        trx.commit().await?;
+    }
+
+    let body = http_get(&server_url, "/metrics").await?;
+    let client_label = format!("peer=\"{}\"", client_node.id.to_base64_short());
+    for name in [
+        metrics::MESSAGES_RECEIVED,
+        metrics::MESSAGES_SENT,
+        metrics::BYTES_RECEIVED,
+        metrics::BYTES_SENT,
+        metrics::REQUEST_DURATION,
+        metrics::PEER_SUBSCRIPTIONS,
+        metrics::NOTIFICATION_FANOUT,
+        metrics::STORAGE_DURATION,
+    ] {
+        assert!(body.contains(name), "{name} missing from:\n{body}");
+    }
+    assert!(body.lines().any(|line| line.starts_with(metrics::BYTES_RECEIVED) && line.contains(&client_label)), "{body}");
+    assert!(body.lines().any(|line| line.starts_with(metrics::MESSAGES_RECEIVED) && line.contains("kind=\"request\"")), "{body}");
+    assert!(body.contains(&format!("{}{{{client_label}}} 1", metrics::PEER_SUBSCRIPTIONS)), "{body}");
+
+    client.shutdown().await?;
+    server_task.abort();
+    Ok(())
+}
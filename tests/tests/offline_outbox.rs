@@ -0,0 +1,183 @@
+//! Transactions committed on an ephemeral node while no durable peer is
+//! connected are queued in the node's outbox and relayed on reconnect.
+
+mod common;
+use ankurah::core::outbox::TransactionStatus;
+use common::*;
+use std::time::Duration;
+
+type TestNode = Node<SledStorageEngine, PermissiveAgent>;
+
+async fn wait_disconnected(client: &TestNode) -> anyhow::Result<()> {
+    let deadline = std::time::Instant::now() + Duration::from_secs(5);
+    while !client.get_durable_peers().is_empty() {
+        if std::time::Instant::now() >= deadline {
+            anyhow::bail!("client still has a durable peer after disconnect");
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    Ok(())
+}
+
+async fn wait_synced(client: &TestNode) -> anyhow::Result<()> {
+    let deadline = std::time::Instant::now() + Duration::from_secs(5);
+    while client.outbox.unsynced().value() != 0 {
+        if std::time::Instant::now() >= deadline {
+            anyhow::bail!("outbox still has {} unsynced transactions", client.outbox.unsynced().value());
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn offline_commits_replay_on_reconnect() -> anyhow::Result<()> {
+    let server = durable_sled_setup().await?;
+    let client = ephemeral_sled_setup().await?;
+    let conn = LocalProcessConnection::new(&server, &client).await?;
+    client.system.wait_system_ready().await;
+
+    let ctx = client.context_async(DEFAULT_CONTEXT).await;
+    let album_id = {
+        let trx = ctx.begin();
+        let album = trx.create(&Album { name: "Origin".into(), year: "2000".into() }).await?;
+        let id = album.id();
+        let trx_id = trx.id().clone();
+        trx.commit().await?;
+        assert_eq!(client.outbox.status(&trx_id), Some(TransactionStatus::Confirmed));
+        id
+    };
+    assert_eq!(client.outbox.unsynced().value(), 0);
+
+    let album = ctx.get::<AlbumView>(album_id).await?;
+    drop(conn);
+    wait_disconnected(&client).await?;
+
+    // Two offline commits: both succeed locally and stay pending
+    let first = {
+        let trx = ctx.begin();
+        album.edit(&trx)?.year().replace("2001")?;
+        let id = trx.id().clone();
+        trx.commit().await?;
+        id
+    };
+    let second = {
+        let trx = ctx.begin();
+        album.edit(&trx)?.name().replace("Offline")?;
+        let id = trx.id().clone();
+        trx.commit().await?;
+        id
+    };
+    assert_eq!(client.outbox.unsynced().value(), 2);
+    assert_eq!(client.outbox.status(&first), Some(TransactionStatus::Pending));
+    assert_eq!(client.outbox.status(&second), Some(TransactionStatus::Pending));
+    let on_server = server.context(DEFAULT_CONTEXT)?.get::<AlbumView>(album_id).await?;
+    assert_eq!((on_server.name()?, on_server.year()?), ("Origin".to_string(), "2000".to_string()));
+
+    // Reconnecting replays both, in order
+    let _conn = LocalProcessConnection::new(&server, &client).await?;
+    wait_synced(&client).await?;
+    assert_eq!(client.outbox.status(&first), Some(TransactionStatus::Confirmed));
+    assert_eq!(client.outbox.status(&second), Some(TransactionStatus::Confirmed));
+    let on_server = server.context(DEFAULT_CONTEXT)?.get::<AlbumView>(album_id).await?;
+    assert_eq!((on_server.name()?, on_server.year()?), ("Offline".to_string(), "2001".to_string()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn commit_behind_backlog_is_replayed_after_it() -> anyhow::Result<()> {
+    let server = durable_sled_setup().await?;
+    let client = ephemeral_sled_setup().await?;
+    let conn = LocalProcessConnection::new(&server, &client).await?;
+    client.system.wait_system_ready().await;
+
+    let ctx = client.context_async(DEFAULT_CONTEXT).await;
+    let album = {
+        let trx = ctx.begin();
+        let album = trx.create(&Album { name: "Origin".into(), year: "2000".into() }).await?;
+        let id = album.id();
+        trx.commit().await?;
+        ctx.get::<AlbumView>(id).await?
+    };
+    drop(conn);
+    wait_disconnected(&client).await?;
+
+    for year in ["2001", "2002", "2003"] {
+        let trx = ctx.begin();
+        album.edit(&trx)?.year().replace(year)?;
+        trx.commit().await?;
+    }
+    assert_eq!(client.outbox.unsynced().value(), 3);
+
+    // Committed while the backlog may still be replaying: it is persisted, and relayed
+    // only after every earlier transaction
+    let _conn = LocalProcessConnection::new(&server, &client).await?;
+    let online = {
+        let trx = ctx.begin();
+        album.edit(&trx)?.name().replace("Online")?;
+        let id = trx.id().clone();
+        trx.commit().await?;
+        id
+    };
+    wait_synced(&client).await?;
+    assert_eq!(client.outbox.status(&online), Some(TransactionStatus::Confirmed));
+    let on_server = server.context(DEFAULT_CONTEXT)?.get::<AlbumView>(album.id()).await?;
+    assert_eq!((on_server.name()?, on_server.year()?), ("Online".to_string(), "2003".to_string()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn outbox_survives_node_restart() -> anyhow::Result<()> {
+    let server = durable_sled_setup().await?;
+    let storage = std::sync::Arc::new(SledStorageEngine::new_test()?);
+    let client: TestNode = Node::new(storage.clone(), PermissiveAgent::new());
+    let conn = LocalProcessConnection::new(&server, &client).await?;
+    client.system.wait_system_ready().await;
+
+    let ctx = client.context_async(DEFAULT_CONTEXT).await;
+    let album_id = {
+        let trx = ctx.begin();
+        let album = trx.create(&Album { name: "Origin".into(), year: "2000".into() }).await?;
+        let id = album.id();
+        trx.commit().await?;
+        id
+    };
+
+    let album = ctx.get::<AlbumView>(album_id).await?;
+    drop(conn);
+    wait_disconnected(&client).await?;
+    let pending = {
+        let trx = ctx.begin();
+        album.edit(&trx)?.year().replace("2002")?;
+        let id = trx.id().clone();
+        trx.commit().await?;
+        id
+    };
+    assert_eq!(client.outbox.unsynced().value(), 1);
+    drop(album);
+    drop(ctx);
+    drop(client);
+
+    // A new node over the same storage picks the queued transaction back up
+    let client: TestNode = Node::new(storage, PermissiveAgent::new());
+    let deadline = std::time::Instant::now() + Duration::from_secs(5);
+    while client.outbox.unsynced().value() != 1 {
+        if std::time::Instant::now() >= deadline {
+            anyhow::bail!("restarted node did not load its outbox");
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(client.outbox.status(&pending), Some(TransactionStatus::Pending));
+
+    // Once rejoined, the replay runs under the node's only write credential
+    let _conn = LocalProcessConnection::new(&server, &client).await?;
+    let _ctx = client.context_async(DEFAULT_CONTEXT).await;
+    wait_synced(&client).await?;
+    assert_eq!(client.outbox.status(&pending), Some(TransactionStatus::Confirmed));
+    let on_server = server.context(DEFAULT_CONTEXT)?.get::<AlbumView>(album_id).await?;
+    assert_eq!(on_server.year()?, "2002");
+
+    Ok(())
+}
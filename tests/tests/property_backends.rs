@@ -11,6 +11,7 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use wasm_bindgen::prelude::*;
 
+// Brings its own wasm binding, so the Property derive generates none for it
 #[derive(Property, Serialize, Deserialize, PartialEq, Eq, Debug)]
 #[wasm_bindgen]
 pub enum Visibility {
@@ -10,6 +10,7 @@ use ankurah_storage_sled::SledStorageEngine;
 use ankurah_websocket_client::WebsocketClient;
 use anyhow::Result;
 use futures_util::{SinkExt, StreamExt};
+use std::ops::RangeInclusive;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio_tungstenite::tungstenite::Message as WsMessage;
@@ -27,26 +28,50 @@ impl PeerSender for NullSender {
     fn cloned(&self) -> Box<dyn PeerSender> { Box::new(self.clone()) }
 }
 
-fn presence(protocol_version: u32) -> proto::Presence {
-    proto::Presence { node_id: proto::EntityId::random(), durable: false, system_root: None, protocol_version }
+fn presence(versions: RangeInclusive<u32>) -> proto::Presence {
+    proto::Presence {
+        node_id: proto::EntityId::random(),
+        durable: false,
+        system_root: None,
+        wire: Default::default(),
+        features: vec![],
+        min_protocol_version: *versions.start(),
+        protocol_version: *versions.end(),
+    }
 }
 
-/// The core enforcement point: register_peer refuses incompatible versions
-/// for every transport, and accepts the current one.
+fn current() -> RangeInclusive<u32> { proto::MIN_PROTOCOL_VERSION..=proto::PROTOCOL_VERSION }
+
+/// The core enforcement point: register_peer refuses peers with no version in
+/// common for every transport, and accepts any overlapping range.
 #[tokio::test]
 async fn register_peer_refuses_incompatible_version() -> Result<()> {
     let node = Node::new(Arc::new(SledStorageEngine::new_test()?), PermissiveAgent::new());
 
-    for bad_version in [0, proto::PROTOCOL_VERSION + 1] {
-        let p = presence(bad_version);
+    for bad_versions in [0..=0, proto::PROTOCOL_VERSION + 1..=proto::PROTOCOL_VERSION + 2] {
+        let p = presence(bad_versions.clone());
         let peer_id = p.node_id;
         let err = node.register_peer(p, Box::new(NullSender(peer_id))).expect_err("must refuse");
-        assert_eq!(err, proto::PresenceRejection { expected: proto::PROTOCOL_VERSION, received: bad_version });
+        assert_eq!(err, proto::PresenceRejection { supported: current(), offered: bad_versions });
     }
 
-    let p = presence(proto::PROTOCOL_VERSION);
+    let p = presence(current());
+    let peer_id = p.node_id;
+    let version = node.register_peer(p, Box::new(NullSender(peer_id))).expect("current version must be accepted");
+    assert_eq!(version, proto::PROTOCOL_VERSION);
+    Ok(())
+}
+
+/// A peer rolled out ahead of us still speaks our version, and the pair settles
+/// on the highest version both ends speak.
+#[tokio::test]
+async fn register_peer_settles_on_highest_common_version() -> Result<()> {
+    let node = Node::new(Arc::new(SledStorageEngine::new_test()?), PermissiveAgent::new());
+
+    let p = presence(proto::MIN_PROTOCOL_VERSION..=proto::PROTOCOL_VERSION + 3);
     let peer_id = p.node_id;
-    node.register_peer(p, Box::new(NullSender(peer_id))).expect("current version must be accepted");
+    let version = node.register_peer(p, Box::new(NullSender(peer_id))).expect("overlapping range must be accepted");
+    assert_eq!(version, proto::PROTOCOL_VERSION);
     Ok(())
 }
 
@@ -81,7 +106,7 @@ async fn server_refuses_version0_handshake() -> Result<()> {
 
     // The 0.9 encoding is a strict prefix of the current one (pinned by a
     // proto unit test), so truncating the version field reproduces it.
-    let mut old_shape = bincode::serialize(&proto::Message::Presence(presence(proto::PROTOCOL_VERSION)))?;
+    let mut old_shape = bincode::serialize(&proto::Message::Presence(presence(current())))?;
     old_shape.truncate(old_shape.len() - 4);
 
     exchange_until_close(&server_url, old_shape).await?;
@@ -96,7 +121,7 @@ async fn server_refuses_mismatched_version() -> Result<()> {
     let _ = tracing_subscriber::fmt().with_max_level(tracing::Level::INFO).try_init();
     let (_server_node, server_url, server_task) = start_test_server().await?;
 
-    let frame = bincode::serialize(&proto::Message::Presence(presence(999)))?;
+    let frame = bincode::serialize(&proto::Message::Presence(presence(999..=999)))?;
     let received = exchange_until_close(&server_url, frame).await?;
 
     let rejection = received
@@ -106,7 +131,7 @@ async fn server_refuses_mismatched_version() -> Result<()> {
             _ => None,
         })
         .expect("server must send PresenceRejected before closing");
-    assert_eq!(rejection, proto::PresenceRejection { expected: proto::PROTOCOL_VERSION, received: 999 });
+    assert_eq!(rejection, proto::PresenceRejection { supported: current(), offered: 999..=999 });
 
     server_task.abort();
     Ok(())
@@ -237,7 +262,15 @@ async fn client_refuses_mismatched_server_without_hot_loop() -> Result<()> {
         let ws = tokio_tungstenite::accept_async(stream).await.expect("ws accept");
         let (mut sink, mut stream) = ws.split();
 
-        let doctored = proto::Presence { node_id: proto::EntityId::random(), durable: true, system_root: None, protocol_version: 999 };
+        let doctored = proto::Presence {
+            node_id: proto::EntityId::random(),
+            durable: true,
+            system_root: None,
+            wire: Default::default(),
+            features: vec![],
+            min_protocol_version: 999,
+            protocol_version: 999,
+        };
         sink.send(WsMessage::Binary(bincode::serialize(&proto::Message::Presence(doctored)).unwrap().into())).await.expect("send");
 
         let mut saw_rejection = None;
@@ -273,7 +306,7 @@ async fn client_refuses_mismatched_server_without_hot_loop() -> Result<()> {
 
     let (rejection, retried_immediately) = fake_server.await?;
     let rejection = rejection.expect("client must send PresenceRejected before closing");
-    assert_eq!(rejection, proto::PresenceRejection { expected: proto::PROTOCOL_VERSION, received: 999 });
+    assert_eq!(rejection, proto::PresenceRejection { supported: current(), offered: 999..=999 });
     assert!(!retried_immediately, "client retried an incompatible server without backoff");
 
     client.shutdown().await?;
@@ -0,0 +1,101 @@
+mod common;
+use ankurah::property::FetchRefs;
+use ankurah::{selection, Mutable, Ref};
+use anyhow::Result;
+use common::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Model, Debug, Serialize, Deserialize)]
+pub struct Assignee {
+    pub name: String,
+}
+
+/// A many-to-many relation without a join model
+#[derive(Model, Debug, Serialize, Deserialize)]
+pub struct Task {
+    pub title: String,
+    pub assignees: Vec<Ref<Assignee>>,
+}
+
+fn titles(tasks: &[TaskView]) -> Vec<String> {
+    let mut titles: Vec<String> = tasks.iter().map(|t| t.title().unwrap()).collect();
+    titles.sort();
+    titles
+}
+
+fn names(assignees: &[AssigneeView]) -> Vec<String> { assignees.iter().map(|a| a.name().unwrap()).collect() }
+
+#[tokio::test]
+async fn concurrent_add_wins_over_remove() -> Result<()> {
+    let node = durable_sled_setup().await?;
+    let ctx = node.context(DEFAULT_CONTEXT)?;
+
+    let trx = ctx.begin();
+    let alice = trx.create(&Assignee { name: "alice".into() }).await?.read();
+    let bob = trx.create(&Assignee { name: "bob".into() }).await?.read();
+    let carol = trx.create(&Assignee { name: "carol".into() }).await?.read();
+    let task = trx.create(&Task { title: "ship".into(), assignees: vec![alice.r(), bob.r()] }).await?.read();
+    trx.commit().await?;
+
+    let task = ctx.get::<TaskView>(task.id()).await?;
+    let mut expected = vec![alice.id(), bob.id()];
+    expected.sort();
+    assert_eq!(task.assignees()?.iter().map(|r| r.id()).collect::<Vec<_>>(), expected, "members are listed by id");
+
+    // One transaction clears the set while the other re-adds alice and adds carol
+    let trx1 = ctx.begin();
+    let trx2 = ctx.begin();
+    let cleared = task.edit(&trx1)?;
+    cleared.assignees().remove(&alice)?;
+    cleared.assignees().remove(&bob)?;
+    let edited = task.edit(&trx2)?;
+    edited.assignees().insert(&alice)?;
+    edited.assignees().insert(&carol)?;
+    assert!(edited.assignees().contains(&carol), "a transaction sees its own adds");
+    assert_eq!(edited.assignees().len(), 3);
+    trx1.commit().await?;
+    trx2.commit().await?;
+
+    let task = ctx.get::<TaskView>(task.id()).await?;
+    let mut members = names(&task.assignees()?.fetch_all(&ctx).await?);
+    members.sort();
+    assert_eq!(members, vec!["alice", "carol"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn membership_queries() -> Result<()> {
+    let node = durable_sled_setup().await?;
+    let ctx = node.context(DEFAULT_CONTEXT)?;
+
+    let trx = ctx.begin();
+    let alice = trx.create(&Assignee { name: "alice".into() }).await?.read();
+    let bob = trx.create(&Assignee { name: "bob".into() }).await?.read();
+    trx.create(&Task { title: "design".into(), assignees: vec![alice.r()] }).await?;
+    trx.create(&Task { title: "review".into(), assignees: vec![alice.r(), bob.r()] }).await?;
+    trx.create(&Task { title: "deploy".into(), assignees: vec![bob.r()] }).await?;
+    let triage = trx.create(&Task { title: "triage".into(), assignees: vec![] }).await?.read();
+    trx.commit().await?;
+    assert!(triage.assignees()?.is_empty());
+
+    let alice_id = alice.id();
+    assert_eq!(titles(&ctx.fetch(selection!("assignees CONTAINS {}", alice_id)).await?), vec!["design", "review"]);
+    assert_eq!(titles(&ctx.fetch(selection!("{} IN assignees", bob.id())).await?), vec!["deploy", "review"]);
+    assert_eq!(titles(&ctx.fetch(selection!("assignees CONTAINS {} AND title = 'design'", alice_id)).await?), vec!["design"]);
+    // A set that never had a member is NULL to queries, as an unset field is
+    assert_eq!(titles(&ctx.fetch(selection!("NOT (assignees CONTAINS {})", alice_id)).await?), vec!["deploy"]);
+
+    // Adding a member moves the task into a live membership query
+    let query = ctx.query_wait::<TaskView>(selection!("assignees CONTAINS {}", bob.id())).await?;
+    assert_eq!(titles(&query.peek()), vec!["deploy", "review"]);
+    let watcher = TestWatcher::changeset();
+    let _guard = query.subscribe(&watcher);
+    let trx = ctx.begin();
+    triage.edit(&trx)?.assignees().insert(&bob)?;
+    trx.commit().await?;
+    assert!(watcher.wait().await, "assigning bob brings triage into the query");
+    assert_eq!(titles(&query.peek()), vec!["deploy", "review", "triage"]);
+
+    Ok(())
+}
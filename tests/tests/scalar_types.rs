@@ -0,0 +1,127 @@
+mod common;
+use ankurah::{selection, Date, Decimal, Timestamp, Uuid, Value};
+use anyhow::Result;
+use common::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Model, Debug, Serialize, Deserialize)]
+pub struct Invoice {
+    pub number: String,
+    pub issued_at: Timestamp,
+    pub due: Date,
+    pub total: Decimal,
+    pub reference: Uuid,
+}
+
+/// Counters and ids past the i64 range
+#[derive(Model, Debug, Serialize, Deserialize)]
+pub struct Transfer {
+    pub name: String,
+    pub chunks: u32,
+    pub bytes: u64,
+    pub balance: i128,
+}
+
+fn invoice(number: &str, issued_at: &str, due: &str, total: &str) -> Invoice {
+    Invoice {
+        number: number.into(),
+        issued_at: issued_at.parse().unwrap(),
+        due: due.parse().unwrap(),
+        total: total.parse().unwrap(),
+        reference: Uuid::new_v4(),
+    }
+}
+
+fn numbers(invoices: &[InvoiceView]) -> Vec<String> { invoices.iter().map(|i| i.number().unwrap()).collect() }
+
+#[tokio::test]
+async fn scalar_fields_round_trip_and_compare_by_value() -> Result<()> {
+    let node = durable_sled_setup().await?;
+    let ctx = node.context(DEFAULT_CONTEXT)?;
+
+    let first = invoice("A-1", "2024-01-15T09:30:00.25Z", "2024-02-14", "99.95");
+    let trx = ctx.begin();
+    let id = trx.create(&first).await?.id();
+    trx.create(&invoice("A-2", "2024-03-01T00:00:00+02:00", "2024-03-31", "100.5")).await?;
+    trx.create(&invoice("A-3", "2024-03-02T12:00:00Z", "2024-04-01", "1250")).await?;
+    trx.commit().await?;
+
+    let loaded = ctx.get::<InvoiceView>(id).await?;
+    assert_eq!(loaded.issued_at()?, Timestamp::from_micros(1_705_311_000_250_000));
+    assert_eq!(loaded.due()?, Date::from_ymd(2024, 2, 14).unwrap());
+    assert_eq!(loaded.total()?, Decimal::new(9995, 2));
+    assert_eq!(loaded.reference()?, first.reference);
+
+    // Typed placeholders compare chronologically and numerically rather than as text
+    // ("1250" < "99.95" as strings)
+    let since: Timestamp = "2024-03-01T00:00:00Z".parse()?;
+    let recent: Vec<InvoiceView> = ctx.fetch(selection!("issued_at >= {} ORDER BY issued_at DESC", since)).await?;
+    assert_eq!(numbers(&recent), vec!["A-3"], "A-2 was issued at 22:00 UTC the day before");
+    let threshold = Decimal::from(100);
+    let large: Vec<InvoiceView> = ctx.fetch(selection!("total > {} ORDER BY total ASC", threshold)).await?;
+    assert_eq!(numbers(&large), vec!["A-2", "A-3"]);
+    let cutoff = Date::from_ymd(2024, 4, 1).unwrap();
+    let due: Vec<InvoiceView> = ctx.fetch(selection!("due < {} ORDER BY due DESC", cutoff)).await?;
+    assert_eq!(numbers(&due), vec!["A-2", "A-1"]);
+    let reference = first.reference;
+    let by_reference: Vec<InvoiceView> = ctx.fetch(selection!(reference = { reference })).await?;
+    assert_eq!(numbers(&by_reference), vec!["A-1"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn live_queries_follow_decimal_edits() -> Result<()> {
+    let node = durable_sled_setup().await?;
+    let ctx = node.context(DEFAULT_CONTEXT)?;
+
+    let trx = ctx.begin();
+    let id = trx.create(&invoice("B-1", "2024-05-01T00:00:00Z", "2024-05-31", "10.01")).await?.id();
+    trx.commit().await?;
+    let small = ctx.get::<InvoiceView>(id).await?;
+
+    let bound: Decimal = "10.1".parse()?;
+    let query = ctx.query_wait::<InvoiceView>(selection!("total >= {}", bound)).await?;
+    assert!(query.peek().is_empty());
+
+    let watcher = TestWatcher::changeset();
+    let _guard = query.subscribe(&watcher);
+    let trx = ctx.begin();
+    small.edit(&trx)?.total().set(&"10.10".parse()?)?;
+    trx.commit().await?;
+    assert!(watcher.wait().await, "10.10 equals the 10.1 bound");
+    assert_eq!(numbers(&query.peek()), vec!["B-1"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn wide_integers_keep_their_range_and_order() -> Result<()> {
+    let node = durable_sled_setup().await?;
+    let ctx = node.context(DEFAULT_CONTEXT)?;
+
+    let trx = ctx.begin();
+    let huge = trx.create(&Transfer { name: "huge".into(), chunks: u32::MAX, bytes: u64::MAX - 1, balance: i128::MIN + 1 }).await?.id();
+    trx.create(&Transfer { name: "large".into(), chunks: 3, bytes: 1 << 63, balance: -(1 << 70) }).await?;
+    trx.create(&Transfer { name: "small".into(), chunks: 1, bytes: 42, balance: 1 << 100 }).await?;
+    trx.commit().await?;
+
+    let loaded = ctx.get::<TransferView>(huge).await?;
+    assert_eq!(loaded.chunks()?, u32::MAX);
+    assert_eq!(loaded.bytes()?, u64::MAX - 1);
+    assert_eq!(loaded.balance()?, i128::MIN + 1);
+
+    let names = |transfers: Vec<TransferView>| transfers.iter().map(|t| t.name().unwrap()).collect::<Vec<_>>();
+
+    // Past i64::MAX, so these only compare correctly as unsigned. Wide placeholders are passed
+    // as values: an untyped integer placeholder must keep inferring i64
+    let threshold = Value::U64(i64::MAX as u64);
+    assert_eq!(names(ctx.fetch(selection!("bytes > {} ORDER BY bytes DESC", threshold)).await?), vec!["huge", "large"]);
+    assert_eq!(names(ctx.fetch("bytes >= 9223372036854775808 ORDER BY bytes ASC").await?), vec!["large", "huge"]);
+    let zero = Value::I128(0);
+    assert_eq!(names(ctx.fetch(selection!("balance < {} ORDER BY balance ASC", zero)).await?), vec!["huge", "large"]);
+    // Plain integer literals cast to the field type
+    assert_eq!(names(ctx.fetch("chunks > 2 ORDER BY name ASC").await?), vec!["huge", "large"]);
+
+    Ok(())
+}
@@ -34,3 +34,160 @@ async fn test_index_creation_and_reconnection() -> Result<(), anyhow::Error> {
 
     Ok(())
 }
+
+mod online_builds {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use ankurah::core::indexing::{IndexKeyPart, KeySpec};
+    use ankurah::core::storage::{StorageEngine, VerifyOptions};
+    use ankurah::proto::CollectionId;
+    use ankurah::{policy::DEFAULT_CONTEXT, Node, PermissiveAgent, ValueType};
+    use ankurah_storage_sled::index::{BuildProgress, BuildStatus, Index, IndexManager, IndexRecord};
+    use ankurah_storage_sled::SledStorageEngine;
+
+    use crate::common::*;
+
+    const ALBUMS: [(&str, &str); 10] = [
+        ("Album0", "2000"),
+        ("Album1", "2001"),
+        ("Album2", "2002"),
+        ("Album3", "2003"),
+        ("Album4", "2004"),
+        ("Album5", "2005"),
+        ("Album6", "2006"),
+        ("Album7", "2007"),
+        ("Album8", "2008"),
+        ("Album9", "2009"),
+    ];
+
+    async fn setup(batch_size: usize) -> Result<(Arc<SledStorageEngine>, ankurah::Context), anyhow::Error> {
+        let engine = Arc::new(SledStorageEngine::new_test()?);
+        engine.database.lock().unwrap().index_manager.set_build_batch_size(batch_size);
+        let node = Node::new_durable(engine.clone(), PermissiveAgent::new());
+        node.system.create().await?;
+        let ctx = node.context_async(DEFAULT_CONTEXT).await;
+        create_albums(&ctx, ALBUMS.to_vec()).await?;
+        Ok((engine, ctx))
+    }
+
+    async fn wait_until_ready(engine: &SledStorageEngine) -> Result<(), anyhow::Error> {
+        let album = CollectionId::fixed_name("album");
+        for _ in 0..200 {
+            let indexes = engine.list_indexes(Some(&album)).await?;
+            if !indexes.is_empty() && indexes.iter().all(|i| i.status == BuildStatus::Ready) {
+                return Ok(());
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        anyhow::bail!("index build did not finish")
+    }
+
+    #[tokio::test]
+    async fn test_queries_answer_while_index_builds() -> Result<(), anyhow::Error> {
+        let (engine, ctx) = setup(2).await?;
+
+        // The first query starts a build it cannot wait for, and scans instead
+        assert_eq!(names(&fetch(&ctx, "year >= '2007' ORDER BY year").await?), vec!["Album7", "Album8", "Album9"]);
+
+        // Writes landing on either side of the build cursor are all indexed
+        create_albums(&ctx, vec![("Album10", "2010"), ("Album11", "2011")]).await?;
+        wait_until_ready(&engine).await?;
+
+        assert_eq!(names(&fetch(&ctx, "year >= '2008' ORDER BY year").await?), vec!["Album8", "Album9", "Album10", "Album11"]);
+        let report = engine.collection(&CollectionId::fixed_name("album")).await?.verify(VerifyOptions::default()).await?;
+        assert!(report.is_clean(), "{:?}", report.problems);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_builds_for_several_shapes_share_the_builder() -> Result<(), anyhow::Error> {
+        let (engine, ctx) = setup(2).await?;
+
+        // Each new shape queues a build behind the previous one
+        assert_eq!(names(&fetch(&ctx, "year >= '2008' ORDER BY year").await?), vec!["Album8", "Album9"]);
+        assert_eq!(names(&fetch(&ctx, "name = 'Album3'").await?), vec!["Album3"]);
+        wait_until_ready(&engine).await?;
+
+        let indexes = engine.list_indexes(Some(&CollectionId::fixed_name("album"))).await?;
+        assert_eq!(indexes.len(), 2);
+        assert!(indexes.iter().all(|i| i.entries == 10), "{indexes:?}");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_and_drop_indexes() -> Result<(), anyhow::Error> {
+        let (engine, ctx) = setup(1024).await?;
+        let album = CollectionId::fixed_name("album");
+        assert!(engine.list_indexes(Some(&album)).await?.is_empty());
+
+        // Small collections are indexed within the first query
+        assert_eq!(names(&fetch(&ctx, "name = 'Album3'").await?), vec!["Album3"]);
+        let indexes = engine.list_indexes(Some(&album)).await?;
+        assert_eq!(indexes.len(), 1);
+        let info = &indexes[0];
+        assert_eq!((info.status, info.entries, info.progress), (BuildStatus::Ready, 10, None));
+        assert!(info.bytes > 0 && info.created_at_unix_ms > 0);
+
+        // Counts follow writes without rescanning the index
+        create_albums(&ctx, vec![("Album10", "2010")]).await?;
+        let info = &engine.list_indexes(Some(&album)).await?[0];
+        assert_eq!(info.entries, 11);
+        assert_eq!(info.bytes, indexes[0].bytes * 11 / 10 + 1, "one key, a byte longer than the rest");
+
+        // and are stored with the index, so reopening does not recount them
+        let database = engine.database.lock().unwrap().clone();
+        let reopen =
+            || IndexManager::open(database.index_manager.index_config_tree.clone(), &database.db, database.property_manager.clone());
+        let reopened = reopen()?.indexes.read().unwrap()[&info.id].info()?;
+        assert_eq!((reopened.entries, reopened.bytes), (info.entries, info.bytes));
+
+        // A record stored before the counts were is counted once when opened
+        let config = &database.index_manager.index_config_tree;
+        let record: IndexRecord = bincode::deserialize(&config.get(info.id.to_be_bytes())?.unwrap())?;
+        let legacy = (record.id, record.collection, record.name, record.spec, record.created_at_unix_ms, record.build_status);
+        config.insert(info.id.to_be_bytes(), bincode::serialize(&legacy)?)?;
+        let upgraded = reopen()?.indexes.read().unwrap()[&info.id].info()?;
+        assert_eq!((upgraded.entries, upgraded.bytes), (info.entries, info.bytes));
+        let stored: IndexRecord = bincode::deserialize(&config.get(info.id.to_be_bytes())?.unwrap())?;
+        assert_eq!((stored.entries, stored.bytes), (info.entries, info.bytes));
+
+        assert!(engine.drop_index(info.id).await?);
+        assert!(!engine.drop_index(info.id).await?);
+        assert!(engine.list_indexes(Some(&album)).await?.is_empty());
+
+        // Dropped indexes are recreated on demand
+        assert_eq!(names(&fetch(&ctx, "name = 'Album4'").await?), vec!["Album4"]);
+        assert_eq!(engine.list_indexes(Some(&album)).await?.len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_build_resumes_from_persisted_cursor() -> Result<(), anyhow::Error> {
+        let (engine, _ctx) = setup(1024).await?;
+        let database = engine.database.lock().unwrap().clone();
+        let manager = &database.index_manager;
+        let spec = KeySpec::new(vec![IndexKeyPart::asc("name", ValueType::String)]);
+        let id = manager.next_index_id()?;
+        let index =
+            Index::new_from_spec("album", spec, &database.db, id, manager.index_config_tree.clone(), database.property_manager.clone())?;
+        manager.indexes.write().unwrap().insert(id, index.clone());
+
+        assert!(!index.build_batch(&database.db, 4)?);
+        assert_eq!(index.status(), BuildStatus::Building);
+        assert_eq!(index.progress(), Some(BuildProgress { scanned: 4, total: 10 }));
+
+        // Reopening picks up the build where it stopped
+        let reopened = IndexManager::open(manager.index_config_tree.clone(), &database.db, database.property_manager.clone())?;
+        let resumed = reopened.indexes.read().unwrap().get(&id).cloned().unwrap();
+        assert_eq!(resumed.status(), BuildStatus::Building);
+        assert_eq!(resumed.info()?.entries, 4);
+        assert!(!resumed.build_batch(&database.db, 4)?);
+        assert_eq!(resumed.progress(), Some(BuildProgress { scanned: 8, total: 10 }));
+        assert!(resumed.build_batch(&database.db, 4)?);
+        assert_eq!(resumed.status(), BuildStatus::Ready);
+        assert_eq!(resumed.tree().len(), 10);
+        assert_eq!(resumed.info()?.entries, 10);
+        Ok(())
+    }
+}
@@ -8,4 +8,6 @@ mod json_property;
 mod multi_column_order_by;
 mod normalization_tests;
 mod pagination;
+mod ref_set;
 mod ref_traversal;
+mod verify;
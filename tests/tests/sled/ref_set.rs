@@ -0,0 +1,68 @@
+//! Sled Reference Set Tests
+//!
+//! Membership queries on a `Vec<Ref<T>>` property are served by a multi-entry
+//! index: an entity has a key per member under the `assignees.[]` keypart.
+
+use ankurah::core::indexing::{IndexKeyPart, KeySpec};
+use ankurah::core::storage::{StorageEngine, VerifyOptions};
+use ankurah::proto::CollectionId;
+use ankurah::{policy::DEFAULT_CONTEXT, selection, Model, Mutable, Node, PermissiveAgent, Ref, ValueType};
+use ankurah_storage_sled::SledStorageEngine;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+#[derive(Model, Debug, Serialize, Deserialize, Clone)]
+pub struct Reviewer {
+    pub name: String,
+}
+
+#[derive(Model, Debug, Serialize, Deserialize, Clone)]
+pub struct Ticket {
+    pub title: String,
+    pub reviewers: Vec<Ref<Reviewer>>,
+}
+
+fn titles(tickets: &[TicketView]) -> Vec<String> {
+    let mut titles: Vec<String> = tickets.iter().map(|t| t.title().unwrap()).collect();
+    titles.sort();
+    titles
+}
+
+#[tokio::test]
+async fn test_membership_query_uses_multi_entry_index() -> Result<()> {
+    let engine = Arc::new(SledStorageEngine::new_test()?);
+    let node = Node::new_durable(engine.clone(), PermissiveAgent::new());
+    node.system.create().await?;
+    let ctx = node.context_async(DEFAULT_CONTEXT).await;
+
+    let trx = ctx.begin();
+    let ann = trx.create(&Reviewer { name: "ann".into() }).await?.read();
+    let ben = trx.create(&Reviewer { name: "ben".into() }).await?.read();
+    let crash = trx.create(&Ticket { title: "crash".into(), reviewers: vec![ann.r(), ben.r()] }).await?.read();
+    trx.create(&Ticket { title: "typo".into(), reviewers: vec![ben.r()] }).await?;
+    trx.commit().await?;
+
+    let tickets: Vec<TicketView> = ctx.fetch(selection!("reviewers CONTAINS {}", ann.id())).await?;
+    assert_eq!(titles(&tickets), vec!["crash"]);
+
+    let indexes = engine.list_indexes(Some(&CollectionId::fixed_name("ticket"))).await?;
+    assert_eq!(indexes.len(), 1);
+    let expected = KeySpec::new(vec![IndexKeyPart::asc_element("reviewers", ValueType::EntityId)]);
+    assert_eq!(indexes[0].spec, expected);
+    assert_eq!(indexes[0].entries, 3, "a key per member");
+
+    // Membership changes move the entity's keys
+    let trx = ctx.begin();
+    let edited = crash.edit(&trx)?;
+    edited.reviewers().remove(&ben)?;
+    trx.commit().await?;
+    let tickets: Vec<TicketView> = ctx.fetch(selection!("{} IN reviewers", ben.id())).await?;
+    assert_eq!(titles(&tickets), vec!["typo"]);
+    let tickets: Vec<TicketView> = ctx.fetch(selection!("reviewers CONTAINS {}", ann.id())).await?;
+    assert_eq!(titles(&tickets), vec!["crash"]);
+
+    let report = engine.collection(&CollectionId::fixed_name("ticket")).await?.verify(VerifyOptions::default()).await?;
+    assert!(report.is_clean(), "{:?}", report.problems);
+    Ok(())
+}
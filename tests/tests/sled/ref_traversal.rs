@@ -2,8 +2,10 @@
 //!
 //! Verifies that typed entity references work correctly for fetching related entities.
 
+use ankurah::core::indexing::{IndexKeyPart, KeySpec};
 use ankurah::property::Ref;
-use ankurah::{policy::DEFAULT_CONTEXT, EntityId, Model, Node, PermissiveAgent};
+use ankurah::proto::CollectionId;
+use ankurah::{policy::DEFAULT_CONTEXT, EntityId, Model, Mutable, Node, PermissiveAgent, ValueType, View};
 use ankurah_storage_sled::SledStorageEngine;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
@@ -92,6 +94,38 @@ async fn test_ref_get_traversal() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_backlinks_use_reverse_reference_index() -> Result<()> {
+    let engine = Arc::new(SledStorageEngine::new_test()?);
+    let node = Node::new_durable(engine.clone(), PermissiveAgent::new());
+    node.system.create().await?;
+    let ctx = node.context_async(DEFAULT_CONTEXT).await;
+
+    let trx = ctx.begin();
+    let blur = trx.create(&RefTestArtist { name: "Blur".to_string() }).await?.read();
+    let oasis = trx.create(&RefTestArtist { name: "Oasis".to_string() }).await?.read();
+    let parklife = trx.create(&RefTestAlbum { name: "Parklife".to_string(), artist: blur.r() }).await?.read();
+    trx.create(&RefTestAlbum { name: "Definitely Maybe".to_string(), artist: oasis.r() }).await?;
+    trx.commit().await?;
+
+    let albums = blur.backlinks::<RefTestAlbum>(&ctx, "artist").await?;
+    assert_eq!(albums.iter().map(|a| a.name().unwrap()).collect::<Vec<_>>(), vec!["Parklife"]);
+
+    // The lookup is served by an index on the referencing field
+    let indexes = engine.list_indexes(Some(&CollectionId::fixed_name("reftestalbum"))).await?;
+    assert_eq!(indexes.len(), 1);
+    assert_eq!(indexes[0].spec, KeySpec::new(vec![IndexKeyPart::asc("artist", ValueType::EntityId)]));
+
+    // which follows the reference when it changes
+    let trx = ctx.begin();
+    parklife.edit(&trx)?.artist().set(&oasis.r())?;
+    trx.commit().await?;
+    assert!(blur.backlinks::<RefTestAlbum>(&ctx, "artist").await?.is_empty());
+    assert_eq!(oasis.backlinks::<RefTestAlbum>(&ctx, "artist").await?.len(), 2);
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_ref_from_entity_id() -> Result<()> {
     let id = EntityId::random();
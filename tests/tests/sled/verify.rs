@@ -0,0 +1,86 @@
+use std::sync::Arc;
+
+use ankurah::core::storage::{Inconsistency, StorageEngine, VerifyOptions};
+use ankurah::proto::CollectionId;
+use ankurah::{policy::DEFAULT_CONTEXT, Node, PermissiveAgent, View};
+use ankurah_storage_sled::SledStorageEngine;
+
+use crate::common::*;
+
+async fn setup() -> Result<(Arc<SledStorageEngine>, ankurah::Context), anyhow::Error> {
+    let engine = Arc::new(SledStorageEngine::new_test()?);
+    let node = Node::new_durable(engine.clone(), PermissiveAgent::new());
+    node.system.create().await?;
+    let ctx = node.context_async(DEFAULT_CONTEXT).await;
+    create_albums(&ctx, vec![("Walking on a Dream", "2008"), ("Ice on the Dune", "2013"), ("Two Vines", "2016")]).await?;
+    // Querying by name creates and backfills the name index
+    assert_eq!(names(&fetch(&ctx, "name = 'Two Vines'").await?), vec!["Two Vines"]);
+    Ok((engine, ctx))
+}
+
+#[tokio::test]
+async fn test_verify_clean_store() -> Result<(), anyhow::Error> {
+    let (engine, _ctx) = setup().await?;
+    let report = engine.collection(&CollectionId::fixed_name("album")).await?.verify(VerifyOptions::default()).await?;
+    assert!(report.is_clean(), "{:?}", report.problems);
+    assert_eq!((report.events, report.states), (3, 3));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_verify_whole_store() -> Result<(), anyhow::Error> {
+    let (engine, _ctx) = setup().await?;
+    let reports = engine.verify(VerifyOptions::default()).await?;
+    let collections: Vec<&str> = reports.iter().map(|(id, _)| id.as_str()).collect();
+    assert!(collections.contains(&"album"), "{collections:?}");
+    for (collection, report) in &reports {
+        assert!(report.is_clean(), "{collection}: {:?}", report.problems);
+    }
+    let (_, album) = reports.iter().find(|(id, _)| id.as_str() == "album").unwrap();
+    assert_eq!((album.events, album.states), (3, 3));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_verify_repairs_missing_index_entry() -> Result<(), anyhow::Error> {
+    let (engine, ctx) = setup().await?;
+    let database = engine.database.lock().unwrap().clone();
+    let index = database.index_manager.indexes.read().unwrap().values().find(|i| i.collection() == "album").cloned().unwrap();
+    let (key, _) = index.tree().first()?.unwrap();
+    index.tree().remove(key)?;
+
+    let collection = engine.collection(&CollectionId::fixed_name("album")).await?;
+    let report = collection.verify(VerifyOptions::default()).await?;
+    assert!(
+        matches!(report.problems.as_slice(), [Inconsistency::Derived { detail, .. }] if detail == "missing entry"),
+        "{:?}",
+        report.problems
+    );
+    assert_eq!(report.repaired, 0);
+
+    let report = collection.verify(VerifyOptions { repair: true }).await?;
+    assert_eq!(report.repaired, 1);
+    assert!(collection.verify(VerifyOptions::default()).await?.is_clean());
+    assert_eq!(sort_names(&fetch(&ctx, "name > 'A'").await?), vec!["Ice on the Dune", "Two Vines", "Walking on a Dream"]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_verify_repairs_state_that_disagrees_with_events() -> Result<(), anyhow::Error> {
+    let (engine, ctx) = setup().await?;
+    let collection = engine.collection(&CollectionId::fixed_name("album")).await?;
+    let albums = fetch(&ctx, "name = 'Two Vines' OR name = 'Ice on the Dune'").await?;
+    let (a, b) = (albums[0].id(), albums[1].id());
+
+    // Overwrite one album's property buffers with the other's, keeping its head
+    let mut forged = collection.get_state(a).await?;
+    forged.payload.state.state_buffers = collection.get_state(b).await?.payload.state.state_buffers;
+    collection.set_state(forged).await?;
+
+    let report = collection.verify(VerifyOptions { repair: true }).await?;
+    assert_eq!(report.problems, vec![Inconsistency::StateMismatch { entity_id: a }]);
+    assert_eq!(report.repaired, 1);
+    assert!(collection.verify(VerifyOptions::default()).await?.is_clean());
+    assert_eq!(collection.get_state(a).await?.payload.state.state_buffers, albums[0].entity().to_state()?.state_buffers);
+    Ok(())
+}
@@ -0,0 +1,189 @@
+//! Peering over the socket connector: TCP, Unix domain sockets and TLS, and
+//! the same Presence refusals as the websocket server.
+
+mod common;
+use ankurah::signals::Wait;
+use ankurah_connector_socket::{tls, ConnectionState, Endpoint, SocketClient, SocketServer};
+use common::*;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+type TestNode = Node<SledStorageEngine, PermissiveAgent>;
+
+async fn album_names(node: &TestNode) -> anyhow::Result<Vec<String>> {
+    let albums: Vec<AlbumView> = node.context(DEFAULT_CONTEXT)?.fetch("name != ''").await?;
+    let mut names: Vec<String> = albums.into_iter().map(|album| album.name().unwrap()).collect();
+    names.sort();
+    Ok(names)
+}
+
+/// A client fetches what the server stores, and its own commits land on the server
+async fn exchange_albums(server: &TestNode, client: &TestNode) -> anyhow::Result<()> {
+    create_albums(&server.context(DEFAULT_CONTEXT)?, [1973]).await?;
+    client.system.wait_system_ready().await;
+    let ctx = client.context_async(DEFAULT_CONTEXT).await;
+    let fetched: Vec<AlbumView> = ctx.fetch("year = '1973'").await?;
+    assert_eq!(fetched.len(), 1);
+
+    create_albums(&ctx, [1975]).await?;
+    assert_eq!(album_names(server).await?, ["Album 1973", "Album 1975"]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn tcp_peering() -> anyhow::Result<()> {
+    let server_node = durable_sled_setup().await?;
+    let server = SocketServer::bind_tcp(server_node.clone(), "127.0.0.1:0").await?;
+    let addr = server.local_addr().unwrap();
+    let server_task = tokio::spawn(server.run());
+
+    let client_node = ephemeral_sled_setup().await?;
+    let client = SocketClient::new(client_node.clone(), Endpoint::tcp(addr.to_string())).await?;
+    client.wait_connected().await?;
+    assert_eq!(client.server_node_id(), Some(server_node.id));
+
+    exchange_albums(&server_node, &client_node).await?;
+    client.shutdown().await?;
+    server_task.abort();
+    Ok(())
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn unix_socket_peering() -> anyhow::Result<()> {
+    let path = std::env::temp_dir().join(format!("ankurah-{}.sock", EntityId::random()));
+    let server_node = durable_sled_setup().await?;
+    let server = SocketServer::bind_unix(server_node.clone(), &path)?;
+    let server_task = tokio::spawn(server.run());
+
+    let client_node = ephemeral_sled_setup().await?;
+    let client = SocketClient::new(client_node.clone(), Endpoint::unix(&path)).await?;
+    client.wait_connected().await?;
+
+    exchange_albums(&server_node, &client_node).await?;
+    client.shutdown().await?;
+    server_task.abort();
+    Ok(())
+}
+
+#[tokio::test]
+async fn tls_peering() -> anyhow::Result<()> {
+    let server_config = tls::server_config(include_bytes!("fixtures/tls/server.pem"), include_bytes!("fixtures/tls/server.key"))?;
+    let client_config = tls::client_config(include_bytes!("fixtures/tls/ca.pem"))?;
+
+    let server_node = durable_sled_setup().await?;
+    let server = SocketServer::bind_tcp(server_node.clone(), "127.0.0.1:0").await?.tls(server_config);
+    let addr = server.local_addr().unwrap();
+    let server_task = tokio::spawn(server.run());
+
+    let client_node = ephemeral_sled_setup().await?;
+    let client =
+        SocketClient::builder(client_node.clone(), Endpoint::tcp(addr.to_string())).tls(client_config, "localhost").build().await?;
+    client.wait_connected().await?;
+
+    exchange_albums(&server_node, &client_node).await?;
+    client.shutdown().await?;
+    server_task.abort();
+    Ok(())
+}
+
+fn presence(protocol_version: u32) -> proto::Presence {
+    proto::Presence {
+        node_id: EntityId::random(),
+        durable: false,
+        system_root: None,
+        wire: Default::default(),
+        features: vec![],
+        min_protocol_version: protocol_version,
+        protocol_version,
+    }
+}
+
+/// Send one raw frame to a fresh server and collect its messages until it closes the connection
+async fn exchange_until_close(first_frame: Vec<u8>) -> anyhow::Result<Vec<proto::Message>> {
+    let server = SocketServer::bind_tcp(durable_sled_setup().await?, "127.0.0.1:0").await?;
+    let addr = server.local_addr().unwrap();
+    let server_task = tokio::spawn(server.run());
+
+    let mut stream = tokio::net::TcpStream::connect(addr).await?;
+    stream.write_u32(first_frame.len() as u32).await?;
+    stream.write_all(&first_frame).await?;
+
+    let mut received = Vec::new();
+    loop {
+        let len = match tokio::time::timeout(Duration::from_secs(10), stream.read_u32()).await {
+            Ok(Ok(len)) => len,
+            Ok(Err(_)) => break,
+            Err(_) => panic!("server did not close the connection; frames so far: {received:?}"),
+        };
+        let mut frame = vec![0; len as usize];
+        stream.read_exact(&mut frame).await?;
+        received.push(bincode::deserialize(&frame)?);
+    }
+    server_task.abort();
+    Ok(received)
+}
+
+#[tokio::test]
+async fn server_refuses_mismatched_version() -> anyhow::Result<()> {
+    let received = exchange_until_close(bincode::serialize(&proto::Message::Presence(presence(999)))?).await?;
+    assert!(matches!(received.first(), Some(proto::Message::Presence(_))), "server leads with its presence");
+    let rejection = received.iter().find_map(|message| match message {
+        proto::Message::PresenceRejected(rejection) => Some(rejection.clone()),
+        _ => None,
+    });
+    assert_eq!(
+        rejection,
+        Some(proto::PresenceRejection { supported: proto::MIN_PROTOCOL_VERSION..=proto::PROTOCOL_VERSION, offered: 999..=999 })
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn server_refuses_version0_handshake() -> anyhow::Result<()> {
+    // A Presence cut short of its version field cannot be decoded, like a 0.9.x one
+    let mut old_shape = bincode::serialize(&proto::Message::Presence(presence(proto::PROTOCOL_VERSION)))?;
+    old_shape.truncate(old_shape.len() - 4);
+    let received = exchange_until_close(old_shape).await?;
+    assert_eq!(received.len(), 1, "only the server's presence precedes the close");
+    Ok(())
+}
+
+#[tokio::test]
+async fn client_follows_goodbye_redirect() -> anyhow::Result<()> {
+    let server_node = durable_sled_setup().await?;
+    let server = SocketServer::bind_tcp(server_node.clone(), "127.0.0.1:0").await?;
+    let addr = server.local_addr().unwrap();
+    let server_task = tokio::spawn(server.run());
+
+    // A draining server introduces itself, then sends the client on to the real one
+    let draining = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let draining_addr = draining.local_addr()?;
+    let draining_task = tokio::spawn(async move {
+        let (mut stream, _) = draining.accept().await?;
+        let goodbye = proto::Goodbye { redirect: Some(Endpoint::tcp(addr.to_string()).to_string()), reconnect_after_ms: 0 };
+        for message in [proto::Message::Presence(presence(proto::PROTOCOL_VERSION)), proto::Message::Goodbye(goodbye)] {
+            let frame = bincode::serialize(&message)?;
+            stream.write_u32(frame.len() as u32).await?;
+            stream.write_all(&frame).await?;
+        }
+        anyhow::Ok(())
+    });
+
+    let client_node = ephemeral_sled_setup().await?;
+    let client = SocketClient::new(client_node.clone(), Endpoint::tcp(draining_addr.to_string())).await?;
+    let server_id = server_node.id;
+    let state = client.state();
+    let redirected = state.wait_for(move |state| match state {
+        ConnectionState::Connected { endpoint, server_presence } if server_presence.node_id == server_id => Some(endpoint.clone()),
+        _ => None,
+    });
+    let endpoint = tokio::time::timeout(Duration::from_secs(10), redirected).await?;
+    assert_eq!(endpoint, Endpoint::tcp(addr.to_string()));
+    draining_task.await??;
+
+    exchange_albums(&server_node, &client_node).await?;
+    client.shutdown().await?;
+    server_task.abort();
+    Ok(())
+}
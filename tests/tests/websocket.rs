@@ -1,4 +1,4 @@
-use ankurah::{changes::ChangeKind, policy::DEFAULT_CONTEXT as c, EntityId, Node, PermissiveAgent};
+use ankurah::{changes::ChangeKind, core::node::nocache, policy::DEFAULT_CONTEXT as c, EntityId, Node, PermissiveAgent};
 use ankurah_storage_sled::SledStorageEngine;
 use ankurah_websocket_client::WebsocketClient;
 use anyhow::Result;
@@ -251,6 +251,121 @@ async fn test_websocket_bidirectional_subscription_impl() -> Result<()> {
     Ok(())
 }
 
+/// Large fetch results are compressed and bursts of commits are batched; both must arrive intact
+#[tokio::test]
+async fn test_websocket_compressed_and_batched_traffic() -> Result<()> {
+    let (server_node, server_url, server_task) = start_test_server().await?;
+    let server_ctx = server_node.context(c)?;
+    {
+        let trx = server_ctx.begin();
+        for i in 0..200 {
+            trx.create(&Album { name: format!("Album {i:03} {}", "with a long and repetitive title ".repeat(4)), year: "1980".into() })
+                .await?;
+        }
+        trx.commit().await?;
+    }
+
+    let client_node = Node::new(Arc::new(SledStorageEngine::new_test()?), PermissiveAgent::new());
+    let client = WebsocketClient::new(client_node.clone(), &server_url).await?;
+    client.wait_connected().await?;
+    client_node.system.wait_system_ready().await;
+    let client_ctx = client_node.context(c)?;
+
+    // One fetch response well past the compression threshold
+    assert_eq!(client_ctx.fetch::<AlbumView>("year = '1980'").await?.len(), 200);
+
+    // Concurrent commits queue up in the outbox behind each other and leave in batches
+    let commits = (0..50).map(|i| {
+        let ctx = client_ctx.clone();
+        async move {
+            let trx = ctx.begin();
+            trx.create(&Album { name: format!("Burst {i:02}"), year: "1990".into() }).await?;
+            trx.commit().await
+        }
+    });
+    for result in futures_util::future::join_all(commits).await {
+        result?;
+    }
+    // Commits queued behind others return once persisted; the outbox relays them afterwards
+    let deadline = std::time::Instant::now() + Duration::from_secs(10);
+    while client_node.outbox.unsynced().value() != 0 {
+        assert!(std::time::Instant::now() < deadline, "outbox still has {} unsynced commits", client_node.outbox.unsynced().value());
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(server_ctx.fetch::<AlbumView>("year = '1990'").await?.len(), 50);
+
+    client.shutdown().await?;
+    server_task.abort();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_websocket_drain_redirects_clients() -> Result<()> {
+    // Two durable nodes over one storage stand in for the outgoing and incoming process of a deploy
+    let storage = Arc::new(SledStorageEngine::new_test()?);
+    let old_node = Node::new_durable(storage.clone(), PermissiveAgent::new());
+    old_node.system.create().await?;
+
+    let drain = Arc::new(std::sync::OnceLock::new());
+    let (old_url, old_task) = serve_test_node(old_node.clone(), {
+        let drain = drain.clone();
+        move |server| {
+            let _ = drain.set(server.drain_handle());
+            server
+        }
+    })
+    .await?;
+
+    {
+        let trx = old_node.context(c)?.begin();
+        trx.create(&Album { name: "Rumours".into(), year: "1977".into() }).await?;
+        trx.commit().await?;
+    }
+    let new_node = Node::new_durable(storage, PermissiveAgent::new());
+    new_node.system.wait_system_ready().await;
+    let (new_url, new_task) = serve_test_node(new_node.clone(), |server| server).await?;
+
+    let client_node = Node::new(Arc::new(SledStorageEngine::new_test()?), PermissiveAgent::new());
+    let client = WebsocketClient::new(client_node.clone(), &old_url).await?;
+    client.wait_connected().await?;
+    client_node.system.wait_system_ready().await;
+    let client_ctx = client_node.context(c)?;
+
+    use ankurah::signals::Subscribe;
+    let watcher = TestWatcher::changeset();
+    let live = client_ctx.query_wait::<AlbumView>(nocache("year = '1977'")?).await?;
+    assert_eq!(names(live.peek()), ["Rumours"]);
+    let _sub = live.subscribe(&watcher);
+
+    let goodbye = ankurah::proto::Goodbye { redirect: Some(new_url), reconnect_after_ms: 0 };
+    tokio::time::timeout(Duration::from_secs(10), drain.get().expect("server configured").drain(goodbye)).await?;
+    assert!(old_node.is_draining());
+
+    // The client goes straight to the new server...
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while client.server_node_id() != Some(new_node.id) {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await?;
+
+    // ...and its live query follows it there
+    let tusk = {
+        let trx = new_node.context(c)?.begin();
+        let album = trx.create(&Album { name: "Tusk".into(), year: "1977".into() }).await?;
+        let id = album.id();
+        trx.commit().await?;
+        id
+    };
+    tokio::time::timeout(Duration::from_secs(5), async { while !watcher.take_one().await.contains(&(tusk, ChangeKind::Add)) {} }).await?;
+    assert_eq!(live.peek().len(), 2);
+
+    client.shutdown().await?;
+    old_task.abort();
+    new_task.abort();
+    Ok(())
+}
+
 /// Helper to extract names from album query results
 fn names(resultset: Vec<AlbumView>) -> Vec<String> { resultset.iter().map(|r| r.name().unwrap()).collect::<Vec<String>>() }
 
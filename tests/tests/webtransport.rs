@@ -0,0 +1,219 @@
+//! Peering over the WebTransport server: the control stream handshake, a request answered on
+//! its own stream, and subscription updates on the server's updates stream.
+//!
+//! Browsers are the real clients; these tests speak just enough HTTP/3 over raw QUIC to open
+//! a session and use its streams the way the wasm client does.
+
+mod common;
+use ankurah::proto;
+use ankurah_connector_socket::tls;
+use ankurah_webtransport_server::{WebTransportServer, SESSION_PATH};
+use common::*;
+use quinn::crypto::rustls::QuicClientConfig;
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+async fn start_server() -> anyhow::Result<(Node<SledStorageEngine, PermissiveAgent>, SocketAddr, tokio::task::JoinHandle<anyhow::Result<()>>)> {
+    let server_config = tls::server_config(include_bytes!("fixtures/tls/server.pem"), include_bytes!("fixtures/tls/server.key"))?;
+    let server_node = durable_sled_setup().await?;
+    let server = WebTransportServer::bind(server_node.clone(), "127.0.0.1:0".parse()?, server_config)?;
+    let addr = server.local_addr()?;
+    Ok((server_node, addr, tokio::spawn(server.run())))
+}
+
+fn encode_varint(value: u64, buf: &mut Vec<u8>) {
+    match value {
+        0..=0x3f => buf.push(value as u8),
+        0x40..=0x3fff => buf.extend_from_slice(&(0x4000 | value as u16).to_be_bytes()),
+        _ => buf.extend_from_slice(&(0x8000_0000 | value as u32).to_be_bytes()),
+    }
+}
+
+async fn read_varint(stream: &mut quinn::RecvStream) -> anyhow::Result<u64> {
+    let mut first = [0u8; 1];
+    stream.read_exact(&mut first).await?;
+    let len = 1 << (first[0] >> 6);
+    let mut value = (first[0] & 0x3f) as u64;
+    let mut rest = [0u8; 7];
+    stream.read_exact(&mut rest[..len - 1]).await?;
+    for byte in &rest[..len - 1] {
+        value = (value << 8) | *byte as u64;
+    }
+    Ok(value)
+}
+
+/// Append a QPACK string literal without Huffman coding
+fn qpack_string(value: &str, buf: &mut Vec<u8>) {
+    buf.push(value.len() as u8);
+    buf.extend_from_slice(value.as_bytes());
+}
+
+/// A WebTransport session opened over raw QUIC
+struct Session {
+    connection: quinn::Connection,
+    id: u64,
+    // The HTTP/3 control stream and the CONNECT stream must stay open for the session's lifetime
+    _streams: (quinn::SendStream, quinn::SendStream, quinn::RecvStream),
+}
+
+impl Session {
+    async fn open(addr: SocketAddr) -> anyhow::Result<Self> {
+        let mut client_config = (*tls::client_config(include_bytes!("fixtures/tls/ca.pem"))?).clone();
+        client_config.alpn_protocols = vec![b"h3".to_vec()];
+        let mut endpoint = quinn::Endpoint::client("127.0.0.1:0".parse()?)?;
+        endpoint.set_default_client_config(quinn::ClientConfig::new(Arc::new(QuicClientConfig::try_from(client_config)?)));
+        let connection = endpoint.connect(addr, "localhost")?.await?;
+
+        // HTTP/3 control stream with an empty SETTINGS frame
+        let mut control = connection.open_uni().await?;
+        control.write_all(&[0x00, 0x04, 0x00]).await?;
+
+        // Extended CONNECT, with every field either in QPACK's static table or literal
+        let (mut send, mut recv) = connection.open_bi().await?;
+        let mut fields = vec![0x00, 0x00, 0xc0 | 15, 0xc0 | 23]; // :method CONNECT, :scheme https
+        fields.push(0x50); // :authority
+        qpack_string(&format!("localhost:{}", addr.port()), &mut fields);
+        fields.push(0x51); // :path
+        qpack_string(SESSION_PATH, &mut fields);
+        fields.extend_from_slice(&[0x27, 0x02]); // a literal name of nine bytes
+        fields.extend_from_slice(b":protocol");
+        qpack_string("webtransport", &mut fields);
+        let mut frame = vec![0x01];
+        encode_varint(fields.len() as u64, &mut frame);
+        frame.extend(fields);
+        send.write_all(&frame).await?;
+
+        // Skip any grease ahead of the response HEADERS, which must open with `:status 200`
+        loop {
+            let frame_type = read_varint(&mut recv).await?;
+            let mut payload = vec![0u8; read_varint(&mut recv).await? as usize];
+            recv.read_exact(&mut payload).await?;
+            if frame_type == 0x01 {
+                anyhow::ensure!(payload.get(2) == Some(&(0xc0 | 25)), "session refused: {:?}", payload);
+                break;
+            }
+        }
+
+        let id = u64::from(send.id());
+        Ok(Self { connection, id, _streams: (control, send, recv) })
+    }
+
+    /// Open a bidirectional stream of the session
+    async fn open_bi(&self) -> anyhow::Result<(quinn::SendStream, quinn::RecvStream)> {
+        let (mut send, recv) = self.connection.open_bi().await?;
+        let mut header = Vec::new();
+        encode_varint(0x41, &mut header);
+        encode_varint(self.id, &mut header);
+        send.write_all(&header).await?;
+        Ok((send, recv))
+    }
+
+    /// Accept a unidirectional stream the server opened for the session
+    async fn accept_uni(&self) -> anyhow::Result<quinn::RecvStream> {
+        loop {
+            let mut recv = tokio::time::timeout(TIMEOUT, self.connection.accept_uni()).await??;
+            // The server's HTTP/3 control and QPACK streams come first
+            if read_varint(&mut recv).await? == 0x54 {
+                anyhow::ensure!(read_varint(&mut recv).await? == self.id, "stream of another session");
+                return Ok(recv);
+            }
+        }
+    }
+}
+
+async fn send_message(stream: &mut quinn::SendStream, message: &proto::Message) -> anyhow::Result<()> {
+    let data = bincode::serialize(message)?;
+    stream.write_all(&(data.len() as u32).to_be_bytes()).await?;
+    stream.write_all(&data).await?;
+    Ok(())
+}
+
+async fn recv_message(stream: &mut quinn::RecvStream) -> anyhow::Result<proto::Message> {
+    let mut len = [0u8; 4];
+    tokio::time::timeout(TIMEOUT, stream.read_exact(&mut len)).await??;
+    let mut data = vec![0u8; u32::from_be_bytes(len) as usize];
+    stream.read_exact(&mut data).await?;
+    Ok(bincode::deserialize(&data)?)
+}
+
+fn presence(protocol_version: u32) -> proto::Presence {
+    proto::Presence {
+        node_id: EntityId::random(),
+        durable: false,
+        system_root: None,
+        wire: Default::default(),
+        features: vec![],
+        min_protocol_version: protocol_version,
+        protocol_version,
+    }
+}
+
+#[tokio::test]
+async fn requests_and_updates_use_their_own_streams() -> anyhow::Result<()> {
+    let (server_node, addr, server_task) = start_server().await?;
+    let ctx = server_node.context(DEFAULT_CONTEXT)?;
+    create_albums(&ctx, [1977]).await?;
+
+    let session = Session::open(addr).await?;
+    let (mut control, mut control_recv) = session.open_bi().await?;
+    let client_presence = presence(proto::PROTOCOL_VERSION);
+    let client_id = client_presence.node_id;
+    send_message(&mut control, &proto::Message::Presence(client_presence)).await?;
+    match recv_message(&mut control_recv).await? {
+        proto::Message::Presence(server_presence) => assert_eq!(server_presence.node_id, server_node.id),
+        other => panic!("expected the server's presence, got {:?}", other),
+    }
+
+    // The response comes back on the stream the request opened
+    let request = proto::NodeRequest {
+        id: proto::RequestId::new(),
+        to: server_node.id,
+        from: client_id,
+        body: proto::NodeRequestBody::SubscribeQuery {
+            query_id: proto::QueryId::new(),
+            collection: "album".into(),
+            selection: ankql::parser::parse_selection("year >= '1970'")?,
+            version: 1,
+            known_matches: vec![],
+        },
+    };
+    let request_id = request.id.clone();
+    let (mut send, mut recv) = session.open_bi().await?;
+    send_message(&mut send, &proto::Message::PeerMessage(proto::NodeMessage::Request { auth: vec![proto::AuthData(vec![])], request })).await?;
+    send.finish()?;
+    match recv_message(&mut recv).await? {
+        proto::Message::PeerMessage(proto::NodeMessage::Response(response)) => {
+            assert_eq!(response.request_id, request_id);
+            let proto::NodeResponseBody::QuerySubscribed { deltas, .. } = response.body else { panic!("expected QuerySubscribed, got {}", response.body) };
+            assert_eq!(deltas.len(), 1);
+        }
+        other => panic!("expected a response, got {:?}", other),
+    }
+    assert!(recv.read_to_end(64).await?.is_empty(), "the request stream is finished after its response");
+
+    // A change to the subscription arrives on the updates stream
+    create_albums(&ctx, [1979]).await?;
+    let mut updates = session.accept_uni().await?;
+    match recv_message(&mut updates).await? {
+        proto::Message::PeerMessage(proto::NodeMessage::Update(update)) => assert_eq!(update.to, client_id),
+        other => panic!("expected an update, got {:?}", other),
+    }
+
+    server_task.abort();
+    Ok(())
+}
+
+#[tokio::test]
+async fn refuses_incompatible_presence_on_the_control_stream() -> anyhow::Result<()> {
+    let (_server_node, addr, server_task) = start_server().await?;
+
+    let session = Session::open(addr).await?;
+    let (mut control, mut control_recv) = session.open_bi().await?;
+    send_message(&mut control, &proto::Message::Presence(presence(proto::PROTOCOL_VERSION + 1000))).await?;
+    assert!(matches!(recv_message(&mut control_recv).await?, proto::Message::Presence(_)));
+    assert!(matches!(recv_message(&mut control_recv).await?, proto::Message::PresenceRejected(_)));
+
+    server_task.abort();
+    Ok(())
+}
@@ -0,0 +1,72 @@
+mod common;
+use anyhow::Result;
+use common::*;
+use serde::{Deserialize, Serialize};
+
+/// A model with a collaborative list alongside an ordinary property
+#[derive(Model, Debug, Serialize, Deserialize)]
+pub struct Playlist {
+    pub name: String,
+    #[active_type(YrsArray)]
+    pub tracks: Vec<String>,
+}
+
+fn tracks(names: &[&str]) -> Vec<String> { names.iter().map(|name| name.to_string()).collect() }
+
+#[tokio::test]
+async fn concurrent_list_edits_in_one_node_merge() -> Result<()> {
+    let node = durable_sled_setup().await?;
+    let ctx = node.context(DEFAULT_CONTEXT)?;
+
+    let trx = ctx.begin();
+    let playlist = trx.create(&Playlist { name: "mix".into(), tracks: tracks(&["a", "b", "c"]) }).await?;
+    let id = playlist.id();
+    trx.commit().await?;
+
+    let playlist = ctx.get::<PlaylistView>(id).await?;
+    assert_eq!(playlist.tracks()?, tracks(&["a", "b", "c"]));
+
+    // One transaction appends while another moves the head down one place
+    let trx1 = ctx.begin();
+    let trx2 = ctx.begin();
+    playlist.edit(&trx1)?.tracks().push("d".into())?;
+    let reordered = playlist.edit(&trx2)?;
+    reordered.tracks().move_to(0, 1)?;
+    assert_eq!(reordered.tracks().value(), tracks(&["b", "a", "c"]));
+    trx1.commit().await?;
+    trx2.commit().await?;
+
+    // Neither edit clobbers the other
+    assert_eq!(ctx.get::<PlaylistView>(id).await?.tracks()?, tracks(&["b", "a", "c", "d"]));
+
+    let trx = ctx.begin();
+    let mutable = playlist.edit(&trx)?;
+    assert!(mutable.tracks().remove(2, 5).is_err(), "removing past the end is refused");
+    mutable.tracks().remove(0, 1)?;
+    mutable.tracks().insert(1, "e".into())?;
+    trx.commit().await?;
+    assert_eq!(ctx.get::<PlaylistView>(id).await?.tracks()?, tracks(&["a", "e", "c", "d"]));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn list_edits_from_two_nodes_converge() -> Result<()> {
+    let (server, client, _conn) = connected_pair().await?;
+    let trx = server.begin();
+    let id = trx.create(&Playlist { name: "road trip".into(), tracks: tracks(&["a", "b", "c"]) }).await?.id();
+    trx.commit().await?;
+
+    // The server moves the tail to the front while the client inserts after the head
+    converge(
+        &server,
+        &client,
+        id,
+        "name = 'road trip'",
+        |playlist: &PlaylistView, trx| Ok(playlist.edit(trx)?.tracks().move_to(2, 0)?),
+        |playlist: &PlaylistView, trx| Ok(playlist.edit(trx)?.tracks().insert(1, "x".into())?),
+        |playlist: &PlaylistView| playlist.tracks().unwrap(),
+        tracks(&["c", "a", "x", "b"]),
+    )
+    .await
+}
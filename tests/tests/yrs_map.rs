@@ -0,0 +1,95 @@
+mod common;
+use ankurah::{property::Json, value::Value};
+use anyhow::Result;
+use common::*;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// A model whose licensing object merges per key instead of last-writer-wins
+#[derive(Model, Debug, Serialize, Deserialize)]
+pub struct Release {
+    pub title: String,
+    #[active_type(YrsMap)]
+    pub licensing: Json,
+}
+
+#[tokio::test]
+async fn concurrent_edits_to_distinct_keys_merge() -> Result<()> {
+    let node = durable_sled_setup().await?;
+    let ctx = node.context(DEFAULT_CONTEXT)?;
+
+    let trx = ctx.begin();
+    let licensing = Json::new(json!({"territory": "US", "rights": {"holder": "Label", "type": "exclusive"}}));
+    let release = trx.create(&Release { title: "first".into(), licensing }).await?;
+    let id = release.id();
+    trx.commit().await?;
+
+    let release = ctx.get::<ReleaseView>(id).await?;
+    let trx1 = ctx.begin();
+    let trx2 = ctx.begin();
+    release.edit(&trx1)?.licensing().set("territory", Json::new(json!("CA")))?;
+    let edited = release.edit(&trx2)?;
+    edited.licensing().set("rights.holder", Json::new(json!("Artist")))?;
+    edited.licensing().remove("rights.type")?;
+    edited.licensing().set("expires.year", Json::new(json!(2030)))?;
+    assert_eq!(edited.licensing().get("expires"), Some(Json::new(json!({"year": 2030}))));
+    trx1.commit().await?;
+    trx2.commit().await?;
+
+    let expected = json!({"territory": "CA", "rights": {"holder": "Artist"}, "expires": {"year": 2030}});
+    assert_eq!(ctx.get::<ReleaseView>(id).await?.licensing()?, Json::new(expected));
+
+    // The merged object is still queryable by path
+    let found: Vec<ReleaseView> = ctx.fetch("licensing.territory = 'CA' AND licensing.rights.holder = 'Artist'").await?;
+    assert_eq!(found.iter().map(|r| r.id()).collect::<Vec<_>>(), vec![id]);
+    let found: Vec<ReleaseView> = ctx.fetch("licensing.territory = 'US'").await?;
+    assert!(found.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn untyped_create_without_the_object_accepts_map_writes() -> Result<()> {
+    let node = durable_sled_setup().await?;
+    let ctx = node.context(DEFAULT_CONTEXT)?;
+
+    // Registers the model for untyped creation
+    let trx = ctx.begin();
+    trx.create(&Release { title: "typed".into(), licensing: Json::new(json!({})) }).await?;
+    trx.commit().await?;
+
+    let trx = ctx.begin();
+    let id = trx.create_entity(&Release::collection(), [("title".to_owned(), Some(Value::String("untyped".into())))]).await?.id();
+    trx.commit().await?;
+
+    let trx = ctx.begin();
+    ctx.get::<ReleaseView>(id).await?.edit(&trx)?.licensing().set("territory", Json::new(json!("US")))?;
+    trx.commit().await?;
+
+    assert_eq!(ctx.get::<ReleaseView>(id).await?.licensing()?, Json::new(json!({"territory": "US"})));
+    let found: Vec<ReleaseView> = ctx.fetch("licensing.territory = 'US'").await?;
+    assert_eq!(found.iter().map(|r| r.id()).collect::<Vec<_>>(), vec![id]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn map_edits_from_two_nodes_converge_and_update_path_queries() -> Result<()> {
+    let (server, client, _conn) = connected_pair().await?;
+    let trx = server.begin();
+    let licensing = Json::new(json!({"territory": "US", "rights": "exclusive"}));
+    let id = trx.create(&Release { title: "second".into(), licensing }).await?.id();
+    trx.commit().await?;
+
+    // The sides set different keys of the same object; the client's path query picks up the server's
+    converge(
+        &server,
+        &client,
+        id,
+        "licensing.territory = 'DE'",
+        |release: &ReleaseView, trx| Ok(release.edit(trx)?.licensing().set("territory", Json::new(json!("DE")))?),
+        |release: &ReleaseView, trx| Ok(release.edit(trx)?.licensing().set("rights", Json::new(json!("shared")))?),
+        |release: &ReleaseView| release.licensing().unwrap(),
+        Json::new(json!({"territory": "DE", "rights": "shared"})),
+    )
+    .await
+}
@@ -0,0 +1,88 @@
+mod common;
+use ankurah::property::{Json, RichText};
+use anyhow::Result;
+use common::*;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// A model whose body keeps formatting; a RichText field needs no #[active_type]
+#[derive(Model, Debug, Serialize, Deserialize)]
+pub struct Note {
+    pub title: String,
+    pub body: RichText,
+}
+
+#[tokio::test]
+async fn formatting_merges_with_concurrent_text_edits() -> Result<()> {
+    let node = durable_sled_setup().await?;
+    let ctx = node.context(DEFAULT_CONTEXT)?;
+
+    let trx = ctx.begin();
+    let body = RichText::plain("Hello ").with("world", [("bold", json!(true))]);
+    let note = trx.create(&Note { title: "greeting".into(), body: body.clone() }).await?;
+    let id = note.id();
+    trx.commit().await?;
+
+    let note = ctx.get::<NoteView>(id).await?;
+    assert_eq!(note.body()?, body);
+
+    // One transaction links "world" while another prepends a heading line
+    let trx1 = ctx.begin();
+    let trx2 = ctx.begin();
+    note.edit(&trx1)?.body().format(6, 5, Json::new(json!({"link": "https://example.com"})))?;
+    note.edit(&trx2)?.body().insert_formatted(0, "Title\n", Json::new(json!({"heading": 1})))?;
+    trx1.commit().await?;
+    trx2.commit().await?;
+
+    let expected: RichText = serde_json::from_value(json!([
+        {"insert": "Title\n", "attributes": {"heading": 1}},
+        {"insert": "Hello "},
+        {"insert": "world", "attributes": {"bold": true, "link": "https://example.com"}}
+    ]))?;
+    let note = ctx.get::<NoteView>(id).await?;
+    assert_eq!(note.body()?, expected);
+
+    // Queries see the plain text
+    let found: Vec<NoteView> = ctx.fetch("body = 'Title\nHello world'").await?;
+    assert_eq!(found.len(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn editor_deltas_apply_and_reach_other_nodes() -> Result<()> {
+    let (server, client, _conn) = connected_pair().await?;
+
+    let trx = server.begin();
+    let note = trx.create(&Note { title: "draft".into(), body: RichText::plain("Hello world") }).await?;
+    let id = note.id();
+    trx.commit().await?;
+
+    let query = client.query_wait::<NoteView>(nocache("title = 'draft'")?).await?;
+    let watcher = TestWatcher::changeset();
+    let _guard = query.subscribe(&watcher);
+
+    // Replace "world" with italic "there" and bold the greeting, as an editor binding would
+    let trx = server.begin();
+    let mutable = server.get::<NoteView>(id).await?.edit(&trx)?;
+    let change = json!([
+        {"retain": 5, "attributes": {"bold": true}},
+        {"retain": 1},
+        {"delete": 5},
+        {"insert": "there", "attributes": {"italic": true}}
+    ]);
+    mutable.body().apply_delta(Json::new(change))?;
+    assert!(mutable.body().apply_delta(Json::new(json!([{"retain": 50}]))).is_err(), "retaining past the end is refused");
+    trx.commit().await?;
+    assert!(watcher.wait().await, "the edit reaches the client");
+
+    let expected: RichText = serde_json::from_value(json!([
+        {"insert": "Hello", "attributes": {"bold": true}},
+        {"insert": " "},
+        {"insert": "there", "attributes": {"italic": true}}
+    ]))?;
+    assert_eq!(client.get::<NoteView>(id).await?.body()?, expected);
+    assert_eq!(query.peek().first().map(|n| n.body().unwrap().to_plain_text()), Some("Hello there".to_string()));
+
+    Ok(())
+}